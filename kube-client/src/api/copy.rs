@@ -0,0 +1,201 @@
+//! `kubectl cp` for pods, streaming tar archives through exec
+use std::{
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use k8s_openapi::api::core::v1::Pod;
+use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio_util::io::SyncIoBridge;
+
+use super::{remote_command, Api, AttachParams, AttachedProcess};
+use crate::{Error, Result};
+
+/// Size of the exec buffers, large enough to not stall the archive on every tar block
+const BUF_SIZE: usize = 64 * 1024;
+
+/// Errors from copying files in and out of pods
+#[cfg_attr(docsrs, doc(cfg(feature = "cp")))]
+#[derive(Debug, Error)]
+pub enum CopyError {
+    /// A path has no file name to put in the archive, like `/` or `..`
+    #[error("path {0:?} has no file name to copy")]
+    InvalidPath(String),
+
+    /// Reading or writing the local files failed
+    #[error("failed to copy local files: {0}")]
+    Io(#[source] io::Error),
+
+    /// The exec connection failed
+    #[error("exec connection failed: {0}")]
+    Exec(#[source] remote_command::Error),
+
+    /// `tar` failed in the container, e.g. because it is not installed or the path does not exist
+    #[error("tar failed in the container: {0}")]
+    Tar(String),
+}
+
+impl Api<Pod> {
+    /// Copy a local file or directory into a directory of a container, like `kubectl cp`
+    ///
+    /// `src` keeps its file name, so copying `./config` into `/etc` creates `/etc/config`.
+    /// The files are streamed as a tar archive to `tar` in the container, which has to be installed
+    /// there. Uses the default container of the pod if `container` is `None`.
+    ///
+    /// ```no_run
+    /// # use k8s_openapi::api::core::v1::Pod;
+    /// # use kube::Api;
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let pods: Api<Pod> = todo!();
+    /// pods.copy_to_pod("web", None, "./fixtures", "/tmp").await?;
+    /// pods.copy_from_pod("web", None, "/var/log/nginx", "./logs").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn copy_to_pod(
+        &self,
+        name: &str,
+        container: Option<&str>,
+        src: impl AsRef<Path>,
+        dest: &str,
+    ) -> Result<()> {
+        let src = src.as_ref().to_path_buf();
+        let entry = match src.file_name() {
+            Some(entry) => PathBuf::from(entry),
+            None => return Err(Error::Copy(CopyError::InvalidPath(src.display().to_string()))),
+        };
+        let ap = attach_params(container).stdin(true).stdout(false);
+        let mut process = self.exec(name, ["tar", "xmf", "-", "-C", dest], &ap).await?;
+        let stdin = SyncIoBridge::new(process.stdin().expect("stdin was requested"));
+        let stderr = process.stderr().expect("stderr was requested");
+
+        let archive = tokio::task::spawn_blocking(move || {
+            let mut archive = tar::Builder::new(stdin);
+            archive.follow_symlinks(false);
+            if src.is_dir() {
+                archive.append_dir_all(&entry, &src)?;
+            } else {
+                archive.append_path_with_name(&src, &entry)?;
+            }
+            // dropping stdin tells tar in the container that the archive is complete
+            archive.into_inner()?.flush()
+        });
+        let (archived, stderr) = tokio::join!(archive, read_to_string(stderr));
+        let archived = archived.unwrap_or_else(|err| Err(io::Error::other(err)));
+        finish(process, archived, stderr).await
+    }
+
+    /// Copy a file or directory of a container into a local directory, like `kubectl cp`
+    ///
+    /// `src` keeps its file name, so copying `/var/log/nginx` into `./logs` creates `./logs/nginx`.
+    /// `tar` in the container streams the files as an archive, which is unpacked without
+    /// following absolute paths or `..` out of `dest`. Uses the default container of the pod
+    /// if `container` is `None`.
+    pub async fn copy_from_pod(
+        &self,
+        name: &str,
+        container: Option<&str>,
+        src: &str,
+        dest: impl AsRef<Path>,
+    ) -> Result<()> {
+        let (parent, entry) = split_remote_path(src).map_err(Error::Copy)?;
+        let dest = dest.as_ref().to_path_buf();
+        let ap = attach_params(container).stdin(false).stdout(true);
+        let mut process = self
+            .exec(name, ["tar", "cf", "-", "-C", parent, entry], &ap)
+            .await?;
+        let stdout = SyncIoBridge::new(process.stdout().expect("stdout was requested"));
+        let stderr = process.stderr().expect("stderr was requested");
+
+        let unpack = tokio::task::spawn_blocking(move || {
+            let mut archive = tar::Archive::new(stdout);
+            archive.unpack(&dest)?;
+            // drain the padding after the archive, so the exec connection can complete
+            io::copy(&mut archive.into_inner(), &mut io::sink()).map(|_| ())
+        });
+        let (unpacked, stderr) = tokio::join!(unpack, read_to_string(stderr));
+        let unpacked = unpacked.unwrap_or_else(|err| Err(io::Error::other(err)));
+        finish(process, unpacked, stderr).await
+    }
+}
+
+fn attach_params(container: Option<&str>) -> AttachParams {
+    let ap = AttachParams::default()
+        .stderr(true)
+        .max_stdin_buf_size(BUF_SIZE)
+        .max_stdout_buf_size(BUF_SIZE);
+    match container {
+        Some(container) => ap.container(container),
+        None => ap,
+    }
+}
+
+/// Split a path in the container into the directory to run `tar` in, and the entry to archive
+fn split_remote_path(path: &str) -> Result<(&str, &str), CopyError> {
+    let trimmed = path.trim_end_matches('/');
+    let (parent, entry) = match trimmed.rsplit_once('/') {
+        Some(("", entry)) => ("/", entry),
+        Some((parent, entry)) => (parent, entry),
+        None => (".", trimmed),
+    };
+    if entry.is_empty() || entry == "." || entry == ".." {
+        return Err(CopyError::InvalidPath(path.to_string()));
+    }
+    Ok((parent, entry))
+}
+
+/// Read stderr concurrently with the archive, so a chatty `tar` cannot stall the connection
+async fn read_to_string(mut stderr: impl AsyncRead + Unpin) -> String {
+    let mut buf = Vec::new();
+    // stderr only explains failures, a broken connection surfaces through the process
+    let _ = stderr.read_to_end(&mut buf).await;
+    String::from_utf8_lossy(&buf).into_owned()
+}
+
+/// Wait for `tar` in the container to exit, and report the failure that caused the others
+async fn finish(mut process: AttachedProcess, local: io::Result<()>, stderr: String) -> Result<()> {
+    let status = process.take_status().expect("status is only taken here").await;
+    process
+        .join()
+        .await
+        .map_err(|err| Error::Copy(CopyError::Exec(err)))?;
+    let local = match local {
+        // a broken pipe only means that tar in the container exited early
+        Err(err) if err.kind() != io::ErrorKind::BrokenPipe => return Err(Error::Copy(CopyError::Io(err))),
+        local => local,
+    };
+    if let Some(status) = status.filter(|s| s.status.as_deref() == Some("Failure")) {
+        let message = match stderr.trim() {
+            "" => status.message.unwrap_or_default(),
+            stderr => stderr.to_string(),
+        };
+        return Err(Error::Copy(CopyError::Tar(message)));
+    }
+    local.map_err(|err| Error::Copy(CopyError::Io(err)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{split_remote_path, CopyError};
+
+    #[test]
+    fn remote_paths_are_split_into_directory_and_entry() {
+        assert_eq!(
+            split_remote_path("/var/log/nginx").unwrap(),
+            ("/var/log", "nginx")
+        );
+        assert_eq!(
+            split_remote_path("/var/log/nginx/").unwrap(),
+            ("/var/log", "nginx")
+        );
+        assert_eq!(split_remote_path("/etc").unwrap(), ("/", "etc"));
+        assert_eq!(split_remote_path("data.txt").unwrap(), (".", "data.txt"));
+        for path in ["/", "", "/tmp/..", "."] {
+            assert!(
+                matches!(split_remote_path(path), Err(CopyError::InvalidPath(_))),
+                "{path}"
+            );
+        }
+    }
+}
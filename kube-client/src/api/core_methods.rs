@@ -1,11 +1,12 @@
 use either::Either;
-use futures::Stream;
+use futures::{Stream, StreamExt, TryStreamExt};
 use serde::{de::DeserializeOwned, Serialize};
 use std::fmt::Debug;
 
 use crate::{api::Api, Error, Result};
 use kube_core::{
-    metadata::PartialObjectMeta, object::ObjectList, params::*, response::Status, ErrorResponse, WatchEvent,
+    metadata::PartialObjectMeta, object::ObjectList, params::*, response::Status, table::Table,
+    LossyWatchEvent, RelistingEvent, Resource, StatusReason, WatchEvent,
 };
 
 /// PUSH/PUT/POST/GET abstractions
@@ -83,7 +84,7 @@ where
     /// Consider using [`Api::get_opt`] if you need to handle missing objects.
     pub async fn get_with(&self, name: &str, gp: &GetParams) -> Result<K> {
         let mut req = self.request.get(name, gp).map_err(Error::BuildRequest)?;
-        req.extensions_mut().insert("get");
+        self.tag(&mut req, "get");
         self.client.request::<K>(req).await
     }
 
@@ -114,7 +115,7 @@ where
     /// Consider using [`Api::get_metadata_opt`] if you need to handle missing objects.
     pub async fn get_metadata_with(&self, name: &str, gp: &GetParams) -> Result<PartialObjectMeta<K>> {
         let mut req = self.request.get_metadata(name, gp).map_err(Error::BuildRequest)?;
-        req.extensions_mut().insert("get_metadata");
+        self.tag(&mut req, "get_metadata");
         self.client.request::<PartialObjectMeta<K>>(req).await
     }
 
@@ -138,7 +139,10 @@ where
     pub async fn get_opt(&self, name: &str) -> Result<Option<K>> {
         match self.get(name).await {
             Ok(obj) => Ok(Some(obj)),
-            Err(Error::Api(ErrorResponse { reason, .. })) if &reason == "NotFound" => Ok(None),
+            // only the reason of a `Status`, 404s without one may come from a proxy or an unserved path
+            Err(Error::Api(err)) if StatusReason::from(err.reason.as_str()) == StatusReason::NotFound => {
+                Ok(None)
+            }
             Err(err) => Err(err),
         }
     }
@@ -193,7 +197,9 @@ where
     ) -> Result<Option<PartialObjectMeta<K>>> {
         match self.get_metadata_with(name, gp).await {
             Ok(meta) => Ok(Some(meta)),
-            Err(Error::Api(ErrorResponse { reason, .. })) if &reason == "NotFound" => Ok(None),
+            Err(Error::Api(err)) if StatusReason::from(err.reason.as_str()) == StatusReason::NotFound => {
+                Ok(None)
+            }
             Err(err) => Err(err),
         }
     }
@@ -218,8 +224,110 @@ where
     /// ```
     pub async fn list(&self, lp: &ListParams) -> Result<ObjectList<K>> {
         let mut req = self.request.list(lp).map_err(Error::BuildRequest)?;
-        req.extensions_mut().insert("list");
-        self.client.request::<ObjectList<K>>(req).await
+        self.tag(&mut req, "list");
+        let Some(projection) = &self.projection else {
+            return self.client.request::<ObjectList<K>>(req).await;
+        };
+        let list = self.client.request::<ObjectList<serde_json::Value>>(req).await?;
+        let items = list.items.into_iter().map(|obj| projection.project(obj));
+        Ok(ObjectList {
+            types: list.types,
+            metadata: list.metadata,
+            items: items.collect::<Result<_, _>>().map_err(Error::SerdeError)?,
+        })
+    }
+
+    /// Get a single page of resources, continuing from a [`Cursor`] of a previous page
+    ///
+    /// The page holds at most [`ListParams::limit`] items, and [`ObjectList::cursor`] is set when
+    /// there are more to fetch.
+    ///
+    /// ```no_run
+    /// use kube::api::{Api, Cursor, ListParams};
+    /// use kube::Error;
+    /// use k8s_openapi::api::core::v1::Pod;
+    ///
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// let pods: Api<Pod> = Api::all(client);
+    /// let lp = ListParams::default().limit(50);
+    /// let mut cursor: Option<Cursor> = None;
+    /// loop {
+    ///     let page = match pods.list_page(&lp, cursor.as_ref()).await {
+    ///         Ok(page) => page,
+    ///         // start over on the first page
+    ///         Err(Error::CursorExpired(_)) => {
+    ///             cursor = None;
+    ///             continue;
+    ///         }
+    ///         Err(err) => return Err(err.into()),
+    ///     };
+    ///     println!("got {} pods", page.items.len());
+    ///     cursor = page.cursor();
+    ///     if cursor.is_none() {
+    ///         break;
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// The `resource_version` and `version_match` of `lp` only apply to the first page, since the
+    /// later pages come from the snapshot of the first one.
+    ///
+    /// A cursor that has expired results in an [`Error::CursorExpired`].
+    pub async fn list_page(&self, lp: &ListParams, cursor: Option<&Cursor>) -> Result<ObjectList<K>> {
+        let Some(cursor) = cursor else {
+            return self.list(lp).await;
+        };
+        let mut lp = lp.clone().cursor(cursor);
+        lp.resource_version = None;
+        lp.version_match = None;
+        match self.list(&lp).await {
+            Err(Error::Api(err)) if err.code == 410 => Err(Error::CursorExpired(err)),
+            res => res,
+        }
+    }
+
+    /// Stream all resources matching the [`ListParams`], fetching them page by page
+    ///
+    /// This drives the `limit` and `continue` parameters internally so that only one page
+    /// of objects (500 unless [`ListParams::limit`] is set) is held in memory at any time.
+    /// All pages come from the same consistent snapshot of the collection.
+    ///
+    /// ```no_run
+    /// use kube::api::{Api, ListParams, ResourceExt};
+    /// use k8s_openapi::api::core::v1::Pod;
+    /// use futures::TryStreamExt;
+    ///
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// let pods: Api<Pod> = Api::all(client);
+    /// let mut stream = std::pin::pin!(pods.list_stream(&ListParams::default()));
+    /// while let Some(p) = stream.try_next().await? {
+    ///     println!("Found Pod: {}", p.name_any());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// If the snapshot expires before the stream has been consumed, it ends with an [`Error::CursorExpired`].
+    pub fn list_stream(&self, lp: &ListParams) -> impl Stream<Item = Result<K>> + '_ {
+        let mut lp = lp.clone();
+        lp.limit = lp.limit.or(Some(500));
+        futures::stream::try_unfold(Some(None), move |cursor: Option<Option<Cursor>>| {
+            let lp = lp.clone();
+            async move {
+                let Some(cursor) = cursor else { return Ok(None) };
+                let list = self.list_page(&lp, cursor.as_ref()).await?;
+                let next = list.cursor().map(Some);
+                Ok(Some((
+                    futures::stream::iter(list.items.into_iter().map(Ok::<K, Error>)),
+                    next,
+                )))
+            }
+        })
+        .try_flatten()
     }
 
     /// Get a list of resources that contains only their metadata as
@@ -245,10 +353,63 @@ where
     /// ```
     pub async fn list_metadata(&self, lp: &ListParams) -> Result<ObjectList<PartialObjectMeta<K>>> {
         let mut req = self.request.list_metadata(lp).map_err(Error::BuildRequest)?;
-        req.extensions_mut().insert("list_metadata");
+        self.tag(&mut req, "list_metadata");
         self.client.request::<ObjectList<PartialObjectMeta<K>>>(req).await
     }
 
+    /// Get a named resource rendered as a server-side [`Table`]
+    ///
+    /// The table contains the same columns as `kubectl get`, including
+    /// the `additionalPrinterColumns` of custom resources.
+    ///
+    /// ```no_run
+    /// # use kube::Api;
+    /// use k8s_openapi::api::core::v1::Pod;
+    ///
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// let pods: Api<Pod> = Api::namespaced(client, "apps");
+    /// let table = pods.get_table("blog").await?;
+    /// for col in &table.column_definitions {
+    ///     println!("{}", col.name);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_table(&self, name: &str) -> Result<Table> {
+        let mut req = self
+            .request
+            .get_table(name, &GetParams::default())
+            .map_err(Error::BuildRequest)?;
+        self.tag(&mut req, "get_table");
+        self.client.request::<Table>(req).await
+    }
+
+    /// List resources rendered as a server-side [`Table`]
+    ///
+    /// Similar to [list](`Api::list`), but returns one row per object with the
+    /// same columns as `kubectl get`.
+    ///
+    /// ```no_run
+    /// use kube::api::{Api, ListParams};
+    /// use k8s_openapi::api::core::v1::Pod;
+    ///
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// let pods: Api<Pod> = Api::namespaced(client, "apps");
+    /// let table = pods.list_table(&ListParams::default()).await?;
+    /// for row in &table.rows {
+    ///     println!("{:?}", row.cells);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn list_table(&self, lp: &ListParams) -> Result<Table> {
+        let mut req = self.request.list_table(lp).map_err(Error::BuildRequest)?;
+        self.tag(&mut req, "list_table");
+        self.client.request::<Table>(req).await
+    }
+
     /// Create a resource
     ///
     /// This function requires a type that Serializes to `K`, which can be:
@@ -274,7 +435,7 @@ where
     {
         let bytes = serde_json::to_vec(&data).map_err(Error::SerdeError)?;
         let mut req = self.request.create(pp, bytes).map_err(Error::BuildRequest)?;
-        req.extensions_mut().insert("create");
+        self.tag(&mut req, "create");
         self.client.request::<K>(req).await
     }
 
@@ -301,7 +462,7 @@ where
     /// ```
     pub async fn delete(&self, name: &str, dp: &DeleteParams) -> Result<Either<K, Status>> {
         let mut req = self.request.delete(name, dp).map_err(Error::BuildRequest)?;
-        req.extensions_mut().insert("delete");
+        self.tag(&mut req, "delete");
         self.client.request_status::<K>(req).await
     }
 
@@ -341,7 +502,7 @@ where
             .request
             .delete_collection(dp, lp)
             .map_err(Error::BuildRequest)?;
-        req.extensions_mut().insert("delete_collection");
+        self.tag(&mut req, "delete_collection");
         self.client.request_status::<ObjectList<K>>(req).await
     }
 
@@ -384,7 +545,7 @@ where
         patch: &Patch<P>,
     ) -> Result<K> {
         let mut req = self.request.patch(name, pp, patch).map_err(Error::BuildRequest)?;
-        req.extensions_mut().insert("patch");
+        self.tag(&mut req, "patch");
         self.client.request::<K>(req).await
     }
 
@@ -434,7 +595,7 @@ where
             .request
             .patch_metadata(name, pp, patch)
             .map_err(Error::BuildRequest)?;
-        req.extensions_mut().insert("patch_metadata");
+        self.tag(&mut req, "patch_metadata");
         self.client.request::<PartialObjectMeta<K>>(req).await
     }
 
@@ -494,7 +655,7 @@ where
             .request
             .replace(name, pp, bytes)
             .map_err(Error::BuildRequest)?;
-        req.extensions_mut().insert("replace");
+        self.tag(&mut req, "replace");
         self.client.request::<K>(req).await
     }
 
@@ -541,8 +702,267 @@ where
         version: &str,
     ) -> Result<impl Stream<Item = Result<WatchEvent<K>>>> {
         let mut req = self.request.watch(wp, version).map_err(Error::BuildRequest)?;
-        req.extensions_mut().insert("watch");
-        self.client.request_events::<K>(req).await
+        self.tag(&mut req, "watch");
+        let Some(projection) = self.projection.clone() else {
+            return Ok(self.client.request_events::<K>(req).await?.left_stream());
+        };
+        let events = self.client.request_events::<serde_json::Value>(req).await?;
+        Ok(events
+            .map(move |event| projection.project_event(event?).map_err(Error::SerdeError))
+            .right_stream())
+    }
+
+    /// Watch a list of resources, keeping events whose object does not deserialize into `K`
+    ///
+    /// This works like [`watch`](Api::watch), but objects that do not match `K`, e.g. because
+    /// the schema of a CRD changed, are yielded as [`LossyWatchEvent::Undecodable`] with
+    /// their raw JSON, instead of as an [`Error::SerdeError`] without it.
+    ///
+    /// ```no_run
+    /// use kube::api::{Api, LossyWatchEvent, WatchParams};
+    /// use k8s_openapi::api::core::v1::Pod;
+    /// use futures::TryStreamExt;
+    ///
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// let pods: Api<Pod> = Api::default_namespaced(client);
+    /// let mut stream = std::pin::pin!(pods.watch_lossy(&WatchParams::default(), "0").await?);
+    /// while let Some(event) = stream.try_next().await? {
+    ///     match event {
+    ///         LossyWatchEvent::Event(event) => println!("{event:?}"),
+    ///         LossyWatchEvent::Undecodable(e) => println!("skipping {:?}: {}", e.raw, e.error),
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn watch_lossy(
+        &self,
+        wp: &WatchParams,
+        version: &str,
+    ) -> Result<impl Stream<Item = Result<LossyWatchEvent<K>>>> {
+        let mut req = self.request.watch(wp, version).map_err(Error::BuildRequest)?;
+        self.tag(&mut req, "watch");
+        let projection = self.projection.clone();
+        let events = self.client.request_events::<serde_json::Value>(req).await?;
+        Ok(events.map_ok(move |mut event| {
+            if let (
+                Some(projection),
+                WatchEvent::Added(obj) | WatchEvent::Modified(obj) | WatchEvent::Deleted(obj),
+            ) = (&projection, &mut event)
+            {
+                projection.apply(obj);
+            }
+            LossyWatchEvent::decode(event)
+        }))
+    }
+
+    /// Watch a list of resources, resuming from the last seen `resourceVersion` after disconnects
+    ///
+    /// Unlike [`watch`](Api::watch), this does not end when the server closes the connection.
+    /// The latest `resourceVersion` seen on objects and [`WatchEvent::Bookmark`] events is tracked
+    /// internally and used to re-issue the watch, so no events are missed and no relist is needed.
+    /// Bookmarks are requested unless disabled on the [`WatchParams`], and are passed through to the caller.
+    ///
+    /// The stream ends after yielding an error from (re)establishing the watch, or after a
+    /// `410 Gone` [`WatchEvent::Error`], which means the tracked version is too old and the caller has to relist.
+    /// The version to relist from can be taken from the last event seen before that.
+    ///
+    /// Watches are re-issued with an exponential backoff from 800ms to 30s, like the one of the
+    /// [`watcher`], so a server that keeps closing watches right away is not hammered. The backoff
+    /// is reset by every event that is not an error.
+    ///
+    /// ```no_run
+    /// use kube::api::{Api, WatchParams, ResourceExt, WatchEvent};
+    /// use k8s_openapi::api::core::v1::Pod;
+    /// use futures::TryStreamExt;
+    ///
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// let pods: Api<Pod> = Api::default_namespaced(client);
+    /// let list = pods.list(&Default::default()).await?;
+    /// let version = list.metadata.resource_version.unwrap_or_default();
+    /// let mut stream = std::pin::pin!(pods.watch_resumable(&WatchParams::default(), &version));
+    /// while let Some(event) = stream.try_next().await? {
+    ///     match event {
+    ///         WatchEvent::Bookmark(b) => println!("now at {}", b.metadata.resource_version),
+    ///         other => println!("{other:?}"),
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`watcher`]: https://docs.rs/kube_runtime/*/kube_runtime/watcher/fn.watcher.html
+    pub fn watch_resumable<'a>(
+        &'a self,
+        wp: &WatchParams,
+        version: &str,
+    ) -> impl Stream<Item = Result<WatchEvent<K>>> + Send + 'a
+    where
+        K: Resource + Send + 'static,
+    {
+        struct State<'a, K> {
+            version: String,
+            stream: Option<futures::stream::BoxStream<'a, Result<WatchEvent<K>>>>,
+            backoff: backon::ExponentialBackoff,
+            done: bool,
+        }
+        fn backoff() -> backon::ExponentialBackoff {
+            use backon::BackoffBuilder;
+            backon::ExponentialBuilder::default()
+                .with_min_delay(std::time::Duration::from_millis(800))
+                .with_max_delay(std::time::Duration::from_secs(30))
+                .with_factor(2.0)
+                .with_jitter()
+                .without_max_times()
+                .build()
+        }
+        let wp = wp.clone();
+        let state = State {
+            version: version.to_string(),
+            stream: None,
+            backoff: backoff(),
+            done: false,
+        };
+        futures::stream::unfold(state, move |mut state| {
+            let wp = wp.clone();
+            async move {
+                loop {
+                    if state.done {
+                        return None;
+                    }
+                    let stream = match &mut state.stream {
+                        Some(stream) => stream,
+                        None => match self.watch(&wp, &state.version).await {
+                            Ok(stream) => state.stream.insert(stream.boxed()),
+                            Err(err) => {
+                                state.done = true;
+                                return Some((Err(err), state));
+                            }
+                        },
+                    };
+                    match stream.next().await {
+                        Some(Ok(event)) => {
+                            match &event {
+                                WatchEvent::Added(obj)
+                                | WatchEvent::Modified(obj)
+                                | WatchEvent::Deleted(obj) => {
+                                    if let Some(rv) = obj.meta().resource_version.clone() {
+                                        state.version = rv;
+                                    }
+                                    state.backoff = backoff();
+                                }
+                                WatchEvent::Bookmark(bm) => {
+                                    state.version = bm.metadata.resource_version.clone();
+                                    state.backoff = backoff();
+                                }
+                                WatchEvent::Error(err) => {
+                                    state.done = err.code == 410;
+                                }
+                            }
+                            return Some((Ok(event), state));
+                        }
+                        Some(Err(err)) => return Some((Err(err), state)),
+                        // the server closed the watch, resume from the tracked version
+                        None => {
+                            state.stream = None;
+                            if let Some(delay) = state.backoff.next() {
+                                tokio::time::sleep(delay).await;
+                            }
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    /// Watch a list of resources, relisting them whenever the watched version expires
+    ///
+    /// This lists the matching objects and yields them as a [`RelistingEvent::Restarted`], then
+    /// follows them with [`watch_resumable`](Api::watch_resumable). When the apiserver answers
+    /// with `410 Gone`, because the tracked version was compacted away, the objects are listed
+    /// again and yielded as another `Restarted`, so callers never have to restart the stream.
+    ///
+    /// The stream ends after yielding an error from listing or (re)establishing the watch.
+    /// Consider using a managed [`watcher`] for retries with backoff and a local cache.
+    ///
+    /// ```no_run
+    /// use kube::api::{Api, RelistingEvent, WatchEvent, WatchParams, ResourceExt};
+    /// use k8s_openapi::api::core::v1::Pod;
+    /// use futures::TryStreamExt;
+    ///
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// let pods: Api<Pod> = Api::default_namespaced(client);
+    /// let mut stream = std::pin::pin!(pods.watch_relisting(&WatchParams::default()));
+    /// while let Some(event) = stream.try_next().await? {
+    ///     match event {
+    ///         RelistingEvent::Restarted(pods) => println!("{} pods", pods.len()),
+    ///         RelistingEvent::Event(WatchEvent::Added(pod)) => println!("Added {}", pod.name_any()),
+    ///         RelistingEvent::Event(other) => println!("{other:?}"),
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    /// [`watcher`]: https://docs.rs/kube_runtime/*/kube_runtime/watcher/fn.watcher.html
+    pub fn watch_relisting<'a>(
+        &'a self,
+        wp: &WatchParams,
+    ) -> impl Stream<Item = Result<RelistingEvent<K>>> + Send + 'a
+    where
+        K: Resource + Send + 'static,
+    {
+        struct State<'a, K> {
+            stream: Option<futures::stream::BoxStream<'a, Result<WatchEvent<K>>>>,
+            done: bool,
+        }
+        let wp = wp.clone();
+        let lp = ListParams {
+            label_selector: wp.label_selector.clone(),
+            field_selector: wp.field_selector.clone(),
+            ..ListParams::default()
+        };
+        let state = State {
+            stream: None,
+            done: false,
+        };
+        futures::stream::unfold(state, move |mut state| {
+            let (wp, lp) = (wp.clone(), lp.clone());
+            async move {
+                loop {
+                    if state.done {
+                        return None;
+                    }
+                    let Some(stream) = &mut state.stream else {
+                        match self.list(&lp).await {
+                            Ok(list) => {
+                                let version = list.metadata.resource_version.unwrap_or_default();
+                                state.stream = Some(self.watch_resumable(&wp, &version).boxed());
+                                return Some((Ok(RelistingEvent::Restarted(list.items)), state));
+                            }
+                            Err(err) => {
+                                state.done = true;
+                                return Some((Err(err), state));
+                            }
+                        }
+                    };
+                    match stream.next().await {
+                        Some(Ok(WatchEvent::Error(err))) if err.code == 410 => {
+                            tracing::debug!("watch version expired, relisting: {err}");
+                            state.stream = None;
+                        }
+                        Some(Err(Error::Api(err))) if err.code == 410 => {
+                            tracing::debug!("watch version expired, relisting: {err}");
+                            state.stream = None;
+                        }
+                        Some(item) => return Some((item.map(RelistingEvent::Event), state)),
+                        None => state.done = true,
+                    }
+                }
+            }
+        })
     }
 
     /// Watch a list of metadata for a given resources
@@ -593,7 +1013,318 @@ where
             .request
             .watch_metadata(wp, version)
             .map_err(Error::BuildRequest)?;
-        req.extensions_mut().insert("watch_metadata");
+        self.tag(&mut req, "watch_metadata");
         self.client.request_events::<PartialObjectMeta<K>>(req).await
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{client::Body, Api, Client, Error};
+    use futures::{StreamExt, TryStreamExt};
+    use http::{Method, Request, Response, StatusCode};
+    use k8s_openapi::api::core::v1::Pod;
+    use kube_core::{
+        params::{Cursor, ListParams, VersionMatch, WatchParams},
+        ApiResource, DynamicObject, ErrorResponse, LossyWatchEvent, RelistingEvent, WatchEvent,
+    };
+    use std::pin::pin;
+
+    #[tokio::test]
+    async fn list_stream_follows_continue_tokens() {
+        let (mock_service, handle) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            let mut handle = pin!(handle);
+            let pages = [
+                ("/api/v1/namespaces/default/pods?&limit=1", "a", "tok"),
+                ("/api/v1/namespaces/default/pods?&limit=1&continue=tok", "b", ""),
+            ];
+            for (uri, name, token) in pages {
+                let (request, send) = handle.next_request().await.expect("service not called");
+                assert_eq!(request.uri().to_string(), uri);
+                let list = serde_json::json!({
+                    "apiVersion": "v1",
+                    "kind": "PodList",
+                    "metadata": { "resourceVersion": "1", "continue": token },
+                    "items": [{ "apiVersion": "v1", "kind": "Pod", "metadata": { "name": name } }]
+                });
+                send.send_response(
+                    Response::builder()
+                        .body(Body::from(serde_json::to_vec(&list).unwrap()))
+                        .unwrap(),
+                );
+            }
+        });
+
+        let api: Api<Pod> = Api::default_namespaced(Client::new(mock_service, "default"));
+        let lp = ListParams::default().limit(1);
+        let pods: Vec<Pod> = api.list_stream(&lp).try_collect().await.unwrap();
+        let names: Vec<_> = pods.iter().map(|p| p.metadata.name.as_deref().unwrap()).collect();
+        assert_eq!(names, ["a", "b"]);
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn list_stream_only_sends_the_resource_version_for_the_first_page() {
+        let (mock_service, handle) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            let mut handle = pin!(handle);
+            let pages = [
+                (
+                    "resourceVersion=10&resourceVersionMatch=NotOlderThan",
+                    "a",
+                    "tok1",
+                ),
+                ("continue=tok1", "b", "tok2"),
+                ("continue=tok2", "c", ""),
+            ];
+            for (query, name, token) in pages {
+                let (request, send) = handle.next_request().await.expect("service not called");
+                let uri = format!("/api/v1/namespaces/default/pods?&limit=1&{query}");
+                assert_eq!(request.uri().to_string(), uri);
+                let list = serde_json::json!({
+                    "apiVersion": "v1",
+                    "kind": "PodList",
+                    "metadata": { "resourceVersion": "12", "continue": token },
+                    "items": [{ "apiVersion": "v1", "kind": "Pod", "metadata": { "name": name } }]
+                });
+                send.send_response(
+                    Response::builder()
+                        .body(Body::from(serde_json::to_vec(&list).unwrap()))
+                        .unwrap(),
+                );
+            }
+        });
+
+        let api: Api<Pod> = Api::default_namespaced(Client::new(mock_service, "default"));
+        let lp = ListParams::default()
+            .limit(1)
+            .at("10")
+            .matching(VersionMatch::NotOlderThan);
+        let pods: Vec<Pod> = api.list_stream(&lp).try_collect().await.unwrap();
+        let names: Vec<_> = pods.iter().map(|p| p.metadata.name.as_deref().unwrap()).collect();
+        assert_eq!(names, ["a", "b", "c"]);
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn list_page_reports_expired_cursors() {
+        let (mock_service, handle) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            let mut handle = pin!(handle);
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(
+                request.uri().to_string(),
+                "/api/v1/namespaces/default/pods?&limit=1&continue=old"
+            );
+            let status = serde_json::json!({
+                "apiVersion": "v1",
+                "kind": "Status",
+                "status": "Failure",
+                "message": "The provided continue parameter is too old to display a consistent list result.",
+                "reason": "Expired",
+                "code": 410
+            });
+            send.send_response(
+                Response::builder()
+                    .status(410)
+                    .body(Body::from(serde_json::to_vec(&status).unwrap()))
+                    .unwrap(),
+            );
+        });
+
+        let api: Api<Pod> = Api::default_namespaced(Client::new(mock_service, "default"));
+        let lp = ListParams::default().limit(1);
+        let err = api.list_page(&lp, Some(&Cursor::new("old"))).await.unwrap_err();
+        assert!(matches!(err, Error::CursorExpired(e) if e.reason == "Expired"));
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn projection_prunes_listed_and_watched_objects() {
+        let (client, mock) = Client::mock();
+        let pod = serde_json::json!({
+            "apiVersion": "v1",
+            "kind": "Pod",
+            "metadata": { "name": "web", "labels": { "app": "web" } },
+            "spec": { "nodeName": "node-1", "containers": [{ "name": "app", "image": "nginx" }] },
+        });
+        let path = "/api/v1/namespaces/default/pods";
+        let list = serde_json::json!({ "apiVersion": "v1", "kind": "PodList", "items": [pod] });
+        mock.expect(Method::GET, path).respond_json(StatusCode::OK, &list);
+        mock.expect(Method::GET, path)
+            .respond_watch([WatchEvent::Added(pod)]);
+
+        let ar = ApiResource::erase::<Pod>(&());
+        let api = Api::<DynamicObject>::default_namespaced_with(client, &ar).projection(&["spec.nodeName"]);
+        let expected = serde_json::json!({
+            "apiVersion": "v1",
+            "kind": "Pod",
+            "metadata": { "name": "web" },
+            "spec": { "nodeName": "node-1" },
+        });
+        let listed = api.list(&ListParams::default()).await.unwrap().items.remove(0);
+        assert_eq!(serde_json::to_value(&listed).unwrap(), expected);
+        let events = api.watch(&WatchParams::default(), "0").await.unwrap();
+        let watched: Vec<_> = events.try_collect().await.unwrap();
+        assert!(matches!(&watched[..], [WatchEvent::Added(obj)] if obj == &listed));
+    }
+
+    #[tokio::test]
+    async fn watch_relisting_relists_after_gone() {
+        let (client, mock) = Client::mock();
+        let pod = |name: &str, rv: &str| {
+            serde_json::json!({
+                "apiVersion": "v1",
+                "kind": "Pod",
+                "metadata": { "name": name, "resourceVersion": rv },
+            })
+        };
+        let list = |item, rv: &str| {
+            serde_json::json!({
+                "apiVersion": "v1",
+                "kind": "PodList",
+                "metadata": { "resourceVersion": rv },
+                "items": [item],
+            })
+        };
+        let gone = ErrorResponse {
+            status: "Failure".into(),
+            message: "too old resource version: 1 (4)".into(),
+            reason: "Expired".into(),
+            code: 410,
+            details: None,
+        };
+        let path = "/api/v1/namespaces/default/pods";
+        mock.expect(Method::GET, path)
+            .respond_json(StatusCode::OK, &list(pod("a", "1"), "1"));
+        mock.expect(Method::GET, path)
+            .respond_watch([WatchEvent::<Pod>::Error(gone)]);
+        mock.expect(Method::GET, path)
+            .respond_json(StatusCode::OK, &list(pod("b", "5"), "5"));
+        mock.expect(Method::GET, path)
+            .respond_watch([WatchEvent::Added(pod("c", "6"))]);
+
+        let api: Api<Pod> = Api::default_namespaced(client);
+        let events: Vec<_> = api.watch_relisting(&WatchParams::default()).collect().await;
+        let name = |pod: &Pod| pod.metadata.name.clone().unwrap();
+        assert!(matches!(&events[0], Ok(RelistingEvent::Restarted(pods)) if name(&pods[0]) == "a"));
+        assert!(matches!(&events[1], Ok(RelistingEvent::Restarted(pods)) if name(&pods[0]) == "b"));
+        assert!(matches!(&events[2], Ok(RelistingEvent::Event(WatchEvent::Added(pod))) if name(pod) == "c"));
+        // the watch is resumed from the last event, which is not expected by the mock
+        assert!(matches!(&events[3], Err(Error::Api(err)) if err.code == 404));
+        assert_eq!(events.len(), 4);
+
+        let watches: Vec<_> = mock
+            .requests()
+            .iter()
+            .map(|r| r.uri.query().unwrap_or_default().contains("watch=true"))
+            .collect();
+        assert_eq!(watches, [false, true, false, true, true]);
+        let uris: Vec<_> = mock.requests().iter().map(|r| r.uri.to_string()).collect();
+        assert!(uris[3].contains("resourceVersion=5") && uris[4].contains("resourceVersion=6"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn watch_resumable_backs_off_until_events_arrive() {
+        let (client, mock) = Client::mock();
+        let path = "/api/v1/namespaces/default/pods";
+        let pod = serde_json::json!({
+            "apiVersion": "v1",
+            "kind": "Pod",
+            "metadata": { "name": "a", "resourceVersion": "2" },
+        });
+        // closed right away twice, then an event
+        mock.expect(Method::GET, path).respond_watch(Vec::<WatchEvent<Pod>>::new());
+        mock.expect(Method::GET, path).respond_watch(Vec::<WatchEvent<Pod>>::new());
+        mock.expect(Method::GET, path).respond_watch([WatchEvent::Added(pod)]);
+
+        let api: Api<Pod> = Api::default_namespaced(client);
+        let start = tokio::time::Instant::now();
+        let mut events = pin!(api.watch_resumable(&WatchParams::default(), "1"));
+        assert!(matches!(events.next().await, Some(Ok(WatchEvent::Added(_)))));
+        let first = start.elapsed();
+        // at least 800ms and 1.6s between the three watches
+        assert!(first >= std::time::Duration::from_millis(2400), "{first:?}");
+
+        // the event reset the backoff, so the next watch is re-issued within twice the minimum delay
+        assert!(matches!(events.next().await, Some(Err(Error::Api(err))) if err.code == 404));
+        assert!(start.elapsed() - first <= std::time::Duration::from_millis(1600));
+        assert!(events.next().await.is_none());
+        assert_eq!(mock.requests().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn watch_lossy_keeps_undecodable_events() {
+        let (client, mock) = Client::mock();
+        let pod = |name: serde_json::Value| {
+            serde_json::json!({
+                "apiVersion": "v1",
+                "kind": "Pod",
+                "metadata": { "name": name },
+            })
+        };
+        mock.expect(Method::GET, "/api/v1/namespaces/default/pods")
+            .respond_watch([
+                WatchEvent::Added(pod(serde_json::json!("a"))),
+                WatchEvent::Modified(pod(serde_json::json!(42))),
+                WatchEvent::Deleted(pod(serde_json::json!("a"))),
+            ]);
+
+        let api: Api<Pod> = Api::default_namespaced(client);
+        let events = api.watch_lossy(&WatchParams::default(), "0").await.unwrap();
+        let events: Vec<_> = events.try_collect().await.unwrap();
+        assert!(matches!(&events[0], LossyWatchEvent::Event(WatchEvent::Added(_))));
+        let LossyWatchEvent::Undecodable(undecodable) = &events[1] else {
+            panic!("expected an undecodable event, got {:?}", events[1]);
+        };
+        assert!(matches!(&undecodable.raw, WatchEvent::Modified(obj) if obj["metadata"]["name"] == 42));
+        assert!(undecodable.error.is_data());
+        assert!(matches!(
+            &events[2],
+            LossyWatchEvent::Event(WatchEvent::Deleted(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn get_opt_is_none_only_for_not_found_statuses() {
+        let (client, mock) = Client::mock();
+        let path = "/api/v1/namespaces/default/pods/web";
+        mock.expect(Method::GET, path).respond_error(
+            StatusCode::NOT_FOUND,
+            "NotFound",
+            "pods \"web\" not found",
+        );
+        let not_a_status = Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(b"404 page not found".to_vec())
+            .unwrap();
+        mock.expect(Method::GET, path).respond_with(not_a_status);
+
+        let api: Api<Pod> = Api::default_namespaced(client);
+        assert!(api.get_opt("web").await.unwrap().is_none());
+        let err = api.get_opt("web").await.unwrap_err();
+        assert!(
+            matches!(err, Error::Api(ErrorResponse { code: 404, .. })),
+            "{err:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn watches_reject_events_that_are_not_json() {
+        let (client, mock) = Client::mock();
+        let protobuf = Response::builder()
+            .header(
+                http::header::CONTENT_TYPE,
+                "application/vnd.kubernetes.protobuf;stream=watch",
+            )
+            .body(b"k8s\x00".to_vec())
+            .unwrap();
+        mock.expect(Method::GET, "/api/v1/namespaces/default/pods")
+            .respond_with(protobuf);
+
+        let api: Api<Pod> = Api::default_namespaced(client).projection(&["spec.nodeName"]);
+        let err = api.watch_lossy(&WatchParams::default(), "0").await.err().unwrap();
+        assert!(matches!(err, Error::Codec(_)), "{err:?}");
+    }
+}
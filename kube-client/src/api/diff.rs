@@ -0,0 +1,114 @@
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::fmt::Debug;
+
+use crate::{api::Api, Error, Result};
+use kube_core::{
+    diff::Diff,
+    params::{Patch, PatchParams},
+};
+
+/// Metadata that the apiserver changes on every write, and that would only add noise to a diff
+const VOLATILE_METADATA: [&str; 5] = [
+    "managedFields",
+    "resourceVersion",
+    "generation",
+    "uid",
+    "creationTimestamp",
+];
+
+impl<K> Api<K>
+where
+    K: Clone + DeserializeOwned + Serialize + Debug,
+{
+    /// Preview the changes a patch would make, like `kubectl diff --server-side`
+    ///
+    /// The patch is sent as a dry run, so nothing is persisted, and the object returned by the
+    /// apiserver is compared to the live object. The result includes the changes made by defaulting
+    /// and mutating admission webhooks. Objects that do not exist yet are diffed against nothing,
+    /// so everything is added.
+    ///
+    /// Metadata that changes on every write, such as `resourceVersion` and `managedFields`, is left out.
+    ///
+    /// ```no_run
+    /// use kube::api::{Api, Patch, PatchParams};
+    /// use k8s_openapi::api::apps::v1::Deployment;
+    /// # async fn wrapper(client: kube::Client, desired: Deployment) -> Result<(), kube::Error> {
+    /// let deploys: Api<Deployment> = Api::default_namespaced(client);
+    /// let pp = PatchParams::apply("my-tool");
+    /// let diff = deploys.diff("web", &pp, &Patch::Apply(&desired)).await?;
+    /// if diff.is_empty() {
+    ///     println!("no changes");
+    /// } else {
+    ///     print!("{diff}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn diff<P: Serialize + Debug>(
+        &self,
+        name: &str,
+        pp: &PatchParams,
+        patch: &Patch<P>,
+    ) -> Result<Diff> {
+        let live = self.get_opt(name).await?;
+        let desired = self.patch(name, &pp.clone().dry_run(), patch).await?;
+        let live = match live {
+            Some(obj) => without_volatile_metadata(&obj)?,
+            None => Value::Null,
+        };
+        Ok(Diff::new(&live, &without_volatile_metadata(&desired)?))
+    }
+}
+
+fn without_volatile_metadata<K: Serialize>(obj: &K) -> Result<Value> {
+    let mut value = serde_json::to_value(obj).map_err(Error::SerdeError)?;
+    if let Some(Value::Object(metadata)) = value.get_mut("metadata") {
+        for field in VOLATILE_METADATA {
+            metadata.remove(field);
+        }
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        api::{Patch, PatchParams},
+        Api, Client,
+    };
+    use http::{Method, StatusCode};
+    use k8s_openapi::api::core::v1::ConfigMap;
+    use kube_core::diff::Change;
+
+    #[tokio::test]
+    async fn diff_compares_live_object_with_dry_run() {
+        let (client, mock) = Client::mock();
+        let path = "/api/v1/namespaces/default/configmaps/settings";
+        let config_map = |value: &str, version: &str| {
+            serde_json::json!({
+                "apiVersion": "v1",
+                "kind": "ConfigMap",
+                "metadata": { "name": "settings", "resourceVersion": version },
+                "data": { "mode": value },
+            })
+        };
+        mock.expect(Method::GET, path).respond_json(StatusCode::OK, &config_map("slow", "1"));
+        mock.expect(Method::PATCH, path).respond_json(StatusCode::OK, &config_map("fast", "2"));
+
+        let api: Api<ConfigMap> = Api::default_namespaced(client);
+        let desired = serde_json::json!({ "data": { "mode": "fast" } });
+        let diff = api
+            .diff("settings", &PatchParams::apply("test"), &Patch::Apply(desired))
+            .await
+            .unwrap();
+        assert_eq!(diff.changes, [Change::Modified {
+            path: ".data.mode".into(),
+            old: "slow".into(),
+            new: "fast".into(),
+        }]);
+        assert_eq!(diff.to_string(), "~ .data.mode: \"slow\" -> \"fast\"\n");
+        let query = mock.requests()[1].uri.query().unwrap().to_string();
+        assert!(query.contains("dryRun=All"), "{query}");
+    }
+}
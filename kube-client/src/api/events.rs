@@ -0,0 +1,392 @@
+//! Publishing `events.k8s.io/v1` Events without the controller runtime
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use k8s_openapi::{
+    api::{
+        core::v1::ObjectReference,
+        events::v1::{Event, EventSeries},
+    },
+    apimachinery::pkg::apis::meta::v1::{MicroTime, ObjectMeta},
+    chrono::{Duration, Utc},
+};
+use serde_json::json;
+
+use crate::{
+    api::{Api, Patch, PatchParams, PostParams, Resource},
+    error::ErrorResponse,
+    Client, Error, Result,
+};
+
+/// How long an event is continued as a series, rather than published as a new event
+const SERIES_TTL: Duration = Duration::minutes(6);
+
+/// Events are named after the object, and names are limited to 253 characters
+const MAX_NAME_PREFIX: usize = 236;
+
+/// The severity of a [`NewEvent`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EventType {
+    /// Something happened as expected
+    Normal,
+    /// Something might need attention
+    Warning,
+}
+
+impl EventType {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Normal => "Normal",
+            Self::Warning => "Warning",
+        }
+    }
+}
+
+/// An event to publish with [`EventPublisher`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct NewEvent {
+    /// The severity, shown as `Type` by `kubectl describe`
+    pub type_: EventType,
+    /// Why the action was taken, at most 128 characters in `PascalCase`, shown as `Reason`
+    pub reason: String,
+    /// What was done, at most 128 characters in `PascalCase`
+    pub action: String,
+    /// A human readable description, at most 1kB, shown as `Message`
+    pub note: Option<String>,
+    /// A secondary object affected by the action, e.g. the `ReplicaSet` of a `Deployment`
+    pub related: Option<ObjectReference>,
+}
+
+impl NewEvent {
+    /// A [`EventType::Normal`] event
+    pub fn normal(reason: impl Into<String>, action: impl Into<String>) -> Self {
+        Self::new(EventType::Normal, reason.into(), action.into())
+    }
+
+    /// A [`EventType::Warning`] event
+    pub fn warning(reason: impl Into<String>, action: impl Into<String>) -> Self {
+        Self::new(EventType::Warning, reason.into(), action.into())
+    }
+
+    fn new(type_: EventType, reason: String, action: String) -> Self {
+        Self {
+            type_,
+            reason,
+            action,
+            note: None,
+            related: None,
+        }
+    }
+
+    /// Set the human readable description
+    #[must_use]
+    pub fn note(mut self, note: impl Into<String>) -> Self {
+        self.note = Some(note.into());
+        self
+    }
+
+    /// Set the secondary object affected by the action
+    #[must_use]
+    pub fn related(mut self, related: ObjectReference) -> Self {
+        self.related = Some(related);
+        self
+    }
+}
+
+/// Publishes `events.k8s.io/v1` Events about objects, e.g. from scripts, CLIs and admission webhooks
+///
+/// Repeated events are not published again, but counted in the `series` of the first event. An event
+/// repeats another if it has the same type, reason and action, is published by the same controller
+/// and instance, and concerns the same objects. Such events also share their name, which is derived
+/// from a hash of these fields, so repeats are counted across publishers and restarts instead of
+/// piling up. Series are forgotten six minutes after their last event, like by the `Recorder`.
+///
+/// The controller runtime has its own `Recorder`, which also supports buffering.
+///
+/// ```no_run
+/// use kube::api::{EventPublisher, NewEvent};
+/// use k8s_openapi::api::core::v1::Pod;
+/// # async fn wrapper(client: kube::Client, pod: Pod) -> Result<(), kube::Error> {
+/// let publisher = EventPublisher::new(client, "image-checker");
+/// let event = NewEvent::warning("OutdatedImage", "Check").note("nginx:1.14 is not supported");
+/// publisher.publish(&pod, &event).await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Publishing needs the `create`, `get` and `patch` verbs for `events` in the `events.k8s.io` group.
+/// Events are created in the namespace of the object, and in `default` for cluster scoped objects.
+#[derive(Clone)]
+pub struct EventPublisher {
+    client: Client,
+    controller: String,
+    instance: String,
+    published: Arc<Mutex<HashMap<String, Event>>>,
+}
+
+impl EventPublisher {
+    /// Publish events as `controller`, which is shown as the source of the events
+    pub fn new(client: Client, controller: impl Into<String>) -> Self {
+        let controller = controller.into();
+        Self {
+            client,
+            instance: controller.clone(),
+            controller,
+            published: Arc::default(),
+        }
+    }
+
+    /// Set the instance of the controller, e.g. the pod name, to tell replicas apart
+    ///
+    /// Defaults to the name of the controller.
+    #[must_use]
+    pub fn instance(mut self, instance: impl Into<String>) -> Self {
+        self.instance = instance.into();
+        self
+    }
+
+    /// Publish an event about `obj`
+    pub async fn publish<K>(&self, obj: &K, event: &NewEvent) -> Result<Event>
+    where
+        K: Resource,
+        K::DynamicType: Default,
+    {
+        self.publish_for(&obj.object_ref(&K::DynamicType::default()), event)
+            .await
+    }
+
+    /// Publish an event about the object that `regarding` refers to
+    pub async fn publish_for(&self, regarding: &ObjectReference, event: &NewEvent) -> Result<Event> {
+        let key = self.dedup_key(regarding, event);
+        let name = event_name(regarding, &self.controller, &key);
+        let namespace = regarding.namespace.as_deref().unwrap_or("default");
+        let api: Api<Event> = Api::namespaced(self.client.clone(), namespace);
+
+        let now = Utc::now();
+        let ongoing = {
+            let mut published = self.published.lock().expect("event cache poisoned");
+            // forget series that ended, so that the cache does not grow with every object
+            published.retain(|_, prev| last_observed(prev).is_some_and(|t| t + SERIES_TTL > now));
+            published.get(&key).cloned()
+        };
+        let published = match ongoing {
+            Some(prev) => match repeat(&api, &name, &prev).await {
+                Err(Error::Api(ErrorResponse { code: 404, .. })) => {
+                    // garbage collected in the meantime, start over
+                    self.create(&api, &name, regarding, event).await?
+                }
+                res => res?,
+            },
+            None => self.create(&api, &name, regarding, event).await?,
+        };
+        self.published
+            .lock()
+            .expect("event cache poisoned")
+            .insert(key, published.clone());
+        Ok(published)
+    }
+
+    /// Create the event, or continue its series if it exists already
+    async fn create(
+        &self,
+        api: &Api<Event>,
+        name: &str,
+        regarding: &ObjectReference,
+        event: &NewEvent,
+    ) -> Result<Event> {
+        let now = MicroTime(Utc::now());
+        let new = Event {
+            metadata: ObjectMeta {
+                name: Some(name.to_string()),
+                namespace: regarding.namespace.clone(),
+                ..ObjectMeta::default()
+            },
+            event_time: Some(now),
+            type_: Some(event.type_.as_str().into()),
+            reason: Some(event.reason.clone()),
+            action: Some(event.action.clone()),
+            note: event.note.clone(),
+            regarding: Some(regarding.clone()),
+            related: event.related.clone(),
+            reporting_controller: Some(self.controller.clone()),
+            reporting_instance: Some(self.instance.clone()),
+            ..Event::default()
+        };
+        match api.create(&PostParams::default(), &new).await {
+            // published before, by another publisher or before a restart
+            Err(Error::Api(ErrorResponse { code: 409, .. })) => {
+                let existing = api.get(name).await?;
+                repeat(api, name, &existing).await
+            }
+            res => res,
+        }
+    }
+
+    /// Identifies repeats of an event, see [`EventPublisher`]
+    fn dedup_key(&self, regarding: &ObjectReference, event: &NewEvent) -> String {
+        let reference = |r: &ObjectReference| {
+            let fields = [&r.api_version, &r.kind, &r.namespace, &r.name, &r.uid];
+            fields.map(|f| f.as_deref().unwrap_or_default()).join("/")
+        };
+        let related = event.related.as_ref().map(reference).unwrap_or_default();
+        [
+            event.type_.as_str(),
+            event.reason.as_str(),
+            event.action.as_str(),
+            self.controller.as_str(),
+            self.instance.as_str(),
+            reference(regarding).as_str(),
+            related.as_str(),
+        ]
+        .join("\n")
+    }
+}
+
+/// Count another occurrence in the series of `event`
+async fn repeat(api: &Api<Event>, name: &str, event: &Event) -> Result<Event> {
+    let series = EventSeries {
+        count: event.series.as_ref().map_or(1, |s| s.count) + 1,
+        last_observed_time: MicroTime(Utc::now()),
+    };
+    api.patch(name, &PatchParams::default(), &Patch::Merge(json!({ "series": series })))
+        .await
+}
+
+fn last_observed(event: &Event) -> Option<k8s_openapi::chrono::DateTime<Utc>> {
+    match &event.series {
+        Some(series) => Some(series.last_observed_time.0),
+        None => event.event_time.as_ref().map(|t| t.0),
+    }
+}
+
+/// A name that is the same for all repeats of an event
+fn event_name(regarding: &ObjectReference, controller: &str, key: &str) -> String {
+    let prefix = regarding.name.as_deref().unwrap_or(controller);
+    let prefix = match prefix.char_indices().nth(MAX_NAME_PREFIX) {
+        Some((end, _)) => &prefix[..end],
+        None => prefix,
+    };
+    // FNV-1a, which unlike the std hashers is stable across releases and restarts
+    let hash = key.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    });
+    format!("{prefix}.{hash:016x}")
+}
+
+#[cfg(test)]
+mod test {
+    use super::{event_name, EventPublisher, NewEvent, SERIES_TTL};
+    use crate::Client;
+    use http::{Method, StatusCode};
+    use k8s_openapi::{
+        api::{
+            core::v1::{ConfigMap, ObjectReference},
+            events::v1::Event,
+        },
+        apimachinery::pkg::apis::meta::v1::MicroTime,
+        chrono::Utc,
+    };
+    use kube_core::{ObjectMeta, Resource};
+
+    #[tokio::test]
+    async fn repeated_events_are_counted_in_a_series() {
+        let (client, mock) = Client::mock();
+        let cm = ConfigMap {
+            metadata: ObjectMeta {
+                name: Some("settings".into()),
+                namespace: Some("apps".into()),
+                uid: Some("1234".into()),
+                ..ObjectMeta::default()
+            },
+            ..ConfigMap::default()
+        };
+        let publisher = EventPublisher::new(client.clone(), "checker");
+        let event = NewEvent::warning("Invalid", "Validate").note("mode must be set");
+        let key = publisher.dedup_key(&cm.object_ref(&()), &event);
+        let name = event_name(&cm.object_ref(&()), "checker", &key);
+        assert!(name.starts_with("settings."));
+        let events = "/apis/events.k8s.io/v1/namespaces/apps/events";
+        let path = format!("{events}/{name}");
+        let published = |count: Option<i32>| {
+            let series = count.map(|count| serde_json::json!({ "count": count, "lastObservedTime": now() }));
+            serde_json::json!({
+                "apiVersion": "events.k8s.io/v1",
+                "kind": "Event",
+                "metadata": { "name": name, "namespace": "apps" },
+                "eventTime": now(),
+                "series": series,
+            })
+        };
+
+        mock.expect(Method::POST, events).respond_json(StatusCode::CREATED, &published(None));
+        mock.expect(Method::PATCH, &path).respond_json(StatusCode::OK, &published(Some(2)));
+        publisher.publish(&cm, &event).await.unwrap();
+        publisher.publish(&cm, &event).await.unwrap();
+
+        // a fresh publisher finds the existing event by its name
+        mock.expect(Method::POST, events).respond_error(StatusCode::CONFLICT, "AlreadyExists", "exists");
+        mock.expect(Method::GET, &path).respond_json(StatusCode::OK, &published(Some(2)));
+        mock.expect(Method::PATCH, &path).respond_json(StatusCode::OK, &published(Some(3)));
+        let restarted = EventPublisher::new(client, "checker");
+        let event = restarted.publish(&cm, &event).await.unwrap();
+        assert_eq!(event.series.unwrap().count, 3);
+        assert!(mock.is_drained());
+
+        let requests = mock.requests();
+        let created: serde_json::Value = requests[0].json().unwrap();
+        assert_eq!(created["regarding"]["uid"], "1234");
+        assert_eq!(created["reportingController"], "checker");
+        assert_eq!(created["type"], "Warning");
+        let counts: Vec<_> = requests
+            .iter()
+            .filter(|req| req.method == Method::PATCH)
+            .map(|req| req.json::<serde_json::Value>().unwrap()["series"]["count"].clone())
+            .collect();
+        assert_eq!(counts, [2, 3]);
+
+        let other = ObjectReference {
+            name: Some("other".into()),
+            ..cm.object_ref(&())
+        };
+        assert_ne!(publisher.dedup_key(&other, &NewEvent::warning("Invalid", "Validate")), key);
+    }
+
+    #[tokio::test]
+    async fn ended_series_are_forgotten() {
+        let (client, mock) = Client::mock();
+        let publisher = EventPublisher::new(client, "checker");
+        let ended = Event {
+            event_time: Some(MicroTime(Utc::now() - SERIES_TTL)),
+            ..Event::default()
+        };
+        publisher.published.lock().unwrap().insert("ended".into(), ended);
+
+        let cm = ConfigMap {
+            metadata: ObjectMeta {
+                name: Some("settings".into()),
+                namespace: Some("apps".into()),
+                ..ObjectMeta::default()
+            },
+            ..ConfigMap::default()
+        };
+        let created = serde_json::json!({
+            "apiVersion": "events.k8s.io/v1",
+            "kind": "Event",
+            "metadata": { "name": "settings.1", "namespace": "apps" },
+            "eventTime": now(),
+        });
+        mock.expect(Method::POST, "/apis/events.k8s.io/v1/namespaces/apps/events")
+            .respond_json(StatusCode::CREATED, &created);
+        publisher.publish(&cm, &NewEvent::normal("Checked", "Validate")).await.unwrap();
+
+        let published = publisher.published.lock().unwrap();
+        assert_eq!(published.len(), 1);
+        assert!(!published.contains_key("ended"));
+    }
+
+    fn now() -> String {
+        Utc::now().to_rfc3339_opts(k8s_openapi::chrono::SecondsFormat::Micros, true)
+    }
+}
@@ -0,0 +1,338 @@
+//! Applying multi-document manifests, like `kubectl apply --server-side`
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    api::{Api, DynamicObject, GroupVersionKind, Patch, PatchParams, ResourceExt},
+    discovery::{CachedDiscovery, Scope},
+    error::DiscoveryError,
+    Client, Error, Result,
+};
+
+/// The outcome of applying a single object from [`apply_manifests`]
+#[derive(Debug)]
+pub struct AppliedManifest {
+    /// The type of the object
+    pub gvk: GroupVersionKind,
+    /// The name of the object
+    pub name: String,
+    /// The namespace the object was applied to, `None` for cluster scoped objects
+    pub namespace: Option<String>,
+    /// The object as returned by the apiserver, or why it could not be applied
+    pub result: Result<DynamicObject>,
+}
+
+/// Server-side apply every object in `manifests`
+///
+/// The manifests are YAML or JSON documents, separated by `---`, and `List` documents are applied
+/// item by item. The type of every object is resolved through discovery, so any resource served
+/// by the cluster can be applied, including custom resources defined earlier in the same manifests.
+///
+/// Namespaces and `CustomResourceDefinition`s are applied first, everything else in the order it
+/// appears. Namespaced objects without a namespace go to the default namespace of the client.
+///
+/// Applied `CustomResourceDefinition`s are polled until they are established, for at most 30 seconds,
+/// so that their custom resources are served once they are applied. `CustomResourceDefinition`s that
+/// are not established in time fail with [`Error::Timeout`].
+///
+/// The patch parameters must name a field manager, see [`PatchParams::apply`].
+///
+/// ```no_run
+/// use kube::{api::{apply_manifests, PatchParams}, Client};
+/// # async fn wrapper(client: Client) -> Result<(), Box<dyn std::error::Error>> {
+/// let manifests = std::fs::read_to_string("deploy.yaml")?;
+/// for applied in apply_manifests(&client, &manifests, &PatchParams::apply("my-tool")).await? {
+///     match applied.result {
+///         Ok(_) => println!("{} {} applied", applied.gvk.kind, applied.name),
+///         Err(err) => println!("{} {} failed: {err}", applied.gvk.kind, applied.name),
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Fails without applying anything if the manifests can not be parsed, or if any object lacks an
+/// `apiVersion` or `kind`. Failures of individual objects are returned in their [`AppliedManifest`].
+pub async fn apply_manifests(
+    client: &Client,
+    manifests: &str,
+    pp: &PatchParams,
+) -> Result<Vec<AppliedManifest>> {
+    let mut objects = parse(manifests)?;
+    // stable sort, so everything else keeps the order of the manifests
+    objects.sort_by_key(|(gvk, _)| match (gvk.group.as_str(), gvk.kind.as_str()) {
+        ("", "Namespace") => 0,
+        ("apiextensions.k8s.io", "CustomResourceDefinition") => 1,
+        _ => 2,
+    });
+
+    let discovery = CachedDiscovery::new(client.clone());
+    let mut applied = Vec::with_capacity(objects.len());
+    for (gvk, obj) in objects {
+        let name = obj.name_any();
+        let (namespace, result) = match discovery.resolve_gvk(&gvk).await {
+            Ok((ar, caps)) => {
+                let (namespace, api) = match caps.scope {
+                    Scope::Namespaced => {
+                        let ns = obj
+                            .namespace()
+                            .unwrap_or_else(|| client.default_namespace().to_string());
+                        (Some(ns.clone()), Api::namespaced_with(client.clone(), &ns, &ar))
+                    }
+                    Scope::Cluster => (None, Api::all_with(client.clone(), &ar)),
+                };
+                let result = match api.patch(&name, pp, &Patch::Apply(&obj)).await {
+                    Ok(crd) if is_crd(&gvk) => await_established(&api, crd).await,
+                    result => result,
+                };
+                (namespace, result)
+            }
+            Err(err) => (obj.namespace(), Err(err)),
+        };
+        applied.push(AppliedManifest {
+            gvk,
+            name,
+            namespace,
+            result,
+        });
+    }
+    Ok(applied)
+}
+
+// How long applied CustomResourceDefinitions are polled until they are established
+const ESTABLISHED_TIMEOUT: Duration = Duration::from_secs(30);
+
+fn is_crd(gvk: &GroupVersionKind) -> bool {
+    gvk.group == "apiextensions.k8s.io" && gvk.kind == "CustomResourceDefinition"
+}
+
+// Polls `crd` with a growing delay until its `Established` condition is true
+async fn await_established(api: &Api<DynamicObject>, mut crd: DynamicObject) -> Result<DynamicObject> {
+    let name = crd.name_any();
+    let deadline = tokio::time::Instant::now() + ESTABLISHED_TIMEOUT;
+    let mut delay = Duration::from_millis(100);
+    loop {
+        let conditions = crd.data["status"]["conditions"].as_array();
+        let established = conditions
+            .into_iter()
+            .flatten()
+            .any(|c| c["type"] == "Established" && c["status"] == "True");
+        if established {
+            return Ok(crd);
+        }
+        if tokio::time::Instant::now() + delay > deadline {
+            return Err(Error::Timeout(ESTABLISHED_TIMEOUT));
+        }
+        tokio::time::sleep(delay).await;
+        delay = (delay * 2).min(Duration::from_secs(2));
+        crd = api.get(&name).await?;
+    }
+}
+
+pub(crate) fn parse(manifests: &str) -> Result<Vec<(GroupVersionKind, DynamicObject)>> {
+    let mut values = vec![];
+    for document in serde_yaml::Deserializer::from_str(manifests) {
+        match Value::deserialize(document).map_err(Error::InvalidManifest)? {
+            // empty documents, e.g. after a trailing `---`
+            Value::Null => {}
+            Value::Object(mut list) if list.get("kind").and_then(Value::as_str) == Some("List") => {
+                if let Some(Value::Array(items)) = list.remove("items") {
+                    values.extend(items);
+                }
+            }
+            value => values.push(value),
+        }
+    }
+    values
+        .into_iter()
+        .map(|value| {
+            let obj: DynamicObject = serde_json::from_value(value).map_err(Error::SerdeError)?;
+            let gvk = obj
+                .types
+                .as_ref()
+                .and_then(|types| GroupVersionKind::try_from(types).ok())
+                .filter(|gvk| !gvk.kind.is_empty() && !gvk.version.is_empty())
+                .ok_or_else(|| {
+                    let message = format!("manifest of {} has no apiVersion or kind", obj.name_any());
+                    Error::Discovery(DiscoveryError::MissingKind(message))
+                })?;
+            Ok((gvk, obj))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use http::{Method, StatusCode};
+    use k8s_openapi::apimachinery::pkg::apis::meta::v1::{APIResource, APIResourceList};
+
+    const MANIFESTS: &str = r#"
+apiVersion: v1
+kind: ConfigMap
+metadata:
+  name: settings
+---
+{ "apiVersion": "v1", "kind": "Namespace", "metadata": { "name": "demo" } }
+---
+apiVersion: v1
+kind: List
+items:
+- apiVersion: v1
+  kind: ConfigMap
+  metadata: { name: other, namespace: demo }
+---
+"#;
+
+    #[test]
+    fn parses_documents_and_lists() {
+        let objects = parse(MANIFESTS).unwrap();
+        let names: Vec<_> = objects
+            .iter()
+            .map(|(gvk, obj)| (gvk.kind.as_str(), obj.name_any()))
+            .collect();
+        assert_eq!(names, [
+            ("ConfigMap", "settings".to_string()),
+            ("Namespace", "demo".to_string()),
+            ("ConfigMap", "other".to_string())
+        ]);
+        assert!(parse("metadata: { name: untyped }").is_err());
+        assert!(parse("kind: [").is_err());
+    }
+
+    #[tokio::test]
+    async fn applies_namespaces_first() {
+        let (client, mock) = Client::mock();
+        let resource = |kind: &str, name: &str, namespaced: bool| APIResource {
+            kind: kind.into(),
+            name: name.into(),
+            namespaced,
+            verbs: vec!["patch".into()],
+            ..APIResource::default()
+        };
+        mock.expect(Method::GET, "/api").respond_json(
+            StatusCode::OK,
+            &serde_json::json!({ "versions": ["v1"], "serverAddressByClientCIDRs": [] }),
+        );
+        mock.expect(Method::GET, "/api/v1")
+            .respond_json(StatusCode::OK, &APIResourceList {
+                group_version: "v1".into(),
+                resources: vec![
+                    resource("Namespace", "namespaces", false),
+                    resource("ConfigMap", "configmaps", true),
+                ],
+            });
+        let applied = serde_json::json!({ "apiVersion": "v1", "kind": "ConfigMap", "metadata": {} });
+        mock.expect(Method::PATCH, "/api/v1/namespaces/demo")
+            .respond_json(StatusCode::OK, &applied);
+        mock.expect(Method::PATCH, "/api/v1/namespaces/default/configmaps/settings")
+            .respond_json(StatusCode::OK, &applied);
+        mock.expect(Method::PATCH, "/api/v1/namespaces/demo/configmaps/other")
+            .respond_error(StatusCode::FORBIDDEN, "Forbidden", "configmaps is forbidden");
+
+        let pp = PatchParams::apply("test");
+        let results = apply_manifests(&client, MANIFESTS, &pp).await.unwrap();
+        let outcomes: Vec<_> = results
+            .iter()
+            .map(|a| (a.name.as_str(), a.namespace.as_deref(), a.result.is_ok()))
+            .collect();
+        assert_eq!(outcomes, [
+            ("demo", None, true),
+            ("settings", Some("default"), true),
+            ("other", Some("demo"), false)
+        ]);
+        let patches: Vec<_> = mock
+            .requests()
+            .into_iter()
+            .filter(|r| r.method == Method::PATCH)
+            .collect();
+        assert_eq!(patches.len(), 3);
+        assert!(patches
+            .iter()
+            .all(|r| r.uri.query().unwrap().contains("fieldManager=test")));
+        assert!(mock.is_drained());
+    }
+
+    #[tokio::test]
+    async fn waits_for_custom_resource_definitions_to_be_established() {
+        let (client, mock) = Client::mock();
+        let group = |name: &str| {
+            serde_json::json!({
+                "name": name,
+                "versions": [{ "groupVersion": format!("{name}/v1"), "version": "v1" }],
+            })
+        };
+        let resources = |group_version: &str, kind: &str, name: &str, namespaced: bool| APIResourceList {
+            group_version: group_version.into(),
+            resources: vec![APIResource {
+                kind: kind.into(),
+                name: name.into(),
+                namespaced,
+                verbs: vec!["get".into(), "patch".into()],
+                ..APIResource::default()
+            }],
+        };
+        let crd = |conditions: serde_json::Value| {
+            serde_json::json!({
+                "apiVersion": "apiextensions.k8s.io/v1",
+                "kind": "CustomResourceDefinition",
+                "metadata": { "name": "widgets.example.com" },
+                "status": { "conditions": conditions },
+            })
+        };
+        let crd_path = "/apis/apiextensions.k8s.io/v1/customresourcedefinitions/widgets.example.com";
+
+        mock.expect(Method::GET, "/apis").respond_json(
+            StatusCode::OK,
+            &serde_json::json!({ "groups": [group("apiextensions.k8s.io")] }),
+        );
+        mock.expect(Method::GET, "/apis/apiextensions.k8s.io/v1")
+            .respond_json(
+                StatusCode::OK,
+                &resources(
+                    "apiextensions.k8s.io/v1",
+                    "CustomResourceDefinition",
+                    "customresourcedefinitions",
+                    false,
+                ),
+            );
+        mock.expect(Method::PATCH, crd_path)
+            .respond_json(StatusCode::OK, &crd(serde_json::json!([])));
+        mock.expect(Method::GET, crd_path).respond_json(
+            StatusCode::OK,
+            &crd(serde_json::json!([{ "type": "Established", "status": "True" }])),
+        );
+        mock.expect(Method::GET, "/apis").respond_json(
+            StatusCode::OK,
+            &serde_json::json!({ "groups": [group("apiextensions.k8s.io"), group("example.com")] }),
+        );
+        mock.expect(Method::GET, "/apis/example.com/v1").respond_json(
+            StatusCode::OK,
+            &resources("example.com/v1", "Widget", "widgets", true),
+        );
+        let widget = serde_json::json!({ "apiVersion": "example.com/v1", "kind": "Widget", "metadata": {} });
+        mock.expect(
+            Method::PATCH,
+            "/apis/example.com/v1/namespaces/default/widgets/gear",
+        )
+        .respond_json(StatusCode::OK, &widget);
+
+        let manifests = r#"
+apiVersion: example.com/v1
+kind: Widget
+metadata: { name: gear }
+---
+apiVersion: apiextensions.k8s.io/v1
+kind: CustomResourceDefinition
+metadata: { name: widgets.example.com }
+"#;
+        let results = apply_manifests(&client, manifests, &PatchParams::apply("test"))
+            .await
+            .unwrap();
+        assert!(results.iter().all(|a| a.result.is_ok()));
+        assert!(mock.is_drained());
+    }
+}
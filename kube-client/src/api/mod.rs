@@ -1,44 +1,62 @@
 //! API helpers for structured interaction with the Kubernetes API
 
 mod core_methods;
+mod diff;
 #[cfg(feature = "ws")] mod remote_command;
-use std::fmt::Debug;
+use std::{fmt::Debug, str::FromStr};
 
 #[cfg(feature = "ws")] pub use remote_command::{AttachedProcess, TerminalSize};
 #[cfg(feature = "ws")] mod portforward;
 #[cfg(feature = "ws")] pub use portforward::Portforwarder;
+#[cfg(feature = "cp")] mod copy;
+#[cfg(feature = "cp")] pub use copy::CopyError;
 
 mod subresource;
 #[cfg(feature = "ws")]
 #[cfg_attr(docsrs, doc(cfg(feature = "ws")))]
 pub use subresource::{Attach, AttachParams, Ephemeral, Execute, Portforward};
-pub use subresource::{Evict, EvictParams, Log, LogParams, ScaleSpec, ScaleStatus};
+pub use subresource::{Bind, Evict, EvictParams, Log, LogParams, Proxy, ScaleSpec, ScaleStatus};
 
 mod util;
+pub use util::{NamespaceDeletionReport, RemainingObject};
 
 pub mod entry;
 
+mod events;
+pub use events::{EventPublisher, EventType, NewEvent};
+
+pub(crate) mod manifests;
+pub use manifests::{apply_manifests, AppliedManifest};
+
+mod multi_namespace;
+pub use multi_namespace::{MultiNamespaceApi, MultiNamespaceList};
+
 // Re-exports from kube-core
 #[cfg(feature = "admission")]
 #[cfg_attr(docsrs, doc(cfg(feature = "admission")))]
 pub use kube_core::admission;
 pub(crate) use kube_core::params;
+use kube_core::{
+    discovery::Scope,
+    gvk::{GroupVersion, ParseGroupVersionError},
+    DynamicResourceScope, NamespaceResourceScope,
+};
 pub use kube_core::{
     dynamic::{ApiResource, DynamicObject},
     gvk::{GroupVersionKind, GroupVersionResource},
     metadata::{ListMeta, ObjectMeta, PartialObjectMeta, PartialObjectMetaExt, TypeMeta},
     object::{NotUsed, Object, ObjectList},
+    projection::Projection,
     request::Request,
-    watch::WatchEvent,
+    watch::{LossyWatchEvent, RelistingEvent, UndecodableEvent, WatchEvent},
     Resource, ResourceExt,
 };
-use kube_core::{DynamicResourceScope, NamespaceResourceScope};
 pub use params::{
-    DeleteParams, GetParams, ListParams, Patch, PatchParams, PostParams, Preconditions, PropagationPolicy,
-    ValidationDirective, VersionMatch, WatchParams,
+    Cursor, DeleteParams, GetParams, ListParams, Patch, PatchParams, PostParams, Preconditions,
+    PropagationPolicy, ValidationDirective, VersionMatch, WatchParams,
 };
 
-use crate::Client;
+use crate::{client::middleware::RequestTimeout, Client};
 /// The generic Api abstraction
 ///
 /// This abstracts over a [`Request`] and a type `K` so that
@@ -52,6 +70,14 @@ pub struct Api<K> {
     /// The client to use (from this library)
     pub(crate) client: Client,
     namespace: Option<String>,
+    /// The resource type, attached to requests for tracing
+    gvk: GroupVersionKind,
+    /// Fields to keep in objects returned by list and watch calls
+    projection: Option<Projection>,
+    /// Extra headers sent with every request
+    headers: http::HeaderMap,
+    /// Timeout of every request, replacing the one of the client
+    timeout: Option<RequestTimeout>,
     /// Note: Using `iter::Empty` over `PhantomData`, because we never actually keep any
     /// `K` objects, so `Empty` better models our constraints (in particular, `Empty<K>`
     /// is `Send`, even if `K` may not be).
@@ -76,6 +102,10 @@ impl<K: Resource> Api<K> {
             client,
             request: Request::new(url),
             namespace: None,
+            gvk: gvk_of::<K>(dyntype),
+            projection: None,
+            headers: http::HeaderMap::new(),
+            timeout: None,
             _phantom: std::iter::empty(),
         }
     }
@@ -93,6 +123,10 @@ impl<K: Resource> Api<K> {
             client,
             request: Request::new(url),
             namespace: Some(ns.to_string()),
+            gvk: gvk_of::<K>(dyntype),
+            projection: None,
+            headers: http::HeaderMap::new(),
+            timeout: None,
             _phantom: std::iter::empty(),
         }
     }
@@ -117,10 +151,97 @@ impl<K: Resource> Api<K> {
         self.into()
     }
 
+    /// Only keep the given fields in objects returned by list and watch calls
+    ///
+    /// Objects are pruned before they are deserialized into `K`, so they take less memory in
+    /// caches such as reflector stores, which use these calls. The type fields and identifying
+    /// metadata are always kept. See [`Projection`] for the field syntax.
+    ///
+    /// Fields that are not selected must be optional in `K`, which is the case for
+    /// [`DynamicObject`](crate::api::DynamicObject) and most `k8s-openapi` types.
+    ///
+    /// Objects are pruned as JSON. Lists in the format of a registered
+    /// [`Codec`](crate::client::codec::Codec) are transcoded to JSON first, while watches are only
+    /// decoded from JSON, see [`Client::request_events`].
+    ///
+    /// ```no_run
+    /// # use kube::{Api, Client};
+    /// # let client: Client = todo!();
+    /// use k8s_openapi::api::core::v1::Pod;
+    /// let pods: Api<Pod> = Api::all(client).projection(&["metadata.labels", "spec.nodeName"]);
+    /// ```
+    #[must_use]
+    pub fn projection(mut self, fields: &[&str]) -> Self {
+        self.projection = Some(Projection::new(fields));
+        self
+    }
+
+    /// Send `value` in the header `name` with every request made through this `Api`
+    ///
+    /// Headers set here replace the ones kube sets for the same name. Use a separate `Api` per scope,
+    /// e.g. per reconciliation, for headers that identify a change:
+    ///
+    /// ```no_run
+    /// # use kube::{Api, Client};
+    /// # let client: Client = todo!();
+    /// use http::HeaderValue;
+    /// use k8s_openapi::api::apps::v1::Deployment;
+    /// let deploys: Api<Deployment> = Api::default_namespaced(client)
+    ///     .with_header("x-change-ticket".parse().unwrap(), HeaderValue::from_static("CHG-1234"));
+    /// ```
+    #[must_use]
+    pub fn with_header(mut self, name: http::HeaderName, value: http::HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    /// Bound every request made through this `Api` by `timeout`, instead of the client timeout
+    ///
+    /// This replaces [`Config::timeout`](crate::Config::timeout) for these requests, including
+    /// watches, which are otherwise exempt. `None` disables the timeout, e.g. for slow list calls.
+    ///
+    /// ```no_run
+    /// # use kube::{Api, Client};
+    /// # let client: Client = todo!();
+    /// use k8s_openapi::api::core::v1::Pod;
+    /// use std::time::Duration;
+    /// let pods: Api<Pod> = Api::default_namespaced(client).with_timeout(Some(Duration::from_secs(5)));
+    /// ```
+    #[must_use]
+    pub fn with_timeout(mut self, timeout: Option<std::time::Duration>) -> Self {
+        self.timeout = Some(RequestTimeout(timeout));
+        self
+    }
+
+    /// Send the `Audit-ID` header with every request made through this `Api`
+    ///
+    /// The apiserver records the given id as the `auditID` of the requests in its audit log, rather
+    /// than generating one per request, so all requests of a change can be correlated by it.
+    #[must_use]
+    pub fn with_audit_id(self, id: http::HeaderValue) -> Self {
+        self.with_header(http::HeaderName::from_static("audit-id"), id)
+    }
+
     /// Return a reference to the current resource url path
     pub fn resource_url(&self) -> &str {
         &self.request.url_path
     }
+
+    /// Tag a request with its verb and resource type for middleware and tracing, and add the extra headers
+    fn tag<B>(&self, req: &mut http::Request<B>, verb: &'static str) {
+        req.extensions_mut().insert(verb);
+        req.extensions_mut().insert(self.gvk.clone());
+        if let Some(timeout) = self.timeout {
+            req.extensions_mut().insert(timeout);
+        }
+        if !self.headers.is_empty() {
+            req.headers_mut().extend(self.headers.clone());
+        }
+    }
+}
+
+fn gvk_of<K: Resource>(dyntype: &K::DynamicType) -> GroupVersionKind {
+    GroupVersionKind::gvk(&K::group(dyntype), &K::version(dyntype), &K::kind(dyntype))
 }
 
 /// Api constructors for Resource implementors with Default DynamicTypes
@@ -187,6 +308,10 @@ where
             client,
             request: Request::new(url),
             namespace: Some(ns.to_string()),
+            gvk: gvk_of::<K>(&dyntype),
+            projection: None,
+            headers: http::HeaderMap::new(),
+            timeout: None,
             _phantom: std::iter::empty(),
         }
     }
@@ -221,6 +346,41 @@ where
     }
 }
 
+/// Api constructors for resources that are only known at runtime
+impl Api<DynamicObject> {
+    /// Construct an [`Api`] for an `apiVersion` and `kind`, as found in manifests
+    ///
+    /// The plural name and the scope of the resource are looked up through
+    /// [discovery](crate::discovery). Namespaced resources use the default namespace of the
+    /// client, cluster scoped resources use [`Api::all_with`].
+    ///
+    /// ```no_run
+    /// # use kube::{Api, Client};
+    /// # async fn wrapper(client: Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let deployments = Api::dynamic_from_gvk(client, "apps/v1", "Deployment").await?;
+    /// for deploy in deployments.list(&Default::default()).await? {
+    ///     println!("{:?}", deploy.metadata.name);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Fails with a [`DiscoveryError`](crate::error::DiscoveryError) for invalid api versions or kinds
+    /// that the server does not serve.
+    pub async fn dynamic_from_gvk(client: Client, api_version: &str, kind: &str) -> crate::Result<Self> {
+        let gvk = GroupVersion::from_str(api_version)
+            .map_err(|ParseGroupVersionError(s)| {
+                crate::Error::Discovery(crate::error::DiscoveryError::InvalidGroupVersion(s))
+            })?
+            .with_kind(kind);
+        let (ar, caps) = crate::discovery::pinned_kind(&client, &gvk).await?;
+        Ok(match caps.scope {
+            Scope::Namespaced => Self::default_namespaced_with(client, &ar),
+            Scope::Cluster => Self::all_with(client, &ar),
+        })
+    }
+}
+
 impl<K> From<Api<K>> for Client {
     fn from(api: Api<K>) -> Self {
         api.client
@@ -234,12 +394,20 @@ impl<K> Debug for Api<K> {
             request,
             client: _,
             namespace,
+            gvk,
+            projection,
+            headers,
+            timeout,
             _phantom,
         } = self;
         f.debug_struct("Api")
             .field("request", &request)
             .field("client", &"...")
             .field("namespace", &namespace)
+            .field("gvk", &gvk)
+            .field("projection", &projection)
+            .field("headers", &headers)
+            .field("timeout", &timeout)
             .finish()
     }
 }
@@ -263,4 +431,76 @@ mod test {
         let _: Api<corev1::PersistentVolume> = Api::all(client.clone());
         let _: Api<corev1::ConfigMap> = Api::namespaced(client, "default");
     }
+
+    #[tokio::test]
+    async fn dynamic_from_gvk_uses_discovered_scope() {
+        use http::{Method, StatusCode};
+        use k8s_openapi::apimachinery::pkg::apis::meta::v1::{APIResource, APIResourceList};
+
+        let (client, mock) = Client::mock();
+        let resources = |kind: &str, name: &str, namespaced: bool| APIResourceList {
+            group_version: if namespaced { "apps/v1" } else { "v1" }.into(),
+            resources: vec![APIResource {
+                kind: kind.into(),
+                name: name.into(),
+                namespaced,
+                verbs: vec!["list".into()],
+                ..APIResource::default()
+            }],
+        };
+        mock.expect(Method::GET, "/apis/apps/v1")
+            .respond_json(StatusCode::OK, &resources("Deployment", "deployments", true));
+        mock.expect(Method::GET, "/api/v1")
+            .respond_json(StatusCode::OK, &resources("Node", "nodes", false));
+
+        let deploys = Api::dynamic_from_gvk(client.clone(), "apps/v1", "Deployment")
+            .await
+            .unwrap();
+        assert_eq!(
+            deploys.resource_url(),
+            "/apis/apps/v1/namespaces/default/deployments"
+        );
+        let nodes = Api::dynamic_from_gvk(client.clone(), "v1", "Node").await.unwrap();
+        assert_eq!(nodes.resource_url(), "/api/v1/nodes");
+        assert!(Api::dynamic_from_gvk(client, "v1", "Widget").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn extra_headers_are_sent_with_every_request() {
+        use http::{HeaderValue, Method, StatusCode};
+
+        let (client, mock) = Client::mock();
+        mock.expect(Method::GET, "/api/v1/namespaces/default/configmaps/settings")
+            .respond_json(StatusCode::OK, &corev1::ConfigMap::default());
+        let configmaps: Api<corev1::ConfigMap> = Api::default_namespaced(client.clone())
+            .with_audit_id(HeaderValue::from_static("CHG-1234"))
+            .with_header(
+                "accept".parse().unwrap(),
+                HeaderValue::from_static("application/json"),
+            );
+        configmaps.get("settings").await.unwrap();
+        Api::<corev1::ConfigMap>::default_namespaced(client)
+            .get_opt("settings")
+            .await
+            .unwrap();
+
+        let requests = mock.requests();
+        assert_eq!(requests[0].headers["audit-id"], "CHG-1234");
+        assert_eq!(requests[0].headers.get_all("accept").iter().count(), 1);
+        assert!(!requests[1].headers.contains_key("audit-id"));
+    }
+
+    #[tokio::test]
+    async fn timeouts_apply_to_every_request() {
+        use crate::client::middleware::TimeoutLayer;
+        use std::time::Duration;
+        use tower::Layer;
+
+        let (mock_service, _handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let service = TimeoutLayer::new(None).layer(mock_service);
+        let pods: Api<corev1::Pod> = Api::default_namespaced(Client::new(service, "default"))
+            .with_timeout(Some(Duration::from_millis(10)));
+        let err = pods.get("blog").await.unwrap_err();
+        assert!(matches!(err, crate::Error::Timeout(timeout) if timeout == Duration::from_millis(10)));
+    }
 }
@@ -0,0 +1,226 @@
+//! Listing and watching across an explicit set of namespaces
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Debug,
+};
+
+use futures::{future, stream, Stream, StreamExt};
+use serde::de::DeserializeOwned;
+
+use crate::{
+    api::{Api, ListParams, ObjectList, Resource, WatchEvent, WatchParams},
+    Client, Result,
+};
+use kube_core::{DynamicResourceScope, NamespaceResourceScope};
+
+/// An [`Api`] over several namespaces, for users that can not list across the whole cluster
+///
+/// [`Api::all`] needs permission to list the resource in every namespace. When RBAC only grants
+/// access to some namespaces, this fans out one request per namespace and merges the results.
+///
+/// Every namespace is listed and watched independently, so each has its own consistent
+/// snapshot and `resourceVersion`. [`MultiNamespaceApi::list`] returns these versions, and
+/// [`MultiNamespaceApi::watch`] resumes every namespace from its own version.
+///
+/// ```no_run
+/// use futures::TryStreamExt;
+/// use k8s_openapi::api::core::v1::Pod;
+/// use kube::{api::{ListParams, MultiNamespaceApi, WatchParams}, Client};
+/// # async fn wrapper(client: Client) -> Result<(), Box<dyn std::error::Error>> {
+/// let pods: MultiNamespaceApi<Pod> = MultiNamespaceApi::new(client, ["frontend", "backend"]);
+/// let list = pods.list(&ListParams::default()).await?;
+/// println!("found {} pods", list.items.len());
+///
+/// let mut events = std::pin::pin!(pods.watch(&WatchParams::default(), &list.resource_versions));
+/// while let Some(event) = events.try_next().await? {
+///     println!("{event:?}");
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct MultiNamespaceApi<K> {
+    apis: BTreeMap<String, Api<K>>,
+}
+
+/// The objects listed by [`MultiNamespaceApi::list`]
+#[derive(Clone, Debug)]
+pub struct MultiNamespaceList<K> {
+    /// The objects of all namespaces, ordered by namespace
+    pub items: Vec<K>,
+    /// The `resourceVersion` of the list of every namespace
+    ///
+    /// Pass these to [`MultiNamespaceApi::watch`] to watch for changes after the list.
+    pub resource_versions: BTreeMap<String, String>,
+}
+
+impl<K: Resource> MultiNamespaceApi<K> {
+    /// Namespaced resources within the given namespaces
+    ///
+    /// Duplicate namespaces are only queried once.
+    pub fn new<S: AsRef<str>>(client: Client, namespaces: impl IntoIterator<Item = S>) -> Self
+    where
+        K: Resource<Scope = NamespaceResourceScope>,
+        <K as Resource>::DynamicType: Default,
+    {
+        Self::from_apis(namespaces, |ns| Api::namespaced(client.clone(), ns))
+    }
+
+    /// Namespaced resources within the given namespaces
+    ///
+    /// This function accepts `K::DynamicType` so it can be used with dynamic resources.
+    pub fn new_with<S: AsRef<str>>(
+        client: Client,
+        namespaces: impl IntoIterator<Item = S>,
+        dyntype: &K::DynamicType,
+    ) -> Self
+    where
+        K: Resource<Scope = DynamicResourceScope>,
+    {
+        Self::from_apis(namespaces, |ns| Api::namespaced_with(client.clone(), ns, dyntype))
+    }
+
+    fn from_apis<S: AsRef<str>>(
+        namespaces: impl IntoIterator<Item = S>,
+        api: impl Fn(&str) -> Api<K>,
+    ) -> Self {
+        let namespaces: BTreeSet<String> = namespaces.into_iter().map(|ns| ns.as_ref().to_string()).collect();
+        let apis = namespaces.into_iter().map(|ns| (ns.clone(), api(&ns))).collect();
+        Self { apis }
+    }
+
+    /// The namespaces covered, in order
+    pub fn namespaces(&self) -> impl Iterator<Item = &str> {
+        self.apis.keys().map(String::as_str)
+    }
+
+    /// The [`Api`] of a single namespace, for requests other than list and watch
+    pub fn api(&self, namespace: &str) -> Option<&Api<K>> {
+        self.apis.get(namespace)
+    }
+}
+
+impl<K> MultiNamespaceApi<K>
+where
+    K: Resource + Clone + DeserializeOwned + Debug,
+{
+    /// List the resources matching the [`ListParams`] in every namespace
+    ///
+    /// The namespaces are listed concurrently. When [`ListParams::limit`] is set, every namespace
+    /// is paged through to the end, so the items of a namespace come from one consistent snapshot.
+    ///
+    /// Fails if listing any namespace fails, e.g. with a `403 Forbidden`.
+    pub async fn list(&self, lp: &ListParams) -> Result<MultiNamespaceList<K>> {
+        let lists = future::try_join_all(self.apis.values().map(|api| list_all(api, lp))).await?;
+        let mut merged = MultiNamespaceList {
+            items: vec![],
+            resource_versions: BTreeMap::new(),
+        };
+        for (ns, mut list) in self.apis.keys().zip(lists) {
+            merged.items.append(&mut list.items);
+            if let Some(version) = list.metadata.resource_version {
+                merged.resource_versions.insert(ns.clone(), version);
+            }
+        }
+        Ok(merged)
+    }
+
+    /// Watch the resources matching the [`WatchParams`] in every namespace
+    ///
+    /// Every namespace is watched from its version in `versions`, as returned by
+    /// [`MultiNamespaceApi::list`]. Namespaces without a version start from `"0"`, which first
+    /// yields all existing objects as `Added` events. Like [`Api::watch_resumable`], every watch
+    /// reconnects from the last version it has seen when the server closes it.
+    ///
+    /// The events of all namespaces are merged into one stream, in the order they arrive.
+    pub fn watch<'a>(
+        &'a self,
+        wp: &WatchParams,
+        versions: &BTreeMap<String, String>,
+    ) -> impl Stream<Item = Result<WatchEvent<K>>> + Send + 'a
+    where
+        K: Send + 'static,
+    {
+        let streams = self.apis.iter().map(|(ns, api)| {
+            let version = versions.get(ns).map_or("0", String::as_str);
+            api.watch_resumable(wp, version).boxed()
+        });
+        stream::select_all(streams)
+    }
+}
+
+async fn list_all<K>(api: &Api<K>, lp: &ListParams) -> Result<ObjectList<K>>
+where
+    K: Resource + Clone + DeserializeOwned + Debug,
+{
+    let mut list = api.list_page(lp, None).await?;
+    while let Some(cursor) = list.cursor() {
+        let mut page = api.list_page(lp, Some(&cursor)).await?;
+        list.items.append(&mut page.items);
+        list.metadata.continue_ = page.metadata.continue_;
+    }
+    Ok(list)
+}
+
+#[cfg(test)]
+mod test {
+    use super::MultiNamespaceApi;
+    use crate::{
+        api::{ListParams, WatchEvent, WatchParams},
+        Client, ResourceExt,
+    };
+    use futures::StreamExt;
+    use http::{Method, StatusCode};
+    use k8s_openapi::api::core::v1::Pod;
+
+    fn pod(ns: &str, name: &str) -> serde_json::Value {
+        serde_json::json!({
+            "apiVersion": "v1",
+            "kind": "Pod",
+            "metadata": { "name": name, "namespace": ns },
+            "spec": { "containers": [] },
+        })
+    }
+
+    #[tokio::test]
+    async fn lists_and_watches_every_namespace() {
+        let (client, mock) = Client::mock();
+        let list = |ns: &str, version: &str, token: &str, names: &[&str]| {
+            let items: Vec<_> = names.iter().map(|name| pod(ns, name)).collect();
+            serde_json::json!({
+                "apiVersion": "v1",
+                "kind": "PodList",
+                "metadata": { "resourceVersion": version, "continue": token },
+                "items": items,
+            })
+        };
+        let (a, b) = ("/api/v1/namespaces/a/pods", "/api/v1/namespaces/b/pods");
+        mock.expect(Method::GET, a).respond_json(StatusCode::OK, &list("a", "10", "next", &["a1"]));
+        mock.expect(Method::GET, a).respond_json(StatusCode::OK, &list("a", "10", "", &["a2"]));
+        mock.expect(Method::GET, b).respond_json(StatusCode::OK, &list("b", "20", "", &["b1"]));
+
+        let pods: MultiNamespaceApi<Pod> = MultiNamespaceApi::new(client, ["b", "a", "b"]);
+        assert_eq!(pods.namespaces().collect::<Vec<_>>(), ["a", "b"]);
+        let listed = pods.list(&ListParams::default().limit(1)).await.unwrap();
+        let names: Vec<_> = listed.items.iter().map(ResourceExt::name_any).collect();
+        assert_eq!(names, ["a1", "a2", "b1"]);
+        assert_eq!(listed.resource_versions["a"], "10");
+        assert_eq!(listed.resource_versions["b"], "20");
+        let continued = mock.requests().iter().filter(|req| req.uri.path() == a).count();
+        assert_eq!(continued, 2);
+        assert!(mock.requests().iter().any(|req| req.uri.to_string().contains("continue=next")));
+
+        mock.expect(Method::GET, a).respond_watch([WatchEvent::Added(pod("a", "a3"))]);
+        mock.expect(Method::GET, b).respond_watch([WatchEvent::Deleted(pod("b", "b1"))]);
+        let mut versions = listed.resource_versions.clone();
+        versions.remove("b");
+        // each watch ends when the mock has no more responses for its reconnect
+        let events: Vec<_> = pods.watch(&WatchParams::default(), &versions).collect().await;
+        let events: Vec<_> = events.into_iter().filter_map(Result::ok).collect();
+        assert!(events.iter().any(|e| matches!(e, WatchEvent::Added(p) if p.name_any() == "a3")));
+        assert!(events.iter().any(|e| matches!(e, WatchEvent::Deleted(p) if p.name_any() == "b1")));
+        let watches: Vec<_> = mock.requests()[3..].iter().map(|req| req.uri.to_string()).collect();
+        assert!(watches.iter().any(|uri| uri.contains("/a/pods") && uri.contains("resourceVersion=10")));
+        assert!(watches.iter().any(|uri| uri.contains("/b/pods") && uri.contains("resourceVersion=0")));
+    }
+}
@@ -4,6 +4,7 @@ use std::fmt::Debug;
 
 use crate::{
     api::{Api, Patch, PatchParams, PostParams},
+    client::Body,
     Error, Result,
 };
 
@@ -15,6 +16,7 @@ pub use kube_core::subresource::{EvictParams, LogParams};
 pub use kube_core::subresource::AttachParams;
 
 pub use k8s_openapi::api::autoscaling::v1::{Scale, ScaleSpec, ScaleStatus};
+use k8s_openapi::api::core::v1::{Binding, EphemeralContainer, Pod};
 
 #[cfg(feature = "ws")] use crate::api::portforward::Portforwarder;
 #[cfg(feature = "ws")] use crate::api::remote_command::AttachedProcess;
@@ -30,7 +32,7 @@ where
             .request
             .get_subresource("scale", name)
             .map_err(Error::BuildRequest)?;
-        req.extensions_mut().insert("get_scale");
+        self.tag(&mut req, "get_scale");
         self.client.request::<Scale>(req).await
     }
 
@@ -45,7 +47,7 @@ where
             .request
             .patch_subresource("scale", name, pp, patch)
             .map_err(Error::BuildRequest)?;
-        req.extensions_mut().insert("patch_scale");
+        self.tag(&mut req, "patch_scale");
         self.client.request::<Scale>(req).await
     }
 
@@ -55,7 +57,7 @@ where
             .request
             .replace_subresource("scale", name, pp, data)
             .map_err(Error::BuildRequest)?;
-        req.extensions_mut().insert("replace_scale");
+        self.tag(&mut req, "replace_scale");
         self.client.request::<Scale>(req).await
     }
 }
@@ -71,7 +73,7 @@ where
             .request
             .get_subresource(subresource_name, name)
             .map_err(Error::BuildRequest)?;
-        req.extensions_mut().insert("get_subresource");
+        self.tag(&mut req, "get_subresource");
         self.client.request::<K>(req).await
     }
 
@@ -90,7 +92,7 @@ where
             .request
             .create_subresource(subresource_name, name, pp, data)
             .map_err(Error::BuildRequest)?;
-        req.extensions_mut().insert("create_subresource");
+        self.tag(&mut req, "create_subresource");
         self.client.request::<T>(req).await
     }
 
@@ -106,7 +108,7 @@ where
             .request
             .patch_subresource(subresource_name, name, pp, patch)
             .map_err(Error::BuildRequest)?;
-        req.extensions_mut().insert("patch_subresource");
+        self.tag(&mut req, "patch_subresource");
         self.client.request::<K>(req).await
     }
 
@@ -122,9 +124,117 @@ where
             .request
             .replace_subresource(subresource_name, name, pp, data)
             .map_err(Error::BuildRequest)?;
-        req.extensions_mut().insert("replace_subresource");
+        self.tag(&mut req, "replace_subresource");
         self.client.request::<K>(req).await
     }
+
+    /// Fetch an arbitrary subresource and deserialize it as `T`
+    ///
+    /// Useful for subresources that do not return the parent object,
+    /// such as custom subresources on aggregated APIs.
+    ///
+    /// ```no_run
+    /// use kube::api::Api;
+    /// use k8s_openapi::api::{apps::v1::Deployment, autoscaling::v1::Scale};
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// let deploys: Api<Deployment> = Api::namespaced(client, "apps");
+    /// let scale: Scale = deploys.get_subresource_as("scale", "blog").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_subresource_as<T>(&self, subresource_name: &str, name: &str) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let mut req = self
+            .request
+            .get_subresource(subresource_name, name)
+            .map_err(Error::BuildRequest)?;
+        self.tag(&mut req, "get_subresource");
+        self.client.request::<T>(req).await
+    }
+
+    /// Patch an arbitrary subresource and deserialize the response as `T`
+    pub async fn patch_subresource_as<P, T>(
+        &self,
+        subresource_name: &str,
+        name: &str,
+        pp: &PatchParams,
+        patch: &Patch<P>,
+    ) -> Result<T>
+    where
+        P: Serialize + Debug,
+        T: DeserializeOwned,
+    {
+        let mut req = self
+            .request
+            .patch_subresource(subresource_name, name, pp, patch)
+            .map_err(Error::BuildRequest)?;
+        self.tag(&mut req, "patch_subresource");
+        self.client.request::<T>(req).await
+    }
+
+    /// Replace an arbitrary subresource with a typed body and deserialize the response as `T`
+    pub async fn replace_subresource_as<B, T>(
+        &self,
+        subresource_name: &str,
+        name: &str,
+        pp: &PostParams,
+        data: &B,
+    ) -> Result<T>
+    where
+        B: Serialize,
+        T: DeserializeOwned,
+    {
+        let bytes = serde_json::to_vec(data).map_err(Error::SerdeError)?;
+        let mut req = self
+            .request
+            .replace_subresource(subresource_name, name, pp, bytes)
+            .map_err(Error::BuildRequest)?;
+        self.tag(&mut req, "replace_subresource");
+        self.client.request::<T>(req).await
+    }
+}
+
+#[tokio::test]
+async fn typed_subresource_accessors() {
+    use crate::{client::Body, Client};
+    use http::{Request, Response};
+    use k8s_openapi::api::apps::v1::Deployment;
+    use std::pin::pin;
+
+    let (mock_service, handle) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+    let spawned = tokio::spawn(async move {
+        let mut handle = pin!(handle);
+        let (request, send) = handle.next_request().await.expect("service not called");
+        assert_eq!(request.method(), http::Method::PUT);
+        assert_eq!(
+            request.uri().to_string(),
+            "/apis/apps/v1/namespaces/default/deployments/blog/scale?&fieldValidation=Strict"
+        );
+        let scale = serde_json::json!({
+            "apiVersion": "autoscaling/v1",
+            "kind": "Scale",
+            "metadata": { "name": "blog" },
+            "spec": { "replicas": 2 }
+        });
+        send.send_response(
+            Response::builder()
+                .body(Body::from(serde_json::to_vec(&scale).unwrap()))
+                .unwrap(),
+        );
+    });
+
+    let api: Api<Deployment> = Api::default_namespaced(Client::new(mock_service, "default"));
+    let body = Scale {
+        spec: Some(ScaleSpec { replicas: Some(2) }),
+        ..Scale::default()
+    };
+    let pp = PostParams::default().validation_strict();
+    let scale: Scale = api.replace_subresource_as("scale", "blog", &pp, &body).await.unwrap();
+    assert_eq!(scale.spec.unwrap().replicas, Some(2));
+    spawned.await.unwrap();
 }
 
 // ----------------------------------------------------------------------------
@@ -201,7 +311,7 @@ where
                 serde_json::to_vec(data).map_err(Error::SerdeError)?,
             )
             .map_err(Error::BuildRequest)?;
-        req.extensions_mut().insert("replace_ephemeralcontainers");
+        self.tag(&mut req, "replace_ephemeralcontainers");
         self.client.request::<K>(req).await
     }
 
@@ -265,7 +375,7 @@ where
             .patch_subresource("ephemeralcontainers", name, pp, patch)
             .map_err(Error::BuildRequest)?;
 
-        req.extensions_mut().insert("patch_ephemeralcontainers");
+        self.tag(&mut req, "patch_ephemeralcontainers");
         self.client.request::<K>(req).await
     }
 
@@ -278,11 +388,168 @@ where
             .get_subresource("ephemeralcontainers", name)
             .map_err(Error::BuildRequest)?;
 
-        req.extensions_mut().insert("get_ephemeralcontainers");
+        self.tag(&mut req, "get_ephemeralcontainers");
         self.client.request::<K>(req).await
     }
 }
 
+impl Api<Pod> {
+    /// Add an ephemeral container to a pod for debugging, like `kubectl debug`
+    ///
+    /// The container is added with a strategic merge patch of the ephemeral containers subresource,
+    /// so existing ephemeral containers are kept. Ephemeral containers can not be changed or removed
+    /// afterwards, and a pod can not have two ephemeral containers with the same name.
+    ///
+    /// ```no_run
+    /// use k8s_openapi::api::core::v1::{EphemeralContainer, Pod};
+    /// use kube::Api;
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = kube::Client::try_default().await?;
+    /// let pods: Api<Pod> = Api::namespaced(client, "apps");
+    /// let debugger = EphemeralContainer {
+    ///     name: "debugger".into(),
+    ///     image: Some("busybox:1.36".into()),
+    ///     command: Some(vec!["sleep".into(), "3600".into()]),
+    ///     target_container_name: Some("app".into()),
+    ///     ..EphemeralContainer::default()
+    /// };
+    /// pods.debug("mypod", &debugger).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn debug(&self, name: &str, container: &EphemeralContainer) -> Result<Pod> {
+        let patch = serde_json::json!({ "spec": { "ephemeralContainers": [container] } });
+        self.patch_ephemeral_containers(name, &PatchParams::default(), &Patch::Strategic(patch))
+            .await
+    }
+
+    /// Add an ephemeral container to a pod like [`Api::debug`], and attach to it once it has started
+    ///
+    /// The container of `ap` is replaced by the ephemeral container. The pod is polled every second
+    /// until the container is running or has terminated. Containers that wait because their image
+    /// can not be pulled or they can not be created fail with [`Error::DebugContainer`].
+    ///
+    /// Other waits are not bounded, e.g. for the image of a slow registry or a node that is gone,
+    /// so wrap the call in a timeout where that matters:
+    ///
+    /// ```no_run
+    /// use k8s_openapi::api::core::v1::{EphemeralContainer, Pod};
+    /// use kube::{api::AttachParams, Api};
+    /// # use std::error::Error;
+    /// # async fn wrapper(pods: Api<Pod>, debugger: EphemeralContainer) -> Result<(), Box<dyn Error>> {
+    /// let ap = AttachParams::interactive_tty();
+    /// let timeout = std::time::Duration::from_secs(60);
+    /// let _attached = tokio::time::timeout(timeout, pods.debug_attach("mypod", &debugger, ap)).await??;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "ws")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "ws")))]
+    pub async fn debug_attach(
+        &self,
+        name: &str,
+        container: &EphemeralContainer,
+        ap: AttachParams,
+    ) -> Result<AttachedProcess> {
+        self.debug(name, container).await?;
+        loop {
+            let pod = self.get(name).await?;
+            let state = pod
+                .status
+                .and_then(|status| status.ephemeral_container_statuses)
+                .into_iter()
+                .flatten()
+                .find(|status| status.name == container.name)
+                .and_then(|status| status.state)
+                .unwrap_or_default();
+            if state.running.is_some() || state.terminated.is_some() {
+                break;
+            }
+            if let Some(waiting) = state.waiting {
+                let reason = waiting.reason.unwrap_or_default();
+                // e.g. ErrImagePull, ImagePullBackOff, CreateContainerConfigError or RunContainerError
+                let pull_failed = reason == "ImagePullBackOff" || reason == "InvalidImageName";
+                if pull_failed || reason.starts_with("Err") || reason.ends_with("Error") {
+                    return Err(Error::DebugContainer {
+                        container: container.name.clone(),
+                        reason,
+                        message: waiting.message.unwrap_or_default(),
+                    });
+                }
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        }
+        self.attach(name, &ap.container(&container.name)).await
+    }
+}
+
+#[tokio::test]
+async fn debug_appends_an_ephemeral_container() {
+    use crate::Client;
+    use http::{Method, StatusCode};
+
+    let (client, mock) = Client::mock();
+    let path = "/api/v1/namespaces/default/pods/web/ephemeralcontainers";
+    mock.expect(Method::PATCH, path).respond_json(StatusCode::OK, &Pod::default());
+    let pods: Api<Pod> = Api::default_namespaced(client);
+    let debugger = EphemeralContainer {
+        name: "debugger".into(),
+        image: Some("busybox".into()),
+        ..EphemeralContainer::default()
+    };
+    pods.debug("web", &debugger).await.unwrap();
+
+    let sent = &mock.requests()[0];
+    assert_eq!(
+        sent.headers[http::header::CONTENT_TYPE],
+        "application/strategic-merge-patch+json"
+    );
+    let patch: serde_json::Value = sent.json().unwrap();
+    assert_eq!(
+        patch,
+        serde_json::json!({ "spec": { "ephemeralContainers": [{ "name": "debugger", "image": "busybox" }] } })
+    );
+}
+
+#[cfg(feature = "ws")]
+#[tokio::test]
+async fn debug_attach_fails_when_the_image_can_not_be_pulled() {
+    use crate::Client;
+    use http::{Method, StatusCode};
+
+    let (client, mock) = Client::mock();
+    let pod = serde_json::json!({
+        "apiVersion": "v1",
+        "kind": "Pod",
+        "metadata": { "name": "web" },
+        "status": {
+            "ephemeralContainerStatuses": [{
+                "name": "debugger",
+                "image": "busybox:nope",
+                "imageID": "",
+                "ready": false,
+                "restartCount": 0,
+                "state": { "waiting": { "reason": "ImagePullBackOff", "message": "Back-off pulling image" } },
+            }],
+        },
+    });
+    let path = "/api/v1/namespaces/default/pods/web";
+    mock.expect(Method::PATCH, format!("{path}/ephemeralcontainers"))
+        .respond_json(StatusCode::OK, &Pod::default());
+    mock.expect(Method::GET, path).respond_json(StatusCode::OK, &pod);
+    let pods: Api<Pod> = Api::default_namespaced(client);
+    let debugger = EphemeralContainer {
+        name: "debugger".into(),
+        image: Some("busybox:nope".into()),
+        ..EphemeralContainer::default()
+    };
+    let err = pods
+        .debug_attach("web", &debugger, AttachParams::default())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::DebugContainer { ref reason, .. } if reason == "ImagePullBackOff"));
+}
+
 // ----------------------------------------------------------------------------
 
 // TODO: Replace examples with owned custom resources. Bad practice to write to owned objects
@@ -300,7 +567,7 @@ where
             .request
             .get_subresource("status", name)
             .map_err(Error::BuildRequest)?;
-        req.extensions_mut().insert("get_status");
+        self.tag(&mut req, "get_status");
         self.client.request::<K>(req).await
     }
 
@@ -336,7 +603,7 @@ where
             .request
             .patch_subresource("status", name, pp, patch)
             .map_err(Error::BuildRequest)?;
-        req.extensions_mut().insert("patch_status");
+        self.tag(&mut req, "patch_status");
         self.client.request::<K>(req).await
     }
 
@@ -363,11 +630,75 @@ where
             .request
             .replace_subresource("status", name, pp, data)
             .map_err(Error::BuildRequest)?;
-        req.extensions_mut().insert("replace_status");
+        self.tag(&mut req, "replace_status");
+        self.client.request::<K>(req).await
+    }
+
+    /// Replace every field on the status object with the status of `data`
+    ///
+    /// This is [`Api::replace_status`] for a typed object, like one returned by [`Api::get_status`].
+    /// The `resourceVersion` of `data` is used for optimistic concurrency, like with [`Api::replace`].
+    ///
+    /// ```no_run
+    /// use kube::api::{Api, PostParams};
+    /// use k8s_openapi::api::batch::v1::{Job, JobStatus};
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// #   let client = kube::Client::try_default().await?;
+    /// let jobs: Api<Job> = Api::namespaced(client, "apps");
+    /// let mut o = jobs.get_status("baz").await?;
+    /// o.status = Some(JobStatus::default());
+    /// let o = jobs.replace_status_object("baz", &PostParams::default(), &o).await?;
+    /// #    Ok(())
+    /// # }
+    /// ```
+    pub async fn replace_status_object(&self, name: &str, pp: &PostParams, data: &K) -> Result<K>
+    where
+        K: Serialize,
+    {
+        let bytes = serde_json::to_vec(data).map_err(Error::SerdeError)?;
+        let mut req = self
+            .request
+            .replace_subresource("status", name, pp, bytes)
+            .map_err(Error::BuildRequest)?;
+        self.tag(&mut req, "replace_status");
         self.client.request::<K>(req).await
     }
 }
 
+#[tokio::test]
+async fn status_is_replaced_from_a_typed_object() {
+    use crate::Client;
+    use http::{Method, StatusCode};
+    use k8s_openapi::api::batch::v1::{Job, JobStatus};
+
+    let (client, mock) = Client::mock();
+    let path = "/apis/batch/v1/namespaces/default/jobs/baz/status";
+    let job = Job {
+        metadata: kube_core::ObjectMeta {
+            name: Some("baz".into()),
+            ..Default::default()
+        },
+        status: Some(JobStatus {
+            succeeded: Some(2),
+            ..JobStatus::default()
+        }),
+        ..Job::default()
+    };
+    mock.expect(Method::GET, path).respond_json(StatusCode::OK, &job);
+    mock.expect(Method::PUT, path).respond_json(StatusCode::OK, &job);
+    let jobs: Api<Job> = Api::default_namespaced(client);
+
+    let current = jobs.get_status("baz").await.unwrap();
+    let replaced = jobs
+        .replace_status_object("baz", &PostParams::default(), &current)
+        .await
+        .unwrap();
+    assert_eq!(replaced.status.unwrap().succeeded, Some(2));
+
+    let sent: Job = mock.requests()[1].json().unwrap();
+    assert_eq!(sent, job);
+}
+
 // ----------------------------------------------------------------------------
 // Log subresource
 // ----------------------------------------------------------------------------
@@ -399,7 +730,7 @@ where
     /// Fetch logs as a string
     pub async fn logs(&self, name: &str, lp: &LogParams) -> Result<String> {
         let mut req = self.request.logs(name, lp).map_err(Error::BuildRequest)?;
-        req.extensions_mut().insert("logs");
+        self.tag(&mut req, "logs");
         self.client.request_text(req).await
     }
 
@@ -430,7 +761,7 @@ where
     /// ```
     pub async fn log_stream(&self, name: &str, lp: &LogParams) -> Result<impl AsyncBufRead> {
         let mut req = self.request.logs(name, lp).map_err(Error::BuildRequest)?;
-        req.extensions_mut().insert("log_stream");
+        self.tag(&mut req, "log_stream");
         self.client.request_stream(req).await
     }
 }
@@ -463,11 +794,151 @@ where
     /// Create an eviction
     pub async fn evict(&self, name: &str, ep: &EvictParams) -> Result<Status> {
         let mut req = self.request.evict(name, ep).map_err(Error::BuildRequest)?;
-        req.extensions_mut().insert("evict");
+        self.tag(&mut req, "evict");
         self.client.request::<Status>(req).await
     }
 }
 
+// ----------------------------------------------------------------------------
+// Binding subresource
+// ----------------------------------------------------------------------------
+
+/// Marker trait for objects that can be bound to a node
+///
+/// See [`Api::bind`] for usage
+pub trait Bind {}
+
+impl Bind for k8s_openapi::api::core::v1::Pod {}
+
+impl<K> Api<K>
+where
+    K: DeserializeOwned + Bind,
+{
+    /// Bind a pending pod to a node, like a scheduler does
+    ///
+    /// ```no_run
+    /// use k8s_openapi::api::core::v1::{Binding, ObjectReference, Pod};
+    /// use kube::api::{Api, ObjectMeta};
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = kube::Client::try_default().await?;
+    /// let pods: Api<Pod> = Api::default_namespaced(client);
+    /// let binding = Binding {
+    ///     metadata: ObjectMeta {
+    ///         name: Some("blog".into()),
+    ///         ..ObjectMeta::default()
+    ///     },
+    ///     target: ObjectReference {
+    ///         api_version: Some("v1".into()),
+    ///         kind: Some("Node".into()),
+    ///         name: Some("node-1".into()),
+    ///         ..ObjectReference::default()
+    ///     },
+    /// };
+    /// pods.bind("blog", &binding).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn bind(&self, name: &str, binding: &Binding) -> Result<Status> {
+        let bytes = serde_json::to_vec(binding).map_err(Error::SerdeError)?;
+        let mut req = self
+            .request
+            .create_subresource("binding", name, &PostParams::default(), bytes)
+            .map_err(Error::BuildRequest)?;
+        self.tag(&mut req, "bind");
+        self.client.request::<Status>(req).await
+    }
+}
+
+#[tokio::test]
+async fn bind_posts_the_binding() {
+    use crate::Client;
+    use http::{Method, StatusCode};
+    use k8s_openapi::api::core::v1::ObjectReference;
+
+    let (client, mock) = Client::mock();
+    mock.expect(Method::POST, "/api/v1/namespaces/default/pods/blog/binding")
+        .respond_json(
+            StatusCode::CREATED,
+            &serde_json::json!({ "kind": "Status", "apiVersion": "v1", "status": "Success", "code": 201 }),
+        );
+    let pods: Api<Pod> = Api::default_namespaced(client);
+    let binding = Binding {
+        metadata: kube_core::ObjectMeta {
+            name: Some("blog".into()),
+            ..Default::default()
+        },
+        target: ObjectReference {
+            kind: Some("Node".into()),
+            name: Some("node-1".into()),
+            ..ObjectReference::default()
+        },
+    };
+    let status = pods.bind("blog", &binding).await.unwrap();
+    assert!(status.is_success());
+
+    let sent: Binding = mock.requests()[0].json().unwrap();
+    assert_eq!(sent, binding);
+}
+
+// ----------------------------------------------------------------------------
+// Proxy subresource
+// ----------------------------------------------------------------------------
+
+#[test]
+fn proxy_path() {
+    use crate::api::{Request, Resource};
+    use k8s_openapi::api::core::v1 as corev1;
+    let url = corev1::Service::url_path(&(), Some("ns"));
+    let proxied = http::Request::get("/metrics?format=text").body(vec![]).unwrap();
+    let req = Request::new(url).proxy("web", 9090, proxied).unwrap();
+    assert_eq!(req.uri(), "/api/v1/namespaces/ns/services/web:9090/proxy/metrics?format=text");
+    assert_eq!(req.method(), http::Method::GET);
+}
+
+/// Marker trait for objects that the apiserver can proxy requests to
+///
+/// See [`Api::proxy`] for usage
+pub trait Proxy {}
+
+impl Proxy for k8s_openapi::api::core::v1::Pod {}
+impl Proxy for k8s_openapi::api::core::v1::Service {}
+impl Proxy for k8s_openapi::api::core::v1::Node {}
+
+impl<K> Api<K>
+where
+    K: DeserializeOwned + Proxy,
+{
+    /// Send a request to a port of an object through the proxy subresource of the apiserver
+    ///
+    /// This reaches endpoints of pods, services and nodes, like health checks or metrics, without a
+    /// direct network route to them. The path and query of `request` are relative to the port of
+    /// the object. The response is returned as is, so a failure status may come from the apiserver
+    /// or from the proxied endpoint.
+    ///
+    /// ```no_run
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # use k8s_openapi::api::core::v1::Pod;
+    /// # use kube::{api::Api, Client};
+    /// # let client: Client = todo!();
+    /// let pods: Api<Pod> = Api::default_namespaced(client);
+    /// let req = http::Request::get("/metrics").body(vec![])?;
+    /// let res = pods.proxy("my-pod", 9090, req).await?;
+    /// let metrics = res.into_body().collect_bytes().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn proxy(
+        &self,
+        name: &str,
+        port: u16,
+        request: http::Request<Vec<u8>>,
+    ) -> Result<http::Response<Body>> {
+        let mut req = self.request.proxy(name, port, request).map_err(Error::BuildRequest)?;
+        self.tag(&mut req, "proxy");
+        self.client.send(req.map(Body::from)).await
+    }
+}
+
 // ----------------------------------------------------------------------------
 // Attach subresource
 // ----------------------------------------------------------------------------
@@ -509,7 +980,7 @@ where
     /// Attach to pod
     pub async fn attach(&self, name: &str, ap: &AttachParams) -> Result<AttachedProcess> {
         let mut req = self.request.attach(name, ap).map_err(Error::BuildRequest)?;
-        req.extensions_mut().insert("attach");
+        self.tag(&mut req, "attach");
         let stream = self.client.connect(req).await?;
         Ok(AttachedProcess::new(stream, ap))
     }
@@ -564,7 +1035,7 @@ where
             .request
             .exec(name, command, ap)
             .map_err(Error::BuildRequest)?;
-        req.extensions_mut().insert("exec");
+        self.tag(&mut req, "exec");
         let stream = self.client.connect(req).await?;
         Ok(AttachedProcess::new(stream, ap))
     }
@@ -602,10 +1073,11 @@ where
 {
     /// Forward ports of a pod
     pub async fn portforward(&self, name: &str, ports: &[u16]) -> Result<Portforwarder> {
-        let req = self
+        let mut req = self
             .request
             .portforward(name, ports)
             .map_err(Error::BuildRequest)?;
+        self.tag(&mut req, "portforward");
         let connection = self.client.connect(req).await?;
         Ok(Portforwarder::new(connection.into_stream(), ports))
     }
@@ -1,6 +1,10 @@
 use crate::{api::Api, Error, Result};
-use k8s_openapi::api::certificates::v1::CertificateSigningRequest;
-use kube_core::params::{Patch, PatchParams};
+use chrono::Utc;
+use k8s_openapi::{
+    api::certificates::v1::{CertificateSigningRequest, CertificateSigningRequestCondition},
+    apimachinery::pkg::apis::meta::v1::Time,
+};
+use kube_core::params::{Patch, PatchParams, PostParams};
 
 
 impl Api<CertificateSigningRequest> {
@@ -15,7 +19,7 @@ impl Api<CertificateSigningRequest> {
             .request
             .patch_subresource("approval", name, pp, patch)
             .map_err(Error::BuildRequest)?;
-        req.extensions_mut().insert("approval");
+        self.tag(&mut req, "approval");
         self.client.request::<CertificateSigningRequest>(req).await
     }
 
@@ -23,4 +27,127 @@ impl Api<CertificateSigningRequest> {
     pub async fn get_approval(&self, name: &str) -> Result<CertificateSigningRequest> {
         self.get_subresource("approval", name).await
     }
+
+    /// Approve the specified CertificateSigningRequest, like `kubectl certificate approve`
+    ///
+    /// The `reason` is a machine readable CamelCase reason, and the `message` a human readable explanation.
+    /// Requests that are already approved or denied are returned unchanged, since that decision is final.
+    pub async fn approve(
+        &self,
+        name: &str,
+        reason: &str,
+        message: &str,
+    ) -> Result<CertificateSigningRequest> {
+        self.decide(name, "Approved", reason, message).await
+    }
+
+    /// Deny the specified CertificateSigningRequest, like `kubectl certificate deny`
+    ///
+    /// The `reason` is a machine readable CamelCase reason, and the `message` a human readable explanation.
+    /// Requests that are already approved or denied are returned unchanged, since that decision is final.
+    pub async fn deny(&self, name: &str, reason: &str, message: &str) -> Result<CertificateSigningRequest> {
+        self.decide(name, "Denied", reason, message).await
+    }
+
+    /// Get the PEM encoded certificate issued for the specified CertificateSigningRequest
+    ///
+    /// Returns `None` while the request is not approved, or the signer has not issued the certificate yet.
+    pub async fn certificate(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        let csr = self.get(name).await?;
+        Ok(csr
+            .status
+            .and_then(|s| s.certificate)
+            .map(|c| c.0)
+            .filter(|c| !c.is_empty()))
+    }
+
+    async fn decide(
+        &self,
+        name: &str,
+        decision: &str,
+        reason: &str,
+        message: &str,
+    ) -> Result<CertificateSigningRequest> {
+        let mut csr = self.get(name).await?;
+        let conditions = &mut csr.status.get_or_insert_with(Default::default).conditions;
+        let decided = conditions
+            .iter()
+            .flatten()
+            .any(|c| c.type_ == "Approved" || c.type_ == "Denied");
+        if decided {
+            return Ok(csr);
+        }
+        let now = Time(Utc::now());
+        conditions
+            .get_or_insert_with(Vec::new)
+            .push(CertificateSigningRequestCondition {
+                type_: decision.into(),
+                status: "True".into(),
+                reason: Some(reason.into()),
+                message: Some(message.into()),
+                last_update_time: Some(now.clone()),
+                last_transition_time: Some(now),
+            });
+        let data = serde_json::to_vec(&csr).map_err(Error::SerdeError)?;
+        let mut req = self
+            .request
+            .replace_subresource("approval", name, &PostParams::default(), data)
+            .map_err(Error::BuildRequest)?;
+        self.tag(&mut req, "approval");
+        self.client.request::<CertificateSigningRequest>(req).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use http::{Method, StatusCode};
+    use k8s_openapi::{
+        api::certificates::v1::CertificateSigningRequestStatus, apimachinery::pkg::apis::meta::v1::ObjectMeta,
+        ByteString,
+    };
+
+    use crate::Client;
+
+    const PATH: &str = "/apis/certificates.k8s.io/v1/certificatesigningrequests/node-csr";
+
+    fn csr(status: Option<CertificateSigningRequestStatus>) -> CertificateSigningRequest {
+        CertificateSigningRequest {
+            metadata: ObjectMeta {
+                name: Some("node-csr".into()),
+                ..ObjectMeta::default()
+            },
+            status,
+            ..CertificateSigningRequest::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn approves_and_fetches_certificates() {
+        let (client, mock) = Client::mock();
+        let csrs: Api<CertificateSigningRequest> = Api::all(client);
+        mock.expect(Method::GET, PATH).respond_json(StatusCode::OK, &csr(None));
+        mock.expect(Method::PUT, format!("{PATH}/approval"))
+            .respond_json(StatusCode::OK, &csr(None));
+        csrs.approve("node-csr", "AutoApproved", "node identity verified")
+            .await
+            .unwrap();
+
+        let sent: CertificateSigningRequest = mock.requests()[1].json().unwrap();
+        let condition = &sent.status.unwrap().conditions.unwrap()[0];
+        assert_eq!(condition.type_, "Approved");
+        assert_eq!(condition.status, "True");
+        assert_eq!(condition.reason.as_deref(), Some("AutoApproved"));
+
+        let issued = CertificateSigningRequestStatus {
+            certificate: Some(ByteString(b"-----BEGIN CERTIFICATE-----".to_vec())),
+            ..CertificateSigningRequestStatus::default()
+        };
+        mock.expect(Method::GET, PATH)
+            .respond_json(StatusCode::OK, &csr(Some(issued)));
+        let cert = csrs.certificate("node-csr").await.unwrap();
+        assert_eq!(cert.as_deref(), Some(&b"-----BEGIN CERTIFICATE-----"[..]));
+        assert!(mock.is_drained());
+    }
 }
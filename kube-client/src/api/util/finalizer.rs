@@ -0,0 +1,144 @@
+use std::fmt::Debug;
+
+use serde::de::DeserializeOwned;
+use serde_json::json;
+
+use crate::{
+    api::{Api, Patch, PatchParams, Resource, ResourceExt},
+    error::ErrorResponse,
+    Error, Result,
+};
+
+/// How often a finalizer change is attempted when the object keeps changing underneath it
+const CONFLICT_ATTEMPTS: usize = 5;
+
+#[cfg_attr(docsrs, doc(cfg(feature = "jsonpatch")))]
+impl<K> Api<K>
+where
+    K: Resource + Clone + DeserializeOwned + Debug,
+{
+    /// Add a finalizer to an object, unless it already has it
+    ///
+    /// The finalizers are changed with a JSON patch that is conditional on the `resourceVersion` of
+    /// the object, so finalizers added or removed by others at the same time are never lost. On a
+    /// conflict the object is fetched again and the change is retried a few times.
+    ///
+    /// This is for code that manages finalizers by hand. Controllers should prefer
+    /// `kube::runtime::finalizer`, which also runs the cleanup before removing the finalizer.
+    ///
+    /// ```no_run
+    /// use kube::api::Api;
+    /// use k8s_openapi::api::core::v1::ConfigMap;
+    /// # async fn wrapper(client: kube::Client) -> Result<(), kube::Error> {
+    /// let cms: Api<ConfigMap> = Api::default_namespaced(client);
+    /// cms.ensure_finalizer("settings", "example.com/cleanup").await?;
+    /// // .. release what the finalizer protects ..
+    /// cms.remove_finalizer("settings", "example.com/cleanup").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Returns the object after the change, or as fetched if it already had the finalizer.
+    pub async fn ensure_finalizer(&self, name: &str, finalizer: &str) -> Result<K> {
+        self.update_finalizers(name, |finalizers| {
+            if finalizers.iter().any(|f| f == finalizer) {
+                return false;
+            }
+            finalizers.push(finalizer.to_string());
+            true
+        })
+        .await
+    }
+
+    /// Remove a finalizer from an object, if it has it
+    ///
+    /// Like [`Api::ensure_finalizer`], this is conditional on the `resourceVersion` and retried on
+    /// conflicts. Once the last finalizer of an object that is being deleted is removed, the object
+    /// goes away, so the returned object may already be gone from the cluster.
+    pub async fn remove_finalizer(&self, name: &str, finalizer: &str) -> Result<K> {
+        self.update_finalizers(name, |finalizers| {
+            let before = finalizers.len();
+            finalizers.retain(|f| f != finalizer);
+            finalizers.len() != before
+        })
+        .await
+    }
+
+    /// Apply `change` to the finalizers, `change` returns whether it changed anything
+    async fn update_finalizers(&self, name: &str, change: impl Fn(&mut Vec<String>) -> bool) -> Result<K> {
+        let mut attempt = 1;
+        loop {
+            let obj = self.get(name).await?;
+            let mut finalizers = obj.finalizers().to_vec();
+            if !change(&mut finalizers) {
+                return Ok(obj);
+            }
+            // setting the resourceVersion makes the apiserver reject the patch if the object has changed
+            let patch = serde_json::from_value(json!([
+                { "op": "add", "path": "/metadata/resourceVersion", "value": obj.resource_version() },
+                { "op": "add", "path": "/metadata/finalizers", "value": finalizers },
+            ]))
+            .map_err(Error::SerdeError)?;
+            match self
+                .patch(name, &PatchParams::default(), &Patch::<()>::Json(patch))
+                .await
+            {
+                Err(Error::Api(ErrorResponse { code: 409, .. })) if attempt < CONFLICT_ATTEMPTS => {
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Api, Client};
+    use http::{Method, StatusCode};
+    use k8s_openapi::api::core::v1::ConfigMap;
+
+    fn config_map(version: &str, finalizers: &[&str]) -> serde_json::Value {
+        serde_json::json!({
+            "apiVersion": "v1",
+            "kind": "ConfigMap",
+            "metadata": { "name": "settings", "resourceVersion": version, "finalizers": finalizers },
+        })
+    }
+
+    #[tokio::test]
+    async fn finalizers_are_changed_conditionally() {
+        let (client, mock) = Client::mock();
+        let path = "/api/v1/namespaces/default/configmaps/settings";
+        mock.expect(Method::GET, path).respond_json(StatusCode::OK, &config_map("1", &["other"]));
+        mock.expect(Method::PATCH, path).respond_error(StatusCode::CONFLICT, "Conflict", "modified");
+        mock.expect(Method::GET, path).respond_json(StatusCode::OK, &config_map("2", &[]));
+        mock.expect(Method::PATCH, path).respond_json(StatusCode::OK, &config_map("3", &["test"]));
+        mock.expect(Method::GET, path).respond_json(StatusCode::OK, &config_map("3", &["test"]));
+
+        let api: Api<ConfigMap> = Api::default_namespaced(client);
+        api.ensure_finalizer("settings", "test").await.unwrap();
+        // already present, so only fetched
+        api.ensure_finalizer("settings", "test").await.unwrap();
+        assert!(mock.is_drained());
+
+        let patches: Vec<serde_json::Value> = mock
+            .requests()
+            .iter()
+            .filter(|req| req.method == Method::PATCH)
+            .map(|req| req.json().unwrap())
+            .collect();
+        assert_eq!(patches.len(), 2);
+        assert_eq!(patches[0][0]["value"], "1");
+        assert_eq!(patches[0][1]["value"], serde_json::json!(["other", "test"]));
+        assert_eq!(patches[1][0]["value"], "2");
+        assert_eq!(patches[1][1]["value"], serde_json::json!(["test"]));
+
+        mock.expect(Method::GET, path).respond_json(StatusCode::OK, &config_map("3", &["test"]));
+        mock.expect(Method::PATCH, path).respond_json(StatusCode::OK, &config_map("4", &[]));
+        let removed = api.remove_finalizer("settings", "test").await.unwrap();
+        assert!(removed.metadata.finalizers.unwrap().is_empty());
+        let patch: serde_json::Value = mock.requests().last().unwrap().json().unwrap();
+        assert_eq!(patch[1]["value"], serde_json::json!([]));
+    }
+}
@@ -10,6 +10,9 @@ use kube_core::{params::PostParams, util::Restart};
 use serde::de::DeserializeOwned;
 
 mod csr;
+#[cfg(feature = "jsonpatch")] mod finalizer;
+mod namespace;
+pub use namespace::{NamespaceDeletionReport, RemainingObject};
 
 impl<K> Api<K>
 where
@@ -18,7 +21,17 @@ where
     /// Trigger a restart of a Resource.
     pub async fn restart(&self, name: &str) -> Result<K> {
         let mut req = self.request.restart(name).map_err(Error::BuildRequest)?;
-        req.extensions_mut().insert("restart");
+        self.tag(&mut req, "restart");
+        self.client.request::<K>(req).await
+    }
+
+    /// Trigger a restart of a Resource like `kubectl rollout restart`.
+    ///
+    /// To wait for the restarted pods to become available, see
+    /// [`conditions::is_rolled_out`](https://docs.rs/kube_runtime/*/kube_runtime/wait/conditions/fn.is_rolled_out.html).
+    pub async fn rollout_restart(&self, name: &str) -> Result<K> {
+        let mut req = self.request.rollout_restart(name).map_err(Error::BuildRequest)?;
+        self.tag(&mut req, "rollout_restart");
         self.client.request::<K>(req).await
     }
 }
@@ -27,14 +40,14 @@ impl Api<Node> {
     /// Cordon a Node.
     pub async fn cordon(&self, name: &str) -> Result<Node> {
         let mut req = self.request.cordon(name).map_err(Error::BuildRequest)?;
-        req.extensions_mut().insert("cordon");
+        self.tag(&mut req, "cordon");
         self.client.request::<Node>(req).await
     }
 
     /// Uncordon a Node.
     pub async fn uncordon(&self, name: &str) -> Result<Node> {
         let mut req = self.request.uncordon(name).map_err(Error::BuildRequest)?;
-        req.extensions_mut().insert("cordon");
+        self.tag(&mut req, "cordon");
         self.client.request::<Node>(req).await
     }
 }
@@ -52,9 +65,79 @@ impl Api<ServiceAccount> {
             .request
             .create_subresource("token", name, pp, bytes)
             .map_err(Error::BuildRequest)?;
-        req.extensions_mut().insert("create_token_request");
+        self.tag(&mut req, "create_token_request");
         self.client.request::<TokenRequest>(req).await
     }
+
+    /// Mint a short-lived token for a ServiceAccount through its `token` subresource
+    ///
+    /// The audiences, lifetime and bound object of the token are set in the `spec` of
+    /// `token_request`; the token and its expiry are returned in the `status`.
+    ///
+    /// This is [`Api::create_token_request`] with the default [`PostParams`].
+    ///
+    /// ```no_run
+    /// use k8s_openapi::api::authentication::v1::{TokenRequest, TokenRequestSpec};
+    /// use k8s_openapi::api::core::v1::ServiceAccount;
+    /// # async fn wrapper(client: kube::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let serviceaccounts: kube::Api<ServiceAccount> = kube::Api::namespaced(client, "default");
+    /// let request = TokenRequest {
+    ///     spec: TokenRequestSpec {
+    ///         audiences: vec!["vault".into()],
+    ///         expiration_seconds: Some(600),
+    ///         ..TokenRequestSpec::default()
+    ///     },
+    ///     ..TokenRequest::default()
+    /// };
+    /// let token = serviceaccounts.create_token("builder", &request).await?.status.unwrap().token;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create_token(&self, name: &str, token_request: &TokenRequest) -> Result<TokenRequest> {
+        self.create_token_request(name, &PostParams::default(), token_request)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod mock_test {
+    use crate::{api::Api, Client};
+    use http::{Method, StatusCode};
+    use k8s_openapi::api::{
+        authentication::v1::{TokenRequest, TokenRequestSpec, TokenRequestStatus},
+        core::v1::ServiceAccount,
+    };
+
+    #[tokio::test]
+    async fn create_token_posts_to_token_subresource() {
+        let (client, mock) = Client::mock();
+        let issued = TokenRequest {
+            status: Some(TokenRequestStatus {
+                token: "t0k3n".into(),
+                ..TokenRequestStatus::default()
+            }),
+            ..TokenRequest::default()
+        };
+        mock.expect(
+            Method::POST,
+            "/api/v1/namespaces/default/serviceaccounts/builder/token",
+        )
+        .respond_json(StatusCode::CREATED, &issued);
+
+        let serviceaccounts: Api<ServiceAccount> = Api::default_namespaced(client);
+        let request = TokenRequest {
+            spec: TokenRequestSpec {
+                audiences: vec!["vault".into()],
+                expiration_seconds: Some(600),
+                ..TokenRequestSpec::default()
+            },
+            ..TokenRequest::default()
+        };
+        let token = serviceaccounts.create_token("builder", &request).await.unwrap();
+        assert_eq!(token.status.unwrap().token, "t0k3n");
+        let sent = mock.requests()[0].json::<TokenRequest>().unwrap();
+        assert_eq!(sent.spec, request.spec);
+    }
 }
 
 // Tests that require a cluster and the complete feature set
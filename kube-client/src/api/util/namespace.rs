@@ -0,0 +1,208 @@
+use crate::{
+    api::{Api, DynamicObject, ListParams, ResourceExt},
+    discovery::{verbs, ApiGroup, ApiResource, Scope},
+    error::Errors,
+    Result,
+};
+use k8s_openapi::{
+    api::core::v1::{Namespace, NamespaceCondition},
+    apimachinery::pkg::apis::meta::v1::Time,
+};
+
+/// Diagnostics for a namespace that is stuck deleting
+///
+/// Returned by [`Api::deletion_report`].
+#[derive(Debug)]
+pub struct NamespaceDeletionReport {
+    /// Name of the namespace
+    pub namespace: String,
+    /// The phase of the namespace, `Terminating` while it is being deleted
+    pub phase: Option<String>,
+    /// When deletion of the namespace was requested
+    pub deletion_timestamp: Option<Time>,
+    /// Conditions reported by the namespace controller, e.g. `NamespaceFinalizersRemaining`
+    pub conditions: Vec<NamespaceCondition>,
+    /// Finalizers on the namespace spec, normally only `kubernetes` until all content is gone
+    pub finalizers: Vec<String>,
+    /// Objects still present in the namespace
+    pub remaining: Vec<RemainingObject>,
+    /// Resources that could not be listed, e.g. due to RBAC or an unavailable aggregated API
+    ///
+    /// An unavailable `APIService` is a common cause of stuck namespaces on its own.
+    pub failures: Errors,
+}
+
+impl NamespaceDeletionReport {
+    /// Whether the namespace is being deleted
+    pub fn is_terminating(&self) -> bool {
+        self.deletion_timestamp.is_some()
+    }
+
+    /// Remaining objects that have finalizers, which are usually what blocks the deletion
+    pub fn blocking(&self) -> impl Iterator<Item = &RemainingObject> {
+        self.remaining.iter().filter(|o| !o.finalizers.is_empty())
+    }
+}
+
+/// An object left in a namespace that is being deleted
+#[derive(Debug, Clone)]
+pub struct RemainingObject {
+    /// The type of the object
+    pub resource: ApiResource,
+    /// Name of the object
+    pub name: String,
+    /// Finalizers that have to be removed before the object can go away
+    pub finalizers: Vec<String>,
+    /// When deletion of the object was requested, if it was
+    pub deletion_timestamp: Option<Time>,
+}
+
+impl Api<Namespace> {
+    /// Inspect a namespace and list everything left inside it
+    ///
+    /// Discovers every API group like a full [`Discovery`](crate::discovery::Discovery) and lists the
+    /// metadata of every namespaced resource type in the namespace, which is the usual way to find out
+    /// why a namespace hangs in `Terminating`.
+    ///
+    /// ```no_run
+    /// use kube::api::Api;
+    /// use k8s_openapi::api::core::v1::Namespace;
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// let namespaces: Api<Namespace> = Api::all(client);
+    /// let report = namespaces.deletion_report("stuck").await?;
+    /// for obj in report.blocking() {
+    ///     println!("{}/{} waits on {:?}", obj.resource.kind, obj.name, obj.finalizers);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Errors from discovering individual groups, e.g. of an unavailable aggregated API, and from listing
+    /// individual resources are collected in [`NamespaceDeletionReport::failures`] rather than failing
+    /// the whole report.
+    pub async fn deletion_report(&self, name: &str) -> Result<NamespaceDeletionReport> {
+        let ns = self.get(name).await?;
+        let status = ns.status.unwrap_or_default();
+        let mut report = NamespaceDeletionReport {
+            namespace: name.to_string(),
+            phase: status.phase,
+            deletion_timestamp: ns.metadata.deletion_timestamp,
+            conditions: status.conditions.unwrap_or_default(),
+            finalizers: ns.spec.and_then(|s| s.finalizers).unwrap_or_default(),
+            remaining: vec![],
+            failures: Errors::new(),
+        };
+        for group in self.discover_groups(&mut report.failures).await? {
+            for (ar, caps) in group.recommended_resources() {
+                if caps.scope != Scope::Namespaced || !caps.supports_operation(verbs::LIST) {
+                    continue;
+                }
+                let api: Api<DynamicObject> = Api::namespaced_with(self.client.clone(), name, &ar);
+                let list = match api.list_metadata(&ListParams::default()).await {
+                    Ok(list) => list,
+                    Err(err) => {
+                        report.failures.push(format!("{}/{}", ar.api_version, ar.plural), err);
+                        continue;
+                    }
+                };
+                for obj in list {
+                    report.remaining.push(RemainingObject {
+                        resource: ar.clone(),
+                        name: obj.name_any(),
+                        finalizers: obj.finalizers().to_vec(),
+                        deletion_timestamp: obj.metadata.deletion_timestamp.clone(),
+                    });
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    // Query each group on its own, so that groups of unavailable APIs end up in `failures`
+    async fn discover_groups(&self, failures: &mut Errors) -> Result<Vec<ApiGroup>> {
+        let mut groups = vec![];
+        for g in self.client.list_api_groups().await?.groups {
+            let context = format!("discovery of {}", g.name);
+            groups.extend(failures.record(context, ApiGroup::query_apis(&self.client, g).await));
+        }
+        let core = match self.client.list_core_api_versions().await {
+            Ok(coreapis) => ApiGroup::query_core(&self.client, coreapis).await,
+            Err(err) => Err(err),
+        };
+        groups.extend(failures.record("discovery of the core group", core));
+        Ok(groups)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Api, Client};
+    use http::{Method, StatusCode};
+    use k8s_openapi::api::core::v1::Namespace;
+
+    #[tokio::test]
+    async fn unavailable_groups_are_reported_as_failures() {
+        let (client, mock) = Client::mock();
+        let namespace = serde_json::json!({
+            "apiVersion": "v1",
+            "kind": "Namespace",
+            "metadata": { "name": "stuck", "deletionTimestamp": "2024-05-01T10:00:00Z" },
+            "spec": { "finalizers": ["kubernetes"] },
+            "status": { "phase": "Terminating" },
+        });
+        let metrics = serde_json::json!({ "groupVersion": "metrics.k8s.io/v1beta1", "version": "v1beta1" });
+        let groups = serde_json::json!({
+            "kind": "APIGroupList",
+            "apiVersion": "v1",
+            "groups": [{ "name": "metrics.k8s.io", "versions": [metrics], "preferredVersion": metrics }],
+        });
+        let core_versions = serde_json::json!({
+            "kind": "APIVersions",
+            "versions": ["v1"],
+            "serverAddressByClientCIDRs": [],
+        });
+        let core_resources = serde_json::json!({
+            "kind": "APIResourceList",
+            "groupVersion": "v1",
+            "resources": [{
+                "name": "configmaps",
+                "singularName": "configmap",
+                "namespaced": true,
+                "kind": "ConfigMap",
+                "verbs": ["get", "list", "delete"],
+            }],
+        });
+        let config_maps = serde_json::json!({
+            "apiVersion": "meta.k8s.io/v1",
+            "kind": "PartialObjectMetadataList",
+            "metadata": {},
+            "items": [{
+                "apiVersion": "meta.k8s.io/v1",
+                "kind": "PartialObjectMetadata",
+                "metadata": { "name": "held", "finalizers": ["example.com/hold"] },
+            }],
+        });
+        mock.expect(Method::GET, "/api/v1/namespaces/stuck")
+            .respond_json(StatusCode::OK, &namespace);
+        mock.expect(Method::GET, "/apis").respond_json(StatusCode::OK, &groups);
+        mock.expect(Method::GET, "/apis/metrics.k8s.io/v1beta1").respond_error(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "ServiceUnavailable",
+            "the server is currently unable to handle the request",
+        );
+        mock.expect(Method::GET, "/api").respond_json(StatusCode::OK, &core_versions);
+        mock.expect(Method::GET, "/api/v1").respond_json(StatusCode::OK, &core_resources);
+        mock.expect(Method::GET, "/api/v1/namespaces/stuck/configmaps")
+            .respond_json(StatusCode::OK, &config_maps);
+
+        let namespaces: Api<Namespace> = Api::all(client);
+        let report = namespaces.deletion_report("stuck").await.unwrap();
+        assert!(mock.is_drained());
+        assert!(report.is_terminating());
+        let failures = report.failures.iter().map(|f| f.context.as_str()).collect::<Vec<_>>();
+        assert_eq!(failures, ["discovery of metrics.k8s.io"]);
+        let blocking = report.blocking().map(|o| o.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(blocking, ["held"]);
+    }
+}
@@ -0,0 +1,147 @@
+//! Pre-flight authorization checks through `SelfSubjectAccessReview`s
+use http::{header::CONTENT_TYPE, Request};
+use k8s_openapi::api::authorization::v1::{
+    ResourceAttributes, SelfSubjectAccessReview, SelfSubjectAccessReviewSpec,
+};
+
+use crate::{Client, Error, Result};
+
+/// Whether the authenticated user may perform an action, from [`Client::can_i`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Access {
+    /// The action is allowed
+    Allowed {
+        /// Why the action is allowed, if the authorizer said
+        reason: Option<String>,
+    },
+    /// The action is denied, either explicitly or because no authorizer allowed it
+    Denied {
+        /// Why the action is denied, if the authorizer said
+        reason: Option<String>,
+        /// An error that occurred while evaluating the authorization rules
+        ///
+        /// The action may still be allowed, e.g. when a webhook authorizer was unreachable.
+        evaluation_error: Option<String>,
+    },
+}
+
+impl Access {
+    /// Whether the action is allowed
+    pub fn is_allowed(&self) -> bool {
+        matches!(self, Self::Allowed { .. })
+    }
+}
+
+impl From<SelfSubjectAccessReview> for Access {
+    fn from(review: SelfSubjectAccessReview) -> Self {
+        let status = review.status.unwrap_or_default();
+        let reason = status.reason.filter(|r| !r.is_empty());
+        if status.allowed {
+            Access::Allowed { reason }
+        } else {
+            Access::Denied {
+                reason,
+                evaluation_error: status.evaluation_error.filter(|e| !e.is_empty()),
+            }
+        }
+    }
+}
+
+/// Authorization checks
+impl Client {
+    /// Check whether the authenticated user may perform `verb` on `resource`, like `kubectl auth can-i`
+    ///
+    /// The `resource` uses `kubectl` notation: the plural name, followed by the group for resources
+    /// outside of the core group, and optionally a subresource, e.g. `pods`, `deployments.apps` or
+    /// `pods/log`. A `namespace` of `None` checks the action across all namespaces, or on a cluster
+    /// level resource.
+    ///
+    /// ```no_run
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// if !client.can_i("delete", "pods", Some("default")).await?.is_allowed() {
+    ///     println!("not allowed to delete pods, only reporting");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn can_i(&self, verb: &str, resource: &str, namespace: Option<&str>) -> Result<Access> {
+        let (resource, subresource) = match resource.split_once('/') {
+            Some((resource, subresource)) => (resource, Some(subresource.to_string())),
+            None => (resource, None),
+        };
+        let (resource, group) = resource.split_once('.').unwrap_or((resource, ""));
+        self.can_i_with(ResourceAttributes {
+            verb: Some(verb.into()),
+            resource: Some(resource.into()),
+            group: Some(group.into()),
+            subresource,
+            namespace: namespace.map(Into::into),
+            ..ResourceAttributes::default()
+        })
+        .await
+    }
+
+    /// Check whether the authenticated user may perform the action described by `attributes`
+    ///
+    /// Use this to check access to a specific object by `name`, or a specific API `version`.
+    pub async fn can_i_with(&self, attributes: ResourceAttributes) -> Result<Access> {
+        let review = SelfSubjectAccessReview {
+            spec: SelfSubjectAccessReviewSpec {
+                resource_attributes: Some(attributes),
+                ..SelfSubjectAccessReviewSpec::default()
+            },
+            ..SelfSubjectAccessReview::default()
+        };
+        let req = Request::post("/apis/authorization.k8s.io/v1/selfsubjectaccessreviews")
+            .header(CONTENT_TYPE, "application/json")
+            .body(serde_json::to_vec(&review).map_err(Error::SerdeError)?)
+            .map_err(Error::HttpError)?;
+        self.request::<SelfSubjectAccessReview>(req).await.map(Access::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use http::{Method, StatusCode};
+    use k8s_openapi::api::authorization::v1::SubjectAccessReviewStatus;
+
+    #[tokio::test]
+    async fn can_i_posts_access_reviews() {
+        let (client, mock) = Client::mock();
+        let path = "/apis/authorization.k8s.io/v1/selfsubjectaccessreviews";
+        for allowed in [true, false] {
+            let review = SelfSubjectAccessReview {
+                status: Some(SubjectAccessReviewStatus {
+                    allowed,
+                    reason: Some("RBAC".into()),
+                    ..SubjectAccessReviewStatus::default()
+                }),
+                ..SelfSubjectAccessReview::default()
+            };
+            mock.expect(Method::POST, path).respond_json(StatusCode::CREATED, &review);
+        }
+
+        let access = client.can_i("get", "pods/log", Some("default")).await.unwrap();
+        assert_eq!(access, Access::Allowed {
+            reason: Some("RBAC".into())
+        });
+        let access = client.can_i("patch", "deployments.apps", None).await.unwrap();
+        assert!(!access.is_allowed());
+
+        let sent = mock
+            .requests()
+            .iter()
+            .map(|r| r.json::<SelfSubjectAccessReview>().unwrap().spec.resource_attributes.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(sent[0].resource.as_deref(), Some("pods"));
+        assert_eq!(sent[0].subresource.as_deref(), Some("log"));
+        assert_eq!(sent[0].group.as_deref(), Some(""));
+        assert_eq!(sent[0].namespace.as_deref(), Some("default"));
+        assert_eq!(sent[1].resource.as_deref(), Some("deployments"));
+        assert_eq!(sent[1].group.as_deref(), Some("apps"));
+        assert_eq!(sent[1].namespace, None);
+    }
+}
@@ -0,0 +1,234 @@
+//! Credentials of IAM roles for service accounts, from STS `AssumeRoleWithWebIdentity`
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use http::{header::CONTENT_TYPE, Request};
+use http_body_util::BodyExt;
+use hyper_util::{
+    client::legacy::{connect::HttpConnector, Client},
+    rt::TokioExecutor,
+};
+use secrecy::SecretString;
+use tower::BoxError;
+
+use super::{cluster, encode, sts_host, var, Credentials, Eks};
+use crate::{
+    client::auth::{Error, SIXTY_SEC},
+    config::ExecConfig,
+};
+
+// Current TLS feature precedence when more than one are set:
+// 1. rustls-tls
+// 2. openssl-tls
+#[cfg(feature = "rustls-tls")]
+type HttpsConnector = hyper_rustls::HttpsConnector<HttpConnector>;
+#[cfg(all(not(feature = "rustls-tls"), feature = "openssl-tls"))]
+type HttpsConnector = hyper_openssl::HttpsConnector<HttpConnector>;
+
+/// Generates the tokens of the EKS exec plugins from the role of a web identity token
+///
+/// This is how IAM roles for service accounts authenticate pods, with a projected service account
+/// token that the kubelet rotates. The role is assumed again whenever a token expires, so that the
+/// current web identity token is used.
+#[derive(Debug)]
+pub struct WebIdentity {
+    cluster_id: String,
+    region: String,
+    role_arn: String,
+    session_name: String,
+    token_file: PathBuf,
+    https_client: Client<HttpsConnector, String>,
+    token: Option<(SecretString, DateTime<Utc>)>,
+}
+
+impl WebIdentity {
+    // Returns `None` unless the exec plugin is one of the EKS plugins and a web identity token is
+    // configured for a role.
+    pub(crate) fn from_exec(exec: &ExecConfig) -> Option<Result<Self, Error>> {
+        let (cluster_id, region) = cluster(exec)?;
+        let role_arn = var(exec, "AWS_ROLE_ARN")?;
+        let token_file = var(exec, "AWS_WEB_IDENTITY_TOKEN_FILE")?.into();
+        // Like the AWS SDKs, sessions without a configured name are named after the time they started
+        let session_name = var(exec, "AWS_ROLE_SESSION_NAME")
+            .unwrap_or_else(|| format!("kube-rs-{}", Utc::now().timestamp_micros()));
+        Some(https_client().map(|https_client| Self {
+            cluster_id,
+            region,
+            role_arn,
+            session_name,
+            token_file,
+            https_client,
+            token: None,
+        }))
+    }
+
+    pub(crate) async fn token(&mut self) -> Result<SecretString, Error> {
+        let now = Utc::now();
+        if let Some((token, expiry)) = &self.token {
+            if now + SIXTY_SEC < *expiry {
+                return Ok(token.clone());
+            }
+        }
+        let (credentials, expiration) = self.assume_role().await?;
+        let eks = Eks {
+            cluster_id: self.cluster_id.clone(),
+            region: self.region.clone(),
+            credentials,
+        };
+        let (token, expiry) = eks.token_at(now);
+        self.token = Some((token.clone(), expiry.min(expiration)));
+        Ok(token)
+    }
+
+    async fn assume_role(&self) -> Result<(Credentials, DateTime<Utc>), Error> {
+        let web_identity_token = std::fs::read_to_string(&self.token_file)
+            .map_err(|err| Error::ReadTokenFile(err, self.token_file.clone()))?;
+        let body = [
+            ("Action", "AssumeRoleWithWebIdentity"),
+            ("Version", "2011-06-15"),
+            ("RoleArn", self.role_arn.as_str()),
+            ("RoleSessionName", self.session_name.as_str()),
+            ("WebIdentityToken", web_identity_token.trim()),
+        ]
+        .iter()
+        .map(|(key, value)| format!("{}={}", encode(key), encode(value)))
+        .collect::<Vec<_>>()
+        .join("&");
+        // The request is authenticated by the web identity token, so it is not signed
+        let request = Request::post(format!("https://{}/", sts_host(&self.region)))
+            .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body(body)
+            .map_err(assume_role_error)?;
+
+        let response = self
+            .https_client
+            .request(request)
+            .await
+            .map_err(assume_role_error)?;
+        let status = response.status();
+        let body = response.into_body().collect().await.map_err(assume_role_error)?;
+        let body = String::from_utf8_lossy(&body.to_bytes()).into_owned();
+        if !status.is_success() {
+            let message = element(&body, "Message").unwrap_or(&body);
+            return Err(assume_role_error(format!("{status}: {message}")));
+        }
+        assumed_credentials(&body).ok_or_else(|| assume_role_error("response has no valid credentials"))
+    }
+}
+
+fn assume_role_error(err: impl Into<BoxError>) -> Error {
+    Error::AssumeRoleWithWebIdentity(err.into())
+}
+
+fn https_client() -> Result<Client<HttpsConnector, String>, Error> {
+    #[cfg(all(feature = "rustls-tls", feature = "aws-lc-rs"))]
+    {
+        if rustls::crypto::CryptoProvider::get_default().is_none() {
+            // the only error here is if it's been initialized in between: we can ignore it
+            // since our semantic is only to set the default value if it does not exist.
+            let _ = rustls::crypto::aws_lc_rs::default_provider().install_default();
+        }
+    }
+
+    #[cfg(all(feature = "rustls-tls", not(feature = "webpki-roots")))]
+    let https = hyper_rustls::HttpsConnectorBuilder::new()
+        .with_native_roots()
+        .map_err(Error::NoValidNativeRootCA)?
+        .https_only()
+        .enable_http1()
+        .build();
+    #[cfg(all(feature = "rustls-tls", feature = "webpki-roots"))]
+    let https = hyper_rustls::HttpsConnectorBuilder::new()
+        .with_webpki_roots()
+        .https_only()
+        .enable_http1()
+        .build();
+    #[cfg(all(not(feature = "rustls-tls"), feature = "openssl-tls"))]
+    let https = hyper_openssl::HttpsConnector::new().map_err(assume_role_error)?;
+
+    Ok(Client::builder(TokioExecutor::new()).build(https))
+}
+
+// The credentials of an `AssumeRoleWithWebIdentity` response and when they expire
+fn assumed_credentials(xml: &str) -> Option<(Credentials, DateTime<Utc>)> {
+    let credentials = Credentials {
+        access_key_id: element(xml, "AccessKeyId")?.to_owned(),
+        secret_access_key: element(xml, "SecretAccessKey")?.to_owned().into(),
+        session_token: Some(element(xml, "SessionToken")?.to_owned().into()),
+    };
+    Some((credentials, element(xml, "Expiration")?.parse().ok()?))
+}
+
+// The text of the first `name` element, which is enough for the flat elements of STS responses
+fn element<'a>(xml: &'a str, name: &str) -> Option<&'a str> {
+    let start = xml.find(&format!("<{name}>"))? + name.len() + 2;
+    let len = xml[start..].find(&format!("</{name}>"))?;
+    Some(&xml[start..start + len])
+}
+
+#[cfg(test)]
+mod tests {
+    use secrecy::ExposeSecret;
+
+    use super::*;
+    use crate::client::auth::eks::tests::exec;
+
+    #[test]
+    fn web_identities_are_recognized() {
+        let irsa = exec("aws", &["eks", "get-token", "--cluster-name", "my-cluster"], &[
+            ("AWS_REGION", "us-west-2"),
+            ("AWS_ROLE_ARN", "arn:aws:iam::111122223333:role/controller"),
+            (
+                "AWS_WEB_IDENTITY_TOKEN_FILE",
+                "/var/run/secrets/eks.amazonaws.com/serviceaccount/token",
+            ),
+            ("AWS_ROLE_SESSION_NAME", "controller"),
+        ]);
+        assert!(Eks::from_exec(&irsa).is_none());
+        let web_identity = WebIdentity::from_exec(&irsa).unwrap().unwrap();
+        assert_eq!(web_identity.cluster_id, "my-cluster");
+        assert_eq!(web_identity.role_arn, "arn:aws:iam::111122223333:role/controller");
+        assert_eq!(web_identity.session_name, "controller");
+
+        let pod_identity = exec("aws", &["eks", "get-token", "--cluster-name", "my-cluster"], &[
+            ("AWS_REGION", "us-west-2"),
+            (
+                "AWS_CONTAINER_CREDENTIALS_FULL_URI",
+                "http://169.254.170.23/v1/credentials",
+            ),
+        ]);
+        assert!(WebIdentity::from_exec(&pod_identity).is_none());
+    }
+
+    #[test]
+    fn credentials_are_parsed_from_the_response() {
+        let response = r#"
+        <AssumeRoleWithWebIdentityResponse xmlns="https://sts.amazonaws.com/doc/2011-06-15/">
+          <AssumeRoleWithWebIdentityResult>
+            <Credentials>
+              <SessionToken>FwoGZXIvYXdzEXAMPLE/TOKEN+=</SessionToken>
+              <SecretAccessKey>wJalrXUtnFEMI/K7MDENG/bPxRfiCYEXAMPLEKEY</SecretAccessKey>
+              <Expiration>2024-05-01T11:00:00Z</Expiration>
+              <AccessKeyId>ASIAEXAMPLE</AccessKeyId>
+            </Credentials>
+          </AssumeRoleWithWebIdentityResult>
+        </AssumeRoleWithWebIdentityResponse>"#;
+        let (credentials, expiration) = assumed_credentials(response).unwrap();
+        assert_eq!(credentials.access_key_id, "ASIAEXAMPLE");
+        assert_eq!(
+            credentials.secret_access_key.expose_secret(),
+            "wJalrXUtnFEMI/K7MDENG/bPxRfiCYEXAMPLEKEY"
+        );
+        assert_eq!(
+            credentials.session_token.unwrap().expose_secret(),
+            "FwoGZXIvYXdzEXAMPLE/TOKEN+="
+        );
+        assert_eq!(
+            expiration,
+            "2024-05-01T11:00:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+
+        let denied = "<ErrorResponse><Error><Code>AccessDenied</Code></Error></ErrorResponse>";
+        assert!(assumed_credentials(denied).is_none());
+    }
+}
@@ -1,4 +1,5 @@
 use std::{
+    io::IsTerminal,
     path::{Path, PathBuf},
     process::Command,
     sync::Arc,
@@ -17,8 +18,12 @@ use thiserror::Error;
 use tokio::sync::{Mutex, RwLock};
 use tower::{filter::AsyncPredicate, BoxError};
 
-use crate::config::{AuthInfo, AuthProviderConfig, ExecAuthCluster, ExecConfig, ExecInteractiveMode};
+use crate::config::{
+    AuthInfo, AuthProviderConfig, AuthProviderPersister, CredentialCache, ExecAuthCluster, ExecConfig,
+    ExecInteractiveMode,
+};
 
+#[cfg(feature = "eks")] mod eks;
 #[cfg(feature = "oauth")] mod oauth;
 #[cfg(feature = "oauth")] pub use oauth::Error as OAuthError;
 #[cfg(feature = "oidc")] mod oidc;
@@ -53,7 +58,10 @@ pub enum Error {
     AuthExecStart(#[source] std::io::Error),
 
     /// Failed to run auth exec command
-    #[error("auth exec command '{cmd}' failed with status {status}: {out:?}")]
+    #[error(
+        "auth exec command '{cmd}' failed with status {status}: {}",
+        plugin_output(out)
+    )]
     AuthExecRun {
         /// The failed command
         cmd: String,
@@ -67,6 +75,15 @@ pub enum Error {
     #[error("failed to parse auth exec output: {0}")]
     AuthExecParse(#[source] serde_json::Error),
 
+    /// Auth exec command returned credentials of another version than it was configured with
+    #[error("auth exec command returned API version {returned} instead of {expected}")]
+    AuthExecVersionMismatch {
+        /// The version in the kubeconfig
+        expected: String,
+        /// The version of the returned `ExecCredential`
+        returned: String,
+    },
+
     /// Fail to serialize input
     #[error("failed to serialize input: {0}")]
     AuthExecSerialize(#[source] serde_json::Error),
@@ -103,9 +120,52 @@ pub enum Error {
     #[error("Cluster spec must be populated when `provideClusterInfo` is true")]
     ExecMissingClusterInfo,
 
+    /// The exec plugin requires interactive input, but stdin is not a terminal
+    #[error("exec plugin requires interactive mode, but standard input is not a terminal")]
+    ExecNotInteractive,
+
     /// No valid native root CA certificates found
     #[error("No valid native root CA certificates found")]
     NoValidNativeRootCA(#[source] std::io::Error),
+
+    /// Failed to assume the role of a web identity token for EKS
+    #[cfg(feature = "eks")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "eks")))]
+    #[error("failed to assume role with web identity: {0}")]
+    AssumeRoleWithWebIdentity(#[source] BoxError),
+
+    /// Custom auth provider failed to produce a header
+    #[error("auth provider failed: {0}")]
+    AuthProvider(#[source] BoxError),
+}
+
+/// Provider of the `Authorization` header for auth schemes that kubeconfigs cannot express
+///
+/// A provider set with [`Config::credential_provider`](crate::Config::credential_provider) is asked for
+/// the header of every request, so it is responsible for caching and refreshing its credentials.
+///
+/// ```
+/// use futures::future::BoxFuture;
+/// use http::HeaderValue;
+/// use kube::client::AuthProvider;
+///
+/// #[derive(Debug)]
+/// struct CorporateSso;
+///
+/// impl AuthProvider for CorporateSso {
+///     fn token(&self) -> BoxFuture<'_, Result<HeaderValue, tower::BoxError>> {
+///         Box::pin(async move {
+///             let ticket = "from the corporate sso";
+///             let mut header = HeaderValue::try_from(format!("Negotiate {ticket}"))?;
+///             header.set_sensitive(true);
+///             Ok(header)
+///         })
+///     }
+/// }
+/// ```
+pub trait AuthProvider: std::fmt::Debug + Send + Sync + 'static {
+    /// The value of the `Authorization` header of the next request
+    fn token(&self) -> BoxFuture<'_, Result<HeaderValue, BoxError>>;
 }
 
 #[derive(Debug, Clone)]
@@ -118,7 +178,7 @@ pub(crate) enum Auth {
     Certificate(String, SecretString, Option<DateTime<Utc>>),
 }
 
-// Token file reference. Reloads at least once per minute.
+// Token file reference. Reloads at least once per minute, and before the token expires.
 #[derive(Debug)]
 pub struct TokenFile {
     path: PathBuf,
@@ -128,13 +188,12 @@ pub struct TokenFile {
 
 impl TokenFile {
     fn new<P: AsRef<Path>>(path: P) -> Result<TokenFile, Error> {
-        let token = std::fs::read_to_string(&path)
+        let token = read_token_file(path.as_ref())
             .map_err(|source| Error::ReadTokenFile(source, path.as_ref().to_owned()))?;
         Ok(Self {
             path: path.as_ref().to_owned(),
+            expires_at: reload_deadline(&token),
             token: SecretString::from(token),
-            // Try to reload at least once a minute
-            expires_at: Utc::now() + SIXTY_SEC,
         })
     }
 
@@ -154,15 +213,48 @@ impl TokenFile {
             // > clients that make token files available on process start and then remove them to
             // > limit credential exposure.
             // > https://github.com/kubernetes/kubernetes/issues/68164
-            if let Ok(token) = std::fs::read_to_string(&self.path) {
-                self.token = SecretString::from(token);
+            match read_token_file(&self.path) {
+                Ok(token) if !token.is_empty() => {
+                    self.expires_at = reload_deadline(&token);
+                    self.token = SecretString::from(token);
+                }
+                _ => self.expires_at = Utc::now() + SIXTY_SEC,
             }
-            self.expires_at = Utc::now() + SIXTY_SEC;
         }
         self.token.expose_secret()
     }
 }
 
+/// Read a token file, without the trailing newline that editors and `echo` leave behind
+fn read_token_file(path: &Path) -> std::io::Result<String> {
+    Ok(std::fs::read_to_string(path)?.trim().to_string())
+}
+
+/// When to reload a token read from a file
+///
+/// Bound service account tokens are rotated by the kubelet well before they expire, so reloading once
+/// a minute picks them up in time. Tokens that expire sooner than that are reloaded when they expire,
+/// and expired ones every ten seconds rather than on every request.
+fn reload_deadline(token: &str) -> DateTime<Utc> {
+    let now = Utc::now();
+    let reload_at = now + SIXTY_SEC;
+    // `is_expiring` is ten seconds early
+    let earliest = now + TEN_SEC + TEN_SEC;
+    jwt_expiry(token).map_or(reload_at, |expiry| expiry.clamp(earliest, reload_at))
+}
+
+/// The `exp` claim of a JWT, `None` for opaque tokens
+fn jwt_expiry(token: &str) -> Option<DateTime<Utc>> {
+    use base64::Engine as _;
+
+    let payload = token.split('.').nth(1)?;
+    let payload = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(payload.trim_end_matches('='))
+        .ok()?;
+    let expiry = serde_json::from_slice::<serde_json::Value>(&payload).ok()?["exp"].as_i64()?;
+    DateTime::from_timestamp(expiry, 0)
+}
+
 // Questionable decisions by chrono: https://github.com/chronotope/chrono/issues/1491
 macro_rules! const_unwrap {
     ($e:expr) => {
@@ -185,17 +277,34 @@ const SIXTY_SEC: chrono::TimeDelta = const_unwrap!(Duration::try_seconds(60));
 // - token-file refreshed at least once per minute
 // - gcp: command based token source (exec)
 // - gcp: application credential based token source (requires `oauth` feature)
+// - azure: `kubelogin get-token` (exec, requires `azure` feature)
+// - eks: `aws eks get-token` and `aws-iam-authenticator token` (exec or generated with `eks` feature,
+//   from static credentials or a web identity token with one of the TLS features)
 //
 // Note that the visibility must be `pub` for `impl Layer for AuthLayer`, but this is not exported from the crate.
 // It's not accessible from outside and not shown on docs.
 #[derive(Debug, Clone)]
 pub enum RefreshableToken {
-    Exec(Arc<Mutex<(SecretString, DateTime<Utc>, AuthInfo)>>),
+    Exec(Arc<Mutex<ExecToken>>),
     File(Arc<RwLock<TokenFile>>),
     #[cfg(feature = "oauth")]
     GcpOauth(Arc<Mutex<oauth::Gcp>>),
     #[cfg(feature = "oidc")]
     Oidc(Arc<Mutex<oidc::Oidc>>),
+    #[cfg(all(feature = "eks", any(feature = "rustls-tls", feature = "openssl-tls")))]
+    Eks(Arc<Mutex<eks::WebIdentity>>),
+    Provider(Arc<dyn AuthProvider>),
+}
+
+/// A token of an exec plugin or auth provider, which is refreshed by running it again
+#[derive(Debug)]
+pub struct ExecToken {
+    pub(crate) token: SecretString,
+    pub(crate) expiry: DateTime<Utc>,
+    /// The `AuthInfo` to refresh the token with
+    pub(crate) auth_info: AuthInfo,
+    /// Where to cache the refreshed token
+    pub(crate) cache: Option<CredentialCache>,
 }
 
 // For use with `AsyncFilterLayer` to add `Authorization` header with a refreshed token.
@@ -224,20 +333,20 @@ impl RefreshableToken {
                 let mut locked_data = data.lock().await;
                 // Add some wiggle room onto the current timestamp so we don't get any race
                 // conditions where the token expires while we are refreshing
-                if Utc::now() + SIXTY_SEC >= locked_data.1 {
+                if Utc::now() + SIXTY_SEC >= locked_data.expiry {
                     // TODO Improve refreshing exec to avoid `Auth::try_from`
-                    match Auth::try_from(&locked_data.2)? {
+                    match Auth::try_from_cached(&locked_data.auth_info, locked_data.cache.as_ref())? {
                         Auth::None | Auth::Basic(_, _) | Auth::Bearer(_) | Auth::Certificate(_, _, _) => {
                             return Err(Error::UnrefreshableTokenResponse);
                         }
 
                         Auth::RefreshableToken(RefreshableToken::Exec(d)) => {
-                            let (new_token, new_expire, new_info) = Arc::try_unwrap(d)
+                            let refreshed = Arc::try_unwrap(d)
                                 .expect("Unable to unwrap Arc, this is likely a programming error")
                                 .into_inner();
-                            locked_data.0 = new_token;
-                            locked_data.1 = new_expire;
-                            locked_data.2 = new_info;
+                            locked_data.token = refreshed.token;
+                            locked_data.expiry = refreshed.expiry;
+                            locked_data.auth_info = refreshed.auth_info;
                         }
 
                         // Unreachable because the token source does not change
@@ -246,10 +355,13 @@ impl RefreshableToken {
                         Auth::RefreshableToken(RefreshableToken::GcpOauth(_)) => unreachable!(),
                         #[cfg(feature = "oidc")]
                         Auth::RefreshableToken(RefreshableToken::Oidc(_)) => unreachable!(),
+                        #[cfg(all(feature = "eks", any(feature = "rustls-tls", feature = "openssl-tls")))]
+                        Auth::RefreshableToken(RefreshableToken::Eks(_)) => unreachable!(),
+                        Auth::RefreshableToken(RefreshableToken::Provider(_)) => unreachable!(),
                     }
                 }
 
-                bearer_header(locked_data.0.expose_secret())
+                bearer_header(locked_data.token.expose_secret())
             }
 
             RefreshableToken::File(token_file) => {
@@ -276,6 +388,14 @@ impl RefreshableToken {
                 let token = oidc.lock().await.id_token().await.map_err(Error::Oidc)?;
                 bearer_header(&token)
             }
+
+            #[cfg(all(feature = "eks", any(feature = "rustls-tls", feature = "openssl-tls")))]
+            RefreshableToken::Eks(web_identity) => {
+                let token = web_identity.lock().await.token().await?;
+                bearer_header(token.expose_secret())
+            }
+
+            RefreshableToken::Provider(provider) => provider.token().await.map_err(Error::AuthProvider),
         }
     }
 }
@@ -286,6 +406,22 @@ fn bearer_header(token: &str) -> Result<HeaderValue, Error> {
     Ok(value)
 }
 
+impl Auth {
+    /// Write tokens refreshed by the auth provider back to a kubeconfig file
+    ///
+    /// Only the `oidc` auth provider refreshes tokens that are persisted.
+    pub(crate) fn persist_to(&mut self, persister: &AuthProviderPersister) {
+        #[cfg(feature = "oidc")]
+        if let Self::RefreshableToken(RefreshableToken::Oidc(oidc)) = self {
+            if let Some(oidc) = Arc::get_mut(oidc) {
+                oidc.get_mut().persister = Some(persister.clone());
+            }
+        }
+        #[cfg(not(feature = "oidc"))]
+        let _ = persister;
+    }
+}
+
 impl TryFrom<&AuthInfo> for Auth {
     type Error = Error;
 
@@ -293,6 +429,16 @@ impl TryFrom<&AuthInfo> for Auth {
     /// exec plugins as well as specified in
     /// https://kubernetes.io/docs/reference/access-authn-authz/authentication/#client-go-credential-plugins
     fn try_from(auth_info: &AuthInfo) -> Result<Self, Self::Error> {
+        Self::try_from_cached(auth_info, None)
+    }
+}
+
+impl Auth {
+    /// Like `Auth::try_from`, but exec plugins only run when `cache` has no valid token of theirs
+    pub(crate) fn try_from_cached(
+        auth_info: &AuthInfo,
+        cache: Option<&CredentialCache>,
+    ) -> Result<Self, Error> {
         if let Some(provider) = &auth_info.auth_provider {
             match token_from_provider(provider)? {
                 #[cfg(feature = "oidc")]
@@ -313,15 +459,26 @@ impl TryFrom<&AuthInfo> for Auth {
                     provider.config.insert("access-token".into(), token.clone());
                     provider.config.insert("expiry".into(), expiry.to_rfc3339());
                     info.auth_provider = Some(provider);
-                    return Ok(Self::RefreshableToken(RefreshableToken::Exec(Arc::new(
-                        Mutex::new((SecretString::from(token), expiry, info)),
-                    ))));
+                    return Ok(Self::exec_token(SecretString::from(token), expiry, info, cache));
                 }
 
                 ProviderToken::GcpCommand(token, None) => {
                     return Ok(Self::Bearer(SecretString::from(token)));
                 }
 
+                #[cfg(feature = "azure")]
+                ProviderToken::Azure(cached, exec) => {
+                    let info = AuthInfo {
+                        auth_provider: None,
+                        exec: Some(exec),
+                        ..auth_info.clone()
+                    };
+                    if let Some((token, expiry)) = cached {
+                        return Ok(Self::exec_token(SecretString::from(token), expiry, info, cache));
+                    }
+                    return Self::try_from_cached(&info, cache);
+                }
+
                 #[cfg(feature = "oauth")]
                 ProviderToken::GcpOauth(gcp) => {
                     return Ok(Self::RefreshableToken(RefreshableToken::GcpOauth(Arc::new(
@@ -348,7 +505,19 @@ impl TryFrom<&AuthInfo> for Auth {
         }
 
         if let Some(exec) = &auth_info.exec {
-            let creds = auth_exec(exec)?;
+            #[cfg(feature = "eks")]
+            if let Some(eks) = eks::Eks::from_exec(exec) {
+                let (token, expire) = eks.token();
+                return Ok(Self::exec_token(token, expire, auth_info.clone(), None));
+            }
+            #[cfg(all(feature = "eks", any(feature = "rustls-tls", feature = "openssl-tls")))]
+            if let Some(web_identity) = eks::WebIdentity::from_exec(exec) {
+                return Ok(Self::RefreshableToken(RefreshableToken::Eks(Arc::new(
+                    Mutex::new(web_identity?),
+                ))));
+            }
+
+            let creds = cached_auth_exec(exec, cache)?;
             let status = creds.status.ok_or(Error::ExecPluginFailed)?;
             let expiration = status
                 .expiration_timestamp
@@ -368,9 +537,7 @@ impl TryFrom<&AuthInfo> for Auth {
             }
 
             match (status.token.map(SecretString::from), expiration) {
-                (Some(token), Some(expire)) => Ok(Self::RefreshableToken(RefreshableToken::Exec(Arc::new(
-                    Mutex::new((token, expire, auth_info.clone())),
-                )))),
+                (Some(token), Some(expire)) => Ok(Self::exec_token(token, expire, auth_info.clone(), cache)),
                 (Some(token), None) => Ok(Self::Bearer(token)),
                 _ => Ok(Self::None),
             }
@@ -378,6 +545,21 @@ impl TryFrom<&AuthInfo> for Auth {
             Ok(Self::None)
         }
     }
+
+    fn exec_token(
+        token: SecretString,
+        expiry: DateTime<Utc>,
+        auth_info: AuthInfo,
+        cache: Option<&CredentialCache>,
+    ) -> Self {
+        let token = ExecToken {
+            token,
+            expiry,
+            auth_info,
+            cache: cache.cloned(),
+        };
+        Self::RefreshableToken(RefreshableToken::Exec(Arc::new(Mutex::new(token))))
+    }
 }
 
 // We need to differentiate providers because the keys/formats to store token expiration differs.
@@ -390,16 +572,21 @@ enum ProviderToken {
     GcpCommand(String, Option<DateTime<Utc>>),
     #[cfg(feature = "oauth")]
     GcpOauth(oauth::Gcp),
-    // "access-token", "expires-on" (timestamp)
-    // Azure(String, Option<DateTime<Utc>>),
+    // "access-token", "expires-on" (timestamp) while valid, and the equivalent kubelogin exec config
+    #[cfg(feature = "azure")]
+    Azure(Option<(String, DateTime<Utc>)>, ExecConfig),
 }
 
 fn token_from_provider(provider: &AuthProviderConfig) -> Result<ProviderToken, Error> {
     match provider.name.as_ref() {
         "oidc" => token_from_oidc_provider(provider),
         "gcp" => token_from_gcp_provider(provider),
+        #[cfg(feature = "azure")]
+        "azure" => token_from_azure_provider(provider),
+        #[cfg(not(feature = "azure"))]
         "azure" => Err(Error::AuthExec(
-            "The azure auth plugin is not supported; use https://github.com/Azure/kubelogin instead".into(),
+            "The azure auth plugin requires the azure feature; or use https://github.com/Azure/kubelogin"
+                .into(),
         )),
         _ => Err(Error::AuthExec(format!(
             "Authentication with provider {:} not supported",
@@ -448,14 +635,14 @@ fn token_from_gcp_provider(provider: &AuthProviderConfig) -> Result<ProviderToke
         // NB: This property does currently not exist upstream in client-go
         // See https://github.com/kube-rs/kube/issues/1060
         let drop_env = provider.config.get("cmd-drop-env").cloned().unwrap_or_default();
-        // TODO splitting args by space is not safe
+        // TODO splitting args by whitespace does not handle quoted args
         let mut command = Command::new(cmd);
         // Do not pass the following env vars to the command
-        for env in drop_env.trim().split(' ') {
+        for env in drop_env.split_whitespace() {
             command.env_remove(env);
         }
         let output = command
-            .args(params.trim().split(' '))
+            .args(params.split_whitespace())
             .output()
             .map_err(|e| Error::AuthExec(format!("Executing {cmd:} failed: {e:?}")))?;
 
@@ -483,6 +670,7 @@ fn token_from_gcp_provider(provider: &AuthProviderConfig) -> Result<ProviderToke
         } else {
             let token = std::str::from_utf8(&output.stdout)
                 .map_err(|e| Error::AuthExec(format!("Result is not a string {e:?} ")))?
+                .trim()
                 .to_owned();
             return Ok(ProviderToken::GcpCommand(token, None));
         }
@@ -504,6 +692,86 @@ fn token_from_gcp_provider(provider: &AuthProviderConfig) -> Result<ProviderToke
     }
 }
 
+// The azure auth provider was removed from client-go in favour of the kubelogin exec plugin.
+// Like `kubelogin convert-kubeconfig`, tokens are requested from kubelogin with the same settings,
+// so it must be installed once the cached access token expires.
+//
+// The login mode is the one kubelogin is configured with in `AAD_LOGIN_METHOD`, and `devicecode`
+// like the auth provider by default.
+#[cfg(feature = "azure")]
+fn token_from_azure_provider(provider: &AuthProviderConfig) -> Result<ProviderToken, Error> {
+    let login = std::env::var("AAD_LOGIN_METHOD")
+        .ok()
+        .filter(|login| !login.is_empty())
+        .unwrap_or_else(|| "devicecode".into());
+    let exec = ExecConfig {
+        api_version: Some("client.authentication.k8s.io/v1beta1".into()),
+        command: Some("kubelogin".into()),
+        args: Some(kubelogin_args(provider, &login)?),
+        env: None,
+        drop_env: None,
+        interactive_mode: Some(ExecInteractiveMode::IfAvailable),
+        provide_cluster_info: false,
+        cluster: None,
+        cluster_name: None,
+        user_name: None,
+    };
+
+    let cached = provider.config.get("access-token").zip(
+        provider
+            .config
+            .get("expires-on")
+            .and_then(|expires_on| expires_on.parse().ok())
+            .and_then(|expires_on| DateTime::from_timestamp(expires_on, 0)),
+    );
+    let cached = cached
+        .filter(|(_, expiry)| Utc::now() + SIXTY_SEC < *expiry)
+        .map(|(token, expiry)| (token.clone(), expiry));
+    Ok(ProviderToken::Azure(cached, exec))
+}
+
+// The arguments of `kubelogin get-token` for the settings of the auth provider and the login mode
+#[cfg(feature = "azure")]
+fn kubelogin_args(provider: &AuthProviderConfig, login: &str) -> Result<Vec<String>, Error> {
+    let setting = |key: &str| {
+        provider
+            .config
+            .get(key)
+            .filter(|value| !value.is_empty())
+            .cloned()
+            .ok_or_else(|| Error::AuthExec(format!("No {key} for azure Authentication provider")))
+    };
+    let mut args = vec!["get-token".into(), "--login".into(), login.into()];
+    args.extend(["--server-id".into(), setting("apiserver-id")?]);
+    // The other modes take their identity from the environment, the azure cli or the instance
+    let (client_id, tenant_id) = match login {
+        "devicecode" | "interactive" => (true, true),
+        "spn" => (false, true),
+        "azurecli" | "msi" | "workloadidentity" => (false, false),
+        _ => {
+            return Err(Error::AuthExec(format!(
+                "Unsupported kubelogin login mode {login:?} for azure Authentication provider"
+            )))
+        }
+    };
+    if client_id {
+        args.extend(["--client-id".into(), setting("client-id")?]);
+    }
+    if tenant_id {
+        args.extend(["--tenant-id".into(), setting("tenant-id")?]);
+        let environment = setting("environment").unwrap_or_else(|_| "AzurePublicCloud".into());
+        args.extend(["--environment".into(), environment]);
+        // Tokens of the legacy config mode have the "spn:" prefix in their audience
+        if matches!(
+            provider.config.get("config-mode").map(String::as_str),
+            None | Some("" | "0")
+        ) {
+            args.push("--legacy".into());
+        }
+    }
+    Ok(args)
+}
+
 fn extract_value(json: &serde_json::Value, context: &str, path: &str) -> Result<String, Error> {
     let parsed_path = path
         .trim_matches(|c| c == '"' || c == '{' || c == '}')
@@ -566,6 +834,41 @@ pub struct ExecCredentialStatus {
     pub client_key_data: Option<String>,
 }
 
+// Reuse a token of the exec plugin from a previous run, or cache the one it returns now
+fn cached_auth_exec(auth: &ExecConfig, cache: Option<&CredentialCache>) -> Result<ExecCredential, Error> {
+    let Some(cache) = cache else {
+        return auth_exec(auth);
+    };
+    let expiry = |creds: &ExecCredential| {
+        let status = creds.status.as_ref().filter(|status| status.token.is_some())?;
+        status
+            .expiration_timestamp
+            .as_ref()?
+            .parse::<DateTime<Utc>>()
+            .ok()
+    };
+    if let Some(creds) = cache
+        .read(auth)
+        .and_then(|data| serde_json::from_str::<ExecCredential>(&data).ok())
+    {
+        if expiry(&creds).is_some_and(|expiry| Utc::now() + SIXTY_SEC < expiry) {
+            return Ok(creds);
+        }
+    }
+
+    let creds = auth_exec(auth)?;
+    if expiry(&creds).is_some() {
+        let written = serde_json::to_string(&creds)
+            .map_err(std::io::Error::from)
+            .and_then(|data| cache.write(auth, &data));
+        // the token can be used either way, so failing to cache it is not fatal
+        if let Err(err) = written {
+            tracing::warn!("failed to cache exec credentials: {err}");
+        }
+    }
+    Ok(creds)
+}
+
 fn auth_exec(auth: &ExecConfig) -> Result<ExecCredential, Error> {
     let mut cmd = match &auth.command {
         Some(cmd) => Command::new(cmd),
@@ -585,9 +888,17 @@ fn auth_exec(auth: &ExecConfig) -> Result<ExecCredential, Error> {
         cmd.envs(envs);
     }
 
-    let interactive = auth.interactive_mode != Some(ExecInteractiveMode::Never);
+    let terminal = std::io::stdin().is_terminal();
+    let interactive = match auth.interactive_mode {
+        Some(ExecInteractiveMode::Never) => false,
+        Some(ExecInteractiveMode::Always) if !terminal => return Err(Error::ExecNotInteractive),
+        Some(ExecInteractiveMode::Always) => true,
+        Some(ExecInteractiveMode::IfAvailable) | None => terminal,
+    };
     if interactive {
+        // plugins prompt on stderr, e.g. for MFA codes, and read the answers from stdin
         cmd.stdin(std::process::Stdio::inherit());
+        cmd.stderr(std::process::Stdio::inherit());
     } else {
         cmd.stdin(std::process::Stdio::piped());
     }
@@ -631,11 +942,29 @@ fn auth_exec(auth: &ExecConfig) -> Result<ExecCredential, Error> {
             out,
         });
     }
-    let creds = serde_json::from_slice(&out.stdout).map_err(Error::AuthExecParse)?;
+    let creds: ExecCredential = serde_json::from_slice(&out.stdout).map_err(Error::AuthExecParse)?;
+    if let (Some(expected), Some(returned)) = (&auth.api_version, &creds.api_version) {
+        if expected != returned {
+            return Err(Error::AuthExecVersionMismatch {
+                expected: expected.clone(),
+                returned: returned.clone(),
+            });
+        }
+    }
 
     Ok(creds)
 }
 
+/// The stderr of a failed auth exec command, which is where plugins explain what went wrong
+fn plugin_output(out: &std::process::Output) -> String {
+    let output = if out.stderr.is_empty() {
+        &out.stdout
+    } else {
+        &out.stderr
+    };
+    String::from_utf8_lossy(output).trim().to_string()
+}
+
 #[cfg(test)]
 mod test {
     use crate::config::Kubeconfig;
@@ -678,9 +1007,9 @@ mod test {
         let auth_info = config.auth_infos[0].auth_info.as_ref().unwrap();
         match Auth::try_from(auth_info).unwrap() {
             Auth::RefreshableToken(RefreshableToken::Exec(refreshable)) => {
-                let (token, _expire, info) = Arc::try_unwrap(refreshable).unwrap().into_inner();
+                let ExecToken { token, auth_info, .. } = Arc::try_unwrap(refreshable).unwrap().into_inner();
                 assert_eq!(token.expose_secret(), &"my_token".to_owned());
-                let config = info.auth_provider.unwrap().config;
+                let config = auth_info.auth_provider.unwrap().config;
                 assert_eq!(config.get("access-token"), Some(&"my_token".to_owned()));
             }
             _ => unreachable!(),
@@ -688,6 +1017,154 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    #[cfg(feature = "azure")]
+    fn azure_provider_uses_kubelogin() {
+        let expires_on = (Utc::now() + Duration::try_hours(1).unwrap()).timestamp();
+        let provider: AuthProviderConfig = serde_yaml::from_str(&format!(
+            r#"
+            name: azure
+            config:
+              access-token: cached_token
+              apiserver-id: 6dae42f8-4368-4678-94ff-3960e28e3630
+              client-id: 80faf920-1908-4b52-b5ef-a8e7bedfc67a
+              tenant-id: 72f988bf-86f1-41af-91ab-2d7cd011db47
+              environment: AzureChinaCloud
+              expires-on: "{expires_on}"
+            "#
+        ))
+        .unwrap();
+        let auth_info = AuthInfo {
+            auth_provider: Some(provider),
+            ..AuthInfo::default()
+        };
+        match Auth::try_from(&auth_info).unwrap() {
+            Auth::RefreshableToken(RefreshableToken::Exec(refreshable)) => {
+                let ExecToken {
+                    token,
+                    expiry,
+                    auth_info,
+                    ..
+                } = Arc::try_unwrap(refreshable).unwrap().into_inner();
+                assert_eq!(token.expose_secret(), "cached_token");
+                assert_eq!(expiry.timestamp(), expires_on);
+                assert!(auth_info.auth_provider.is_none());
+                let exec = auth_info.exec.unwrap();
+                assert_eq!(exec.command.as_deref(), Some("kubelogin"));
+                let args = exec.args.unwrap().join(" ");
+                assert_eq!(
+                    args,
+                    "get-token --login devicecode \
+                     --server-id 6dae42f8-4368-4678-94ff-3960e28e3630 \
+                     --client-id 80faf920-1908-4b52-b5ef-a8e7bedfc67a \
+                     --tenant-id 72f988bf-86f1-41af-91ab-2d7cd011db47 \
+                     --environment AzureChinaCloud --legacy"
+                );
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "azure")]
+    fn azure_login_modes_pass_their_settings() {
+        let provider: AuthProviderConfig = serde_yaml::from_str(
+            r#"
+            name: azure
+            config:
+              apiserver-id: server
+              client-id: client
+              tenant-id: tenant
+              config-mode: "1"
+            "#,
+        )
+        .unwrap();
+        for (login, args) in [
+            (
+                "interactive",
+                "--server-id server --client-id client --tenant-id tenant --environment AzurePublicCloud",
+            ),
+            (
+                "spn",
+                "--server-id server --tenant-id tenant --environment AzurePublicCloud",
+            ),
+            ("azurecli", "--server-id server"),
+            ("msi", "--server-id server"),
+            ("workloadidentity", "--server-id server"),
+        ] {
+            let expected = format!("get-token --login {login} {args}");
+            assert_eq!(kubelogin_args(&provider, login).unwrap().join(" "), expected);
+        }
+        assert!(kubelogin_args(&provider, "ropc").is_err());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn gcp_command_prints_the_token() {
+        let provider: AuthProviderConfig = serde_yaml::from_str(
+            r#"
+            name: gcp
+            config:
+              cmd-path: echo
+              cmd-args: ' my_token '
+            "#,
+        )
+        .unwrap();
+        let auth_info = AuthInfo {
+            auth_provider: Some(provider),
+            ..AuthInfo::default()
+        };
+        match Auth::try_from(&auth_info).unwrap() {
+            Auth::Bearer(token) => assert_eq!(token.expose_secret(), "my_token"),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn exec_tokens_are_cached_on_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let runs = dir.path().join("runs");
+        let expiry = (Utc::now() + Duration::try_hours(1).unwrap()).to_rfc3339();
+        let credential = serde_json::json!({
+            "apiVersion": "client.authentication.k8s.io/v1",
+            "kind": "ExecCredential",
+            "status": { "token": "my_token", "expirationTimestamp": expiry },
+        });
+        let exec = ExecConfig {
+            api_version: Some("client.authentication.k8s.io/v1".into()),
+            command: Some("sh".into()),
+            args: Some(vec![
+                "-c".into(),
+                format!("echo >> {}; echo '{credential}'", runs.display()),
+            ]),
+            env: None,
+            drop_env: None,
+            interactive_mode: Some(ExecInteractiveMode::Never),
+            provide_cluster_info: false,
+            cluster: None,
+            cluster_name: None,
+            user_name: None,
+        };
+        let auth_info = AuthInfo {
+            exec: Some(exec),
+            ..AuthInfo::default()
+        };
+        let cache = CredentialCache::new(dir.path().join("cache"));
+        for _ in 0..2 {
+            match Auth::try_from_cached(&auth_info, Some(&cache)).unwrap() {
+                Auth::RefreshableToken(RefreshableToken::Exec(refreshable)) => {
+                    let token = Arc::try_unwrap(refreshable).unwrap().into_inner();
+                    assert_eq!(token.token.expose_secret(), "my_token");
+                    assert!(token.cache.is_some());
+                }
+                _ => unreachable!(),
+            }
+        }
+        // the second client reused the token of the first run
+        assert_eq!(std::fs::read_to_string(&runs).unwrap().lines().count(), 1);
+    }
+
     #[test]
     fn token_file() {
         let file = tempfile::NamedTempFile::new().unwrap();
@@ -707,4 +1184,87 @@ mod test {
         assert!(!token_file.is_expiring());
         assert_eq!(token_file.cached_token().unwrap(), "token2");
     }
+
+    // A service account token expiring at `expiry`
+    fn jwt(expiry: DateTime<Utc>) -> String {
+        use base64::Engine as _;
+
+        let claims = format!(
+            r#"{{"exp":{},"sub":"system:serviceaccount:default:foo"}}"#,
+            expiry.timestamp()
+        );
+        let payload = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(claims);
+        format!("eyJhbGciOiJSUzI1NiJ9.{payload}.c2lnbmF0dXJl")
+    }
+
+    #[test]
+    fn token_file_is_reloaded_before_the_token_expires() {
+        let expiry = Utc::now() + Duration::try_seconds(30).unwrap();
+        let jwt = jwt(expiry);
+
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), format!("{jwt}\n")).unwrap();
+        let token_file = TokenFile::new(file.path()).unwrap();
+        assert_eq!(token_file.cached_token().unwrap(), jwt);
+        assert_eq!(token_file.expires_at.timestamp(), expiry.timestamp());
+
+        std::fs::write(file.path(), "opaque").unwrap();
+        let token_file = TokenFile::new(file.path()).unwrap();
+        assert!(token_file.expires_at > expiry);
+    }
+
+    #[test]
+    fn expired_token_files_are_not_reread_on_every_request() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), jwt(Utc::now() - SIXTY_SEC)).unwrap();
+        let mut token_file = TokenFile::new(file.path()).unwrap();
+        assert!(!token_file.is_expiring());
+
+        std::fs::write(file.path(), "rotated").unwrap();
+        assert_ne!(token_file.token(), "rotated");
+        token_file.expires_at = Utc::now();
+        assert_eq!(token_file.token(), "rotated");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn auth_exec_failures_surface_the_plugin_output() {
+        let exec = |script: &str| -> ExecConfig {
+            serde_json::from_value(serde_json::json!({
+                "apiVersion": "client.authentication.k8s.io/v1",
+                "command": "sh",
+                "args": ["-c", script],
+                "interactiveMode": "Never",
+            }))
+            .unwrap()
+        };
+
+        let err = auth_exec(&exec("echo 'error: SSO session expired' >&2; exit 1")).unwrap_err();
+        assert!(matches!(err, Error::AuthExecRun { .. }));
+        assert!(err.to_string().ends_with(": error: SSO session expired"), "{err}");
+
+        let v1beta1 = r#"echo '{"apiVersion":"client.authentication.k8s.io/v1beta1"}'"#;
+        let err = auth_exec(&exec(v1beta1)).unwrap_err();
+        assert!(
+            matches!(err, Error::AuthExecVersionMismatch { returned, .. } if returned.ends_with("v1beta1"))
+        );
+
+        let v1 = r#"echo '{"apiVersion":"client.authentication.k8s.io/v1","status":{"token":"t"}}'"#;
+        let creds = auth_exec(&exec(v1)).unwrap();
+        assert_eq!(creds.status.unwrap().token.as_deref(), Some("t"));
+    }
+
+    #[test]
+    fn interactive_plugins_require_a_terminal() {
+        if std::io::stdin().is_terminal() {
+            return;
+        }
+        let exec: ExecConfig = serde_json::from_value(serde_json::json!({
+            "apiVersion": "client.authentication.k8s.io/v1",
+            "command": "kubelogin",
+            "interactiveMode": "Always",
+        }))
+        .unwrap();
+        assert!(matches!(auth_exec(&exec), Err(Error::ExecNotInteractive)));
+    }
 }
@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 
 use super::TEN_SEC;
+use crate::config::AuthProviderPersister;
 use chrono::{TimeZone, Utc};
 use form_urlencoded::Serializer;
 use http::{
@@ -158,6 +159,8 @@ use base64::engine::general_purpose::STANDARD as STANDARD_BASE64_ENGINE;
 pub struct Oidc {
     id_token: SecretString,
     refresher: Result<Refresher, errors::RefreshInitError>,
+    /// Where refreshed tokens are written back to, if anywhere.
+    pub(super) persister: Option<AuthProviderPersister>,
 }
 
 impl Oidc {
@@ -190,10 +193,31 @@ impl Oidc {
             return Ok(self.id_token.expose_secret().to_string());
         }
 
-        let id_token = self.refresher.as_mut().map_err(|e| e.clone())?.id_token().await?;
+        let refresher = self.refresher.as_mut().map_err(|e| e.clone())?;
+        let id_token = refresher.id_token().await?;
 
         self.id_token = id_token.clone().into();
 
+        if let Some(persister) = self.persister.clone() {
+            let id = id_token.clone();
+            let refresh = refresher.refresh_token.expose_secret().to_owned();
+            // the file system is blocking, which would otherwise stall the runtime
+            let persisted = tokio::task::spawn_blocking(move || {
+                let tokens = [
+                    (Self::CONFIG_ID_TOKEN, id.as_str()),
+                    (Refresher::CONFIG_REFRESH_TOKEN, refresh.as_str()),
+                ];
+                persister.persist(&tokens)
+            })
+            .await;
+            // the refreshed token can be used either way, so failing to persist it is not fatal
+            match persisted {
+                Ok(Ok(())) => {}
+                Ok(Err(err)) => tracing::warn!("failed to persist refreshed oidc tokens: {err}"),
+                Err(err) => tracing::warn!("failed to persist refreshed oidc tokens: {err}"),
+            }
+        }
+
         Ok(id_token)
     }
 
@@ -206,7 +230,11 @@ impl Oidc {
             .into();
         let refresher = Refresher::from_config(config);
 
-        Ok(Self { id_token, refresher })
+        Ok(Self {
+            id_token,
+            refresher,
+            persister: None,
+        })
     }
 }
 
@@ -473,6 +501,7 @@ mod tests {
             refresher: Err(errors::RefreshInitError::MissingField(
                 Refresher::CONFIG_REFRESH_TOKEN,
             )),
+            persister: None,
         };
 
         // Proper JWT expiring at 2123-06-28T15:18:12.629Z
@@ -49,6 +49,22 @@ impl Body {
         Body::new(Kind::Wrap(body.map_err(Into::into).boxed_unsync()))
     }
 
+    // The contents of a body that is fully buffered in memory
+    pub(crate) fn as_bytes(&self) -> Option<&[u8]> {
+        match &self.kind {
+            Kind::Once(bytes) => Some(bytes.as_deref().unwrap_or_default()),
+            Kind::Wrap(_) => None,
+        }
+    }
+
+    // Clone a body that is fully buffered in memory, streaming bodies cannot be replayed
+    pub(crate) fn try_clone(&self) -> Option<Self> {
+        match &self.kind {
+            Kind::Once(bytes) => Some(Self::new(Kind::Once(bytes.clone()))),
+            Kind::Wrap(_) => None,
+        }
+    }
+
     /// Collect all the data frames and trailers of this request body and return the data frame
     pub async fn collect_bytes(self) -> Result<Bytes, crate::Error> {
         Ok(self.collect().await?.to_bytes())
@@ -0,0 +1,287 @@
+//! Clients for many clusters, by kubeconfig context or name
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use kube_core::server::HealthReport;
+use tokio::sync::{Mutex, OnceCell};
+
+use crate::{
+    config::{KubeConfigOptions, Kubeconfig, KubeconfigError},
+    Client, Config, Error, Result,
+};
+
+/// Clients for many clusters, built when they are first used
+///
+/// Clusters are named by the contexts of a [`Kubeconfig`], or by the name a [`Config`] or
+/// [`Client`] is inserted with. Clients are built on the first [`ClientSet::get`] and shared
+/// afterwards, so fleet management controllers only connect to the clusters they talk to.
+///
+/// Cached clients can be evicted, so that the next `get` builds them from the latest config,
+/// e.g. after [`ClientSet::evict_unhealthy`] found their apiserver not ready. Clients are not evicted
+/// after a time to live or when they are idle; controllers that need that can call
+/// [`ClientSet::evict`] for the clusters they stopped talking to.
+///
+/// ```no_run
+/// use kube::{client::ClientSet, config::Kubeconfig};
+/// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+/// let clients = ClientSet::from_kubeconfig(Kubeconfig::read()?);
+/// for name in clients.names().await {
+///     let version = clients.get(&name).await?.apiserver_version().await?;
+///     println!("{name}: {}", version.git_version);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct ClientSet {
+    inner: Arc<Mutex<Inner>>,
+    health_timeout: Duration,
+}
+
+#[derive(Default)]
+struct Inner {
+    kubeconfig: Option<Kubeconfig>,
+    configs: HashMap<String, Config>,
+    // A cell per cluster, so that clients are built once without holding the lock of the set
+    clients: HashMap<String, Arc<OnceCell<Client>>>,
+}
+
+enum Source {
+    Config(Config),
+    Kubeconfig(Kubeconfig),
+}
+
+impl Default for ClientSet {
+    fn default() -> Self {
+        Self {
+            inner: Arc::default(),
+            health_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl ClientSet {
+    /// An empty set, for clusters that are inserted later
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A set of the contexts of `kubeconfig`
+    pub fn from_kubeconfig(kubeconfig: Kubeconfig) -> Self {
+        let inner = Inner {
+            kubeconfig: Some(kubeconfig),
+            ..Inner::default()
+        };
+        Self {
+            inner: Arc::new(Mutex::new(inner)),
+            ..Self::default()
+        }
+    }
+
+    /// How long [`ClientSet::health`] waits for the readiness of each apiserver, 10s by default
+    ///
+    /// Apiservers that do not respond in time are reported with [`Error::Timeout`].
+    #[must_use]
+    pub fn health_timeout(mut self, timeout: Duration) -> Self {
+        self.health_timeout = timeout;
+        self
+    }
+
+    /// The names of the clusters, which are the contexts of the kubeconfig and the inserted names
+    pub async fn names(&self) -> Vec<String> {
+        let inner = self.inner.lock().await;
+        let contexts = inner
+            .kubeconfig
+            .iter()
+            .flat_map(|k| k.contexts.iter().map(|c| &c.name));
+        let clients = inner.clients.iter().filter(|(_, client)| client.initialized());
+        let mut names = contexts
+            .chain(inner.configs.keys())
+            .chain(clients.map(|(name, _)| name))
+            .cloned()
+            .collect::<Vec<_>>();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Add the cluster `name` with `config`, replacing the client of a cluster with the same name
+    pub async fn insert(&self, name: impl Into<String>, config: Config) {
+        let name = name.into();
+        let mut inner = self.inner.lock().await;
+        inner.clients.remove(&name);
+        inner.configs.insert(name, config);
+    }
+
+    /// Add the cluster `name` with a client that was built already
+    ///
+    /// Unless it was also inserted with a config or is a context of the kubeconfig, the cluster is
+    /// removed when the client is evicted.
+    pub async fn insert_client(&self, name: impl Into<String>, client: Client) {
+        let client = Arc::new(OnceCell::from(client));
+        self.inner.lock().await.clients.insert(name.into(), client);
+    }
+
+    /// The client of the cluster `name`, which is built if it is not cached
+    ///
+    /// Inserted configs take precedence over the contexts of the kubeconfig.
+    ///
+    /// Clients are built without blocking the other clusters of the set, e.g. while an exec plugin
+    /// runs, and concurrent calls for the same cluster wait for the same client.
+    pub async fn get(&self, name: &str) -> Result<Client> {
+        let (client, source) = {
+            let mut inner = self.inner.lock().await;
+            if let Some(client) = inner.clients.get(name).and_then(|client| client.get()) {
+                return Ok(client.clone());
+            }
+            let source = match (inner.configs.get(name), &inner.kubeconfig) {
+                (Some(config), _) => Source::Config(config.clone()),
+                (None, Some(kubeconfig)) => Source::Kubeconfig(kubeconfig.clone()),
+                (None, None) => return Err(Error::LoadKubeconfig(KubeconfigError::LoadContext(name.into()))),
+            };
+            (inner.clients.entry(name.to_owned()).or_default().clone(), source)
+        };
+        let client = client
+            .get_or_try_init(|| async {
+                let config = match source {
+                    Source::Config(config) => config,
+                    Source::Kubeconfig(kubeconfig) => {
+                        let options = KubeConfigOptions {
+                            context: Some(name.to_owned()),
+                            ..KubeConfigOptions::default()
+                        };
+                        Config::from_custom_kubeconfig(kubeconfig, &options)
+                            .await
+                            .map_err(Error::LoadKubeconfig)?
+                    }
+                };
+                Client::try_from(config)
+            })
+            .await?;
+        Ok(client.clone())
+    }
+
+    /// Drop the cached client of `name`, so that the next [`ClientSet::get`] builds a new one
+    pub async fn evict(&self, name: &str) -> Option<Client> {
+        let client = self.inner.lock().await.clients.remove(name)?;
+        client.get().cloned()
+    }
+
+    /// Probe whether the apiservers of the cached clients are ready, from `/readyz`
+    ///
+    /// Each probe is bounded by the [`ClientSet::health_timeout`].
+    pub async fn health(&self) -> Vec<(String, Result<HealthReport>)> {
+        let clients = self
+            .inner
+            .lock()
+            .await
+            .clients
+            .iter()
+            .filter_map(|(name, client)| Some((name.clone(), client.get()?.clone())))
+            .collect::<Vec<_>>();
+        let timeout = self.health_timeout;
+        let probes = clients.into_iter().map(|(name, client)| async move {
+            let report = tokio::time::timeout(timeout, client.readyz())
+                .await
+                .unwrap_or(Err(Error::Timeout(timeout)));
+            (name, report)
+        });
+        futures::future::join_all(probes).await
+    }
+
+    /// Evict the cached clients whose apiserver is not ready or cannot be reached
+    ///
+    /// Returns the names of the evicted clusters.
+    pub async fn evict_unhealthy(&self) -> Vec<String> {
+        let mut evicted = Vec::new();
+        for (name, report) in self.health().await {
+            match report {
+                Ok(report) if report.healthy => continue,
+                Ok(report) => {
+                    let failed = report.failed().map(|c| c.name.as_str()).collect::<Vec<_>>();
+                    tracing::warn!(cluster = %name, ?failed, "apiserver is not ready, evicting its client");
+                }
+                Err(err) => {
+                    tracing::warn!(
+                        cluster = %name,
+                        error = &err as &dyn std::error::Error,
+                        "apiserver is unreachable, evicting its client"
+                    );
+                }
+            }
+            self.evict(&name).await;
+            evicted.push(name);
+        }
+        evicted.sort();
+        evicted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::ClientSet;
+    use crate::{client::Body, config::Kubeconfig, Client, Config, Error};
+    use http::{Method, Request, Response, StatusCode};
+
+    #[tokio::test]
+    async fn clients_are_built_per_context_and_evicted() {
+        let kubeconfig = Kubeconfig::from_yaml(
+            r#"
+        apiVersion: v1
+        clusters:
+        - cluster:
+            server: http://prod:8080
+          name: prod
+        contexts:
+        - context:
+            cluster: prod
+            namespace: apps
+          name: prod
+        kind: Config
+        "#,
+        )
+        .unwrap();
+        let clients = ClientSet::from_kubeconfig(kubeconfig);
+        clients
+            .insert("staging", Config::new("http://staging:8080".parse().unwrap()))
+            .await;
+        let (flaky, mock) = Client::mock();
+        clients.insert_client("flaky", flaky).await;
+        assert_eq!(clients.names().await, ["flaky", "prod", "staging"]);
+
+        assert_eq!(clients.get("prod").await.unwrap().default_namespace(), "apps");
+        assert_eq!(
+            clients.get("staging").await.unwrap().default_namespace(),
+            "default"
+        );
+        assert!(clients.get("dev").await.is_err());
+
+        let failing = Response::builder()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .body(b"[-]etcd failed: reason withheld\nreadyz check failed\n".to_vec())
+            .unwrap();
+        mock.expect(Method::GET, "/readyz").respond_with(failing);
+        let (healthy, mock) = Client::mock();
+        mock.expect(Method::GET, "/readyz")
+            .respond_with(Response::new(b"[+]etcd ok\nreadyz check passed\n".to_vec()));
+        clients.insert_client("prod", healthy).await;
+        clients.evict("staging").await;
+
+        assert_eq!(clients.evict_unhealthy().await, ["flaky"]);
+        assert_eq!(clients.names().await, ["prod", "staging"]);
+    }
+
+    #[tokio::test]
+    async fn health_probes_time_out() {
+        let (mock_service, _handle) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let clients = ClientSet::new().health_timeout(Duration::from_millis(10));
+        clients
+            .insert_client("stuck", Client::new(mock_service, "default"))
+            .await;
+
+        let health = clients.health().await;
+        assert!(matches!(health[..], [(ref name, Err(Error::Timeout(_)))] if name == "stuck"));
+        assert_eq!(clients.evict_unhealthy().await, ["stuck"]);
+    }
+}
@@ -0,0 +1,169 @@
+//! Pluggable decoding of alternative wire formats
+//!
+//! The API server can serve responses in encodings other than JSON, such as
+//! `application/cbor` (KEP-4222) or protobuf. A [`Codec`] registered on a
+//! [`Client`](crate::Client) via [`Client::with_codec`](crate::Client::with_codec)
+//! is advertised in the `Accept` header of requests that would otherwise ask for plain JSON,
+//! with JSON kept as a lower priority fallback for servers that do not support it.
+//!
+//! Responses are transcoded to JSON by the matching codec before deserialization,
+//! so all typed APIs keep working regardless of the negotiated format.
+//!
+//! Codecs are advertised without asking discovery which encodings the server supports. The
+//! API server answers in the first advertised type that it can serve for the resource, so servers
+//! and resources without support for a codec fall back to the JSON that stays acceptable. No CBOR
+//! codec ships with this crate; it can be added as a [`Codec`] for `application/cbor` once the
+//! server serves it.
+use std::sync::Arc;
+
+use http::{header, HeaderMap, HeaderValue, Uri};
+use tower::BoxError;
+
+use crate::{Error, Result};
+
+mod protobuf;
+pub use protobuf::{ProtobufCodec, PROTOBUF_MIME};
+
+/// A decoder for an alternative response content type
+pub trait Codec: Send + Sync + 'static {
+    /// The media type handled by this codec, e.g. `application/cbor`
+    ///
+    /// Parameters are not included; they are ignored when matching a response `Content-Type`.
+    fn media_type(&self) -> &str;
+
+    /// Whether this codec should be advertised for a request to `uri`
+    ///
+    /// Defaults to every request. Codecs that only understand some resources
+    /// can restrict negotiation here so other requests keep using JSON.
+    fn supports(&self, uri: &Uri) -> bool {
+        let _ = uri;
+        true
+    }
+
+    /// Transcode a response body in this format into JSON
+    fn decode_json(&self, body: &[u8]) -> Result<Vec<u8>, BoxError>;
+}
+
+/// The set of codecs registered on a client
+#[derive(Clone, Default)]
+pub(crate) struct Codecs(Arc<Vec<Arc<dyn Codec>>>);
+
+impl Codecs {
+    /// A new set with `codec` added, taking priority over earlier registrations of the same type
+    pub(crate) fn with(&self, codec: Arc<dyn Codec>) -> Self {
+        let mut codecs: Vec<_> = self
+            .0
+            .iter()
+            .filter(|c| !c.media_type().eq_ignore_ascii_case(codec.media_type()))
+            .cloned()
+            .collect();
+        codecs.push(codec);
+        Self(Arc::new(codecs))
+    }
+
+    /// Advertise registered codecs on requests that only ask for JSON
+    ///
+    /// Requests with a more specific `Accept` header (e.g. metadata or table requests) are left alone
+    /// since those renderings are only available as JSON. Watches always stay on JSON, since their
+    /// events are decoded line by line rather than transcoded by a codec.
+    pub(crate) fn negotiate(&self, uri: &Uri, headers: &mut HeaderMap) {
+        if self.0.is_empty() || is_watch(uri) {
+            return;
+        }
+        let json_only = match headers.get(header::ACCEPT) {
+            None => true,
+            Some(accept) => accept == "application/json" || accept == "*/*",
+        };
+        if !json_only {
+            return;
+        }
+        let mut accept: Vec<&str> = self
+            .0
+            .iter()
+            .rev()
+            .filter(|c| c.supports(uri))
+            .map(|c| c.media_type())
+            .collect();
+        if accept.is_empty() {
+            return;
+        }
+        accept.push("application/json;q=0.9");
+        if let Ok(value) = HeaderValue::from_str(&accept.join(", ")) {
+            headers.insert(header::ACCEPT, value);
+        }
+    }
+
+    /// Transcode a response body to JSON if the server answered in a registered format
+    pub(crate) fn decode(&self, headers: &HeaderMap, body: Vec<u8>) -> Result<Vec<u8>> {
+        let Some(content_type) = headers.get(header::CONTENT_TYPE).and_then(|v| v.to_str().ok()) else {
+            return Ok(body);
+        };
+        let media_type = content_type.split(';').next().unwrap_or_default().trim();
+        match self
+            .0
+            .iter()
+            .find(|c| c.media_type().eq_ignore_ascii_case(media_type))
+        {
+            Some(codec) => codec.decode_json(&body).map_err(Error::Codec),
+            None => Ok(body),
+        }
+    }
+}
+
+fn is_watch(uri: &Uri) -> bool {
+    let query = uri.query().unwrap_or_default();
+    query.split('&').any(|pair| pair == "watch=true" || pair == "watch=1")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct Reversed;
+    impl Codec for Reversed {
+        fn media_type(&self) -> &str {
+            "application/x-reversed"
+        }
+
+        fn decode_json(&self, body: &[u8]) -> Result<Vec<u8>, BoxError> {
+            Ok(body.iter().rev().copied().collect())
+        }
+    }
+
+    #[test]
+    fn negotiates_only_plain_json_requests() {
+        let codecs = Codecs::default().with(Arc::new(Reversed));
+        let uri = Uri::from_static("/api/v1/pods");
+        let mut headers = HeaderMap::new();
+        codecs.negotiate(&uri, &mut headers);
+        assert_eq!(
+            headers[header::ACCEPT],
+            "application/x-reversed, application/json;q=0.9"
+        );
+
+        let table = "application/json;as=Table;v=v1;g=meta.k8s.io,application/json";
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, HeaderValue::from_static(table));
+        codecs.negotiate(&uri, &mut headers);
+        assert_eq!(headers[header::ACCEPT], table);
+
+        let watch = Uri::from_static("/api/v1/pods?watch=true&resourceVersion=0");
+        let mut headers = HeaderMap::new();
+        codecs.negotiate(&watch, &mut headers);
+        assert!(headers.get(header::ACCEPT).is_none());
+    }
+
+    #[test]
+    fn decodes_matching_content_type() {
+        let codecs = Codecs::default().with(Arc::new(Reversed));
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/x-reversed; charset=utf-8"),
+        );
+        assert_eq!(codecs.decode(&headers, b"}{".to_vec()).unwrap(), b"{}");
+
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        assert_eq!(codecs.decode(&headers, b"}{".to_vec()).unwrap(), b"}{");
+    }
+}
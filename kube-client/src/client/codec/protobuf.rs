@@ -0,0 +1,369 @@
+//! Kubernetes protobuf envelope decoding
+//!
+//! Built-in resources can be served as `application/vnd.kubernetes.protobuf`, which is
+//! considerably smaller and cheaper to produce than JSON on large clusters. Every such body
+//! is a 4 byte magic number followed by a `runtime.Unknown` message carrying the type
+//! information and the encoded object. This module unwraps that envelope and hands the
+//! object bytes to a decoder registered for its kind.
+//!
+//! `k8s-openapi` does not ship protobuf messages, so decoders are supplied by the caller
+//! (e.g. generated with `prost` from the upstream `.proto` files). Requests for resources
+//! without a decoder, which includes all custom resources, keep negotiating JSON.
+//!
+//! Protobuf is negotiated for gets and lists. Watches stay on JSON, since the length prefixed
+//! protobuf frames of watch events are not decoded.
+use std::{collections::HashMap, fmt, sync::Arc};
+
+use http::Uri;
+use kube_core::Resource;
+use serde::Serialize;
+use tower::BoxError;
+
+use super::Codec;
+
+/// Media type of the Kubernetes protobuf encoding
+pub const PROTOBUF_MIME: &str = "application/vnd.kubernetes.protobuf";
+
+const MAGIC: &[u8] = b"k8s\x00";
+
+type Decoder = Arc<dyn Fn(&[u8]) -> Result<Vec<u8>, BoxError> + Send + Sync>;
+
+/// A [`Codec`] for `application/vnd.kubernetes.protobuf`
+///
+/// ```no_run
+/// # use k8s_openapi::api::core::v1::Pod;
+/// # use kube::client::codec::ProtobufCodec;
+/// # fn decode_pod(_: &[u8]) -> Result<Pod, tower::BoxError> { todo!() }
+/// # fn decode_pod_list(_: &[u8]) -> Result<Vec<u8>, tower::BoxError> { todo!() }
+/// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+/// let codec = ProtobufCodec::new()
+///     .resource::<Pod>(decode_pod)
+///     .decoder("v1", "PodList", decode_pod_list);
+/// let client = kube::Client::try_default().await?.with_codec(codec);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct ProtobufCodec {
+    decoders: HashMap<(String, String), Decoder>,
+    // (group/version, plural) of resources negotiated as protobuf
+    resources: Vec<(String, String)>,
+}
+
+impl fmt::Debug for ProtobufCodec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProtobufCodec")
+            .field("kinds", &self.decoders.keys().collect::<Vec<_>>())
+            .field("resources", &self.resources)
+            .finish()
+    }
+}
+
+impl ProtobufCodec {
+    /// An empty codec that does not negotiate protobuf for any resource
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a decoder producing JSON for objects of the given `apiVersion` and `kind`
+    ///
+    /// This is the low level hook, and is needed for `List` kinds.
+    /// It does not enable negotiation on its own; see [`ProtobufCodec::resource`].
+    #[must_use]
+    pub fn decoder<F>(mut self, api_version: &str, kind: &str, decode: F) -> Self
+    where
+        F: Fn(&[u8]) -> Result<Vec<u8>, BoxError> + Send + Sync + 'static,
+    {
+        self.decoders
+            .insert((api_version.to_string(), kind.to_string()), Arc::new(decode));
+        self
+    }
+
+    /// Register a typed decoder for `K` and negotiate protobuf when requesting it
+    ///
+    /// Decoded objects are re-serialized to JSON so they flow through the regular client deserialization.
+    /// Lists are served with the `List` kind, so also register a [`decoder`](ProtobufCodec::decoder)
+    /// for e.g. `PodList` when listing.
+    #[must_use]
+    pub fn resource<K>(mut self, decode: fn(&[u8]) -> Result<K, BoxError>) -> Self
+    where
+        K: Resource<DynamicType = ()> + Serialize + 'static,
+    {
+        let api_version = K::api_version(&()).to_string();
+        let kind = K::kind(&()).to_string();
+        self.resources.push((api_version.clone(), K::plural(&()).to_string()));
+        self.decoder(&api_version, &kind, move |body| {
+            let obj = decode(body)?;
+            // protobuf messages carry no type meta, so add it back for JSON consumers
+            let mut value = serde_json::to_value(&obj)?;
+            value["apiVersion"] = K::api_version(&()).into();
+            value["kind"] = K::kind(&()).into();
+            Ok(serde_json::to_vec(&value)?)
+        })
+    }
+}
+
+impl Codec for ProtobufCodec {
+    fn media_type(&self) -> &str {
+        PROTOBUF_MIME
+    }
+
+    fn supports(&self, uri: &Uri) -> bool {
+        match resource_of(uri.path()) {
+            Some((gv, plural)) => self.resources.iter().any(|(g, p)| g == gv && p == plural),
+            None => false,
+        }
+    }
+
+    fn decode_json(&self, body: &[u8]) -> Result<Vec<u8>, BoxError> {
+        let unknown = Unknown::parse(body)?;
+        if !unknown.content_encoding.is_empty() {
+            return Err(format!("unsupported protobuf content encoding {}", unknown.content_encoding).into());
+        }
+        let key = (unknown.api_version.to_string(), unknown.kind.to_string());
+        match self.decoders.get(&key) {
+            Some(decode) => decode(unknown.raw),
+            // error responses are served in the negotiated format, so they decode without registration
+            None if key.0 == "v1" && key.1 == "Status" => decode_status(unknown.raw),
+            None => Err(format!("no protobuf decoder registered for {}/{}", key.0, key.1).into()),
+        }
+    }
+}
+
+/// Extract the group/version and plural of a plain resource request path
+///
+/// Returns `None` for subresources, which are not negotiated as protobuf.
+fn resource_of(path: &str) -> Option<(String, &str)> {
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let (gv, rest) = match segments.as_slice() {
+        ["api", version, rest @ ..] => (version.to_string(), rest),
+        ["apis", group, version, rest @ ..] => (format!("{group}/{version}"), rest),
+        _ => return None,
+    };
+    let rest = match rest {
+        ["namespaces", _, rest @ ..] if !rest.is_empty() => rest,
+        rest => rest,
+    };
+    match rest {
+        [plural] | [plural, _] => Some((gv, *plural)),
+        _ => None,
+    }
+}
+
+/// The `runtime.Unknown` envelope of a protobuf response
+#[derive(Debug, Default, PartialEq)]
+struct Unknown<'a> {
+    api_version: &'a str,
+    kind: &'a str,
+    raw: &'a [u8],
+    content_encoding: &'a str,
+}
+
+impl<'a> Unknown<'a> {
+    fn parse(body: &'a [u8]) -> Result<Self, BoxError> {
+        let mut buf = body
+            .strip_prefix(MAGIC)
+            .ok_or("missing kubernetes protobuf magic number")?;
+        let mut unknown = Unknown::default();
+        while let Some((field, value)) = next_field(&mut buf)? {
+            match (field, value) {
+                (1, Field::Bytes(type_meta)) => {
+                    let mut buf = type_meta;
+                    while let Some((field, value)) = next_field(&mut buf)? {
+                        match (field, value) {
+                            (1, Field::Bytes(v)) => unknown.api_version = std::str::from_utf8(v)?,
+                            (2, Field::Bytes(v)) => unknown.kind = std::str::from_utf8(v)?,
+                            _ => {}
+                        }
+                    }
+                }
+                (2, Field::Bytes(raw)) => unknown.raw = raw,
+                (3, Field::Bytes(v)) => unknown.content_encoding = std::str::from_utf8(v)?,
+                _ => {}
+            }
+        }
+        Ok(unknown)
+    }
+}
+
+/// Transcode a `meta/v1.Status` message into its JSON form
+fn decode_status(mut buf: &[u8]) -> Result<Vec<u8>, BoxError> {
+    let mut status = serde_json::json!({ "apiVersion": "v1", "kind": "Status" });
+    while let Some((field, value)) = next_field(&mut buf)? {
+        match (field, value) {
+            (2, Field::Bytes(v)) => status["status"] = std::str::from_utf8(v)?.into(),
+            (3, Field::Bytes(v)) => status["message"] = std::str::from_utf8(v)?.into(),
+            (4, Field::Bytes(v)) => status["reason"] = std::str::from_utf8(v)?.into(),
+            (5, Field::Bytes(v)) => status["details"] = decode_status_details(v)?,
+            (6, Field::Varint(v)) => status["code"] = (v as i32).into(),
+            _ => {}
+        }
+    }
+    Ok(serde_json::to_vec(&status)?)
+}
+
+fn decode_status_details(mut buf: &[u8]) -> Result<serde_json::Value, BoxError> {
+    let mut details = serde_json::json!({});
+    let mut causes = vec![];
+    while let Some((field, value)) = next_field(&mut buf)? {
+        match (field, value) {
+            (1, Field::Bytes(v)) => details["name"] = std::str::from_utf8(v)?.into(),
+            (2, Field::Bytes(v)) => details["group"] = std::str::from_utf8(v)?.into(),
+            (3, Field::Bytes(v)) => details["kind"] = std::str::from_utf8(v)?.into(),
+            (4, Field::Bytes(mut cause)) => {
+                let mut value = serde_json::json!({});
+                while let Some((field, v)) = next_field(&mut cause)? {
+                    let key = match field {
+                        1 => "reason",
+                        2 => "message",
+                        3 => "field",
+                        _ => continue,
+                    };
+                    if let Field::Bytes(v) = v {
+                        value[key] = std::str::from_utf8(v)?.into();
+                    }
+                }
+                causes.push(value);
+            }
+            (5, Field::Varint(v)) => details["retryAfterSeconds"] = (v as i32).into(),
+            (6, Field::Bytes(v)) => details["uid"] = std::str::from_utf8(v)?.into(),
+            _ => {}
+        }
+    }
+    details["causes"] = causes.into();
+    Ok(details)
+}
+
+enum Field<'a> {
+    /// A varint value
+    Varint(u64),
+    /// A fixed width value, which none of the decoded messages use
+    Skipped,
+    /// A length delimited value
+    Bytes(&'a [u8]),
+}
+
+/// Read the next field of a protobuf message, skipping over fixed width values
+fn next_field<'a>(buf: &mut &'a [u8]) -> Result<Option<(u64, Field<'a>)>, BoxError> {
+    if buf.is_empty() {
+        return Ok(None);
+    }
+    let key = read_varint(buf)?;
+    let field = match key & 0x7 {
+        0 => Field::Varint(read_varint(buf)?),
+        1 => {
+            take(buf, 8)?;
+            Field::Skipped
+        }
+        2 => {
+            let len = usize::try_from(read_varint(buf)?)?;
+            Field::Bytes(take(buf, len)?)
+        }
+        5 => {
+            take(buf, 4)?;
+            Field::Skipped
+        }
+        wire => return Err(format!("unsupported protobuf wire type {wire}").into()),
+    };
+    Ok(Some((key >> 3, field)))
+}
+
+fn read_varint(buf: &mut &[u8]) -> Result<u64, BoxError> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = buf.split_first().ok_or("truncated protobuf varint")?;
+        *buf = rest;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err("protobuf varint too long".into())
+}
+
+fn take<'a>(buf: &mut &'a [u8], len: usize) -> Result<&'a [u8], BoxError> {
+    if buf.len() < len {
+        return Err("truncated protobuf field".into());
+    }
+    let (field, rest) = buf.split_at(len);
+    *buf = rest;
+    Ok(field)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn bytes_field(field: u8, value: &[u8]) -> Vec<u8> {
+        let mut out = vec![(field << 3) | 2, value.len() as u8];
+        out.extend_from_slice(value);
+        out
+    }
+
+    fn envelope(api_version: &str, kind: &str, raw: &[u8]) -> Vec<u8> {
+        let mut type_meta = bytes_field(1, api_version.as_bytes());
+        type_meta.extend(bytes_field(2, kind.as_bytes()));
+        let mut body = MAGIC.to_vec();
+        body.extend(bytes_field(1, &type_meta));
+        body.extend(bytes_field(2, raw));
+        body
+    }
+
+    #[test]
+    fn parses_unknown_envelope() {
+        let body = envelope("v1", "Pod", b"payload");
+        let unknown = Unknown::parse(&body).unwrap();
+        assert_eq!(unknown, Unknown {
+            api_version: "v1",
+            kind: "Pod",
+            raw: b"payload",
+            content_encoding: "",
+        });
+        assert!(Unknown::parse(b"{}").is_err());
+    }
+
+    #[test]
+    fn decodes_registered_kinds_only() {
+        let codec = ProtobufCodec::new().decoder("v1", "Pod", |raw| Ok(raw.to_vec()));
+        let json = codec.decode_json(&envelope("v1", "Pod", b"{}")).unwrap();
+        assert_eq!(json, b"{}");
+        assert!(codec.decode_json(&envelope("v1", "Secret", b"{}")).is_err());
+    }
+
+    #[test]
+    fn decodes_status_without_registration() {
+        let mut cause = bytes_field(1, b"FieldValueInvalid");
+        cause.extend(bytes_field(3, b"metadata.name"));
+        let mut details = bytes_field(1, b"blog");
+        details.extend(bytes_field(3, b"pods"));
+        details.extend(bytes_field(4, &cause));
+        let mut status = bytes_field(2, b"Failure");
+        status.extend(bytes_field(3, b"pods \"blog\" not found"));
+        status.extend(bytes_field(4, b"NotFound"));
+        status.extend(bytes_field(5, &details));
+        status.extend([6 << 3, 0x94, 0x03]);
+
+        let json = ProtobufCodec::new()
+            .decode_json(&envelope("v1", "Status", &status))
+            .unwrap();
+        let err: kube_core::ErrorResponse = serde_json::from_slice(&json).unwrap();
+        assert_eq!(err.code, 404);
+        assert_eq!(err.reason, "NotFound");
+        assert_eq!(err.message, "pods \"blog\" not found");
+        let details = err.details.unwrap();
+        assert_eq!(details.name, "blog");
+        assert_eq!(details.causes[0].reason, "FieldValueInvalid");
+        assert_eq!(details.causes[0].field, "metadata.name");
+    }
+
+    #[test]
+    fn negotiates_registered_resources() {
+        use k8s_openapi::api::core::v1::Pod;
+        let codec = ProtobufCodec::new().resource::<Pod>(|_| Ok(Pod::default()));
+        assert!(codec.supports(&Uri::from_static("/api/v1/namespaces/ns/pods")));
+        assert!(codec.supports(&Uri::from_static("/api/v1/namespaces/ns/pods/blog?")));
+        assert!(codec.supports(&Uri::from_static("/api/v1/pods")));
+        assert!(!codec.supports(&Uri::from_static("/api/v1/namespaces/ns/pods/blog/log")));
+        assert!(!codec.supports(&Uri::from_static("/apis/clux.dev/v1/foos")));
+    }
+}
@@ -9,7 +9,7 @@ use tower::{filter::AsyncFilterLayer, util::Either};
 
 #[cfg(any(feature = "rustls-tls", feature = "openssl-tls"))] use super::tls;
 use super::{
-    auth::Auth,
+    auth::{Auth, RefreshableToken},
     middleware::{AddAuthorizationLayer, AuthLayer, BaseUriLayer, ExtraHeadersLayer},
 };
 use crate::{Config, Error, Result};
@@ -49,6 +49,9 @@ pub trait ConfigExt: private::Sealed {
 
     /// Create [`hyper_rustls::HttpsConnector`] based on config and `connector`.
     ///
+    /// With the `http2` feature the connector offers HTTP/2 over ALPN, which servers prefer, but
+    /// connection upgrades for exec, attach and port-forward need HTTP/1.1.
+    ///
     /// # Example
     ///
     /// ```rust
@@ -108,6 +111,10 @@ pub trait ConfigExt: private::Sealed {
         -> Result<hyper_openssl::client::legacy::HttpsConnector<HttpConnector>>;
 
     /// Create [`hyper_openssl::HttpsConnector`] based on config and `connector`.
+    ///
+    /// With the `http2` feature the connector offers HTTP/2 over ALPN, which servers prefer, but
+    /// connection upgrades for exec, attach and port-forward need HTTP/1.1.
+    ///
     /// # Example
     ///
     /// ```rust
@@ -166,7 +173,16 @@ impl ConfigExt for Config {
     }
 
     fn auth_layer(&self) -> Result<Option<AuthLayer>> {
-        Ok(match Auth::try_from(&self.auth_info).map_err(Error::Auth)? {
+        if let Some(provider) = &self.credential_provider {
+            let refreshable = RefreshableToken::Provider(provider.clone());
+            return Ok(Some(AuthLayer(Either::Right(AsyncFilterLayer::new(refreshable)))));
+        }
+        let mut auth =
+            Auth::try_from_cached(&self.auth_info, self.credential_cache.as_ref()).map_err(Error::Auth)?;
+        if let Some(persister) = &self.auth_provider_persister {
+            auth.persist_to(persister);
+        }
+        Ok(match auth {
             Auth::None => None,
             Auth::Basic(user, pass) => Some(AuthLayer(Either::Left(
                 AddAuthorizationLayer::basic(&user, pass.expose_secret()).as_sensitive(true),
@@ -201,6 +217,14 @@ impl ConfigExt for Config {
                 ));
             }
         }
+        if let Some(impersonate_uid) = &self.auth_info.impersonate_uid {
+            headers.push((
+                HeaderName::from_static("impersonate-uid"),
+                HeaderValue::from_str(impersonate_uid)
+                    .map_err(http::Error::from)
+                    .map_err(Error::HttpError)?,
+            ));
+        }
         Ok(ExtraHeadersLayer {
             headers: Arc::new(headers),
         })
@@ -208,13 +232,34 @@ impl ConfigExt for Config {
 
     #[cfg(feature = "rustls-tls")]
     fn rustls_client_config(&self) -> Result<rustls::ClientConfig> {
-        let identity = self.exec_identity_pem().0.or_else(|| self.identity_pem());
-        tls::rustls_tls::rustls_client_config(
-            identity.as_deref(),
-            self.root_cert.as_deref(),
-            self.accept_invalid_certs,
-        )
-        .map_err(Error::RustlsTls)
+        let exec_identity = self.exec_identity_pem().0;
+        let mut client_config =
+            if let (None, Some((cert_path, key_path))) = (&exec_identity, self.identity_files()) {
+                tls::rustls_tls::rustls_client_config_with_identity_files(
+                    cert_path,
+                    key_path,
+                    self.root_cert.as_deref(),
+                    &self.extra_root_certs,
+                    self.accept_invalid_certs,
+                    self.accept_invalid_hostnames,
+                )
+            } else {
+                let identity = exec_identity.or_else(|| self.identity_pem());
+                tls::rustls_tls::rustls_client_config(
+                    identity.as_deref(),
+                    self.root_cert.as_deref(),
+                    &self.extra_root_certs,
+                    self.accept_invalid_certs,
+                    self.accept_invalid_hostnames,
+                )
+            }
+            .map_err(Error::RustlsTls)?;
+        if let (Some(path), false) = (&self.root_cert_file, self.accept_invalid_certs) {
+            let (extra, accept_invalid_hostnames) = (&self.extra_root_certs, self.accept_invalid_hostnames);
+            tls::rustls_tls::reload_root_certs(&mut client_config, path, extra, accept_invalid_hostnames)
+                .map_err(Error::RustlsTls)?;
+        }
+        Ok(client_config)
     }
 
     #[cfg(feature = "rustls-tls")]
@@ -228,6 +273,53 @@ impl ConfigExt for Config {
     fn rustls_https_connector_with_connector<H>(
         &self,
         connector: H,
+    ) -> Result<hyper_rustls::HttpsConnector<H>> {
+        self.rustls_https_connector_with_alpn(connector, cfg!(feature = "http2"))
+    }
+
+    #[cfg(feature = "openssl-tls")]
+    fn openssl_ssl_connector_builder(&self) -> Result<openssl::ssl::SslConnectorBuilder> {
+        let identity = self.exec_identity_pem().0.or_else(|| self.identity_pem());
+        let (root_certs, extra_root_certs) = (self.root_cert.as_ref(), &self.extra_root_certs);
+        tls::openssl_tls::ssl_connector_builder(identity.as_ref(), root_certs, extra_root_certs)
+            .map_err(|e| Error::OpensslTls(tls::openssl_tls::Error::CreateSslConnector(e)))
+    }
+
+    #[cfg(feature = "openssl-tls")]
+    fn openssl_https_connector(
+        &self,
+    ) -> Result<hyper_openssl::client::legacy::HttpsConnector<HttpConnector>> {
+        let mut connector = HttpConnector::new();
+        connector.enforce_http(false);
+        self.openssl_https_connector_with_connector(connector)
+    }
+
+    #[cfg(feature = "openssl-tls")]
+    fn openssl_https_connector_with_connector<H>(
+        &self,
+        connector: H,
+    ) -> Result<hyper_openssl::client::legacy::HttpsConnector<H>>
+    where
+        H: tower::Service<http::Uri> + Send,
+        H::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+        H::Future: Send + 'static,
+        H::Response: Read + Write + hyper_util::client::legacy::connect::Connection + Unpin,
+    {
+        self.openssl_https_connector_with_alpn(connector, cfg!(feature = "http2"))
+    }
+}
+
+impl Config {
+    /// Create a [`hyper_rustls::HttpsConnector`] that offers HTTP/2 over ALPN when `http2` is set
+    ///
+    /// Connection upgrades (for exec, attach and port-forward) only work over HTTP/1.1,
+    /// so they need a connector that does not offer HTTP/2.
+    #[cfg(feature = "rustls-tls")]
+    #[cfg_attr(not(feature = "http2"), allow(unused_variables))]
+    pub(crate) fn rustls_https_connector_with_alpn<H>(
+        &self,
+        connector: H,
+        http2: bool,
     ) -> Result<hyper_rustls::HttpsConnector<H>> {
         use hyper_rustls::FixedServerNameResolver;
 
@@ -245,30 +337,21 @@ impl ConfigExt for Config {
                     .map_err(Error::RustlsTls)?,
             ));
         }
+        #[cfg(feature = "http2")]
+        if http2 {
+            return Ok(builder.enable_http1().enable_http2().wrap_connector(connector));
+        }
         Ok(builder.enable_http1().wrap_connector(connector))
     }
 
+    /// Create a [`hyper_openssl::client::legacy::HttpsConnector`] that offers HTTP/2 over ALPN
+    /// when `http2` is set
     #[cfg(feature = "openssl-tls")]
-    fn openssl_ssl_connector_builder(&self) -> Result<openssl::ssl::SslConnectorBuilder> {
-        let identity = self.exec_identity_pem().0.or_else(|| self.identity_pem());
-        // TODO: pass self.tls_server_name for openssl
-        tls::openssl_tls::ssl_connector_builder(identity.as_ref(), self.root_cert.as_ref())
-            .map_err(|e| Error::OpensslTls(tls::openssl_tls::Error::CreateSslConnector(e)))
-    }
-
-    #[cfg(feature = "openssl-tls")]
-    fn openssl_https_connector(
-        &self,
-    ) -> Result<hyper_openssl::client::legacy::HttpsConnector<HttpConnector>> {
-        let mut connector = HttpConnector::new();
-        connector.enforce_http(false);
-        self.openssl_https_connector_with_connector(connector)
-    }
-
-    #[cfg(feature = "openssl-tls")]
-    fn openssl_https_connector_with_connector<H>(
+    #[cfg_attr(not(feature = "http2"), allow(unused_variables))]
+    pub(crate) fn openssl_https_connector_with_alpn<H>(
         &self,
         connector: H,
+        http2: bool,
     ) -> Result<hyper_openssl::client::legacy::HttpsConnector<H>>
     where
         H: tower::Service<http::Uri> + Send,
@@ -276,28 +359,77 @@ impl ConfigExt for Config {
         H::Future: Send + 'static,
         H::Response: Read + Write + hyper_util::client::legacy::connect::Connection + Unpin,
     {
-        let mut https = hyper_openssl::client::legacy::HttpsConnector::with_connector(
-            connector,
-            self.openssl_ssl_connector_builder()?,
-        )
-        .map_err(|e| Error::OpensslTls(tls::openssl_tls::Error::CreateHttpsConnector(e)))?;
-        if self.accept_invalid_certs {
-            https.set_callback(|ssl, _uri| {
-                ssl.set_verify(openssl::ssl::SslVerifyMode::NONE);
-                Ok(())
-            });
+        let mut builder = self.openssl_ssl_connector_builder()?;
+        #[cfg(feature = "http2")]
+        if http2 {
+            builder
+                .set_alpn_protos(b"\x02h2\x08http/1.1")
+                .map_err(|e| Error::OpensslTls(tls::openssl_tls::Error::CreateSslConnector(e)))?;
+        }
+        let mut https = hyper_openssl::client::legacy::HttpsConnector::with_connector(connector, builder)
+            .map_err(|e| Error::OpensslTls(tls::openssl_tls::Error::CreateHttpsConnector(e)))?;
+        let accept_invalid_certs = self.accept_invalid_certs;
+        let verify_hostname = !accept_invalid_certs && !self.accept_invalid_hostnames;
+        if accept_invalid_certs {
+            tracing::warn!("verification of the apiserver certificate is disabled, connections are insecure");
+        } else if self.accept_invalid_hostnames {
+            tracing::warn!("hostname verification of the apiserver certificate is disabled");
         }
+        let tls_server_name = self.tls_server_name.clone();
+        // certificates rotated on disk, e.g. by cert-manager, are used by new connections
+        let identity = match self.identity_files() {
+            Some((cert_path, key_path)) if self.exec_identity_pem().0.is_none() => Some(Arc::new(
+                tls::openssl_tls::ReloadingIdentity::new(cert_path.to_owned(), key_path.to_owned()),
+            )),
+            _ => None,
+        };
+        let roots = match (&self.root_cert_file, accept_invalid_certs) {
+            (Some(path), false) => Some(Arc::new(
+                tls::openssl_tls::ReloadingRoots::new(path.clone(), &self.extra_root_certs)
+                    .map_err(|e| Error::OpensslTls(tls::openssl_tls::Error::CreateSslConnector(e)))?,
+            )),
+            _ => None,
+        };
+        https.set_callback(move |ssl, _uri| {
+            if let Some(identity) = &identity {
+                identity.apply(ssl)?;
+            }
+            if let Some(roots) = &roots {
+                roots.apply(ssl)?;
+            }
+            if accept_invalid_certs {
+                ssl.set_verify(openssl::ssl::SslVerifyMode::NONE);
+            }
+            if !verify_hostname {
+                ssl.set_verify_hostname(false);
+            }
+            if let Some(name) = &tls_server_name {
+                tls::openssl_tls::set_server_name(ssl, name, verify_hostname)?;
+            }
+            Ok(())
+        });
         Ok(https)
     }
-}
 
-impl Config {
+    /// Paths of the client certificate and key, when both are only given as files
+    #[cfg(any(feature = "rustls-tls", feature = "openssl-tls"))]
+    fn identity_files(&self) -> Option<(&std::path::Path, &std::path::Path)> {
+        let auth_info = &self.auth_info;
+        if auth_info.client_certificate_data.is_some() || auth_info.client_key_data.is_some() {
+            return None;
+        }
+        Some((
+            std::path::Path::new(auth_info.client_certificate.as_deref()?),
+            std::path::Path::new(auth_info.client_key.as_deref()?),
+        ))
+    }
+
     // This is necessary to retrieve an identity when an exec plugin
     // returns a client certificate and key instead of a token.
     // This has be to be checked on TLS configuration vs tokens
     // which can be added in as an AuthLayer.
     pub(crate) fn exec_identity_pem(&self) -> (Option<Vec<u8>>, Option<DateTime<Utc>>) {
-        match Auth::try_from(&self.auth_info) {
+        match Auth::try_from_cached(&self.auth_info, self.credential_cache.as_ref()) {
             Ok(Auth::Certificate(client_certificate_data, client_key_data, expiratiom)) => {
                 const NEW_LINE: u8 = b'\n';
 
@@ -311,3 +443,28 @@ impl Config {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn impersonation_headers() {
+        let config = Config::new("https://localhost:6443".parse().unwrap())
+            .impersonate("system:serviceaccount:default:foo")
+            .impersonate_groups(["system:serviceaccounts", "dev"])
+            .impersonate_uid("1234");
+        let layer = config.extra_headers_layer().unwrap();
+        let headers = layer
+            .headers
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.to_str().unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(headers, [
+            ("impersonate-user", "system:serviceaccount:default:foo"),
+            ("impersonate-group", "system:serviceaccounts"),
+            ("impersonate-group", "dev"),
+            ("impersonate-uid", "1234"),
+        ]);
+    }
+}
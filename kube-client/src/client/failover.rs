@@ -0,0 +1,248 @@
+//! Failover between several apiserver endpoints
+use std::{
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use futures::future::BoxFuture;
+use http::{uri::Authority, Uri};
+use k8s_openapi::api::core::v1::Endpoints;
+use tower::{Service, ServiceExt};
+
+use crate::{Api, Client, Result};
+
+/// How long an endpoint that failed to connect is skipped for
+const UNHEALTHY_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Connector that fails over between several addresses of the apiserver
+///
+/// Connections to the primary authority are attempted against each endpoint in turn, starting
+/// with the ones that are believed to be healthy. An endpoint is considered unhealthy for 30
+/// seconds after a connection to it fails, and is only tried again after all healthy ones.
+/// Connections to any other authority are passed through unchanged.
+///
+/// The connector is placed below the TLS connector, so certificates are verified against the
+/// primary authority no matter which endpoint the connection ends up at.
+///
+/// This is set up automatically by the default client when [`Config::fallback_urls`](crate::Config::fallback_urls) is not empty.
+#[derive(Clone)]
+pub struct FailoverConnector<C> {
+    inner: C,
+    primary: Option<Authority>,
+    endpoints: Arc<Mutex<Vec<Endpoint>>>,
+}
+
+#[derive(Debug)]
+struct Endpoint {
+    authority: Authority,
+    unhealthy_until: Option<Instant>,
+}
+
+impl<C> FailoverConnector<C> {
+    /// Fail over from the authority of `primary` to the authorities of `fallbacks`
+    ///
+    /// Without any `fallbacks` all connections are passed through unchanged.
+    pub fn new(inner: C, primary: &Uri, fallbacks: &[Uri]) -> Self {
+        let endpoints = std::iter::once(primary)
+            .chain(fallbacks)
+            .filter_map(|uri| uri.authority().cloned())
+            .map(|authority| Endpoint {
+                authority,
+                unhealthy_until: None,
+            })
+            .collect();
+        Self {
+            inner,
+            primary: primary.authority().cloned().filter(|_| !fallbacks.is_empty()),
+            endpoints: Arc::new(Mutex::new(endpoints)),
+        }
+    }
+
+    /// Endpoint authorities in the order they should be tried
+    fn candidates(&self) -> Vec<Authority> {
+        let now = Instant::now();
+        let endpoints = self.endpoints.lock().expect("failover endpoints poisoned");
+        let (healthy, unhealthy): (Vec<_>, Vec<_>) = endpoints
+            .iter()
+            .partition(|e| !matches!(e.unhealthy_until, Some(until) if until > now));
+        healthy
+            .into_iter()
+            .chain(unhealthy)
+            .map(|e| e.authority.clone())
+            .collect()
+    }
+
+    fn mark(endpoints: &Mutex<Vec<Endpoint>>, authority: &Authority, healthy: bool) {
+        let mut endpoints = endpoints.lock().expect("failover endpoints poisoned");
+        if let Some(endpoint) = endpoints.iter_mut().find(|e| &e.authority == authority) {
+            endpoint.unhealthy_until = (!healthy).then(|| Instant::now() + UNHEALTHY_COOLDOWN);
+        }
+    }
+}
+
+impl<C> Service<Uri> for FailoverConnector<C>
+where
+    C: Service<Uri> + Clone + Send + 'static,
+    C::Future: Send + 'static,
+    C::Response: Send + 'static,
+    C::Error: Send + 'static,
+{
+    type Error = C::Error;
+    type Future = BoxFuture<'static, Result<C::Response, C::Error>>;
+    type Response = C::Response;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        if self.primary.is_none() || uri.authority() != self.primary.as_ref() {
+            return Box::pin(self.inner.call(uri));
+        }
+        let candidates = self.candidates();
+        let endpoints = self.endpoints.clone();
+        // the inner connector has been polled ready, so use it for the first attempt
+        let mut inner = std::mem::replace(&mut self.inner, self.inner.clone());
+        Box::pin(async move {
+            let mut last_err = None;
+            for (i, authority) in candidates.into_iter().enumerate() {
+                let mut parts = uri.clone().into_parts();
+                parts.authority = Some(authority.clone());
+                let Ok(target) = Uri::from_parts(parts) else {
+                    continue;
+                };
+                if i > 0 {
+                    inner.ready().await?;
+                }
+                match inner.call(target).await {
+                    Ok(conn) => {
+                        Self::mark(&endpoints, &authority, true);
+                        return Ok(conn);
+                    }
+                    Err(err) => {
+                        tracing::warn!(%authority, "failed to connect to apiserver endpoint");
+                        Self::mark(&endpoints, &authority, false);
+                        last_err = Some(err);
+                    }
+                }
+            }
+            match last_err {
+                Some(err) => Err(err),
+                // every candidate produced an invalid uri, so connect to the original one
+                None => {
+                    inner.ready().await?;
+                    inner.call(uri).await
+                }
+            }
+        })
+    }
+}
+
+impl Client {
+    /// Resolve the addresses of all apiserver instances behind the `kubernetes` Service
+    ///
+    /// The result can be used as [`Config::fallback_urls`](crate::Config::fallback_urls) of an in-cluster
+    /// config, so the client keeps working when a single apiserver goes away.
+    ///
+    /// ```no_run
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// use kube::{Client, Config};
+    /// let mut config = Config::incluster()?;
+    /// let client = Client::try_from(config.clone())?;
+    /// config.fallback_urls = client.apiserver_endpoints().await?;
+    /// let client = Client::try_from(config)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn apiserver_endpoints(&self) -> Result<Vec<Uri>> {
+        let api: Api<Endpoints> = Api::namespaced(self.clone(), "default");
+        let endpoints = api.get("kubernetes").await?;
+        let mut urls = vec![];
+        for subset in endpoints.subsets.unwrap_or_default() {
+            let ports = subset.ports.unwrap_or_default();
+            let Some(port) = ports
+                .iter()
+                .find(|p| p.name.as_deref() == Some("https"))
+                .or(ports.first())
+            else {
+                continue;
+            };
+            for address in subset.addresses.unwrap_or_default() {
+                let host = if address.ip.contains(':') {
+                    format!("[{}]", address.ip)
+                } else {
+                    address.ip
+                };
+                if let Ok(url) = format!("https://{host}:{}", port.port).parse() {
+                    urls.push(url);
+                }
+            }
+        }
+        Ok(urls)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::future::{ready, Ready};
+
+    /// Connector that only succeeds for one authority and records every attempt
+    #[derive(Clone)]
+    struct TestConnector {
+        reachable: &'static str,
+        attempts: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Service<Uri> for TestConnector {
+        type Error = &'static str;
+        type Future = Ready<Result<Uri, &'static str>>;
+        type Response = Uri;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, uri: Uri) -> Self::Future {
+            let authority = uri.authority().unwrap().to_string();
+            self.attempts.lock().unwrap().push(authority.clone());
+            ready(if authority == self.reachable {
+                Ok(uri)
+            } else {
+                Err("connection refused")
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn fails_over_to_healthy_endpoints() {
+        let attempts = Arc::new(Mutex::new(vec![]));
+        let inner = TestConnector {
+            reachable: "10.0.0.2:6443",
+            attempts: attempts.clone(),
+        };
+        let primary = Uri::from_static("https://kubernetes.default.svc:6443");
+        let fallbacks = [
+            Uri::from_static("https://10.0.0.1:6443"),
+            Uri::from_static("https://10.0.0.2:6443"),
+        ];
+        let mut connector = FailoverConnector::new(inner, &primary, &fallbacks);
+
+        let conn = connector.ready().await.unwrap().call(primary.clone()).await.unwrap();
+        assert_eq!(conn, "https://10.0.0.2:6443/");
+        // failed endpoints are tried last until their cooldown runs out
+        connector.ready().await.unwrap().call(primary).await.unwrap();
+        assert_eq!(*attempts.lock().unwrap(), [
+            "kubernetes.default.svc:6443",
+            "10.0.0.1:6443",
+            "10.0.0.2:6443",
+            "10.0.0.2:6443",
+        ]);
+
+        // other authorities are passed through
+        let other = Uri::from_static("https://example.com");
+        assert!(connector.ready().await.unwrap().call(other).await.is_err());
+    }
+}
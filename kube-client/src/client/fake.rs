@@ -0,0 +1,1024 @@
+//! A [`Client`] backed by an in-memory apiserver, for unit tests that need cluster state
+//!
+//! Unlike a [`mock`](crate::client::mock), which hands out canned responses, a [`FakeCluster`]
+//! stores objects and serves get, list, watch, create, replace, patch and delete requests for them,
+//! including the `status` subresource. Objects are loaded into the fake directly, and every change
+//! made through the client is recorded as a [`Mutation`], so tests can assert on what was changed.
+//!
+//! ```
+//! # async fn doc() -> Result<(), Box<dyn std::error::Error>> {
+//! use k8s_openapi::api::core::v1::ConfigMap;
+//! use kube::{api::{Patch, PatchParams}, Api, Client};
+//!
+//! let (client, cluster) = Client::fake();
+//! cluster.load_yaml(r#"
+//! apiVersion: v1
+//! kind: ConfigMap
+//! metadata: { name: settings, namespace: default }
+//! data: { mode: fast }
+//! "#)?;
+//!
+//! let api: Api<ConfigMap> = Api::default_namespaced(client);
+//! let patch = serde_json::json!({ "data": { "mode": "slow" } });
+//! api.patch("settings", &PatchParams::default(), &Patch::Merge(&patch)).await?;
+//!
+//! let mutations = cluster.take_mutations();
+//! assert_eq!(mutations.len(), 1);
+//! assert_eq!(mutations[0].after.as_ref().unwrap()["data"]["mode"], "slow");
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The fake is deliberately simple:
+//! - there is no validation, defaulting or admission
+//! - strategic merge patches and server-side apply are treated as json merge patches
+//! - writes to the main resource never change the `status`, as if every resource had a `status` subresource
+//! - watches send the matching objects as `ADDED` events, and then end
+//! - label selectors and equality based field selectors on string fields are supported
+//! - resource plurals of objects loaded without their type are guessed from the kind
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use http::{header::CONTENT_TYPE, Method, Request, Response, StatusCode, Uri};
+use k8s_openapi::chrono::{SecondsFormat, Utc};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Map, Value};
+use tower::{BoxError, Service};
+
+use super::Body;
+use crate::{
+    api::manifests,
+    core::{ApiResource, Expression, Resource, SelectorExt, Status},
+    Client, Result,
+};
+
+impl Client {
+    /// Create a [`Client`] backed by an empty [`FakeCluster`] instead of a cluster
+    ///
+    /// The client uses `default` as its default namespace. See the [`fake`](crate::client::fake) module.
+    pub fn fake() -> (Client, FakeCluster) {
+        let cluster = FakeCluster::default();
+        let client = Client::new(FakeService(cluster.clone()), "default");
+        (client, cluster)
+    }
+}
+
+/// Handle for the objects and recorded changes of a [`Client::fake`]
+#[derive(Clone, Default)]
+pub struct FakeCluster(Arc<Mutex<State>>);
+
+/// A change made to an object of a [`FakeCluster`] through its client
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mutation {
+    /// The api version of the object, e.g. `apps/v1`
+    pub api_version: String,
+    /// The kind of the object, e.g. `Deployment`
+    pub kind: String,
+    /// The namespace of the object, `None` for cluster scoped objects
+    pub namespace: Option<String>,
+    /// The name of the object
+    pub name: String,
+    /// The object before the change, `None` if it was created
+    pub before: Option<Value>,
+    /// The object after the change, `None` if it was deleted
+    pub after: Option<Value>,
+}
+
+impl Mutation {
+    /// Whether the object was created
+    pub fn is_create(&self) -> bool {
+        self.before.is_none()
+    }
+
+    /// Whether the object was deleted
+    pub fn is_delete(&self) -> bool {
+        self.after.is_none()
+    }
+}
+
+impl FakeCluster {
+    /// Add an object, replacing any object of the same name
+    ///
+    /// The object gets a `uid`, `resourceVersion` and `creationTimestamp` unless it already has them.
+    /// Loading objects is not recorded as a [`Mutation`].
+    pub fn insert<K>(&self, obj: &K)
+    where
+        K: Resource + Serialize,
+        K::DynamicType: Default,
+    {
+        self.insert_with(obj, &ApiResource::erase::<K>(&K::DynamicType::default()));
+    }
+
+    /// Add an object of the resource `ar`, replacing any object of the same name
+    ///
+    /// Like [`FakeCluster::insert`], for objects whose type is only known at runtime.
+    pub fn insert_with<K: Serialize>(&self, obj: &K, ar: &ApiResource) {
+        let mut obj = serde_json::to_value(obj).expect("serializable fake object");
+        if obj.get("apiVersion").is_none() {
+            obj["apiVersion"] = ar.api_version.clone().into();
+        }
+        if obj.get("kind").is_none() {
+            obj["kind"] = ar.kind.clone().into();
+        }
+        let mut state = self.state();
+        state.kinds.insert((ar.api_version.clone(), ar.plural.clone()), ar.kind.clone());
+        state.stamp(&mut obj);
+        let key = Key {
+            api_version: ar.api_version.clone(),
+            plural: ar.plural.clone(),
+            namespace: obj["metadata"]["namespace"].as_str().map(String::from),
+            name: obj["metadata"]["name"].as_str().unwrap_or_default().to_string(),
+        };
+        state.objects.insert(key, obj);
+    }
+
+    /// Add every object in `manifests`
+    ///
+    /// The manifests are YAML or JSON documents, like for [`apply_manifests`](crate::api::apply_manifests).
+    /// Every object needs an `apiVersion` and a `kind`, and namespaced objects a namespace. The resource
+    /// plurals are guessed from the kinds, use [`FakeCluster::insert_with`] otherwise.
+    pub fn load_yaml(&self, manifests: &str) -> Result<()> {
+        for (gvk, obj) in manifests::parse(manifests)? {
+            self.insert_with(&obj, &ApiResource::from_gvk(&gvk));
+        }
+        Ok(())
+    }
+
+    /// The object of type `K` named `name` in `namespace`, `None` for cluster scoped objects
+    pub fn get<K>(&self, namespace: Option<&str>, name: &str) -> Option<K>
+    where
+        K: Resource + DeserializeOwned,
+        K::DynamicType: Default,
+    {
+        let dt = K::DynamicType::default();
+        let key = Key {
+            api_version: K::api_version(&dt).into_owned(),
+            plural: K::plural(&dt).into_owned(),
+            namespace: namespace.map(String::from),
+            name: name.to_string(),
+        };
+        let obj = self.state().objects.get(&key).cloned()?;
+        Some(serde_json::from_value(obj).expect("fake object of the requested type"))
+    }
+
+    /// All objects of the fake, ordered by resource, namespace and name
+    pub fn objects(&self) -> Vec<Value> {
+        self.state().objects.values().cloned().collect()
+    }
+
+    /// All changes made through the client so far, in the order they were made
+    pub fn mutations(&self) -> Vec<Mutation> {
+        self.state().mutations.clone()
+    }
+
+    /// Like [`FakeCluster::mutations`], but also forgets them
+    pub fn take_mutations(&self) -> Vec<Mutation> {
+        std::mem::take(&mut self.state().mutations)
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.0.lock().expect("fake cluster state poisoned")
+    }
+
+    fn respond(&self, method: &Method, uri: &Uri, content_type: &str, body: &[u8]) -> Response<Bytes> {
+        let query = query_params(uri);
+        let result = match Target::parse(uri.path()) {
+            Some(target) => {
+                let mut state = self.state();
+                if query.contains_key("dryRun") {
+                    state.clone().serve(method, &target, &query, content_type, body)
+                } else {
+                    state.serve(method, &target, &query, content_type, body)
+                }
+            }
+            None => Err(Failure::not_found(format!("the fake cluster does not serve {}", uri.path()))),
+        };
+        result.unwrap_or_else(|failure| {
+            let status = Status::failure(&failure.message, failure.reason).with_code(failure.code.as_u16());
+            json_response(failure.code, &status)
+        })
+    }
+}
+
+#[derive(Clone, Default)]
+struct State {
+    objects: BTreeMap<Key, Value>,
+    /// Kinds of the resources seen so far, by api version and plural
+    kinds: BTreeMap<(String, String), String>,
+    resource_version: u64,
+    mutations: Vec<Mutation>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Key {
+    api_version: String,
+    plural: String,
+    namespace: Option<String>,
+    name: String,
+}
+
+/// The resource a request is for, parsed from its path
+struct Target {
+    api_version: String,
+    plural: String,
+    namespace: Option<String>,
+    name: Option<String>,
+    subresource: Option<String>,
+}
+
+impl Target {
+    fn parse(path: &str) -> Option<Self> {
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        let (api_version, rest) = match segments.as_slice() {
+            ["api", version, rest @ ..] => (version.to_string(), rest),
+            ["apis", group, version, rest @ ..] => (format!("{group}/{version}"), rest),
+            _ => return None,
+        };
+        // `/api/v1/namespaces/{name}/status` is the status of a namespace, not a resource in it
+        let (namespace, rest) = match rest {
+            ["namespaces", namespace, plural, ..]
+                if rest.len() > 3 || !matches!(*plural, "status" | "finalize") =>
+            {
+                (Some(namespace.to_string()), &rest[2..])
+            }
+            _ => (None, rest),
+        };
+        let (plural, name, subresource) = match rest {
+            [plural] => (plural, None, None),
+            [plural, name] => (plural, Some(name.to_string()), None),
+            [plural, name, subresource] => (plural, Some(name.to_string()), Some(subresource.to_string())),
+            _ => return None,
+        };
+        Some(Self {
+            api_version,
+            plural: plural.to_string(),
+            namespace,
+            name,
+            subresource,
+        })
+    }
+
+    fn key(&self, name: &str) -> Key {
+        Key {
+            api_version: self.api_version.clone(),
+            plural: self.plural.clone(),
+            namespace: self.namespace.clone(),
+            name: name.to_string(),
+        }
+    }
+
+    fn contains(&self, key: &Key) -> bool {
+        key.api_version == self.api_version
+            && key.plural == self.plural
+            && (self.namespace.is_none() || key.namespace == self.namespace)
+    }
+
+    fn is_status(&self) -> bool {
+        self.subresource.as_deref() == Some("status")
+    }
+}
+
+/// A failure status to respond with
+struct Failure {
+    code: StatusCode,
+    reason: &'static str,
+    message: String,
+}
+
+impl Failure {
+    fn new(code: StatusCode, reason: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            reason,
+            message: message.into(),
+        }
+    }
+
+    fn not_found(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_FOUND, "NotFound", message)
+    }
+
+    fn invalid(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::UNPROCESSABLE_ENTITY, "Invalid", message)
+    }
+
+    fn conflict(key: &Key) -> Self {
+        let message = format!(
+            "Operation cannot be fulfilled on {} \"{}\": the object has been modified",
+            key.plural, key.name
+        );
+        Self::new(StatusCode::CONFLICT, "Conflict", message)
+    }
+}
+
+type Reply = Result<Response<Bytes>, Failure>;
+
+impl State {
+    fn serve(
+        &mut self,
+        method: &Method,
+        target: &Target,
+        query: &BTreeMap<String, String>,
+        content_type: &str,
+        body: &[u8],
+    ) -> Reply {
+        let subresource = target.subresource.as_deref();
+        if !matches!(subresource, None | Some("status")) {
+            let subresource = subresource.unwrap_or_default();
+            let message = format!("the fake cluster does not serve the {subresource} subresource");
+            return Err(Failure::not_found(message));
+        }
+        let ok = |obj: Value| json_response(StatusCode::OK, &obj);
+        match (method.as_str(), target.name.as_deref()) {
+            ("GET", None) if query.get("watch").is_some_and(|w| w == "true" || w == "1") => {
+                self.watch(target, query)
+            }
+            ("GET", None) => self.list(target, query).map(ok),
+            ("GET", Some(name)) => self.get(&target.key(name)).map(ok),
+            ("POST", None) if subresource.is_none() => {
+                let obj = self.create(target, parse_object(body)?)?;
+                Ok(json_response(StatusCode::CREATED, &obj))
+            }
+            ("PUT", Some(name)) => self.replace(target, name, parse_object(body)?).map(ok),
+            ("PATCH", Some(name)) => self.patch(target, name, content_type, body).map(ok),
+            ("DELETE", Some(name)) if subresource.is_none() => self.delete(&target.key(name)).map(ok),
+            ("DELETE", None) if subresource.is_none() => self.delete_collection(target, query).map(ok),
+            _ => Err(Failure::new(
+                StatusCode::METHOD_NOT_ALLOWED,
+                "MethodNotAllowed",
+                format!("the fake cluster does not support {method} on this path"),
+            )),
+        }
+    }
+
+    fn get(&self, key: &Key) -> Result<Value, Failure> {
+        self.objects
+            .get(key)
+            .cloned()
+            .ok_or_else(|| Failure::not_found(format!("{} \"{}\" not found", key.plural, key.name)))
+    }
+
+    fn select(&self, target: &Target, query: &BTreeMap<String, String>) -> Result<Vec<Value>, Failure> {
+        let labels = match query.get("labelSelector") {
+            Some(selector) => parse_label_selector(selector)?,
+            None => vec![],
+        };
+        let fields = match query.get("fieldSelector") {
+            Some(selector) => parse_field_selector(selector)?,
+            None => vec![],
+        };
+        Ok(self
+            .objects
+            .iter()
+            .filter(|(key, _)| target.contains(key))
+            .map(|(_, obj)| obj)
+            .filter(|obj| {
+                let object_labels = labels_of(obj);
+                labels.iter().all(|expr| expr.matches(&object_labels))
+                    && fields.iter().all(|field| field.matches(obj))
+            })
+            .cloned()
+            .collect())
+    }
+
+    fn list(&self, target: &Target, query: &BTreeMap<String, String>) -> Result<Value, Failure> {
+        Ok(self.list_of(target, self.select(target, query)?))
+    }
+
+    fn list_of(&self, target: &Target, items: Vec<Value>) -> Value {
+        let kind = self.kinds.get(&(target.api_version.clone(), target.plural.clone()));
+        json!({
+            "apiVersion": target.api_version,
+            "kind": kind.map_or_else(|| "List".to_string(), |kind| format!("{kind}List")),
+            "metadata": { "resourceVersion": self.resource_version.to_string() },
+            "items": items,
+        })
+    }
+
+    fn watch(&self, target: &Target, query: &BTreeMap<String, String>) -> Reply {
+        let mut body = vec![];
+        for obj in self.select(target, query)? {
+            let event = json!({ "type": "ADDED", "object": obj });
+            serde_json::to_writer(&mut body, &event).expect("serializable watch event");
+            body.push(b'\n');
+        }
+        Ok(Response::new(Bytes::from(body)))
+    }
+
+    fn create(&mut self, target: &Target, mut obj: Value) -> Result<Value, Failure> {
+        let name = match (obj["metadata"]["name"].as_str(), obj["metadata"]["generateName"].as_str()) {
+            (Some(name), _) => name.to_string(),
+            (None, Some(prefix)) => format!("{prefix}{:05x}", self.resource_version + 1),
+            (None, None) => return Err(Failure::invalid("name or generateName is required")),
+        };
+        obj["metadata"]["name"] = name.clone().into();
+        let obj_namespace = obj["metadata"]["namespace"].as_str().map(String::from);
+        match (&target.namespace, obj_namespace) {
+            (Some(ns), Some(obj_ns)) if *ns != obj_ns => {
+                let message = "the namespace of the object does not match the namespace of the request";
+                return Err(Failure::new(StatusCode::BAD_REQUEST, "BadRequest", message));
+            }
+            (Some(ns), _) => obj["metadata"]["namespace"] = ns.clone().into(),
+            (None, _) => {
+                if let Some(meta) = obj["metadata"].as_object_mut() {
+                    meta.remove("namespace");
+                }
+            }
+        }
+        let key = target.key(&name);
+        if self.objects.contains_key(&key) {
+            let message = format!("{} \"{name}\" already exists", target.plural);
+            return Err(Failure::new(StatusCode::CONFLICT, "AlreadyExists", message));
+        }
+        if obj.get("apiVersion").is_none() {
+            obj["apiVersion"] = target.api_version.clone().into();
+        }
+        if let Some(kind) = obj["kind"].as_str() {
+            let resource = (target.api_version.clone(), target.plural.clone());
+            self.kinds.insert(resource, kind.to_string());
+        }
+        // created objects never carry over bookkeeping from the request
+        if let Some(meta) = obj["metadata"].as_object_mut() {
+            for field in ["uid", "resourceVersion", "creationTimestamp", "deletionTimestamp", "generation"] {
+                meta.remove(field);
+            }
+        }
+        self.stamp(&mut obj);
+        Ok(self.commit(key, None, Some(obj)))
+    }
+
+    fn replace(&mut self, target: &Target, name: &str, obj: Value) -> Result<Value, Failure> {
+        let key = target.key(name);
+        let current = self.get(&key)?;
+        if obj["metadata"]["resourceVersion"].as_str().is_some_and(|rv| {
+            Some(rv) != current["metadata"]["resourceVersion"].as_str()
+        }) {
+            return Err(Failure::conflict(&key));
+        }
+        self.update(target, key, current, obj)
+    }
+
+    fn patch(
+        &mut self,
+        target: &Target,
+        name: &str,
+        content_type: &str,
+        body: &[u8],
+    ) -> Result<Value, Failure> {
+        let key = target.key(name);
+        let patch: Value = serde_json::from_slice(body).map_err(|e| Failure::invalid(e.to_string()))?;
+        let current = match self.objects.get(&key).cloned() {
+            Some(current) => current,
+            // server-side apply creates objects that do not exist yet
+            None if content_type == "application/apply-patch+yaml" && !target.is_status() => {
+                let mut obj = patch;
+                obj["metadata"]["name"] = name.into();
+                return self.create(target, obj);
+            }
+            None => return self.get(&key),
+        };
+        let mut patched = current.clone();
+        match content_type {
+            "application/json-patch+json" => json_patch(&mut patched, &patch).map_err(Failure::invalid)?,
+            "application/merge-patch+json"
+            | "application/strategic-merge-patch+json"
+            | "application/apply-patch+yaml" => merge_patch(&mut patched, patch),
+            _ => {
+                let message = format!("the fake cluster does not support patches of type {content_type}");
+                return Err(Failure::new(
+                    StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                    "UnsupportedMediaType",
+                    message,
+                ));
+            }
+        }
+        // patches that set the resourceVersion only apply to that version
+        if patched["metadata"]["resourceVersion"] != current["metadata"]["resourceVersion"] {
+            return Err(Failure::conflict(&key));
+        }
+        self.update(target, key, current, patched)
+    }
+
+    /// Write a new version of an existing object, keeping what clients can not change
+    fn update(
+        &mut self,
+        target: &Target,
+        key: Key,
+        before: Value,
+        mut after: Value,
+    ) -> Result<Value, Failure> {
+        if !after.is_object() {
+            return Err(Failure::invalid("the object must be a json object"));
+        }
+        if target.is_status() {
+            let status = after.get("status").cloned();
+            after = before.clone();
+            set_or_remove(&mut after, "status", status);
+        } else {
+            set_or_remove(&mut after, "status", before.get("status").cloned());
+        }
+        for field in [
+            "name",
+            "namespace",
+            "uid",
+            "resourceVersion",
+            "creationTimestamp",
+            "deletionTimestamp",
+            "generation",
+        ] {
+            let value = before["metadata"].get(field).cloned();
+            if let Some(meta) = after["metadata"].as_object_mut() {
+                match value {
+                    Some(value) => meta.insert(field.to_string(), value),
+                    None => meta.remove(field),
+                };
+            }
+        }
+        if after == before {
+            return Ok(before);
+        }
+        if without_metadata_and_status(&after) != without_metadata_and_status(&before) {
+            let generation = before["metadata"]["generation"].as_i64().unwrap_or_default();
+            after["metadata"]["generation"] = (generation + 1).into();
+        }
+        let finalized = after["metadata"]["finalizers"].as_array().map_or(true, Vec::is_empty);
+        if finalized && after["metadata"].get("deletionTimestamp").is_some() {
+            self.commit(key, Some(before), None);
+            return Ok(after);
+        }
+        Ok(self.commit(key, Some(before), Some(after)))
+    }
+
+    fn delete(&mut self, key: &Key) -> Result<Value, Failure> {
+        let current = self.get(key)?;
+        if current["metadata"]["finalizers"].as_array().map_or(true, Vec::is_empty) {
+            self.commit(key.clone(), Some(current.clone()), None);
+            return Ok(current);
+        }
+        // objects with finalizers are only marked, and go away once the finalizers are removed
+        if current["metadata"].get("deletionTimestamp").is_some() {
+            return Ok(current);
+        }
+        let mut deleting = current.clone();
+        deleting["metadata"]["deletionTimestamp"] = now().into();
+        Ok(self.commit(key.clone(), Some(current), Some(deleting)))
+    }
+
+    fn delete_collection(
+        &mut self,
+        target: &Target,
+        query: &BTreeMap<String, String>,
+    ) -> Result<Value, Failure> {
+        let mut deleted = vec![];
+        for obj in self.select(target, query)? {
+            let key = Key {
+                namespace: obj["metadata"]["namespace"].as_str().map(String::from),
+                ..target.key(obj["metadata"]["name"].as_str().unwrap_or_default())
+            };
+            deleted.push(self.delete(&key)?);
+        }
+        Ok(self.list_of(target, deleted))
+    }
+
+    /// Fill in the metadata the apiserver sets on new objects
+    fn stamp(&mut self, obj: &mut Value) {
+        self.resource_version += 1;
+        let meta = &mut obj["metadata"];
+        if meta.get("uid").is_none() {
+            meta["uid"] = format!("00000000-0000-4000-8000-{:012x}", self.resource_version).into();
+        }
+        if meta.get("creationTimestamp").is_none() {
+            meta["creationTimestamp"] = now().into();
+        }
+        if meta.get("generation").is_none() {
+            meta["generation"] = 1.into();
+        }
+        meta["resourceVersion"] = self.resource_version.to_string().into();
+    }
+
+    /// Store a change with a new resource version, and record it
+    ///
+    /// Returns the stored object, or the object as it was when it is deleted.
+    fn commit(&mut self, key: Key, before: Option<Value>, after: Option<Value>) -> Value {
+        let stored = after.map(|mut obj| {
+            self.resource_version += 1;
+            obj["metadata"]["resourceVersion"] = self.resource_version.to_string().into();
+            self.objects.insert(key.clone(), obj.clone());
+            obj
+        });
+        if stored.is_none() {
+            self.objects.remove(&key);
+        }
+        let object = stored.as_ref().or(before.as_ref());
+        let kind = object.and_then(|obj| obj["kind"].as_str()).unwrap_or_default().to_string();
+        let returned = object.cloned().unwrap_or_default();
+        self.mutations.push(Mutation {
+            api_version: key.api_version,
+            kind,
+            namespace: key.namespace,
+            name: key.name,
+            before,
+            after: stored,
+        });
+        returned
+    }
+}
+
+fn parse_object(body: &[u8]) -> Result<Value, Failure> {
+    match serde_json::from_slice(body) {
+        Ok(obj @ Value::Object(_)) => Ok(obj),
+        Ok(_) => Err(Failure::invalid("the object must be a json object")),
+        Err(e) => Err(Failure::invalid(e.to_string())),
+    }
+}
+
+fn set_or_remove(obj: &mut Value, field: &str, value: Option<Value>) {
+    if let Some(obj) = obj.as_object_mut() {
+        match value {
+            Some(value) => obj.insert(field.to_string(), value),
+            None => obj.remove(field),
+        };
+    }
+}
+
+fn without_metadata_and_status(obj: &Value) -> Value {
+    let mut obj = obj.clone();
+    if let Some(obj) = obj.as_object_mut() {
+        obj.remove("metadata");
+        obj.remove("status");
+    }
+    obj
+}
+
+fn now() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn labels_of(obj: &Value) -> BTreeMap<String, String> {
+    obj["metadata"]["labels"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter_map(|(k, v)| Some((k.clone(), v.as_str()?.to_string())))
+        .collect()
+}
+
+/// Apply a json merge patch, see RFC 7386
+fn merge_patch(obj: &mut Value, patch: Value) {
+    match patch {
+        Value::Object(patch) => {
+            if !obj.is_object() {
+                *obj = Value::Object(Map::new());
+            }
+            if let Value::Object(obj) = obj {
+                for (key, value) in patch {
+                    if value.is_null() {
+                        obj.remove(&key);
+                    } else {
+                        merge_patch(obj.entry(key).or_insert(Value::Null), value);
+                    }
+                }
+            }
+        }
+        patch => *obj = patch,
+    }
+}
+
+/// Apply a json patch, see RFC 6902
+fn json_patch(obj: &mut Value, patch: &Value) -> Result<(), String> {
+    let operations = patch.as_array().ok_or("a json patch must be a list of operations")?;
+    for operation in operations {
+        let path = operation["path"].as_str().ok_or("json patch operation without a path")?;
+        let value = || operation.get("value").cloned().ok_or(format!("no value to set {path} to"));
+        let from = || operation["from"].as_str().ok_or(format!("no path to move or copy to {path} from"));
+        match operation["op"].as_str().unwrap_or_default() {
+            "add" => pointer_add(obj, path, value()?)?,
+            "remove" => {
+                pointer_remove(obj, path)?;
+            }
+            "replace" => {
+                pointer_remove(obj, path)?;
+                pointer_add(obj, path, value()?)?;
+            }
+            "move" => {
+                let moved = pointer_remove(obj, from()?)?;
+                pointer_add(obj, path, moved)?;
+            }
+            "copy" => {
+                let copied = obj.pointer(from()?).cloned().ok_or(format!("{} does not exist", from()?))?;
+                pointer_add(obj, path, copied)?;
+            }
+            "test" => {
+                if obj.pointer(path) != Some(&value()?) {
+                    return Err(format!("test of {path} failed"));
+                }
+            }
+            op => return Err(format!("unsupported json patch operation {op:?}")),
+        }
+    }
+    Ok(())
+}
+
+fn split_pointer(path: &str) -> Result<(&str, String), String> {
+    let (parent, last) = path.rsplit_once('/').ok_or(format!("invalid json pointer {path:?}"))?;
+    Ok((parent, last.replace("~1", "/").replace("~0", "~")))
+}
+
+fn pointer_add(obj: &mut Value, path: &str, value: Value) -> Result<(), String> {
+    if path.is_empty() {
+        *obj = value;
+        return Ok(());
+    }
+    let (parent, last) = split_pointer(path)?;
+    match obj.pointer_mut(parent) {
+        Some(Value::Object(map)) => {
+            map.insert(last, value);
+        }
+        Some(Value::Array(items)) => {
+            let index = match last.as_str() {
+                "-" => items.len(),
+                index => index
+                    .parse()
+                    .ok()
+                    .filter(|i| *i <= items.len())
+                    .ok_or(format!("{path} is out of bounds"))?,
+            };
+            items.insert(index, value);
+        }
+        _ => return Err(format!("{parent} does not exist")),
+    }
+    Ok(())
+}
+
+fn pointer_remove(obj: &mut Value, path: &str) -> Result<Value, String> {
+    let (parent, last) = split_pointer(path)?;
+    let removed = match obj.pointer_mut(parent) {
+        Some(Value::Object(map)) => map.remove(&last),
+        Some(Value::Array(items)) => last
+            .parse()
+            .ok()
+            .filter(|i| *i < items.len())
+            .map(|i| items.remove(i)),
+        _ => None,
+    };
+    removed.ok_or(format!("{path} does not exist"))
+}
+
+fn query_params(uri: &Uri) -> BTreeMap<String, String> {
+    uri.query()
+        .unwrap_or_default()
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect()
+}
+
+fn percent_decode(encoded: &str) -> String {
+    let mut decoded = Vec::with_capacity(encoded.len());
+    let mut bytes = encoded.bytes();
+    while let Some(byte) = bytes.next() {
+        match byte {
+            b'+' => decoded.push(b' '),
+            b'%' => {
+                let hex: Vec<u8> = bytes.by_ref().take(2).collect();
+                let value = std::str::from_utf8(&hex).ok().and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                match value {
+                    Some(value) if hex.len() == 2 => decoded.push(value),
+                    _ => {
+                        decoded.push(b'%');
+                        decoded.extend(hex);
+                    }
+                }
+            }
+            byte => decoded.push(byte),
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Split a selector on the commas that are not part of a set like `in (a,b)`
+fn selector_terms(selector: &str) -> Vec<&str> {
+    let mut terms = vec![];
+    let (mut depth, mut start) = (0, 0);
+    for (i, c) in selector.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                terms.push(&selector[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    terms.push(&selector[start..]);
+    terms.into_iter().map(str::trim).filter(|term| !term.is_empty()).collect()
+}
+
+fn parse_label_selector(selector: &str) -> Result<Vec<Expression>, Failure> {
+    let values = |set: &str| -> Result<BTreeSet<String>, Failure> {
+        let set = set.trim().strip_prefix('(').and_then(|set| set.strip_suffix(')'));
+        let set = set.ok_or_else(|| Failure::invalid(format!("invalid label selector {selector:?}")))?;
+        Ok(set.split(',').map(|value| value.trim().to_string()).collect())
+    };
+    selector_terms(selector)
+        .into_iter()
+        .map(|term| -> Result<Expression, Failure> {
+            Ok(if let Some((key, set)) = term.split_once(" notin ") {
+                Expression::NotIn(key.trim().to_string(), values(set)?)
+            } else if let Some((key, set)) = term.split_once(" in ") {
+                Expression::In(key.trim().to_string(), values(set)?)
+            } else if let Some((key, value)) = term.split_once("!=") {
+                Expression::NotEqual(key.trim().to_string(), value.trim().to_string())
+            } else if let Some((key, value)) = term.split_once("==").or_else(|| term.split_once('=')) {
+                Expression::Equal(key.trim().to_string(), value.trim().to_string())
+            } else if let Some(key) = term.strip_prefix('!') {
+                Expression::DoesNotExist(key.trim().to_string())
+            } else {
+                Expression::Exists(term.to_string())
+            })
+        })
+        .collect()
+}
+
+/// A term of a field selector, like `metadata.name=web` or `status.phase!=Running`
+struct FieldTerm {
+    path: String,
+    value: String,
+    equal: bool,
+}
+
+impl FieldTerm {
+    fn matches(&self, obj: &Value) -> bool {
+        let field = self.path.split('.').try_fold(obj, |obj, key| obj.get(key));
+        (field.and_then(Value::as_str).unwrap_or_default() == self.value) == self.equal
+    }
+}
+
+fn parse_field_selector(selector: &str) -> Result<Vec<FieldTerm>, Failure> {
+    selector_terms(selector)
+        .into_iter()
+        .map(|term| {
+            let (path, value, equal) = if let Some((path, value)) = term.split_once("!=") {
+                (path, value, false)
+            } else if let Some((path, value)) = term.split_once("==").or_else(|| term.split_once('=')) {
+                (path, value, true)
+            } else {
+                return Err(Failure::invalid(format!("invalid field selector {selector:?}")));
+            };
+            Ok(FieldTerm {
+                path: path.trim().to_string(),
+                value: value.trim().to_string(),
+                equal,
+            })
+        })
+        .collect()
+}
+
+fn json_response<T: Serialize>(status: StatusCode, body: &T) -> Response<Bytes> {
+    let body = serde_json::to_vec(body).expect("serializable fake response");
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Bytes::from(body))
+        .expect("valid fake response")
+}
+
+#[derive(Clone)]
+struct FakeService(FakeCluster);
+
+impl Service<Request<Body>> for FakeService {
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<Response<Body>, BoxError>>;
+    type Response = Response<Body>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let cluster = self.0.clone();
+        Box::pin(async move {
+            let (parts, body) = req.into_parts();
+            let content_type = parts
+                .headers
+                .get(CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default()
+                .to_string();
+            let body = body.collect_bytes().await?;
+            Ok(cluster
+                .respond(&parts.method, &parts.uri, &content_type, &body)
+                .map(Body::from))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use k8s_openapi::api::core::v1::{ConfigMap, Pod};
+
+    use crate::{
+        api::{DeleteParams, ListParams, ObjectMeta, Patch, PatchParams, PostParams},
+        Api, Error,
+    };
+
+    fn config_map(name: &str, labels: &[(&str, &str)]) -> ConfigMap {
+        ConfigMap {
+            metadata: ObjectMeta {
+                name: Some(name.into()),
+                namespace: Some("default".into()),
+                labels: Some(labels.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()),
+                ..ObjectMeta::default()
+            },
+            ..ConfigMap::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn serves_and_records_changes() {
+        let (client, cluster) = Client::fake();
+        cluster.insert(&config_map("web", &[("app", "web")]));
+        cluster.insert(&config_map("db", &[("app", "db")]));
+        let api: Api<ConfigMap> = Api::default_namespaced(client.clone());
+
+        let web = api.list(&ListParams::default().labels("app in (web,proxy)")).await.unwrap();
+        assert_eq!(web.items.len(), 1);
+        assert_eq!(web.items[0].metadata.name.as_deref(), Some("web"));
+        let db = api.list(&ListParams::default().fields("metadata.name!=web")).await.unwrap();
+        assert_eq!(db.items[0].metadata.name.as_deref(), Some("db"));
+
+        let created = api.create(&PostParams::default(), &config_map("cache", &[])).await.unwrap();
+        assert!(created.metadata.uid.is_some());
+        let err = api.create(&PostParams::default(), &config_map("cache", &[])).await.unwrap_err();
+        assert!(matches!(err, Error::Api(ref e) if e.code == 409 && e.reason == "AlreadyExists"));
+
+        // an outdated resourceVersion conflicts, unchanged patches are not recorded
+        let mut stale = created.clone();
+        stale.metadata.resource_version = Some("1".into());
+        let err = api.replace("cache", &PostParams::default(), &stale).await.unwrap_err();
+        assert!(matches!(err, Error::Api(ref e) if e.code == 409));
+        let patch = serde_json::json!({ "data": { "size": "1Gi" } });
+        for _ in 0..2 {
+            api.patch("cache", &PatchParams::default(), &Patch::Merge(&patch)).await.unwrap();
+        }
+        assert_eq!(cluster.take_mutations().len(), 2);
+
+        // finalizers keep deleted objects around until they are removed
+        let finalizers = serde_json::json!({ "metadata": { "finalizers": ["test"] } });
+        api.patch("cache", &PatchParams::default(), &Patch::Merge(&finalizers)).await.unwrap();
+        api.delete("cache", &DeleteParams::default()).await.unwrap();
+        let deleting = cluster.get::<ConfigMap>(Some("default"), "cache").unwrap();
+        assert!(deleting.metadata.deletion_timestamp.is_some());
+        let finalizers = serde_json::json!({ "metadata": { "finalizers": null } });
+        api.patch("cache", &PatchParams::default(), &Patch::Merge(&finalizers)).await.unwrap();
+        assert!(cluster.get::<ConfigMap>(Some("default"), "cache").is_none());
+        let mutations = cluster.take_mutations();
+        assert_eq!(mutations.len(), 3);
+        assert!(mutations[2].is_delete());
+        assert_eq!(mutations[2].kind, "ConfigMap");
+
+        let pods: Api<Pod> = Api::default_namespaced(client);
+        let err = pods.get("web").await.unwrap_err();
+        assert!(matches!(err, Error::Api(ref e) if e.code == 404));
+    }
+
+    #[tokio::test]
+    async fn status_is_only_changed_through_the_subresource() {
+        let (client, cluster) = Client::fake();
+        cluster
+            .load_yaml(
+                r#"
+apiVersion: v1
+kind: Pod
+metadata: { name: web, namespace: default }
+spec: { containers: [{ name: web, image: nginx }] }
+status: { phase: Pending }
+"#,
+            )
+            .unwrap();
+        let api: Api<Pod> = Api::default_namespaced(client);
+
+        let patch = serde_json::json!({
+            "spec": { "activeDeadlineSeconds": 10 },
+            "status": { "phase": "Running" },
+        });
+        let pod = api.patch("web", &PatchParams::default(), &Patch::Merge(&patch)).await.unwrap();
+        assert_eq!(pod.status.unwrap().phase.as_deref(), Some("Pending"));
+        assert_eq!(pod.metadata.generation, Some(2));
+
+        let pod = api.patch_status("web", &PatchParams::default(), &Patch::Merge(&patch)).await.unwrap();
+        assert_eq!(pod.status.unwrap().phase.as_deref(), Some("Running"));
+        assert_eq!(pod.metadata.generation, Some(2));
+    }
+}
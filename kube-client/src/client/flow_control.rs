@@ -0,0 +1,119 @@
+//! API Priority and Fairness metadata of responses
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use http::{header::RETRY_AFTER, HeaderMap, Response, StatusCode};
+
+/// Header with the UID of the FlowSchema a request was classified into
+pub const FLOW_SCHEMA_UID: &str = "x-kubernetes-pf-flowschema-uid";
+/// Header with the UID of the PriorityLevelConfiguration a request was assigned to
+pub const PRIORITY_LEVEL_UID: &str = "x-kubernetes-pf-prioritylevel-uid";
+
+/// How the apiserver's [API Priority and Fairness](https://kubernetes.io/docs/concepts/cluster-administration/flow-control/)
+/// handled a request
+///
+/// Look up the UIDs among the `flowschemas` and `prioritylevelconfigurations` of the
+/// `flowcontrol.apiserver.k8s.io` group to find out which flow a client's traffic lands in.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FlowControl {
+    /// The UID of the FlowSchema the request matched
+    pub flow_schema_uid: Option<String>,
+    /// The UID of the PriorityLevelConfiguration the request was queued in
+    pub priority_level_uid: Option<String>,
+    /// Whether the request was rejected with `429 Too Many Requests`
+    pub throttled: bool,
+    /// How long the apiserver asked to wait before retrying a throttled request
+    pub retry_after: Option<Duration>,
+}
+
+impl FlowControl {
+    /// Read the flow control metadata of a response
+    ///
+    /// Returns `None` for responses that were not handled by API Priority and Fairness.
+    pub fn from_response<B>(res: &Response<B>) -> Option<Self> {
+        Self::from_parts(res.status(), res.headers())
+    }
+
+    fn from_parts(status: StatusCode, headers: &HeaderMap) -> Option<Self> {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(ToString::to_string)
+        };
+        let flow_schema_uid = header(FLOW_SCHEMA_UID);
+        let priority_level_uid = header(PRIORITY_LEVEL_UID);
+        if flow_schema_uid.is_none() && priority_level_uid.is_none() {
+            return None;
+        }
+        let throttled = status == StatusCode::TOO_MANY_REQUESTS;
+        let retry_after = headers
+            .get(RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse().ok())
+            .map(Duration::from_secs)
+            .filter(|_| throttled);
+        Some(Self {
+            flow_schema_uid,
+            priority_level_uid,
+            throttled,
+            retry_after,
+        })
+    }
+}
+
+/// The most recent flow control metadata seen by a client, shared between clones
+#[derive(Clone, Default)]
+pub(crate) struct LastFlowControl(Arc<Mutex<Option<FlowControl>>>);
+
+impl LastFlowControl {
+    pub(crate) fn observe<B>(&self, res: &Response<B>) {
+        let Some(flow_control) = FlowControl::from_response(res) else {
+            return;
+        };
+        if flow_control.throttled {
+            tracing::warn!(
+                flow_schema_uid = flow_control.flow_schema_uid.as_deref(),
+                priority_level_uid = flow_control.priority_level_uid.as_deref(),
+                "request throttled by API Priority and Fairness"
+            );
+        }
+        *self.0.lock().expect("flow control poisoned") = Some(flow_control);
+    }
+
+    pub(crate) fn get(&self) -> Option<FlowControl> {
+        self.0.lock().expect("flow control poisoned").clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_flow_control_headers() {
+        let plain = Response::builder().body(()).unwrap();
+        assert_eq!(FlowControl::from_response(&plain), None);
+
+        let throttled = Response::builder()
+            .status(StatusCode::TOO_MANY_REQUESTS)
+            .header(FLOW_SCHEMA_UID, "fs-uid")
+            .header(PRIORITY_LEVEL_UID, "pl-uid")
+            .header(RETRY_AFTER, "2")
+            .body(())
+            .unwrap();
+        let last = LastFlowControl::default();
+        last.observe(&throttled);
+        assert_eq!(
+            last.get(),
+            Some(FlowControl {
+                flow_schema_uid: Some("fs-uid".into()),
+                priority_level_uid: Some("pl-uid".into()),
+                throttled: true,
+                retry_after: Some(Duration::from_secs(2)),
+            })
+        );
+    }
+}
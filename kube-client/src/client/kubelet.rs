@@ -0,0 +1,87 @@
+use crate::{client::AsyncBufRead, Client, Error, Result};
+use k8s_openapi::api::core::v1::Pod;
+use kube_core::{kubelet::Summary, ObjectList, Request};
+
+/// Methods to read the pods, stats and logs of a node directly from its `kubelet`
+///
+/// The client has to point at the kubelet rather than the apiserver, see [`Config::kubelet`](crate::Config::kubelet).
+/// The kubelet authorizes these through the apiserver, so the credentials need `get` access to the
+/// `nodes/proxy`, `nodes/stats` and `nodes/log` subresources, like a monitoring daemon would have.
+///
+/// ```no_run
+/// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+/// use kube::{Client, Config};
+///
+/// let config = Config::incluster()?.kubelet(&std::env::var("NODE_IP")?)?;
+/// let kubelet = Client::try_from(config)?;
+/// let summary = kubelet.kubelet_stats_summary(true).await?;
+/// for pod in summary.pods {
+///     let memory = pod.memory.and_then(|m| m.working_set_bytes);
+///     println!("{}/{}: {memory:?}", pod.pod_ref.namespace, pod.pod_ref.name);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+impl Client {
+    /// List the pods the kubelet is running, including static pods
+    pub async fn kubelet_pods(&self) -> Result<ObjectList<Pod>> {
+        let mut req = Request::kubelet_pods().map_err(Error::BuildRequest)?;
+        req.extensions_mut().insert("kubelet_pods");
+        self.request(req).await
+    }
+
+    /// Get the resource usage of the node and its pods
+    ///
+    /// With `only_cpu_and_memory`, the kubelet skips the more expensive network and filesystem stats.
+    pub async fn kubelet_stats_summary(&self, only_cpu_and_memory: bool) -> Result<Summary> {
+        let mut req = Request::kubelet_stats_summary(only_cpu_and_memory).map_err(Error::BuildRequest)?;
+        req.extensions_mut().insert("kubelet_stats_summary");
+        self.request(req).await
+    }
+
+    /// Stream a file from the log directory of the node, or its listing for an empty `path`
+    ///
+    /// `path` is relative to `/var/log` on the node, e.g. `pods/` or `syslog`.
+    pub async fn kubelet_logs(&self, path: &str) -> Result<impl AsyncBufRead> {
+        let mut req = Request::kubelet_logs(path).map_err(Error::BuildRequest)?;
+        req.extensions_mut().insert("kubelet_logs");
+        self.request_stream(req).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Client;
+    use futures::AsyncReadExt;
+    use http::{Method, StatusCode};
+    use kube_core::kubelet::{NodeStats, Summary};
+
+    #[tokio::test]
+    async fn kubelet_endpoints_are_requested_from_the_client_root() {
+        let (client, mock) = Client::mock();
+        let summary = Summary {
+            node: NodeStats {
+                node_name: "worker".into(),
+                ..NodeStats::default()
+            },
+            pods: vec![],
+        };
+        mock.expect(Method::GET, "/stats/summary")
+            .respond_json(StatusCode::OK, &summary);
+        mock.expect(Method::GET, "/logs/syslog").respond_with(
+            http::Response::builder()
+                .status(StatusCode::OK)
+                .body(b"Mar  6 06:06:57 worker kubelet".to_vec())
+                .unwrap(),
+        );
+
+        assert_eq!(client.kubelet_stats_summary(true).await.unwrap(), summary);
+        let mut syslog = String::new();
+        let mut logs = std::pin::pin!(client.kubelet_logs("syslog").await.unwrap());
+        logs.read_to_string(&mut syslog).await.unwrap();
+        assert!(syslog.ends_with("kubelet"));
+
+        let uris: Vec<_> = mock.requests().into_iter().map(|r| r.uri.to_string()).collect();
+        assert_eq!(uris, ["/stats/summary?only_cpu_and_memory=true", "/logs/syslog"]);
+    }
+}
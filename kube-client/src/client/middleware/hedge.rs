@@ -0,0 +1,193 @@
+use std::{pin::pin, time::Duration};
+
+use futures::future::{select, BoxFuture, Either};
+use http::{Method, Request, Response};
+use tower::{buffer::Buffer, util::BoxService, BoxError, Layer, Service, ServiceExt};
+
+use super::{is_upgrade, is_watch, try_clone_request};
+use crate::client::Body;
+
+/// Layer that hedges slow reads by sending a duplicate request
+///
+/// When a `GET` request has not completed after the configured delay, an identical request is sent
+/// and whichever response arrives first is returned. Behind a load balancer in front of several
+/// apiservers the duplicate usually lands on a different instance, which cuts down tail latency
+/// for latency sensitive lookups such as those done by admission webhooks.
+///
+/// Only plain reads with a bounded response are hedged. Watches, followed logs and connection
+/// upgrades (for exec, attach and port-forward) are sent once. The delay should be well above the
+/// typical latency of the requests, since every hedged request adds load to the apiserver.
+///
+/// ```no_run
+/// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+/// use std::time::Duration;
+/// use kube::{client::{middleware::HedgeLayer, ClientBuilder}, Client, Config};
+///
+/// let config = Config::infer().await?;
+/// let client: Client = ClientBuilder::try_from(config)?
+///     .with_layer(&HedgeLayer::new(Duration::from_millis(200)))
+///     .build();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct HedgeLayer {
+    delay: Duration,
+}
+
+impl HedgeLayer {
+    /// Hedge reads that take longer than `delay`
+    pub fn new(delay: Duration) -> Self {
+        Self { delay }
+    }
+}
+
+impl<S, B> Layer<S> for HedgeLayer
+where
+    S: Service<Request<Body>, Response = Response<B>> + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Into<BoxError>,
+    B: Send + 'static,
+{
+    type Service = Hedge<Response<B>>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Hedge {
+            // `Buffer` allows sending the duplicate through the same inner service
+            inner: Buffer::new(BoxService::new(inner.map_err(Into::into)), 1024),
+            delay: self.delay,
+        }
+    }
+}
+
+/// Service that hedges slow reads by sending a duplicate request
+pub struct Hedge<Res> {
+    inner: Buffer<Request<Body>, BoxFuture<'static, Result<Res, BoxError>>>,
+    delay: Duration,
+}
+
+impl<Res> Clone for Hedge<Res> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            delay: self.delay,
+        }
+    }
+}
+
+impl<B> Service<Request<Body>> for Hedge<Response<B>>
+where
+    B: Send + 'static,
+{
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<Response<B>, BoxError>>;
+    type Response = Response<B>;
+
+    fn poll_ready(&mut self, _cx: &mut std::task::Context<'_>) -> std::task::Poll<Result<(), Self::Error>> {
+        // readiness of the buffer is awaited for each copy of the request in `call`
+        std::task::Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let primary = self.inner.clone();
+        let Some(hedge) = hedged_copy(&req) else {
+            return Box::pin(primary.oneshot(req));
+        };
+        let secondary = self.inner.clone();
+        let delay = self.delay;
+        Box::pin(async move {
+            let primary = pin!(primary.oneshot(req));
+            let secondary = pin!(async move {
+                tokio::time::sleep(delay).await;
+                tracing::debug!(?delay, "hedging slow request");
+                secondary.oneshot(hedge).await
+            });
+            // prefer the first success, but fall back to the other request when the first one fails
+            match select(primary, secondary).await {
+                Either::Left((Ok(res), _)) | Either::Right((Ok(res), _)) => Ok(res),
+                Either::Left((Err(_), other)) => other.await,
+                Either::Right((Err(_), other)) => other.await,
+            }
+        })
+    }
+}
+
+/// Copy a request that is safe to hedge
+fn hedged_copy(req: &Request<Body>) -> Option<Request<Body>> {
+    if req.method() != Method::GET || is_watch(req) || is_follow(req) || is_upgrade(req) {
+        return None;
+    }
+    try_clone_request(req)
+}
+
+/// Whether the request follows a log, which streams until the container exits
+fn is_follow<B>(req: &Request<B>) -> bool {
+    req.uri()
+        .query()
+        .is_some_and(|q| q.split('&').any(|pair| pair == "follow=true"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use http::{header, StatusCode};
+    use tower_test::mock;
+
+    #[tokio::test]
+    async fn slow_reads_are_hedged() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let mut service = HedgeLayer::new(Duration::from_millis(10)).layer(mock_service);
+        let spawned = tokio::spawn(async move {
+            let mut handle = pin!(handle);
+            // keep the first request hanging
+            let (_, _slow) = handle.next_request().await.expect("service not called");
+            let (request, send) = handle.next_request().await.expect("request not hedged");
+            assert_eq!(request.uri(), "/api/v1/namespaces/ns/pods/blog");
+            send.send_response(
+                Response::builder()
+                    .status(StatusCode::OK)
+                    .body(Body::empty())
+                    .unwrap(),
+            );
+            // ensure the hanging request stays pending until the response is received
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        });
+
+        let req = Request::get("/api/v1/namespaces/ns/pods/blog")
+            .body(Body::empty())
+            .unwrap();
+        let res = service.ready().await.unwrap().call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        spawned.await.unwrap();
+    }
+
+    #[test]
+    fn only_plain_reads_are_hedged() {
+        let get = Request::get("/api/v1/pods?limit=5").body(Body::empty()).unwrap();
+        assert!(hedged_copy(&get).is_some());
+        let watch = Request::get("/api/v1/pods?watch=true&resourceVersion=1")
+            .body(Body::empty())
+            .unwrap();
+        assert!(hedged_copy(&watch).is_none());
+        let follow = Request::get("/api/v1/namespaces/ns/pods/blog/log?follow=true")
+            .body(Body::empty())
+            .unwrap();
+        assert!(hedged_copy(&follow).is_none());
+        let exec = Request::get("/api/v1/namespaces/ns/pods/blog/exec?command=sh")
+            .header(header::CONNECTION, "keep-alive, Upgrade")
+            .header(header::UPGRADE, "websocket")
+            .body(Body::empty())
+            .unwrap();
+        assert!(hedged_copy(&exec).is_none());
+        let upgrade = Request::get("/api/v1/namespaces/ns/pods/blog/attach")
+            .header(header::CONNECTION, "upgrade")
+            .body(Body::empty())
+            .unwrap();
+        assert!(hedged_copy(&upgrade).is_none());
+        let delete = Request::delete("/api/v1/namespaces/ns/pods/blog")
+            .body(Body::empty())
+            .unwrap();
+        assert!(hedged_copy(&delete).is_none());
+    }
+}
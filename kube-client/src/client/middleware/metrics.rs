@@ -0,0 +1,225 @@
+use std::time::Instant;
+
+use futures::future::BoxFuture;
+use http::{Request, Response};
+use prometheus::{HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry};
+use tower::{BoxError, Layer, Service};
+
+use crate::client::builder::ResourcePath;
+
+/// Prometheus metrics of the requests sent by a [`Client`](crate::Client)
+///
+/// All metrics are labeled with the `verb` of the [`Api`](crate::Api) method, or `request` for
+/// requests made directly through the client, and the `resource`, e.g. `deployments.apps`:
+///
+/// - `kube_client_requests_total`: requests sent
+/// - `kube_client_request_errors_total`: failed requests, additionally labeled with the HTTP `status`
+///   or `error` when no response was received
+/// - `kube_client_request_duration_seconds`: time until the response headers were received
+///
+/// The metrics are shared between clones, so the same metrics can be used for several clients.
+///
+/// ```no_run
+/// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+/// use kube::{client::{middleware::ClientMetrics, ClientBuilder}, Client, Config};
+/// let registry = prometheus::Registry::new();
+/// let metrics = ClientMetrics::new();
+/// metrics.register(&registry)?;
+///
+/// let config = Config::infer().await?;
+/// let client: Client = ClientBuilder::try_from(config)?.with_metrics(metrics).build();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ClientMetrics {
+    requests: IntCounterVec,
+    errors: IntCounterVec,
+    duration: HistogramVec,
+}
+
+impl Default for ClientMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClientMetrics {
+    /// Create a new set of metrics
+    pub fn new() -> Self {
+        let requests = IntCounterVec::new(
+            Opts::new("kube_client_requests_total", "Number of requests sent to the apiserver"),
+            &["verb", "resource"],
+        )
+        .expect("valid metric");
+        let errors = IntCounterVec::new(
+            Opts::new("kube_client_request_errors_total", "Number of failed requests to the apiserver"),
+            &["verb", "resource", "status"],
+        )
+        .expect("valid metric");
+        let duration = HistogramVec::new(
+            HistogramOpts::new(
+                "kube_client_request_duration_seconds",
+                "Time until the apiserver responded to a request",
+            ),
+            &["verb", "resource"],
+        )
+        .expect("valid metric");
+        Self {
+            requests,
+            errors,
+            duration,
+        }
+    }
+
+    /// Register the metrics with `registry`
+    pub fn register(&self, registry: &Registry) -> prometheus::Result<()> {
+        registry.register(Box::new(self.requests.clone()))?;
+        registry.register(Box::new(self.errors.clone()))?;
+        registry.register(Box::new(self.duration.clone()))?;
+        Ok(())
+    }
+}
+
+/// Layer that records [`ClientMetrics`] of requests
+#[derive(Clone, Debug)]
+pub struct MetricsLayer {
+    metrics: ClientMetrics,
+}
+
+impl MetricsLayer {
+    /// Record request metrics in `metrics`
+    pub fn new(metrics: ClientMetrics) -> Self {
+        Self { metrics }
+    }
+}
+
+impl<S> Layer<S> for MetricsLayer {
+    type Service = Metrics<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Metrics {
+            inner,
+            metrics: self.metrics.clone(),
+        }
+    }
+}
+
+/// Service that records [`ClientMetrics`] of requests
+#[derive(Clone, Debug)]
+pub struct Metrics<S> {
+    inner: S,
+    metrics: ClientMetrics,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for Metrics<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    S::Error: Into<BoxError>,
+    S::Future: Send + 'static,
+{
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<S::Response, BoxError>>;
+    type Response = S::Response;
+
+    fn poll_ready(&mut self, cx: &mut std::task::Context<'_>) -> std::task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let verb = req.extensions().get::<&'static str>().copied().unwrap_or("request");
+        let resource = resource_label(req.uri().path());
+        let metrics = self.metrics.clone();
+        metrics.requests.with_label_values(&[verb, &resource]).inc();
+        let start = Instant::now();
+        let fut = self.inner.call(req);
+        Box::pin(async move {
+            let res = fut.await.map_err(Into::into);
+            metrics
+                .duration
+                .with_label_values(&[verb, &resource])
+                .observe(start.elapsed().as_secs_f64());
+            let status = match &res {
+                Ok(res) if res.status().is_client_error() || res.status().is_server_error() => {
+                    Some(res.status().as_str().to_string())
+                }
+                Ok(_) => None,
+                Err(_) => Some("error".to_string()),
+            };
+            if let Some(status) = status {
+                metrics.errors.with_label_values(&[verb, &resource, &status]).inc();
+            }
+            res
+        })
+    }
+}
+
+/// The resource of a request path in `kubectl` notation, e.g. `deployments.apps`
+fn resource_label(path: &str) -> String {
+    match ResourcePath::parse(path).unwrap_or_default() {
+        ResourcePath {
+            resource: Some(resource),
+            group: Some(group),
+            ..
+        } if !group.is_empty() => format!("{resource}.{group}"),
+        ResourcePath {
+            resource: Some(resource),
+            ..
+        } => resource.to_string(),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::pin::pin;
+
+    use http::StatusCode;
+    use tower::ServiceExt;
+    use tower_test::mock;
+
+    use crate::client::Body;
+
+    #[test]
+    fn resource_labels() {
+        assert_eq!(resource_label("/api/v1/namespaces/ns/pods/web/log"), "pods");
+        assert_eq!(resource_label("/apis/apps/v1/deployments"), "deployments.apps");
+        assert_eq!(resource_label("/version"), "");
+    }
+
+    #[tokio::test]
+    async fn records_requests_and_errors() {
+        let metrics = ClientMetrics::new();
+        let registry = Registry::new();
+        metrics.register(&registry).unwrap();
+
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let mut service = MetricsLayer::new(metrics.clone()).layer(mock_service);
+        let spawned = tokio::spawn(async move {
+            let mut handle = pin!(handle);
+            for status in [StatusCode::OK, StatusCode::NOT_FOUND] {
+                let (_, send) = handle.next_request().await.expect("service not called");
+                send.send_response(Response::builder().status(status).body(Body::empty()).unwrap());
+            }
+        });
+
+        for _ in 0..2 {
+            let mut req = Request::get("/api/v1/namespaces/ns/pods/web")
+                .body(Body::empty())
+                .unwrap();
+            req.extensions_mut().insert("get");
+            service.ready().await.unwrap().call(req).await.unwrap();
+        }
+        spawned.await.unwrap();
+
+        assert_eq!(metrics.requests.with_label_values(&["get", "pods"]).get(), 2);
+        assert_eq!(metrics.errors.with_label_values(&["get", "pods", "404"]).get(), 1);
+        assert_eq!(
+            metrics.duration.with_label_values(&["get", "pods"]).get_sample_count(),
+            2
+        );
+        assert_eq!(registry.gather().len(), 3);
+    }
+}
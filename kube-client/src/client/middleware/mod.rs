@@ -3,12 +3,61 @@ use tower::{filter::AsyncFilterLayer, util::Either, Layer};
 pub(crate) use tower_http::auth::AddAuthorizationLayer;
 
 mod base_uri;
+#[cfg(feature = "gzip")] mod compression;
 mod extra_headers;
+mod hedge;
+#[cfg(feature = "prometheus")] mod metrics;
+mod rate_limit;
+mod retry;
+mod signer;
+mod timeout;
 
 pub use base_uri::{BaseUri, BaseUriLayer};
+#[cfg(feature = "gzip")]
+#[cfg_attr(docsrs, doc(cfg(feature = "gzip")))]
+pub use compression::{RequestCompression, RequestCompressionLayer};
 pub use extra_headers::{ExtraHeaders, ExtraHeadersLayer};
+pub use hedge::{Hedge, HedgeLayer};
+#[cfg(feature = "prometheus")]
+#[cfg_attr(docsrs, doc(cfg(feature = "prometheus")))]
+pub use metrics::{ClientMetrics, Metrics, MetricsLayer};
+pub use rate_limit::{RateLimit, RateLimitLayer};
+pub use retry::{Retry, RetryLayer, RetryPolicy};
+pub use signer::{RequestSigner, Signer, SignerLayer};
+pub use timeout::{RequestTimeout, Timeout, TimeoutLayer};
+
+use super::{auth::RefreshableToken, Body};
+
+/// Copy a request with a buffered body so it can be sent again
+pub(crate) fn try_clone_request(req: &http::Request<Body>) -> Option<http::Request<Body>> {
+    let mut clone = http::Request::new(req.body().try_clone()?);
+    *clone.method_mut() = req.method().clone();
+    *clone.uri_mut() = req.uri().clone();
+    *clone.version_mut() = req.version();
+    *clone.headers_mut() = req.headers().clone();
+    *clone.extensions_mut() = req.extensions().clone();
+    Some(clone)
+}
+
+/// Whether the request is a watch, which stays open for a long time
+pub(crate) fn is_watch<B>(req: &http::Request<B>) -> bool {
+    req.uri()
+        .query()
+        .is_some_and(|q| q.split('&').any(|pair| pair == "watch=true"))
+}
+
+/// Whether the request asks to upgrade the connection, e.g. to a websocket
+pub(crate) fn is_upgrade<B>(req: &http::Request<B>) -> bool {
+    let headers = req.headers();
+    headers.contains_key(http::header::UPGRADE)
+        || headers
+            .get_all(http::header::CONNECTION)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .any(|token| token.trim().eq_ignore_ascii_case("upgrade"))
+}
 
-use super::auth::RefreshableToken;
 /// Layer to set up `Authorization` header depending on the config.
 pub struct AuthLayer(pub(crate) Either<AddAuthorizationLayer, AsyncFilterLayer<RefreshableToken>>);
 
@@ -38,7 +87,7 @@ mod tests {
     use tower_test::{mock, mock::Handle};
 
     use crate::{
-        client::{AuthError, Body},
+        client::{auth::ExecToken, AuthError, Body},
         config::AuthInfo,
     };
 
@@ -86,6 +135,41 @@ mod tests {
         ));
     }
 
+    #[derive(Debug)]
+    struct StaticProvider;
+
+    impl crate::client::AuthProvider for StaticProvider {
+        fn token(&self) -> futures::future::BoxFuture<'_, Result<HeaderValue, tower::BoxError>> {
+            Box::pin(async { Ok(HeaderValue::from_static("Negotiate ticket")) })
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn credential_provider_sets_the_header() {
+        use crate::client::ConfigExt;
+        let config = crate::Config::new("http://localhost:8080".parse().unwrap());
+        let config = config.credential_provider(StaticProvider);
+        let layer = config.auth_layer().unwrap().expect("auth layer");
+        let (mut service, handle): (_, Handle<Request<Body>, Response<Body>>) = mock::spawn_layer(layer);
+
+        let spawned = tokio::spawn(async move {
+            let mut handle = pin!(handle);
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(
+                request.headers().get(AUTHORIZATION).unwrap(),
+                HeaderValue::from_static("Negotiate ticket")
+            );
+            send.send_response(Response::builder().body(Body::empty()).unwrap());
+        });
+
+        assert_ready_ok!(service.poll_ready());
+        service
+            .call(Request::builder().uri("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        spawned.await.unwrap();
+    }
+
     fn test_token(token: String) -> RefreshableToken {
         let expiry = Utc::now() + Duration::try_seconds(60 * 60).unwrap();
         let secret_token = SecretString::from(token);
@@ -93,6 +177,11 @@ mod tests {
             token: Some(secret_token.clone()),
             ..Default::default()
         };
-        RefreshableToken::Exec(Arc::new(Mutex::new((secret_token, expiry, info))))
+        RefreshableToken::Exec(Arc::new(Mutex::new(ExecToken {
+            token: secret_token,
+            expiry,
+            auth_info: info,
+            cache: None,
+        })))
     }
 }
@@ -0,0 +1,186 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{ready, Context, Poll},
+    time::Duration,
+};
+
+use http::Request;
+use tokio::time::{Instant, Sleep};
+use tower::{Layer, Service};
+
+/// Layer that limits the rate of requests with a token bucket
+///
+/// This mirrors the `QPS` and `Burst` settings of client-go: up to `burst` requests are sent
+/// immediately, after which requests are delayed so that on average no more than `qps` requests
+/// per second reach the apiserver. All clones of a client share the same bucket.
+///
+/// Usually added through [`ClientBuilder::with_rate_limit`](crate::client::ClientBuilder::with_rate_limit).
+#[derive(Clone, Debug)]
+pub struct RateLimitLayer {
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+impl RateLimitLayer {
+    /// Allow `qps` requests per second on average, and bursts of up to `burst` requests
+    ///
+    /// # Panics
+    /// Panics if `qps` is not a positive number.
+    pub fn new(qps: f32, burst: u32) -> Self {
+        assert!(qps > 0.0, "qps must be positive");
+        let capacity = f64::from(burst.max(1));
+        Self {
+            bucket: Arc::new(Mutex::new(Bucket {
+                tokens: capacity,
+                capacity,
+                rate: f64::from(qps),
+                last: Instant::now(),
+            })),
+        }
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimit<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimit {
+            inner,
+            bucket: self.bucket.clone(),
+            sleep: None,
+            acquired: false,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    capacity: f64,
+    // tokens added per second
+    rate: f64,
+    last: Instant,
+}
+
+impl Bucket {
+    /// Take a token, or return how long to wait until one is available
+    fn try_acquire(&mut self, now: Instant) -> Result<(), Duration> {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.last = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / self.rate))
+        }
+    }
+}
+
+/// Service that limits the rate of requests with a token bucket
+pub struct RateLimit<S> {
+    inner: S,
+    bucket: Arc<Mutex<Bucket>>,
+    sleep: Option<Pin<Box<Sleep>>>,
+    // whether a token has been taken for the next call
+    acquired: bool,
+}
+
+impl<S: Clone> Clone for RateLimit<S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            bucket: self.bucket.clone(),
+            sleep: None,
+            acquired: false,
+        }
+    }
+}
+
+impl<S, ReqBody> Service<Request<ReqBody>> for RateLimit<S>
+where
+    S: Service<Request<ReqBody>>,
+{
+    type Error = S::Error;
+    type Future = S::Future;
+    type Response = S::Response;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        while !self.acquired {
+            if let Some(sleep) = self.sleep.as_mut() {
+                ready!(sleep.as_mut().poll(cx));
+                self.sleep = None;
+            }
+            let acquired = self
+                .bucket
+                .lock()
+                .expect("rate limit bucket poisoned")
+                .try_acquire(Instant::now());
+            match acquired {
+                Ok(()) => self.acquired = true,
+                Err(wait) => self.sleep = Some(Box::pin(tokio::time::sleep(wait))),
+            }
+        }
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        assert!(self.acquired, "poll_ready must be called before call");
+        self.acquired = false;
+        self.inner.call(req)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::pin::pin;
+
+    use http::Response;
+    use tower::ServiceExt;
+    use tower_test::mock;
+
+    use crate::client::Body;
+
+    #[test]
+    fn bucket_refills_over_time() {
+        let start = Instant::now();
+        let mut bucket = Bucket {
+            tokens: 2.0,
+            capacity: 2.0,
+            rate: 10.0,
+            last: start,
+        };
+        assert!(bucket.try_acquire(start).is_ok());
+        assert!(bucket.try_acquire(start).is_ok());
+        let wait = bucket.try_acquire(start).unwrap_err();
+        assert!(wait > Duration::from_millis(99) && wait <= Duration::from_millis(100));
+        assert!(bucket.try_acquire(start + Duration::from_millis(100)).is_ok());
+        // refilling never exceeds the burst
+        let later = start + Duration::from_secs(60);
+        assert!(bucket.try_acquire(later).is_ok());
+        assert!(bucket.try_acquire(later).is_ok());
+        assert!(bucket.try_acquire(later).is_err());
+    }
+
+    #[tokio::test]
+    async fn requests_beyond_burst_are_delayed() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let mut service = RateLimitLayer::new(20.0, 2).layer(mock_service);
+        tokio::spawn(async move {
+            let mut handle = pin!(handle);
+            while let Some((_, send)) = handle.next_request().await {
+                send.send_response(Response::builder().body(Body::empty()).unwrap());
+            }
+        });
+
+        let start = Instant::now();
+        for _ in 0..3 {
+            let req = Request::get("/api/v1/pods").body(Body::empty()).unwrap();
+            service.ready().await.unwrap().call(req).await.unwrap();
+        }
+        assert!(start.elapsed() >= Duration::from_millis(40));
+    }
+}
@@ -0,0 +1,368 @@
+use std::{error::Error as StdError, io::ErrorKind, time::Duration};
+
+use backon::BackoffBuilder;
+use chrono::{DateTime, Utc};
+use futures::future::BoxFuture;
+use http::{header::RETRY_AFTER, HeaderMap, Method, Request, Response, StatusCode};
+use tower::{buffer::Buffer, util::BoxService, BoxError, Layer, Service, ServiceExt};
+
+use super::try_clone_request;
+use crate::client::Body;
+
+/// Retry behavior for [`RetryLayer`]
+///
+/// Only idempotent requests (`GET`, `HEAD`, `OPTIONS`, `PUT`, `DELETE`) with a buffered body are retried.
+/// They are retried when the apiserver responds with `429 Too Many Requests` or `503 Service Unavailable`,
+/// or when the connection fails with a transient IO error.
+///
+/// The delay between attempts grows exponentially, but a `Retry-After` header sent by the apiserver
+/// takes precedence over the computed delay. Both are bounded by [`RetryPolicy::max_delay`].
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    min_delay: Duration,
+    max_delay: Duration,
+    jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            min_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Set the total number of attempts per request, including the first one
+    ///
+    /// A value of `1` disables retries.
+    #[must_use]
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Set the delay before the first retry
+    #[must_use]
+    pub fn min_delay(mut self, min_delay: Duration) -> Self {
+        self.min_delay = min_delay;
+        self
+    }
+
+    /// Set the upper bound for the exponentially growing delay and the `Retry-After` of responses
+    #[must_use]
+    pub fn max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Whether to randomize delays to avoid retrying in lockstep with other clients
+    #[must_use]
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    fn backoff(&self) -> backon::ExponentialBackoff {
+        let builder = backon::ExponentialBuilder::default()
+            .with_min_delay(self.min_delay)
+            .with_max_delay(self.max_delay)
+            .with_factor(2.0)
+            .without_max_times();
+        if self.jitter {
+            builder.with_jitter().build()
+        } else {
+            builder.build()
+        }
+    }
+
+    /// Clone the request if it is allowed to be retried
+    fn try_clone_request(&self, req: &Request<Body>) -> Option<Request<Body>> {
+        if !is_idempotent(req.method()) {
+            return None;
+        }
+        try_clone_request(req)
+    }
+}
+
+/// Layer that retries failed idempotent requests according to a [`RetryPolicy`]
+///
+/// The layer is not part of the default stack and has to be added to the
+/// [`ClientBuilder`](crate::client::ClientBuilder):
+///
+/// ```no_run
+/// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+/// use kube::{client::{middleware::{RetryLayer, RetryPolicy}, ClientBuilder}, Client, Config};
+///
+/// let config = Config::infer().await?;
+/// let client: Client = ClientBuilder::try_from(config)?
+///     .with_layer(&RetryLayer::new(RetryPolicy::default().max_attempts(3)))
+///     .build();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct RetryLayer {
+    policy: RetryPolicy,
+}
+
+impl RetryLayer {
+    /// Create a retry layer using the given policy
+    pub fn new(policy: RetryPolicy) -> Self {
+        Self { policy }
+    }
+}
+
+impl<S, B> Layer<S> for RetryLayer
+where
+    S: Service<Request<Body>, Response = Response<B>> + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Into<BoxError>,
+    B: Send + 'static,
+{
+    type Service = Retry<Response<B>>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Retry {
+            // `Buffer` makes the inner service cheap to clone for the retries
+            inner: Buffer::new(BoxService::new(inner.map_err(Into::into)), 1024),
+            policy: self.policy.clone(),
+        }
+    }
+}
+
+/// Service that retries failed idempotent requests according to a [`RetryPolicy`]
+pub struct Retry<Res> {
+    inner: Buffer<Request<Body>, BoxFuture<'static, Result<Res, BoxError>>>,
+    policy: RetryPolicy,
+}
+
+impl<Res> Clone for Retry<Res> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            policy: self.policy.clone(),
+        }
+    }
+}
+
+impl<B> Service<Request<Body>> for Retry<Response<B>>
+where
+    B: Send + 'static,
+{
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<Response<B>, BoxError>>;
+    type Response = Response<B>;
+
+    fn poll_ready(&mut self, _cx: &mut std::task::Context<'_>) -> std::task::Poll<Result<(), Self::Error>> {
+        // readiness of the buffer is awaited for every attempt in `call`
+        std::task::Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let mut svc = self.inner.clone();
+        let policy = self.policy.clone();
+        Box::pin(async move {
+            let mut backoff = policy.backoff();
+            let mut req = req;
+            let mut attempt = 1;
+            loop {
+                let retry = if attempt < policy.max_attempts {
+                    policy.try_clone_request(&req)
+                } else {
+                    None
+                };
+                let res = svc.ready().await?.call(req).await;
+                let Some(retry) = retry else {
+                    return res;
+                };
+                let delay = match &res {
+                    Ok(res) if is_retryable_status(res.status()) => {
+                        let retry_after = retry_after(res.headers()).map(|d| d.min(policy.max_delay));
+                        retry_after.or_else(|| backoff.next())
+                    }
+                    Err(err) if is_transient(err.as_ref()) => backoff.next(),
+                    _ => None,
+                };
+                let Some(delay) = delay else {
+                    return res;
+                };
+                drop(res);
+                tracing::debug!(attempt, ?delay, "retrying request");
+                tokio::time::sleep(delay).await;
+                req = retry;
+                attempt += 1;
+            }
+        })
+    }
+}
+
+fn is_idempotent(method: &Method) -> bool {
+    [
+        Method::GET,
+        Method::HEAD,
+        Method::OPTIONS,
+        Method::PUT,
+        Method::DELETE,
+    ]
+    .contains(method)
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status == StatusCode::SERVICE_UNAVAILABLE
+}
+
+/// Parse a `Retry-After` header given either in seconds or as an HTTP date
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (date.with_timezone(&Utc) - Utc::now())
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+/// Whether the error is a connection problem that is likely to go away on its own
+fn is_transient(err: &(dyn StdError + 'static)) -> bool {
+    let mut source = Some(err);
+    while let Some(err) = source {
+        if let Some(err) = err.downcast_ref::<std::io::Error>() {
+            return matches!(
+                err.kind(),
+                ErrorKind::ConnectionRefused
+                    | ErrorKind::ConnectionReset
+                    | ErrorKind::ConnectionAborted
+                    | ErrorKind::BrokenPipe
+                    | ErrorKind::TimedOut
+                    | ErrorKind::UnexpectedEof
+            );
+        }
+        if let Some(err) = err.downcast_ref::<hyper::Error>() {
+            if err.is_incomplete_message() || err.is_closed() {
+                return true;
+            }
+        }
+        source = err.source();
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::pin::pin;
+
+    use http::HeaderValue;
+    use tower_test::mock;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy::default()
+            .min_delay(Duration::from_millis(1))
+            .max_delay(Duration::from_millis(1))
+            .jitter(false)
+    }
+
+    fn response(status: StatusCode) -> Response<Body> {
+        Response::builder().status(status).body(Body::empty()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn retries_idempotent_requests_until_success() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let mut service = RetryLayer::new(policy()).layer(mock_service);
+        let spawned = tokio::spawn(async move {
+            let mut handle = pin!(handle);
+            let (_, send) = handle.next_request().await.expect("service not called");
+            let mut throttled = response(StatusCode::TOO_MANY_REQUESTS);
+            throttled
+                .headers_mut()
+                .insert(RETRY_AFTER, HeaderValue::from_static("0"));
+            send.send_response(throttled);
+            let (_, send) = handle.next_request().await.expect("request not retried");
+            send.send_response(response(StatusCode::SERVICE_UNAVAILABLE));
+            let (request, send) = handle.next_request().await.expect("request not retried");
+            assert_eq!(request.uri(), "/api/v1/pods");
+            send.send_response(response(StatusCode::OK));
+        });
+
+        let req = Request::get("/api/v1/pods").body(Body::empty()).unwrap();
+        let res = service.ready().await.unwrap().call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn retry_after_is_bounded_by_max_delay() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let mut service = RetryLayer::new(policy()).layer(mock_service);
+        let spawned = tokio::spawn(async move {
+            let mut handle = pin!(handle);
+            let (_, send) = handle.next_request().await.expect("service not called");
+            let mut throttled = response(StatusCode::TOO_MANY_REQUESTS);
+            throttled
+                .headers_mut()
+                .insert(RETRY_AFTER, HeaderValue::from_static("3600"));
+            send.send_response(throttled);
+            let (_, send) = handle.next_request().await.expect("request not retried");
+            send.send_response(response(StatusCode::OK));
+        });
+
+        let req = Request::get("/api/v1/pods").body(Body::empty()).unwrap();
+        let call = service.ready().await.unwrap().call(req);
+        let res = tokio::time::timeout(Duration::from_secs(5), call).await;
+        assert_eq!(
+            res.expect("Retry-After not bounded").unwrap().status(),
+            StatusCode::OK
+        );
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn respects_max_attempts_and_methods() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let mut service = RetryLayer::new(policy().max_attempts(2)).layer(mock_service);
+        let spawned = tokio::spawn(async move {
+            let mut handle = pin!(handle);
+            for _ in 0..3 {
+                let (_, send) = handle.next_request().await.expect("service not called");
+                send.send_response(response(StatusCode::SERVICE_UNAVAILABLE));
+            }
+        });
+
+        // two attempts for the GET
+        let req = Request::get("/api/v1/pods").body(Body::empty()).unwrap();
+        let res = service.ready().await.unwrap().call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        // a single attempt for the POST
+        let req = Request::post("/api/v1/pods")
+            .body(Body::from(b"{}".to_vec()))
+            .unwrap();
+        let res = service.ready().await.unwrap().call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        spawned.await.unwrap();
+    }
+
+    #[test]
+    fn parses_retry_after() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("3"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(3)));
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("soon"));
+        assert_eq!(retry_after(&headers), None);
+    }
+}
@@ -0,0 +1,177 @@
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use http::Request;
+use tower::{BoxError, Layer, Service};
+
+use super::is_watch;
+use crate::Error;
+
+/// Extra time given to list requests beyond their server side `timeoutSeconds`
+const SERVER_TIMEOUT_GRACE: Duration = Duration::from_secs(5);
+
+/// Per request override of the client timeout
+///
+/// Insert this into the extensions of a request to replace the timeout of the [`TimeoutLayer`]
+/// for that request only. `RequestTimeout(None)` disables the timeout.
+/// [`Api::with_timeout`](crate::Api::with_timeout) sets it for every request of an `Api`.
+///
+/// ```
+/// # use std::time::Duration;
+/// use kube::client::middleware::RequestTimeout;
+/// let mut req = http::Request::get("/api/v1/pods").body(vec![]).unwrap();
+/// req.extensions_mut().insert(RequestTimeout(Some(Duration::from_secs(5))));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RequestTimeout(pub Option<Duration>);
+
+/// Layer that fails requests when no response arrives within a deadline
+///
+/// The deadline covers everything up to the response headers; streaming the body
+/// is bounded by the read timeout of the connection instead.
+///
+/// By default, watches are exempt from the deadline since they are long polls by design,
+/// and list requests with a `timeoutSeconds` parameter (see `ListParams::timeout`) get
+/// at least that long to complete. Either can be overridden with a [`RequestTimeout`].
+///
+/// Set up by the default client from [`Config::timeout`](crate::Config::timeout).
+#[derive(Clone, Debug)]
+pub struct TimeoutLayer {
+    timeout: Option<Duration>,
+}
+
+impl TimeoutLayer {
+    /// Apply `timeout` to requests without a [`RequestTimeout`]
+    pub fn new(timeout: Option<Duration>) -> Self {
+        Self { timeout }
+    }
+}
+
+impl<S> Layer<S> for TimeoutLayer {
+    type Service = Timeout<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Timeout {
+            inner,
+            timeout: self.timeout,
+        }
+    }
+}
+
+/// Service that fails requests when no response arrives within a deadline
+#[derive(Clone, Debug)]
+pub struct Timeout<S> {
+    inner: S,
+    timeout: Option<Duration>,
+}
+
+impl<S, ReqBody> Service<Request<ReqBody>> for Timeout<S>
+where
+    S: Service<Request<ReqBody>>,
+    S::Error: Into<BoxError>,
+    S::Future: Send + 'static,
+{
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<S::Response, BoxError>>;
+    type Response = S::Response;
+
+    fn poll_ready(&mut self, cx: &mut std::task::Context<'_>) -> std::task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let timeout = deadline(&req, self.timeout);
+        let fut = self.inner.call(req);
+        Box::pin(async move {
+            match timeout {
+                Some(timeout) => match tokio::time::timeout(timeout, fut).await {
+                    Ok(res) => res.map_err(Into::into),
+                    Err(_) => Err(Error::Timeout(timeout).into()),
+                },
+                None => fut.await.map_err(Into::into),
+            }
+        })
+    }
+}
+
+/// The timeout to apply to a request
+fn deadline<B>(req: &Request<B>, default: Option<Duration>) -> Option<Duration> {
+    if let Some(RequestTimeout(timeout)) = req.extensions().get() {
+        return *timeout;
+    }
+    if is_watch(req) {
+        return None;
+    }
+    let server_timeout = req
+        .uri()
+        .query()
+        .and_then(|q| q.split('&').find_map(|pair| pair.strip_prefix("timeoutSeconds=")))
+        .and_then(|secs| secs.parse().ok())
+        .map(|secs| Duration::from_secs(secs) + SERVER_TIMEOUT_GRACE);
+    match (default, server_timeout) {
+        (Some(default), Some(server)) => Some(default.max(server)),
+        (default, _) => default,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::pin::pin;
+
+    use http::Response;
+    use tower::ServiceExt;
+    use tower_test::mock;
+
+    use crate::client::Body;
+
+    fn get(uri: &str) -> Request<Body> {
+        Request::get(uri).body(Body::empty()).unwrap()
+    }
+
+    #[test]
+    fn deadline_exempts_watches_and_extends_server_timeouts() {
+        let default = Some(Duration::from_secs(10));
+        assert_eq!(deadline(&get("/api/v1/pods"), default), default);
+        assert_eq!(deadline(&get("/api/v1/pods"), None), None);
+        assert_eq!(
+            deadline(&get("/api/v1/pods?watch=true&timeoutSeconds=290"), default),
+            None
+        );
+        assert_eq!(
+            deadline(&get("/api/v1/pods?timeoutSeconds=60"), default),
+            Some(Duration::from_secs(65))
+        );
+        assert_eq!(deadline(&get("/api/v1/pods?timeoutSeconds=1"), default), default);
+
+        let mut req = get("/api/v1/pods?watch=true");
+        req.extensions_mut()
+            .insert(RequestTimeout(Some(Duration::from_secs(1))));
+        assert_eq!(deadline(&req, default), Some(Duration::from_secs(1)));
+        req.extensions_mut().insert(RequestTimeout(None));
+        assert_eq!(deadline(&req, default), None);
+    }
+
+    #[tokio::test]
+    async fn slow_responses_time_out() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let mut service = TimeoutLayer::new(Some(Duration::from_millis(10))).layer(mock_service);
+        let spawned = tokio::spawn(async move {
+            let mut handle = pin!(handle);
+            let (_, send) = handle.next_request().await.expect("service not called");
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            send.send_response(Response::builder().body(Body::empty()).unwrap());
+        });
+
+        let err = service
+            .ready()
+            .await
+            .unwrap()
+            .call(get("/api/v1/pods"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err.downcast_ref::<Error>(), Some(Error::Timeout(_))));
+        spawned.await.unwrap();
+    }
+}
@@ -0,0 +1,257 @@
+//! A [`Client`] that serves canned responses, for unit tests without a cluster
+//!
+//! Responses are registered on the [`Mock`] handle for a method and path, and are handed out once
+//! each, in the order they were registered. Requests without a matching response are answered with
+//! a `404 Not Found` failure status. Every request that reaches the mock is recorded, so tests can
+//! assert on what was sent.
+//!
+//! ```
+//! # async fn doc() -> Result<(), Box<dyn std::error::Error>> {
+//! use http::{Method, StatusCode};
+//! use k8s_openapi::api::core::v1::ConfigMap;
+//! use kube::{api::PostParams, Api, Client};
+//!
+//! let (client, mock) = Client::mock();
+//! mock.expect(Method::POST, "/api/v1/namespaces/default/configmaps")
+//!     .respond_json(StatusCode::CREATED, &ConfigMap::default());
+//!
+//! let api: Api<ConfigMap> = Api::default_namespaced(client);
+//! api.create(&PostParams::default(), &ConfigMap::default()).await?;
+//!
+//! let sent = mock.requests();
+//! assert_eq!(sent.len(), 1);
+//! assert_eq!(sent[0].json::<ConfigMap>()?, ConfigMap::default());
+//! # Ok(())
+//! # }
+//! ```
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use http::{HeaderMap, Method, Request, Response, StatusCode, Uri};
+use serde::{de::DeserializeOwned, Serialize};
+use tower::{BoxError, Service};
+
+use super::Body;
+use crate::{core::Status, Client};
+
+impl Client {
+    /// Create a [`Client`] backed by a [`Mock`] instead of a cluster
+    ///
+    /// The client uses `default` as its default namespace. See the [`mock`](crate::client::mock) module.
+    pub fn mock() -> (Client, Mock) {
+        let mock = Mock::default();
+        let client = Client::new(MockService(mock.clone()), "default");
+        (client, mock)
+    }
+}
+
+/// Handle for the canned responses and recorded requests of a [`Client::mock`]
+#[derive(Clone, Default)]
+pub struct Mock(Arc<Mutex<State>>);
+
+#[derive(Default)]
+struct State {
+    responses: VecDeque<(Method, String, Response<Bytes>)>,
+    requests: Vec<MockRequest>,
+}
+
+impl Mock {
+    /// Register a response for the next request with `method` to `path`
+    ///
+    /// The path is matched exactly, ignoring any query string.
+    pub fn expect(&self, method: Method, path: impl Into<String>) -> Expectation<'_> {
+        Expectation {
+            mock: self,
+            method,
+            path: path.into(),
+        }
+    }
+
+    /// All requests received so far, in the order they were sent
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.state().requests.clone()
+    }
+
+    /// Whether every registered response has been handed out
+    pub fn is_drained(&self) -> bool {
+        self.state().responses.is_empty()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.0.lock().expect("mock state poisoned")
+    }
+
+    fn respond(&self, req: MockRequest) -> Response<Bytes> {
+        let mut state = self.state();
+        let matched = state
+            .responses
+            .iter()
+            .position(|(method, path, _)| method == req.method && path == req.uri.path());
+        state.requests.push(req.clone());
+        match matched.and_then(|i| state.responses.remove(i)) {
+            Some((_, _, res)) => res,
+            None => {
+                let message = format!("no mock response for {} {}", req.method, req.uri);
+                let status = Status::failure(&message, "NotFound").with_code(404);
+                json_response(StatusCode::NOT_FOUND, &status)
+            }
+        }
+    }
+}
+
+/// A response being registered with [`Mock::expect`]
+#[must_use = "expectations only take effect once a response is set"]
+pub struct Expectation<'a> {
+    mock: &'a Mock,
+    method: Method,
+    path: String,
+}
+
+impl Expectation<'_> {
+    /// Respond with a raw response
+    pub fn respond_with(self, res: Response<Vec<u8>>) {
+        let res = res.map(Bytes::from);
+        self.mock.state().responses.push_back((self.method, self.path, res));
+    }
+
+    /// Respond with `body` serialized as json
+    pub fn respond_json<T: Serialize>(self, status: StatusCode, body: &T) {
+        let res = json_response(status, body).map(Vec::from);
+        self.respond_with(res);
+    }
+
+    /// Respond with a failure [`Status`], like the apiserver does for errors
+    ///
+    /// `reason` is a machine readable reason, e.g. `NotFound` or `Conflict`.
+    pub fn respond_error(self, status: StatusCode, reason: &str, message: &str) {
+        let body = Status::failure(message, reason).with_code(status.as_u16());
+        self.respond_json(status, &body);
+    }
+
+    /// Respond with a watch stream of the given events
+    ///
+    /// Events are usually [`WatchEvent`](crate::api::WatchEvent)s. The stream ends after the last event.
+    pub fn respond_watch<T: Serialize>(self, events: impl IntoIterator<Item = T>) {
+        let mut body = vec![];
+        for event in events {
+            serde_json::to_writer(&mut body, &event).expect("serializable watch event");
+            body.push(b'\n');
+        }
+        self.respond_with(Response::new(body));
+    }
+}
+
+fn json_response<T: Serialize>(status: StatusCode, body: &T) -> Response<Bytes> {
+    let body = serde_json::to_vec(body).expect("serializable mock response");
+    Response::builder()
+        .status(status)
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .body(Bytes::from(body))
+        .expect("valid mock response")
+}
+
+/// A request received by a [`Mock`]
+#[derive(Clone, Debug)]
+pub struct MockRequest {
+    /// The request method
+    pub method: Method,
+    /// The request uri, without the scheme and authority
+    pub uri: Uri,
+    /// The request headers
+    pub headers: HeaderMap,
+    /// The request body
+    pub body: Bytes,
+}
+
+impl MockRequest {
+    /// Deserialize the json body of the request
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        serde_json::from_slice(&self.body)
+    }
+}
+
+#[derive(Clone)]
+struct MockService(Mock);
+
+impl Service<Request<Body>> for MockService {
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<Response<Body>, BoxError>>;
+    type Response = Response<Body>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let mock = self.0.clone();
+        Box::pin(async move {
+            let (parts, body) = req.into_parts();
+            let req = MockRequest {
+                method: parts.method,
+                uri: parts.uri,
+                headers: parts.headers,
+                body: body.collect_bytes().await?,
+            };
+            Ok(mock.respond(req).map(Body::from))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::TryStreamExt;
+    use k8s_openapi::api::core::v1::Pod;
+
+    use crate::{
+        api::{ObjectMeta, WatchEvent, WatchParams},
+        Api, Error,
+    };
+
+    fn pod(name: &str) -> Pod {
+        Pod {
+            metadata: ObjectMeta {
+                name: Some(name.into()),
+                ..ObjectMeta::default()
+            },
+            ..Pod::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn serves_canned_responses_in_order() {
+        let (client, mock) = Client::mock();
+        let path = "/api/v1/namespaces/default/pods/web";
+        mock.expect(Method::GET, path).respond_json(StatusCode::OK, &pod("web"));
+        mock.expect(Method::GET, path)
+            .respond_error(StatusCode::NOT_FOUND, "NotFound", "pods \"web\" not found");
+        mock.expect(Method::GET, "/api/v1/namespaces/default/pods")
+            .respond_watch([WatchEvent::Added(pod("web"))]);
+
+        let api: Api<Pod> = Api::default_namespaced(client);
+        assert_eq!(api.get("web").await.unwrap(), pod("web"));
+        let err = api.get("web").await.unwrap_err();
+        assert!(matches!(err, Error::Api(ref e) if e.code == 404 && e.reason == "NotFound"));
+        let events = api
+            .watch(&WatchParams::default(), "0")
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert!(matches!(events.as_slice(), [WatchEvent::Added(p)] if p == &pod("web")));
+        assert!(mock.is_drained());
+
+        // unmatched requests are recorded and rejected
+        assert!(api.delete("web", &Default::default()).await.is_err());
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 4);
+        assert_eq!(requests[3].method, Method::DELETE);
+    }
+}
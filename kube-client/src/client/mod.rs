@@ -7,14 +7,16 @@
 //!
 //! The [`Client`] can also be used with [`Discovery`](crate::Discovery) to dynamically
 //! retrieve the resources served by the kubernetes API.
+use bytes::Bytes;
 use chrono::{DateTime, Utc};
 use either::{Either, Left, Right};
-use futures::{future::BoxFuture, AsyncBufRead, StreamExt, TryStream, TryStreamExt};
+use futures::{future::BoxFuture, AsyncBufRead, Stream, StreamExt, TryStream, TryStreamExt};
 use http::{self, Request, Response};
 use http_body_util::BodyExt;
 #[cfg(feature = "ws")] use hyper_util::rt::TokioIo;
 use k8s_openapi::apimachinery::pkg::apis::meta::v1 as k8s_meta_v1;
 pub use kube_core::response::Status;
+use kube_core::server::HealthReport;
 use serde::de::DeserializeOwned;
 use serde_json::{self, Value};
 #[cfg(feature = "ws")]
@@ -29,26 +31,35 @@ use tower_http::map_response_body::MapResponseBodyLayer;
 pub use self::body::Body;
 use crate::{api::WatchEvent, error::ErrorResponse, Config, Error, Result};
 
+mod access;
 mod auth;
 mod body;
 mod builder;
 #[cfg_attr(docsrs, doc(cfg(feature = "unstable-client")))]
 #[cfg(feature = "unstable-client")]
 mod client_ext;
+mod client_set;
+pub mod codec;
+mod failover;
+pub mod flow_control;
 #[cfg_attr(docsrs, doc(cfg(feature = "unstable-client")))]
 #[cfg(feature = "unstable-client")]
 pub use client_ext::scope;
 mod config_ext;
-pub use auth::Error as AuthError;
+pub use auth::{AuthProvider, Error as AuthError};
 pub use config_ext::ConfigExt;
+pub mod fake;
 pub mod middleware;
+pub mod mock;
 
 #[cfg(any(feature = "rustls-tls", feature = "openssl-tls"))] mod tls;
 
 #[cfg(feature = "openssl-tls")]
 pub use tls::openssl_tls::Error as OpensslTlsError;
 #[cfg(feature = "rustls-tls")] pub use tls::rustls_tls::Error as RustlsTlsError;
+#[cfg(unix)] mod unix;
 #[cfg(feature = "ws")] mod upgrade;
+pub mod warning;
 
 #[cfg(feature = "oauth")]
 #[cfg_attr(docsrs, doc(cfg(feature = "oauth")))]
@@ -64,7 +75,15 @@ pub use auth::oidc_errors;
 #[cfg_attr(docsrs, doc(cfg(feature = "kubelet-debug")))]
 mod kubelet_debug;
 
-pub use builder::{ClientBuilder, DynBody};
+#[cfg(feature = "kubelet")]
+#[cfg_attr(docsrs, doc(cfg(feature = "kubelet")))]
+mod kubelet;
+
+pub use access::Access;
+pub use builder::{ClientBuilder, ConnectorService, DynBody};
+pub use client_set::ClientSet;
+pub use failover::FailoverConnector;
+#[cfg(unix)] pub use unix::{UnixConnection, UnixConnector};
 
 /// Client for connecting with a Kubernetes cluster.
 ///
@@ -80,6 +99,9 @@ pub struct Client {
     inner: Buffer<Request<Body>, BoxFuture<'static, Result<Response<Body>, BoxError>>>,
     default_ns: String,
     valid_until: Option<DateTime<Utc>>,
+    codecs: codec::Codecs,
+    warnings: warning::Warnings,
+    flow_control: flow_control::LastFlowControl,
 }
 
 /// Represents a WebSocket connection.
@@ -157,6 +179,9 @@ impl Client {
             inner: Buffer::new(BoxService::new(service), 1024),
             default_ns: default_namespace.into(),
             valid_until: None,
+            codecs: codec::Codecs::default(),
+            warnings: warning::Warnings::default(),
+            flow_control: flow_control::LastFlowControl::default(),
         }
     }
 
@@ -165,6 +190,43 @@ impl Client {
         Client { valid_until, ..self }
     }
 
+    /// Registers a [`Codec`](codec::Codec) for an alternative response encoding.
+    ///
+    /// The codec's media type is advertised ahead of JSON in the `Accept` header,
+    /// and responses in that format are transcoded to JSON before deserialization.
+    /// Servers that do not support the media type keep answering with JSON.
+    /// Registering a codec for an already registered media type replaces it.
+    pub fn with_codec(self, codec: impl codec::Codec) -> Self {
+        let codecs = self.codecs.with(std::sync::Arc::new(codec));
+        Client { codecs, ..self }
+    }
+
+    /// Sets a handler for the [`Warning`](warning::Warning)s sent by the apiserver.
+    ///
+    /// By default warnings, such as the use of deprecated APIs, are emitted as `tracing` events at the warn level.
+    ///
+    /// ```no_run
+    /// # async fn doc() -> Result<(), Box<dyn std::error::Error>> {
+    /// use kube::{client::warning::Warning, Client};
+    /// let client = Client::try_default()
+    ///     .await?
+    ///     .with_warning_handler(|w: &Warning| eprintln!("apiserver warning: {w}"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_warning_handler(self, handler: impl warning::WarningHandler) -> Self {
+        let warnings = warning::Warnings::new(std::sync::Arc::new(handler));
+        Client { warnings, ..self }
+    }
+
+    /// The API Priority and Fairness metadata of the most recent response, if the apiserver sent any.
+    ///
+    /// Shared between clones of the client. Useful to find out which flow schema and priority level the
+    /// traffic of a controller lands in, and whether it is being throttled.
+    pub fn last_flow_control(&self) -> Option<flow_control::FlowControl> {
+        self.flow_control.get()
+    }
+
     /// Get the expiration timestamp of the client, if it has been set.
     pub fn valid_until(&self) -> &Option<DateTime<Utc>> {
         &self.valid_until
@@ -200,6 +262,17 @@ impl Client {
         &self.default_ns
     }
 
+    /// Sets the default namespace of the client
+    ///
+    /// The client shares its connections with the client it was derived from, which makes this a cheap
+    /// way to work in another namespace of the same cluster.
+    pub fn with_default_namespace(self, default_namespace: impl Into<String>) -> Self {
+        Client {
+            default_ns: default_namespace.into(),
+            ..self
+        }
+    }
+
     /// Perform a raw HTTP request against the API and return the raw response back.
     /// This method can be used to get raw access to the API which may be used to, for example,
     /// create a proxy server or application-level gateway between localhost and the API server.
@@ -220,6 +293,8 @@ impl Client {
                     // Error from another middleware
                     .unwrap_or_else(Error::Service)
             })?;
+        self.warnings.dispatch(res.headers());
+        self.flow_control.observe(&res);
         Ok(res)
     }
 
@@ -281,11 +356,15 @@ impl Client {
 
     /// Perform a raw HTTP request against the API and get back the response
     /// as a string
-    pub async fn request_text(&self, request: Request<Vec<u8>>) -> Result<String> {
+    pub async fn request_text(&self, mut request: Request<Vec<u8>>) -> Result<String> {
+        let uri = request.uri().clone();
+        self.codecs.negotiate(&uri, request.headers_mut());
         let res = self.send(request.map(Body::from)).await?;
-        let res = handle_api_errors(res).await?;
+        let res = handle_api_errors(&self.codecs, res).await?;
+        let headers = res.headers().clone();
         let body_bytes = res.into_body().collect().await?.to_bytes();
-        let text = String::from_utf8(body_bytes.to_vec()).map_err(Error::FromUtf8)?;
+        let body = self.codecs.decode(&headers, body_bytes.to_vec())?;
+        let text = String::from_utf8(body).map_err(Error::FromUtf8)?;
         Ok(text)
     }
 
@@ -295,13 +374,52 @@ impl Client {
     /// and [`AsyncBufReadExt`](futures::AsyncBufReadExt).
     pub async fn request_stream(&self, request: Request<Vec<u8>>) -> Result<impl AsyncBufRead> {
         let res = self.send(request.map(Body::from)).await?;
-        let res = handle_api_errors(res).await?;
+        let res = handle_api_errors(&self.codecs, res).await?;
         // Map the error, since we want to convert this into an `AsyncBufReader` using
         // `into_async_read` which specifies `std::io::Error` as the stream's error type.
         let body = res.into_body().into_data_stream().map_err(std::io::Error::other);
         Ok(body.into_async_read())
     }
 
+    /// Perform a raw HTTP request against the API and stream the response body as chunks of bytes
+    ///
+    /// This is an escape hatch for endpoints that the typed [`Api`](crate::Api) does not cover, like
+    /// aggregated APIs or proxied paths, which still uses the authentication, TLS and middleware of
+    /// the client. Failure statuses are turned into errors before the stream is returned, like for
+    /// [`Client::request_text`].
+    ///
+    /// ```no_run
+    /// use futures::TryStreamExt;
+    /// # async fn doc(client: kube::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let path = "/api/v1/namespaces/default/services/web:80/proxy/metrics";
+    /// let req = http::Request::get(path).body(vec![])?;
+    /// let mut chunks = std::pin::pin!(client.request_bytes_stream(req).await?);
+    /// while let Some(chunk) = chunks.try_next().await? {
+    ///     println!("got {} bytes", chunk.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn request_bytes_stream(
+        &self,
+        request: Request<Vec<u8>>,
+    ) -> Result<impl Stream<Item = Result<Bytes>>> {
+        let res = self.send(request.map(Body::from)).await?;
+        let res = handle_api_errors(&self.codecs, res).await?;
+        Ok(res.into_body().into_data_stream())
+    }
+
+    /// Perform a raw HTTP request against the API and stream the response body as text
+    ///
+    /// Like [`Client::request_bytes_stream`], but the chunks are decoded as UTF-8. Characters split
+    /// across chunks are held back until they are complete, so every item is valid text.
+    pub async fn request_text_stream(
+        &self,
+        request: Request<Vec<u8>>,
+    ) -> Result<impl Stream<Item = Result<String>>> {
+        Ok(utf8_chunks(self.request_bytes_stream(request).await?))
+    }
+
     /// Perform a raw HTTP request against the API and get back either an object
     /// deserialized as JSON or a [`Status`] Object.
     pub async fn request_status<T>(&self, request: Request<Vec<u8>>) -> Result<Either<T, Status>>
@@ -326,6 +444,9 @@ impl Client {
     }
 
     /// Perform a raw request and get back a stream of [`WatchEvent`] objects
+    ///
+    /// Watch events are only decoded from JSON, since registered codecs are never negotiated for
+    /// `watch=true` requests. Responses in other formats fail with [`Error::Codec`].
     pub async fn request_events<T>(
         &self,
         request: Request<Vec<u8>>,
@@ -336,6 +457,7 @@ impl Client {
         let res = self.send(request.map(Body::from)).await?;
         // trace!("Streaming from {} -> {}", res.url(), res.status().as_str());
         tracing::trace!("headers: {:?}", res.headers());
+        ensure_json_events(res.headers())?;
 
         let frames = FramedRead::new(
             StreamReader::new(res.into_body().into_data_stream().map_err(|e| {
@@ -400,6 +522,20 @@ impl Client {
 /// The following methods might be deprecated to avoid confusion between similarly named types within `discovery`.
 impl Client {
     /// Returns apiserver version.
+    ///
+    /// To gate on features of a release, compare the version with [`ServerVersion`](kube_core::server::ServerVersion):
+    ///
+    /// ```no_run
+    /// # async fn scope(client: kube::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use kube::core::server::ServerVersion;
+    ///
+    /// let info = client.apiserver_version().await?;
+    /// if ServerVersion::parse(&info).is_some_and(|v| v.at_least(1, 30)) {
+    ///     // use ValidatingAdmissionPolicies
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
     pub async fn apiserver_version(&self) -> Result<k8s_openapi::apimachinery::pkg::version::Info> {
         self.request(
             Request::builder()
@@ -410,6 +546,40 @@ impl Client {
         .await
     }
 
+    /// Probes whether the apiserver is alive, from `/livez`.
+    ///
+    /// A failing probe is returned as an unhealthy [`HealthReport`] rather than an error, while
+    /// client errors like `401 Unauthorized` or `403 Forbidden` are returned as [`Error::Api`].
+    pub async fn livez(&self) -> Result<HealthReport> {
+        self.health_probe("/livez?verbose").await
+    }
+
+    /// Probes whether the apiserver is ready to serve requests, from `/readyz`.
+    ///
+    /// A failing probe is returned as an unhealthy [`HealthReport`] rather than an error, while
+    /// client errors like `401 Unauthorized` or `403 Forbidden` are returned as [`Error::Api`].
+    pub async fn readyz(&self) -> Result<HealthReport> {
+        self.health_probe("/readyz?verbose").await
+    }
+
+    async fn health_probe(&self, uri: &str) -> Result<HealthReport> {
+        let req = Request::builder()
+            .uri(uri)
+            .body(Body::empty())
+            .map_err(Error::HttpError)?;
+        let res = self.send(req).await?;
+        // failing checks are reported with a server error, client errors mean the probe was not run
+        let res = if res.status().is_client_error() {
+            handle_api_errors(&self.codecs, res).await?
+        } else {
+            res
+        };
+        let healthy = res.status().is_success();
+        let body = res.into_body().collect_bytes().await?;
+        let text = String::from_utf8(body.to_vec()).map_err(Error::FromUtf8)?;
+        Ok(HealthReport::from_verbose(healthy, &text))
+    }
+
     /// Lists api groups that apiserver serves.
     pub async fn list_api_groups(&self) -> Result<k8s_meta_v1::APIGroupList> {
         self.request(
@@ -474,6 +644,23 @@ impl Client {
     }
 }
 
+/// Reject watch responses that are not JSON, instead of failing to parse each of their events
+fn ensure_json_events(headers: &http::HeaderMap) -> Result<()> {
+    let Some(content_type) = headers
+        .get(http::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+    else {
+        return Ok(());
+    };
+    let media_type = content_type.split(';').next().unwrap_or_default().trim();
+    if media_type.eq_ignore_ascii_case("application/json") {
+        return Ok(());
+    }
+    Err(Error::Codec(
+        format!("watch events can only be decoded from JSON, not {media_type}").into(),
+    ))
+}
+
 /// Kubernetes returned error handling
 ///
 /// Either kube returned an explicit ApiError struct,
@@ -481,24 +668,33 @@ impl Client {
 ///
 /// In either case, present an ApiError upstream.
 /// The latter is probably a bug if encountered.
-async fn handle_api_errors(res: Response<Body>) -> Result<Response<Body>> {
+///
+/// The explicit error is a `Status` in the negotiated format, so it is transcoded
+/// to JSON by `codecs` like any other response.
+async fn handle_api_errors(codecs: &codec::Codecs, res: Response<Body>) -> Result<Response<Body>> {
     let status = res.status();
     if status.is_client_error() || status.is_server_error() {
         // trace!("Status = {:?} for {}", status, res.url());
-        let body_bytes = res.into_body().collect().await?.to_bytes();
-        let text = String::from_utf8(body_bytes.to_vec()).map_err(Error::FromUtf8)?;
+        let headers = res.headers().clone();
+        let body_bytes = res.into_body().collect().await?.to_bytes().to_vec();
+        let body = codecs.decode(&headers, body_bytes.clone()).unwrap_or_else(|err| {
+            tracing::warn!("Failed to decode error data: {err}");
+            body_bytes
+        });
         // Print better debug when things do fail
         // trace!("Parsing error: {}", text);
-        if let Ok(errdata) = serde_json::from_str::<ErrorResponse>(&text) {
+        if let Ok(errdata) = serde_json::from_slice::<ErrorResponse>(&body) {
             tracing::debug!("Unsuccessful: {errdata:?}");
             Err(Error::Api(errdata))
         } else {
+            let text = String::from_utf8_lossy(&body);
             tracing::warn!("Unsuccessful data error parse: {}", text);
             let error_response = ErrorResponse {
                 status: status.to_string(),
                 code: status.as_u16(),
                 message: format!("{text:?}"),
                 reason: "Failed to parse error data".into(),
+                details: None,
             };
             tracing::debug!("Unsuccessful: {error_response:?} (reconstruct)");
             Err(Error::Api(error_response))
@@ -519,6 +715,31 @@ impl TryFrom<Config> for Client {
     }
 }
 
+/// Decode a stream of bytes as UTF-8 without splitting characters
+fn utf8_chunks(chunks: impl Stream<Item = Result<Bytes>>) -> impl Stream<Item = Result<String>> {
+    futures::stream::try_unfold(
+        (Box::pin(chunks), vec![]),
+        |(mut chunks, mut pending)| async move {
+            while let Some(chunk) = chunks.try_next().await? {
+                pending.extend_from_slice(&chunk);
+                let complete = match std::str::from_utf8(&pending) {
+                    // an incomplete character at the end is completed by a later chunk
+                    Err(err) if err.error_len().is_none() => err.valid_up_to(),
+                    _ => pending.len(),
+                };
+                if complete > 0 {
+                    let rest = pending.split_off(complete);
+                    let text = String::from_utf8(pending).map_err(Error::FromUtf8)?;
+                    return Ok(Some((text, (chunks, rest))));
+                }
+            }
+            // anything left over is an incomplete character
+            String::from_utf8(pending).map_err(Error::FromUtf8)?;
+            Ok(None)
+        },
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use std::pin::pin;
@@ -534,6 +755,8 @@ mod tests {
         let (mock_service, _) = mock::pair::<Request<Body>, Response<Body>>();
         let client = Client::new(mock_service, "test-namespace");
         assert_eq!(client.default_namespace(), "test-namespace");
+        let client = client.with_default_namespace("other-namespace");
+        assert_eq!(client.default_namespace(), "other-namespace");
     }
 
     #[tokio::test]
@@ -569,4 +792,113 @@ mod tests {
         assert_eq!(pod.metadata.annotations.unwrap().get("kube-rs").unwrap(), "test");
         spawned.await.unwrap();
     }
+
+    #[tokio::test]
+    async fn text_streams_do_not_split_characters() {
+        use bytes::Bytes;
+        use futures::TryStreamExt;
+
+        let chunks = ["h\u{e9}".as_bytes(), "llo w\u{f6}rld \u{1f980}".as_bytes()].concat();
+        // split inside the two byte é and the four byte crab
+        let chunks = [&chunks[..2], &chunks[2..12], &chunks[12..15], &chunks[15..]]
+            .map(|chunk| Ok(Bytes::copy_from_slice(chunk)));
+        let text: Vec<String> = super::utf8_chunks(futures::stream::iter(chunks))
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(text.concat(), "h\u{e9}llo w\u{f6}rld \u{1f980}");
+        assert!(text.iter().all(|chunk| !chunk.is_empty()));
+
+        let truncated = [Ok(Bytes::from_static(&[b'a', 0xc3]))];
+        let result: crate::Result<Vec<String>> = super::utf8_chunks(futures::stream::iter(truncated))
+            .try_collect()
+            .await;
+        assert!(matches!(result.unwrap_err(), crate::Error::FromUtf8(_)));
+    }
+
+    #[tokio::test]
+    async fn byte_streams_fail_on_error_statuses() {
+        use futures::TryStreamExt;
+        use http::{Method, StatusCode};
+
+        let (client, mock) = Client::mock();
+        let path = "/apis/metrics.example.com/v1/raw";
+        mock.expect(Method::GET, path)
+            .respond_with(Response::new(b"line 1\nline 2\n".to_vec()));
+        mock.expect(Method::GET, path)
+            .respond_error(StatusCode::FORBIDDEN, "Forbidden", "no access");
+
+        let req = || Request::get(path).body(vec![]).unwrap();
+        let body: Vec<_> = client
+            .request_bytes_stream(req())
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(body.concat(), b"line 1\nline 2\n");
+        let err = client.request_text_stream(req()).await.err().unwrap();
+        assert!(matches!(err, crate::Error::Api(ref e) if e.code == 403));
+    }
+
+    #[tokio::test]
+    async fn error_statuses_are_decoded_with_the_negotiated_codec() {
+        use http::{header, Method, StatusCode};
+
+        struct Reversed;
+        impl super::codec::Codec for Reversed {
+            fn media_type(&self) -> &str {
+                "application/x-reversed"
+            }
+
+            fn decode_json(&self, body: &[u8]) -> Result<Vec<u8>, tower::BoxError> {
+                Ok(body.iter().rev().copied().collect())
+            }
+        }
+
+        let (client, mock) = Client::mock();
+        let status = br#"{"status":"Failure","message":"not found","reason":"NotFound","code":404}"#;
+        let not_found = Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .header(header::CONTENT_TYPE, "application/x-reversed")
+            .body(status.iter().rev().copied().collect::<Vec<_>>())
+            .unwrap();
+        mock.expect(Method::GET, "/api/v1/namespaces/default/pods/blog")
+            .respond_with(not_found);
+
+        let pods: Api<Pod> = Api::default_namespaced(client.with_codec(Reversed));
+        assert!(pods.get_opt("blog").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn failing_probes_are_reports() {
+        use http::{Method, StatusCode};
+
+        let (client, mock) = Client::mock();
+        mock.expect(Method::GET, "/livez")
+            .respond_with(Response::new(b"[+]ping ok\nlivez check passed\n".to_vec()));
+        let failing = Response::builder()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .body(b"[+]ping ok\n[-]etcd failed: reason withheld\nreadyz check failed\n".to_vec())
+            .unwrap();
+        mock.expect(Method::GET, "/readyz").respond_with(failing);
+
+        let livez = client.livez().await.unwrap();
+        assert!(livez.healthy);
+        assert_eq!(livez.checks.len(), 1);
+        let readyz = client.readyz().await.unwrap();
+        assert!(!readyz.healthy);
+        assert_eq!(readyz.failed().map(|c| c.name.as_str()).collect::<Vec<_>>(), [
+            "etcd"
+        ]);
+        assert_eq!(mock.requests()[1].uri, "/readyz?verbose");
+
+        mock.expect(Method::GET, "/readyz").respond_error(
+            StatusCode::FORBIDDEN,
+            "Forbidden",
+            "forbidden: /readyz",
+        );
+        let err = client.readyz().await.unwrap_err();
+        assert!(matches!(err, crate::Error::Api(e) if e.code == 403));
+    }
 }
@@ -1,14 +1,28 @@
+#[cfg(any(feature = "rustls-tls", feature = "openssl-tls"))] mod reload;
+
 #[cfg(feature = "rustls-tls")]
 pub mod rustls_tls {
-    use hyper_rustls::ConfigBuilderExt;
+    use std::{
+        path::{Path, PathBuf},
+        sync::Arc,
+    };
+
     use rustls::{
         self,
-        client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+        client::{
+            danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+            verify_server_cert_signed_by_trust_anchor, ResolvesClientCert, WebPkiServerVerifier,
+        },
+        crypto::{CryptoProvider, WebPkiSupportedAlgorithms},
         pki_types::{CertificateDer, InvalidDnsNameError, PrivateKeyDer, ServerName},
-        ClientConfig, DigitallySignedStruct,
+        server::ParsedCertificate,
+        sign::CertifiedKey,
+        ClientConfig, DigitallySignedStruct, SignatureScheme,
     };
     use thiserror::Error;
 
+    use super::reload::Reloading;
+
     /// Errors from Rustls
     #[derive(Debug, Error)]
     pub enum Error {
@@ -44,55 +58,165 @@ pub mod rustls_tls {
         /// Invalid server name
         #[error("invalid server name: {0}")]
         InvalidServerName(#[source] InvalidDnsNameError),
+
+        /// Failed to read a client certificate or key file
+        #[error("failed to read identity file '{1:?}': {0}")]
+        ReadIdentityFile(#[source] std::io::Error, PathBuf),
+
+        /// Failed to read a root certificates file
+        #[error("failed to read root certificates file '{1:?}': {0}")]
+        ReadRootCertificatesFile(#[source] std::io::Error, PathBuf),
+
+        /// Root certificates PEM is invalid
+        #[error("root certificates PEM is invalid: {0}")]
+        InvalidRootCertificatesPem(#[source] rustls::pki_types::pem::Error),
     }
 
     /// Create `rustls::ClientConfig`.
+    ///
+    /// The server certificate is verified against `root_certs`, or the roots of the platform when there
+    /// are none, and the `extra_root_certs`.
     pub fn rustls_client_config(
         identity_pem: Option<&[u8]>,
         root_certs: Option<&[Vec<u8>]>,
+        extra_root_certs: &[Vec<u8>],
         accept_invalid: bool,
+        accept_invalid_hostnames: bool,
     ) -> Result<ClientConfig, Error> {
-        let config_builder = if let Some(certs) = root_certs {
-            ClientConfig::builder().with_root_certificates(root_store(certs)?)
-        } else {
-            #[cfg(feature = "webpki-roots")]
-            {
-                // Use WebPKI roots.
-                ClientConfig::builder().with_webpki_roots()
-            }
-            #[cfg(not(feature = "webpki-roots"))]
-            {
-                // Use native roots. This will panic on Android and iOS.
-                ClientConfig::builder()
-                    .with_native_roots()
-                    .map_err(Error::NoValidNativeRootCA)?
-            }
-        };
-
-        let mut client_config = if let Some((chain, pkey)) = identity_pem.map(client_auth).transpose()? {
+        let roots = trusted_roots(root_certs, extra_root_certs)?;
+        let config_builder = ClientConfig::builder().with_root_certificates(roots.clone());
+        let client_config = if let Some((chain, pkey)) = identity_pem.map(client_auth).transpose()? {
             config_builder
                 .with_client_auth_cert(chain, pkey)
                 .map_err(Error::InvalidPrivateKey)?
         } else {
             config_builder.with_no_client_auth()
         };
+        verify_server(client_config, roots, accept_invalid, accept_invalid_hostnames)
+    }
+
+    /// Create `rustls::ClientConfig` with a client certificate that is reloaded when its files change.
+    ///
+    /// The files are checked whenever a connection is established, so a rotated certificate is
+    /// used for new connections, while established ones keep using the certificate they started with.
+    pub fn rustls_client_config_with_identity_files(
+        cert_path: &Path,
+        key_path: &Path,
+        root_certs: Option<&[Vec<u8>]>,
+        extra_root_certs: &[Vec<u8>],
+        accept_invalid: bool,
+        accept_invalid_hostnames: bool,
+    ) -> Result<ClientConfig, Error> {
+        let roots = trusted_roots(root_certs, extra_root_certs)?;
+        let config_builder = ClientConfig::builder().with_root_certificates(roots.clone());
+        let resolver = ReloadingIdentity::new(
+            cert_path.to_owned(),
+            key_path.to_owned(),
+            config_builder.crypto_provider().clone(),
+        )?;
+        let client_config = config_builder.with_client_cert_resolver(Arc::new(resolver));
+        verify_server(client_config, roots, accept_invalid, accept_invalid_hostnames)
+    }
 
+    /// Verify the server certificate against root certificates that are reloaded when their file changes.
+    ///
+    /// Like with [`rustls_client_config_with_identity_files`], the file is checked whenever a connection
+    /// is established, so that a rotated CA bundle is trusted by new connections. The `extra_root_certs`
+    /// are trusted in addition to the ones in the file.
+    pub fn reload_root_certs(
+        client_config: &mut ClientConfig,
+        path: &Path,
+        extra_root_certs: &[Vec<u8>],
+        accept_invalid_hostnames: bool,
+    ) -> Result<(), Error> {
+        let verifier = ReloadingRoots::new(
+            path.to_owned(),
+            client_config.crypto_provider().clone(),
+            extra_root_certs.to_vec(),
+            accept_invalid_hostnames,
+        )?;
+        client_config
+            .dangerous()
+            .set_certificate_verifier(Arc::new(verifier));
+        Ok(())
+    }
+
+    /// The `root_certs`, or the roots of the platform without them, and the `extra_root_certs`
+    fn trusted_roots(
+        root_certs: Option<&[Vec<u8>]>,
+        extra_root_certs: &[Vec<u8>],
+    ) -> Result<rustls::RootCertStore, Error> {
+        let mut roots = match root_certs {
+            Some(certs) => root_store(certs)?,
+            None => platform_roots()?,
+        };
+        add_root_certs(&mut roots, extra_root_certs)?;
+        Ok(roots)
+    }
+
+    fn platform_roots() -> Result<rustls::RootCertStore, Error> {
+        #[cfg(feature = "webpki-roots")]
+        {
+            // Use WebPKI roots.
+            Ok(rustls::RootCertStore {
+                roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+            })
+        }
+        #[cfg(not(feature = "webpki-roots"))]
+        {
+            // Use native roots, skipping invalid ones like `ConfigBuilderExt::with_native_roots`.
+            // This will panic on Android and iOS.
+            let native = rustls_native_certs::load_native_certs();
+            let mut roots = rustls::RootCertStore::empty();
+            let (_, invalid) = roots.add_parsable_certificates(native.certs);
+            if roots.is_empty() {
+                let err = std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("{invalid} invalid certificates, errors: {:?}", native.errors),
+                );
+                return Err(Error::NoValidNativeRootCA(err));
+            }
+            Ok(roots)
+        }
+    }
+
+    fn verify_server(
+        mut client_config: ClientConfig,
+        roots: rustls::RootCertStore,
+        accept_invalid: bool,
+        accept_invalid_hostnames: bool,
+    ) -> Result<ClientConfig, Error> {
         if accept_invalid {
+            tracing::warn!("verification of the apiserver certificate is disabled, connections are insecure");
             client_config
                 .dangerous()
-                .set_certificate_verifier(std::sync::Arc::new(NoCertificateVerification {}));
+                .set_certificate_verifier(Arc::new(NoCertificateVerification {}));
+        } else if accept_invalid_hostnames {
+            tracing::warn!("hostname verification of the apiserver certificate is disabled");
+            let verifier = NoHostnameVerification {
+                roots,
+                algorithms: client_config.crypto_provider().signature_verification_algorithms,
+            };
+            client_config
+                .dangerous()
+                .set_certificate_verifier(Arc::new(verifier));
         }
         Ok(client_config)
     }
 
     fn root_store(root_certs: &[Vec<u8>]) -> Result<rustls::RootCertStore, Error> {
         let mut root_store = rustls::RootCertStore::empty();
+        add_root_certs(&mut root_store, root_certs)?;
+        Ok(root_store)
+    }
+
+    fn add_root_certs(root_store: &mut rustls::RootCertStore, root_certs: &[Vec<u8>]) -> Result<(), Error> {
         for der in root_certs {
             root_store
                 .add(CertificateDer::from(der.to_owned()))
                 .map_err(|e| Error::AddRootCertificate(Box::new(e)))?;
         }
-        Ok(root_store)
+        Ok(())
     }
 
     fn client_auth(data: &[u8]) -> Result<(Vec<CertificateDer<'static>>, PrivateKeyDer<'static>), Error> {
@@ -123,6 +247,192 @@ pub mod rustls_tls {
         Ok((cert_chain, private_key))
     }
 
+    /// Resolves the client certificate from files, reloading them when they are modified
+    #[derive(Debug)]
+    struct ReloadingIdentity(Reloading<Arc<CertifiedKey>>);
+
+    impl ReloadingIdentity {
+        fn new(cert_path: PathBuf, key_path: PathBuf, provider: Arc<CryptoProvider>) -> Result<Self, Error> {
+            let paths = vec![cert_path.clone(), key_path.clone()];
+            let load = move || load_identity(&cert_path, &key_path, &provider);
+            let key = load()?;
+            Ok(Self(Reloading::new("client certificate", paths, key, load)))
+        }
+    }
+
+    fn load_identity(
+        cert_path: &Path,
+        key_path: &Path,
+        provider: &CryptoProvider,
+    ) -> Result<Arc<CertifiedKey>, Error> {
+        let read = |path: &Path| std::fs::read(path).map_err(|e| Error::ReadIdentityFile(e, path.to_owned()));
+        let mut pem = read(key_path)?;
+        pem.push(b'\n');
+        pem.extend(read(cert_path)?);
+        let (chain, key) = client_auth(&pem)?;
+        let key = provider
+            .key_provider
+            .load_private_key(key)
+            .map_err(Error::InvalidPrivateKey)?;
+        Ok(Arc::new(CertifiedKey::new(chain, key)))
+    }
+
+    impl ResolvesClientCert for ReloadingIdentity {
+        fn resolve(
+            &self,
+            _root_hint_subjects: &[&[u8]],
+            _sigschemes: &[SignatureScheme],
+        ) -> Option<Arc<CertifiedKey>> {
+            Some(self.0.get())
+        }
+
+        fn has_certs(&self) -> bool {
+            true
+        }
+    }
+
+    /// Verifies server certificates against root certificates from a file, reloading it when it is modified
+    #[derive(Debug)]
+    struct ReloadingRoots {
+        provider: Arc<CryptoProvider>,
+        verifier: Reloading<Arc<dyn ServerCertVerifier>>,
+    }
+
+    impl ReloadingRoots {
+        fn new(
+            path: PathBuf,
+            provider: Arc<CryptoProvider>,
+            extra_root_certs: Vec<Vec<u8>>,
+            accept_invalid_hostnames: bool,
+        ) -> Result<Self, Error> {
+            let load = {
+                let (path, provider) = (path.clone(), provider.clone());
+                move || roots_verifier(&path, &provider, &extra_root_certs, accept_invalid_hostnames)
+            };
+            let verifier = Reloading::new("root certificates", vec![path], load()?, load);
+            Ok(Self { provider, verifier })
+        }
+
+        fn verifier(&self) -> Arc<dyn ServerCertVerifier> {
+            self.verifier.get()
+        }
+    }
+
+    fn roots_verifier(
+        path: &Path,
+        provider: &Arc<CryptoProvider>,
+        extra_root_certs: &[Vec<u8>],
+        accept_invalid_hostnames: bool,
+    ) -> Result<Arc<dyn ServerCertVerifier>, Error> {
+        use rustls::pki_types::pem::{self, SectionKind};
+
+        let data = std::fs::read(path).map_err(|e| Error::ReadRootCertificatesFile(e, path.to_owned()))?;
+        let mut certs = Vec::new();
+        let mut reader = std::io::Cursor::new(data);
+        while let Some((kind, der)) = pem::from_buf(&mut reader).map_err(Error::InvalidRootCertificatesPem)? {
+            if kind == SectionKind::Certificate {
+                certs.push(der);
+            }
+        }
+        let mut roots = root_store(&certs)?;
+        add_root_certs(&mut roots, extra_root_certs)?;
+        if accept_invalid_hostnames {
+            return Ok(Arc::new(NoHostnameVerification {
+                roots,
+                algorithms: provider.signature_verification_algorithms,
+            }));
+        }
+        let verifier = WebPkiServerVerifier::builder_with_provider(Arc::new(roots), provider.clone())
+            .build()
+            .map_err(|e| Error::AddRootCertificate(Box::new(e)))?;
+        Ok(verifier)
+    }
+
+    impl ServerCertVerifier for ReloadingRoots {
+        fn verify_server_cert(
+            &self,
+            end_entity: &CertificateDer,
+            intermediates: &[CertificateDer],
+            server_name: &ServerName,
+            ocsp_response: &[u8],
+            now: rustls::pki_types::UnixTime,
+        ) -> Result<ServerCertVerified, rustls::Error> {
+            self.verifier()
+                .verify_server_cert(end_entity, intermediates, server_name, ocsp_response, now)
+        }
+
+        fn verify_tls12_signature(
+            &self,
+            message: &[u8],
+            cert: &CertificateDer,
+            dss: &DigitallySignedStruct,
+        ) -> Result<HandshakeSignatureValid, rustls::Error> {
+            let algorithms = &self.provider.signature_verification_algorithms;
+            rustls::crypto::verify_tls12_signature(message, cert, dss, algorithms)
+        }
+
+        fn verify_tls13_signature(
+            &self,
+            message: &[u8],
+            cert: &CertificateDer,
+            dss: &DigitallySignedStruct,
+        ) -> Result<HandshakeSignatureValid, rustls::Error> {
+            let algorithms = &self.provider.signature_verification_algorithms;
+            rustls::crypto::verify_tls13_signature(message, cert, dss, algorithms)
+        }
+
+        fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+            self.provider
+                .signature_verification_algorithms
+                .supported_schemes()
+        }
+    }
+
+    /// Verifies that the certificate is signed by the root certificates, for any server name
+    #[derive(Debug)]
+    struct NoHostnameVerification {
+        roots: rustls::RootCertStore,
+        algorithms: WebPkiSupportedAlgorithms,
+    }
+
+    impl ServerCertVerifier for NoHostnameVerification {
+        fn verify_server_cert(
+            &self,
+            end_entity: &CertificateDer,
+            intermediates: &[CertificateDer],
+            _server_name: &ServerName,
+            _ocsp_response: &[u8],
+            now: rustls::pki_types::UnixTime,
+        ) -> Result<ServerCertVerified, rustls::Error> {
+            let cert = ParsedCertificate::try_from(end_entity)?;
+            let algorithms = self.algorithms.all;
+            verify_server_cert_signed_by_trust_anchor(&cert, &self.roots, intermediates, now, algorithms)?;
+            Ok(ServerCertVerified::assertion())
+        }
+
+        fn verify_tls12_signature(
+            &self,
+            message: &[u8],
+            cert: &CertificateDer,
+            dss: &DigitallySignedStruct,
+        ) -> Result<HandshakeSignatureValid, rustls::Error> {
+            rustls::crypto::verify_tls12_signature(message, cert, dss, &self.algorithms)
+        }
+
+        fn verify_tls13_signature(
+            &self,
+            message: &[u8],
+            cert: &CertificateDer,
+            dss: &DigitallySignedStruct,
+        ) -> Result<HandshakeSignatureValid, rustls::Error> {
+            rustls::crypto::verify_tls13_signature(message, cert, dss, &self.algorithms)
+        }
+
+        fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+            self.algorithms.supported_schemes()
+        }
+    }
+
     #[derive(Debug)]
     struct NoCertificateVerification {}
 
@@ -176,17 +486,129 @@ pub mod rustls_tls {
             ]
         }
     }
+
+    #[cfg(test)]
+    mod tests {
+        use std::time::Duration;
+
+        use rustls::pki_types::UnixTime;
+
+        use super::*;
+        use crate::client::tls::reload::tests::rewrite;
+
+        const CERT_A: &[u8] = include_bytes!("tls/test_data/a.crt");
+        const KEY_A: &[u8] = include_bytes!("tls/test_data/a.key");
+        const CERT_B: &[u8] = include_bytes!("tls/test_data/b.crt");
+        const KEY_B: &[u8] = include_bytes!("tls/test_data/b.key");
+
+        fn der(pem: &[u8]) -> CertificateDer<'static> {
+            let (_, der) = rustls::pki_types::pem::from_buf(&mut std::io::Cursor::new(pem))
+                .unwrap()
+                .unwrap();
+            der.into()
+        }
+
+        #[test]
+        fn rotated_identity_files_are_used_by_new_connections() {
+            let dir = tempfile::tempdir().unwrap();
+            let (cert_path, key_path) = (dir.path().join("tls.crt"), dir.path().join("tls.key"));
+            std::fs::write(&cert_path, CERT_A).unwrap();
+            std::fs::write(&key_path, KEY_A).unwrap();
+            let provider = ClientConfig::builder().crypto_provider().clone();
+            let identity = ReloadingIdentity::new(cert_path.clone(), key_path.clone(), provider).unwrap();
+            let served = || identity.resolve(&[], &[]).unwrap().cert[0].clone();
+            assert_eq!(served(), der(CERT_A));
+
+            rewrite(&cert_path, CERT_B);
+            rewrite(&key_path, KEY_B);
+            assert_eq!(served(), der(CERT_B));
+
+            // a half written certificate keeps the previous identity
+            rewrite(&cert_path, &CERT_A[..CERT_A.len() / 2]);
+            assert_eq!(served(), der(CERT_B));
+        }
+
+        #[test]
+        fn rotated_root_certificates_are_trusted_by_new_connections() {
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("ca.crt");
+            std::fs::write(&path, CERT_A).unwrap();
+            let provider = ClientConfig::builder().crypto_provider().clone();
+            let roots = ReloadingRoots::new(path.clone(), provider, vec![], false).unwrap();
+            let server_name = ServerName::try_from("localhost").unwrap();
+            // within the validity of the self-signed test certificates
+            let now = UnixTime::since_epoch(Duration::from_secs(1_700_000_000));
+            let trusted = |pem| {
+                roots
+                    .verify_server_cert(&der(pem), &[], &server_name, &[], now)
+                    .is_ok()
+            };
+            assert!(trusted(CERT_A));
+            assert!(!trusted(CERT_B));
+
+            rewrite(&path, CERT_B);
+            assert!(!trusted(CERT_A));
+            assert!(trusted(CERT_B));
+        }
+
+        #[test]
+        fn extra_root_certificates_are_trusted_in_addition() {
+            let provider = ClientConfig::builder().crypto_provider().clone();
+            let roots = trusted_roots(Some(&[der(CERT_A).to_vec()]), &[der(CERT_B).to_vec()]).unwrap();
+            let verifier = WebPkiServerVerifier::builder_with_provider(Arc::new(roots), provider)
+                .build()
+                .unwrap();
+            let server_name = ServerName::try_from("localhost").unwrap();
+            let now = UnixTime::since_epoch(Duration::from_secs(1_700_000_000));
+            for cert in [CERT_A, CERT_B] {
+                assert!(verifier
+                    .verify_server_cert(&der(cert), &[], &server_name, &[], now)
+                    .is_ok());
+            }
+        }
+
+        #[test]
+        fn certificates_for_other_hostnames_are_only_accepted_without_hostname_verification() {
+            let provider = ClientConfig::builder().crypto_provider().clone();
+            let algorithms = provider.signature_verification_algorithms;
+            let roots = trusted_roots(Some(&[der(CERT_A).to_vec()]), &[]).unwrap();
+            // the test certificates are only valid for localhost
+            let server_name = ServerName::try_from("apiserver.example").unwrap();
+            let now = UnixTime::since_epoch(Duration::from_secs(1_700_000_000));
+
+            let verifier = WebPkiServerVerifier::builder_with_provider(Arc::new(roots.clone()), provider)
+                .build()
+                .unwrap();
+            assert!(verifier
+                .verify_server_cert(&der(CERT_A), &[], &server_name, &[], now)
+                .is_err());
+
+            let verifier = NoHostnameVerification { roots, algorithms };
+            assert!(verifier
+                .verify_server_cert(&der(CERT_A), &[], &server_name, &[], now)
+                .is_ok());
+            // the certificate still has to be signed by a trusted root
+            assert!(verifier
+                .verify_server_cert(&der(CERT_B), &[], &server_name, &[], now)
+                .is_err());
+        }
+    }
 }
 
 #[cfg(feature = "openssl-tls")]
 pub mod openssl_tls {
+    use std::path::{Path, PathBuf};
+
     use openssl::{
-        pkey::PKey,
-        ssl::{SslConnector, SslConnectorBuilder, SslMethod},
-        x509::X509,
+        error::ErrorStack,
+        pkey::{PKey, Private},
+        ssl::{ConnectConfiguration, SslConnector, SslConnectorBuilder, SslMethod, SslRef},
+        x509::{store::X509StoreBuilder, X509},
     };
     use thiserror::Error;
 
+    use super::reload::Reloading;
+
     /// Errors from OpenSSL TLS
     #[derive(Debug, Error)]
     pub enum Error {
@@ -240,9 +662,12 @@ pub mod openssl_tls {
     }
 
     /// Create `openssl::ssl::SslConnectorBuilder` required for `hyper_openssl::HttpsConnector`.
+    ///
+    /// The `root_certs` and `extra_root_certs` are trusted in addition to the default roots of OpenSSL.
     pub fn ssl_connector_builder(
         identity_pem: Option<&Vec<u8>>,
         root_certs: Option<&Vec<Vec<u8>>>,
+        extra_root_certs: &[Vec<u8>],
     ) -> Result<SslConnectorBuilder, SslConnectorError> {
         let mut builder =
             SslConnector::builder(SslMethod::tls()).map_err(SslConnectorError::CreateBuilder)?;
@@ -266,16 +691,249 @@ pub mod openssl_tls {
                 .map_err(SslConnectorError::SetPrivateKey)?;
         }
 
-        if let Some(ders) = root_certs {
-            for der in ders {
-                let cert = X509::from_der(der).map_err(SslConnectorError::DeserializeRootCertificate)?;
-                builder
-                    .cert_store_mut()
-                    .add_cert(cert)
-                    .map_err(SslConnectorError::AddRootCertificate)?;
-            }
+        for cert in root_certs.into_iter().flatten().chain(extra_root_certs) {
+            builder
+                .cert_store_mut()
+                .add_cert(root_cert(cert)?)
+                .map_err(SslConnectorError::AddRootCertificate)?;
         }
 
         Ok(builder)
     }
+
+    fn root_cert(der: &[u8]) -> Result<X509, SslConnectorError> {
+        X509::from_der(der).map_err(SslConnectorError::DeserializeRootCertificate)
+    }
+
+    /// Send `name` as SNI and verify the certificate against it, instead of the host of the uri
+    ///
+    /// Like for the host of a uri, IP addresses are not sent as SNI.
+    pub(crate) fn set_server_name(
+        ssl: &mut ConnectConfiguration,
+        name: &str,
+        verify_hostname: bool,
+    ) -> Result<(), ErrorStack> {
+        ssl.set_use_server_name_indication(false);
+        ssl.set_verify_hostname(false);
+        match name.parse::<std::net::IpAddr>() {
+            Ok(ip) if verify_hostname => ssl.param_mut().set_ip(ip),
+            Ok(_) => Ok(()),
+            Err(_) => {
+                ssl.set_hostname(name)?;
+                if verify_hostname {
+                    ssl.param_mut().set_host(name)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// A client certificate from files, reloaded when they are modified
+    ///
+    /// The connector is built with the identity the files had at the time, so a reloaded identity is
+    /// set on each new connection instead.
+    #[derive(Debug)]
+    pub(crate) struct ReloadingIdentity(Reloading<Option<(Vec<X509>, PKey<Private>)>>);
+
+    impl ReloadingIdentity {
+        pub(crate) fn new(cert_path: PathBuf, key_path: PathBuf) -> Self {
+            let paths = vec![cert_path.clone(), key_path.clone()];
+            let load = move || load_identity(&cert_path, &key_path).map(Some);
+            Self(Reloading::new("client certificate", paths, None, load))
+        }
+
+        /// Set the current identity on a connection, if the files changed since the connector was built
+        pub(crate) fn apply(&self, ssl: &mut SslRef) -> Result<(), ErrorStack> {
+            if let Some((chain, key)) = self.0.get() {
+                let mut chain = chain.into_iter();
+                if let Some(leaf) = chain.next() {
+                    ssl.set_certificate(&leaf)?;
+                }
+                for cert in chain {
+                    ssl.add_chain_cert(cert)?;
+                }
+                ssl.set_private_key(&key)?;
+            }
+            Ok(())
+        }
+    }
+
+    /// Root certificates from a file, reloaded when it is modified
+    ///
+    /// Like for [`ReloadingIdentity`], the connector trusts the certificates the file had when it was
+    /// built, and reloaded ones replace them on each new connection. As by [`ssl_connector_builder`],
+    /// the extra root certificates and the default roots of OpenSSL are trusted in addition to the ones
+    /// in the file.
+    #[derive(Debug)]
+    pub(crate) struct ReloadingRoots(Reloading<Option<Vec<X509>>>, Vec<X509>);
+
+    impl ReloadingRoots {
+        pub(crate) fn new(path: PathBuf, extra_root_certs: &[Vec<u8>]) -> Result<Self, SslConnectorError> {
+            let extra = extra_root_certs
+                .iter()
+                .map(|der| root_cert(der))
+                .collect::<Result<_, _>>()?;
+            let paths = vec![path.clone()];
+            let load = move || load_roots(&path).map(Some);
+            Ok(Self(
+                Reloading::new("root certificates", paths, None, load),
+                extra,
+            ))
+        }
+
+        /// Verify the server certificate of a connection against the current root certificates
+        pub(crate) fn apply(&self, ssl: &mut SslRef) -> Result<(), ErrorStack> {
+            if let Some(roots) = self.0.get() {
+                let mut store = X509StoreBuilder::new()?;
+                // the store replaces the one of the connector, which trusts the default roots too
+                store.set_default_paths()?;
+                for cert in roots.into_iter().chain(self.1.iter().cloned()) {
+                    store.add_cert(cert)?;
+                }
+                ssl.set_verify_cert_store(store.build())?;
+            }
+            Ok(())
+        }
+    }
+
+    fn load_identity(cert_path: &Path, key_path: &Path) -> Result<(Vec<X509>, PKey<Private>), ReloadError> {
+        let read = |path: &Path| std::fs::read(path).map_err(|e| ReloadError::Read(e, path.to_owned()));
+        let chain = X509::stack_from_pem(&read(cert_path)?).map_err(ReloadError::Parse)?;
+        if chain.is_empty() {
+            return Err(ReloadError::EmptyChain);
+        }
+        let key = PKey::private_key_from_pem(&read(key_path)?).map_err(ReloadError::Parse)?;
+        Ok((chain, key))
+    }
+
+    fn load_roots(path: &Path) -> Result<Vec<X509>, ReloadError> {
+        let pem = std::fs::read(path).map_err(|e| ReloadError::Read(e, path.to_owned()))?;
+        let roots = X509::stack_from_pem(&pem).map_err(ReloadError::Parse)?;
+        if roots.is_empty() {
+            return Err(ReloadError::EmptyChain);
+        }
+        Ok(roots)
+    }
+
+    /// Errors from reloading certificates, which are only logged
+    #[derive(Debug, Error)]
+    enum ReloadError {
+        #[error("failed to read {1:?}: {0}")]
+        Read(#[source] std::io::Error, PathBuf),
+
+        #[error("failed to parse the certificates or key: {0}")]
+        Parse(#[source] ErrorStack),
+
+        #[error("the file holds no certificates")]
+        EmptyChain,
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::{
+            net::{TcpListener, TcpStream},
+            thread,
+        };
+
+        use openssl::ssl::{NameType, Ssl, SslAcceptor, SslContext};
+
+        use super::*;
+        use crate::client::tls::reload::tests::rewrite;
+
+        const CERT_A: &[u8] = include_bytes!("tls/test_data/a.crt");
+        const KEY_A: &[u8] = include_bytes!("tls/test_data/a.key");
+        const CERT_B: &[u8] = include_bytes!("tls/test_data/b.crt");
+        const KEY_B: &[u8] = include_bytes!("tls/test_data/b.key");
+
+        fn der(pem: &[u8]) -> Vec<u8> {
+            X509::from_pem(pem).unwrap().to_der().unwrap()
+        }
+
+        fn connection() -> Ssl {
+            Ssl::new(&SslContext::builder(SslMethod::tls()).unwrap().build()).unwrap()
+        }
+
+        #[test]
+        fn rotated_identity_files_are_set_on_new_connections() {
+            let dir = tempfile::tempdir().unwrap();
+            let (cert_path, key_path) = (dir.path().join("tls.crt"), dir.path().join("tls.key"));
+            std::fs::write(&cert_path, CERT_A).unwrap();
+            std::fs::write(&key_path, KEY_A).unwrap();
+            let identity = ReloadingIdentity::new(cert_path.clone(), key_path.clone());
+            let served = || {
+                let mut ssl = connection();
+                identity.apply(&mut ssl).unwrap();
+                ssl.certificate().map(|cert| cert.to_der().unwrap())
+            };
+            // connections use the identity the connector was built with until the files change
+            assert_eq!(served(), None);
+
+            rewrite(&cert_path, CERT_B);
+            rewrite(&key_path, KEY_B);
+            assert_eq!(served(), Some(der(CERT_B)));
+
+            // a half written certificate keeps the previous identity
+            rewrite(&cert_path, &CERT_A[..CERT_A.len() / 2]);
+            assert_eq!(served(), Some(der(CERT_B)));
+        }
+
+        #[test]
+        fn rotated_root_certificates_are_set_on_new_connections() {
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("ca.crt");
+            std::fs::write(&path, CERT_A).unwrap();
+            let roots = ReloadingRoots::new(path.clone(), &[]).unwrap();
+            let trusted = || {
+                roots.apply(&mut connection()).unwrap();
+                let certs = roots.0.get()?;
+                Some(
+                    certs
+                        .iter()
+                        .map(|cert| cert.to_der().unwrap())
+                        .collect::<Vec<_>>(),
+                )
+            };
+            assert_eq!(trusted(), None);
+
+            rewrite(&path, &[CERT_A, CERT_B].concat());
+            assert_eq!(trusted(), Some(vec![der(CERT_A), der(CERT_B)]));
+        }
+
+        // Whether a connection to `host` with the server name `name` is established, and the SNI it sent
+        fn handshake(name: &str, host: &str) -> (bool, Option<String>) {
+            let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).unwrap();
+            acceptor
+                .set_certificate(&X509::from_pem(CERT_A).unwrap())
+                .unwrap();
+            acceptor
+                .set_private_key(&PKey::private_key_from_pem(KEY_A).unwrap())
+                .unwrap();
+            let acceptor = acceptor.build();
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let addr = listener.local_addr().unwrap();
+            let server = thread::spawn(move || {
+                let stream = acceptor.accept(listener.accept().unwrap().0).ok()?;
+                stream.ssl().servername(NameType::HOST_NAME).map(String::from)
+            });
+
+            let connector = ssl_connector_builder(None, Some(&vec![der(CERT_A)]), &[])
+                .unwrap()
+                .build();
+            let mut ssl = connector.configure().unwrap();
+            set_server_name(&mut ssl, name, true).unwrap();
+            // the stream is kept open until the server is done with the handshake
+            let connected = ssl.connect(host, TcpStream::connect(addr).unwrap());
+            (connected.is_ok(), server.join().unwrap())
+        }
+
+        #[test]
+        fn server_names_are_used_for_sni_and_hostname_verification() {
+            // the test certificates are only valid for localhost
+            assert_eq!(
+                handshake("localhost", "10.0.0.1"),
+                (true, Some("localhost".into()))
+            );
+            assert!(!handshake("apiserver.example", "localhost").0);
+        }
+    }
 }
@@ -0,0 +1,131 @@
+//! Values loaded from files that are reloaded when the files change
+//!
+//! Client certificates and CA bundles are often rotated on disk, e.g. by cert-manager or the
+//! kubelet. Both TLS stacks check the files whenever a connection is established, so rotated
+//! certificates are used by new connections while established ones keep the ones they started with.
+use std::{
+    fmt,
+    path::PathBuf,
+    sync::Mutex,
+    time::SystemTime,
+};
+
+use tower::BoxError;
+
+type Load<T> = Box<dyn Fn() -> Result<T, BoxError> + Send + Sync>;
+
+/// A value loaded from `paths`, which is loaded again when one of them is modified
+pub(super) struct Reloading<T> {
+    what: &'static str,
+    paths: Vec<PathBuf>,
+    load: Load<T>,
+    loaded: Mutex<Loaded<T>>,
+}
+
+struct Loaded<T> {
+    modified: Vec<Option<SystemTime>>,
+    value: T,
+}
+
+impl<T: Clone> Reloading<T> {
+    /// Watch `paths`, starting out with `value`, and reload `what` with `load` when they change
+    ///
+    /// `value` should have just been read from the files, since their current modification times
+    /// are the ones it is assumed to be up to date with.
+    pub(super) fn new<E>(
+        what: &'static str,
+        paths: Vec<PathBuf>,
+        value: T,
+        load: impl Fn() -> Result<T, E> + Send + Sync + 'static,
+    ) -> Self
+    where
+        E: Into<BoxError>,
+    {
+        let loaded = Loaded {
+            modified: modified(&paths),
+            value,
+        };
+        Self {
+            what,
+            paths,
+            load: Box::new(move || load().map_err(Into::into)),
+            loaded: Mutex::new(loaded),
+        }
+    }
+
+    /// The current value, which is loaded again if the files were modified since it was loaded
+    pub(super) fn get(&self) -> T {
+        let modified = modified(&self.paths);
+        let mut loaded = self.loaded.lock().expect("reloading lock poisoned");
+        if loaded.modified != modified {
+            // files that are being rotated may not parse yet, which is retried on the next connection
+            match (self.load)() {
+                Ok(value) => {
+                    tracing::debug!(paths = ?self.paths, "reloaded {}", self.what);
+                    *loaded = Loaded { modified, value };
+                }
+                Err(err) => tracing::warn!(
+                    error = &*err as &dyn std::error::Error,
+                    "failed to reload {}, keeping the current one",
+                    self.what
+                ),
+            }
+        }
+        loaded.value.clone()
+    }
+}
+
+impl<T> fmt::Debug for Reloading<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Reloading")
+            .field("what", &self.what)
+            .field("paths", &self.paths)
+            .finish_non_exhaustive()
+    }
+}
+
+fn modified(paths: &[PathBuf]) -> Vec<Option<SystemTime>> {
+    paths
+        .iter()
+        .map(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok())
+        .collect()
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use std::{path::Path, time::Duration};
+
+    use super::Reloading;
+
+    /// Write `data` to `path` with a modification time that differs from the previous write
+    pub(crate) fn rewrite(path: &Path, data: &[u8]) {
+        let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        std::fs::write(path, data).unwrap();
+        // coarse file system timestamps may not change between quick writes
+        let file = std::fs::File::options().write(true).open(path).unwrap();
+        let later = modified.map_or_else(std::time::SystemTime::now, |m| m + Duration::from_secs(1));
+        file.set_modified(later).unwrap();
+    }
+
+    #[test]
+    fn modified_files_are_reloaded_unless_they_fail_to_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("value");
+        std::fs::write(&path, "1").unwrap();
+        let load = {
+            let path = path.clone();
+            move || -> Result<u32, tower::BoxError> { Ok(std::fs::read_to_string(&path)?.trim().parse()?) }
+        };
+        let value = Reloading::new("value", vec![path.clone()], load().unwrap(), load);
+        assert_eq!(value.get(), 1);
+
+        rewrite(&path, b"2");
+        assert_eq!(value.get(), 2);
+
+        // a half written file keeps the previous value until it is complete
+        rewrite(&path, b"");
+        assert_eq!(value.get(), 2);
+        rewrite(&path, b"3");
+        assert_eq!(value.get(), 3);
+    }
+}
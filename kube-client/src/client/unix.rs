@@ -0,0 +1,133 @@
+//! Connecting to the apiserver through a unix socket
+use std::{
+    io,
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use futures::future::BoxFuture;
+use http::Uri;
+use hyper::rt::{Read, ReadBufCursor, Write};
+use hyper_util::{
+    client::legacy::connect::{Connected, Connection},
+    rt::TokioIo,
+};
+use tokio::net::UnixStream;
+use tower::Service;
+
+/// Connector that connects to a unix socket, no matter which uri is requested
+///
+/// The uri is still used for the `Host` header and TLS, so `https` urls work with sockets that
+/// forward to the TLS port of an apiserver, like the ones created by `ssh -L`.
+///
+/// This is set up automatically by the default client when [`Config::unix_socket`](crate::Config::unix_socket) is set.
+#[derive(Clone, Debug)]
+pub struct UnixConnector {
+    path: Arc<Path>,
+}
+
+impl UnixConnector {
+    /// Connect to the socket at `path`
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into().into(),
+        }
+    }
+}
+
+impl Service<Uri> for UnixConnector {
+    type Error = io::Error;
+    type Future = BoxFuture<'static, io::Result<UnixConnection>>;
+    type Response = UnixConnection;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, _uri: Uri) -> Self::Future {
+        let path = self.path.clone();
+        Box::pin(async move {
+            let stream = UnixStream::connect(&*path).await?;
+            Ok(UnixConnection(TokioIo::new(stream)))
+        })
+    }
+}
+
+/// A connection made by a [`UnixConnector`]
+#[derive(Debug)]
+pub struct UnixConnection(TokioIo<UnixStream>);
+
+impl Connection for UnixConnection {
+    fn connected(&self) -> Connected {
+        Connected::new()
+    }
+}
+
+impl Read for UnixConnection {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: ReadBufCursor<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().0).poll_read(cx, buf)
+    }
+}
+
+impl Write for UnixConnection {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().0).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().0).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().0).poll_shutdown(cx)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.0.is_write_vectored()
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().0).poll_write_vectored(cx, bufs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Client, Config};
+    use http_body_util::Full;
+    use hyper::{server::conn::http1, service::service_fn};
+    use hyper_util::rt::TokioIo;
+    use tokio::net::UnixListener;
+
+    #[tokio::test]
+    async fn requests_are_sent_through_the_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("apiserver.sock");
+        let listener = UnixListener::bind(&path).unwrap();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let service = service_fn(|req: http::Request<hyper::body::Incoming>| async move {
+                let host = req.headers()[http::header::HOST].as_bytes().to_vec();
+                Ok::<_, std::convert::Infallible>(http::Response::new(Full::new(bytes::Bytes::from(host))))
+            });
+            http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await
+                .unwrap();
+        });
+
+        let config = Config {
+            unix_socket: Some(path),
+            ..Config::new("http://localhost".parse().unwrap())
+        };
+        let client = Client::try_from(config).unwrap();
+        let host = client.request_text(http::Request::default()).await.unwrap();
+        assert_eq!(host, "localhost");
+    }
+}
@@ -0,0 +1,182 @@
+//! Warnings returned by the apiserver in `Warning` response headers
+use std::{fmt, sync::Arc};
+
+use http::{header::WARNING, HeaderMap};
+
+/// A warning sent by the apiserver alongside a response
+///
+/// The apiserver uses these to announce e.g. the use of deprecated APIs and fields,
+/// and admission webhooks can attach warnings to the requests they admit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Warning {
+    /// The warning code, always `299` ("miscellaneous persistent warning") for Kubernetes
+    pub code: u16,
+    /// The agent that added the warning, `-` when unknown
+    pub agent: String,
+    /// The text of the warning
+    pub text: String,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+impl Warning {
+    /// Parse all warnings from the `Warning` headers of a response
+    ///
+    /// Malformed values are skipped.
+    pub fn from_headers(headers: &HeaderMap) -> Vec<Warning> {
+        headers
+            .get_all(WARNING)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(parse_warnings)
+            .collect()
+    }
+}
+
+/// Receives the [`Warning`]s sent by the apiserver
+///
+/// Set with [`Client::with_warning_handler`](crate::Client::with_warning_handler).
+/// Implemented for closures taking a `&Warning`.
+pub trait WarningHandler: Send + Sync + 'static {
+    /// Handle a single warning
+    fn handle(&self, warning: &Warning);
+}
+
+impl<F> WarningHandler for F
+where
+    F: Fn(&Warning) + Send + Sync + 'static,
+{
+    fn handle(&self, warning: &Warning) {
+        self(warning)
+    }
+}
+
+/// Dispatches warnings to the configured handler, or to `tracing` by default
+#[derive(Clone, Default)]
+pub(crate) struct Warnings(Option<Arc<dyn WarningHandler>>);
+
+impl Warnings {
+    pub(crate) fn new(handler: Arc<dyn WarningHandler>) -> Self {
+        Self(Some(handler))
+    }
+
+    pub(crate) fn dispatch(&self, headers: &HeaderMap) {
+        if !headers.contains_key(WARNING) {
+            return;
+        }
+        for warning in Warning::from_headers(headers) {
+            match &self.0 {
+                Some(handler) => handler.handle(&warning),
+                None => tracing::warn!(code = warning.code, agent = %warning.agent, "{}", warning.text),
+            }
+        }
+    }
+}
+
+/// Parse a `Warning` header value, which may hold several comma separated warnings
+///
+/// Each warning is `code SP agent SP quoted-text [SP quoted-date]` as per RFC 7234.
+fn parse_warnings(value: &str) -> Vec<Warning> {
+    let mut warnings = vec![];
+    let mut rest = value;
+    loop {
+        rest = rest.trim_start_matches([' ', ',']);
+        if rest.is_empty() {
+            return warnings;
+        }
+        let Some(parsed) = parse_warning(rest) else {
+            return warnings;
+        };
+        warnings.push(parsed.0);
+        rest = parsed.1;
+    }
+}
+
+fn parse_warning(value: &str) -> Option<(Warning, &str)> {
+    let (code, rest) = value.split_once(' ')?;
+    let (agent, rest) = rest.split_once(' ')?;
+    let (text, rest) = parse_quoted(rest.trim_start())?;
+    let rest = rest.trim_start();
+    // the optional date is not useful to us
+    let rest = if rest.starts_with('"') {
+        parse_quoted(rest)?.1
+    } else {
+        rest
+    };
+    let warning = Warning {
+        code: code.parse().ok()?,
+        agent: agent.to_string(),
+        text,
+    };
+    Some((warning, rest))
+}
+
+/// Parse a quoted string with backslash escapes, returning its contents and the remainder
+fn parse_quoted(value: &str) -> Option<(String, &str)> {
+    let mut chars = value.strip_prefix('"')?.char_indices();
+    let mut text = String::new();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((text, &value[i + 2..])),
+            '\\' => text.push(chars.next()?.1),
+            c => text.push(c),
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use http::HeaderValue;
+
+    fn warning(text: &str) -> Warning {
+        Warning {
+            code: 299,
+            agent: "-".into(),
+            text: text.into(),
+        }
+    }
+
+    #[test]
+    fn parses_warning_headers() {
+        let mut headers = HeaderMap::new();
+        headers.append(
+            WARNING,
+            HeaderValue::from_static(
+                r#"299 - "policy/v1beta1 PodSecurityPolicy is deprecated in v1.21+, unavailable in v1.25+""#,
+            ),
+        );
+        headers.append(
+            WARNING,
+            HeaderValue::from_static(
+                r#"299 - "a \"quoted\" word", 299 - "dated" "Sat, 25 Aug 2012 23:34:45 GMT",299 - "last""#,
+            ),
+        );
+        headers.append(WARNING, HeaderValue::from_static("garbage"));
+        assert_eq!(Warning::from_headers(&headers), [
+            warning("policy/v1beta1 PodSecurityPolicy is deprecated in v1.21+, unavailable in v1.25+"),
+            warning(r#"a "quoted" word"#),
+            warning("dated"),
+            warning("last"),
+        ]);
+    }
+
+    #[test]
+    fn dispatches_to_handler() {
+        let seen = Arc::new(std::sync::Mutex::new(vec![]));
+        let handler = {
+            let seen = seen.clone();
+            move |w: &Warning| seen.lock().unwrap().push(w.text.clone())
+        };
+        let mut headers = HeaderMap::new();
+        headers.insert(WARNING, HeaderValue::from_static(r#"299 - "deprecated""#));
+        Warnings::new(Arc::new(handler)).dispatch(&headers);
+        assert_eq!(*seen.lock().unwrap(), ["deprecated"]);
+    }
+}
@@ -0,0 +1,91 @@
+use base64::Engine;
+use secrecy::SecretString;
+use thiserror::Error;
+
+use super::Config;
+
+const MASTER_ENV: &str = "KUBERNETES_MASTER";
+const TOKEN_ENV: &str = "KUBERNETES_TOKEN";
+const CA_DATA_ENV: &str = "KUBERNETES_CA_DATA";
+const NAMESPACE_ENV: &str = "KUBERNETES_NAMESPACE";
+
+/// Errors from loading config from environment variables
+#[derive(Error, Debug)]
+pub enum Error {
+    /// The environment variable with the apiserver URL is not set
+    #[error("the environment variable {MASTER_ENV} is not set")]
+    MissingClusterUrl,
+
+    /// Failed to parse cluster url
+    #[error("failed to parse cluster url: {0}")]
+    ParseClusterUrl(#[source] http::uri::InvalidUri),
+
+    /// Failed to decode the base64-encoded certificate authority data
+    #[error("failed to decode {CA_DATA_ENV}: {0}")]
+    DecodeCertificateAuthority(#[source] base64::DecodeError),
+
+    /// Failed to parse PEM-encoded certificates
+    #[error("failed to parse PEM-encoded certificates: {0}")]
+    ParseCertificates(#[source] pem::PemError),
+}
+
+pub(super) fn load(var: impl Fn(&str) -> Option<String>) -> Result<Config, Error> {
+    let var = |name: &str| var(name).filter(|value| !value.trim().is_empty());
+    let cluster_url = var(MASTER_ENV)
+        .ok_or(Error::MissingClusterUrl)?
+        .trim()
+        .parse::<http::Uri>()
+        .map_err(Error::ParseClusterUrl)?;
+    let mut config = Config::new(cluster_url);
+
+    if let Some(ca) = var(CA_DATA_ENV) {
+        // PEM is accepted as is, since it is easy to confuse with base64
+        let pem = if ca.trim_start().starts_with("-----BEGIN") {
+            ca.into_bytes()
+        } else {
+            base64::engine::general_purpose::STANDARD
+                .decode(ca.trim())
+                .map_err(Error::DecodeCertificateAuthority)?
+        };
+        config.root_cert = Some(super::certs(&pem).map_err(Error::ParseCertificates)?);
+    }
+    if let Some(token) = var(TOKEN_ENV) {
+        config.auth_info.token = Some(SecretString::from(token.trim().to_owned()));
+    }
+    if let Some(namespace) = var(NAMESPACE_ENV) {
+        config.default_namespace = namespace.trim().to_owned();
+    }
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{load, Error};
+    use secrecy::ExposeSecret;
+
+    #[test]
+    fn config_is_loaded_from_the_environment() {
+        // a truncated certificate, which only needs to parse as PEM here
+        let pem = "-----BEGIN CERTIFICATE-----\nMIIBszCCAVmgAwIBAgIUKx0=\n-----END CERTIFICATE-----\n";
+        let ca_data = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, pem);
+        let env = [
+            ("KUBERNETES_MASTER", "https://10.0.0.1:6443"),
+            ("KUBERNETES_TOKEN", "secret\n"),
+            ("KUBERNETES_CA_DATA", ca_data.as_str()),
+            ("KUBERNETES_NAMESPACE", "ci"),
+        ];
+        let config = load(|name| {
+            env.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        })
+        .unwrap();
+        assert_eq!(config.cluster_url.host(), Some("10.0.0.1"));
+        assert_eq!(config.cluster_url.port_u16(), Some(6443));
+        assert_eq!(config.default_namespace, "ci");
+        assert_eq!(config.auth_info.token.unwrap().expose_secret(), "secret");
+        assert_eq!(config.root_cert.unwrap().len(), 1);
+
+        assert!(matches!(load(|_| None), Err(Error::MissingClusterUrl)));
+    }
+}
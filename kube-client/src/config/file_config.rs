@@ -2,6 +2,7 @@ use std::{
     collections::HashMap,
     fs, io,
     path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
 };
 
 use secrecy::{ExposeSecret, SecretString};
@@ -199,6 +200,10 @@ pub struct AuthInfo {
     pub token_file: Option<String>,
 
     /// Path to a client cert file for TLS.
+    ///
+    /// This and `client_key` are reloaded for new connections when they change, with both the
+    /// `rustls-tls` and `openssl-tls` features, so short-lived certificates can be rotated without
+    /// restarting the client.
     #[serde(rename = "client-certificate")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub client_certificate: Option<String>,
@@ -230,6 +235,10 @@ pub struct AuthInfo {
     #[serde(rename = "as-groups")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub impersonate_groups: Option<Vec<String>>,
+    /// The uid to impersonate.
+    #[serde(rename = "as-uid")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub impersonate_uid: Option<String>,
 
     /// Specifies a custom authentication plugin for the kubernetes cluster.
     #[serde(rename = "auth-provider")]
@@ -304,6 +313,17 @@ pub struct ExecConfig {
     /// Should be used only when `provide_cluster_info` is True.
     #[serde(skip)]
     pub cluster: Option<ExecAuthCluster>,
+
+    /// Name of the kubeconfig cluster the plugin authenticates to, when loaded from a kubeconfig.
+    ///
+    /// Together with `user_name` this keeps the [`CredentialCache`] entries of contexts apart
+    /// that run the same plugin.
+    #[serde(skip)]
+    pub cluster_name: Option<String>,
+
+    /// Name of the kubeconfig user the plugin authenticates, when loaded from a kubeconfig.
+    #[serde(skip)]
+    pub user_name: Option<String>,
 }
 
 /// ExecInteractiveMode define the interactity of the child process
@@ -415,32 +435,96 @@ impl Kubeconfig {
         }
     }
 
+    /// Read and merge the kubeconfig files at `paths`, like kubectl does for the paths in `KUBECONFIG`
+    ///
+    /// Empty paths and files that do not exist are skipped, and the first file to set a value wins,
+    /// see [`Kubeconfig::merge`].
+    pub fn read_from_paths<P: AsRef<Path>>(
+        paths: impl IntoIterator<Item = P>,
+    ) -> Result<Kubeconfig, KubeconfigError> {
+        paths
+            .into_iter()
+            .filter(|p| !p.as_ref().as_os_str().is_empty() && p.as_ref().exists())
+            .try_fold(Kubeconfig::default(), |m, p| {
+                Kubeconfig::read_from(p).and_then(|c| m.merge(c))
+            })
+    }
+
     /// Create `Kubeconfig` from `KUBECONFIG` environment variable.
-    /// Supports list of files to be merged.
+    /// Supports list of files to be merged, see [`Kubeconfig::read_from_paths`].
     ///
     /// # Panics
     ///
     /// Panics if `KUBECONFIG` value contains the NUL character.
     pub fn from_env() -> Result<Option<Self>, KubeconfigError> {
-        match std::env::var_os(KUBECONFIG) {
-            Some(value) => {
-                let paths = std::env::split_paths(&value)
-                    .filter(|p| !p.as_os_str().is_empty())
-                    .collect::<Vec<_>>();
-                if paths.is_empty() {
-                    return Ok(None);
-                }
-
-                let merged = paths.iter().try_fold(Kubeconfig::default(), |m, p| {
-                    Kubeconfig::read_from(p).and_then(|c| m.merge(c))
-                })?;
-                Ok(Some(merged))
-            }
-
+        match env_paths() {
+            Some(paths) => Self::read_from_paths(paths).map(Some),
             None => Ok(None),
         }
     }
 
+    /// Write the config to `path`, creating or replacing the file
+    ///
+    /// Like kubectl, a `<path>.lock` file is held while writing, and writing fails if another
+    /// process holds it. The file is replaced atomically, so readers never see a partial file.
+    /// Note that configs [read](Kubeconfig::read_from) from a file refer to other files by
+    /// absolute paths.
+    ///
+    /// ```no_run
+    /// use kube::config::{Context, Kubeconfig, NamedContext};
+    ///
+    /// let path = "/home/user/.kube/config";
+    /// let mut config = Kubeconfig::read_from(path)?;
+    /// config.set_context(NamedContext {
+    ///     name: "staging".into(),
+    ///     context: Some(Context {
+    ///         cluster: "staging".into(),
+    ///         user: Some("admin".into()),
+    ///         ..Context::default()
+    ///     }),
+    /// });
+    /// config.write_to(path)?;
+    /// # Ok::<(), kube::config::KubeconfigError>(())
+    /// ```
+    pub fn write_to<P: AsRef<Path>>(&self, path: P) -> Result<(), KubeconfigError> {
+        let path = path.as_ref();
+        let data = serde_yaml::to_string(self).map_err(KubeconfigError::Serialize)?;
+        let _lock = LockFile::acquire(path).map_err(|e| KubeconfigError::WriteConfig(e, path.into()))?;
+        replace_path(path, &data).map_err(|e| KubeconfigError::WriteConfig(e, path.into()))
+    }
+
+    /// Add the cluster, replacing the cluster with the same name
+    pub fn set_cluster(&mut self, cluster: NamedCluster) {
+        set_named(&mut self.clusters, cluster, |x| &x.name);
+    }
+
+    /// Remove the cluster with the name, returning it if it existed
+    pub fn remove_cluster(&mut self, name: &str) -> Option<NamedCluster> {
+        remove_named(&mut self.clusters, name, |x| &x.name)
+    }
+
+    /// Add the user, replacing the user with the same name
+    pub fn set_auth_info(&mut self, auth_info: NamedAuthInfo) {
+        set_named(&mut self.auth_infos, auth_info, |x| &x.name);
+    }
+
+    /// Remove the user with the name, returning it if it existed
+    pub fn remove_auth_info(&mut self, name: &str) -> Option<NamedAuthInfo> {
+        remove_named(&mut self.auth_infos, name, |x| &x.name)
+    }
+
+    /// Add the context, replacing the context with the same name
+    pub fn set_context(&mut self, context: NamedContext) {
+        set_named(&mut self.contexts, context, |x| &x.name);
+    }
+
+    /// Remove the context with the name, returning it if it existed
+    ///
+    /// Like `kubectl config delete-context`, this keeps the `current-context`, even when it was removed.
+    pub fn remove_context(&mut self, name: &str) -> Option<NamedContext> {
+        remove_named(&mut self.contexts, name, |x| &x.name)
+    }
+
     /// Merge kubeconfig file according to the rules described in
     /// <https://kubernetes.io/docs/concepts/configuration/organize-cluster-access-kubeconfig/#merging-kubeconfig-files>
     ///
@@ -467,7 +551,11 @@ impl Kubeconfig {
         append_new_named(&mut self.clusters, next.clusters, |x| &x.name);
         append_new_named(&mut self.auth_infos, next.auth_infos, |x| &x.name);
         append_new_named(&mut self.contexts, next.contexts, |x| &x.name);
-        self.current_context = self.current_context.or(next.current_context);
+        // An empty `current-context` is unset, and does not win over the next file
+        self.current_context = self
+            .current_context
+            .filter(|c| !c.is_empty())
+            .or(next.current_context);
         self.extensions = self.extensions.or(next.extensions);
         Ok(self)
     }
@@ -497,6 +585,74 @@ where
     });
 }
 
+/// The lock file kubectl creates next to a kubeconfig file while writing it
+struct LockFile(PathBuf);
+
+impl LockFile {
+    fn acquire(path: &Path) -> io::Result<Self> {
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        let lock = path.with_file_name(format!("{file_name}.lock"));
+        fs::OpenOptions::new().write(true).create_new(true).open(&lock)?;
+        Ok(Self(lock))
+    }
+}
+
+impl Drop for LockFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+// Write to a temporary file that is renamed over `path`, so that readers never see a partial file.
+// The temporary file is named uniquely, so that concurrent writers do not write to the same one.
+// The permissions of an existing file are kept, new files are only accessible by the user.
+fn replace_path(path: &Path, data: &str) -> io::Result<()> {
+    static TEMPORARY_FILES: AtomicU64 = AtomicU64::new(0);
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let id = TEMPORARY_FILES.fetch_add(1, Ordering::Relaxed);
+    let tmp = path.with_file_name(format!(".{file_name}.{}.{id}.tmp", std::process::id()));
+    let write = || -> io::Result<()> {
+        let permissions = match fs::metadata(path) {
+            Ok(metadata) => Some(metadata.permissions()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+            Err(err) => return Err(err),
+        };
+        fs::write(&tmp, data)?;
+        match permissions {
+            Some(permissions) => fs::set_permissions(&tmp, permissions)?,
+            #[cfg(unix)]
+            None => {
+                use std::os::unix::fs::PermissionsExt;
+                fs::set_permissions(&tmp, fs::Permissions::from_mode(0o600))?
+            }
+            #[cfg(not(unix))]
+            None => {}
+        }
+        fs::rename(&tmp, path)
+    };
+    write().inspect_err(|_| {
+        let _ = fs::remove_file(&tmp);
+    })
+}
+
+fn set_named<T, F>(base: &mut Vec<T>, value: T, f: F)
+where
+    F: Fn(&T) -> &String,
+{
+    match base.iter_mut().find(|x| f(x) == f(&value)) {
+        Some(existing) => *existing = value,
+        None => base.push(value),
+    }
+}
+
+fn remove_named<T, F>(base: &mut Vec<T>, name: &str, f: F) -> Option<T>
+where
+    F: Fn(&T) -> &String,
+{
+    let index = base.iter().position(|x| f(x) == name)?;
+    Some(base.remove(index))
+}
+
 fn read_path<P: AsRef<Path>>(path: P) -> io::Result<String> {
     let bytes = fs::read(&path)?;
     match bytes.as_slice() {
@@ -627,6 +783,177 @@ impl TryFrom<&Cluster> for ExecAuthCluster {
     }
 }
 
+/// Writes the config of an `auth-provider` back to the kubeconfig file that defines its user
+///
+/// This persists the tokens refreshed by the `oidc` auth provider like kubectl does, see
+/// [`Config::persist_refreshed_tokens`](crate::Config::persist_refreshed_tokens).
+/// The rest of the file is kept, apart from its comments and formatting.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuthProviderPersister {
+    /// The kubeconfig file that defines the user
+    pub path: PathBuf,
+    /// The name of the user
+    pub user: String,
+}
+
+impl AuthProviderPersister {
+    /// Find the kubeconfig file that defines `user`, in `KUBECONFIG` or the default location
+    ///
+    /// Like when merging the files in `KUBECONFIG`, the first file that defines the user wins.
+    pub fn find(user: &str) -> Result<Option<Self>, KubeconfigError> {
+        let paths = env_paths().unwrap_or_else(|| default_kube_path().into_iter().collect());
+        for path in paths {
+            // files that do not exist are skipped, like when they are merged
+            let kubeconfig = Kubeconfig::read_from_paths([&path])?;
+            if kubeconfig.auth_infos.iter().any(|named| named.name == user) {
+                return Ok(Some(Self {
+                    path,
+                    user: user.to_string(),
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Set `values` in the auth provider config of the user, and write the file back
+    ///
+    /// Like [`Kubeconfig::write_to`], the file is locked while it is modified and replaced atomically.
+    pub fn persist(&self, values: &[(&str, &str)]) -> Result<(), KubeconfigError> {
+        let _lock =
+            LockFile::acquire(&self.path).map_err(|e| KubeconfigError::WriteConfig(e, self.path.clone()))?;
+        let data =
+            read_path(&self.path).map_err(|source| KubeconfigError::ReadConfig(source, self.path.clone()))?;
+        let mut doc: serde_yaml::Value = serde_yaml::from_str(&data).map_err(KubeconfigError::Parse)?;
+        let provider = doc
+            .get_mut("users")
+            .and_then(serde_yaml::Value::as_sequence_mut)
+            .and_then(|users| {
+                users
+                    .iter_mut()
+                    .find(|named| named.get("name").and_then(serde_yaml::Value::as_str) == Some(&self.user))
+            })
+            .and_then(|named| named.get_mut("user")?.get_mut("auth-provider")?.as_mapping_mut())
+            .ok_or_else(|| KubeconfigError::FindAuthProvider(self.user.clone()))?;
+        let config = provider
+            .entry("config".into())
+            .or_insert_with(|| serde_yaml::Mapping::new().into());
+        let config = config
+            .as_mapping_mut()
+            .ok_or_else(|| KubeconfigError::FindAuthProvider(self.user.clone()))?;
+        for (key, value) in values {
+            config.insert((*key).into(), (*value).into());
+        }
+        let data = serde_yaml::to_string(&doc).map_err(KubeconfigError::Serialize)?;
+        replace_path(&self.path, &data).map_err(|e| KubeconfigError::WriteConfig(e, self.path.clone()))
+    }
+}
+
+/// An on-disk cache of the credentials returned by exec plugins
+///
+/// Short-lived CLI invocations otherwise run the exec plugin of the user every time, which is slow
+/// for plugins that talk to an identity provider. Tokens with an expiration are cached in a file per
+/// cluster and user, and reused by later runs until shortly before they expire. See
+/// [`Config::cache_credentials`](crate::Config::cache_credentials).
+///
+/// Files are named after a hash of the kubeconfig cluster and user names, the exec config, and the
+/// inherited environment variables that select a cloud identity, such as `AWS_PROFILE`, so that
+/// switching profiles does not reuse the token of another identity.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CredentialCache {
+    /// The directory of the cache files
+    pub dir: PathBuf,
+}
+
+impl CredentialCache {
+    /// A cache in `dir`, which is created when the first credentials are cached
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The cache of the user in `~/.kube/cache/credentials`, like the other caches of kubectl
+    pub fn in_home_dir() -> Option<Self> {
+        home::home_dir().map(|h| Self::new(h.join(".kube").join("cache").join("credentials")))
+    }
+
+    /// The cached output of the exec plugin, if any
+    pub(crate) fn read(&self, exec: &ExecConfig) -> Option<String> {
+        fs::read_to_string(self.path(exec)).ok()
+    }
+
+    /// Cache the output of the exec plugin, only accessible by the user
+    pub(crate) fn write(&self, exec: &ExecConfig, data: &str) -> io::Result<()> {
+        let mut builder = fs::DirBuilder::new();
+        builder.recursive(true);
+        #[cfg(unix)]
+        std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+        builder.create(&self.dir)?;
+        // the mode only applies to created directories, not to one that already exists
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&self.dir, fs::Permissions::from_mode(0o700))?;
+        }
+        replace_path(&self.path(exec), data)
+    }
+
+    fn path(&self, exec: &ExecConfig) -> PathBuf {
+        self.path_with_env(exec, |var| std::env::var(var).ok())
+    }
+
+    // The same plugin invocation by the same kubeconfig user authenticates the same identity, so it
+    // identifies the cache entry. The env is sorted, since its entries are maps that serialize in
+    // arbitrary order.
+    fn path_with_env(&self, exec: &ExecConfig, var: impl Fn(&str) -> Option<String>) -> PathBuf {
+        let mut env = exec
+            .env
+            .iter()
+            .flatten()
+            .filter_map(|env| Some((env.get("name")?, env.get("value")?)))
+            .collect::<Vec<_>>();
+        env.sort();
+        let dropped = |name: &str| exec.drop_env.iter().flatten().any(|dropped| dropped == name);
+        let inherited = IDENTITY_ENV
+            .iter()
+            .filter(|&&name| !dropped(name))
+            .filter_map(|&name| Some((name, var(name)?)))
+            .collect::<Vec<_>>();
+        let server = exec.cluster.as_ref().and_then(|cluster| cluster.server.as_ref());
+        let names = (&exec.cluster_name, &exec.user_name);
+        let key = (
+            &exec.api_version,
+            &exec.command,
+            &exec.args,
+            env,
+            server,
+            names,
+            inherited,
+        );
+        let key = serde_json::to_vec(&key).unwrap_or_default();
+        // FNV-1a, which is stable across runs and Rust versions unlike the std hasher
+        let hash = key.iter().fold(0xcbf2_9ce4_8422_2325_u64, |hash, b| {
+            (hash ^ u64::from(*b)).wrapping_mul(0x0100_0000_01b3)
+        });
+        self.dir.join(format!("{hash:016x}.json"))
+    }
+}
+
+/// Environment variables that exec plugins inherit, which select the identity they authenticate as
+const IDENTITY_ENV: &[&str] = &[
+    "AWS_PROFILE",
+    "AWS_DEFAULT_PROFILE",
+    "AWS_CONFIG_FILE",
+    "AWS_SHARED_CREDENTIALS_FILE",
+    "AWS_ROLE_ARN",
+    "AWS_ACCESS_KEY_ID",
+    "AZURE_CONFIG_DIR",
+    "AZURE_CLIENT_ID",
+    "AZURE_TENANT_ID",
+    "CLOUDSDK_CONFIG",
+    "CLOUDSDK_ACTIVE_CONFIG_NAME",
+    "CLOUDSDK_CORE_ACCOUNT",
+    "GOOGLE_APPLICATION_CREDENTIALS",
+];
+
 fn load_from_base64_or_file<P: AsRef<Path>>(
     value: &Option<&str>,
     file: &Option<P>,
@@ -663,6 +990,15 @@ fn default_kube_path() -> Option<PathBuf> {
     home::home_dir().map(|h| h.join(".kube").join("config"))
 }
 
+// The paths in `KUBECONFIG`, unless it is not set or only has empty paths
+fn env_paths() -> Option<Vec<PathBuf>> {
+    let value = std::env::var_os(KUBECONFIG)?;
+    let paths = std::env::split_paths(&value)
+        .filter(|p| !p.as_os_str().is_empty())
+        .collect::<Vec<_>>();
+    (!paths.is_empty()).then_some(paths)
+}
+
 mod base64serde {
     use base64::Engine;
     use serde::{Deserialize, Deserializer, Serialize, Serializer};
@@ -913,6 +1249,113 @@ users:
         Ok(())
     }
 
+    #[test]
+    fn kubeconfig_paths_merge() -> Result<(), KubeconfigError> {
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("first");
+        let second = dir.path().join("second");
+        std::fs::write(
+            &first,
+            r#"
+current-context: ""
+users:
+- name: admin
+  user:
+    token: first
+"#,
+        )
+        .unwrap();
+        std::fs::write(
+            &second,
+            r#"
+current-context: kind
+clusters:
+- name: kind
+  cluster:
+    server: https://127.0.0.1:6443
+users:
+- name: admin
+  user:
+    token: second
+- name: viewer
+  user:
+    token: viewer
+"#,
+        )
+        .unwrap();
+
+        let missing = dir.path().join("missing");
+        let merged = Kubeconfig::read_from_paths([&first, &missing, &second])?;
+        assert_eq!(merged.current_context.as_deref(), Some("kind"));
+        assert_eq!(merged.clusters.len(), 1);
+        let users: Vec<_> = merged
+            .auth_infos
+            .iter()
+            .map(|named| named.name.as_str())
+            .collect();
+        assert_eq!(users, ["admin", "viewer"]);
+        let admin = merged.auth_infos[0].auth_info.as_ref().unwrap();
+        assert_eq!(admin.token.as_ref().unwrap().expose_secret(), "first");
+        Ok(())
+    }
+
+    #[test]
+    fn kubeconfig_write_back() -> Result<(), KubeconfigError> {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config");
+        let mut config = Kubeconfig::default();
+        config.set_cluster(NamedCluster {
+            name: "kind".into(),
+            cluster: Some(Cluster {
+                server: Some("https://127.0.0.1:6443".into()),
+                ..Cluster::default()
+            }),
+        });
+        for name in ["kind", "old"] {
+            config.set_context(NamedContext {
+                name: name.into(),
+                context: Some(Context {
+                    cluster: "kind".into(),
+                    ..Context::default()
+                }),
+            });
+        }
+        config.set_context(NamedContext {
+            name: "kind".into(),
+            context: Some(Context {
+                cluster: "kind".into(),
+                namespace: Some("apps".into()),
+                ..Context::default()
+            }),
+        });
+        assert_eq!(config.remove_context("old").unwrap().name, "old");
+        assert!(config.remove_auth_info("unknown").is_none());
+        config.write_to(&path)?;
+
+        let written = Kubeconfig::read_from(&path)?;
+        assert_eq!(written.clusters, config.clusters);
+        assert_eq!(written.contexts, config.contexts);
+        assert_eq!(
+            written.contexts[0].context.as_ref().unwrap().namespace.as_deref(),
+            Some("apps")
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+
+        // writing fails while another process holds the lock
+        let lock = LockFile::acquire(&path).unwrap();
+        assert!(matches!(
+            config.write_to(&path),
+            Err(KubeconfigError::WriteConfig(..))
+        ));
+        drop(lock);
+        config.write_to(&path)?;
+        Ok(())
+    }
+
     #[test]
     fn kubeconfig_from_empty_string() {
         let cfg = Kubeconfig::from_yaml("").unwrap();
@@ -945,7 +1388,7 @@ password: kube_rs
         token: None, token_file: None, client_certificate: None, \
         client_certificate_data: None, client_key: None, \
         client_key_data: None, impersonate: None, \
-        impersonate_groups: None, \
+        impersonate_groups: None, impersonate_uid: None, \
         auth_provider: None, \
         exec: None \
         }";
@@ -995,6 +1438,67 @@ users:
             cluster.config.unwrap(),
             json!({"audience": "foo", "other": "bar"})
         );
+        assert_eq!(exec.cluster_name.as_deref(), Some("foo-cluster"));
+        assert_eq!(exec.user_name.as_deref(), Some("foo-user"));
+    }
+
+    #[test]
+    fn credential_cache_entries_are_per_cluster_user_and_identity_env() {
+        let cache = CredentialCache::new("/cache");
+        let exec: ExecConfig = serde_json::from_value(json!({
+            "apiVersion": "client.authentication.k8s.io/v1beta1",
+            "command": "aws",
+            "args": ["eks", "get-token", "--cluster-name", "prod"],
+        }))
+        .unwrap();
+        let no_env = |_: &str| None;
+        let profile = |name: &str| (name == "AWS_PROFILE").then(|| "admin".to_owned());
+
+        let path = cache.path_with_env(&exec, no_env);
+        assert_eq!(path, cache.path_with_env(&exec.clone(), no_env));
+        assert_ne!(path, cache.path_with_env(&exec, profile));
+        let other_cluster = ExecConfig {
+            cluster_name: Some("prod".into()),
+            ..exec.clone()
+        };
+        assert_ne!(path, cache.path_with_env(&other_cluster, no_env));
+        let other_user = ExecConfig {
+            user_name: Some("admin".into()),
+            ..exec.clone()
+        };
+        assert_ne!(path, cache.path_with_env(&other_user, no_env));
+
+        // dropped variables are not inherited by the plugin, so they do not select its identity
+        let dropped = ExecConfig {
+            drop_env: Some(vec!["AWS_PROFILE".into()]),
+            ..exec
+        };
+        assert_eq!(
+            cache.path_with_env(&dropped, no_env),
+            cache.path_with_env(&dropped, profile)
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn credential_cache_is_only_accessible_by_the_user() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let cache = CredentialCache::new(dir.path().join("credentials"));
+        fs::create_dir(&cache.dir).unwrap();
+        fs::set_permissions(&cache.dir, fs::Permissions::from_mode(0o755)).unwrap();
+        let exec: ExecConfig = serde_json::from_value(json!({
+            "apiVersion": "client.authentication.k8s.io/v1",
+            "command": "aws",
+        }))
+        .unwrap();
+
+        cache.write(&exec, "{}").unwrap();
+        let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(&cache.dir), 0o700);
+        assert_eq!(mode(&cache.path(&exec)), 0o600);
+        assert_eq!(cache.read(&exec).as_deref(), Some("{}"));
     }
 
     #[tokio::test]
@@ -1017,4 +1521,53 @@ users:
             assert_eq!(cfg.auth_infos[0].name, "admin@k3d-k3s-default");
         }
     }
+
+    #[test]
+    fn auth_provider_tokens_are_persisted() {
+        let config = r#"
+apiVersion: v1
+kind: Config
+current-context: oidc
+users:
+- name: other
+  user:
+    token: secret
+- name: oidc
+  user:
+    auth-provider:
+      name: oidc
+      config:
+        client-id: kube
+        id-token: old
+        idp-issuer-url: https://issuer.example.com
+"#;
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), config).unwrap();
+        let persister = AuthProviderPersister {
+            path: file.path().to_owned(),
+            user: "oidc".into(),
+        };
+        persister
+            .persist(&[("id-token", "new"), ("refresh-token", "refresh")])
+            .unwrap();
+
+        let kubeconfig = Kubeconfig::read_from(file.path()).unwrap();
+        assert_eq!(kubeconfig.current_context.as_deref(), Some("oidc"));
+        let other = kubeconfig.auth_infos[0].auth_info.as_ref().unwrap();
+        assert!(other.token.is_some());
+        let oidc = kubeconfig.auth_infos[1].auth_info.as_ref().unwrap();
+        let provider = oidc.auth_provider.as_ref().unwrap();
+        assert_eq!(provider.config["id-token"], "new");
+        assert_eq!(provider.config["refresh-token"], "refresh");
+        assert_eq!(provider.config["client-id"], "kube");
+
+        let unknown = AuthProviderPersister {
+            user: "unknown".into(),
+            ..persister
+        };
+        assert!(matches!(
+            unknown.persist(&[("id-token", "new")]),
+            Err(KubeconfigError::FindAuthProvider(_))
+        ));
+    }
 }
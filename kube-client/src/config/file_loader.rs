@@ -1,3 +1,5 @@
+use std::net::IpAddr;
+
 use super::{
     file_config::{AuthInfo, Cluster, Context, Kubeconfig},
     KubeconfigError,
@@ -14,6 +16,129 @@ pub struct KubeConfigOptions {
     pub user: Option<String>,
 }
 
+/// Overrides of the loaded kubeconfig, like the global flags of kubectl
+///
+/// This is meant for CLI tools that accept `--context`, `--cluster`, `--user`, `--namespace`,
+/// `--server` and the `--as` impersonation flags, and should treat them like kubectl does:
+/// the context selects the cluster, user and namespace, and each of those can be overridden on its
+/// own. The server only replaces the URL of the selected cluster, so its certificates still apply.
+///
+/// ```no_run
+/// use kube::{config::ConfigOverrides, Config};
+/// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+/// let overrides = ConfigOverrides::default().context("staging").namespace("kube-system");
+/// let config = Config::from_kubeconfig_with_overrides(&overrides).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default, Clone, Debug)]
+pub struct ConfigOverrides {
+    /// The named context to load instead of the current context, like `--context`
+    pub context: Option<String>,
+    /// The cluster to load instead of the cluster of the context, like `--cluster`
+    pub cluster: Option<String>,
+    /// The user to load instead of the user of the context, like `--user`
+    pub user: Option<String>,
+    /// The default namespace instead of the namespace of the context, like `--namespace`
+    pub namespace: Option<String>,
+    /// The URL of the apiserver instead of the server of the cluster, like `--server`
+    pub server: Option<String>,
+    /// The user to impersonate, like `--as`
+    pub impersonate: Option<String>,
+    /// The groups to impersonate, like `--as-group`
+    pub impersonate_groups: Vec<String>,
+    /// The uid to impersonate, like `--as-uid`
+    pub impersonate_uid: Option<String>,
+}
+
+impl ConfigOverrides {
+    /// Load the named context instead of the current context
+    #[must_use]
+    pub fn context(mut self, context: impl Into<String>) -> Self {
+        self.context = Some(context.into());
+        self
+    }
+
+    /// Load the named cluster instead of the cluster of the context
+    #[must_use]
+    pub fn cluster(mut self, cluster: impl Into<String>) -> Self {
+        self.cluster = Some(cluster.into());
+        self
+    }
+
+    /// Load the named user instead of the user of the context
+    #[must_use]
+    pub fn user(mut self, user: impl Into<String>) -> Self {
+        self.user = Some(user.into());
+        self
+    }
+
+    /// Use `namespace` as the default namespace
+    #[must_use]
+    pub fn namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    /// Connect to the apiserver at `server`
+    #[must_use]
+    pub fn server(mut self, server: impl Into<String>) -> Self {
+        self.server = Some(server.into());
+        self
+    }
+
+    /// Act as `user` for all requests
+    #[must_use]
+    pub fn impersonate(mut self, user: impl Into<String>) -> Self {
+        self.impersonate = Some(user.into());
+        self
+    }
+
+    /// Act as a member of `group`, which can be repeated like `--as-group`
+    #[must_use]
+    pub fn impersonate_group(mut self, group: impl Into<String>) -> Self {
+        self.impersonate_groups.push(group.into());
+        self
+    }
+
+    /// Act as the user with `uid`
+    #[must_use]
+    pub fn impersonate_uid(mut self, uid: impl Into<String>) -> Self {
+        self.impersonate_uid = Some(uid.into());
+        self
+    }
+
+    pub(super) fn options(&self) -> KubeConfigOptions {
+        KubeConfigOptions {
+            context: self.context.clone(),
+            cluster: self.cluster.clone(),
+            user: self.user.clone(),
+        }
+    }
+
+    pub(super) fn apply(&self, loader: &mut ConfigLoader) {
+        if let Some(namespace) = &self.namespace {
+            loader.current_context.namespace = Some(namespace.clone());
+        }
+        if let Some(server) = &self.server {
+            loader.cluster.server = Some(server.clone());
+            // the exec plugin was given the cluster before it was overridden
+            if let Some(cluster) = loader.user.exec.as_mut().and_then(|exec| exec.cluster.as_mut()) {
+                cluster.server = Some(server.clone());
+            }
+        }
+        if let Some(user) = &self.impersonate {
+            loader.user.impersonate = Some(user.clone());
+        }
+        if !self.impersonate_groups.is_empty() {
+            loader.user.impersonate_groups = Some(self.impersonate_groups.clone());
+        }
+        if let Some(uid) = &self.impersonate_uid {
+            loader.user.impersonate_uid = Some(uid.clone());
+        }
+    }
+}
+
 /// ConfigLoader loads current context, cluster, and authentication information
 /// from a kubeconfig file.
 #[derive(Clone, Debug)]
@@ -101,6 +226,8 @@ impl ConfigLoader {
             if exec_config.provide_cluster_info {
                 exec_config.cluster = Some((&cluster).try_into()?);
             }
+            exec_config.cluster_name = Some(cluster_name.clone());
+            exec_config.user_name = user_name.cloned();
         }
 
         Ok(ConfigLoader {
@@ -121,19 +248,203 @@ impl ConfigLoader {
     }
 
     pub fn proxy_url(&self) -> Result<Option<http::Uri>, KubeconfigError> {
-        let nonempty = |o: Option<String>| o.filter(|s| !s.is_empty());
+        let cluster = &self.cluster;
+        let proxy = proxy_for_server(cluster.proxy_url.as_deref(), cluster.server.as_deref(), |var| {
+            std::env::var(var).ok()
+        });
+        proxy
+            .map(|proxy| proxy.parse::<http::Uri>().map_err(KubeconfigError::ParseProxyUrl))
+            .transpose()
+    }
+}
 
-        if let Some(proxy) = nonempty(self.cluster.proxy_url.clone())
-            .or_else(|| nonempty(std::env::var("HTTPS_PROXY").ok()))
-            .or_else(|| nonempty(std::env::var("https_proxy").ok()))
-        {
-            Ok(Some(
-                proxy
-                    .parse::<http::Uri>()
-                    .map_err(KubeconfigError::ParseProxyUrl)?,
-            ))
-        } else {
-            Ok(None)
+// Like client-go, the `proxy-url` of the cluster is always used. Otherwise, the proxy environment
+// variable for the scheme of the server is used, unless the server is `localhost` or a loopback
+// address, or `NO_PROXY` excludes it.
+fn proxy_for_server(
+    proxy_url: Option<&str>,
+    server: Option<&str>,
+    var: impl Fn(&str) -> Option<String>,
+) -> Option<String> {
+    let nonempty = |o: Option<String>| o.filter(|s| !s.is_empty());
+    if let Some(proxy) = nonempty(proxy_url.map(str::to_owned)) {
+        return Some(proxy);
+    }
+
+    let server = server.and_then(|server| server.parse::<http::Uri>().ok());
+    if server.as_ref().is_some_and(is_loopback) {
+        return None;
+    }
+    let vars = match server.as_ref().and_then(http::Uri::scheme_str) {
+        Some("http") => ["HTTP_PROXY", "http_proxy"],
+        _ => ["HTTPS_PROXY", "https_proxy"],
+    };
+    let proxy = vars.into_iter().find_map(|v| nonempty(var(v)))?;
+    let no_proxy = nonempty(var("NO_PROXY")).or_else(|| nonempty(var("no_proxy")));
+    match (server, no_proxy) {
+        (Some(server), Some(no_proxy)) if is_no_proxy(&server, &no_proxy) => None,
+        _ => Some(proxy),
+    }
+}
+
+// Go's `httpproxy` never proxies these, whatever `NO_PROXY` says
+fn is_loopback(server: &http::Uri) -> bool {
+    let Some(host) = server.host() else {
+        return false;
+    };
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    host.eq_ignore_ascii_case("localhost") || host.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+// Whether an entry of the comma-separated `no_proxy` matches the server, like Go's `httpproxy`:
+// `*` matches all, domains match themselves and their subdomains, `.domain` only its subdomains,
+// and IPs may be given as CIDRs
+fn is_no_proxy(server: &http::Uri, no_proxy: &str) -> bool {
+    let Some(host) = server.host() else {
+        return false;
+    };
+    let host = host
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_ascii_lowercase();
+    let port = server.port_u16().unwrap_or(match server.scheme_str() {
+        Some("http") => 80,
+        _ => 443,
+    });
+    let ip = host.parse::<IpAddr>().ok();
+
+    no_proxy
+        .split(',')
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+        .any(|entry| {
+            if entry == "*" {
+                return true;
+            }
+            if let Some((network, prefix)) = entry.split_once('/') {
+                let (Ok(network), Ok(prefix), Some(ip)) =
+                    (network.parse::<IpAddr>(), prefix.parse::<u32>(), ip)
+                else {
+                    return false;
+                };
+                return in_network(ip, network, prefix);
+            }
+            let entry = entry.to_ascii_lowercase();
+            // an IPv6 entry has colons, but only a port if it is bracketed
+            let (entry_host, entry_port) = match entry.rsplit_once(':') {
+                Some((h, p)) if !h.contains(':') || h.ends_with(']') => (h, p.parse::<u16>().ok()),
+                _ => (entry.as_str(), None),
+            };
+            let entry_host = entry_host.trim_start_matches('[').trim_end_matches(']');
+            if entry_port.is_some_and(|p| p != port) {
+                return false;
+            }
+            if let (Ok(entry_ip), Some(ip)) = (entry_host.parse::<IpAddr>(), ip) {
+                return entry_ip == ip;
+            }
+            let entry_host = entry_host.strip_prefix('*').unwrap_or(entry_host);
+            let (domain, match_host) = match entry_host.strip_prefix('.') {
+                Some(domain) => (domain, false),
+                None => (entry_host, true),
+            };
+            host.ends_with(&format!(".{domain}")) || (match_host && host == domain)
+        })
+}
+
+fn in_network(ip: IpAddr, network: IpAddr, prefix: u32) -> bool {
+    match (ip, network) {
+        (IpAddr::V4(ip), IpAddr::V4(network)) if prefix <= 32 => {
+            let mask = u32::MAX.checked_shl(32 - prefix).unwrap_or(0);
+            u32::from(ip) & mask == u32::from(network) & mask
+        }
+        (IpAddr::V6(ip), IpAddr::V6(network)) if prefix <= 128 => {
+            let mask = u128::MAX.checked_shl(128 - prefix).unwrap_or(0);
+            u128::from(ip) & mask == u128::from(network) & mask
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::proxy_for_server;
+
+    fn proxy(proxy_url: Option<&str>, server: &str, env: &[(&str, &str)]) -> Option<String> {
+        proxy_for_server(proxy_url, Some(server), |var| {
+            env.iter()
+                .find(|(name, _)| *name == var)
+                .map(|(_, value)| value.to_string())
+        })
+    }
+
+    #[test]
+    fn proxy_url_of_the_cluster_wins() {
+        let env = [("HTTPS_PROXY", "http://env:3128"), ("NO_PROXY", "*")];
+        assert_eq!(
+            proxy(Some("socks5://cluster:1080"), "https://k8s.example.com", &env).as_deref(),
+            Some("socks5://cluster:1080")
+        );
+        assert_eq!(proxy(None, "https://k8s.example.com", &env), None);
+    }
+
+    #[test]
+    fn proxy_env_follows_the_server_scheme() {
+        let env = [
+            ("HTTPS_PROXY", "http://secure:3128"),
+            ("http_proxy", "http://plain:3128"),
+        ];
+        assert_eq!(
+            proxy(None, "https://k8s.example.com", &env).as_deref(),
+            Some("http://secure:3128")
+        );
+        assert_eq!(
+            proxy(None, "http://k8s.example.com:8080", &env).as_deref(),
+            Some("http://plain:3128")
+        );
+    }
+
+    #[test]
+    fn no_proxy_excludes_servers() {
+        let env = [
+            ("HTTPS_PROXY", "http://proxy:3128"),
+            (
+                "NO_PROXY",
+                "localhost, .internal,example.org:8443,10.0.0.0/8,[::1]",
+            ),
+        ];
+        for server in [
+            "https://localhost:6443",
+            "https://k8s.internal",
+            "https://api.example.org:8443",
+            "https://10.96.0.1",
+            "https://[::1]:6443",
+        ] {
+            assert_eq!(proxy(None, server, &env), None, "{server}");
+        }
+        for server in [
+            "https://example.org",
+            "https://internal",
+            "https://notinternal",
+            "https://11.0.0.1",
+            "https://k8s.example.com",
+        ] {
+            assert!(proxy(None, server, &env).is_some(), "{server}");
+        }
+    }
+
+    #[test]
+    fn loopback_servers_are_never_proxied() {
+        let env = [("HTTPS_PROXY", "http://proxy:3128"), ("HTTP_PROXY", "http://proxy:3128")];
+        for server in [
+            "https://localhost:6443",
+            "https://LOCALHOST",
+            "https://127.0.0.1:6443",
+            "http://127.1.2.3:8080",
+            "https://[::1]:6443",
+        ] {
+            assert_eq!(proxy(None, server, &env), None, "{server}");
         }
+        assert!(proxy(None, "https://k8s.localhost.example.com", &env).is_some());
+        assert!(proxy(None, "https://10.96.0.1", &env).is_some());
     }
 }
@@ -12,12 +12,14 @@ use std::{path::PathBuf, time::Duration};
 use http::{HeaderName, HeaderValue};
 use thiserror::Error;
 
+mod env_config;
 mod file_config;
 mod file_loader;
 mod incluster_config;
 
+pub use env_config::Error as EnvConfigError;
 use file_loader::ConfigLoader;
-pub use file_loader::KubeConfigOptions;
+pub use file_loader::{ConfigOverrides, KubeConfigOptions};
 pub use incluster_config::Error as InClusterError;
 
 /// Failed to infer config
@@ -65,6 +67,18 @@ pub enum KubeconfigError {
     #[error("failed to parse kubeconfig YAML: {0}")]
     Parse(#[source] serde_yaml::Error),
 
+    /// Failed to serialize kubeconfig YAML
+    #[error("failed to serialize kubeconfig YAML: {0}")]
+    Serialize(#[source] serde_yaml::Error),
+
+    /// Failed to write kubeconfig
+    #[error("failed to write kubeconfig to '{1:?}': {0}")]
+    WriteConfig(#[source] std::io::Error, PathBuf),
+
+    /// Failed to find the auth provider of a user in the kubeconfig
+    #[error("failed to find the auth provider of user '{0}' in kubeconfig")]
+    FindAuthProvider(String),
+
     /// The structure of the parsed kubeconfig is invalid
     #[error("the structure of the parsed kubeconfig is invalid: {0}")]
     InvalidStructure(#[source] serde_yaml::Error),
@@ -131,10 +145,26 @@ pub enum LoadDataError {
 pub struct Config {
     /// The configured cluster url
     pub cluster_url: http::Uri,
+    /// Additional apiserver URLs to fail over to when connecting to `cluster_url` fails
+    ///
+    /// Connections are still verified against the host of `cluster_url` (or `tls_server_name`),
+    /// so the serving certificates of all endpoints must be valid for it.
+    pub fallback_urls: Vec<http::Uri>,
     /// The configured default namespace
     pub default_namespace: String,
     /// The configured root certificate
     pub root_cert: Option<Vec<Vec<u8>>>,
+    /// A file to reload `root_cert` from when it changes, like the CA bundle mounted in-cluster
+    ///
+    /// The file is checked whenever a connection is established, with both TLS features.
+    /// Its certificates are trusted instead of `root_cert`, which should hold the same ones,
+    /// so set `root_cert` with [`Config::root_certs`] to stop reloading the file.
+    pub root_cert_file: Option<PathBuf>,
+    /// Root certificates trusted in addition to `root_cert` or `root_cert_file`
+    ///
+    /// Without those, these are trusted in addition to the roots of the platform.
+    /// See [`Config::add_root_certs_pem`].
+    pub extra_root_certs: Vec<Vec<u8>>,
     /// Set the timeout for connecting to the Kubernetes API.
     ///
     /// A value of `None` means no timeout
@@ -147,20 +177,95 @@ pub struct Config {
     ///
     /// A value of `None` means no timeout
     pub write_timeout: Option<std::time::Duration>,
+    /// Set the overall timeout for receiving a response from the Kubernetes API.
+    ///
+    /// Watch requests are exempt, and list requests with a server side timeout get at least that long.
+    /// A value of `None` means no timeout
+    pub timeout: Option<std::time::Duration>,
+    /// How long an idle connection is kept in the pool for reuse
+    ///
+    /// A value of `None` keeps idle connections until the server closes them
+    pub pool_idle_timeout: Option<std::time::Duration>,
+    /// The maximum number of idle connections kept in the pool
+    ///
+    /// A value of `None` means no limit
+    pub pool_max_idle_per_host: Option<usize>,
+    /// Interval of TCP keepalive probes on idle connections, to detect peers that vanished
+    ///
+    /// A value of `None` disables TCP keepalive
+    pub tcp_keepalive: Option<std::time::Duration>,
+    /// Interval of HTTP/2 keepalive pings, which are also sent while no request is in flight
+    ///
+    /// Only has an effect with the `http2` feature, when the apiserver negotiates HTTP/2.
+    /// HTTP/2 is offered over ALPN with both `rustls-tls` and `openssl-tls`, except for connection
+    /// upgrades (exec, attach and port-forward), which are sent over separate HTTP/1.1 connections.
+    /// A value of `None` disables keepalive pings
+    pub http2_keep_alive_interval: Option<std::time::Duration>,
+    /// How long to wait for the acknowledgement of a HTTP/2 keepalive ping before closing the connection
+    ///
+    /// A value of `None` uses the default of 20 seconds
+    pub http2_keep_alive_timeout: Option<std::time::Duration>,
+    /// The maximum number of concurrent streams opened on a HTTP/2 connection,
+    /// until the apiserver announces its own limit
+    ///
+    /// A value of `None` uses the default of 100 streams
+    pub http2_initial_max_send_streams: Option<usize>,
     /// Whether to accept invalid certificates
+    ///
+    /// Set from `insecure-skip-tls-verify` in a kubeconfig. This makes connections insecure, which
+    /// is logged as a warning whenever a client is created.
     pub accept_invalid_certs: bool,
+    /// Whether to accept certificates that are not valid for the hostname of the apiserver
+    ///
+    /// The certificate still has to be signed by one of the trusted root certificates.
+    /// Prefer setting `tls_server_name` to a name the certificate is valid for.
+    pub accept_invalid_hostnames: bool,
     /// Stores information to tell the cluster who you are.
     pub auth_info: AuthInfo,
+    /// Where to write tokens refreshed by the `auth_provider` of `auth_info` back to
+    ///
+    /// Unset by default, so refreshed tokens only live as long as the client.
+    /// See [`Config::persist_refreshed_tokens`].
+    pub auth_provider_persister: Option<AuthProviderPersister>,
+    /// Where to cache the tokens of the exec plugin of `auth_info` between runs
+    ///
+    /// Unset by default, so the plugin runs whenever a client is created.
+    /// See [`Config::cache_credentials`].
+    pub credential_cache: Option<CredentialCache>,
     /// Whether to disable compression (would only have an effect when the `gzip` feature is enabled)
     pub disable_compression: bool,
-    /// Optional proxy URL. Proxy support requires the `socks5` feature.
+    /// Optional proxy URL
+    ///
+    /// `socks5://` and `socks5h://` proxies, optionally with a `user:password@`, require the
+    /// `socks5` feature, and `http://` proxies the `http-proxy` feature.
+    ///
+    /// From a kubeconfig, this is the `proxy-url` of the cluster, or else `HTTPS_PROXY` (`HTTP_PROXY`
+    /// for `http://` servers) unless the server is excluded by `NO_PROXY`.
     pub proxy_url: Option<http::Uri>,
+    /// Connect to the apiserver through the unix socket at this path, instead of the host of `cluster_url`
+    ///
+    /// The `cluster_url` is still used for the `Host` header and TLS, and the proxy is ignored.
+    /// Read from kubeconfig clusters with a `server` like `unix:///run/k3s/apiserver.sock`,
+    /// which are sent to `http://localhost`.
+    pub unix_socket: Option<PathBuf>,
     /// If set, apiserver certificate will be validated to contain this string
     ///
     /// If not set, the `cluster_url` is used instead
+    ///
+    /// It is also sent as the TLS server name (SNI), with both `rustls-tls` and `openssl-tls`.
     pub tls_server_name: Option<String>,
     /// Headers to pass with every request.
     pub headers: Vec<(HeaderName, HeaderValue)>,
+    /// Signer applied to every request after the authentication and extra headers are set.
+    #[cfg(feature = "client")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "client")))]
+    pub request_signer: Option<std::sync::Arc<dyn crate::client::middleware::RequestSigner>>,
+    /// Provider of the `Authorization` header of every request, instead of the [`Config::auth_info`]
+    ///
+    /// Client certificates of the `auth_info` are still used.
+    #[cfg(feature = "client")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "client")))]
+    pub credential_provider: Option<std::sync::Arc<dyn crate::client::AuthProvider>>,
 }
 
 impl Config {
@@ -172,17 +277,35 @@ impl Config {
     pub fn new(cluster_url: http::Uri) -> Self {
         Self {
             cluster_url,
+            fallback_urls: Vec::new(),
             default_namespace: String::from("default"),
             root_cert: None,
+            root_cert_file: None,
+            extra_root_certs: Vec::new(),
             connect_timeout: Some(DEFAULT_CONNECT_TIMEOUT),
             read_timeout: Some(DEFAULT_READ_TIMEOUT),
             write_timeout: Some(DEFAULT_WRITE_TIMEOUT),
+            timeout: None,
+            pool_idle_timeout: Some(DEFAULT_POOL_IDLE_TIMEOUT),
+            pool_max_idle_per_host: None,
+            tcp_keepalive: None,
+            http2_keep_alive_interval: None,
+            http2_keep_alive_timeout: None,
+            http2_initial_max_send_streams: None,
             accept_invalid_certs: false,
+            accept_invalid_hostnames: false,
             auth_info: AuthInfo::default(),
             disable_compression: false,
             proxy_url: None,
+            unix_socket: None,
             tls_server_name: None,
             headers: Vec::new(),
+            auth_provider_persister: None,
+            credential_cache: None,
+            #[cfg(feature = "client")]
+            request_signer: None,
+            #[cfg(feature = "client")]
+            credential_provider: None,
         }
     }
 
@@ -246,26 +369,58 @@ impl Config {
         Self::incluster_with_uri(incluster_config::kube_dns())
     }
 
+    /// Load a config from environment variables only, without a kubeconfig or in-cluster files
+    ///
+    /// This is meant for CI environments that inject the credentials of a cluster:
+    ///
+    /// - `KUBERNETES_MASTER`: The URL of the apiserver (required)
+    /// - `KUBERNETES_TOKEN`: A bearer token to authenticate with
+    /// - `KUBERNETES_CA_DATA`: The base64-encoded PEM certificates of the apiserver CA
+    /// - `KUBERNETES_NAMESPACE`: The default namespace, instead of `default`
+    ///
+    /// Unlike [`Config::infer`], this never falls back to other sources.
+    pub fn from_env() -> Result<Self, EnvConfigError> {
+        env_config::load(|name| std::env::var(name).ok())
+    }
+
     fn incluster_with_uri(cluster_url: http::uri::Uri) -> Result<Self, InClusterError> {
         let default_namespace = incluster_config::load_default_ns()?;
         let root_cert = incluster_config::load_cert()?;
 
         Ok(Self {
             cluster_url,
+            fallback_urls: Vec::new(),
             default_namespace,
             root_cert: Some(root_cert),
+            root_cert_file: Some(incluster_config::cert_file()),
+            extra_root_certs: Vec::new(),
             connect_timeout: Some(DEFAULT_CONNECT_TIMEOUT),
             read_timeout: Some(DEFAULT_READ_TIMEOUT),
             write_timeout: Some(DEFAULT_WRITE_TIMEOUT),
+            timeout: None,
+            pool_idle_timeout: Some(DEFAULT_POOL_IDLE_TIMEOUT),
+            pool_max_idle_per_host: None,
+            tcp_keepalive: None,
+            http2_keep_alive_interval: None,
+            http2_keep_alive_timeout: None,
+            http2_initial_max_send_streams: None,
             accept_invalid_certs: false,
+            accept_invalid_hostnames: false,
             auth_info: AuthInfo {
                 token_file: Some(incluster_config::token_file()),
                 ..Default::default()
             },
             disable_compression: false,
             proxy_url: None,
+            unix_socket: None,
             tls_server_name: None,
             headers: Vec::new(),
+            auth_provider_persister: None,
+            credential_cache: None,
+            #[cfg(feature = "client")]
+            request_signer: None,
+            #[cfg(feature = "client")]
+            credential_provider: None,
         })
     }
 
@@ -279,6 +434,36 @@ impl Config {
         Self::new_from_loader(loader).await
     }
 
+    /// Create configuration for the named context of the default local config file
+    ///
+    /// Like [`Config::from_kubeconfig`], this respects the `$KUBECONFIG` evar. To hop between the
+    /// contexts of a kubeconfig that is already loaded, use [`Config::from_custom_kubeconfig`].
+    ///
+    /// ```no_run
+    /// use kube::{config::{KubeConfigOptions, Kubeconfig}, Client, Config};
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// let staging = Client::try_from(Config::from_kubeconfig_context("staging").await?)?;
+    ///
+    /// let kubeconfig = Kubeconfig::read()?;
+    /// for context in &kubeconfig.contexts {
+    ///     let options = KubeConfigOptions {
+    ///         context: Some(context.name.clone()),
+    ///         ..KubeConfigOptions::default()
+    ///     };
+    ///     let config = Config::from_custom_kubeconfig(kubeconfig.clone(), &options).await?;
+    ///     println!("{}: {}", context.name, config.cluster_url);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn from_kubeconfig_context(context: &str) -> Result<Self, KubeconfigError> {
+        Self::from_kubeconfig(&KubeConfigOptions {
+            context: Some(context.to_owned()),
+            ..KubeConfigOptions::default()
+        })
+        .await
+    }
+
     /// Create configuration from a [`Kubeconfig`] struct
     ///
     /// This bypasses kube's normal config parsing to obtain custom functionality.
@@ -290,14 +475,40 @@ impl Config {
         Self::new_from_loader(loader).await
     }
 
+    /// Create configuration from the default local config file with kubectl-style [`ConfigOverrides`]
+    ///
+    /// Like [`Config::from_kubeconfig`], this respects the `$KUBECONFIG` evar.
+    pub async fn from_kubeconfig_with_overrides(
+        overrides: &ConfigOverrides,
+    ) -> Result<Self, KubeconfigError> {
+        let mut loader = ConfigLoader::new_from_options(&overrides.options()).await?;
+        overrides.apply(&mut loader);
+        Self::new_from_loader(loader).await
+    }
+
+    /// Create configuration from a [`Kubeconfig`] struct with kubectl-style [`ConfigOverrides`]
+    pub async fn from_custom_kubeconfig_with_overrides(
+        kubeconfig: Kubeconfig,
+        overrides: &ConfigOverrides,
+    ) -> Result<Self, KubeconfigError> {
+        let mut loader = ConfigLoader::new_from_kubeconfig(kubeconfig, &overrides.options()).await?;
+        overrides.apply(&mut loader);
+        Self::new_from_loader(loader).await
+    }
+
     async fn new_from_loader(loader: ConfigLoader) -> Result<Self, KubeconfigError> {
-        let cluster_url = loader
+        let server = loader
             .cluster
             .server
             .clone()
-            .ok_or(KubeconfigError::MissingClusterUrl)?
-            .parse::<http::Uri>()
-            .map_err(KubeconfigError::ParseClusterUrl)?;
+            .ok_or(KubeconfigError::MissingClusterUrl)?;
+        let (cluster_url, unix_socket) = match server.strip_prefix("unix://") {
+            Some(path) => (http::Uri::from_static("http://localhost"), Some(PathBuf::from(path))),
+            None => (
+                server.parse::<http::Uri>().map_err(KubeconfigError::ParseClusterUrl)?,
+                None,
+            ),
+        };
 
         let default_namespace = loader
             .current_context
@@ -306,6 +517,17 @@ impl Config {
             .unwrap_or_else(|| String::from("default"));
 
         let accept_invalid_certs = loader.cluster.insecure_skip_tls_verify.unwrap_or(false);
+        if accept_invalid_certs {
+            // configs are loaded again on every reload or client, so this is only logged once
+            static WARNED: std::sync::Once = std::sync::Once::new();
+            WARNED.call_once(|| {
+                tracing::warn!(
+                    %server,
+                    "insecure-skip-tls-verify is set in the kubeconfig, TLS certificates of the apiserver \
+                     are NOT verified and connections are open to interception"
+                );
+            });
+        }
         let disable_compression = loader.cluster.disable_compression.unwrap_or(false);
 
         let mut root_cert = None;
@@ -313,20 +535,42 @@ impl Config {
         if let Some(ca_bundle) = loader.ca_bundle()? {
             root_cert = Some(ca_bundle);
         }
+        let root_cert_file = match &loader.cluster.certificate_authority_data {
+            Some(_) => None,
+            None => loader.cluster.certificate_authority.as_ref().map(PathBuf::from),
+        };
 
         Ok(Self {
             cluster_url,
+            fallback_urls: Vec::new(),
             default_namespace,
             root_cert,
+            root_cert_file,
+            extra_root_certs: Vec::new(),
             connect_timeout: Some(DEFAULT_CONNECT_TIMEOUT),
             read_timeout: Some(DEFAULT_READ_TIMEOUT),
             write_timeout: Some(DEFAULT_WRITE_TIMEOUT),
+            timeout: None,
+            pool_idle_timeout: Some(DEFAULT_POOL_IDLE_TIMEOUT),
+            pool_max_idle_per_host: None,
+            tcp_keepalive: None,
+            http2_keep_alive_interval: None,
+            http2_keep_alive_timeout: None,
+            http2_initial_max_send_streams: None,
             accept_invalid_certs,
+            accept_invalid_hostnames: false,
             disable_compression,
             proxy_url: loader.proxy_url()?,
+            unix_socket,
             auth_info: loader.user,
             tls_server_name: loader.cluster.tls_server_name,
             headers: Vec::new(),
+            auth_provider_persister: None,
+            credential_cache: None,
+            #[cfg(feature = "client")]
+            request_signer: None,
+            #[cfg(feature = "client")]
+            credential_provider: None,
         })
     }
 
@@ -339,6 +583,7 @@ impl Config {
     ///
     /// - `KUBE_RS_DEBUG_IMPERSONATE_USER`: A Kubernetes user to impersonate, for example: `system:serviceaccount:default:foo` will impersonate the `ServiceAccount` `foo` in the `Namespace` `default`
     /// - `KUBE_RS_DEBUG_IMPERSONATE_GROUP`: A Kubernetes group to impersonate, multiple groups may be specified by separating them with commas
+    /// - `KUBE_RS_DEBUG_IMPERSONATE_UID`: The uid of the impersonated user
     /// - `KUBE_RS_DEBUG_OVERRIDE_URL`: A Kubernetes cluster URL to use rather than the one specified in the config, useful for proxying traffic through `kubectl proxy`
     pub fn apply_debug_overrides(&mut self) {
         // Log these overrides loudly, to emphasize that this is only a debugging aid, and should not be relied upon in production
@@ -351,6 +596,10 @@ impl Config {
             tracing::warn!(?impersonate_groups, "impersonating groups");
             self.auth_info.impersonate_groups = Some(impersonate_groups);
         }
+        if let Ok(impersonate_uid) = std::env::var("KUBE_RS_DEBUG_IMPERSONATE_UID") {
+            tracing::warn!(?impersonate_uid, "impersonating uid");
+            self.auth_info.impersonate_uid = Some(impersonate_uid);
+        }
         if let Ok(url) = std::env::var("KUBE_RS_DEBUG_OVERRIDE_URL") {
             tracing::warn!(?url, "overriding cluster URL");
             match url.parse() {
@@ -368,6 +617,164 @@ impl Config {
         }
     }
 
+    /// Act as `user` for all requests, by sending the `Impersonate-User` header
+    ///
+    /// The authenticated user needs the `impersonate` verb on `users` for this to be accepted.
+    /// This overrides the `as` field of the kubeconfig. A user can be a `ServiceAccount`, e.g.
+    /// `system:serviceaccount:default:foo`, which is useful to test RBAC rules.
+    #[must_use]
+    pub fn impersonate(mut self, user: impl Into<String>) -> Self {
+        self.auth_info.impersonate = Some(user.into());
+        self
+    }
+
+    /// Act as a member of `groups`, by sending an `Impersonate-Group` header per group
+    ///
+    /// Requires an impersonated user, see [`Config::impersonate`].
+    #[must_use]
+    pub fn impersonate_groups<I, S>(mut self, groups: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.auth_info.impersonate_groups = Some(groups.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Act as the user with `uid`, by sending the `Impersonate-Uid` header
+    ///
+    /// Requires an impersonated user, see [`Config::impersonate`].
+    #[must_use]
+    pub fn impersonate_uid(mut self, uid: impl Into<String>) -> Self {
+        self.auth_info.impersonate_uid = Some(uid.into());
+        self
+    }
+
+    /// Trust the DER-encoded root certificates `certs`, instead of the ones in [`Config::root_cert`]
+    ///
+    /// The [`Config::root_cert_file`] is no longer reloaded, since it would replace these.
+    #[must_use]
+    pub fn root_certs(mut self, certs: Vec<Vec<u8>>) -> Self {
+        self.root_cert = Some(certs);
+        self.root_cert_file = None;
+        self
+    }
+
+    /// Trust the PEM-encoded root certificates in `pem`, in addition to the ones already trusted
+    ///
+    /// These are added to the [`Config::root_cert`], the [`Config::root_cert_file`], which is still
+    /// reloaded, or the roots of the platform when neither is set. Meant for adding e.g. the CA of a
+    /// re-encrypting proxy in front of the apiserver. See [`Config::extra_root_certs`].
+    pub fn add_root_certs_pem(mut self, pem: &[u8]) -> Result<Self, pem::PemError> {
+        self.extra_root_certs.extend(certs(pem)?);
+        Ok(self)
+    }
+
+    /// Verify the certificate of the apiserver against `name`, rather than the host of `cluster_url`
+    #[must_use]
+    pub fn tls_server_name(mut self, name: impl Into<String>) -> Self {
+        self.tls_server_name = Some(name.into());
+        self
+    }
+
+    /// Accept apiserver certificates that are not valid for its hostname
+    ///
+    /// This is dangerous, since any certificate signed by the root certificates is accepted for the
+    /// apiserver, and logs a warning whenever a client is created. See [`Config::accept_invalid_hostnames`].
+    #[must_use]
+    pub fn danger_accept_invalid_hostnames(mut self) -> Self {
+        self.accept_invalid_hostnames = true;
+        self
+    }
+
+    /// Accept any apiserver certificate, like `insecure-skip-tls-verify: true` in a kubeconfig
+    ///
+    /// This makes connections insecure and should only be used with development clusters that have
+    /// self-signed certificates. A warning is logged whenever a client is created.
+    /// See [`Config::accept_invalid_certs`].
+    #[must_use]
+    pub fn danger_accept_invalid_certs(mut self) -> Self {
+        self.accept_invalid_certs = true;
+        self
+    }
+
+    /// Write tokens refreshed by the `oidc` auth provider back to a kubeconfig file, like kubectl does
+    ///
+    /// This lets other clients and later runs reuse the refreshed tokens, which matters for
+    /// providers that rotate refresh tokens, since the old refresh token in the file stops working.
+    ///
+    /// ```no_run
+    /// use kube::config::{AuthProviderPersister, Config, KubeConfigOptions};
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// let options = KubeConfigOptions::default();
+    /// let mut config = Config::from_kubeconfig(&options).await?;
+    /// if let Some(persister) = AuthProviderPersister::find("oidc-user")? {
+    ///     config = config.persist_refreshed_tokens(persister);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn persist_refreshed_tokens(mut self, persister: AuthProviderPersister) -> Self {
+        self.auth_provider_persister = Some(persister);
+        self
+    }
+
+    /// Authenticate requests with the header of a custom [`AuthProvider`](crate::client::AuthProvider)
+    ///
+    /// This replaces the token, basic auth and auth plugins of the [`Config::auth_info`], including its
+    /// `auth-provider`.
+    #[cfg(feature = "client")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "client")))]
+    #[must_use]
+    pub fn credential_provider(mut self, provider: impl crate::client::AuthProvider) -> Self {
+        self.credential_provider = Some(std::sync::Arc::new(provider));
+        self
+    }
+
+    /// Cache the tokens of exec plugins on disk, so that later runs can reuse them until they expire
+    ///
+    /// This is meant for short-lived CLI invocations, which otherwise run slow auth plugins every time.
+    ///
+    /// ```no_run
+    /// use kube::config::{Config, CredentialCache};
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut config = Config::infer().await?;
+    /// if let Some(cache) = CredentialCache::in_home_dir() {
+    ///     config = config.cache_credentials(cache);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn cache_credentials(mut self, cache: CredentialCache) -> Self {
+        self.credential_cache = Some(cache);
+        self
+    }
+
+    /// A config for the kubelet at `address`, with the credentials and trusted certificates of this config
+    ///
+    /// `address` is a host name or IP of the node, e.g. its `InternalIP`, and the kubelet is
+    /// expected on its default port 10250. Kubelet serving certificates are only signed by the
+    /// cluster CA if the cluster enables `serverTLSBootstrap`; otherwise add the node CA to
+    /// [`Config::root_cert`], or set [`Config::accept_invalid_certs`] for testing.
+    #[cfg(feature = "kubelet")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "kubelet")))]
+    pub fn kubelet(&self, address: &str) -> Result<Self, http::uri::InvalidUri> {
+        let host = if address.contains(':') && !address.starts_with('[') {
+            format!("[{address}]")
+        } else {
+            address.to_string()
+        };
+        Ok(Self {
+            cluster_url: format!("https://{host}:10250").parse()?,
+            fallback_urls: vec![],
+            unix_socket: None,
+            tls_server_name: None,
+            ..self.clone()
+        })
+    }
+
     /// Client certificate and private key in PEM.
     pub(crate) fn identity_pem(&self) -> Option<Vec<u8>> {
         self.auth_info.identity_pem().ok()
@@ -391,11 +798,14 @@ fn certs(data: &[u8]) -> Result<Vec<Vec<u8>>, pem::PemError> {
 const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
 const DEFAULT_READ_TIMEOUT: Duration = Duration::from_secs(295);
 const DEFAULT_WRITE_TIMEOUT: Duration = Duration::from_secs(295);
+// matches the default of hyper's connection pool
+const DEFAULT_POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
 
 // Expose raw config structs
 pub use file_config::{
-    AuthInfo, AuthProviderConfig, Cluster, Context, ExecAuthCluster, ExecConfig, ExecInteractiveMode,
-    Kubeconfig, NamedAuthInfo, NamedCluster, NamedContext, NamedExtension, Preferences,
+    AuthInfo, AuthProviderConfig, AuthProviderPersister, Cluster, Context, CredentialCache, ExecAuthCluster,
+    ExecConfig, ExecInteractiveMode, Kubeconfig, NamedAuthInfo, NamedCluster, NamedContext, NamedExtension,
+    Preferences,
 };
 
 #[cfg(test)]
@@ -431,4 +841,125 @@ mod tests {
         let kubeconfig = Config::infer().await.unwrap();
         assert_eq!(kubeconfig.cluster_url, "https://0.0.0.0:6443/");
     }
+
+    #[tokio::test]
+    async fn overrides_apply_like_kubectl_flags() {
+        use super::{Config, ConfigOverrides, Kubeconfig};
+        let kubeconfig = Kubeconfig::from_yaml(
+            r#"
+        apiVersion: v1
+        clusters:
+        - cluster:
+            server: https://prod:6443
+          name: prod
+        - cluster:
+            server: https://staging:6443
+          name: staging
+        contexts:
+        - context:
+            cluster: prod
+            user: admin
+            namespace: apps
+          name: prod
+        - context:
+            cluster: staging
+            user: admin
+            namespace: apps
+          name: staging
+        current-context: prod
+        kind: Config
+        users:
+        - name: admin
+          user:
+            token: admin-token
+        "#,
+        )
+        .unwrap();
+
+        let overrides = ConfigOverrides::default().context("staging").namespace("kube-system");
+        let config = Config::from_custom_kubeconfig_with_overrides(kubeconfig.clone(), &overrides)
+            .await
+            .unwrap();
+        assert_eq!(config.cluster_url, "https://staging:6443/");
+        assert_eq!(config.default_namespace, "kube-system");
+
+        let overrides = ConfigOverrides::default()
+            .server("https://localhost:8443")
+            .impersonate("jane")
+            .impersonate_group("developers")
+            .impersonate_group("testers");
+        let config = Config::from_custom_kubeconfig_with_overrides(kubeconfig, &overrides)
+            .await
+            .unwrap();
+        assert_eq!(config.cluster_url, "https://localhost:8443/");
+        assert_eq!(config.default_namespace, "apps");
+        assert_eq!(config.auth_info.impersonate.as_deref(), Some("jane"));
+        assert_eq!(
+            config.auth_info.impersonate_groups,
+            Some(vec!["developers".into(), "testers".into()])
+        );
+    }
+
+    #[tokio::test]
+    async fn insecure_skip_tls_verify_is_honored() {
+        use super::{Config, KubeConfigOptions, Kubeconfig};
+        let kubeconfig = Kubeconfig::from_yaml(
+            r#"
+        apiVersion: v1
+        clusters:
+        - cluster:
+            server: https://dev:6443
+            insecure-skip-tls-verify: true
+          name: dev
+        contexts:
+        - context:
+            cluster: dev
+            user: dev
+          name: dev
+        current-context: dev
+        kind: Config
+        users:
+        - name: dev
+          user: {}
+        "#,
+        )
+        .unwrap();
+        let config = Config::from_custom_kubeconfig(kubeconfig, &KubeConfigOptions::default())
+            .await
+            .unwrap();
+        assert!(config.accept_invalid_certs);
+    }
+
+    #[tokio::test]
+    async fn unix_socket_servers_are_sent_to_localhost() {
+        use super::{Config, KubeConfigOptions, Kubeconfig};
+        let kubeconfig = Kubeconfig::from_yaml(
+            r#"
+        apiVersion: v1
+        clusters:
+        - cluster:
+            server: unix:///run/k3s/apiserver.sock
+          name: k3s
+        contexts:
+        - context:
+            cluster: k3s
+            user: k3s
+          name: k3s
+        current-context: k3s
+        kind: Config
+        users:
+        - name: k3s
+          user: {}
+        "#,
+        )
+        .unwrap();
+        let config = Config::from_custom_kubeconfig(kubeconfig, &KubeConfigOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(config.cluster_url, "http://localhost/");
+        assert_eq!(
+            config.unix_socket.as_deref(),
+            Some(std::path::Path::new("/run/k3s/apiserver.sock"))
+        );
+    }
 }
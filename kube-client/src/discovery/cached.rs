@@ -0,0 +1,312 @@
+//! Lazily filled discovery cache with expiry and invalidation
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use kube_core::{
+    discovery::{ApiCapabilities, ApiResource},
+    gvk::GroupVersionKind,
+};
+
+use super::{oneshot, ApiGroup};
+use crate::{error::DiscoveryError, Client, Error, Result};
+
+/// A discovery client that caches api groups as they are queried
+///
+/// Unlike [`Discovery`](crate::discovery::Discovery), which scans every group up front, this
+/// only queries the groups that are asked for, and keeps them for a time to live (10 minutes by
+/// default). Clones share the same cache, so one instance can serve a whole application.
+///
+/// Cached groups can go stale when CRDs or aggregated apis are installed or removed. Lookups of
+/// kinds that are missing from a cached group refresh the group once before failing, and
+/// [`CachedDiscovery::invalidate_on_error`] drops a group when requests against it return
+/// `404 Not Found` or `410 Gone`.
+///
+/// ```no_run
+/// use kube::{api::{Api, DynamicObject}, discovery::CachedDiscovery, Client};
+/// # async fn wrapper(client: Client) -> Result<(), Box<dyn std::error::Error>> {
+/// let discovery = CachedDiscovery::new(client.clone());
+/// let (ar, _caps) = discovery.resolve("deployments.apps").await?;
+/// let api: Api<DynamicObject> = Api::default_namespaced_with(client, &ar);
+/// if let Err(err) = api.list(&Default::default()).await {
+///     discovery.invalidate_on_error(&ar.group, &err);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct CachedDiscovery {
+    client: Client,
+    ttl: Duration,
+    cache: Arc<Mutex<Cache>>,
+}
+
+#[derive(Default)]
+struct Cache {
+    /// Names of the served groups, including the core group
+    names: Option<(Instant, Vec<String>)>,
+    groups: HashMap<String, (Instant, Arc<ApiGroup>)>,
+}
+
+impl CachedDiscovery {
+    /// Construct an empty discovery cache
+    #[must_use]
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            ttl: Duration::from_secs(10 * 60),
+            cache: Arc::default(),
+        }
+    }
+
+    /// Set how long discovered groups are kept before they are queried again
+    #[must_use]
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Drop everything from the cache
+    pub fn invalidate(&self) {
+        let mut cache = self.cache();
+        cache.names = None;
+        cache.groups.clear();
+    }
+
+    /// Drop a single group from the cache
+    pub fn invalidate_group(&self, group: &str) {
+        self.cache().groups.remove(group);
+    }
+
+    /// Drop `group` from the cache if `err` suggests that its discovery information is outdated
+    ///
+    /// This is the case for `404 Not Found` and `410 Gone` responses, which are returned for
+    /// resources that are no longer served. Returns whether the group was invalidated.
+    pub fn invalidate_on_error(&self, group: &str, err: &Error) -> bool {
+        let stale = matches!(err, Error::Api(e) if e.code == 404 || e.code == 410);
+        if stale {
+            self.invalidate_group(group);
+            self.cache().names = None;
+        }
+        stale
+    }
+
+    /// Returns the [`ApiGroup`] for a given group, querying it if it is not cached
+    pub async fn group(&self, group: &str) -> Result<Arc<ApiGroup>> {
+        if let Some(cached) = self.cached_group(group) {
+            return Ok(cached);
+        }
+        let queried = match oneshot::group(&self.client, group).await {
+            Ok(g) => Arc::new(g),
+            Err(err) => {
+                self.invalidate_on_error(group, &err);
+                return Err(err);
+            }
+        };
+        let entry = (Instant::now(), queried.clone());
+        self.cache().groups.insert(group.to_string(), entry);
+        Ok(queried)
+    }
+
+    /// Returns all served groups, querying the ones that are not cached
+    ///
+    /// The core group comes first, followed by the other groups in the order the apiserver lists them.
+    pub async fn groups(&self) -> Result<Vec<Arc<ApiGroup>>> {
+        let mut groups = vec![];
+        for name in self.group_names().await? {
+            groups.push(self.group(&name).await?);
+        }
+        Ok(groups)
+    }
+
+    /// Finds an [`ApiResource`] and its [`ApiCapabilities`] by matching a GVK
+    ///
+    /// A cached group that does not contain the kind is queried again before giving up.
+    pub async fn resolve_gvk(&self, gvk: &GroupVersionKind) -> Result<(ApiResource, ApiCapabilities)> {
+        let was_cached = self.cached_group(&gvk.group).is_some();
+        let find = |group: &ApiGroup| {
+            group
+                .versioned_resources(&gvk.version)
+                .into_iter()
+                .find(|(ar, _)| ar.kind == gvk.kind)
+        };
+        if let Some(found) = find(&*self.group(&gvk.group).await?) {
+            return Ok(found);
+        }
+        if was_cached {
+            self.invalidate_group(&gvk.group);
+            if let Some(found) = find(&*self.group(&gvk.group).await?) {
+                return Ok(found);
+            }
+        }
+        Err(Error::Discovery(DiscoveryError::MissingKind(format!("{gvk:?}"))))
+    }
+
+    /// Finds an [`ApiResource`] and its [`ApiCapabilities`] from a name like `kubectl get` accepts
+    ///
+    /// The name is a kind or plural resource name, matched without regard to case, optionally
+    /// qualified by a group or a version and group, e.g. `Deployment`, `deployments.apps` or
+    /// `deployment.v1.apps`. Without a version, the recommended version of the group is used.
+    /// Unqualified names are looked up in the core group first.
+    pub async fn resolve(&self, name: &str) -> Result<(ApiResource, ApiCapabilities)> {
+        let (resource, qualifier) = name.split_once('.').unwrap_or((name, ""));
+        let is_match = |ar: &ApiResource| {
+            ar.kind.eq_ignore_ascii_case(resource) || ar.plural.eq_ignore_ascii_case(resource)
+        };
+        for group_name in self.group_names().await? {
+            let version = if qualifier.is_empty() || qualifier == group_name {
+                None
+            } else {
+                match qualifier.split_once('.') {
+                    Some((version, g)) if g == group_name => Some(version),
+                    None if group_name == ApiGroup::CORE_GROUP => Some(qualifier),
+                    _ => continue,
+                }
+            };
+            let group = self.group(&group_name).await?;
+            let resources = match version {
+                Some(v) => group.versioned_resources(v),
+                None => group.recommended_resources(),
+            };
+            if let Some(found) = resources.into_iter().find(|(ar, _)| is_match(ar)) {
+                return Ok(found);
+            }
+        }
+        Err(Error::Discovery(DiscoveryError::MissingKind(name.to_string())))
+    }
+
+    async fn group_names(&self) -> Result<Vec<String>> {
+        let cached = self.cache().names.clone();
+        if let Some((fetched, names)) = cached {
+            if fetched.elapsed() < self.ttl {
+                return Ok(names);
+            }
+        }
+        let mut names = vec![ApiGroup::CORE_GROUP.to_string()];
+        let api_groups = self.client.list_api_groups().await?;
+        names.extend(api_groups.groups.into_iter().map(|g| g.name));
+        self.cache().names = Some((Instant::now(), names.clone()));
+        Ok(names)
+    }
+
+    fn cached_group(&self, group: &str) -> Option<Arc<ApiGroup>> {
+        let cache = self.cache();
+        let (fetched, cached) = cache.groups.get(group)?;
+        (fetched.elapsed() < self.ttl).then(|| cached.clone())
+    }
+
+    fn cache(&self) -> std::sync::MutexGuard<'_, Cache> {
+        self.cache.lock().expect("discovery cache poisoned")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use http::{Method, StatusCode};
+    use k8s_openapi::apimachinery::pkg::apis::meta::v1::{
+        APIGroup, APIGroupList, APIResource, APIResourceList, GroupVersionForDiscovery,
+    };
+
+    fn apps_group() -> APIGroupList {
+        let v1 = GroupVersionForDiscovery {
+            group_version: "apps/v1".into(),
+            version: "v1".into(),
+        };
+        APIGroupList {
+            groups: vec![APIGroup {
+                name: "apps".into(),
+                preferred_version: Some(v1.clone()),
+                versions: vec![v1],
+                ..APIGroup::default()
+            }],
+        }
+    }
+
+    fn apps_resources(kinds: &[(&str, &str)]) -> APIResourceList {
+        APIResourceList {
+            group_version: "apps/v1".into(),
+            resources: kinds
+                .iter()
+                .map(|(kind, plural)| APIResource {
+                    kind: kind.to_string(),
+                    name: plural.to_string(),
+                    namespaced: true,
+                    verbs: vec!["get".into(), "list".into()],
+                    ..APIResource::default()
+                })
+                .collect(),
+        }
+    }
+
+    #[tokio::test]
+    async fn caches_and_invalidates_groups() {
+        let (client, mock) = Client::mock();
+        mock.expect(Method::GET, "/apis").respond_json(StatusCode::OK, &apps_group());
+        mock.expect(Method::GET, "/apis/apps/v1")
+            .respond_json(StatusCode::OK, &apps_resources(&[("Deployment", "deployments")]));
+        let discovery = CachedDiscovery::new(client);
+
+        let gvk = GroupVersionKind::gvk("apps", "v1", "Deployment");
+        let (ar, caps) = discovery.resolve_gvk(&gvk).await.unwrap();
+        assert_eq!(ar.plural, "deployments");
+        assert!(caps.supports_operation("list"));
+        // served from the cache
+        discovery.resolve_gvk(&gvk).await.unwrap();
+        assert_eq!(mock.requests().len(), 2);
+
+        // kinds that are missing from the cache cause one refresh of the group
+        mock.expect(Method::GET, "/apis").respond_json(StatusCode::OK, &apps_group());
+        mock.expect(Method::GET, "/apis/apps/v1").respond_json(
+            StatusCode::OK,
+            &apps_resources(&[("Deployment", "deployments"), ("StatefulSet", "statefulsets")]),
+        );
+        let gvk = GroupVersionKind::gvk("apps", "v1", "StatefulSet");
+        assert_eq!(discovery.resolve_gvk(&gvk).await.unwrap().0.plural, "statefulsets");
+        assert!(mock.is_drained());
+
+        // gone resources invalidate the group
+        let gone = Error::Api(crate::error::ErrorResponse {
+            status: "Failure".into(),
+            message: "gone".into(),
+            reason: "Gone".into(),
+            code: 410,
+            details: None,
+        });
+        assert!(discovery.invalidate_on_error("apps", &gone));
+        assert!(discovery.resolve_gvk(&gvk).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn resolves_kubectl_style_names() {
+        let (client, mock) = Client::mock();
+        mock.expect(Method::GET, "/apis").respond_json(StatusCode::OK, &apps_group());
+        mock.expect(Method::GET, "/api").respond_json(
+            StatusCode::OK,
+            &serde_json::json!({ "versions": ["v1"], "serverAddressByClientCIDRs": [] }),
+        );
+        mock.expect(Method::GET, "/api/v1").respond_json(StatusCode::OK, &APIResourceList {
+            group_version: "v1".into(),
+            resources: vec![APIResource {
+                kind: "Pod".into(),
+                name: "pods".into(),
+                namespaced: true,
+                ..APIResource::default()
+            }],
+        });
+        mock.expect(Method::GET, "/apis").respond_json(StatusCode::OK, &apps_group());
+        mock.expect(Method::GET, "/apis/apps/v1")
+            .respond_json(StatusCode::OK, &apps_resources(&[("Deployment", "deployments")]));
+        let discovery = CachedDiscovery::new(client);
+
+        for name in ["Deployment", "deployments.apps", "deployment.v1.apps", "pods", "Pod.v1"] {
+            let (ar, _) = discovery.resolve(name).await.unwrap();
+            assert!(name.to_lowercase().starts_with(&ar.kind.to_lowercase()), "{name}");
+        }
+        assert!(discovery.resolve("deployments.batch").await.is_err());
+        assert!(mock.is_drained());
+    }
+}
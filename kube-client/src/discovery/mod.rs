@@ -6,7 +6,10 @@ use kube_core::gvk::GroupVersionKind;
 use std::collections::HashMap;
 mod apigroup;
 pub mod oneshot;
+pub mod openapi;
 pub use apigroup::ApiGroup;
+mod cached;
+pub use cached::CachedDiscovery;
 mod parse;
 
 // re-export one-shots
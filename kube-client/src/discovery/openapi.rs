@@ -0,0 +1,166 @@
+//! Fetching the OpenAPI documents of a cluster
+use std::collections::BTreeMap;
+
+use http::Request;
+use serde::Deserialize;
+
+pub use kube_core::openapi::OpenApiDocument;
+use kube_core::GroupVersionKind;
+
+use crate::{Client, Error, Result};
+
+/// The OpenAPI v3 index of a cluster, from `/openapi/v3`
+///
+/// The index lists one document per group version, which are fetched on demand since the
+/// documents of large clusters add up to many megabytes.
+#[derive(Clone)]
+pub struct OpenApiV3 {
+    client: Client,
+    paths: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct Index {
+    #[serde(default)]
+    paths: BTreeMap<String, IndexEntry>,
+}
+
+#[derive(Deserialize)]
+struct IndexEntry {
+    #[serde(rename = "serverRelativeURL")]
+    server_relative_url: String,
+}
+
+impl OpenApiV3 {
+    /// The paths of the group versions with a document, like `api/v1` and `apis/apps/v1`
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.paths.keys().map(String::as_str)
+    }
+
+    /// Fetch the document of a group version path, `None` if the cluster does not serve it
+    ///
+    /// The document is fetched through its versioned url, which the apiserver allows to be cached.
+    pub async fn document(&self, path: &str) -> Result<Option<OpenApiDocument>> {
+        let Some(url) = self.paths.get(path.trim_start_matches('/')) else {
+            return Ok(None);
+        };
+        let req = Request::get(url.as_str())
+            .body(vec![])
+            .map_err(Error::HttpError)?;
+        self.client.request(req).await.map(Some)
+    }
+
+    /// Fetch the document describing the kind `gvk`, see [`OpenApiV3::document`]
+    pub async fn document_for(&self, gvk: &GroupVersionKind) -> Result<Option<OpenApiDocument>> {
+        self.document(&group_version_path(gvk)).await
+    }
+}
+
+/// The path of the group version of `gvk` in the OpenAPI v3 index
+fn group_version_path(gvk: &GroupVersionKind) -> String {
+    if gvk.group.is_empty() {
+        format!("api/{}", gvk.version)
+    } else {
+        format!("apis/{}/{}", gvk.group, gvk.version)
+    }
+}
+
+/// Methods to fetch the OpenAPI schemas served by the apiserver
+///
+/// ```no_run
+/// # async fn scope(client: kube::Client) -> Result<(), Box<dyn std::error::Error>> {
+/// use kube::core::GroupVersionKind;
+///
+/// let gvk = GroupVersionKind::gvk("apps", "v1", "Deployment");
+/// let doc = client.openapi_document_for(&gvk).await?;
+/// println!("{:?}", doc.field_description(&gvk, "spec.replicas"));
+/// # Ok(())
+/// # }
+/// ```
+impl Client {
+    /// Fetch the OpenAPI v3 index, served from Kubernetes 1.27
+    pub async fn openapi_v3(&self) -> Result<OpenApiV3> {
+        let req = Request::get("/openapi/v3")
+            .body(vec![])
+            .map_err(Error::HttpError)?;
+        let index: Index = self.request(req).await?;
+        let paths = index
+            .paths
+            .into_iter()
+            .map(|(path, entry)| (path, entry.server_relative_url))
+            .collect();
+        Ok(OpenApiV3 {
+            client: self.clone(),
+            paths,
+        })
+    }
+
+    /// Fetch the OpenAPI v2 document, which describes every group version at once
+    pub async fn openapi_v2(&self) -> Result<OpenApiDocument> {
+        let req = Request::get("/openapi/v2")
+            .body(vec![])
+            .map_err(Error::HttpError)?;
+        self.request(req).await
+    }
+
+    /// Fetch a document describing the kind `gvk`
+    ///
+    /// Prefers the OpenAPI v3 document of its group version, and falls back to the OpenAPI v2
+    /// document on clusters that do not serve v3, or do not have a v3 document for it.
+    pub async fn openapi_document_for(&self, gvk: &GroupVersionKind) -> Result<OpenApiDocument> {
+        match self.openapi_v3().await {
+            Ok(v3) => {
+                if let Some(doc) = v3.document_for(gvk).await? {
+                    return Ok(doc);
+                }
+            }
+            Err(Error::Api(err)) if err.code == 404 => {}
+            Err(err) => return Err(err),
+        }
+        self.openapi_v2().await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Client;
+    use http::{Method, StatusCode};
+    use kube_core::GroupVersionKind;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn v3_documents_are_fetched_through_the_index() {
+        let (client, mock) = Client::mock();
+        mock.expect(Method::GET, "/openapi/v3").respond_json(
+            StatusCode::OK,
+            &json!({ "paths": { "apis/apps/v1": { "serverRelativeURL": "/openapi/v3/apis/apps/v1?hash=F00" } } }),
+        );
+        mock.expect(Method::GET, "/openapi/v3/apis/apps/v1")
+            .respond_json(StatusCode::OK, &json!({ "openapi": "3.0.0" }));
+
+        let v3 = client.openapi_v3().await.unwrap();
+        assert_eq!(v3.paths().collect::<Vec<_>>(), ["apis/apps/v1"]);
+        let gvk = GroupVersionKind::gvk("apps", "v1", "Deployment");
+        let doc = v3.document_for(&gvk).await.unwrap().unwrap();
+        assert_eq!(doc.0["openapi"], "3.0.0");
+        assert!(v3.document("api/v1").await.unwrap().is_none());
+        let uris: Vec<_> = mock.requests().into_iter().map(|r| r.uri.to_string()).collect();
+        assert_eq!(uris, ["/openapi/v3", "/openapi/v3/apis/apps/v1?hash=F00"]);
+    }
+
+    #[tokio::test]
+    async fn documents_fall_back_to_v2() {
+        let (client, mock) = Client::mock();
+        mock.expect(Method::GET, "/openapi/v3").respond_error(
+            StatusCode::NOT_FOUND,
+            "NotFound",
+            "the server could not find the requested resource",
+        );
+        mock.expect(Method::GET, "/openapi/v2")
+            .respond_json(StatusCode::OK, &json!({ "swagger": "2.0" }));
+
+        let gvk = GroupVersionKind::gvk("", "v1", "Pod");
+        let doc = client.openapi_document_for(&gvk).await.unwrap();
+        assert_eq!(doc.0["swagger"], "2.0");
+    }
+}
@@ -17,6 +17,12 @@ pub enum Error {
     #[error("ApiError: {0} ({0:?})")]
     Api(#[source] ErrorResponse),
 
+    /// A paginated list could not be continued because its [`Cursor`](kube_core::params::Cursor) expired
+    ///
+    /// The snapshot the cursor points into is gone, so the list has to be restarted without a cursor.
+    #[error("list cursor expired, restart the list from the beginning: {0}")]
+    CursorExpired(#[source] ErrorResponse),
+
     /// Hyper error
     #[cfg(feature = "client")]
     #[error("HyperError: {0}")]
@@ -41,6 +47,22 @@ pub enum Error {
         protocol_feature: &'static str,
     },
 
+    /// Returned when a unix socket is configured on a platform without unix sockets
+    #[error("unix socket {0:?} is not supported on this platform")]
+    UnixSocketUnsupported(std::path::PathBuf),
+
+    /// The apiserver did not respond within the request timeout
+    #[cfg(feature = "client")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "client")))]
+    #[error("request timed out after {0:?}")]
+    Timeout(std::time::Duration),
+
+    /// Failed to decode a response with a registered codec
+    #[cfg(feature = "client")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "client")))]
+    #[error("codec error: {0}")]
+    Codec(#[source] tower::BoxError),
+
     /// UTF-8 Error
     #[error("UTF-8 Error: {0}")]
     FromUtf8(#[source] std::string::FromUtf8Error),
@@ -71,10 +93,20 @@ pub enum Error {
     #[error("Failed to infer configuration: {0}")]
     InferConfig(#[source] crate::config::InferConfigError),
 
+    /// Failed to load a context of a kubeconfig
+    #[error("Failed to load kubeconfig: {0}")]
+    LoadKubeconfig(#[source] crate::config::KubeconfigError),
+
     /// Discovery errors
     #[error("Error from discovery: {0}")]
     Discovery(#[source] DiscoveryError),
 
+    /// Manifests could not be parsed as YAML or JSON
+    #[cfg(feature = "client")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "client")))]
+    #[error("invalid manifest: {0}")]
+    InvalidManifest(#[source] serde_yaml::Error),
+
     /// Errors from OpenSSL TLS
     #[cfg(feature = "openssl-tls")]
     #[cfg_attr(docsrs, doc(cfg(feature = "openssl-tls")))]
@@ -97,6 +129,25 @@ pub enum Error {
     #[error("failed to upgrade to a WebSocket connection: {0}")]
     UpgradeConnection(#[source] crate::client::UpgradeConnectionError),
 
+    /// An ephemeral container of [`Api::debug_attach`](crate::Api::debug_attach) can not start
+    #[cfg(feature = "ws")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "ws")))]
+    #[error("debug container {container} can not start: {reason}: {message}")]
+    DebugContainer {
+        /// The name of the ephemeral container
+        container: String,
+        /// The reason it is waiting, e.g. `ImagePullBackOff`
+        reason: String,
+        /// The message of the kubelet
+        message: String,
+    },
+
+    /// Failed to copy files in or out of a pod
+    #[cfg(feature = "cp")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cp")))]
+    #[error("failed to copy files: {0}")]
+    Copy(#[source] crate::api::CopyError),
+
     /// Errors related to client auth
     #[cfg(feature = "client")]
     #[cfg_attr(docsrs, doc(cfg(feature = "client")))]
@@ -133,3 +184,171 @@ pub enum DiscoveryError {
     #[error("Empty Api Group: {0}")]
     EmptyApiGroup(String),
 }
+
+/// How serious a failure recorded in [`Errors`] is
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// The operation partially succeeded or can be ignored
+    Warning,
+    /// The operation failed
+    Error,
+}
+
+/// A single failure recorded in [`Errors`]
+#[derive(Debug)]
+pub struct ItemError<E = Error> {
+    /// What the failing operation was about, e.g. `ConfigMap default/foo`
+    pub context: String,
+    /// How serious the failure is
+    pub severity: Severity,
+    /// Whether retrying the operation may succeed
+    pub retryable: bool,
+    /// The underlying error
+    pub error: E,
+}
+
+/// An aggregate of failures from bulk operations
+///
+/// Collects one [`ItemError`] per failed item instead of collapsing partial failures
+/// into a single error, so callers can inspect, retry, or report them individually.
+///
+/// ```
+/// use kube::error::{Errors, Severity};
+///
+/// let mut errors: Errors<std::io::Error> = Errors::new();
+/// errors.push("ConfigMap default/foo", std::io::Error::other("boom"));
+/// errors.push_with("ConfigMap default/bar", Severity::Warning, true, std::io::Error::other("slow"));
+/// assert_eq!(errors.len(), 2);
+/// assert!(!errors.is_retryable());
+/// assert!(errors.into_result().is_err());
+/// ```
+#[derive(Debug)]
+pub struct Errors<E = Error> {
+    items: Vec<ItemError<E>>,
+}
+
+impl<E> Default for Errors<E> {
+    fn default() -> Self {
+        Self { items: Vec::new() }
+    }
+}
+
+impl<E> Errors<E> {
+    /// An empty set of errors
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a non-retryable failure with [`Severity::Error`]
+    pub fn push(&mut self, context: impl Into<String>, error: E) {
+        self.push_with(context, Severity::Error, false, error);
+    }
+
+    /// Record a failure with explicit severity and retryability
+    pub fn push_with(&mut self, context: impl Into<String>, severity: Severity, retryable: bool, error: E) {
+        self.items.push(ItemError {
+            context: context.into(),
+            severity,
+            retryable,
+            error,
+        });
+    }
+
+    /// Record the error of `result` if it failed, returning the success value otherwise
+    pub fn record<T>(&mut self, context: impl Into<String>, result: std::result::Result<T, E>) -> Option<T> {
+        match result {
+            Ok(v) => Some(v),
+            Err(e) => {
+                self.push(context, e);
+                None
+            }
+        }
+    }
+
+    /// Whether no failures were recorded
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// The number of recorded failures
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Iterate over the recorded failures
+    pub fn iter(&self) -> std::slice::Iter<'_, ItemError<E>> {
+        self.items.iter()
+    }
+
+    /// The highest severity among the recorded failures
+    pub fn severity(&self) -> Option<Severity> {
+        self.items.iter().map(|i| i.severity).max()
+    }
+
+    /// Whether every failure of [`Severity::Error`] can be retried
+    ///
+    /// Returns `false` when there are no such failures, since there is nothing to retry.
+    pub fn is_retryable(&self) -> bool {
+        let mut errors = self.items.iter().filter(|i| i.severity == Severity::Error).peekable();
+        errors.peek().is_some() && errors.all(|i| i.retryable)
+    }
+
+    /// `Ok(())` if nothing of [`Severity::Error`] was recorded, otherwise `Err(self)`
+    pub fn into_result(self) -> std::result::Result<(), Self> {
+        if self.severity() == Some(Severity::Error) {
+            Err(self)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<E> IntoIterator for Errors<E> {
+    type IntoIter = std::vec::IntoIter<ItemError<E>>;
+    type Item = ItemError<E>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter()
+    }
+}
+
+impl<E> Extend<ItemError<E>> for Errors<E> {
+    fn extend<T: IntoIterator<Item = ItemError<E>>>(&mut self, iter: T) {
+        self.items.extend(iter);
+    }
+}
+
+impl<E: std::fmt::Display> std::fmt::Display for Errors<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} operation(s) failed", self.items.len())?;
+        for item in &self.items {
+            write!(f, "; {}: {}", item.context, item.error)?;
+        }
+        Ok(())
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for Errors<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.items.first().map(|i| &i.error as &(dyn std::error::Error + 'static))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Errors, Severity};
+
+    #[test]
+    fn errors_aggregate_severity_and_retryability() {
+        let mut errors: Errors<std::io::Error> = Errors::new();
+        assert!(errors.record("a", Ok::<_, std::io::Error>(1)).is_some());
+        errors.push_with("b", Severity::Warning, false, std::io::Error::other("warn"));
+        assert_eq!(errors.severity(), Some(Severity::Warning));
+        assert!(!errors.is_retryable());
+
+        errors.push_with("c", Severity::Error, true, std::io::Error::other("boom"));
+        assert!(errors.is_retryable());
+        assert_eq!(errors.to_string(), "2 operation(s) failed; b: warn; c: boom");
+        assert!(errors.into_result().is_err());
+    }
+}
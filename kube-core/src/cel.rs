@@ -162,6 +162,16 @@ impl FromStr for Reason {
     }
 }
 
+/// An update changed a field that is marked `#[kube(immutable)]`
+///
+/// Returned by the `check_immutable` function generated by `#[derive(CustomResource)]`.
+#[derive(thiserror::Error, Clone, Debug, PartialEq, Eq)]
+#[error("{field} is immutable")]
+pub struct ImmutableFieldError {
+    /// The path of the changed field, e.g. `spec.storageClass`
+    pub field: &'static str,
+}
+
 /// Validate takes schema and applies a set of validation rules to it. The rules are stored
 /// on the top level under the "x-kubernetes-validations".
 ///
@@ -6,3 +6,6 @@ pub use self::types::{
 
 /// Defines low-level typings.
 mod types;
+
+mod status;
+pub use status::{convert_status, ConvertStatus, StatusConversionError};
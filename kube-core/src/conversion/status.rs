@@ -0,0 +1,64 @@
+//! Conversion of the `status` of custom resources between versions
+use serde::Serialize;
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::{object::HasStatus, Resource};
+
+/// Possible errors when converting the status of a custom resource
+#[derive(Debug, Error)]
+pub enum StatusConversionError {
+    /// The status cannot be converted from the given version
+    #[error("status conversion from version {0} is not supported")]
+    UnsupportedVersion(String),
+
+    /// The object does not have an `apiVersion`
+    #[error("object has no apiVersion")]
+    MissingApiVersion,
+
+    /// The status does not match the status type of its version
+    #[error("failed to deserialize status: {0}")]
+    Deserialize(#[source] serde_json::Error),
+
+    /// The converted status could not be serialized
+    #[error("failed to serialize status: {0}")]
+    Serialize(#[source] serde_json::Error),
+}
+
+/// A custom resource version whose status can be converted from other versions
+///
+/// This is implemented by `#[derive(CustomResource)]` for every resource with a `status`. Other
+/// versions are declared with `#[kube(status_from = "v1::Foo")]`, which requires a
+/// `From` conversion from the status type of `v1::Foo` into the status type of this version.
+pub trait ConvertStatus: HasStatus + Resource<DynamicType = ()> {
+    /// Convert a serialized `status` of the given `version` into the status of this version
+    fn status_from_version(version: &str, status: Value) -> Result<Self::Status, StatusConversionError>;
+}
+
+/// Convert the `status` of a serialized custom resource of any version to the status of `K`
+///
+/// The source version is read from the `apiVersion` of the object, so call this on the objects
+/// of a [`ConversionRequest`](super::ConversionRequest) before converting the rest of the object
+/// and updating its `apiVersion`. Objects without a status are left unchanged.
+///
+/// # Errors
+///
+/// Fails when the object has no `apiVersion`, when `K` cannot convert from its version, or
+/// when the status does not match the status type of its version.
+pub fn convert_status<K>(object: &mut Value) -> Result<(), StatusConversionError>
+where
+    K: ConvertStatus,
+    K::Status: Serialize,
+{
+    let api_version = object
+        .get("apiVersion")
+        .and_then(Value::as_str)
+        .ok_or(StatusConversionError::MissingApiVersion)?;
+    let version = api_version.rsplit('/').next().unwrap_or(api_version).to_string();
+    let Some(status) = object.get_mut("status").filter(|s| !s.is_null()) else {
+        return Ok(());
+    };
+    let converted = K::status_from_version(&version, status.take())?;
+    *status = serde_json::to_value(converted).map_err(StatusConversionError::Serialize)?;
+    Ok(())
+}
@@ -0,0 +1,184 @@
+//! Structured differences between two versions of an object
+use std::{collections::BTreeSet, fmt};
+
+use serde_json::Value;
+
+/// The changes between two JSON values, e.g. a live object and its desired state
+///
+/// Objects are compared field by field and arrays element by element, so the changes point at the
+/// innermost values that differ. Paths use the JSONPath notation of `kubectl`, with keys that are
+/// not plain identifiers in brackets, e.g. `.metadata.labels['app.kubernetes.io/name']`.
+///
+/// ```
+/// use kube::core::diff::{Change, Diff};
+/// use serde_json::json;
+///
+/// let live = json!({ "spec": { "replicas": 1, "paused": true } });
+/// let desired = json!({ "spec": { "replicas": 3, "image": "nginx" } });
+/// let diff = Diff::new(&live, &desired);
+/// assert_eq!(diff.changes[0], Change::Added {
+///     path: ".spec.image".into(),
+///     value: json!("nginx"),
+/// });
+/// assert_eq!(diff.to_string(), "\
+/// + .spec.image: \"nginx\"
+/// - .spec.paused: true
+/// ~ .spec.replicas: 1 -> 3
+/// ");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Diff {
+    /// The changes, ordered by path
+    pub changes: Vec<Change>,
+}
+
+/// A single difference in a [`Diff`]
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change {
+    /// A value that only exists in the new version
+    Added {
+        /// Where the value was added
+        path: String,
+        /// The added value
+        value: Value,
+    },
+    /// A value that only exists in the old version
+    Removed {
+        /// Where the value was removed
+        path: String,
+        /// The removed value
+        value: Value,
+    },
+    /// A value that differs between the versions
+    Modified {
+        /// Where the value changed
+        path: String,
+        /// The old value
+        old: Value,
+        /// The new value
+        new: Value,
+    },
+}
+
+impl Change {
+    /// The path of the changed value, empty for the root
+    pub fn path(&self) -> &str {
+        match self {
+            Self::Added { path, .. } | Self::Removed { path, .. } | Self::Modified { path, .. } => path,
+        }
+    }
+}
+
+impl Diff {
+    /// Compute the changes from `old` to `new`
+    ///
+    /// A `null` value is treated as missing, so diffing against `Value::Null` adds everything.
+    pub fn new(old: &Value, new: &Value) -> Self {
+        let mut diff = Self::default();
+        diff.compare(String::new(), old, new);
+        diff
+    }
+
+    /// Whether the values are the same
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    fn compare(&mut self, path: String, old: &Value, new: &Value) {
+        match (old, new) {
+            _ if old == new => {}
+            (Value::Null, value) => self.changes.push(Change::Added {
+                path,
+                value: value.clone(),
+            }),
+            (value, Value::Null) => self.changes.push(Change::Removed {
+                path,
+                value: value.clone(),
+            }),
+            (Value::Object(old), Value::Object(new)) => {
+                let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+                for key in keys {
+                    let old = old.get(key).unwrap_or(&Value::Null);
+                    let new = new.get(key).unwrap_or(&Value::Null);
+                    self.compare(child_path(&path, key), old, new);
+                }
+            }
+            (Value::Array(old), Value::Array(new)) => {
+                for i in 0..old.len().max(new.len()) {
+                    let old = old.get(i).unwrap_or(&Value::Null);
+                    let new = new.get(i).unwrap_or(&Value::Null);
+                    self.compare(format!("{path}[{i}]"), old, new);
+                }
+            }
+            (old, new) => self.changes.push(Change::Modified {
+                path,
+                old: old.clone(),
+                new: new.clone(),
+            }),
+        }
+    }
+}
+
+fn child_path(parent: &str, key: &str) -> String {
+    let plain = key
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if plain {
+        format!("{parent}.{key}")
+    } else {
+        format!("{parent}['{}']", key.replace('\'', "\\'"))
+    }
+}
+
+/// Renders one line per change, prefixed with `+`, `-` or `~`
+impl fmt::Display for Diff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            let path = if change.path().is_empty() {
+                "."
+            } else {
+                change.path()
+            };
+            match change {
+                Change::Added { value, .. } => writeln!(f, "+ {path}: {value}")?,
+                Change::Removed { value, .. } => writeln!(f, "- {path}: {value}")?,
+                Change::Modified { old, new, .. } => writeln!(f, "~ {path}: {old} -> {new}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Change, Diff};
+    use serde_json::json;
+
+    #[test]
+    fn diffs_nested_objects_and_arrays() {
+        let old = json!({
+            "metadata": { "labels": { "app.kubernetes.io/name": "web" } },
+            "spec": { "containers": [{ "image": "nginx:1" }, { "image": "sidecar" }] },
+        });
+        let new = json!({
+            "metadata": { "labels": { "app.kubernetes.io/name": "api" } },
+            "spec": { "containers": [{ "image": "nginx:2" }] },
+        });
+        let diff = Diff::new(&old, &new);
+        let paths: Vec<_> = diff.changes.iter().map(Change::path).collect();
+        assert_eq!(paths, [
+            ".metadata.labels['app.kubernetes.io/name']",
+            ".spec.containers[0].image",
+            ".spec.containers[1]"
+        ]);
+        assert!(matches!(&diff.changes[2], Change::Removed { .. }));
+
+        assert!(Diff::new(&old, &old).is_empty());
+        let created = Diff::new(&serde_json::Value::Null, &new);
+        assert_eq!(created.to_string().lines().next().unwrap(), format!("+ .: {new}"));
+    }
+}
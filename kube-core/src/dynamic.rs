@@ -113,13 +113,17 @@ mod test {
     use crate::{
         dynamic::{ApiResource, DynamicObject},
         gvk::GroupVersionKind,
+        resource::Resource,
+    };
+    #[cfg(feature = "request")]
+    use crate::{
         params::{Patch, PatchParams, PostParams},
         request::Request,
-        resource::Resource,
     };
     use k8s_openapi::api::core::v1::Pod;
 
     #[test]
+    #[cfg(feature = "request")]
     fn raw_custom_resource() {
         let gvk = GroupVersionKind::gvk("clux.dev", "v1", "Foo");
         let res = ApiResource::from_gvk(&gvk);
@@ -137,6 +141,7 @@ mod test {
     }
 
     #[test]
+    #[cfg(feature = "request")]
     fn raw_resource_in_default_group() {
         let gvk = GroupVersionKind::gvk("", "v1", "Service");
         let api_resource = ApiResource::from_gvk(&gvk);
@@ -1,6 +1,10 @@
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::response::StatusDetails;
+
 /// An error response from the API.
 #[derive(Error, Deserialize, Serialize, Debug, Clone, Eq, PartialEq)]
 #[error("{message}: {reason}")]
@@ -11,8 +15,243 @@ pub struct ErrorResponse {
     #[serde(default)]
     pub message: String,
     /// The reason for the error
+    ///
+    /// See [`ErrorResponse::status_reason`] for it as a [`StatusReason`].
     #[serde(default)]
     pub reason: String,
     /// The error code
     pub code: u16,
+    /// Extended data about the error, like the object it is about or when to retry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<StatusDetails>,
+}
+
+impl ErrorResponse {
+    /// The reason for the error, falling back to the one implied by the code when it is not set or unknown
+    ///
+    /// ```
+    /// use kube_core::{ErrorResponse, StatusReason};
+    ///
+    /// let err: ErrorResponse = serde_json::from_str(
+    ///     r#"{"status":"Failure","message":"pods \"blog\" not found","reason":"NotFound","code":404}"#,
+    /// )?;
+    /// assert_eq!(err.status_reason(), StatusReason::NotFound);
+    /// # Ok::<(), serde_json::Error>(())
+    /// ```
+    pub fn status_reason(&self) -> StatusReason {
+        match StatusReason::from(self.reason.as_str()) {
+            StatusReason::Unknown | StatusReason::Other(_) => {
+                StatusReason::from_code(self.code).unwrap_or_else(|| self.reason.as_str().into())
+            }
+            reason => reason,
+        }
+    }
+
+    /// How long the apiserver asked to wait before retrying the request
+    pub fn retry_after(&self) -> Option<Duration> {
+        let seconds = self.details.as_ref()?.retry_after_seconds;
+        (seconds > 0).then(|| Duration::from_secs(seconds.into()))
+    }
+
+    /// Whether the request may succeed when it is retried unchanged
+    ///
+    /// This is the case for throttled requests, timeouts, and an unavailable apiserver, as well as
+    /// any error the apiserver asked to retry after a while. Conflicts are not retriable, since
+    /// the request has to be built from the latest version of the object.
+    pub fn is_retriable(&self) -> bool {
+        self.retry_after().is_some()
+            || matches!(
+                self.status_reason(),
+                StatusReason::TooManyRequests
+                    | StatusReason::ServerTimeout
+                    | StatusReason::Timeout
+                    | StatusReason::ServiceUnavailable
+            )
+    }
+}
+
+/// The machine-readable reason of an [`ErrorResponse`], like `metav1.StatusReason`
+///
+/// The apiserver may add reasons in later versions, which are kept as [`StatusReason::Other`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum StatusReason {
+    /// No reason was given
+    Unknown,
+    /// The request is not authenticated
+    Unauthorized,
+    /// The request is authenticated, but not allowed
+    Forbidden,
+    /// The resource or object does not exist
+    NotFound,
+    /// The object to create already exists
+    AlreadyExists,
+    /// The object was modified since it was read, or a concurrent operation is in progress
+    Conflict,
+    /// The resource is no longer available, e.g. the `resourceVersion` of a watch is too old
+    Gone,
+    /// The object failed validation
+    Invalid,
+    /// The apiserver could not complete the operation in time, and it should be retried
+    ServerTimeout,
+    /// The apiserver could not read from its storage
+    StoreReadError,
+    /// The operation did not complete within the timeout of the request
+    Timeout,
+    /// The request was throttled, by API Priority and Fairness or a rate limit
+    TooManyRequests,
+    /// The request is malformed
+    BadRequest,
+    /// The verb is not supported by the resource
+    MethodNotAllowed,
+    /// None of the requested content types can be served
+    NotAcceptable,
+    /// The request body is too large
+    RequestEntityTooLarge,
+    /// The content type of the request body is not supported
+    UnsupportedMediaType,
+    /// An unexpected error occurred in the apiserver
+    InternalError,
+    /// The content of the request has expired, e.g. a `continue` token
+    Expired,
+    /// The apiserver, or the service behind it, is unavailable
+    ServiceUnavailable,
+    /// A reason not known to this version of kube
+    Other(String),
+}
+
+impl StatusReason {
+    /// The reason as sent by the apiserver
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Unknown => "",
+            Self::Unauthorized => "Unauthorized",
+            Self::Forbidden => "Forbidden",
+            Self::NotFound => "NotFound",
+            Self::AlreadyExists => "AlreadyExists",
+            Self::Conflict => "Conflict",
+            Self::Gone => "Gone",
+            Self::Invalid => "Invalid",
+            Self::ServerTimeout => "ServerTimeout",
+            Self::StoreReadError => "StorageReadError",
+            Self::Timeout => "Timeout",
+            Self::TooManyRequests => "TooManyRequests",
+            Self::BadRequest => "BadRequest",
+            Self::MethodNotAllowed => "MethodNotAllowed",
+            Self::NotAcceptable => "NotAcceptable",
+            Self::RequestEntityTooLarge => "RequestEntityTooLarge",
+            Self::UnsupportedMediaType => "UnsupportedMediaType",
+            Self::InternalError => "InternalError",
+            Self::Expired => "Expired",
+            Self::ServiceUnavailable => "ServiceUnavailable",
+            Self::Other(reason) => reason,
+        }
+    }
+
+    /// The reason the apiserver uses for errors with the HTTP status `code`, if unambiguous
+    pub fn from_code(code: u16) -> Option<Self> {
+        Some(match code {
+            400 => Self::BadRequest,
+            401 => Self::Unauthorized,
+            403 => Self::Forbidden,
+            404 => Self::NotFound,
+            405 => Self::MethodNotAllowed,
+            406 => Self::NotAcceptable,
+            409 => Self::Conflict,
+            410 => Self::Gone,
+            413 => Self::RequestEntityTooLarge,
+            415 => Self::UnsupportedMediaType,
+            422 => Self::Invalid,
+            429 => Self::TooManyRequests,
+            500 => Self::InternalError,
+            503 => Self::ServiceUnavailable,
+            504 => Self::Timeout,
+            _ => return None,
+        })
+    }
+}
+
+impl From<&str> for StatusReason {
+    fn from(reason: &str) -> Self {
+        match reason {
+            "" => Self::Unknown,
+            "Unauthorized" => Self::Unauthorized,
+            "Forbidden" => Self::Forbidden,
+            "NotFound" => Self::NotFound,
+            "AlreadyExists" => Self::AlreadyExists,
+            "Conflict" => Self::Conflict,
+            "Gone" => Self::Gone,
+            "Invalid" => Self::Invalid,
+            "ServerTimeout" => Self::ServerTimeout,
+            "StorageReadError" => Self::StoreReadError,
+            "Timeout" => Self::Timeout,
+            "TooManyRequests" => Self::TooManyRequests,
+            "BadRequest" => Self::BadRequest,
+            "MethodNotAllowed" => Self::MethodNotAllowed,
+            "NotAcceptable" => Self::NotAcceptable,
+            "RequestEntityTooLarge" => Self::RequestEntityTooLarge,
+            "UnsupportedMediaType" => Self::UnsupportedMediaType,
+            "InternalError" => Self::InternalError,
+            "Expired" => Self::Expired,
+            "ServiceUnavailable" => Self::ServiceUnavailable,
+            other => Self::Other(other.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for StatusReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ErrorResponse, StatusReason};
+    use std::time::Duration;
+
+    #[test]
+    fn errors_are_classified() {
+        let throttled: ErrorResponse = serde_json::from_value(serde_json::json!({
+            "status": "Failure",
+            "message": "Too many requests, please try again later.",
+            "reason": "TooManyRequests",
+            "details": { "retryAfterSeconds": 2 },
+            "code": 429
+        }))
+        .unwrap();
+        assert_eq!(throttled.status_reason(), StatusReason::TooManyRequests);
+        assert_eq!(throttled.retry_after(), Some(Duration::from_secs(2)));
+        assert!(throttled.is_retriable());
+
+        let conflict: ErrorResponse = serde_json::from_value(serde_json::json!({
+            "status": "Failure",
+            "message": "the object has been modified",
+            "reason": "Conflict",
+            "code": 409
+        }))
+        .unwrap();
+        assert_eq!(conflict.status_reason(), StatusReason::Conflict);
+        assert_eq!(conflict.retry_after(), None);
+        assert!(!conflict.is_retriable());
+
+        // errors that could not be parsed are classified by their code
+        let unparsed = ErrorResponse {
+            status: "503 Service Unavailable".into(),
+            message: "\"upstream connect error\"".into(),
+            reason: "Failed to parse error data".into(),
+            code: 503,
+            details: None,
+        };
+        assert_eq!(unparsed.status_reason(), StatusReason::ServiceUnavailable);
+        assert!(unparsed.is_retriable());
+
+        let future = ErrorResponse {
+            reason: "SomethingNew".into(),
+            code: 418,
+            ..unparsed
+        };
+        assert_eq!(future.status_reason(), StatusReason::Other("SomethingNew".into()));
+        assert_eq!(future.status_reason().to_string(), "SomethingNew");
+    }
 }
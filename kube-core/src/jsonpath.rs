@@ -0,0 +1,548 @@
+//! Kubernetes flavored JSONPath evaluation, as used by `kubectl get -o jsonpath`
+//!
+//! This supports the expressions used for printer columns and by `kubectl`:
+//!
+//! - fields with `.name` or `['name']`, with `\.` escaping dots in field names, and `*` for all children
+//! - recursive descent with `..name`
+//! - array indices, negative indices, slices and unions, e.g. `[0]`, `[-1]`, `[1:3]` or `[0,2]`
+//! - filters comparing a field to a literal, e.g. `[?(@.type=='Ready')]` or `[?(@.replicas>2)]`,
+//!   and checking for the existence of a field, e.g. `[?(@.status)]`
+//!
+//! The expression may be wrapped in `{}` and start with `$`, like
+//! `kubectl` templates. Templates with several expressions or `range` are not supported.
+//!
+//! ```
+//! use kube::core::jsonpath;
+//! use serde_json::json;
+//!
+//! let pod = json!({
+//!     "status": { "conditions": [
+//!         { "type": "Initialized", "status": "True" },
+//!         { "type": "Ready", "status": "False" },
+//!     ]}
+//! });
+//! let ready = jsonpath::query(&pod, ".status.conditions[?(@.type=='Ready')].status")?;
+//! assert_eq!(ready, vec![json!("False")]);
+//! # Ok::<(), jsonpath::Error>(())
+//! ```
+use std::{cmp::Ordering, str::FromStr};
+
+use serde::Serialize;
+use serde_json::Value;
+use thiserror::Error;
+
+/// Possible errors when evaluating a JSONPath
+#[derive(Debug, Error)]
+pub enum Error {
+    /// The expression is not a valid JSONPath
+    #[error("failed to parse jsonpath: {0}")]
+    Parse(String),
+
+    /// The object could not be serialized to json
+    #[error("failed to serialize object: {0}")]
+    Serialize(#[source] serde_json::Error),
+}
+
+/// Evaluate a JSONPath expression against an object, returning all matching values
+///
+/// Use [`JsonPath`] to parse the expression once when evaluating it against many objects.
+pub fn query<T: Serialize>(obj: &T, path: &str) -> Result<Vec<Value>, Error> {
+    let path = path.parse::<JsonPath>()?;
+    let value = serde_json::to_value(obj).map_err(Error::Serialize)?;
+    Ok(path.find(&value).into_iter().cloned().collect())
+}
+
+/// A parsed JSONPath expression
+#[derive(Clone, Debug, PartialEq)]
+pub struct JsonPath(Vec<Segment>);
+
+impl JsonPath {
+    /// Find all values matching the expression in `value`, in document order
+    pub fn find<'a>(&self, value: &'a Value) -> Vec<&'a Value> {
+        let mut current = vec![value];
+        for segment in &self.0 {
+            let mut next = vec![];
+            for v in current {
+                match segment {
+                    Segment::Child(sel) => sel.select(v, &mut next),
+                    Segment::Descendant(sel) => descend(v, &mut |d| sel.select(d, &mut next)),
+                }
+            }
+            current = next;
+        }
+        current
+    }
+}
+
+impl FromStr for JsonPath {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut expr = s.trim();
+        if let Some(inner) = expr.strip_prefix('{').and_then(|e| e.strip_suffix('}')) {
+            expr = inner.trim();
+        }
+        expr = expr.strip_prefix('$').unwrap_or(expr);
+        let mut parser = Parser {
+            input: s,
+            chars: expr.chars().collect(),
+            pos: 0,
+        };
+        // kubectl also accepts paths without the leading dot
+        if parser.peek().is_some_and(|c| c != '.' && c != '[') {
+            parser.chars.insert(0, '.');
+        }
+        let segments = parser.segments()?;
+        if parser.peek().is_some() {
+            return Err(parser.error("unexpected character"));
+        }
+        Ok(JsonPath(segments))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Segment {
+    Child(Selector),
+    Descendant(Selector),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Selector {
+    Name(String),
+    Wildcard,
+    Index(i64),
+    Slice(Option<i64>, Option<i64>, Option<i64>),
+    Union(Vec<Selector>),
+    Filter(Filter),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Filter {
+    Exists(JsonPath),
+    Compare(Operand, Op, Operand),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Operand {
+    Path(JsonPath),
+    Literal(Value),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// Call `f` on `value` and all values nested in it, in document order
+fn descend<'a>(value: &'a Value, f: &mut impl FnMut(&'a Value)) {
+    f(value);
+    match value {
+        Value::Object(map) => {
+            for v in map.values() {
+                descend(v, &mut *f);
+            }
+        }
+        Value::Array(items) => {
+            for v in items {
+                descend(v, &mut *f);
+            }
+        }
+        _ => {}
+    }
+}
+
+impl Selector {
+    fn select<'a>(&self, value: &'a Value, out: &mut Vec<&'a Value>) {
+        match (self, value) {
+            (Selector::Name(name), Value::Object(map)) => out.extend(map.get(name)),
+            (Selector::Wildcard, Value::Object(map)) => out.extend(map.values()),
+            (Selector::Wildcard, Value::Array(items)) => out.extend(items),
+            (Selector::Index(i), Value::Array(items)) => {
+                out.extend(resolve_index(*i, items.len()).map(|i| &items[i]));
+            }
+            (Selector::Slice(start, end, step), Value::Array(items)) => {
+                let len = i64::try_from(items.len()).unwrap_or(i64::MAX);
+                let clamp = |i: i64| if i < 0 { (len + i).max(0) } else { i.min(len) };
+                let start = start.map_or(0, clamp);
+                let end = end.map_or(len, clamp);
+                let step = usize::try_from(step.unwrap_or(1)).unwrap_or(1);
+                for i in (start..end).step_by(step) {
+                    out.extend(usize::try_from(i).ok().and_then(|i| items.get(i)));
+                }
+            }
+            (Selector::Union(selectors), _) => selectors.iter().for_each(|s| s.select(value, out)),
+            (Selector::Filter(filter), Value::Array(items)) => {
+                out.extend(items.iter().filter(|item| filter.matches(item)));
+            }
+            (Selector::Filter(filter), Value::Object(map)) => {
+                out.extend(map.values().filter(|item| filter.matches(item)));
+            }
+            _ => {}
+        }
+    }
+}
+
+fn resolve_index(index: i64, len: usize) -> Option<usize> {
+    let index = if index < 0 {
+        i64::try_from(len).ok()? + index
+    } else {
+        index
+    };
+    usize::try_from(index).ok().filter(|&i| i < len)
+}
+
+impl Filter {
+    fn matches(&self, item: &Value) -> bool {
+        match self {
+            Filter::Exists(path) => !path.find(item).is_empty(),
+            Filter::Compare(lhs, op, rhs) => match (lhs.first(item), rhs.first(item)) {
+                (Some(l), Some(r)) => op.holds(compare(l, r)),
+                _ => false,
+            },
+        }
+    }
+}
+
+impl Operand {
+    fn first<'a>(&'a self, item: &'a Value) -> Option<&'a Value> {
+        match self {
+            Operand::Path(path) => path.find(item).into_iter().next(),
+            Operand::Literal(value) => Some(value),
+        }
+    }
+}
+
+/// Order two values of the same type, `None` if they cannot be ordered
+fn compare(l: &Value, r: &Value) -> Option<Ordering> {
+    match (l, r) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64()?.partial_cmp(&b.as_f64()?),
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        _ => (l == r).then_some(Ordering::Equal),
+    }
+}
+
+impl Op {
+    fn holds(self, ord: Option<Ordering>) -> bool {
+        match self {
+            Op::Eq => ord == Some(Ordering::Equal),
+            Op::Ne => ord != Some(Ordering::Equal),
+            Op::Lt => ord == Some(Ordering::Less),
+            Op::Le => matches!(ord, Some(Ordering::Less | Ordering::Equal)),
+            Op::Gt => ord == Some(Ordering::Greater),
+            Op::Ge => matches!(ord, Some(Ordering::Greater | Ordering::Equal)),
+        }
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> Error {
+        Error::Parse(format!("{message} at position {} of {:?}", self.pos, self.input))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let found = self.peek() == Some(c);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), Error> {
+        self.skip_whitespace();
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected {c:?}")))
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn segments(&mut self) -> Result<Vec<Segment>, Error> {
+        let mut segments = vec![];
+        loop {
+            if self.eat('.') {
+                if self.eat('.') {
+                    let selector = if self.eat('[') {
+                        self.bracket()?
+                    } else {
+                        self.dot_selector()?
+                    };
+                    segments.push(Segment::Descendant(selector));
+                } else if self.peek().is_some_and(is_name_char) || self.peek() == Some('*') {
+                    segments.push(Segment::Child(self.dot_selector()?));
+                }
+                // a lone dot refers to the current object
+            } else if self.eat('[') {
+                segments.push(Segment::Child(self.bracket()?));
+            } else {
+                return Ok(segments);
+            }
+        }
+    }
+
+    fn dot_selector(&mut self) -> Result<Selector, Error> {
+        if self.eat('*') {
+            return Ok(Selector::Wildcard);
+        }
+        let mut name = String::new();
+        while let Some(c) = self.peek().filter(|&c| is_name_char(c)) {
+            self.pos += 1;
+            if c == '\\' {
+                name.extend(self.peek());
+                self.pos += 1;
+            } else {
+                name.push(c);
+            }
+        }
+        if name.is_empty() {
+            return Err(self.error("expected a field name"));
+        }
+        Ok(Selector::Name(name))
+    }
+
+    fn bracket(&mut self) -> Result<Selector, Error> {
+        self.skip_whitespace();
+        if self.eat('*') {
+            self.expect(']')?;
+            return Ok(Selector::Wildcard);
+        }
+        if self.eat('?') {
+            self.expect('(')?;
+            let filter = self.filter()?;
+            self.expect(')')?;
+            self.expect(']')?;
+            return Ok(Selector::Filter(filter));
+        }
+        let mut selectors = vec![];
+        loop {
+            self.skip_whitespace();
+            let selector = match self.peek() {
+                Some(q @ ('\'' | '"')) => Selector::Name(self.quoted(q)?),
+                _ => self.index_or_slice()?,
+            };
+            selectors.push(selector);
+            self.skip_whitespace();
+            if self.eat(']') {
+                break;
+            }
+            self.expect(',')?;
+        }
+        Ok(if selectors.len() == 1 {
+            selectors.remove(0)
+        } else {
+            Selector::Union(selectors)
+        })
+    }
+
+    fn index_or_slice(&mut self) -> Result<Selector, Error> {
+        let start = self.integer()?;
+        if !self.eat(':') {
+            return start
+                .map(Selector::Index)
+                .ok_or_else(|| self.error("expected an index"));
+        }
+        let end = self.integer()?;
+        let step = if self.eat(':') { self.integer()? } else { None };
+        if step.is_some_and(|s| s <= 0) {
+            return Err(self.error("slice step must be positive"));
+        }
+        Ok(Selector::Slice(start, end, step))
+    }
+
+    fn integer(&mut self) -> Result<Option<i64>, Error> {
+        self.skip_whitespace();
+        let start = self.pos;
+        self.eat('-');
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        if self.pos == start {
+            return Ok(None);
+        }
+        let digits: String = self.chars[start..self.pos].iter().collect();
+        let int = digits.parse().map_err(|_| self.error("invalid integer"))?;
+        self.skip_whitespace();
+        Ok(Some(int))
+    }
+
+    fn quoted(&mut self, quote: char) -> Result<String, Error> {
+        self.pos += 1;
+        let mut text = String::new();
+        loop {
+            match self.peek() {
+                None => return Err(self.error("unterminated string")),
+                Some(c) if c == quote => {
+                    self.pos += 1;
+                    return Ok(text);
+                }
+                Some('\\') => {
+                    self.pos += 1;
+                    text.extend(self.peek());
+                    self.pos += 1;
+                }
+                Some(c) => {
+                    text.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+
+    fn filter(&mut self) -> Result<Filter, Error> {
+        self.skip_whitespace();
+        let lhs = self.operand()?;
+        self.skip_whitespace();
+        let op = match (self.peek(), self.chars.get(self.pos + 1)) {
+            (Some(')'), _) => {
+                return match lhs {
+                    Operand::Path(path) => Ok(Filter::Exists(path)),
+                    Operand::Literal(_) => Err(self.error("expected a comparison")),
+                };
+            }
+            (Some('='), Some('=')) => Op::Eq,
+            (Some('!'), Some('=')) => Op::Ne,
+            (Some('<'), Some('=')) => Op::Le,
+            (Some('>'), Some('=')) => Op::Ge,
+            (Some('<'), _) => Op::Lt,
+            (Some('>'), _) => Op::Gt,
+            _ => return Err(self.error("expected a comparison operator")),
+        };
+        self.pos += if matches!(op, Op::Lt | Op::Gt) { 1 } else { 2 };
+        self.skip_whitespace();
+        let rhs = self.operand()?;
+        self.skip_whitespace();
+        Ok(Filter::Compare(lhs, op, rhs))
+    }
+
+    fn operand(&mut self) -> Result<Operand, Error> {
+        match self.peek() {
+            Some('@') => {
+                self.pos += 1;
+                Ok(Operand::Path(JsonPath(self.segments()?)))
+            }
+            Some(q @ ('\'' | '"')) => Ok(Operand::Literal(Value::String(self.quoted(q)?))),
+            Some(c) if c == '-' || c.is_ascii_digit() => {
+                let start = self.pos;
+                while self
+                    .peek()
+                    .is_some_and(|c| c == '-' || c == '.' || c.is_ascii_alphanumeric())
+                {
+                    self.pos += 1;
+                }
+                let number: String = self.chars[start..self.pos].iter().collect();
+                serde_json::from_str::<serde_json::Number>(&number)
+                    .map(|n| Operand::Literal(Value::Number(n)))
+                    .map_err(|_| self.error("invalid number"))
+            }
+            _ => {
+                for (word, value) in [("true", true), ("false", false)] {
+                    let end = self.pos + word.len();
+                    if self
+                        .chars
+                        .get(self.pos..end)
+                        .is_some_and(|w| w.iter().copied().eq(word.chars()))
+                    {
+                        self.pos = end;
+                        return Ok(Operand::Literal(Value::Bool(value)));
+                    }
+                }
+                Err(self.error("expected a field or a literal"))
+            }
+        }
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    !c.is_whitespace() && !".[]()=!<>,'\"{}@*".contains(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn pod() -> Value {
+        json!({
+            "metadata": {
+                "name": "web",
+                "labels": { "app.kubernetes.io/name": "web" },
+            },
+            "spec": { "containers": [
+                {
+                    "name": "app",
+                    "image": "nginx",
+                    "ports": [{ "containerPort": 80 }, { "containerPort": 443 }],
+                },
+                { "name": "sidecar", "image": "envoy", "ports": [{ "containerPort": 9901 }] },
+            ]},
+            "status": { "conditions": [
+                { "type": "Initialized", "status": "True" },
+                { "type": "Ready", "status": "False", "reason": "ContainersNotReady" },
+            ]},
+        })
+    }
+
+    #[test]
+    fn evaluates_kubectl_expressions() {
+        let cases = [
+            ("{.metadata.name}", json!(["web"])),
+            ("metadata.name", json!(["web"])),
+            ("$.spec.containers[*].name", json!(["app", "sidecar"])),
+            (".spec.containers[-1].image", json!(["envoy"])),
+            (".spec.containers[0:1].name", json!(["app"])),
+            (".spec.containers[0].ports[0,1].containerPort", json!([80, 443])),
+            ("..containerPort", json!([80, 443, 9901])),
+            (".metadata.labels.app\\.kubernetes\\.io/name", json!(["web"])),
+            (".metadata.labels['app.kubernetes.io/name']", json!(["web"])),
+            (".status.conditions[?(@.type=='Ready')].status", json!(["False"])),
+            (
+                ".status.conditions[?(@.type != \"Ready\")].type",
+                json!(["Initialized"]),
+            ),
+            (
+                ".status.conditions[?(@.reason)].reason",
+                json!(["ContainersNotReady"]),
+            ),
+            (
+                ".spec.containers[?(@.ports[0].containerPort > 100)].name",
+                json!(["sidecar"]),
+            ),
+            (".spec.volumes", json!([])),
+        ];
+        for (path, expected) in cases {
+            assert_eq!(json!(query(&pod(), path).unwrap()), expected, "{path}");
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_expressions() {
+        for path in [
+            ".spec[",
+            ".spec.containers[?(@.name=='app']",
+            ".spec.containers[0:1:0]",
+            ".a b",
+        ] {
+            assert!(matches!(path.parse::<JsonPath>(), Err(Error::Parse(_))), "{path}");
+        }
+    }
+}
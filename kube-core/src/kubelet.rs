@@ -0,0 +1,275 @@
+//! Read-only kubelet endpoints, for node agents and monitoring
+//!
+//! The kubelet serves these on its own port, 10250 by default, rather than through the apiserver.
+//! The [`Summary`] types are a subset of the kubelet's `stats/v1alpha1` API; fields that are not
+//! reported by the kubelet, e.g. because only cpu and memory were requested, are `None`.
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::Time;
+use serde::{Deserialize, Serialize};
+
+use crate::{request::Error, Request};
+
+/// Resource usage of a node and its pods, from `/stats/summary`
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Summary {
+    /// Usage of the node as a whole
+    pub node: NodeStats,
+    /// Usage of every pod on the node
+    #[serde(default)]
+    pub pods: Vec<PodStats>,
+}
+
+/// Resource usage of a node
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeStats {
+    /// The name of the node
+    pub node_name: String,
+    /// When the node started
+    pub start_time: Option<Time>,
+    /// Cpu usage of the node
+    pub cpu: Option<CpuStats>,
+    /// Memory usage of the node
+    pub memory: Option<MemoryStats>,
+    /// Network usage of the node
+    pub network: Option<NetworkStats>,
+    /// Usage of the filesystem that kubelet and pod volumes live on
+    pub fs: Option<FsStats>,
+}
+
+/// Resource usage of a pod
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PodStats {
+    /// The pod the usage is for
+    pub pod_ref: PodReference,
+    /// When the pod started
+    pub start_time: Option<Time>,
+    /// Usage of every container of the pod
+    #[serde(default)]
+    pub containers: Vec<ContainerStats>,
+    /// Cpu usage of the pod
+    pub cpu: Option<CpuStats>,
+    /// Memory usage of the pod
+    pub memory: Option<MemoryStats>,
+    /// Network usage of the pod
+    pub network: Option<NetworkStats>,
+    /// Usage of local ephemeral storage, for logs, writable layers and `emptyDir` volumes
+    #[serde(rename = "ephemeral-storage")]
+    pub ephemeral_storage: Option<FsStats>,
+}
+
+/// The pod that [`PodStats`] are for
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct PodReference {
+    /// The name of the pod
+    pub name: String,
+    /// The namespace of the pod
+    pub namespace: String,
+    /// The uid of the pod
+    pub uid: String,
+}
+
+/// Resource usage of a container
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ContainerStats {
+    /// The name of the container
+    pub name: String,
+    /// When the container started
+    pub start_time: Option<Time>,
+    /// Cpu usage of the container
+    pub cpu: Option<CpuStats>,
+    /// Memory usage of the container
+    pub memory: Option<MemoryStats>,
+    /// Usage of the writable layer of the container
+    pub rootfs: Option<FsStats>,
+    /// Usage of the logs of the container
+    pub logs: Option<FsStats>,
+}
+
+/// Cpu usage at a point in time
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CpuStats {
+    /// When the sample was taken
+    pub time: Option<Time>,
+    /// Average cpu usage over the sampling window, in billionths of a core
+    pub usage_nano_cores: Option<u64>,
+    /// Cumulative cpu time used, in nanoseconds
+    pub usage_core_nano_seconds: Option<u64>,
+}
+
+/// Memory usage at a point in time
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryStats {
+    /// When the sample was taken
+    pub time: Option<Time>,
+    /// Memory that is available for use, in bytes
+    pub available_bytes: Option<u64>,
+    /// Total memory in use, including caches that can be reclaimed, in bytes
+    pub usage_bytes: Option<u64>,
+    /// Memory in use that cannot be reclaimed, which the kubelet evicts on, in bytes
+    pub working_set_bytes: Option<u64>,
+    /// Anonymous and swap cache memory, in bytes
+    pub rss_bytes: Option<u64>,
+    /// Cumulative number of minor page faults
+    pub page_faults: Option<u64>,
+    /// Cumulative number of major page faults
+    pub major_page_faults: Option<u64>,
+}
+
+/// Network usage at a point in time
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkStats {
+    /// When the sample was taken
+    pub time: Option<Time>,
+    /// Usage of the default interface
+    #[serde(flatten)]
+    pub default: InterfaceStats,
+    /// Usage of every interface, including the default one
+    #[serde(default)]
+    pub interfaces: Vec<InterfaceStats>,
+}
+
+/// Cumulative usage of a network interface
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct InterfaceStats {
+    /// The name of the interface
+    #[serde(default)]
+    pub name: String,
+    /// Bytes received
+    pub rx_bytes: Option<u64>,
+    /// Receive errors
+    pub rx_errors: Option<u64>,
+    /// Bytes transmitted
+    pub tx_bytes: Option<u64>,
+    /// Transmit errors
+    pub tx_errors: Option<u64>,
+}
+
+/// Filesystem usage at a point in time
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FsStats {
+    /// When the sample was taken
+    pub time: Option<Time>,
+    /// Storage that is available for use, in bytes
+    pub available_bytes: Option<u64>,
+    /// Total size of the filesystem, in bytes
+    pub capacity_bytes: Option<u64>,
+    /// Storage in use, in bytes
+    pub used_bytes: Option<u64>,
+    /// Free inodes
+    pub inodes_free: Option<u64>,
+    /// Total inodes
+    pub inodes: Option<u64>,
+    /// Inodes in use
+    pub inodes_used: Option<u64>,
+}
+
+impl Request {
+    /// List the pods the kubelet is running, from `/pods`
+    ///
+    /// Unlike a pod list from the apiserver, this includes static pods, and reflects what the
+    /// kubelet has admitted rather than what is scheduled.
+    pub fn kubelet_pods() -> Result<http::Request<Vec<u8>>, Error> {
+        http::Request::get("/pods")
+            .body(vec![])
+            .map_err(Error::BuildRequest)
+    }
+
+    /// Get resource usage of the node and its pods, from `/stats/summary`
+    ///
+    /// With `only_cpu_and_memory`, the kubelet skips the more expensive network and filesystem stats.
+    pub fn kubelet_stats_summary(only_cpu_and_memory: bool) -> Result<http::Request<Vec<u8>>, Error> {
+        let target = if only_cpu_and_memory {
+            "/stats/summary?only_cpu_and_memory=true"
+        } else {
+            "/stats/summary"
+        };
+        http::Request::get(target)
+            .body(vec![])
+            .map_err(Error::BuildRequest)
+    }
+
+    /// Read a file from the log directory of the node, or list it, from `/logs/{path}`
+    ///
+    /// `path` is relative to `/var/log` on the node, and an empty `path` lists the directory.
+    pub fn kubelet_logs(path: &str) -> Result<http::Request<Vec<u8>>, Error> {
+        let path = path.trim_start_matches('/');
+        if path.split('/').any(|segment| segment == "..") {
+            return Err(Error::Validation(format!(
+                "log path {path:?} must not leave the log directory"
+            )));
+        }
+        let target = format!("/logs/{path}");
+        http::Request::get(target)
+            .body(vec![])
+            .map_err(Error::BuildRequest)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Summary;
+    use crate::Request;
+
+    #[test]
+    fn kubelet_paths() {
+        assert_eq!(Request::kubelet_pods().unwrap().uri(), "/pods");
+        assert_eq!(
+            Request::kubelet_stats_summary(false).unwrap().uri(),
+            "/stats/summary"
+        );
+        assert_eq!(
+            Request::kubelet_stats_summary(true).unwrap().uri(),
+            "/stats/summary?only_cpu_and_memory=true"
+        );
+        assert_eq!(Request::kubelet_logs("").unwrap().uri(), "/logs/");
+        assert_eq!(
+            Request::kubelet_logs("/pods/kube.log").unwrap().uri(),
+            "/logs/pods/kube.log"
+        );
+        assert!(Request::kubelet_logs("../etc/shadow").is_err());
+    }
+
+    #[test]
+    fn summary_deserialize() {
+        let summary: Summary = serde_json::from_value(serde_json::json!({
+            "node": {
+                "nodeName": "kind-control-plane",
+                "cpu": { "time": "2025-03-06T06:06:57Z", "usageNanoCores": 182999515 },
+                "memory": { "time": "2025-03-06T06:06:57Z", "workingSetBytes": 1068412928 },
+                "network": {
+                    "time": "2025-03-06T06:06:57Z",
+                    "name": "eth0",
+                    "rxBytes": 1024,
+                    "interfaces": [{ "name": "eth0", "rxBytes": 1024 }]
+                }
+            },
+            "pods": [{
+                "podRef": { "name": "coredns", "namespace": "kube-system", "uid": "8e2d" },
+                "containers": [{ "name": "coredns", "memory": { "usageBytes": 14692352 } }],
+                "ephemeral-storage": { "usedBytes": 32768 }
+            }]
+        }))
+        .unwrap();
+
+        assert_eq!(summary.node.cpu.unwrap().usage_nano_cores, Some(182_999_515));
+        let network = summary.node.network.unwrap();
+        assert_eq!(network.default.name, "eth0");
+        assert_eq!(network.interfaces[0].rx_bytes, Some(1024));
+        let pod = &summary.pods[0];
+        assert_eq!(pod.pod_ref.namespace, "kube-system");
+        assert_eq!(
+            pod.containers[0].memory.as_ref().unwrap().usage_bytes,
+            Some(14_692_352)
+        );
+        assert_eq!(pod.ephemeral_storage.as_ref().unwrap().used_bytes, Some(32_768));
+        assert!(pod.cpu.is_none());
+    }
+}
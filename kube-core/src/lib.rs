@@ -4,6 +4,14 @@
 //!
 //! It does not export export a client, but it also has almost no dependencies.
 //!
+//! The request builders of [`request`] are behind the default `request` feature, which pulls in `http`.
+//! Without it, only the plain data types remain, such as [`params`], [`gvk`], [`metadata`], [`labels`]
+//! and the schema helpers, so they can be shared with code that never talks to an apiserver:
+//!
+//! ```toml
+//! kube-core = { version = "0.99", default-features = false }
+//! ```
+//!
 //! Everything in this crate is re-exported from [`kube`](https://crates.io/crates/kube)
 //! (even with zero features) under [`kube::core`]((https://docs.rs/kube/*/kube/core/index.html)).
 #![cfg_attr(docsrs, feature(doc_cfg))]
@@ -25,8 +33,10 @@ pub use dynamic::{ApiResource, DynamicObject};
 pub mod crd;
 pub use crd::CustomResourceExt;
 
+pub mod diff;
+
 pub mod cel;
-pub use cel::{Message, Reason, Rule};
+pub use cel::{ImmutableFieldError, Message, Reason, Rule};
 
 #[cfg(feature = "schema")]
 pub use cel::{merge_properties, validate, validate_property};
@@ -34,20 +44,37 @@ pub use cel::{merge_properties, validate, validate_property};
 pub mod gvk;
 pub use gvk::{GroupVersion, GroupVersionKind, GroupVersionResource};
 
+pub mod jsonpath;
+
 pub mod metadata;
 pub use metadata::{ListMeta, ObjectMeta, PartialObjectMeta, PartialObjectMetaExt, TypeMeta};
 
+pub mod metrics;
+
 pub mod labels;
 
 #[cfg(feature = "kubelet-debug")] pub mod kubelet_debug;
 
+#[cfg_attr(docsrs, doc(cfg(feature = "kubelet")))]
+#[cfg(feature = "kubelet")]
+pub mod kubelet;
+
 pub mod object;
 pub use object::{NotUsed, Object, ObjectList};
 
+pub mod openapi;
+
 pub mod params;
 
+pub mod printer;
+
+pub mod projection;
+pub use projection::Projection;
+
+#[cfg_attr(docsrs, doc(cfg(feature = "request")))]
+#[cfg(feature = "request")]
 pub mod request;
-pub use request::Request;
+#[cfg(feature = "request")] pub use request::Request;
 
 mod resource;
 pub use resource::{
@@ -64,15 +91,20 @@ pub use labels::{Expression, ParseExpressionError, Selector, SelectorExt};
 #[cfg(feature = "schema")]
 pub mod schema;
 
+pub mod server;
+
 pub mod subresource;
 
+pub mod table;
+pub use table::Table;
+
 pub mod util;
 
 pub mod watch;
-pub use watch::WatchEvent;
+pub use watch::{LossyWatchEvent, RelistingEvent, WatchEvent};
 
 mod error;
-pub use error::ErrorResponse;
+pub use error::{ErrorResponse, StatusReason};
 
 mod version;
 pub use version::Version;
@@ -0,0 +1,219 @@
+//! Resource usage from the `metrics.k8s.io` API, as served by metrics-server
+//!
+//! These are the types behind `kubectl top`. The metrics API only supports `get` and `list`,
+//! and reports the usage averaged over the [`window`](NodeMetrics::window) ending at the
+//! [`timestamp`](NodeMetrics::timestamp) of each sample:
+//!
+//! ```no_run
+//! # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+//! # let client: kube::Client = todo!();
+//! use kube::{api::{Api, ListParams}, core::metrics::PodMetrics};
+//!
+//! let pods: Api<PodMetrics> = Api::default_namespaced(client);
+//! for pod in pods.list(&ListParams::default()).await? {
+//!     for container in &pod.containers {
+//!         println!("{}: {:?}", container.name, container.cpu());
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+use std::{borrow::Cow, collections::BTreeMap};
+
+use k8s_openapi::apimachinery::pkg::{api::resource::Quantity, apis::meta::v1::Time};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    metadata::{ObjectMeta, TypeMeta},
+    resource::Resource,
+    ClusterResourceScope, Duration, NamespaceResourceScope,
+};
+
+const GROUP: &str = "metrics.k8s.io";
+const VERSION: &str = "v1beta1";
+
+/// Resource usage of a node, `kubectl top node`
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct NodeMetrics {
+    /// The type fields, normally `metrics.k8s.io/v1beta1` and `NodeMetrics`
+    #[serde(flatten, default)]
+    pub types: TypeMeta,
+
+    /// Object metadata, named after the node
+    #[serde(default)]
+    pub metadata: ObjectMeta,
+
+    /// The end of the window the usage was collected over
+    pub timestamp: Time,
+
+    /// The length of the window the usage was collected over
+    pub window: Duration,
+
+    /// The memory and cpu usage of the node
+    #[serde(default)]
+    pub usage: BTreeMap<String, Quantity>,
+}
+
+/// Resource usage of the containers of a pod, `kubectl top pod`
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct PodMetrics {
+    /// The type fields, normally `metrics.k8s.io/v1beta1` and `PodMetrics`
+    #[serde(flatten, default)]
+    pub types: TypeMeta,
+
+    /// Object metadata, named after the pod
+    #[serde(default)]
+    pub metadata: ObjectMeta,
+
+    /// The end of the window the usage was collected over
+    pub timestamp: Time,
+
+    /// The length of the window the usage was collected over
+    pub window: Duration,
+
+    /// The usage of every container of the pod
+    #[serde(default)]
+    pub containers: Vec<ContainerMetrics>,
+}
+
+/// Resource usage of a single container in [`PodMetrics`]
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+pub struct ContainerMetrics {
+    /// The name of the container
+    pub name: String,
+
+    /// The memory and cpu usage of the container
+    #[serde(default)]
+    pub usage: BTreeMap<String, Quantity>,
+}
+
+impl NodeMetrics {
+    /// The cpu usage, in cores like `250m`
+    pub fn cpu(&self) -> Option<&Quantity> {
+        self.usage.get("cpu")
+    }
+
+    /// The memory usage, in bytes like `512Mi`
+    pub fn memory(&self) -> Option<&Quantity> {
+        self.usage.get("memory")
+    }
+}
+
+impl PodMetrics {
+    /// The usage of the container with the given name
+    pub fn container(&self, name: &str) -> Option<&ContainerMetrics> {
+        self.containers.iter().find(|c| c.name == name)
+    }
+}
+
+impl ContainerMetrics {
+    /// The cpu usage, in cores like `250m`
+    pub fn cpu(&self) -> Option<&Quantity> {
+        self.usage.get("cpu")
+    }
+
+    /// The memory usage, in bytes like `512Mi`
+    pub fn memory(&self) -> Option<&Quantity> {
+        self.usage.get("memory")
+    }
+}
+
+impl Resource for NodeMetrics {
+    type DynamicType = ();
+    type Scope = ClusterResourceScope;
+
+    fn kind(&(): &()) -> Cow<'_, str> {
+        "NodeMetrics".into()
+    }
+
+    fn group(&(): &()) -> Cow<'_, str> {
+        GROUP.into()
+    }
+
+    fn version(&(): &()) -> Cow<'_, str> {
+        VERSION.into()
+    }
+
+    fn plural(&(): &()) -> Cow<'_, str> {
+        "nodes".into()
+    }
+
+    fn meta(&self) -> &ObjectMeta {
+        &self.metadata
+    }
+
+    fn meta_mut(&mut self) -> &mut ObjectMeta {
+        &mut self.metadata
+    }
+}
+
+impl Resource for PodMetrics {
+    type DynamicType = ();
+    type Scope = NamespaceResourceScope;
+
+    fn kind(&(): &()) -> Cow<'_, str> {
+        "PodMetrics".into()
+    }
+
+    fn group(&(): &()) -> Cow<'_, str> {
+        GROUP.into()
+    }
+
+    fn version(&(): &()) -> Cow<'_, str> {
+        VERSION.into()
+    }
+
+    fn plural(&(): &()) -> Cow<'_, str> {
+        "pods".into()
+    }
+
+    fn meta(&self) -> &ObjectMeta {
+        &self.metadata
+    }
+
+    fn meta_mut(&mut self) -> &mut ObjectMeta {
+        &mut self.metadata
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{NodeMetrics, PodMetrics};
+    use crate::{ObjectList, Resource};
+    use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
+
+    #[test]
+    fn metrics_urls() {
+        assert_eq!(
+            NodeMetrics::url_path(&(), None),
+            "/apis/metrics.k8s.io/v1beta1/nodes"
+        );
+        assert_eq!(
+            PodMetrics::url_path(&(), Some("kube-system")),
+            "/apis/metrics.k8s.io/v1beta1/namespaces/kube-system/pods"
+        );
+    }
+
+    #[test]
+    fn pod_metrics_deserialize() {
+        let list: ObjectList<PodMetrics> = serde_json::from_value(serde_json::json!({
+            "kind": "PodMetricsList",
+            "apiVersion": "metrics.k8s.io/v1beta1",
+            "metadata": {},
+            "items": [{
+                "metadata": { "name": "coredns-5d78c9869d-vx7ns", "namespace": "kube-system" },
+                "timestamp": "2025-03-06T06:06:57Z",
+                "window": "20.044s",
+                "containers": [{ "name": "coredns", "usage": { "cpu": "1843649n", "memory": "14348Ki" } }]
+            }]
+        }))
+        .unwrap();
+
+        let pod = &list.items[0];
+        assert_eq!(pod.window, std::time::Duration::from_millis(20_044));
+        let coredns = pod.container("coredns").unwrap();
+        assert_eq!(coredns.cpu(), Some(&Quantity("1843649n".into())));
+        assert_eq!(coredns.memory(), Some(&Quantity("14348Ki".into())));
+        assert!(pod.container("sidecar").is_none());
+    }
+}
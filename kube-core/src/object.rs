@@ -2,6 +2,7 @@
 use crate::{
     discovery::ApiResource,
     metadata::{ListMeta, ObjectMeta, TypeMeta},
+    params::Cursor,
     resource::{DynamicResourceScope, Resource},
 };
 use serde::{Deserialize, Deserializer, Serialize};
@@ -103,6 +104,15 @@ impl<T: Clone> ObjectList<T> {
     pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
         self.items.iter_mut()
     }
+
+    /// The [`Cursor`] to fetch the next page with, if there are more items
+    pub fn cursor(&self) -> Option<Cursor> {
+        self.metadata
+            .continue_
+            .as_deref()
+            .filter(|token| !token.is_empty())
+            .map(Cursor::new)
+    }
 }
 
 impl<T: Clone> IntoIterator for ObjectList<T> {
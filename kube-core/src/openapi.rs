@@ -0,0 +1,191 @@
+//! OpenAPI documents served by the apiserver, for introspecting the schemas of resources
+//!
+//! An [`OpenApiDocument`] is either one of the per group version OpenAPI v3 documents under
+//! `/openapi/v3`, or the single OpenAPI v2 document under `/openapi/v2`. Both hold the schemas
+//! of the resources they describe, which can be navigated like `kubectl explain`:
+//!
+//! ```
+//! use kube_core::{openapi::OpenApiDocument, GroupVersionKind};
+//!
+//! let doc: OpenApiDocument = serde_json::from_value(serde_json::json!({
+//!     "components": { "schemas": {
+//!         "io.k8s.api.apps.v1.Deployment": {
+//!             "x-kubernetes-group-version-kind": [{ "group": "apps", "version": "v1", "kind": "Deployment" }],
+//!             "properties": { "spec": { "allOf": [{ "$ref": "#/components/schemas/io.k8s.api.apps.v1.DeploymentSpec" }] } }
+//!         },
+//!         "io.k8s.api.apps.v1.DeploymentSpec": {
+//!             "properties": { "replicas": { "type": "integer", "description": "Number of desired pods." } }
+//!         }
+//!     } }
+//! }))?;
+//!
+//! let gvk = GroupVersionKind::gvk("apps", "v1", "Deployment");
+//! let replicas = doc.field_schema(&gvk, "spec.replicas").unwrap();
+//! assert_eq!(replicas["type"], "integer");
+//! # Ok::<(), serde_json::Error>(())
+//! ```
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::gvk::GroupVersionKind;
+
+/// How many `$ref`s are followed before a schema is considered to be cyclic
+const MAX_REF_DEPTH: usize = 32;
+
+/// A parsed OpenAPI v3 or v2 document
+///
+/// The document is kept as JSON, since the schemas are only navigated and never fully typed.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(transparent)]
+pub struct OpenApiDocument(pub Value);
+
+impl OpenApiDocument {
+    /// All named schemas, `components.schemas` for v3 and `definitions` for v2
+    pub fn schemas(&self) -> Option<&Map<String, Value>> {
+        self.0
+            .pointer("/components/schemas")
+            .or_else(|| self.0.get("definitions"))
+            .and_then(Value::as_object)
+    }
+
+    /// The schema of a resource, found through its `x-kubernetes-group-version-kind` extension
+    pub fn schema_for(&self, gvk: &GroupVersionKind) -> Option<&Value> {
+        self.schemas()?.values().find(|schema| {
+            let gvks = schema
+                .get("x-kubernetes-group-version-kind")
+                .and_then(Value::as_array);
+            gvks.into_iter().flatten().any(|candidate| {
+                candidate["group"] == gvk.group.as_str()
+                    && candidate["version"] == gvk.version.as_str()
+                    && candidate["kind"] == gvk.kind.as_str()
+            })
+        })
+    }
+
+    /// Follow `$ref`s, and `allOf`s wrapping a single schema, to the schema they point to
+    ///
+    /// Unknown references and cycles resolve to the last schema that could be found.
+    pub fn resolve<'a>(&'a self, mut schema: &'a Value) -> &'a Value {
+        for _ in 0..MAX_REF_DEPTH {
+            let next = if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+                let name = reference
+                    .strip_prefix("#/components/schemas/")
+                    .or_else(|| reference.strip_prefix("#/definitions/"));
+                name.and_then(|name| self.schemas()?.get(name))
+            } else {
+                match schema.get("allOf").and_then(Value::as_array).map(Vec::as_slice) {
+                    Some([single]) => Some(single),
+                    _ => None,
+                }
+            };
+            match next {
+                Some(next) => schema = next,
+                None => break,
+            }
+        }
+        schema
+    }
+
+    /// The resolved schema of a field of a resource, like `kubectl explain`
+    ///
+    /// `path` is a dot separated list of field names, which steps into the items of arrays and
+    /// the values of maps, so `spec.template.spec.containers.image` is the image of any container.
+    /// An empty `path` returns the schema of the resource itself. The descriptions of the fields
+    /// are kept in the schemas of their parents, see [`OpenApiDocument::field_description`].
+    pub fn field_schema(&self, gvk: &GroupVersionKind, path: &str) -> Option<&Value> {
+        let mut schema = self.resolve(self.schema_for(gvk)?);
+        for field in path.split('.').filter(|f| !f.is_empty()) {
+            schema = self.resolve(self.field(schema, field)?);
+        }
+        Some(schema)
+    }
+
+    /// The description of a field of a resource, see [`OpenApiDocument::field_schema`]
+    ///
+    /// Falls back to the description of the schema of the field.
+    pub fn field_description(&self, gvk: &GroupVersionKind, path: &str) -> Option<&str> {
+        let (parent, field) = match path.rsplit_once('.') {
+            Some((parent, field)) => (self.field_schema(gvk, parent)?, field),
+            None => (self.field_schema(gvk, "")?, path),
+        };
+        let field = self.field(parent, field)?;
+        field
+            .get("description")
+            .or_else(|| self.resolve(field).get("description"))
+            .and_then(Value::as_str)
+    }
+
+    /// The unresolved schema of `field` in `schema`, stepping through arrays and maps
+    fn field<'a>(&'a self, mut schema: &'a Value, field: &str) -> Option<&'a Value> {
+        for _ in 0..MAX_REF_DEPTH {
+            if let Some(found) = schema.get("properties").and_then(|p| p.get(field)) {
+                return Some(found);
+            }
+            let inner = schema
+                .get("items")
+                .or_else(|| schema.get("additionalProperties"))?;
+            schema = self.resolve(inner);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::OpenApiDocument;
+    use crate::GroupVersionKind;
+
+    fn swagger() -> OpenApiDocument {
+        serde_json::from_value(serde_json::json!({
+            "swagger": "2.0",
+            "definitions": {
+                "io.k8s.api.core.v1.Pod": {
+                    "x-kubernetes-group-version-kind": [{ "group": "", "version": "v1", "kind": "Pod" }],
+                    "properties": {
+                        "spec": { "$ref": "#/definitions/io.k8s.api.core.v1.PodSpec", "description": "Pod spec." }
+                    }
+                },
+                "io.k8s.api.core.v1.PodSpec": {
+                    "description": "PodSpec is a description of a pod.",
+                    "properties": {
+                        "containers": { "type": "array", "items": { "$ref": "#/definitions/io.k8s.api.core.v1.Container" } },
+                        "nodeSelector": { "type": "object", "additionalProperties": { "type": "string" } }
+                    }
+                },
+                "io.k8s.api.core.v1.Container": {
+                    "properties": { "image": { "type": "string", "description": "Container image name." } }
+                },
+                "io.k8s.Cyclic": { "$ref": "#/definitions/io.k8s.Cyclic" }
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn fields_are_found_through_refs_and_arrays() {
+        let doc = swagger();
+        let pod = GroupVersionKind::gvk("", "v1", "Pod");
+        assert_eq!(
+            doc.field_schema(&pod, "spec.containers.image").unwrap()["type"],
+            "string"
+        );
+        assert_eq!(
+            doc.field_description(&pod, "spec.containers.image"),
+            Some("Container image name.")
+        );
+        // the description next to the reference wins over the one of the referenced schema
+        assert_eq!(doc.field_description(&pod, "spec"), Some("Pod spec."));
+        assert!(doc.field_schema(&pod, "spec.nodeSelector").is_some());
+        assert!(doc.field_schema(&pod, "spec.missing").is_none());
+        assert!(doc
+            .field_schema(&GroupVersionKind::gvk("apps", "v1", "Pod"), "")
+            .is_none());
+    }
+
+    #[test]
+    fn cyclic_refs_terminate() {
+        let doc = swagger();
+        let cyclic = &doc.schemas().unwrap()["io.k8s.Cyclic"];
+        assert!(doc.resolve(cyclic).get("$ref").is_some());
+    }
+}
@@ -1,6 +1,7 @@
 //! A port of request parameter *Optionals from apimachinery/types.go
-use crate::{request::Error, Selector};
-use serde::Serialize;
+#[cfg(feature = "request")] use crate::request::Error;
+use crate::Selector;
+use serde::{Deserialize, Serialize};
 
 /// Controls how the resource version parameter is applied for list calls
 ///
@@ -35,6 +36,36 @@ pub enum VersionMatch {
     Exact,
 }
 
+/// An opaque position in a paginated list
+///
+/// Wraps the `continue` token returned in the metadata of a list response limited with [`ListParams::limit`].
+/// Get it from [`ObjectList::cursor`](crate::ObjectList::cursor) and pass it to [`ListParams::cursor`]
+/// to fetch the next page. Cursors serialize as plain strings, so they can be handed to e.g. web frontends.
+///
+/// Cursors expire after a few minutes (when the apiserver compacts the snapshot they refer to),
+/// after which listing with them fails with a `410 Gone`. The list has to be restarted without a cursor then.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Cursor(String);
+
+impl Cursor {
+    /// Wrap a raw `continue` token
+    pub fn new(token: impl Into<String>) -> Self {
+        Self(token.into())
+    }
+
+    /// The raw `continue` token
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for Cursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
 /// Common query parameters used in list/delete calls on collections
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct ListParams {
@@ -75,6 +106,7 @@ pub struct ListParams {
 }
 
 impl ListParams {
+    #[cfg(feature = "request")]
     pub(crate) fn validate(&self) -> Result<(), Error> {
         if let Some(rv) = &self.resource_version {
             if self.version_match == Some(VersionMatch::Exact) && rv == "0" {
@@ -90,6 +122,7 @@ impl ListParams {
         Ok(())
     }
 
+    #[cfg(feature = "request")]
     // Partially populate query parameters (needs resourceVersion out of band)
     pub(crate) fn populate_qp(&self, qp: &mut form_urlencoded::Serializer<String>) {
         if let Some(fields) = &self.field_selector {
@@ -205,6 +238,12 @@ impl ListParams {
         self
     }
 
+    /// Continue listing from a [`Cursor`] returned with a previous page.
+    #[must_use]
+    pub fn cursor(self, cursor: &Cursor) -> Self {
+        self.continue_token(cursor.as_str())
+    }
+
     /// Sets the resource version
     #[must_use]
     pub fn at(mut self, resource_version: &str) -> Self {
@@ -271,8 +310,10 @@ impl GetParams {
     }
 }
 
-/// The validation directive to use for `fieldValidation` when using server-side apply.
-#[derive(Clone, Debug)]
+/// The validation directive to use for `fieldValidation` on create, replace and patch calls.
+///
+/// Requires kubernetes >= 1.25 for server-side field validation.
+#[derive(Clone, Debug, PartialEq)]
 pub enum ValidationDirective {
     /// Strict mode will fail any invalid manifests.
     ///
@@ -360,6 +401,7 @@ pub struct WatchParams {
 }
 
 impl WatchParams {
+    #[cfg(feature = "request")]
     pub(crate) fn validate(&self) -> Result<(), Error> {
         if let Some(to) = &self.timeout {
             // https://github.com/kubernetes/kubernetes/issues/6513
@@ -375,6 +417,7 @@ impl WatchParams {
         Ok(())
     }
 
+    #[cfg(feature = "request")]
     // Partially populate query parameters (needs resourceVersion out of band)
     pub(crate) fn populate_qp(&self, qp: &mut form_urlencoded::Serializer<String>) {
         qp.append_pair("watch", "true");
@@ -539,9 +582,12 @@ pub struct PostParams {
     pub dry_run: bool,
     /// fieldManager is a name of the actor that is making changes
     pub field_manager: Option<String>,
+    /// The server-side validation directive to use for unknown or duplicate fields.
+    pub field_validation: Option<ValidationDirective>,
 }
 
 impl PostParams {
+    #[cfg(feature = "request")]
     pub(crate) fn populate_qp(&self, qp: &mut form_urlencoded::Serializer<String>) {
         if self.dry_run {
             qp.append_pair("dryRun", "All");
@@ -549,8 +595,12 @@ impl PostParams {
         if let Some(ref fm) = self.field_manager {
             qp.append_pair("fieldManager", fm);
         }
+        if let Some(sv) = &self.field_validation {
+            qp.append_pair("fieldValidation", sv.as_str());
+        }
     }
 
+    #[cfg(feature = "request")]
     pub(crate) fn validate(&self) -> Result<(), Error> {
         if let Some(field_manager) = &self.field_manager {
             // Implement the easy part of validation, in future this may be extended to provide validation as in go code
@@ -565,6 +615,54 @@ impl PostParams {
     }
 }
 
+/// Builder interface to PostParams
+///
+/// Usage:
+/// ```
+/// use kube::api::PostParams;
+/// let pp = PostParams::default().dry_run().validation_strict();
+/// ```
+impl PostParams {
+    /// Perform a dryRun only
+    #[must_use]
+    pub fn dry_run(mut self) -> Self {
+        self.dry_run = true;
+        self
+    }
+
+    /// Set the name of the field manager making the changes
+    #[must_use]
+    pub fn field_manager(mut self, manager: &str) -> Self {
+        self.field_manager = Some(manager.into());
+        self
+    }
+
+    /// Set the validation directive for `fieldValidation`.
+    #[must_use]
+    pub fn validation(mut self, vd: ValidationDirective) -> Self {
+        self.field_validation = Some(vd);
+        self
+    }
+
+    /// Set the validation directive to `Ignore`
+    #[must_use]
+    pub fn validation_ignore(self) -> Self {
+        self.validation(ValidationDirective::Ignore)
+    }
+
+    /// Set the validation directive to `Warn`
+    #[must_use]
+    pub fn validation_warn(self) -> Self {
+        self.validation(ValidationDirective::Warn)
+    }
+
+    /// Set the validation directive to `Strict`
+    #[must_use]
+    pub fn validation_strict(self) -> Self {
+        self.validation(ValidationDirective::Strict)
+    }
+}
+
 /// Describes changes that should be applied to a resource
 ///
 /// Takes arbitrary serializable data for all strategies except `Json`.
@@ -632,10 +730,12 @@ pub enum Patch<T: Serialize> {
 }
 
 impl<T: Serialize> Patch<T> {
+    #[cfg(feature = "request")]
     pub(crate) fn is_apply(&self) -> bool {
         matches!(self, Patch::Apply(_))
     }
 
+    #[cfg(feature = "request")]
     pub(crate) fn content_type(&self) -> &'static str {
         match &self {
             Self::Apply(_) => "application/apply-patch+yaml",
@@ -649,6 +749,7 @@ impl<T: Serialize> Patch<T> {
 }
 
 impl<T: Serialize> Patch<T> {
+    #[cfg(feature = "request")]
     pub(crate) fn serialize(&self) -> Result<Vec<u8>, serde_json::Error> {
         match self {
             Self::Apply(p) => serde_json::to_vec(p),
@@ -671,11 +772,12 @@ pub struct PatchParams {
     /// fieldManager is a name of the actor that is making changes. Required for [`Patch::Apply`]
     /// optional for everything else.
     pub field_manager: Option<String>,
-    /// The server-side validation directive to use. Applicable only to [`Patch::Apply`].
+    /// The server-side validation directive to use for unknown or duplicate fields.
     pub field_validation: Option<ValidationDirective>,
 }
 
 impl PatchParams {
+    #[cfg(feature = "request")]
     pub(crate) fn validate<P: Serialize>(&self, patch: &Patch<P>) -> Result<(), Error> {
         if let Some(field_manager) = &self.field_manager {
             // Implement the easy part of validation, in future this may be extended to provide validation as in go code
@@ -694,6 +796,7 @@ impl PatchParams {
         Ok(())
     }
 
+    #[cfg(feature = "request")]
     pub(crate) fn populate_qp(&self, qp: &mut form_urlencoded::Serializer<String>) {
         if self.dry_run {
             qp.append_pair("dryRun", "All");
@@ -734,7 +837,8 @@ impl PatchParams {
         self
     }
 
-    /// Set the validation directive for `fieldValidation` during server-side apply.
+    /// Set the validation directive for `fieldValidation`.
+    #[must_use]
     pub fn validation(mut self, vd: ValidationDirective) -> Self {
         self.field_validation = Some(vd);
         self
@@ -845,6 +949,7 @@ impl DeleteParams {
         self
     }
 
+    #[cfg(feature = "request")]
     pub(crate) fn is_default(&self) -> bool {
         !self.dry_run
             && self.grace_period_seconds.is_none()
@@ -878,7 +983,18 @@ where
 mod test {
     use crate::{params::WatchParams, Expression, Selector};
 
-    use super::{DeleteParams, ListParams, PatchParams};
+    use super::{Cursor, DeleteParams, ListParams, PatchParams, PostParams};
+
+    #[test]
+    fn cursor_sets_continue_token() {
+        let cursor: Cursor = serde_json::from_str("\"eyJ2IjoibWV0YS5rOHMuaW8vdjEifQ\"").unwrap();
+        let lp = ListParams::default().limit(10).cursor(&cursor);
+        assert_eq!(
+            lp.continue_token.as_deref(),
+            Some("eyJ2IjoibWV0YS5rOHMuaW8vdjEifQ")
+        );
+        assert_eq!(serde_json::to_value(&cursor).unwrap(), cursor.as_str());
+    }
     #[test]
     fn delete_param_serialize() {
         let mut dp = DeleteParams::default();
@@ -908,6 +1024,7 @@ mod test {
     }
 
     #[test]
+    #[cfg(feature = "request")]
     fn patch_param_serializes_field_validation() {
         let pp = PatchParams::default().validation_ignore();
         let mut qp = form_urlencoded::Serializer::new(String::from("some/resource?"));
@@ -928,6 +1045,25 @@ mod test {
         assert_eq!(String::from("some/resource?&fieldValidation=Strict"), urlstr);
     }
 
+    #[test]
+    #[cfg(feature = "request")]
+    fn post_param_serializes_field_validation() {
+        let pp = PostParams::default().validation_strict();
+        let mut qp = form_urlencoded::Serializer::new(String::from("some/resource?"));
+        pp.populate_qp(&mut qp);
+        let urlstr = qp.finish();
+        assert_eq!(String::from("some/resource?&fieldValidation=Strict"), urlstr);
+
+        let pp = PostParams::default().dry_run().validation_warn();
+        let mut qp = form_urlencoded::Serializer::new(String::from("some/resource?"));
+        pp.populate_qp(&mut qp);
+        let urlstr = qp.finish();
+        assert_eq!(
+            String::from("some/resource?&dryRun=All&fieldValidation=Warn"),
+            urlstr
+        );
+    }
+
     #[test]
     fn list_params_serialize() {
         let selector: Selector =
@@ -0,0 +1,266 @@
+//! Client-side rendering of custom resources into tables, like `kubectl get`
+//!
+//! The [`Printer`] evaluates the `additionalPrinterColumns` of a [`CustomResourceDefinition`]
+//! against [`DynamicObject`]s and returns the same [`Table`] the API server would, without
+//! a round trip through the server side Table API. This is useful for TUIs and CLIs that already
+//! have the objects, e.g. from a reflector.
+//!
+//! ```
+//! use kube::core::{printer::Printer, DynamicObject};
+//! # use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition;
+//! # fn wrapper(crd: CustomResourceDefinition, objects: Vec<DynamicObject>) {
+//! let printer = Printer::for_crd(&crd, "v1").expect("served version");
+//! print!("{}", printer.table(&objects).render(false));
+//! # }
+//! ```
+use chrono::{DateTime, Utc};
+use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::{
+    CustomResourceColumnDefinition, CustomResourceDefinition,
+};
+use serde_json::Value;
+
+use crate::{
+    jsonpath::JsonPath,
+    table::{Table, TableColumnDefinition, TableRow},
+    DynamicObject,
+};
+
+/// Renders [`DynamicObject`]s into [`Table`]s using printer columns
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Printer {
+    columns: Vec<CustomResourceColumnDefinition>,
+}
+
+impl Printer {
+    /// Create a printer for the given printer columns
+    ///
+    /// Like the API server, a printer without columns shows an `Age` column.
+    pub fn new(columns: Vec<CustomResourceColumnDefinition>) -> Self {
+        Self { columns }
+    }
+
+    /// Create a printer for the printer columns of a version of a custom resource
+    ///
+    /// Returns `None` if the definition does not contain the version.
+    pub fn for_crd(crd: &CustomResourceDefinition, version: &str) -> Option<Self> {
+        let version = crd.spec.versions.iter().find(|v| v.name == version)?;
+        Some(Self::new(
+            version.additional_printer_columns.clone().unwrap_or_default(),
+        ))
+    }
+
+    /// Render `objects` into a table with a `Name` column followed by the printer columns
+    ///
+    /// Columns of type `date` show the age of the timestamp, like `kubectl`. Cells are `null`
+    /// when the JSONPath of the column does not match anything.
+    pub fn table<'a>(&self, objects: impl IntoIterator<Item = &'a DynamicObject>) -> Table {
+        self.table_at(objects, Utc::now())
+    }
+
+    fn table_at<'a>(
+        &self,
+        objects: impl IntoIterator<Item = &'a DynamicObject>,
+        now: DateTime<Utc>,
+    ) -> Table {
+        let age = CustomResourceColumnDefinition {
+            name: "Age".into(),
+            type_: "date".into(),
+            json_path: ".metadata.creationTimestamp".into(),
+            ..CustomResourceColumnDefinition::default()
+        };
+        let columns = if self.columns.is_empty() {
+            std::slice::from_ref(&age)
+        } else {
+            self.columns.as_slice()
+        };
+
+        let mut column_definitions = vec![TableColumnDefinition {
+            name: "Name".into(),
+            type_: "string".into(),
+            format: "name".into(),
+            description: "Name must be unique within a namespace.".into(),
+            priority: 0,
+        }];
+        column_definitions.extend(columns.iter().map(|c| TableColumnDefinition {
+            name: c.name.clone(),
+            type_: c.type_.clone(),
+            format: c.format.clone().unwrap_or_default(),
+            description: c.description.clone().unwrap_or_default(),
+            priority: c.priority.unwrap_or_default(),
+        }));
+
+        // columns with invalid paths are left empty, like the API server does
+        let paths: Vec<Option<JsonPath>> = columns.iter().map(|c| c.json_path.parse().ok()).collect();
+        let rows = objects
+            .into_iter()
+            .map(|obj| {
+                let value = serde_json::to_value(obj).unwrap_or_default();
+                let name = obj.metadata.name.clone().unwrap_or_default();
+                let mut cells = vec![Value::String(name)];
+                for (column, path) in columns.iter().zip(&paths) {
+                    let matches = path.as_ref().map(|p| p.find(&value)).unwrap_or_default();
+                    cells.push(cell(column, &matches, now));
+                }
+                TableRow {
+                    cells,
+                    ..TableRow::default()
+                }
+            })
+            .collect();
+
+        Table {
+            column_definitions,
+            rows,
+            ..Table::default()
+        }
+    }
+}
+
+fn cell(column: &CustomResourceColumnDefinition, matches: &[&Value], now: DateTime<Utc>) -> Value {
+    let value = match matches {
+        [] => return Value::Null,
+        [value] => *value,
+        // like kubectl custom columns, several matches are joined
+        values => {
+            let joined: Vec<String> = values.iter().map(|v| display(v)).collect();
+            return Value::String(joined.join(","));
+        }
+    };
+    match (column.type_.as_str(), value) {
+        ("date", Value::String(s)) => match DateTime::parse_from_rfc3339(s) {
+            Ok(ts) => Value::String(human_duration(now.signed_duration_since(ts))),
+            Err(_) => Value::String(s.clone()),
+        },
+        ("string", Value::String(_) | Value::Null) => value.clone(),
+        ("string", other) => Value::String(other.to_string()),
+        _ => value.clone(),
+    }
+}
+
+fn display(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Format a duration the way `kubectl` shows ages, e.g. `90s`, `5m3s`, `2d4h` or `3y`
+fn human_duration(d: chrono::TimeDelta) -> String {
+    let seconds = d.num_seconds();
+    if seconds < -1 {
+        return "<invalid>".into();
+    } else if seconds < 0 {
+        return "0s".into();
+    } else if seconds < 60 * 2 {
+        return format!("{seconds}s");
+    }
+    let minutes = d.num_minutes();
+    if minutes < 10 {
+        return match seconds % 60 {
+            0 => format!("{minutes}m"),
+            s => format!("{minutes}m{s}s"),
+        };
+    } else if minutes < 60 * 3 {
+        return format!("{minutes}m");
+    }
+    let hours = d.num_hours();
+    if hours < 8 {
+        match minutes % 60 {
+            0 => format!("{hours}h"),
+            m => format!("{hours}h{m}m"),
+        }
+    } else if hours < 48 {
+        format!("{hours}h")
+    } else if hours < 24 * 8 {
+        match hours % 24 {
+            0 => format!("{}d", hours / 24),
+            h => format!("{}d{h}h", hours / 24),
+        }
+    } else if hours < 24 * 365 * 2 {
+        format!("{}d", hours / 24)
+    } else if hours < 24 * 365 * 8 {
+        match (hours / 24) % 365 {
+            0 => format!("{}y", hours / 24 / 365),
+            d => format!("{}y{d}d", hours / 24 / 365),
+        }
+    } else {
+        format!("{}y", hours / 24 / 365)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn renders_printer_columns() {
+        let crd: CustomResourceDefinition = serde_yaml::from_str(
+            r#"
+            apiVersion: apiextensions.k8s.io/v1
+            kind: CustomResourceDefinition
+            metadata:
+              name: gadgets.example.com
+            spec:
+              group: example.com
+              names: { kind: Gadget, plural: gadgets }
+              scope: Namespaced
+              versions:
+              - name: v1
+                served: true
+                storage: true
+                additionalPrinterColumns:
+                - { name: Replicas, type: integer, jsonPath: .spec.replicas }
+                - { name: Phase, type: string, jsonPath: .status.phase }
+                - { name: Image, type: string, jsonPath: ".spec.containers[0].image", priority: 1 }
+                - { name: Age, type: date, jsonPath: .metadata.creationTimestamp }
+            "#,
+        )
+        .unwrap();
+        let obj: DynamicObject = serde_json::from_value(serde_json::json!({
+            "apiVersion": "example.com/v1",
+            "kind": "Gadget",
+            "metadata": { "name": "blog", "creationTimestamp": "2024-01-01T00:00:00Z" },
+            "spec": { "replicas": 3, "containers": [{ "image": "nginx" }] }
+        }))
+        .unwrap();
+        let now = "2024-01-01T00:05:03Z".parse().unwrap();
+
+        let printer = Printer::for_crd(&crd, "v1").unwrap();
+        let table = printer.table_at([&obj], now);
+        let names: Vec<_> = table.column_definitions.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["Name", "Replicas", "Phase", "Image", "Age"]);
+        assert_eq!(table.column_definitions[3].priority, 1);
+        assert_eq!(
+            serde_json::json!(table.rows[0].cells),
+            serde_json::json!(["blog", 3, null, "nginx", "5m3s"])
+        );
+        assert_eq!(
+            table.render(false),
+            "NAME   REPLICAS   PHASE    AGE\nblog   3          <none>   5m3s\n"
+        );
+        assert!(Printer::for_crd(&crd, "v2").is_none());
+
+        // without printer columns, the age is shown
+        let table = Printer::default().table_at([&obj], now);
+        assert_eq!(table.column_definitions[1].name, "Age");
+        assert_eq!(table.rows[0].cells[1], "5m3s");
+    }
+
+    #[test]
+    fn formats_ages_like_kubectl() {
+        let cases = [
+            (-5, "<invalid>"),
+            (90, "90s"),
+            (5 * 60, "5m"),
+            (65 * 60, "65m"),
+            (4 * 3600 + 60, "4h1m"),
+            (30 * 3600, "30h"),
+            (50 * 3600, "2d2h"),
+            (40 * 86400, "40d"),
+            (3 * 365 * 86400 + 86400, "3y1d"),
+        ];
+        for (seconds, age) in cases {
+            assert_eq!(human_duration(chrono::TimeDelta::seconds(seconds)), age);
+        }
+    }
+}
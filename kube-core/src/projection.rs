@@ -0,0 +1,162 @@
+//! Client-side projection of objects onto a subset of their fields
+use std::collections::BTreeMap;
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::WatchEvent;
+
+/// A whitelist of fields to keep in objects
+///
+/// Fields are dotted paths into the object, e.g. `spec.nodeName`. Selecting a field keeps
+/// everything below it, and paths through arrays apply to every element, so
+/// `spec.containers.image` keeps the image of every container.
+///
+/// The type fields and the identifying metadata (`name`, `namespace`, `uid` and
+/// `resourceVersion`) are always kept, so projected objects still work with watchers and caches.
+///
+/// ```
+/// use kube::core::projection::Projection;
+/// use serde_json::json;
+///
+/// let projection = Projection::new(&["spec.nodeName", "status.phase"]);
+/// let mut pod = json!({
+///     "apiVersion": "v1",
+///     "kind": "Pod",
+///     "metadata": { "name": "web", "labels": { "app": "web" } },
+///     "spec": { "nodeName": "node-1", "containers": [{ "name": "app", "image": "nginx" }] },
+///     "status": { "phase": "Running", "podIP": "10.0.0.1" },
+/// });
+/// projection.apply(&mut pod);
+/// assert_eq!(pod, json!({
+///     "apiVersion": "v1",
+///     "kind": "Pod",
+///     "metadata": { "name": "web" },
+///     "spec": { "nodeName": "node-1" },
+///     "status": { "phase": "Running" },
+/// }));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Projection {
+    fields: Fields,
+}
+
+const ALWAYS_KEPT: [&str; 6] = [
+    "apiVersion",
+    "kind",
+    "metadata.name",
+    "metadata.namespace",
+    "metadata.uid",
+    "metadata.resourceVersion",
+];
+
+/// Selected children of a field, an empty map keeps all children
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct Fields(BTreeMap<String, Fields>);
+
+impl Projection {
+    /// Create a projection keeping the given fields
+    pub fn new(fields: &[&str]) -> Self {
+        let mut projection = Self::default();
+        for field in ALWAYS_KEPT.iter().chain(fields) {
+            let path: Vec<&str> = field.split('.').filter(|s| !s.is_empty()).collect();
+            projection.fields.insert(&path);
+        }
+        projection
+    }
+
+    /// Remove every field that is not selected from `value`
+    pub fn apply(&self, value: &mut Value) {
+        self.fields.prune(value);
+    }
+
+    /// Project `value` and deserialize the remaining fields
+    ///
+    /// # Errors
+    ///
+    /// Fails when the projected object does not deserialize into `K`, e.g. when required fields
+    /// of `K` are not selected.
+    pub fn project<K: DeserializeOwned>(&self, mut value: Value) -> Result<K, serde_json::Error> {
+        self.apply(&mut value);
+        serde_json::from_value(value)
+    }
+
+    /// Project the object of a watch event
+    ///
+    /// # Errors
+    ///
+    /// Fails like [`Projection::project`].
+    pub fn project_event<K: DeserializeOwned>(
+        &self,
+        event: WatchEvent<Value>,
+    ) -> Result<WatchEvent<K>, serde_json::Error> {
+        Ok(match event {
+            WatchEvent::Added(obj) => WatchEvent::Added(self.project(obj)?),
+            WatchEvent::Modified(obj) => WatchEvent::Modified(self.project(obj)?),
+            WatchEvent::Deleted(obj) => WatchEvent::Deleted(self.project(obj)?),
+            WatchEvent::Bookmark(bookmark) => WatchEvent::Bookmark(bookmark),
+            WatchEvent::Error(err) => WatchEvent::Error(err),
+        })
+    }
+}
+
+impl Fields {
+    fn insert(&mut self, path: &[&str]) {
+        let Some((first, rest)) = path.split_first() else {
+            return;
+        };
+        match self.0.get_mut(*first) {
+            // everything below is already selected
+            Some(child) if child.0.is_empty() => {}
+            Some(child) if rest.is_empty() => child.0.clear(),
+            Some(child) => child.insert(rest),
+            None => {
+                let mut child = Fields::default();
+                child.insert(rest);
+                self.0.insert((*first).to_string(), child);
+            }
+        }
+    }
+
+    fn prune(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => map.retain(|key, child| match self.0.get(key) {
+                Some(fields) if fields.0.is_empty() => true,
+                Some(fields) => {
+                    fields.prune(child);
+                    true
+                }
+                None => false,
+            }),
+            Value::Array(items) => items.iter_mut().for_each(|item| self.prune(item)),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Projection;
+    use serde_json::json;
+
+    #[test]
+    fn projects_through_arrays_and_merges_paths() {
+        let projection = Projection::new(&["spec.containers.image", "spec", "status.conditions.type"]);
+        let mut pod = json!({
+            "kind": "Pod",
+            "metadata": { "name": "web", "resourceVersion": "12", "annotations": { "a": "b" } },
+            "spec": { "containers": [{ "name": "app", "image": "nginx" }] },
+            "status": { "conditions": [{ "type": "Ready", "status": "True" }], "phase": "Running" },
+        });
+        projection.apply(&mut pod);
+        assert_eq!(
+            pod,
+            json!({
+                "kind": "Pod",
+                "metadata": { "name": "web", "resourceVersion": "12" },
+                "spec": { "containers": [{ "name": "app", "image": "nginx" }] },
+                "status": { "conditions": [{ "type": "Ready" }] },
+            })
+        );
+    }
+}
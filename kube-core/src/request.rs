@@ -18,6 +18,12 @@ pub(crate) const JSON_METADATA_MIME: &str = "application/json;as=PartialObjectMe
 pub(crate) const JSON_METADATA_LIST_MIME: &str =
     "application/json;as=PartialObjectMetadataList;g=meta.k8s.io;v=v1";
 
+/// Extended Accept Header
+///
+/// Requests a meta.k8s.io/v1 Table rendering of a resource, falling back to
+/// plain JSON on servers that cannot render tables.
+pub(crate) const JSON_TABLE_MIME: &str = "application/json;as=Table;v=v1;g=meta.k8s.io,application/json";
+
 /// Possible errors when building a request.
 #[derive(Debug, Error)]
 pub enum Error {
@@ -334,6 +340,43 @@ impl Request {
     }
 }
 
+/// Table request implementations
+///
+/// Requests set an extended Accept header asking the API server to render the
+/// result as a [`Table`](crate::table::Table), i.e. the columns shown by `kubectl get`.
+impl Request {
+    /// Get a single named resource rendered as a table
+    pub fn get_table(&self, name: &str, gp: &GetParams) -> Result<http::Request<Vec<u8>>, Error> {
+        validate_name(name)?;
+        let urlstr = if let Some(rv) = &gp.resource_version {
+            let target = format!("{}/{}?", self.url_path, name);
+            form_urlencoded::Serializer::new(target)
+                .append_pair("resourceVersion", rv)
+                .finish()
+        } else {
+            let target = format!("{}/{}", self.url_path, name);
+            form_urlencoded::Serializer::new(target).finish()
+        };
+        let req = http::Request::get(urlstr)
+            .header(http::header::ACCEPT, JSON_TABLE_MIME)
+            .header(http::header::CONTENT_TYPE, JSON_MIME);
+        req.body(vec![]).map_err(Error::BuildRequest)
+    }
+
+    /// List a collection of a resource rendered as a table
+    pub fn list_table(&self, lp: &ListParams) -> Result<http::Request<Vec<u8>>, Error> {
+        let target = format!("{}?", self.url_path);
+        let mut qp = form_urlencoded::Serializer::new(target);
+        lp.validate()?;
+        lp.populate_qp(&mut qp);
+        let urlstr = qp.finish();
+        let req = http::Request::get(urlstr)
+            .header(http::header::ACCEPT, JSON_TABLE_MIME)
+            .header(http::header::CONTENT_TYPE, JSON_MIME);
+        req.body(vec![]).map_err(Error::BuildRequest)
+    }
+}
+
 /// Names must not be empty as otherwise API server would interpret a `get` as `list`, or a `delete` as `delete_collection`
 fn validate_name(name: &str) -> Result<(), Error> {
     if name.is_empty() {
@@ -538,6 +581,23 @@ mod test {
         );
     }
     #[test]
+    fn list_table_path() {
+        let url = corev1::Pod::url_path(&(), Some("ns"));
+        let lp = ListParams::default().labels("app=web");
+        let req = Request::new(url).list_table(&lp).unwrap();
+        assert_eq!(req.uri(), "/api/v1/namespaces/ns/pods?&labelSelector=app%3Dweb");
+        assert_eq!(req.headers().get(header::ACCEPT).unwrap(), super::JSON_TABLE_MIME);
+    }
+    #[test]
+    fn get_table_path() {
+        let url = corev1::Pod::url_path(&(), Some("ns"));
+        let req = Request::new(url)
+            .get_table("mypod", &GetParams::default())
+            .unwrap();
+        assert_eq!(req.uri(), "/api/v1/namespaces/ns/pods/mypod");
+        assert_eq!(req.headers().get(header::ACCEPT).unwrap(), super::JSON_TABLE_MIME);
+    }
+    #[test]
     fn watch_path() {
         let url = corev1::Pod::url_path(&(), Some("ns"));
         let wp = WatchParams::default();
@@ -2,8 +2,7 @@
 //!
 //! [`CustomResourceDefinition`]: `k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition`
 
-// Used in docs
-#[allow(unused_imports)] use schemars::gen::SchemaSettings;
+use schemars::gen::SchemaSettings;
 
 use schemars::{
     schema::{InstanceType, Metadata, ObjectValidation, Schema, SchemaObject, SingleOrVec},
@@ -75,6 +74,216 @@ impl Visitor for StructuralSchemaRewriter {
     }
 }
 
+/// schemars [`Visitor`] that turns descriptions generated from doc comments into plain prose
+///
+/// Descriptions are shown verbatim by `kubectl explain`, so rustdoc markup like `` `code` ``,
+/// `**emphasis**`, intra-doc links and example code blocks are noise there. Optionally, long
+/// descriptions are truncated on a word boundary.
+///
+/// This is used by `kube::derive`'s `#[derive(CustomResource)]` when configured with
+/// `#[kube(strip_markdown)]` or `#[kube(description_max_length = N)]`,
+/// but it can also be used manually with [`SchemaSettings::with_visitor`].
+#[derive(Debug, Clone, Default)]
+pub struct DescriptionRewriter {
+    /// Whether to strip markdown markup
+    pub strip_markdown: bool,
+    /// The maximum length of descriptions in characters, including the trailing `...` of truncated ones
+    pub max_length: Option<usize>,
+}
+
+impl DescriptionRewriter {
+    /// Rewrite a single description
+    pub fn rewrite(&self, description: &str) -> String {
+        let description = if self.strip_markdown {
+            strip_markdown(description)
+        } else {
+            description.to_string()
+        };
+        match self.max_length {
+            Some(max) => truncate(&description, max),
+            None => description,
+        }
+    }
+}
+
+impl Visitor for DescriptionRewriter {
+    fn visit_schema_object(&mut self, schema: &mut SchemaObject) {
+        schemars::visit::visit_schema_object(self, schema);
+        if let Some(description) = schema.metadata.as_mut().and_then(|m| m.description.as_mut()) {
+            *description = self.rewrite(description);
+        }
+    }
+}
+
+/// Reduce markdown to plain text
+///
+/// Code blocks are dropped, links are replaced by their text, and inline markup is removed.
+/// Lines of a paragraph are joined, while paragraphs stay separated by blank lines.
+fn strip_markdown(markdown: &str) -> String {
+    let mut paragraphs: Vec<String> = vec![];
+    let mut current = String::new();
+    let mut in_code_block = false;
+    for line in markdown.lines() {
+        let line = line.trim();
+        if line.starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+        if line.is_empty() {
+            if !current.is_empty() {
+                paragraphs.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if line.starts_with('#') && line.trim_start_matches('#').starts_with(' ') {
+            // headings become paragraphs of their own
+            if !current.is_empty() {
+                paragraphs.push(std::mem::take(&mut current));
+            }
+            paragraphs.push(strip_inline_markdown(line.trim_start_matches('#').trim_start()));
+            continue;
+        }
+        let (bullet, line) = match line.strip_prefix("* ").or_else(|| line.strip_prefix("- ")) {
+            Some(item) => (true, item),
+            None => (false, line),
+        };
+        if bullet && !current.is_empty() {
+            current.push('\n');
+            current.push_str("- ");
+        } else if bullet {
+            current.push_str("- ");
+        } else if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(&strip_inline_markdown(line));
+    }
+    if !current.is_empty() {
+        paragraphs.push(current);
+    }
+    paragraphs.join("\n\n")
+}
+
+fn strip_inline_markdown(line: &str) -> String {
+    let chars: Vec<char> = line.chars().collect();
+    let mut out = String::with_capacity(line.len());
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '`' => {}
+            // emphasis markers hug the emphasized text, unlike a lone `*` in prose
+            '*' if chars.get(i + 1).is_some_and(|c| !c.is_whitespace())
+                || (i > 0 && !chars[i - 1].is_whitespace()) => {}
+            '[' => {
+                if let Some(close) = chars[i..].iter().position(|&c| c == ']').map(|p| p + i) {
+                    let text: String = chars[i + 1..close].iter().collect();
+                    out.push_str(&strip_inline_markdown(&text));
+                    i = close + 1;
+                    // skip the target of `[text](url)` and `[text][ref]` links
+                    let end = match chars.get(i) {
+                        Some('(') => ')',
+                        Some('[') => ']',
+                        _ => continue,
+                    };
+                    if let Some(p) = chars[i..].iter().position(|&c| c == end) {
+                        i += p + 1;
+                    }
+                    continue;
+                }
+                out.push('[');
+            }
+            c => out.push(c),
+        }
+        i += 1;
+    }
+    out
+}
+
+/// Shorten `text` to at most `max` characters, cutting at a word boundary when possible
+fn truncate(text: &str, max: usize) -> String {
+    const ELLIPSIS: &str = "...";
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let keep = max.saturating_sub(ELLIPSIS.len());
+    let cut: String = text.chars().take(keep).collect();
+    let cut = match cut.rfind(char::is_whitespace) {
+        Some(pos) if pos > 0 => &cut[..pos],
+        _ => &cut,
+    };
+    format!(
+        "{}{ELLIPSIS}",
+        cut.trim_end_matches(|c: char| c.is_whitespace() || c == ',' || c == '.')
+    )
+}
+
+/// Override the description of a nested property of `schema`
+///
+/// The `path` is a list of property names, e.g. `["spec", "replicas"]`. Nothing is changed when
+/// the property does not exist.
+///
+/// This is used by `kube::derive`'s `#[derive(CustomResource)]` for fields with
+/// `#[kube(description = "...")]`.
+pub fn set_property_description(schema: &mut SchemaObject, path: &[&str], description: &str) {
+    let Some((first, rest)) = path.split_first() else {
+        schema.metadata().description = Some(description.to_string());
+        return;
+    };
+    let property = schema.object.as_mut().and_then(|o| o.properties.get_mut(*first));
+    if let Some(Schema::Object(property)) = property {
+        set_property_description(property, rest, description);
+    }
+}
+
+/// The `$schema` of documents produced by [`json_schema_document`]
+pub const JSON_SCHEMA_DRAFT_2020_12: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Generate a standalone JSON Schema (draft 2020-12) document for `T`
+///
+/// Unlike the schema embedded in a `CustomResourceDefinition`, this is not rewritten to a structural schema,
+/// and keeps shared definitions under `$defs`, making it suitable for IDE validation and UI form generation.
+///
+/// This is used by the `json_schema_bundle` function generated by `#[derive(CustomResource)]`.
+pub fn json_schema_document<T: schemars::JsonSchema>() -> serde_json::Value {
+    let gen = SchemaSettings::draft2019_09()
+        .with(|s| s.meta_schema = Some(JSON_SCHEMA_DRAFT_2020_12.into()))
+        .into_generator();
+    let mut doc = serde_json::to_value(gen.into_root_schema_for::<T>()).expect("schemas serialize to json");
+    if let Some(obj) = doc.as_object_mut() {
+        if let Some(defs) = obj.remove("definitions") {
+            obj.insert("$defs".into(), defs);
+        }
+    }
+    upgrade_to_draft_2020_12(&mut doc);
+    doc
+}
+
+/// Rewrite the keywords that changed meaning between draft 2019-09 and draft 2020-12
+fn upgrade_to_draft_2020_12(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(obj) => {
+            if let Some(serde_json::Value::String(r)) = obj.get_mut("$ref") {
+                if let Some(name) = r.strip_prefix("#/definitions/") {
+                    *r = format!("#/$defs/{name}");
+                }
+            }
+            // tuples are described by `prefixItems`, and `items` now means what `additionalItems` did
+            if obj.get("items").is_some_and(|i| i.is_array()) {
+                let prefix = obj.remove("items").unwrap_or_default();
+                obj.insert("prefixItems".into(), prefix);
+                if let Some(additional) = obj.remove("additionalItems") {
+                    obj.insert("items".into(), additional);
+                }
+            }
+            obj.values_mut().for_each(upgrade_to_draft_2020_12);
+        }
+        serde_json::Value::Array(arr) => arr.iter_mut().for_each(upgrade_to_draft_2020_12),
+        _ => {}
+    }
+}
+
 /// Bring all plain enum values up to the root schema,
 /// since Kubernetes doesn't allow subschemas to define enum options.
 ///
@@ -194,3 +403,32 @@ fn merge_metadata(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewrites_descriptions() {
+        let doc = "The **number** of `replicas`, see [`Scale`] and [docs](https://k8s.io).\nDefaults to 1.\n\n# Example\n```\nreplicas: 3\n```\n* first\n* second";
+        let rewriter = DescriptionRewriter {
+            strip_markdown: true,
+            max_length: None,
+        };
+        assert_eq!(
+            rewriter.rewrite(doc),
+            "The number of replicas, see Scale and docs. Defaults to 1.\n\nExample\n\n- first\n- second"
+        );
+        assert_eq!(rewriter.rewrite("2 * 3 = 6"), "2 * 3 = 6");
+
+        let rewriter = DescriptionRewriter {
+            strip_markdown: false,
+            max_length: Some(20),
+        };
+        assert_eq!(rewriter.rewrite("short"), "short");
+        assert_eq!(
+            rewriter.rewrite("The number of replicas to run"),
+            "The number of..."
+        );
+    }
+}
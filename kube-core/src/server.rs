@@ -0,0 +1,134 @@
+//! Version and health information of the apiserver
+use k8s_openapi::apimachinery::pkg::version::Info;
+use serde::{Deserialize, Serialize};
+
+/// The Kubernetes version of an apiserver, parsed from its `/version`
+///
+/// ```
+/// use k8s_openapi::apimachinery::pkg::version::Info;
+/// use kube_core::server::ServerVersion;
+///
+/// let info = Info {
+///     major: "1".into(),
+///     minor: "28+".into(),
+///     git_version: "v1.28.3-eks-4f4795d".into(),
+///     ..Info::default()
+/// };
+/// let version = ServerVersion::parse(&info).unwrap();
+/// assert!(version.at_least(1, 27));
+/// assert!(!version.at_least(1, 29));
+/// ```
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerVersion {
+    /// The major version, `1` for every Kubernetes release so far
+    pub major: u32,
+    /// The minor version, like `28`
+    pub minor: u32,
+    /// The full version, including the patch release and vendor suffixes, like `v1.28.3-eks-4f4795d`
+    pub git_version: String,
+}
+
+impl ServerVersion {
+    /// Parse the major and minor version of `info`
+    ///
+    /// Vendors often append a `+` to the minor version, which is ignored. Returns `None` if either
+    /// version does not start with a number.
+    pub fn parse(info: &Info) -> Option<Self> {
+        Some(Self {
+            major: leading_number(&info.major)?,
+            minor: leading_number(&info.minor)?,
+            git_version: info.git_version.clone(),
+        })
+    }
+
+    /// Whether this is at least version `major.minor`
+    pub fn at_least(&self, major: u32, minor: u32) -> bool {
+        (self.major, self.minor) >= (major, minor)
+    }
+}
+
+fn leading_number(version: &str) -> Option<u32> {
+    let end = version
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(version.len());
+    version[..end].parse().ok()
+}
+
+/// The outcome of a `/livez` or `/readyz` probe, with its individual checks
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct HealthReport {
+    /// Whether the probe passed
+    pub healthy: bool,
+    /// The individual checks, in the order the apiserver reported them
+    pub checks: Vec<HealthCheck>,
+}
+
+/// A single check of a [`HealthReport`], like `etcd` or `poststarthook/rbac/bootstrap-roles`
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct HealthCheck {
+    /// The name of the check
+    pub name: String,
+    /// Whether the check passed
+    pub healthy: bool,
+    /// What the apiserver reported, like `ok`, `excluded: ok`, or `failed: reason withheld`
+    pub message: String,
+}
+
+impl HealthReport {
+    /// Parse the output of a probe with the `verbose` parameter
+    ///
+    /// `healthy` is whether the probe responded with a success status, since the apiserver does
+    /// not list every check that failed, e.g. when it shuts down.
+    pub fn from_verbose(healthy: bool, body: &str) -> Self {
+        let checks = body
+            .lines()
+            .filter_map(|line| {
+                let (healthy, check) = if let Some(check) = line.strip_prefix("[+]") {
+                    (true, check)
+                } else {
+                    (false, line.strip_prefix("[-]")?)
+                };
+                let (name, message) = check.split_once(' ').unwrap_or((check, ""));
+                Some(HealthCheck {
+                    name: name.to_string(),
+                    healthy,
+                    message: message.to_string(),
+                })
+            })
+            .collect();
+        Self { healthy, checks }
+    }
+
+    /// The checks that failed
+    pub fn failed(&self) -> impl Iterator<Item = &HealthCheck> {
+        self.checks.iter().filter(|c| !c.healthy)
+    }
+
+    /// The check with the given name
+    pub fn check(&self, name: &str) -> Option<&HealthCheck> {
+        self.checks.iter().find(|c| c.name == name)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::HealthReport;
+
+    #[test]
+    fn verbose_probes_are_parsed() {
+        let body = "[+]ping ok\n[+]log ok\n[-]etcd failed: reason withheld\n\
+                    [+]poststarthook/start-apiextensions-informers excluded: ok\nreadyz check failed\n";
+        let report = HealthReport::from_verbose(false, body);
+        assert!(!report.healthy);
+        assert_eq!(report.checks.len(), 4);
+        let failed: Vec<_> = report.failed().map(|c| c.name.as_str()).collect();
+        assert_eq!(failed, ["etcd"]);
+        assert_eq!(report.check("etcd").unwrap().message, "failed: reason withheld");
+        let informers = report
+            .check("poststarthook/start-apiextensions-informers")
+            .unwrap();
+        assert!(informers.healthy);
+        assert_eq!(informers.message, "excluded: ok");
+    }
+}
@@ -1,10 +1,8 @@
 //! Request builder types and parameters for subresources
 use std::fmt::Debug;
 
-use crate::{
-    params::{DeleteParams, PostParams},
-    request::{Error, Request, JSON_MIME},
-};
+use crate::params::{DeleteParams, PostParams};
+#[cfg(feature = "request")] use crate::request::{Error, Request, JSON_MIME};
 
 pub use k8s_openapi::api::autoscaling::v1::{Scale, ScaleSpec, ScaleStatus};
 
@@ -42,6 +40,7 @@ pub struct LogParams {
     pub timestamps: bool,
 }
 
+#[cfg(feature = "request")]
 impl Request {
     /// Get a pod logs
     pub fn logs(&self, name: &str, lp: &LogParams) -> Result<http::Request<Vec<u8>>, Error> {
@@ -102,6 +101,7 @@ pub struct EvictParams {
     pub post_options: PostParams,
 }
 
+#[cfg(feature = "request")]
 impl Request {
     /// Create an eviction
     pub fn evict(&self, name: &str, ep: &EvictParams) -> Result<http::Request<Vec<u8>>, Error> {
@@ -123,6 +123,33 @@ impl Request {
     }
 }
 
+// ----------------------------------------------------------------------------
+// Proxy subresource
+// ----------------------------------------------------------------------------
+
+#[cfg(feature = "request")]
+impl Request {
+    /// Proxy a request to a port of a pod, service or node
+    ///
+    /// The method, headers and body of `request` are kept, and its path and query are appended to the
+    /// proxy path of the object, e.g. `/metrics` becomes `/api/v1/namespaces/ns/pods/web:9090/proxy/metrics`.
+    pub fn proxy(
+        &self,
+        name: &str,
+        port: u16,
+        request: http::Request<Vec<u8>>,
+    ) -> Result<http::Request<Vec<u8>>, Error> {
+        let (mut parts, body) = request.into_parts();
+        let path = parts.uri.path_and_query().map_or("/", |pq| pq.as_str());
+        let separator = if path.starts_with('/') { "" } else { "/" };
+        let target = format!("{}/{}:{}/proxy{}{}", self.url_path, name, port, separator, path);
+        parts.uri = target
+            .parse()
+            .map_err(|e| Error::BuildRequest(http::Error::from(e)))?;
+        Ok(http::Request::from_parts(parts, body))
+    }
+}
+
 // ----------------------------------------------------------------------------
 // Attach subresource
 // ----------------------------------------------------------------------------
@@ -410,7 +437,7 @@ impl Request {
 // ----------------------------------------------------------------------------
 
 /// Cheap sanity check to ensure type maps work as expected
-#[cfg(test)]
+#[cfg(all(test, feature = "request"))]
 mod test {
     use crate::{request::Request, resource::Resource};
     use chrono::{DateTime, TimeZone, Utc};
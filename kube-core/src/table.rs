@@ -0,0 +1,193 @@
+//! Server-side rendered tabular output, as used by `kubectl get`
+//!
+//! The API server can render any list or object as a `meta.k8s.io/v1` `Table`
+//! when asked for it through the `Accept` header. The columns are the same ones
+//! kubectl shows, including `additionalPrinterColumns` of custom resources.
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::metadata::{ListMeta, TypeMeta};
+
+/// A tabular representation of a set of API resources
+///
+/// See `Request::get_table` and `Request::list_table` for how to request one.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Table {
+    /// The type fields, normally `meta.k8s.io/v1` and `Table`
+    #[serde(flatten, default)]
+    pub types: TypeMeta,
+
+    /// Standard list metadata
+    #[serde(default)]
+    pub metadata: ListMeta,
+
+    /// Describes each column in the returned rows
+    #[serde(default)]
+    pub column_definitions: Vec<TableColumnDefinition>,
+
+    /// The rows of the table, one per object
+    #[serde(default)]
+    pub rows: Vec<TableRow>,
+}
+
+impl Table {
+    /// Index of the column with the given name
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        self.column_definitions.iter().position(|c| c.name == name)
+    }
+
+    /// Iterate over the cells of the named column
+    ///
+    /// Rows with fewer cells than columns yield `None` for the missing cells.
+    pub fn column<'a>(&'a self, name: &str) -> Option<impl Iterator<Item = Option<&'a Value>> + 'a> {
+        let idx = self.column_index(name)?;
+        Some(self.rows.iter().map(move |r| r.cells.get(idx)))
+    }
+
+    /// Render the table as aligned text, like `kubectl get`
+    ///
+    /// Column names are upper-cased and missing cells are shown as `<none>`. Columns with a
+    /// priority above 0 are only included when `wide` is set, like `kubectl get -o wide`.
+    pub fn render(&self, wide: bool) -> String {
+        let shown: Vec<usize> = (0..self.column_definitions.len())
+            .filter(|&i| wide || self.column_definitions[i].priority <= 0)
+            .collect();
+        let header: Vec<String> = shown
+            .iter()
+            .map(|&i| self.column_definitions[i].name.to_uppercase())
+            .collect();
+        let rows: Vec<Vec<String>> = self
+            .rows
+            .iter()
+            .map(|row| {
+                shown
+                    .iter()
+                    .map(|&i| match row.cells.get(i) {
+                        None | Some(Value::Null) => "<none>".to_string(),
+                        Some(Value::String(s)) => s.clone(),
+                        Some(other) => other.to_string(),
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let mut widths: Vec<usize> = header.iter().map(|h| h.chars().count()).collect();
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        let mut out = String::new();
+        for line in std::iter::once(&header).chain(&rows) {
+            let mut text = String::new();
+            for (cell, width) in line.iter().zip(&widths) {
+                text.push_str(&format!("{cell:<width$}   "));
+            }
+            out.push_str(text.trim_end());
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Describes a column in a [`Table`]
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TableColumnDefinition {
+    /// Human readable name for the column
+    pub name: String,
+
+    /// OpenAPI type of the column, e.g. `string`, `integer`, `number` or `boolean`
+    #[serde(rename = "type")]
+    pub type_: String,
+
+    /// Optional OpenAPI format modifier, e.g. `name` or `date-time`
+    #[serde(default)]
+    pub format: String,
+
+    /// Human readable description of the column
+    #[serde(default)]
+    pub description: String,
+
+    /// Relative importance of the column
+    ///
+    /// Columns with a priority above 0 are only shown by `kubectl get -o wide`.
+    #[serde(default)]
+    pub priority: i32,
+}
+
+/// A single row of a [`Table`]
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TableRow {
+    /// The cells of the row, in the same order as [`Table::column_definitions`]
+    ///
+    /// Cells can be any JSON value, but are usually strings, numbers or booleans.
+    #[serde(default)]
+    pub cells: Vec<Value>,
+
+    /// Additional conditions describing the row, e.g. whether the object is completed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conditions: Vec<TableRowCondition>,
+
+    /// The object shown in this row
+    ///
+    /// Depending on the server this is either the full object or a `PartialObjectMetadata`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub object: Option<Value>,
+}
+
+/// A condition attached to a [`TableRow`]
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TableRowCondition {
+    /// Type of the condition, currently only `Completed`
+    #[serde(rename = "type")]
+    pub type_: String,
+
+    /// Status of the condition, one of `True`, `False` or `Unknown`
+    pub status: String,
+
+    /// Machine readable reason for the last transition
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+
+    /// Human readable message for the last transition
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::Table;
+
+    #[test]
+    fn deserialize_pod_table() {
+        let table: Table = serde_json::from_value(serde_json::json!({
+            "kind": "Table",
+            "apiVersion": "meta.k8s.io/v1",
+            "metadata": { "resourceVersion": "1234" },
+            "columnDefinitions": [
+                { "name": "Name", "type": "string", "format": "name", "description": "Name must be unique", "priority": 0 },
+                { "name": "Ready", "type": "string", "format": "", "description": "The aggregate readiness state", "priority": 0 },
+                { "name": "IP", "type": "string", "format": "", "description": "Pod IP", "priority": 1 }
+            ],
+            "rows": [
+                {
+                    "cells": ["blog", "1/1", "10.0.0.1"],
+                    "object": { "kind": "PartialObjectMetadata", "apiVersion": "meta.k8s.io/v1", "metadata": { "name": "blog" } }
+                },
+                { "cells": ["old"], "conditions": [{ "type": "Completed", "status": "True" }] }
+            ]
+        }))
+        .unwrap();
+        assert_eq!(table.types.kind, "Table");
+        assert_eq!(table.metadata.resource_version.as_deref(), Some("1234"));
+        assert_eq!(table.column_definitions[2].priority, 1);
+        assert_eq!(table.rows[1].conditions[0].type_, "Completed");
+        let ips: Vec<_> = table.column("IP").unwrap().collect();
+        assert_eq!(ips, vec![Some(&serde_json::json!("10.0.0.1")), None]);
+        assert!(table.column("Age").is_none());
+    }
+}
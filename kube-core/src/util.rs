@@ -1,10 +1,11 @@
 //! Utils and helpers
 
+#[cfg(feature = "request")]
 use crate::{
     params::{Patch, PatchParams},
     request, Request,
 };
-use chrono::Utc;
+#[cfg(feature = "request")] use chrono::Utc;
 use k8s_openapi::api::apps::v1::{DaemonSet, Deployment, ReplicaSet, StatefulSet};
 
 /// Restartable Resource marker trait
@@ -15,6 +16,7 @@ impl Restart for DaemonSet {}
 impl Restart for StatefulSet {}
 impl Restart for ReplicaSet {}
 
+#[cfg(feature = "request")]
 impl Request {
     /// Restart a resource
     pub fn restart(&self, name: &str) -> Result<http::Request<Vec<u8>>, request::Error> {
@@ -33,8 +35,30 @@ impl Request {
         let pparams = PatchParams::default();
         self.patch(name, &pparams, &Patch::Merge(patch))
     }
+
+    /// Restart a resource like `kubectl rollout restart`
+    ///
+    /// Sets the `kubectl.kubernetes.io/restartedAt` annotation of the pod template, so restarts
+    /// from kube and from kubectl overwrite each other rather than piling up in the template.
+    pub fn rollout_restart(&self, name: &str) -> Result<http::Request<Vec<u8>>, request::Error> {
+        let patch = serde_json::json!({
+          "spec": {
+            "template": {
+              "metadata": {
+                "annotations": {
+                  "kubectl.kubernetes.io/restartedAt": Utc::now().to_rfc3339()
+                }
+              }
+            }
+          }
+        });
+
+        let pparams = PatchParams::default();
+        self.patch(name, &pparams, &Patch::Merge(patch))
+    }
 }
 
+#[cfg(feature = "request")]
 impl Request {
     /// Cordon a resource
     pub fn cordon(&self, name: &str) -> Result<http::Request<Vec<u8>>, request::Error> {
@@ -59,7 +83,7 @@ impl Request {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "request"))]
 mod test {
     use crate::{params::Patch, request::Request, resource::Resource};
 
@@ -77,6 +101,19 @@ mod test {
         );
     }
 
+    #[test]
+    fn rollout_restart_patch_is_correct() {
+        use k8s_openapi::api::apps::v1 as appsv1;
+
+        let url = appsv1::StatefulSet::url_path(&(), Some("ns"));
+        let req = Request::new(url).rollout_restart("web").unwrap();
+        assert_eq!(req.uri(), "/apis/apps/v1/namespaces/ns/statefulsets/web?");
+        assert_eq!(req.method(), "PATCH");
+        let patch: serde_json::Value = serde_json::from_slice(req.body()).unwrap();
+        let annotations = &patch["spec"]["template"]["metadata"]["annotations"];
+        assert!(annotations["kubectl.kubernetes.io/restartedAt"].is_string());
+    }
+
     #[test]
     fn cordon_patch_is_correct() {
         use k8s_openapi::api::core::v1::Node;
@@ -3,7 +3,8 @@
 //! See <https://kubernetes.io/docs/reference/using-api/api-concepts/#efficient-detection-of-changes>
 
 use crate::{error::ErrorResponse, metadata::TypeMeta};
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
 use std::fmt::Debug;
 /// A raw event returned from a watch query
 ///
@@ -39,6 +40,70 @@ impl<K> Debug for WatchEvent<K> {
     }
 }
 
+/// An event of a watch that relists when its tracked version expires
+///
+/// Returned by `Api::watch_relisting`, which lists all objects before it starts watching,
+/// and again whenever the apiserver answers with `410 Gone`.
+#[derive(Clone, Debug)]
+pub enum RelistingEvent<K> {
+    /// The watch was (re)started from a list of all objects
+    ///
+    /// This replaces everything seen before it, since events may have been missed while the
+    /// version was expired.
+    Restarted(Vec<K>),
+    /// An event of the watch
+    Event(WatchEvent<K>),
+}
+
+/// A watch event that is kept even when its object does not deserialize into `K`
+///
+/// Returned by `Api::watch_lossy`, so that objects that do not match the schema of `K`, e.g.
+/// after a change to a CRD, do not have to end the watch.
+#[derive(Debug)]
+pub enum LossyWatchEvent<K> {
+    /// An event whose object was deserialized
+    Event(WatchEvent<K>),
+    /// An event whose object could not be deserialized
+    Undecodable(UndecodableEvent),
+}
+
+/// A watch event whose object could not be deserialized, with the object as sent by the apiserver
+#[derive(Debug)]
+pub struct UndecodableEvent {
+    /// The event with the raw JSON of its object
+    pub raw: WatchEvent<Value>,
+    /// Why the object could not be deserialized
+    pub error: serde_json::Error,
+}
+
+impl<K: DeserializeOwned> LossyWatchEvent<K> {
+    /// Deserialize the object of a raw event, keeping the raw event when that fails
+    ///
+    /// ```
+    /// use k8s_openapi::api::core::v1::Pod;
+    /// use kube_core::watch::{LossyWatchEvent, WatchEvent};
+    ///
+    /// let raw = WatchEvent::Added(serde_json::json!({ "metadata": { "name": 42 } }));
+    /// let LossyWatchEvent::Undecodable(event) = LossyWatchEvent::<Pod>::decode(raw) else {
+    ///     panic!("name is not a string");
+    /// };
+    /// assert!(matches!(event.raw, WatchEvent::Added(obj) if obj["metadata"]["name"] == 42));
+    /// ```
+    pub fn decode(raw: WatchEvent<Value>) -> Self {
+        let (obj, wrap): (&Value, fn(K) -> WatchEvent<K>) = match &raw {
+            WatchEvent::Added(obj) => (obj, WatchEvent::Added),
+            WatchEvent::Modified(obj) => (obj, WatchEvent::Modified),
+            WatchEvent::Deleted(obj) => (obj, WatchEvent::Deleted),
+            WatchEvent::Bookmark(bm) => return Self::Event(WatchEvent::Bookmark(bm.clone())),
+            WatchEvent::Error(err) => return Self::Event(WatchEvent::Error(err.clone())),
+        };
+        match K::deserialize(obj) {
+            Ok(obj) => Self::Event(wrap(obj)),
+            Err(error) => Self::Undecodable(UndecodableEvent { raw, error }),
+        }
+    }
+}
+
 /// Slimed down K for [`WatchEvent::Bookmark`] due to [#285](https://github.com/kube-rs/kube/issues/285).
 ///
 /// Can only be relied upon to have metadata with resource version.
@@ -53,6 +118,23 @@ pub struct Bookmark {
     pub metadata: BookmarkMeta,
 }
 
+impl Bookmark {
+    /// Annotation set on the bookmark that ends the initial events of a streaming list
+    pub const INITIAL_EVENTS_END: &'static str = "k8s.io/initial-events-end";
+
+    /// Whether this bookmark marks the end of the initial events of a streaming list
+    ///
+    /// When watching with [`WatchParams::initial_events`](crate::params::WatchParams::initial_events),
+    /// all events before this bookmark together form a consistent snapshot of the collection
+    /// as of the bookmark's resource version.
+    pub fn is_initial_events_end(&self) -> bool {
+        self.metadata
+            .annotations
+            .get(Self::INITIAL_EVENTS_END)
+            .is_some_and(|v| v == "true")
+    }
+}
+
 /// Slimed down Metadata for WatchEvent::Bookmark
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -65,3 +147,29 @@ pub struct BookmarkMeta {
     #[serde(default)]
     pub annotations: std::collections::BTreeMap<String, String>,
 }
+
+#[cfg(test)]
+mod test {
+    use super::WatchEvent;
+    use k8s_openapi::api::core::v1::Pod;
+
+    #[test]
+    fn bookmark_marks_initial_events_end() {
+        let ev: WatchEvent<Pod> = serde_json::from_value(serde_json::json!({
+            "type": "BOOKMARK",
+            "object": {
+                "kind": "Pod",
+                "apiVersion": "v1",
+                "metadata": {
+                    "resourceVersion": "123",
+                    "annotations": { "k8s.io/initial-events-end": "true" }
+                }
+            }
+        }))
+        .unwrap();
+        let WatchEvent::Bookmark(bm) = ev else {
+            panic!("expected bookmark");
+        };
+        assert!(bm.is_initial_events_end());
+    }
+}
@@ -26,6 +26,9 @@ struct KubeAttrs {
     derives: Vec<String>,
     schema: Option<SchemaMode>,
     status: Option<Path>,
+    /// Root types of other versions whose status can be converted into this version's status
+    #[darling(multiple)]
+    status_from: Vec<Path>,
     #[darling(multiple, rename = "category")]
     categories: Vec<String>,
     #[darling(multiple, rename = "shortname")]
@@ -61,6 +64,17 @@ struct KubeAttrs {
     #[darling(default = default_served_arg)]
     served: bool,
 
+    /// Strip markdown markup from the descriptions generated from doc comments
+    #[darling(default)]
+    strip_markdown: bool,
+
+    /// Truncate the descriptions generated from doc comments to this many characters
+    description_max_length: Option<usize>,
+
+    /// Implement k8s-openapi's `DeepMerge` for the root type and the spec
+    #[darling(default)]
+    deep_merge: bool,
+
     /// Sets the `deprecated` and optionally the `deprecationWarning` property.
     ///
     /// See https://kubernetes.io/docs/tasks/extend-kubernetes/custom-resources/custom-resource-definition-versioning/#version-deprecation
@@ -172,6 +186,7 @@ enum SchemaMode {
     Disabled,
     Manual,
     Derived,
+    Lazy,
 }
 
 impl SchemaMode {
@@ -180,6 +195,7 @@ impl SchemaMode {
             SchemaMode::Disabled => false,
             SchemaMode::Manual => false,
             SchemaMode::Derived => true,
+            SchemaMode::Lazy => true,
         }
     }
 
@@ -188,8 +204,13 @@ impl SchemaMode {
             SchemaMode::Disabled => false,
             SchemaMode::Manual => true,
             SchemaMode::Derived => true,
+            SchemaMode::Lazy => true,
         }
     }
+
+    fn lazy(self) -> bool {
+        matches!(self, SchemaMode::Lazy)
+    }
 }
 
 impl FromMeta for SchemaMode {
@@ -198,6 +219,7 @@ impl FromMeta for SchemaMode {
             "disabled" => Ok(SchemaMode::Disabled),
             "manual" => Ok(SchemaMode::Manual),
             "derived" => Ok(SchemaMode::Derived),
+            "lazy" => Ok(SchemaMode::Lazy),
             x => Err(darling::Error::unknown_value(x)),
         }
     }
@@ -297,6 +319,17 @@ impl FromMeta for Scale {
 }
 
 impl Scale {
+    fn to_json(&self) -> serde_json::Value {
+        let mut scale = serde_json::json!({
+            "specReplicasPath": self.spec_replicas_path,
+            "statusReplicasPath": self.status_replicas_path,
+        });
+        if let Some(p) = &self.label_selector_path {
+            scale["labelSelectorPath"] = p.as_str().into();
+        }
+        scale
+    }
+
     fn to_tokens(&self, k8s_openapi: &Path) -> TokenStream {
         let apiext = quote! {
             #k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1
@@ -362,6 +395,10 @@ pub(crate) fn derive(input: proc_macro2::TokenStream) -> proc_macro2::TokenStrea
         storage,
         served,
         deprecated,
+        strip_markdown,
+        description_max_length,
+        status_from,
+        deep_merge,
         crates:
             Crates {
                 kube_core,
@@ -376,6 +413,14 @@ pub(crate) fn derive(input: proc_macro2::TokenStream) -> proc_macro2::TokenStrea
         labels,
     } = kube_attrs;
 
+    let spec_fields = match spec_fields(&derive_input) {
+        Err(err) => return err.to_compile_error(),
+        Ok(fields) => fields,
+    };
+    let immutable_fields: Vec<&SpecField> = spec_fields.iter().filter(|f| f.immutable).collect();
+    let impl_spec_deep_merge =
+        deep_merge.then(|| crate::deep_merge::impl_deep_merge(&derive_input, &k8s_openapi));
+
     let struct_name = kind_struct.unwrap_or_else(|| kind.clone());
     if derive_input.ident == struct_name {
         return syn::Error::new_spanned(
@@ -397,11 +442,27 @@ pub(crate) fn derive(input: proc_macro2::TokenStream) -> proc_macro2::TokenStrea
     let rootident_str = rootident.to_string();
 
     // if status set, also add that
+    if status.is_none() && !status_from.is_empty() {
+        return syn::Error::new_spanned(
+            &status_from[0],
+            r#"#[kube(status_from = "...")] requires #[kube(status = "...")]"#,
+        )
+        .to_compile_error();
+    }
     let StatusInformation {
         field: status_field,
         default: status_default,
         impl_hasstatus,
-    } = process_status(&rootident, &status, &visibility, &kube_core);
+        impl_convertstatus,
+    } = process_status(
+        &rootident,
+        &status,
+        &visibility,
+        &version,
+        &status_from,
+        &kube_core,
+        &serde_json,
+    );
     let has_status = status.is_some();
     let serialize_status = if has_status {
         quote! {
@@ -441,14 +502,26 @@ pub(crate) fn derive(input: proc_macro2::TokenStream) -> proc_macro2::TokenStrea
     // these are validated by the API server implicitly. Also, we can't generate the
     // schema for `metadata` (`ObjectMeta`) because it doesn't implement `JsonSchema`.
     let schemars_skip = schema_mode.derive().then_some(quote! { #[schemars(skip)] });
-    if schema_mode.derive() && !rules.is_empty() {
+    // Immutable fields are enforced by transition rules, which only make sense in a derived schema
+    let immutable_rules: Vec<TokenStream> = if schema_mode.derive() {
+        immutable_fields.iter().map(|f| f.rule()).collect()
+    } else {
+        vec![]
+    };
+    if schema_mode.derive() && (!rules.is_empty() || !immutable_rules.is_empty()) {
         derive_paths.push(syn::parse_quote! { #kube::CELSchema });
     } else if schema_mode.derive() {
         derive_paths.push(syn::parse_quote! { #schemars::JsonSchema });
     }
 
     let struct_rules: Option<Vec<TokenStream>> =
-        (!rules.is_empty()).then(|| rules.iter().map(|r| quote! {rule = #r,}).collect());
+        (!rules.is_empty() || !immutable_rules.is_empty()).then(|| {
+            rules
+                .iter()
+                .map(|r| quote! {rule = #r,})
+                .chain(immutable_rules.iter().map(|r| quote! {rule = #r,}))
+                .collect()
+        });
     let struct_rules = struct_rules.map(|r| quote! { #[cel_validate(#(#r)*)]});
 
     let meta_annotations = if !annotations.is_empty() {
@@ -580,6 +653,76 @@ pub(crate) fn derive(input: proc_macro2::TokenStream) -> proc_macro2::TokenStrea
         .map(|s| format!(r#"{{ "jsonPath": "{s}" }}"#))
         .collect();
     let fields = format!("[ {} ]", fields.join(","));
+
+    let crd_meta_name = format!("{plural}.{group}");
+    // In lazy mode everything but the schema is known at compile time,
+    // so we embed it as one (uncompressed) string literal rather than expanding a `json!` invocation.
+    let lazy_skeleton = if schema_mode.lazy() {
+        let mut meta = serde_json::json!({ "name": crd_meta_name });
+        if !annotations.is_empty() {
+            let map: serde_json::Map<_, _> = annotations
+                .iter()
+                .map(|kv| (kv.0.clone(), kv.1.clone().into()))
+                .collect();
+            meta["annotations"] = map.into();
+        }
+        if !labels.is_empty() {
+            let map: serde_json::Map<_, _> = labels
+                .iter()
+                .map(|kv| (kv.0.clone(), kv.1.clone().into()))
+                .collect();
+            meta["labels"] = map.into();
+        }
+        let columns: serde_json::Value = match serde_json::from_str(&printers) {
+            Ok(columns) => columns,
+            Err(err) => {
+                return syn::Error::new(Span::call_site(), format!("invalid printcolumn json: {err}"))
+                    .to_compile_error()
+            }
+        };
+        let subres = match (has_status, &scale) {
+            (true, Some(s)) => serde_json::json!({ "status": {}, "scale": s.to_json() }),
+            (true, None) => serde_json::json!({ "status": {} }),
+            (false, _) => serde_json::json!({}),
+        };
+        let mut ver = serde_json::json!({
+            "name": version,
+            "served": served,
+            "storage": storage,
+            "additionalPrinterColumns": columns,
+            "subresources": subres,
+        });
+        match &deprecated {
+            Some(Override::Inherit) => ver["deprecated"] = true.into(),
+            Some(Override::Explicit(warning)) => {
+                ver["deprecated"] = true.into();
+                ver["deprecationWarning"] = warning.as_str().into();
+            }
+            None => {}
+        }
+        if !selectable.is_empty() {
+            ver["selectableFields"] = serde_json::from_str(&fields).unwrap();
+        }
+        let skeleton = serde_json::json!({
+            "metadata": meta,
+            "spec": {
+                "group": group,
+                "scope": scope,
+                "names": {
+                    "categories": categories,
+                    "plural": plural,
+                    "singular": name,
+                    "kind": kind,
+                    "shortNames": shortnames,
+                },
+                "versions": [ver],
+            }
+        });
+        Some(skeleton.to_string())
+    } else {
+        None
+    };
+
     let scale = scale.map_or_else(
         || quote! { None },
         |s| {
@@ -606,7 +749,6 @@ pub(crate) fn derive(input: proc_macro2::TokenStream) -> proc_macro2::TokenStrea
 
     let categories_json = serde_json::to_string(&categories).unwrap();
     let short_json = serde_json::to_string(&shortnames).unwrap();
-    let crd_meta_name = format!("{plural}.{group}");
 
     let mut crd_meta = TokenStream::new();
     crd_meta.extend(quote! { "name": #crd_meta_name });
@@ -619,6 +761,36 @@ pub(crate) fn derive(input: proc_macro2::TokenStream) -> proc_macro2::TokenStrea
         crd_meta.extend(quote! { , "labels": #meta_labels });
     }
 
+    let description_rewriter = (strip_markdown || description_max_length.is_some()).then(|| {
+        let max_length = match description_max_length {
+            Some(max) => quote! { Some(#max) },
+            None => quote! { None },
+        };
+        quote! {
+            .with_visitor(#kube_core::schema::DescriptionRewriter {
+                strip_markdown: #strip_markdown,
+                max_length: #max_length,
+            })
+        }
+    });
+    let description_overrides: Vec<TokenStream> = spec_fields
+        .iter()
+        .filter_map(|f| {
+            let description = f.description.as_ref()?;
+            let name = &f.name;
+            Some(quote! {
+                #kube_core::schema::set_property_description(&mut schema.schema, &["spec", #name], #description);
+            })
+        })
+        .collect();
+    let root_schema = if description_overrides.is_empty() {
+        quote! { let schema = gen.into_root_schema_for::<Self>(); }
+    } else {
+        quote! {
+            let mut schema = gen.into_root_schema_for::<Self>();
+            #(#description_overrides)*
+        }
+    };
     let schemagen = if schema_mode.use_in_crd() {
         quote! {
             // Don't use definitions and don't include `$schema` because these are not allowed.
@@ -628,8 +800,9 @@ pub(crate) fn derive(input: proc_macro2::TokenStream) -> proc_macro2::TokenStrea
                     s.meta_schema = None;
                 })
                 .with_visitor(#kube_core::schema::StructuralSchemaRewriter)
+                #description_rewriter
                 .into_generator();
-            let schema = gen.into_root_schema_for::<Self>();
+            #root_schema
         }
     } else {
         // we could issue a compile time warning for this, but it would hit EVERY compile, which would be noisy
@@ -702,11 +875,23 @@ pub(crate) fn derive(input: proc_macro2::TokenStream) -> proc_macro2::TokenStrea
         });
     };
 
-    // Implement the CustomResourceExt trait to allow users writing generic logic on top of them
-    let impl_crd = quote! {
-        impl #extver::CustomResourceExt for #rootident {
-
-            fn crd() -> #apiext::CustomResourceDefinition {
+    let crd_body = if let Some(skeleton) = lazy_skeleton {
+        quote! {
+            static CRD: #std::sync::OnceLock<#apiext::CustomResourceDefinition> = #std::sync::OnceLock::new();
+            CRD.get_or_init(|| {
+                #schemagen
+
+                let mut jsondata: #serde_json::Value = #serde_json::from_str(#skeleton).expect("valid custom resource skeleton");
+                jsondata["spec"]["versions"][0]["schema"] = #serde_json::json!({
+                    "openAPIV3Schema": schema,
+                });
+                #serde_json::from_value(jsondata)
+                    .expect("valid custom resource from #[kube(attrs..)]")
+            })
+            .clone()
+        }
+    } else {
+        quote! {
                 let columns : Vec<#apiext::CustomResourceColumnDefinition> = #serde_json::from_str(#printers).expect("valid printer column json");
                 #k8s_openapi::k8s_if_ge_1_30! {
                     let fields : Vec<#apiext::SelectableField> = #serde_json::from_str(#fields).expect("valid selectableField column json");
@@ -730,6 +915,15 @@ pub(crate) fn derive(input: proc_macro2::TokenStream) -> proc_macro2::TokenStrea
                 #jsondata
                 #serde_json::from_value(jsondata)
                     .expect("valid custom resource from #[kube(attrs..)]")
+        }
+    };
+
+    // Implement the CustomResourceExt trait to allow users writing generic logic on top of them
+    let impl_crd = quote! {
+        impl #extver::CustomResourceExt for #rootident {
+
+            fn crd() -> #apiext::CustomResourceDefinition {
+                #crd_body
             }
 
             fn crd_name() -> &'static str {
@@ -748,6 +942,63 @@ pub(crate) fn derive(input: proc_macro2::TokenStream) -> proc_macro2::TokenStrea
 
     let impl_hasspec = generate_hasspec(&ident, &rootident, &kube_core);
 
+    // Export the schemas of the user's structs as standalone documents, when we know they implement JsonSchema
+    let impl_schema_bundle = if schema_mode.derive() {
+        let status_schema = status.as_ref().map(|pth| {
+            quote! { "status": #kube_core::schema::json_schema_document::<#pth>(), }
+        });
+        quote! {
+            impl #rootident {
+                /// Standalone JSON Schema (draft 2020-12) documents for the `spec` and `status` of this resource
+                ///
+                /// Returns an object with a `spec` key, and a `status` key if the resource has a status.
+                pub fn json_schema_bundle() -> #serde_json::Value {
+                    #serde_json::json!({
+                        "spec": #kube_core::schema::json_schema_document::<#ident>(),
+                        #status_schema
+                    })
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    let impl_check_immutable = if immutable_fields.is_empty() {
+        quote! {}
+    } else {
+        let checks = immutable_fields.iter().map(|f| f.check(&kube_core));
+        quote! {
+            impl #rootident {
+                /// Check that an update from `old` to `new` leaves all `#[kube(immutable)]` fields unchanged
+                ///
+                /// Mirrors the transition rules in the generated schema, for validating updates outside of the apiserver.
+                pub fn check_immutable(old: &Self, new: &Self) -> #std::result::Result<(), #kube_core::ImmutableFieldError> {
+                    #(#checks)*
+                    Ok(())
+                }
+            }
+        }
+    };
+
+    let impl_deep_merge = if deep_merge {
+        let merge_status = has_status.then(|| {
+            quote! { #k8s_openapi::DeepMerge::merge_from(&mut self.status, other.status); }
+        });
+        quote! {
+            impl #k8s_openapi::DeepMerge for #rootident {
+                fn merge_from(&mut self, other: Self) {
+                    #k8s_openapi::DeepMerge::merge_from(&mut self.metadata, other.metadata);
+                    #k8s_openapi::DeepMerge::merge_from(&mut self.spec, other.spec);
+                    #merge_status
+                }
+            }
+            #impl_spec_deep_merge
+        }
+    } else {
+        quote! {}
+    };
+
     // Concat output
     quote! {
         #compile_constraints
@@ -757,9 +1008,161 @@ pub(crate) fn derive(input: proc_macro2::TokenStream) -> proc_macro2::TokenStrea
         #impl_crd
         #impl_hasspec
         #impl_hasstatus
+        #impl_convertstatus
+        #impl_schema_bundle
+        #impl_check_immutable
+        #impl_deep_merge
+    }
+}
+
+/// A spec field with `#[kube(...)]` attributes
+struct SpecField {
+    ident: Ident,
+    /// The serialized name of the field
+    name: String,
+    optional: bool,
+    /// Whether the field is marked with `#[kube(immutable)]`
+    immutable: bool,
+    /// The description set with `#[kube(description = "...")]`
+    description: Option<String>,
+}
+
+impl SpecField {
+    /// The CEL transition rule rejecting changes to the field
+    ///
+    /// Optional fields may be set once, but not changed or unset afterwards.
+    fn rule(&self) -> TokenStream {
+        let name = &self.name;
+        let rule = if self.optional {
+            format!("!has(oldSelf.spec.{name}) || (has(self.spec.{name}) && self.spec.{name} == oldSelf.spec.{name})")
+        } else {
+            format!("self.spec.{name} == oldSelf.spec.{name}")
+        };
+        let message = format!("spec.{name} is immutable");
+        quote! { Rule::new(#rule).message(#message) }
+    }
+
+    /// The check rejecting changes to the field in `check_immutable`
+    fn check(&self, kube_core: &Path) -> TokenStream {
+        let ident = &self.ident;
+        let field = format!("spec.{}", self.name);
+        let changed = if self.optional {
+            quote! { old.spec.#ident.is_some() && old.spec.#ident != new.spec.#ident }
+        } else {
+            quote! { old.spec.#ident != new.spec.#ident }
+        };
+        quote! {
+            if #changed {
+                return Err(#kube_core::ImmutableFieldError { field: #field });
+            }
+        }
     }
 }
 
+/// Collect the fields of the spec struct that have `#[kube(...)]` attributes
+fn spec_fields(input: &DeriveInput) -> syn::Result<Vec<SpecField>> {
+    let Data::Struct(data) = &input.data else {
+        return Ok(vec![]);
+    };
+    let mut rename_all = None;
+    for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename_all") {
+                rename_all = Some(meta.value()?.parse::<syn::LitStr>()?);
+            } else if meta.input.peek(syn::Token![=]) {
+                meta.value()?.parse::<Expr>()?;
+            } else if meta.input.peek(syn::token::Paren) {
+                meta.input.parse::<proc_macro2::Group>()?;
+            }
+            Ok(())
+        })?;
+    }
+
+    let mut fields = vec![];
+    for field in &data.fields {
+        let mut immutable = false;
+        let mut description = None;
+        for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("kube")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("immutable") {
+                    immutable = true;
+                    Ok(())
+                } else if meta.path.is_ident("description") {
+                    description = Some(meta.value()?.parse::<syn::LitStr>()?.value());
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported field attribute, expected `immutable` or `description`"))
+                }
+            })?;
+        }
+        if !immutable && description.is_none() {
+            continue;
+        }
+        let Some(ident) = field.ident.clone() else {
+            return Err(syn::Error::new_spanned(
+                field,
+                "fields with kube attributes must be named",
+            ));
+        };
+
+        let mut name = None;
+        for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    name = Some(meta.value()?.parse::<syn::LitStr>()?.value());
+                } else if meta.input.peek(syn::Token![=]) {
+                    meta.value()?.parse::<Expr>()?;
+                } else if meta.input.peek(syn::token::Paren) {
+                    meta.input.parse::<proc_macro2::Group>()?;
+                }
+                Ok(())
+            })?;
+        }
+        let name = match (name, &rename_all) {
+            (Some(name), _) => name,
+            (None, rename_all) => {
+                let raw = ident.to_string().trim_start_matches("r#").to_string();
+                match rename_all.as_ref().map(syn::LitStr::value).as_deref() {
+                    None | Some("snake_case") => raw,
+                    Some("camelCase") => to_camel_case(&raw),
+                    Some("lowercase") => raw.to_lowercase(),
+                    Some(_) => {
+                        return Err(syn::Error::new_spanned(
+                            rename_all,
+                            "fields with kube attributes require `#[serde(rename = \"...\")]` with this `rename_all` convention",
+                        ))
+                    }
+                }
+            }
+        };
+        let optional = matches!(&field.ty, syn::Type::Path(ty) if ty.qself.is_none() && ty.path.segments.last().is_some_and(|s| s.ident == "Option"));
+        fields.push(SpecField {
+            ident,
+            name,
+            optional,
+            immutable,
+            description,
+        });
+    }
+    Ok(fields)
+}
+
+fn to_camel_case(snake: &str) -> String {
+    let mut camel = String::with_capacity(snake.len());
+    let mut upper = false;
+    for c in snake.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            camel.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            camel.push(c);
+        }
+    }
+    camel
+}
+
 /// This generates the code for the `#kube_core::object::HasSpec` trait implementation.
 ///
 /// All CRDs have a spec so it is implemented for all of them.
@@ -792,6 +1195,8 @@ struct StatusInformation {
     default: TokenStream,
     /// The implementation code for the `HasStatus` trait
     impl_hasstatus: TokenStream,
+    /// The implementation code for the `ConvertStatus` trait
+    impl_convertstatus: TokenStream,
 }
 
 /// This processes the `status` field of a CRD.
@@ -803,16 +1208,23 @@ struct StatusInformation {
 /// * `root ident`: The identity (name) of the main CRD struct (the one we generate in this macro)
 /// * `status`: The optional name of the `status` struct to use
 /// * `visibility`: Desired visibility of the generated field
+/// * `version`: The version of the CRD
+/// * `status_from`: The root structs of other versions whose status converts into this one
 /// * `kube_core`: The path stream for the analagous kube::core import location from users POV
+/// * `serde_json`: The path stream for the serde_json import location from users POV
 ///
 /// returns: A `StatusInformation` struct
 fn process_status(
     root_ident: &Ident,
     status: &Option<Path>,
     visibility: &Visibility,
+    version: &str,
+    status_from: &[Path],
     kube_core: &Path,
+    serde_json: &Path,
 ) -> StatusInformation {
     if let Some(pth) = &status {
+        let error = quote! { #kube_core::conversion::StatusConversionError };
         StatusInformation {
             field: quote! {
                 #[serde(skip_serializing_if = "Option::is_none")]
@@ -833,13 +1245,34 @@ fn process_status(
                     }
                 }
             },
+            impl_convertstatus: quote! {
+                impl #kube_core::conversion::ConvertStatus for #root_ident {
+                    fn status_from_version(
+                        version: &str,
+                        status: #serde_json::Value,
+                    ) -> ::std::result::Result<#pth, #error> {
+                        if version == #version {
+                            return #serde_json::from_value(status).map_err(#error::Deserialize);
+                        }
+                        #(
+                            if version == <#status_from as #kube_core::Resource>::version(&()) {
+                                let status: <#status_from as #kube_core::object::HasStatus>::Status =
+                                    #serde_json::from_value(status).map_err(#error::Deserialize)?;
+                                return ::std::result::Result::Ok(::std::convert::From::from(status));
+                            }
+                        )*
+                        ::std::result::Result::Err(#error::UnsupportedVersion(version.to_string()))
+                    }
+                }
+            },
         }
     } else {
         let empty_quote = quote! {};
         StatusInformation {
             field: empty_quote.clone(),
             default: empty_quote.clone(),
-            impl_hasstatus: empty_quote,
+            impl_hasstatus: empty_quote.clone(),
+            impl_convertstatus: empty_quote,
         }
     }
 }
@@ -896,6 +1329,26 @@ mod tests {
         assert!(kube_attrs.namespaced);
     }
 
+    #[test]
+    fn test_parse_schema_modes() {
+        let modes = [
+            ("disabled", SchemaMode::Disabled),
+            ("manual", SchemaMode::Manual),
+            ("derived", SchemaMode::Derived),
+            ("lazy", SchemaMode::Lazy),
+        ];
+        for (name, mode) in modes {
+            let input = quote! {
+                #[derive(CustomResource, Serialize, Deserialize, Debug, PartialEq, Clone, JsonSchema)]
+                #[kube(group = "clux.dev", version = "v1", kind = "Foo", schema = #name)]
+                struct FooSpec { foo: String }
+            };
+            let input = syn::parse2(input).unwrap();
+            let kube_attrs = KubeAttrs::from_derive_input(&input).unwrap();
+            assert_eq!(kube_attrs.schema, Some(mode), "{name}");
+        }
+    }
+
     #[test]
     fn test_derive_crd() {
         let path = env::current_dir().unwrap().join("tests").join("crd_enum_test.rs");
@@ -0,0 +1,124 @@
+// Generated by darling macros, out of our control
+#![allow(clippy::manual_unwrap_or_default)]
+
+use darling::{FromDeriveInput, FromMeta};
+use proc_macro2::TokenStream;
+use syn::{parse_quote, Data, DeriveInput, Index, Member, Path};
+
+/// Values we can parse from #[deep_merge(attrs)]
+#[derive(Debug, FromDeriveInput)]
+#[darling(attributes(deep_merge))]
+struct DeepMergeAttrs {
+    #[darling(default)]
+    crates: Crates,
+}
+
+#[derive(Debug, FromMeta)]
+struct Crates {
+    #[darling(default = "Self::default_k8s_openapi")]
+    k8s_openapi: Path,
+}
+
+// Default is required when the subattribute isn't mentioned at all
+// Delegate to darling rather than deriving, so that we can piggyback off the `#[darling(default)]` clauses
+impl Default for Crates {
+    fn default() -> Self {
+        Self::from_list(&[]).unwrap()
+    }
+}
+
+impl Crates {
+    fn default_k8s_openapi() -> Path {
+        parse_quote! { ::k8s_openapi }
+    }
+}
+
+pub(crate) fn derive(input: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    let derive_input: DeriveInput = match syn::parse2(input) {
+        Err(err) => return err.to_compile_error(),
+        Ok(di) => di,
+    };
+    if let Data::Union(_) = derive_input.data {
+        return syn::Error::new_spanned(&derive_input.ident, r#"Unions can not #[derive(DeepMerge)]"#)
+            .to_compile_error();
+    }
+    let attrs = match DeepMergeAttrs::from_derive_input(&derive_input) {
+        Err(err) => return err.write_errors(),
+        Ok(attrs) => attrs,
+    };
+    impl_deep_merge(&derive_input, &attrs.crates.k8s_openapi)
+}
+
+/// Implement `DeepMerge` for a struct or enum
+///
+/// Structs are merged field by field, enums are replaced as a whole like other atomic values.
+///
+/// How a field is merged is up to the `DeepMerge` impl of its type, which for the std types is the
+/// one of `k8s-openapi`:
+/// - `Option` fields are only changed by `Some`, whose value is merged into the current one
+/// - maps are merged by key, the values of `other` are merged into the entries with the same key and
+///   the other keys are inserted
+/// - `Vec` fields are replaced as a whole, nothing is appended or deduplicated, like atomic lists
+///
+/// The set and map list types of the Kubernetes schemas are not known from the field types, so lists
+/// that need them need a manual impl with `k8s_openapi::merge_strategies::list`.
+pub(crate) fn impl_deep_merge(input: &DeriveInput, k8s_openapi: &Path) -> TokenStream {
+    let ident = &input.ident;
+    let mut generics = input.generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(parse_quote! { #k8s_openapi::DeepMerge });
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let body = match &input.data {
+        Data::Struct(data) if !data.fields.is_empty() => {
+            let merges = data.fields.iter().enumerate().map(|(i, field)| {
+                let member = match &field.ident {
+                    Some(ident) => Member::Named(ident.clone()),
+                    None => Member::Unnamed(Index::from(i)),
+                };
+                quote! { #k8s_openapi::DeepMerge::merge_from(&mut self.#member, other.#member); }
+            });
+            quote! { #(#merges)* }
+        }
+        Data::Struct(_) => quote! { let _ = other; },
+        _ => quote! { *self = other; },
+    };
+
+    quote! {
+        impl #impl_generics #k8s_openapi::DeepMerge for #ident #ty_generics #where_clause {
+            fn merge_from(&mut self, other: Self) {
+                #body
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merges_structs_by_field_and_enums_whole() {
+        let input = syn::parse2(quote! {
+            struct FooStatus { ready: bool, conditions: Vec<String> }
+        })
+        .unwrap();
+        let expanded = impl_deep_merge(&input, &parse_quote! { ::k8s_openapi }).to_string();
+        for field in ["ready", "conditions"] {
+            let field = syn::Ident::new(field, proc_macro2::Span::call_site());
+            let merge = quote! { ::k8s_openapi::DeepMerge::merge_from(&mut self.#field, other.#field); };
+            assert!(expanded.contains(&merge.to_string()), "{expanded}");
+        }
+
+        let input = syn::parse2(quote! {
+            enum Phase { Pending, Ready }
+        })
+        .unwrap();
+        let expanded = impl_deep_merge(&input, &parse_quote! { ::k8s_openapi }).to_string();
+        assert!(
+            expanded.contains(&quote! { *self = other; }.to_string()),
+            "{expanded}"
+        );
+    }
+}
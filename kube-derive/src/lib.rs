@@ -5,6 +5,7 @@ extern crate proc_macro;
 
 mod cel_schema;
 mod custom_resource;
+mod deep_merge;
 mod resource;
 
 /// A custom derive for kubernetes custom resource definitions.
@@ -109,6 +110,61 @@ mod resource;
 /// Adds a status struct to the top level generated type and enables the status
 /// subresource in your crd.
 ///
+/// ## `#[kube(status_from = "v1::RootStructName")]`
+/// Declares that the status of another version of the same custom resource converts into the status of
+/// this version, when versions use different status types. This requires a `From` implementation from
+/// the status type of that version into the status type of this version, and can be repeated for more versions.
+///
+/// Every custom resource with a `status` implements `kube::core::conversion::ConvertStatus`, and
+/// `kube::core::conversion::convert_status` uses it to convert the status of objects in conversion webhooks:
+///
+/// ```rust
+/// use kube::{core::conversion::convert_status, CustomResource};
+/// use schemars::JsonSchema;
+/// use serde::{Deserialize, Serialize};
+///
+/// mod v1 {
+/// #   use super::*;
+///     #[derive(CustomResource, Deserialize, Serialize, Clone, Debug, JsonSchema)]
+///     #[kube(group = "example.com", version = "v1", kind = "Gadget", status = "GadgetStatus")]
+///     pub struct GadgetSpec {}
+///
+///     #[derive(Deserialize, Serialize, Clone, Debug, JsonSchema)]
+///     pub struct GadgetStatus {
+///         pub ready: bool,
+///     }
+/// }
+///
+/// mod v2 {
+/// #   use super::*;
+///     #[derive(CustomResource, Deserialize, Serialize, Clone, Debug, JsonSchema)]
+///     #[kube(group = "example.com", version = "v2", kind = "Gadget", status = "GadgetStatus")]
+///     #[kube(status_from = "super::v1::Gadget")]
+///     pub struct GadgetSpec {}
+///
+///     #[derive(Deserialize, Serialize, Clone, Debug, JsonSchema)]
+///     pub struct GadgetStatus {
+///         pub phase: String,
+///     }
+///
+///     impl From<super::v1::GadgetStatus> for GadgetStatus {
+///         fn from(old: super::v1::GadgetStatus) -> Self {
+///             let phase = if old.ready { "Ready" } else { "Pending" };
+///             Self { phase: phase.into() }
+///         }
+///     }
+/// }
+///
+/// let mut obj = serde_json::json!({
+///     "apiVersion": "example.com/v1",
+///     "kind": "Gadget",
+///     "status": { "ready": true },
+/// });
+/// convert_status::<v2::Gadget>(&mut obj)?;
+/// assert_eq!(obj["status"], serde_json::json!({ "phase": "Ready" }));
+/// # Ok::<(), kube::core::conversion::StatusConversionError>(())
+/// ```
+///
 /// ## `#[kube(derive = "Trait")]`
 /// Adding `#[kube(derive = "PartialEq")]` is required if you want your generated
 /// top level type to be able to `#[derive(PartialEq)]`
@@ -120,6 +176,11 @@ mod resource;
 /// - `"derived"`: A `JsonSchema` implementation is automatically derived
 /// - `"manual"`: `JsonSchema` is not derived, but used when creating the `CustomResourceDefinition` object
 /// - `"disabled"`: No `JsonSchema` is used
+/// - `"lazy"`: Like `"derived"`, but everything except the schema is embedded as a single
+///   precomputed JSON string, and the resulting `CustomResourceDefinition` is built once and cached.
+///   This reduces the amount of code generated per type, which helps compile times in crates with many CRDs.
+///   The string is embedded uncompressed and parsed on the first call, and the schema is still derived
+///   at runtime, so this does not shrink the binary or move the schema into static data.
 ///
 /// This can be used to provide a completely custom schema, or to interact with third-party custom resources
 /// where you are not responsible for installing the `CustomResourceDefinition`.
@@ -192,6 +253,50 @@ mod resource;
 /// Inject a top level CEL validation rule for the top level generated struct.
 /// This attribute is for resources deriving [`CELSchema`] instead of [`schemars::JsonSchema`].
 ///
+/// ## Field attribute `#[kube(immutable)]`
+/// Marks a field of the spec struct as immutable.
+///
+/// This adds a transition rule rejecting changes to the field to the generated schema, and generates a
+/// `check_immutable(&old, &new)` function on the root struct applying the same check in code,
+/// e.g. for controllers validating updates outside of webhooks. Optional fields may be set once,
+/// but cannot be changed or unset afterwards. Field types must implement `PartialEq`.
+///
+/// ```ignore
+/// struct FooSpec {
+///     #[kube(immutable)]
+///     storage_class: String,
+/// }
+/// ```
+///
+/// ## `#[kube(strip_markdown)]`
+/// Strip markdown markup from the schema descriptions generated from doc comments.
+///
+/// Descriptions are shown as is by `kubectl explain`, so inline code, emphasis and links are reduced to their
+/// text, and code blocks are dropped. See `kube::core::schema::DescriptionRewriter`.
+///
+/// ## `#[kube(description_max_length = 200)]`
+/// Truncate the schema descriptions generated from doc comments on a word boundary.
+///
+/// ## `#[kube(deep_merge)]`
+/// Implement [`DeepMerge`] for the generated root type and the spec struct, so merge utilities work with
+/// the custom resource like with native types. The spec is merged field by field, so every field type
+/// must implement `DeepMerge`. The status struct needs its own implementation, e.g. with
+/// [`derive(DeepMerge)`](macro@DeepMerge).
+///
+/// [`DeepMerge`]: https://docs.rs/k8s-openapi/*/k8s_openapi/trait.DeepMerge.html
+///
+/// ## Field attribute `#[kube(description = "...")]`
+/// Overrides the schema description of a field of the spec struct, instead of using its doc comment.
+/// The description is used as is, even with `strip_markdown` or `description_max_length`.
+///
+/// ```ignore
+/// struct FooSpec {
+///     /// Number of replicas, see [`Scale`] for how it relates to `status.replicas`.
+///     #[kube(description = "Number of replicas to run")]
+///     replicas: i32,
+/// }
+/// ```
+///
 /// ## Example with all properties
 ///
 /// ```rust
@@ -257,9 +362,13 @@ mod resource;
 /// impl FooCrd {
 ///     pub fn new(name: &str, spec: FooSpec) -> Self { .. }
 ///     pub fn crd() -> CustomResourceDefinition { .. }
+///     pub fn json_schema_bundle() -> serde_json::Value { .. }
 /// }
 /// ```
 ///
+/// `json_schema_bundle` is only generated when the schema is derived, and returns standalone
+/// JSON Schema (draft 2020-12) documents for the spec and status structs, e.g. for IDE validation.
+///
 /// # Customizing Schemas
 /// Should you need to customize the schemas, you can use:
 /// - [Serde/Schemars Attributes](https://graham.cool/schemars/examples/3-schemars_attrs/) (no need to duplicate serde renames)
@@ -327,6 +436,9 @@ mod resource;
 /// If you need to maintain support for the old version for some time, then you have to repeat or continuously
 /// run steps 2 and 3. I.e. you probably need a **conversion webhook**.
 ///
+/// When versions use different status types, declare the conversions with `#[kube(status_from = "...")]`,
+/// so conversion webhooks can convert the status of objects between versions.
+///
 /// **NB**: kube does currently [not implement conversion webhooks yet](https://github.com/kube-rs/kube/issues/865).
 ///
 /// ## Debugging
@@ -398,6 +510,38 @@ pub fn derive_schema_validation(input: proc_macro::TokenStream) -> proc_macro::T
     cel_schema::derive_validated_schema(input.into()).into()
 }
 
+/// Implements [`k8s_openapi::DeepMerge`] for a struct or enum
+///
+/// [`k8s_openapi::DeepMerge`]: https://docs.rs/k8s-openapi/*/k8s_openapi/trait.DeepMerge.html
+///
+/// Structs are merged field by field, so every field type must implement `DeepMerge`. As in
+/// `k8s-openapi`, lists and enums are replaced as a whole, and maps are merged by key.
+/// `Option` fields are only changed by `Some`, and `Vec` fields are never appended to or deduplicated;
+/// lists that should be merged as sets or by key need a manual impl with
+/// [`merge_strategies::list`](https://docs.rs/k8s-openapi/*/k8s_openapi/merge_strategies/list/index.html).
+/// Use with `#[kube(deep_merge)]` on [`CustomResource`](macro@CustomResource) to merge whole resources.
+///
+/// ```rust
+/// use k8s_openapi::DeepMerge;
+/// use kube::DeepMerge;
+///
+/// #[derive(DeepMerge, Clone, Debug, PartialEq)]
+/// struct FooStatus {
+///     ready: Option<bool>,
+///     replicas: Option<i32>,
+/// }
+///
+/// let mut status = FooStatus { ready: Some(false), replicas: Some(1) };
+/// status.merge_from(FooStatus { ready: Some(true), replicas: None });
+/// assert_eq!(status, FooStatus { ready: Some(true), replicas: Some(1) });
+/// ```
+///
+/// The path of `k8s-openapi` can be set with `#[deep_merge(crates(k8s_openapi = "::k8s_openapi"))]`.
+#[proc_macro_derive(DeepMerge, attributes(deep_merge))]
+pub fn derive_deep_merge(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    deep_merge::derive(proc_macro2::TokenStream::from(input)).into()
+}
+
 /// A custom derive for inheriting Resource impl for the type.
 ///
 /// This will generate a [`kube::Resource`] trait implementation,
@@ -434,3 +434,234 @@ fn flattening() {
     assert_eq!(spec.x_kubernetes_preserve_unknown_fields, Some(true));
     assert_eq!(spec.additional_properties, None);
 }
+
+// Each case derives the same custom resource with a derived and a lazy schema, whose CRDs must match
+macro_rules! schema_mode_cases {
+    ($($case:ident { $($attrs:tt)* })*) => {
+        $(
+            mod $case {
+                pub mod derived {
+                    use super::super::*;
+
+                    #[derive(CustomResource, Deserialize, Serialize, Clone, Debug, JsonSchema)]
+                    #[kube(group = "clux.dev", version = "v1", kind = "Widget", $($attrs)*)]
+                    pub struct WidgetSpec {
+                        size: i32,
+                        name: Option<String>,
+                    }
+                }
+
+                pub mod lazy {
+                    use super::super::*;
+
+                    #[derive(CustomResource, Deserialize, Serialize, Clone, Debug, JsonSchema)]
+                    #[kube(group = "clux.dev", version = "v1", kind = "Widget", schema = "lazy", $($attrs)*)]
+                    pub struct WidgetSpec {
+                        size: i32,
+                        name: Option<String>,
+                    }
+                }
+            }
+        )*
+
+        #[test]
+        fn lazy_schema_matches_derived() {
+            use kube::core::CustomResourceExt;
+            $(
+                assert_json_eq!($case::lazy::Widget::crd(), $case::derived::Widget::crd());
+                // cached after the first call
+                assert_eq!($case::lazy::Widget::crd(), $case::lazy::Widget::crd());
+            )*
+        }
+    };
+}
+
+schema_mode_cases! {
+    minimal {}
+    names { plural = "widgetz", shortname = "wd", category = "clux" }
+    full {
+        namespaced,
+        category = "clux",
+        shortname = "wd",
+        deprecated = "my warning",
+        annotation("clux.dev", "cluxingv1"),
+        label("clux.dev", "cluxingv1"),
+        printcolumn = r#"{"name":"Size", "type":"integer", "jsonPath":".spec.size"}"#,
+        status = "WidgetStatus",
+        scale(spec_replicas_path = ".spec.size", status_replicas_path = ".status.size"),
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, JsonSchema)]
+struct WidgetStatus {
+    size: i32,
+}
+
+#[test]
+fn json_schema_bundle_exports_spec_and_status() {
+    let bundle = full::derived::Widget::json_schema_bundle();
+    let spec = &bundle["spec"];
+    assert_eq!(spec["$schema"], "https://json-schema.org/draft/2020-12/schema");
+    assert_eq!(spec["title"], "WidgetSpec");
+    assert_eq!(spec["required"], serde_json::json!(["size"]));
+    assert_eq!(bundle["status"]["properties"]["size"]["type"], "integer");
+}
+
+#[derive(CustomResource, Deserialize, Serialize, Clone, Debug, JsonSchema)]
+#[kube(group = "clux.dev", version = "v1", kind = "Volume", namespaced)]
+#[serde(rename_all = "camelCase")]
+struct VolumeSpec {
+    #[kube(immutable)]
+    storage_class: String,
+    #[kube(immutable)]
+    claim_ref: Option<String>,
+    size: i32,
+}
+
+#[test]
+fn immutable_fields_emit_rules_and_checks() {
+    use kube::core::{CustomResourceExt, ImmutableFieldError};
+
+    let crd = serde_json::to_value(Volume::crd()).unwrap();
+    let schema = &crd["spec"]["versions"][0]["schema"]["openAPIV3Schema"];
+    assert_eq!(
+        schema["x-kubernetes-validations"],
+        serde_json::json!([
+            {
+                "rule": "self.spec.storageClass == oldSelf.spec.storageClass",
+                "message": "spec.storageClass is immutable",
+            },
+            {
+                "rule": "!has(oldSelf.spec.claimRef) || (has(self.spec.claimRef) && self.spec.claimRef == oldSelf.spec.claimRef)",
+                "message": "spec.claimRef is immutable",
+            },
+        ])
+    );
+
+    let old = Volume::new("data", VolumeSpec {
+        storage_class: "ssd".into(),
+        claim_ref: None,
+        size: 1,
+    });
+    let mut new = old.clone();
+    new.spec.size = 2;
+    new.spec.claim_ref = Some("claim".into());
+    assert_eq!(Volume::check_immutable(&old, &new), Ok(()));
+
+    let mut changed = new.clone();
+    changed.spec.claim_ref = None;
+    assert_eq!(
+        Volume::check_immutable(&new, &changed),
+        Err(ImmutableFieldError {
+            field: "spec.claimRef"
+        })
+    );
+    changed.spec.storage_class = "hdd".into();
+    assert_eq!(
+        Volume::check_immutable(&new, &changed).unwrap_err().to_string(),
+        "spec.storageClass is immutable"
+    );
+}
+
+#[derive(CustomResource, Deserialize, Serialize, Clone, Debug, JsonSchema)]
+#[kube(
+    group = "clux.dev",
+    version = "v1",
+    kind = "Gadget",
+    namespaced,
+    strip_markdown,
+    description_max_length = 40
+)]
+#[serde(rename_all = "camelCase")]
+struct GadgetSpec {
+    /// The `size` of the **gadget**, see [`GadgetSpec`].
+    size: i32,
+    /// A very long description that goes on and on about the color of the gadget.
+    color: String,
+    /// Number of replicas, see [`Scale`].
+    #[kube(description = "Number of `replicas` to run")]
+    replicas: i32,
+}
+
+#[test]
+fn descriptions_are_rewritten_and_overridden() {
+    use kube::core::CustomResourceExt;
+
+    let crd = serde_json::to_value(Gadget::crd()).unwrap();
+    let spec = &crd["spec"]["versions"][0]["schema"]["openAPIV3Schema"]["properties"]["spec"]["properties"];
+    assert_eq!(
+        spec["size"]["description"],
+        "The size of the gadget, see GadgetSpec."
+    );
+    assert_eq!(
+        spec["color"]["description"],
+        "A very long description that goes on..."
+    );
+    assert_eq!(spec["replicas"]["description"], "Number of `replicas` to run");
+}
+
+mod widget_v1 {
+    use super::*;
+
+    #[derive(CustomResource, Serialize, Deserialize, Debug, Clone, JsonSchema)]
+    #[kube(group = "clux.dev", version = "v1", kind = "Widget", status = "WidgetStatus")]
+    #[kube(status_from = "super::widget_v2::Widget")]
+    pub struct WidgetSpec {}
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+    pub struct WidgetStatus {
+        pub ready: bool,
+    }
+
+    impl From<super::widget_v2::WidgetStatus> for WidgetStatus {
+        fn from(status: super::widget_v2::WidgetStatus) -> Self {
+            Self {
+                ready: status.phase == "Ready",
+            }
+        }
+    }
+}
+
+mod widget_v2 {
+    use super::*;
+
+    #[derive(CustomResource, Serialize, Deserialize, Debug, Clone, JsonSchema)]
+    #[kube(group = "clux.dev", version = "v2", kind = "Widget", status = "WidgetStatus")]
+    #[kube(status_from = "super::widget_v1::Widget")]
+    pub struct WidgetSpec {}
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+    pub struct WidgetStatus {
+        pub phase: String,
+    }
+
+    impl From<super::widget_v1::WidgetStatus> for WidgetStatus {
+        fn from(status: super::widget_v1::WidgetStatus) -> Self {
+            let phase = if status.ready { "Ready" } else { "Pending" };
+            Self { phase: phase.into() }
+        }
+    }
+}
+
+#[test]
+fn status_converts_between_versions() {
+    use kube::core::conversion::{convert_status, ConvertStatus, StatusConversionError};
+
+    let mut obj = serde_json::json!({
+        "apiVersion": "clux.dev/v1",
+        "kind": "Widget",
+        "status": { "ready": true },
+    });
+    convert_status::<widget_v2::Widget>(&mut obj).unwrap();
+    assert_eq!(obj["status"], serde_json::json!({ "phase": "Ready" }));
+
+    obj["apiVersion"] = "clux.dev/v2".into();
+    convert_status::<widget_v1::Widget>(&mut obj).unwrap();
+    assert_eq!(obj["status"], serde_json::json!({ "ready": true }));
+
+    // same version is passed through, unknown versions are rejected
+    let status = widget_v1::Widget::status_from_version("v1", serde_json::json!({ "ready": false }));
+    assert_eq!(status.unwrap(), widget_v1::WidgetStatus { ready: false });
+    let status = widget_v1::Widget::status_from_version("v3", serde_json::json!({}));
+    assert!(matches!(status, Err(StatusConversionError::UnsupportedVersion(v)) if v == "v3"));
+}
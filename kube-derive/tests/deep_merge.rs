@@ -0,0 +1,69 @@
+use std::collections::BTreeMap;
+
+use k8s_openapi::DeepMerge;
+use kube_derive::{CustomResource, DeepMerge};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(CustomResource, Serialize, Deserialize, Default, Debug, PartialEq, Clone, JsonSchema)]
+#[kube(group = "clux.dev", version = "v1", kind = "Foo", namespaced)]
+#[kube(status = "FooStatus", deep_merge, derive = "PartialEq")]
+struct FooSpec {
+    replicas: Option<i32>,
+    image: Option<String>,
+    labels: BTreeMap<String, String>,
+    args: Vec<String>,
+}
+
+#[derive(DeepMerge, Serialize, Deserialize, Default, Debug, PartialEq, Clone, JsonSchema)]
+struct FooStatus {
+    ready: Option<bool>,
+    phase: Option<Phase>,
+}
+
+#[derive(DeepMerge, Serialize, Deserialize, Debug, PartialEq, Clone, JsonSchema)]
+enum Phase {
+    Pending,
+    Running,
+}
+
+#[test]
+fn custom_resources_are_merged_like_native_types() {
+    let mut foo = Foo::new("foo", FooSpec {
+        replicas: Some(1),
+        image: Some("nginx:1".into()),
+        labels: BTreeMap::from([("app".into(), "web".into())]),
+        args: vec!["--verbose".into()],
+    });
+    foo.status = Some(FooStatus {
+        ready: Some(false),
+        phase: Some(Phase::Pending),
+    });
+
+    let mut patch = Foo::new("foo", FooSpec {
+        replicas: None,
+        image: Some("nginx:2".into()),
+        labels: BTreeMap::from([("tier".into(), "frontend".into())]),
+        args: vec![],
+    });
+    patch.status = Some(FooStatus {
+        ready: None,
+        phase: Some(Phase::Running),
+    });
+    foo.merge_from(patch);
+
+    assert_eq!(foo.spec, FooSpec {
+        replicas: Some(1),
+        image: Some("nginx:2".into()),
+        labels: BTreeMap::from([("app".into(), "web".into()), ("tier".into(), "frontend".into())]),
+        args: vec![],
+    });
+    assert_eq!(
+        foo.status,
+        Some(FooStatus {
+            ready: Some(false),
+            phase: Some(Phase::Running),
+        })
+    );
+    assert_eq!(foo.metadata.name.as_deref(), Some("foo"));
+}
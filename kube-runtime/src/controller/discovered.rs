@@ -0,0 +1,156 @@
+use std::{fmt::Debug, sync::Arc};
+
+use futures::{Stream, TryFuture};
+use kube_client::{
+    api::{Api, ApiResource, DynamicObject, GroupVersionKind},
+    discovery, Client,
+};
+
+use super::{Action, Config, Controller, Error};
+use crate::{reflector::ObjectRef, watcher};
+
+/// A [`Controller`] for a kind that is only known at runtime
+///
+/// Resolves [`GroupVersionKind`]s through discovery, and runs the regular controller machinery
+/// over [`DynamicObject`]s. This makes it possible to write generic operators, e.g. for policies
+/// or templating, that are configured with the kinds to manage rather than compiled against them.
+///
+/// All kinds are watched across all namespaces. For more control over the watched scope, resolve the
+/// [`ApiResource`] and use [`Controller::new_with`] and friends directly.
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use futures::StreamExt;
+/// use kube::{
+///     api::{DynamicObject, GroupVersionKind},
+///     runtime::{controller::Action, watcher, DynamicController},
+///     Client,
+/// };
+/// # async fn reconcile(_: Arc<DynamicObject>, _: Arc<()>) -> Result<Action, kube::Error> { Ok(Action::await_change()) }
+/// # fn error_policy(_: Arc<DynamicObject>, _: &kube::Error, _: Arc<()>) -> Action { Action::await_change() }
+/// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+/// # let client: Client = todo!();
+/// let policy = GroupVersionKind::gvk("policies.example.com", "v1", "Policy");
+/// let deployment = GroupVersionKind::gvk("apps", "v1", "Deployment");
+/// DynamicController::new(client, &policy, watcher::Config::default())
+///     .await?
+///     .owns(&deployment, watcher::Config::default())
+///     .await?
+///     .run(reconcile, error_policy, Arc::new(()))
+///     .for_each(|_| std::future::ready(()))
+///     .await;
+/// # Ok(())
+/// # }
+/// ```
+pub struct DynamicController {
+    client: Client,
+    resource: ApiResource,
+    controller: Controller<DynamicObject>,
+}
+
+impl DynamicController {
+    /// Create a controller for the kind `gvk`
+    ///
+    /// # Errors
+    ///
+    /// Fails when `gvk` cannot be resolved through discovery.
+    pub async fn new(
+        client: Client,
+        gvk: &GroupVersionKind,
+        wc: watcher::Config,
+    ) -> kube_client::Result<Self> {
+        let resource = Self::resolve(&client, gvk).await?;
+        let api = Api::all_with(client.clone(), &resource);
+        let controller = Controller::new_with(api, wc, resource.clone());
+        Ok(Self {
+            client,
+            resource,
+            controller,
+        })
+    }
+
+    /// The resolved resource of the reconciled kind
+    #[must_use]
+    pub fn api_resource(&self) -> &ApiResource {
+        &self.resource
+    }
+
+    /// Specify a child kind `gvk` which the reconciled kind owns and should be watched
+    ///
+    /// Same as [`Controller::owns`].
+    ///
+    /// # Errors
+    ///
+    /// Fails when `gvk` cannot be resolved through discovery.
+    pub async fn owns(mut self, gvk: &GroupVersionKind, wc: watcher::Config) -> kube_client::Result<Self> {
+        let child = Self::resolve(&self.client, gvk).await?;
+        let api = Api::all_with(self.client.clone(), &child);
+        self.controller = self.controller.owns_with(api, child, wc);
+        Ok(self)
+    }
+
+    /// Specify a kind `gvk` which the reconciled kind has a custom relation to and should be watched
+    ///
+    /// Same as [`Controller::watches`].
+    ///
+    /// # Errors
+    ///
+    /// Fails when `gvk` cannot be resolved through discovery.
+    pub async fn watches<I>(
+        mut self,
+        gvk: &GroupVersionKind,
+        wc: watcher::Config,
+        mapper: impl Fn(DynamicObject) -> I + Sync + Send + 'static,
+    ) -> kube_client::Result<Self>
+    where
+        I: 'static + IntoIterator<Item = ObjectRef<DynamicObject>>,
+        I::IntoIter: Send,
+    {
+        let other = Self::resolve(&self.client, gvk).await?;
+        let api = Api::all_with(self.client.clone(), &other);
+        self.controller = self.controller.watches_with(api, other, wc, mapper);
+        Ok(self)
+    }
+
+    /// Specify the configuration for the controller's behavior
+    #[must_use]
+    pub fn with_config(mut self, config: Config) -> Self {
+        self.controller = self.controller.with_config(config);
+        self
+    }
+
+    /// Return the underlying [`Controller`], e.g. to use its other builder methods
+    #[must_use]
+    pub fn into_controller(self) -> Controller<DynamicObject> {
+        self.controller
+    }
+
+    /// Start the controller
+    ///
+    /// Same as [`Controller::run`].
+    pub fn run<ReconcilerFut, Ctx>(
+        self,
+        reconciler: impl FnMut(Arc<DynamicObject>, Arc<Ctx>) -> ReconcilerFut,
+        error_policy: impl Fn(Arc<DynamicObject>, &ReconcilerFut::Error, Arc<Ctx>) -> Action,
+        context: Arc<Ctx>,
+    ) -> impl Stream<Item = Result<(ObjectRef<DynamicObject>, Action), Error<ReconcilerFut::Error, watcher::Error>>>
+    where
+        ReconcilerFut: TryFuture<Ok = Action> + Send + 'static,
+        ReconcilerFut::Error: std::error::Error + Send + 'static,
+    {
+        self.controller.run(reconciler, error_policy, context)
+    }
+
+    async fn resolve(client: &Client, gvk: &GroupVersionKind) -> kube_client::Result<ApiResource> {
+        let (resource, _caps) = discovery::pinned_kind(client, gvk).await?;
+        Ok(resource)
+    }
+}
+
+impl Debug for DynamicController {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DynamicController")
+            .field("resource", &self.resource)
+            .finish_non_exhaustive()
+    }
+}
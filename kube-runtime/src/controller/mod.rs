@@ -35,9 +35,12 @@ use thiserror::Error;
 use tokio::{runtime::Handle, time::Instant};
 use tracing::{info_span, Instrument};
 
+mod discovered;
 mod future_hash_map;
 mod runner;
 
+pub use discovered::DynamicController;
+
 pub type RunnerError = runner::Error<reflector::store::WriterDropped>;
 
 #[derive(Debug, Error)]
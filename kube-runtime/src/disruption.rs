@@ -0,0 +1,200 @@
+//! Pacing of disruptive actions against [`PodDisruptionBudget`]s
+//!
+//! Maintenance controllers that drain nodes, restart pods or roll out changes should respect the
+//! budgets that cover the pods they are about to disrupt. Evictions are checked by the apiserver,
+//! but deleting pods directly is not, so controllers that delete pods or do other disruptive work
+//! need to check the budgets themselves.
+use std::{collections::BTreeMap, pin::pin};
+
+use futures::TryStreamExt;
+use k8s_openapi::api::policy::v1::PodDisruptionBudget;
+use kube_client::{
+    api::ListParams,
+    core::{ParseExpressionError, Selector, SelectorExt},
+    Api, ResourceExt,
+};
+use thiserror::Error;
+
+use crate::{
+    reflector::{self, store},
+    watcher,
+};
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("failed to list disruption budgets: {0}")]
+    List(#[source] kube_client::Error),
+    #[error("failed to watch disruption budgets: {0}")]
+    Watch(#[source] watcher::Error),
+    #[error("invalid selector in disruption budget {0}: {1}")]
+    InvalidSelector(String, #[source] ParseExpressionError),
+    #[error("disruption budget watch terminated")]
+    WatchTerminated,
+}
+
+/// How many pods with a set of labels may currently be disrupted
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Allowance {
+    /// The smallest `status.disruptionsAllowed` of the covering budgets, `None` if no budget covers the pods
+    pub disruptions_allowed: Option<i32>,
+    /// The names of the budgets that cover the pods
+    pub budgets: Vec<String>,
+}
+
+impl Allowance {
+    /// Compute the allowance for pods with `labels` from the budgets of their namespace
+    ///
+    /// Budgets whose status has not caught up with their spec yet allow no disruptions, like the
+    /// apiserver's eviction check.
+    ///
+    /// # Errors
+    ///
+    /// Fails when a budget has a selector that cannot be parsed.
+    pub fn from_budgets<'a>(
+        budgets: impl IntoIterator<Item = &'a PodDisruptionBudget>,
+        labels: &BTreeMap<String, String>,
+    ) -> Result<Self, Error> {
+        let mut allowance = Self::default();
+        for pdb in budgets {
+            // a budget without a selector selects no pods
+            let Some(selector) = pdb.spec.as_ref().and_then(|s| s.selector.clone()) else {
+                continue;
+            };
+            let selector =
+                Selector::try_from(selector).map_err(|e| Error::InvalidSelector(pdb.name_any(), e))?;
+            if !selector.matches(labels) {
+                continue;
+            }
+            let allowed = pdb
+                .status
+                .as_ref()
+                .filter(|s| s.observed_generation >= pdb.metadata.generation)
+                .map_or(0, |s| s.disruptions_allowed);
+            let smallest = allowance.disruptions_allowed.map_or(allowed, |a| a.min(allowed));
+            allowance.disruptions_allowed = Some(smallest);
+            allowance.budgets.push(pdb.name_any());
+        }
+        Ok(allowance)
+    }
+
+    /// Whether at least one of the pods may be disrupted
+    #[must_use]
+    pub fn is_allowed(&self) -> bool {
+        !matches!(self.disruptions_allowed, Some(allowed) if allowed <= 0)
+    }
+}
+
+/// Check how many pods with `labels` may currently be disrupted
+///
+/// `api` should be scoped to the namespace of the pods, since budgets only cover pods in their own namespace.
+///
+/// # Errors
+///
+/// Fails when the budgets cannot be listed, or have invalid selectors.
+pub async fn allowance(
+    api: &Api<PodDisruptionBudget>,
+    labels: &BTreeMap<String, String>,
+) -> Result<Allowance, Error> {
+    let budgets = api.list(&ListParams::default()).await.map_err(Error::List)?;
+    Allowance::from_budgets(&budgets.items, labels)
+}
+
+/// Wait until at least one pod with `labels` may be disrupted
+///
+/// Returns immediately when no budget covers the pods. Like
+/// [`await_condition`](crate::wait::await_condition), this does not add a timeout, so wrap it in
+/// [`tokio::time::timeout`] if desired.
+///
+/// ```no_run
+/// use std::collections::BTreeMap;
+/// use k8s_openapi::api::{core::v1::Pod, policy::v1::PodDisruptionBudget};
+/// use kube::{api::DeleteParams, runtime::disruption, Api, ResourceExt};
+/// # async fn wrapper(client: kube::Client, pod: Pod) -> Result<(), Box<dyn std::error::Error>> {
+/// let ns = pod.namespace().unwrap();
+/// let pdbs: Api<PodDisruptionBudget> = Api::namespaced(client.clone(), &ns);
+/// disruption::await_allowed(pdbs, pod.labels()).await?;
+/// Api::<Pod>::namespaced(client, &ns).delete(&pod.name_any(), &DeleteParams::default()).await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// Fails when the budgets cannot be watched, or have invalid selectors.
+pub async fn await_allowed(
+    api: Api<PodDisruptionBudget>,
+    labels: &BTreeMap<String, String>,
+) -> Result<Allowance, Error> {
+    let (reader, writer) = store::store();
+    let mut events = pin!(reflector::reflector(
+        writer,
+        watcher::watcher(api, watcher::Config::default())
+    ));
+    while let Some(event) = events.try_next().await.map_err(Error::Watch)? {
+        // only judge complete sets of budgets
+        if matches!(event, watcher::Event::Init | watcher::Event::InitApply(_)) {
+            continue;
+        }
+        let budgets = reader.state();
+        let allowance = Allowance::from_budgets(budgets.iter().map(AsRef::as_ref), labels)?;
+        if allowance.is_allowed() {
+            return Ok(allowance);
+        }
+    }
+    Err(Error::WatchTerminated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use k8s_openapi::{
+        api::policy::v1::{PodDisruptionBudgetSpec, PodDisruptionBudgetStatus},
+        apimachinery::pkg::apis::meta::v1::LabelSelector,
+    };
+    use kube_client::api::ObjectMeta;
+
+    fn pdb(name: &str, app: &str, allowed: i32, observed: i64) -> PodDisruptionBudget {
+        PodDisruptionBudget {
+            metadata: ObjectMeta {
+                name: Some(name.into()),
+                generation: Some(2),
+                ..ObjectMeta::default()
+            },
+            spec: Some(PodDisruptionBudgetSpec {
+                selector: Some(LabelSelector {
+                    match_labels: Some([("app".to_string(), app.to_string())].into()),
+                    ..LabelSelector::default()
+                }),
+                ..PodDisruptionBudgetSpec::default()
+            }),
+            status: Some(PodDisruptionBudgetStatus {
+                disruptions_allowed: allowed,
+                observed_generation: Some(observed),
+                ..PodDisruptionBudgetStatus::default()
+            }),
+        }
+    }
+
+    #[test]
+    fn allowance_is_smallest_of_matching_budgets() {
+        let labels = [("app".to_string(), "web".to_string())].into();
+        let budgets = [pdb("a", "web", 2, 2), pdb("b", "web", 1, 2), pdb("c", "db", 0, 2)];
+        let allowance = Allowance::from_budgets(&budgets, &labels).unwrap();
+        assert_eq!(allowance, Allowance {
+            disruptions_allowed: Some(1),
+            budgets: vec!["a".into(), "b".into()],
+        });
+        assert!(allowance.is_allowed());
+
+        // stale budgets allow nothing
+        let stale = Allowance::from_budgets(&[pdb("a", "web", 2, 1)], &labels).unwrap();
+        assert_eq!(stale.disruptions_allowed, Some(0));
+        assert!(!stale.is_allowed());
+
+        // uncovered pods are not limited
+        let uncovered = Allowance::from_budgets(&budgets[2..], &labels).unwrap();
+        assert_eq!(uncovered, Allowance::default());
+        assert!(uncovered.is_allowed());
+    }
+}
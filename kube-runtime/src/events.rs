@@ -2,7 +2,7 @@
 use std::{
     collections::HashMap,
     hash::{Hash, Hasher},
-    sync::Arc,
+    sync::{Arc, Mutex},
 };
 
 use k8s_openapi::{
@@ -24,6 +24,7 @@ const CACHE_TTL: Duration = Duration::minutes(6);
 /// Minimal event type for publishing through [`Recorder::publish`].
 ///
 /// All string fields must be human readable.
+#[derive(Clone, Debug)]
 pub struct Event {
     /// The event severity.
     ///
@@ -218,11 +219,50 @@ impl From<&str> for Reporter {
 ///   resources: ["events"]
 ///   verbs: ["create", "patch"]
 /// ```
+///
+/// ## Buffering
+///
+/// Bursty reconcilers can avoid a write to the apiserver for every event by buffering them with
+/// [`Recorder::with_buffer`]. Identical events are then coalesced in memory until the next
+/// [`Recorder::flush`], and events that don't fit into the buffer are dropped.
 #[derive(Clone)]
 pub struct Recorder {
     client: Client,
     reporter: Reporter,
     cache: Arc<RwLock<HashMap<EventKey, K8sEvent>>>,
+    queue: Option<Arc<Mutex<EventQueue>>>,
+}
+
+/// Events waiting for [`Recorder::flush`]
+struct EventQueue {
+    capacity: usize,
+    events: Vec<QueuedEvent>,
+    dropped: u64,
+}
+
+struct QueuedEvent {
+    key: EventKey,
+    event: Event,
+    reference: ObjectReference,
+    occurrences: i32,
+}
+
+impl EventQueue {
+    fn push(&mut self, key: EventKey, event: &Event, reference: &ObjectReference) {
+        if let Some(queued) = self.events.iter_mut().find(|queued| queued.key == key) {
+            queued.occurrences += 1;
+        } else if self.events.len() < self.capacity {
+            self.events.push(QueuedEvent {
+                key,
+                event: event.clone(),
+                reference: reference.clone(),
+                occurrences: 1,
+            });
+        } else {
+            self.dropped += 1;
+            tracing::debug!(reason = %event.reason, "event buffer full, dropping event");
+        }
+    }
 }
 
 impl Recorder {
@@ -238,7 +278,64 @@ impl Recorder {
             client,
             reporter,
             cache,
+            queue: None,
+        }
+    }
+
+    /// Buffer up to `capacity` distinct events in memory instead of publishing them immediately
+    ///
+    /// [`Recorder::publish`] then only queues events, and identical events (see [`Recorder::publish`])
+    /// are coalesced into one queue entry. Queued events are sent by [`Recorder::flush`].
+    /// Events that arrive while the buffer is full are dropped, and counted in [`Recorder::dropped_events`].
+    #[must_use]
+    pub fn with_buffer(mut self, capacity: usize) -> Self {
+        self.queue = Some(Arc::new(Mutex::new(EventQueue {
+            capacity,
+            events: Vec::new(),
+            dropped: 0,
+        })));
+        self
+    }
+
+    /// The number of distinct events waiting to be flushed
+    #[must_use]
+    pub fn queued_events(&self) -> usize {
+        self.queue.as_ref().map_or(0, |queue| {
+            queue.lock().expect("event queue poisoned").events.len()
+        })
+    }
+
+    /// The number of events dropped because the buffer was full
+    #[must_use]
+    pub fn dropped_events(&self) -> u64 {
+        self.queue
+            .as_ref()
+            .map_or(0, |queue| queue.lock().expect("event queue poisoned").dropped)
+    }
+
+    /// Publish all buffered events
+    ///
+    /// Does nothing for recorders without a buffer. All queued events are attempted, and events that
+    /// fail to publish are not retried.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first [`Error`](`kube_client::Error`) that an event was rejected with.
+    pub async fn flush(&self) -> Result<(), kube_client::Error> {
+        let Some(queue) = &self.queue else {
+            return Ok(());
+        };
+        let events = std::mem::take(&mut queue.lock().expect("event queue poisoned").events);
+        let mut result = Ok(());
+        for queued in events {
+            let sent = self
+                .send(&queued.event, &queued.reference, queued.occurrences)
+                .await;
+            if let (Err(err), Ok(())) = (sent, &result) {
+                result = Err(err);
+            }
         }
+        result
     }
 
     /// Builds unique event key based on reportingController, reportingInstance, regarding, reason
@@ -306,7 +403,26 @@ impl Recorder {
     /// # Errors
     ///
     /// Returns an [`Error`](`kube_client::Error`) if the event is rejected by Kubernetes.
+    /// Buffered recorders only return errors from [`Recorder::flush`].
     pub async fn publish(&self, ev: &Event, reference: &ObjectReference) -> Result<(), kube_client::Error> {
+        if let Some(queue) = &self.queue {
+            let key = self.get_event_key(ev, reference);
+            queue
+                .lock()
+                .expect("event queue poisoned")
+                .push(key, ev, reference);
+            return Ok(());
+        }
+        self.send(ev, reference, 1).await
+    }
+
+    /// Publish an event that occurred `occurrences` times
+    async fn send(
+        &self,
+        ev: &Event,
+        reference: &ObjectReference,
+        occurrences: i32,
+    ) -> Result<(), kube_client::Error> {
         let now = Utc::now();
 
         // gc past events older than now + CACHE_TTL
@@ -321,25 +437,34 @@ impl Recorder {
         });
 
         let key = self.get_event_key(ev, reference);
-        let event = match self.cache.read().await.get(&key) {
+        let (event, exists) = match self.cache.read().await.get(&key) {
             Some(e) => {
-                let count = if let Some(s) = &e.series { s.count + 1 } else { 2 };
+                let count = e.series.as_ref().map_or(1, |s| s.count) + occurrences;
                 let series = EventSeries {
                     count,
                     last_observed_time: MicroTime(now),
                 };
                 let mut event = e.clone();
                 event.series = Some(series);
-                event
+                (event, true)
+            }
+            None => {
+                let mut event = self.generate_event(ev, reference);
+                if occurrences > 1 {
+                    event.series = Some(EventSeries {
+                        count: occurrences,
+                        last_observed_time: MicroTime(now),
+                    });
+                }
+                (event, false)
             }
-            None => self.generate_event(ev, reference),
         };
 
         let events = Api::namespaced(
             self.client.clone(),
             reference.namespace.as_ref().unwrap_or(&"default".to_string()),
         );
-        if event.series.is_some() {
+        if exists {
             events
                 .patch(&event.name_any(), &PatchParams::default(), &Patch::Merge(&event))
                 .await?;
@@ -359,6 +484,8 @@ impl Recorder {
 mod test {
     use super::{Event, EventKey, EventType, Recorder, Reference, Reporter};
 
+    use k8s_openapi::api::core::v1::ObjectReference;
+
     use k8s_openapi::{
         api::{
             core::v1::{ComponentStatus, Service},
@@ -369,6 +496,39 @@ mod test {
     };
     use kube::{Api, Client, Resource};
 
+    #[tokio::test]
+    async fn buffered_recorder_coalesces_and_drops_events() -> Result<(), Box<dyn std::error::Error>> {
+        // nothing listens here, so flushing fails without a cluster
+        let client = Client::try_from(kube::Config::new("http://127.0.0.1:1".parse()?))?;
+        let recorder = Recorder::new(client, "kube".into()).with_buffer(2);
+        let event = |reason: &str| Event {
+            type_: EventType::Normal,
+            reason: reason.into(),
+            note: None,
+            action: "Test".into(),
+            secondary: None,
+        };
+        let reference = ObjectReference {
+            name: Some("obj".into()),
+            namespace: Some("default".into()),
+            ..ObjectReference::default()
+        };
+
+        for reason in ["First", "First", "Second", "Third"] {
+            recorder.publish(&event(reason), &reference).await?;
+        }
+        assert_eq!(recorder.queued_events(), 2);
+        assert_eq!(recorder.dropped_events(), 1);
+        assert_eq!(
+            recorder.queue.as_ref().unwrap().lock().unwrap().events[0].occurrences,
+            2
+        );
+
+        assert!(recorder.flush().await.is_err());
+        assert_eq!(recorder.queued_events(), 0);
+        Ok(())
+    }
+
     #[tokio::test]
     #[ignore = "needs cluster (creates an event for the default kubernetes service)"]
     async fn event_recorder_attaches_events() -> Result<(), Box<dyn std::error::Error>> {
@@ -0,0 +1,748 @@
+//! Leader election for running several replicas of an operator
+//!
+//! Replicas compete for a shared [`Lock`], and only the current holder should reconcile. The lock is
+//! a [`Lease`] by default, but can be kept in a [`ConfigMap`] for clusters where the
+//! `coordination.k8s.io` API group is disabled, or where tenants are not allowed to manage leases.
+//! Both backends store the same [`LeaderRecord`], using the format of `client-go` resource locks.
+//!
+//! Large controllers can shorten failovers with a [`Handover`]: the outgoing leader publishes where
+//! it left off with [`LeaderElector::step_down_with_handover`], e.g. the `resourceVersion`s of its
+//! caches, and the next leader picks it up with [`LeaderElector::take_handover`] to start its
+//! watchers from there with a [`seeded_watcher`](crate::watcher::seeded_watcher).
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use async_stream::stream;
+use futures::Stream;
+use k8s_openapi::{
+    api::{
+        coordination::v1::{Lease, LeaseSpec},
+        core::v1::ConfigMap,
+    },
+    apimachinery::pkg::apis::meta::v1::{MicroTime, Time},
+    chrono::{self, Utc},
+};
+use kube_client::{
+    api::{ObjectMeta, PostParams},
+    Api, Client,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Annotation that holds the [`LeaderRecord`] of a [`ConfigMap`] lock
+pub const LEADER_ANNOTATION: &str = "control-plane.alpha.kubernetes.io/leader";
+
+/// Annotation of the lock object that holds the [`Handover`] of the previous leader
+pub const HANDOVER_ANNOTATION: &str = "kube.rs/leader-handover";
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("failed to get lock: {0}")]
+    GetLock(#[source] kube_client::Error),
+    #[error("failed to create lock: {0}")]
+    CreateLock(#[source] kube_client::Error),
+    #[error("failed to update lock: {0}")]
+    UpdateLock(#[source] kube_client::Error),
+    #[error("failed to discover lock backend: {0}")]
+    Discovery(#[source] kube_client::Error),
+    #[error("invalid leader record: {0}")]
+    InvalidRecord(#[source] serde_json::Error),
+    #[error("invalid handover: {0}")]
+    InvalidHandover(#[source] serde_json::Error),
+}
+
+/// The state of a [`Lock`], as stored in the cluster
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaderRecord {
+    /// Identity of the current leader, empty when the lock has been released
+    #[serde(default)]
+    pub holder_identity: String,
+    /// How long the leadership is valid after the last renewal
+    #[serde(default)]
+    pub lease_duration_seconds: i32,
+    /// When the current leader acquired the lock
+    pub acquire_time: Option<Time>,
+    /// When the current leader last renewed the lock
+    pub renew_time: Option<Time>,
+    /// How many times the leadership changed hands
+    #[serde(default)]
+    pub leader_transitions: i32,
+}
+
+impl LeaderRecord {
+    /// The current holder, if the lock is held and has not expired at `now`
+    ///
+    /// This compares `now` with the `renew_time` that the holder wrote with its own clock. The
+    /// [`LeaderElector`] does not rely on it, since the clocks of replicas may be skewed.
+    #[must_use]
+    pub fn holder(&self, now: chrono::DateTime<Utc>) -> Option<&str> {
+        let renewed = self.renew_time.as_ref()?.0;
+        let expiry = renewed + chrono::Duration::seconds(self.lease_duration_seconds.into());
+        (!self.holder_identity.is_empty() && expiry > now).then_some(self.holder_identity.as_str())
+    }
+
+    fn from_lease(lease: &Lease) -> Self {
+        let spec = lease.spec.clone().unwrap_or_default();
+        Self {
+            holder_identity: spec.holder_identity.unwrap_or_default(),
+            lease_duration_seconds: spec.lease_duration_seconds.unwrap_or_default(),
+            acquire_time: spec.acquire_time.map(|t| Time(t.0)),
+            renew_time: spec.renew_time.map(|t| Time(t.0)),
+            leader_transitions: spec.lease_transitions.unwrap_or_default(),
+        }
+    }
+
+    fn to_lease_spec(&self) -> LeaseSpec {
+        LeaseSpec {
+            holder_identity: Some(self.holder_identity.clone()),
+            lease_duration_seconds: Some(self.lease_duration_seconds),
+            acquire_time: self.acquire_time.as_ref().map(|t| MicroTime(t.0)),
+            renew_time: self.renew_time.as_ref().map(|t| MicroTime(t.0)),
+            lease_transitions: Some(self.leader_transitions),
+            ..LeaseSpec::default()
+        }
+    }
+
+    fn from_config_map(cm: &ConfigMap) -> Result<Self, Error> {
+        match cm
+            .metadata
+            .annotations
+            .as_ref()
+            .and_then(|a| a.get(LEADER_ANNOTATION))
+        {
+            Some(record) => serde_json::from_str(record).map_err(Error::InvalidRecord),
+            None => Ok(Self::default()),
+        }
+    }
+
+    fn to_annotation(&self) -> Result<String, Error> {
+        serde_json::to_string(self).map_err(Error::InvalidRecord)
+    }
+}
+
+/// State that an outgoing leader leaves for the next one
+///
+/// The contents are up to the application; typically the `resourceVersion`s that the caches of the
+/// leader were current at, and where a snapshot of the cached objects can be found.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Handover {
+    /// Identity of the leader that published the handover
+    #[serde(default)]
+    pub from: String,
+    /// When the handover was published
+    pub published: Option<Time>,
+    /// Where the next leader can find a snapshot of the state, e.g. a `ConfigMap` or a file path
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<String>,
+    /// The last seen `resourceVersion`s, keyed by names of the application's choosing
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub resource_versions: BTreeMap<String, String>,
+}
+
+impl Handover {
+    /// Set where the next leader can find a snapshot of the state
+    #[must_use]
+    pub fn snapshot(mut self, snapshot: impl Into<String>) -> Self {
+        self.snapshot = Some(snapshot.into());
+        self
+    }
+
+    /// Record the last seen `resourceVersion` under `name`
+    #[must_use]
+    pub fn resource_version(mut self, name: impl Into<String>, resource_version: impl Into<String>) -> Self {
+        self.resource_versions
+            .insert(name.into(), resource_version.into());
+        self
+    }
+}
+
+/// The object that replicas compete for
+///
+/// All replicas must use the same backend and name for the election to be meaningful.
+#[derive(Clone, Debug)]
+pub struct Lock {
+    name: String,
+    backend: Backend,
+}
+
+#[derive(Clone, Debug)]
+enum Backend {
+    Lease(Api<Lease>),
+    ConfigMap(Api<ConfigMap>),
+}
+
+/// A lock object as read from the cluster, kept around for optimistic concurrency on updates
+enum Held {
+    Lease(Lease),
+    ConfigMap(ConfigMap),
+}
+
+impl Held {
+    fn annotations_mut(&mut self) -> &mut BTreeMap<String, String> {
+        let meta = match self {
+            Self::Lease(lease) => &mut lease.metadata,
+            Self::ConfigMap(cm) => &mut cm.metadata,
+        };
+        meta.annotations.get_or_insert_with(Default::default)
+    }
+}
+
+impl Lock {
+    /// A lock kept in the [`Lease`] `name` in `namespace`
+    #[must_use]
+    pub fn lease(client: Client, namespace: &str, name: &str) -> Self {
+        Self {
+            name: name.into(),
+            backend: Backend::Lease(Api::namespaced(client, namespace)),
+        }
+    }
+
+    /// A lock kept in an annotation of the [`ConfigMap`] `name` in `namespace`
+    ///
+    /// Use this when the `coordination.k8s.io` API group is unavailable.
+    #[must_use]
+    pub fn config_map(client: Client, namespace: &str, name: &str) -> Self {
+        Self {
+            name: name.into(),
+            backend: Backend::ConfigMap(Api::namespaced(client, namespace)),
+        }
+    }
+
+    /// A [`Lease`] lock if the cluster serves `coordination.k8s.io/v1`, and a [`ConfigMap`] lock otherwise
+    ///
+    /// # Errors
+    ///
+    /// Fails when the API groups of the cluster cannot be listed.
+    pub async fn detect(client: Client, namespace: &str, name: &str) -> Result<Self, Error> {
+        let groups = client.list_api_groups().await.map_err(Error::Discovery)?;
+        let has_leases = groups
+            .groups
+            .iter()
+            .any(|g| g.name == "coordination.k8s.io" && g.versions.iter().any(|v| v.version == "v1"));
+        if has_leases {
+            Ok(Self::lease(client, namespace, name))
+        } else {
+            Ok(Self::config_map(client, namespace, name))
+        }
+    }
+
+    /// The name of the lock object
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn get(&self) -> Result<Option<(LeaderRecord, Held)>, Error> {
+        match &self.backend {
+            Backend::Lease(api) => {
+                let lease = api.get_opt(&self.name).await.map_err(Error::GetLock)?;
+                Ok(lease.map(|l| (LeaderRecord::from_lease(&l), Held::Lease(l))))
+            }
+            Backend::ConfigMap(api) => match api.get_opt(&self.name).await.map_err(Error::GetLock)? {
+                Some(cm) => Ok(Some((LeaderRecord::from_config_map(&cm)?, Held::ConfigMap(cm)))),
+                None => Ok(None),
+            },
+        }
+    }
+
+    /// Create the lock object, returns false if someone else created it first
+    async fn create(&self, record: &LeaderRecord) -> Result<bool, Error> {
+        let meta = ObjectMeta {
+            name: Some(self.name.clone()),
+            ..ObjectMeta::default()
+        };
+        let pp = PostParams::default();
+        let res = match &self.backend {
+            Backend::Lease(api) => {
+                let lease = Lease {
+                    metadata: meta,
+                    spec: Some(record.to_lease_spec()),
+                };
+                api.create(&pp, &lease).await.map(|_| ())
+            }
+            Backend::ConfigMap(api) => {
+                let mut cm = ConfigMap {
+                    metadata: meta,
+                    ..ConfigMap::default()
+                };
+                set_annotation(&mut cm, record.to_annotation()?);
+                api.create(&pp, &cm).await.map(|_| ())
+            }
+        };
+        unless_conflict(res).map_err(Error::CreateLock)
+    }
+
+    /// Replace the lock object, returns false if it was changed since it was read
+    async fn update(&self, held: Held, record: &LeaderRecord) -> Result<bool, Error> {
+        let pp = PostParams::default();
+        let res = match (&self.backend, held) {
+            (Backend::Lease(api), Held::Lease(mut lease)) => {
+                lease.spec = Some(record.to_lease_spec());
+                api.replace(&self.name, &pp, &lease).await.map(|_| ())
+            }
+            (Backend::ConfigMap(api), Held::ConfigMap(mut cm)) => {
+                set_annotation(&mut cm, record.to_annotation()?);
+                api.replace(&self.name, &pp, &cm).await.map(|_| ())
+            }
+            _ => unreachable!("lock objects are read from their own backend"),
+        };
+        unless_conflict(res).map_err(Error::UpdateLock)
+    }
+}
+
+fn set_annotation(cm: &mut ConfigMap, record: String) {
+    cm.metadata
+        .annotations
+        .get_or_insert_with(Default::default)
+        .insert(LEADER_ANNOTATION.into(), record);
+}
+
+/// Turns `Conflict` and `AlreadyExists` responses into `Ok(false)`
+fn unless_conflict(res: Result<(), kube_client::Error>) -> Result<bool, kube_client::Error> {
+    match res {
+        Ok(()) => Ok(true),
+        Err(kube_client::Error::Api(err)) if err.code == 409 => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+/// Timings of a [`LeaderElector`]
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// How long followers wait after the last renewal before taking over the lock
+    pub lease_duration: Duration,
+    /// How long the leader keeps trying to renew before giving up the leadership
+    ///
+    /// Must be shorter than the `lease_duration`.
+    pub renew_deadline: Duration,
+    /// How often to try to acquire or renew the lock
+    pub retry_period: Duration,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            lease_duration: Duration::from_secs(15),
+            renew_deadline: Duration::from_secs(10),
+            retry_period: Duration::from_secs(2),
+        }
+    }
+}
+
+impl Config {
+    /// Set the duration that followers wait before taking over the lock
+    #[must_use]
+    pub fn lease_duration(mut self, lease_duration: Duration) -> Self {
+        self.lease_duration = lease_duration;
+        self
+    }
+
+    /// Set the duration that the leader keeps trying to renew the lock
+    #[must_use]
+    pub fn renew_deadline(mut self, renew_deadline: Duration) -> Self {
+        self.renew_deadline = renew_deadline;
+        self
+    }
+
+    /// Set the interval between attempts to acquire or renew the lock
+    #[must_use]
+    pub fn retry_period(mut self, retry_period: Duration) -> Self {
+        self.retry_period = retry_period;
+        self
+    }
+}
+
+/// The outcome of an election round
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Leadership {
+    /// This replica holds the lock
+    Leading,
+    /// Another replica holds the lock, or the lock could not be acquired this round
+    Following {
+        /// The identity of the current leader, if known
+        holder: Option<String>,
+    },
+}
+
+impl Leadership {
+    /// Whether this replica holds the lock
+    #[must_use]
+    pub fn is_leader(&self) -> bool {
+        matches!(self, Self::Leading)
+    }
+}
+
+/// Competes for a [`Lock`] on behalf of one replica
+///
+/// ```no_run
+/// use futures::TryStreamExt;
+/// use kube::{runtime::leader_election::{Config, LeaderElector, Lock}, Client};
+/// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = Client::try_default().await?;
+/// let lock = Lock::detect(client, "default", "my-operator").await?;
+/// let identity = std::env::var("POD_NAME")?;
+/// let mut leadership = Box::pin(LeaderElector::new(lock, identity, Config::default()).run());
+/// while let Some(state) = leadership.try_next().await? {
+///     println!("leader: {}", state.is_leader());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct LeaderElector {
+    lock: Lock,
+    identity: String,
+    config: Config,
+    observed: Arc<Mutex<Option<Observed>>>,
+}
+
+/// A record of the lock as last read, and when it was first read on the local clock
+///
+/// Like in `client-go`, a lock expires a lease duration after its record was last seen to change,
+/// so that clock skew between the replicas does not shorten or extend the leases.
+#[derive(Debug)]
+struct Observed {
+    record: LeaderRecord,
+    at: tokio::time::Instant,
+}
+
+impl LeaderElector {
+    /// Compete for `lock` as `identity`, which must be unique among the replicas
+    #[must_use]
+    pub fn new(lock: Lock, identity: impl Into<String>, config: Config) -> Self {
+        Self {
+            lock,
+            identity: identity.into(),
+            config,
+            observed: Arc::default(),
+        }
+    }
+
+    /// The identity of this replica
+    #[must_use]
+    pub fn identity(&self) -> &str {
+        &self.identity
+    }
+
+    /// Acquire the lock if it is free or expired, or renew it if this replica already holds it
+    ///
+    /// # Errors
+    ///
+    /// Fails when the lock cannot be read or written. Losing a race against another replica is not
+    /// an error, but results in [`Leadership::Following`].
+    pub async fn try_acquire_or_renew(&self) -> Result<Leadership, Error> {
+        let now = Utc::now();
+        let mut record = LeaderRecord {
+            holder_identity: self.identity.clone(),
+            lease_duration_seconds: i32::try_from(self.config.lease_duration.as_secs()).unwrap_or(i32::MAX),
+            acquire_time: Some(Time(now)),
+            renew_time: Some(Time(now)),
+            leader_transitions: 0,
+        };
+        let Some((old, held)) = self.lock.get().await? else {
+            return Ok(if self.lock.create(&record).await? {
+                Leadership::Leading
+            } else {
+                Leadership::Following { holder: None }
+            });
+        };
+        let observed = self.observe(&old);
+        let lease_duration = Duration::from_secs(old.lease_duration_seconds.try_into().unwrap_or_default());
+        if old.holder_identity == self.identity {
+            record.acquire_time = old.acquire_time.clone();
+            record.leader_transitions = old.leader_transitions;
+        } else if !old.holder_identity.is_empty() && observed.elapsed() < lease_duration {
+            return Ok(Leadership::Following {
+                holder: Some(old.holder_identity),
+            });
+        } else {
+            record.leader_transitions = old.leader_transitions + 1;
+        }
+        if self.lock.update(held, &record).await? {
+            Ok(Leadership::Leading)
+        } else {
+            Ok(Leadership::Following {
+                holder: Some(old.holder_identity).filter(|h| !h.is_empty() && *h != self.identity),
+            })
+        }
+    }
+
+    /// When `record` was first read, which is now unless it is unchanged since the last read
+    fn observe(&self, record: &LeaderRecord) -> tokio::time::Instant {
+        let mut observed = self.observed.lock().expect("observed record lock poisoned");
+        match &*observed {
+            Some(observed) if observed.record == *record => observed.at,
+            _ => {
+                let at = tokio::time::Instant::now();
+                *observed = Some(Observed {
+                    record: record.clone(),
+                    at,
+                });
+                at
+            }
+        }
+    }
+
+    /// Release the lock if this replica holds it, so that another replica can take over immediately
+    ///
+    /// # Errors
+    ///
+    /// Fails when the lock cannot be read or written.
+    pub async fn step_down(&self) -> Result<(), Error> {
+        self.release(None).await
+    }
+
+    /// Release the lock like [`step_down`](Self::step_down), leaving a [`Handover`] for the next leader
+    ///
+    /// The `from` and `published` fields are filled in. The handover stays on the lock object until
+    /// the next leader takes it with [`take_handover`](Self::take_handover).
+    ///
+    /// ```no_run
+    /// # use kube::runtime::leader_election::{Handover, LeaderElector};
+    /// # async fn wrapper(elector: LeaderElector, rv: String) -> Result<(), Box<dyn std::error::Error>> {
+    /// let handover = Handover::default()
+    ///     .resource_version("pods", rv)
+    ///     .snapshot("configmap/operator-cache");
+    /// elector.step_down_with_handover(handover).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails when the lock cannot be read or written.
+    pub async fn step_down_with_handover(&self, handover: Handover) -> Result<(), Error> {
+        self.release(Some(handover)).await
+    }
+
+    /// Take the [`Handover`] left by the previous leader, if this replica holds the lock
+    ///
+    /// The handover is removed from the lock object, so it is only taken once. Check
+    /// [`Handover::published`] to decide whether it is still recent enough to use.
+    ///
+    /// # Errors
+    ///
+    /// Fails when the lock cannot be read or written, or when the handover cannot be parsed.
+    pub async fn take_handover(&self) -> Result<Option<Handover>, Error> {
+        let Some((record, mut held)) = self.lock.get().await? else {
+            return Ok(None);
+        };
+        if record.holder_identity != self.identity {
+            return Ok(None);
+        }
+        let Some(raw) = held.annotations_mut().remove(HANDOVER_ANNOTATION) else {
+            return Ok(None);
+        };
+        let handover: Handover = serde_json::from_str(&raw).map_err(Error::InvalidHandover)?;
+        // a conflicting update means the lock changed hands again, so the handover is not ours
+        Ok(self.lock.update(held, &record).await?.then_some(handover))
+    }
+
+    async fn release(&self, handover: Option<Handover>) -> Result<(), Error> {
+        let Some((old, mut held)) = self.lock.get().await? else {
+            return Ok(());
+        };
+        if old.holder_identity != self.identity {
+            return Ok(());
+        }
+        if let Some(handover) = handover {
+            let handover = Handover {
+                from: self.identity.clone(),
+                published: Some(Time(Utc::now())),
+                ..handover
+            };
+            let raw = serde_json::to_string(&handover).map_err(Error::InvalidHandover)?;
+            held.annotations_mut().insert(HANDOVER_ANNOTATION.into(), raw);
+        }
+        let record = LeaderRecord {
+            holder_identity: String::new(),
+            lease_duration_seconds: 1,
+            renew_time: Some(Time(Utc::now())),
+            ..old
+        };
+        self.lock.update(held, &record).await?;
+        Ok(())
+    }
+
+    /// Keep competing for the lock, yielding the [`Leadership`] whenever it changes
+    ///
+    /// The first state is yielded after the first round. When renewals fail for longer than the
+    /// `renew_deadline`, the leadership is considered lost. Errors are yielded, but do not end the stream.
+    pub fn run(self) -> impl Stream<Item = Result<Leadership, Error>> + Send {
+        stream! {
+            let mut current: Option<Leadership> = None;
+            let mut renewed = tokio::time::Instant::now();
+            loop {
+                match self.try_acquire_or_renew().await {
+                    Ok(state) => {
+                        if state.is_leader() {
+                            renewed = tokio::time::Instant::now();
+                        }
+                        if current.as_ref() != Some(&state) {
+                            current = Some(state.clone());
+                            yield Ok(state);
+                        }
+                    }
+                    Err(err) => {
+                        let leading = current.as_ref().is_some_and(Leadership::is_leader);
+                        if leading && renewed.elapsed() > self.config.renew_deadline {
+                            let lost = Leadership::Following { holder: None };
+                            current = Some(lost.clone());
+                            yield Ok(lost);
+                        }
+                        yield Err(err);
+                    }
+                }
+                tokio::time::sleep(self.config.retry_period).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use http::{Method, StatusCode};
+    use kube::Client;
+
+    const PATH: &str = "/api/v1/namespaces/default/configmaps/operator";
+
+    fn config_map(record: &LeaderRecord) -> ConfigMap {
+        let mut cm = ConfigMap {
+            metadata: ObjectMeta {
+                name: Some("operator".into()),
+                resource_version: Some("1".into()),
+                ..ObjectMeta::default()
+            },
+            ..ConfigMap::default()
+        };
+        set_annotation(&mut cm, record.to_annotation().unwrap());
+        cm
+    }
+
+    #[tokio::test]
+    async fn config_map_lock_acquires_and_respects_holders() {
+        tokio::time::pause();
+        let (client, mock) = Client::mock();
+        let elector = LeaderElector::new(
+            Lock::config_map(client, "default", "operator"),
+            "a",
+            Config::default(),
+        );
+
+        // missing lock is created
+        mock.expect(Method::POST, "/api/v1/namespaces/default/configmaps")
+            .respond_json(StatusCode::CREATED, &ConfigMap::default());
+        assert_eq!(elector.try_acquire_or_renew().await.unwrap(), Leadership::Leading);
+        let created: ConfigMap = mock.requests()[1].json().unwrap();
+        let record = LeaderRecord::from_config_map(&created).unwrap();
+        assert_eq!(record.holder_identity, "a");
+        assert_eq!(record.lease_duration_seconds, 15);
+
+        // lock held by someone else is respected until it has not been renewed for the lease
+        // duration, by the local clock, even if the clock of the holder is behind
+        let held = LeaderRecord {
+            holder_identity: "b".into(),
+            renew_time: Some(Time(Utc::now() - chrono::Duration::seconds(60))),
+            lease_duration_seconds: 15,
+            ..record.clone()
+        };
+        let following = Leadership::Following {
+            holder: Some("b".into()),
+        };
+        mock.expect(Method::GET, PATH)
+            .respond_json(StatusCode::OK, &config_map(&held));
+        assert_eq!(elector.try_acquire_or_renew().await.unwrap(), following);
+
+        tokio::time::advance(Duration::from_secs(10)).await;
+        let renewed = LeaderRecord {
+            renew_time: Some(Time(Utc::now())),
+            ..held
+        };
+        mock.expect(Method::GET, PATH)
+            .respond_json(StatusCode::OK, &config_map(&renewed));
+        assert_eq!(elector.try_acquire_or_renew().await.unwrap(), following);
+
+        tokio::time::advance(Duration::from_secs(10)).await;
+        mock.expect(Method::GET, PATH)
+            .respond_json(StatusCode::OK, &config_map(&renewed));
+        assert_eq!(elector.try_acquire_or_renew().await.unwrap(), following);
+
+        tokio::time::advance(Duration::from_secs(10)).await;
+        mock.expect(Method::GET, PATH)
+            .respond_json(StatusCode::OK, &config_map(&renewed));
+        mock.expect(Method::PUT, PATH)
+            .respond_json(StatusCode::OK, &ConfigMap::default());
+        assert_eq!(elector.try_acquire_or_renew().await.unwrap(), Leadership::Leading);
+        let replaced: ConfigMap = mock.requests().last().unwrap().json().unwrap();
+        let record = LeaderRecord::from_config_map(&replaced).unwrap();
+        assert_eq!(record.holder_identity, "a");
+        assert_eq!(record.leader_transitions, 1);
+        assert_eq!(replaced.metadata.resource_version.as_deref(), Some("1"));
+        assert!(mock.is_drained());
+    }
+
+    #[tokio::test]
+    async fn handover_is_published_and_taken_once() {
+        let (client, mock) = Client::mock();
+        let lock = Lock::config_map(client, "default", "operator");
+        let outgoing = LeaderElector::new(lock.clone(), "a", Config::default());
+        let incoming = LeaderElector::new(lock, "b", Config::default());
+        let held_by = |holder: &str| {
+            config_map(&LeaderRecord {
+                holder_identity: holder.into(),
+                renew_time: Some(Time(Utc::now())),
+                lease_duration_seconds: 15,
+                ..LeaderRecord::default()
+            })
+        };
+
+        mock.expect(Method::GET, PATH)
+            .respond_json(StatusCode::OK, &held_by("a"));
+        mock.expect(Method::PUT, PATH)
+            .respond_json(StatusCode::OK, &ConfigMap::default());
+        let handover = Handover::default().resource_version("pods", "42");
+        outgoing.step_down_with_handover(handover).await.unwrap();
+        let released: ConfigMap = mock.requests()[1].json().unwrap();
+        assert_eq!(
+            LeaderRecord::from_config_map(&released).unwrap().holder_identity,
+            ""
+        );
+
+        // the next leader inherits the annotation when it acquires the lock
+        let mut acquired = held_by("b");
+        let published = released.metadata.annotations.unwrap()[HANDOVER_ANNOTATION].clone();
+        acquired
+            .metadata
+            .annotations
+            .as_mut()
+            .unwrap()
+            .insert(HANDOVER_ANNOTATION.into(), published);
+        mock.expect(Method::GET, PATH)
+            .respond_json(StatusCode::OK, &acquired);
+        mock.expect(Method::PUT, PATH)
+            .respond_json(StatusCode::OK, &ConfigMap::default());
+        let taken = incoming.take_handover().await.unwrap().unwrap();
+        assert_eq!(taken.from, "a");
+        assert_eq!(taken.resource_versions["pods"], "42");
+        assert!(taken.published.is_some());
+        let cleared: ConfigMap = mock.requests()[3].json().unwrap();
+        assert!(!cleared
+            .metadata
+            .annotations
+            .unwrap()
+            .contains_key(HANDOVER_ANNOTATION));
+
+        // followers do not take handovers
+        mock.expect(Method::GET, PATH)
+            .respond_json(StatusCode::OK, &acquired);
+        assert_eq!(outgoing.take_handover().await.unwrap(), None);
+        assert!(mock.is_drained());
+    }
+}
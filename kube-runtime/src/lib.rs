@@ -21,21 +21,24 @@
 #![allow(clippy::let_underscore_untyped)]
 
 pub mod controller;
+pub mod disruption;
 pub mod events;
 
 pub mod finalizer;
+pub mod leader_election;
+pub mod logs;
 pub mod reflector;
 pub mod scheduler;
 pub mod utils;
 pub mod wait;
 pub mod watcher;
 
-pub use controller::{applier, Config, Controller};
+pub use controller::{applier, Config, Controller, DynamicController};
 pub use finalizer::finalizer;
 pub use reflector::reflector;
 pub use scheduler::scheduler;
 pub use utils::WatchStreamExt;
-pub use watcher::{metadata_watcher, watcher};
+pub use watcher::{metadata_watcher, seeded_watcher, watcher};
 
 pub use utils::{predicates, Predicate};
 pub use wait::conditions;
@@ -0,0 +1,421 @@
+//! Follows the logs of every pod matching a selector, like `stern`
+use std::{collections::HashSet, pin::pin};
+
+use async_stream::stream;
+use futures::{stream::SelectAll, AsyncBufReadExt, Stream, StreamExt};
+use k8s_openapi::{
+    api::core::v1::{ContainerStatus, Pod},
+    chrono::{DateTime, Utc},
+};
+use kube_client::{api::LogParams, Api, ResourceExt};
+use thiserror::Error;
+
+use crate::{
+    watcher::{self, watcher},
+    WatchStreamExt,
+};
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("failed to watch pods: {0}")]
+    WatchFailed(#[source] watcher::Error),
+
+    #[error("failed to stream the logs of {namespace}/{pod}/{container}: {source}")]
+    LogStreamFailed {
+        namespace: String,
+        pod: String,
+        container: String,
+        #[source]
+        source: kube_client::Error,
+    },
+
+    #[error("failed to read the logs of {namespace}/{pod}/{container}: {source}")]
+    ReadFailed {
+        namespace: String,
+        pod: String,
+        container: String,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+/// A line of logs, labeled with the container that wrote it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogLine {
+    /// The namespace of the pod
+    pub namespace: String,
+    /// The name of the pod
+    pub pod: String,
+    /// The name of the container
+    pub container: String,
+    /// When the container wrote the line, as recorded by the kubelet
+    pub timestamp: Option<DateTime<Utc>>,
+    /// The line, without the timestamp and the trailing newline
+    pub line: String,
+}
+
+/// Follow the logs of all containers of all pods matching `wc`
+///
+/// Containers are followed as soon as they have started, including containers of pods created
+/// later, init containers, ephemeral containers, and containers after they restart. When the log
+/// stream of a running container ends, e.g. because the kubelet rotated its log file or the
+/// connection dropped, it is resumed from the last line seen.
+///
+/// `lp` applies to the containers that are already running when the logs are first followed, so
+/// `tail_lines` or `since_seconds` can limit how much history is shown. Containers that start
+/// later are followed from their first line. `lp.container` restricts the containers to follow,
+/// and `follow` and `timestamps` are always enabled, since they are needed to resume streams.
+///
+/// Errors of individual log streams are yielded. When a log stream cannot be opened, that container
+/// is no longer followed, while a log stream that fails while it is read is resumed like one that
+/// ended. Errors of the watch are yielded and retried with the
+/// [default backoff](crate::watcher::DefaultBackoff).
+///
+/// ```no_run
+/// use futures::TryStreamExt;
+/// use k8s_openapi::api::core::v1::Pod;
+/// use kube::{
+///     api::{Api, LogParams},
+///     runtime::{logs::pod_logs, watcher},
+///     Client,
+/// };
+/// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+/// # let client: Client = todo!();
+/// let pods: Api<Pod> = Api::default_namespaced(client);
+/// let lp = LogParams {
+///     tail_lines: Some(10),
+///     ..LogParams::default()
+/// };
+/// let mut lines = std::pin::pin!(pod_logs(pods, watcher::Config::default().labels("app=blog"), lp));
+/// while let Some(line) = lines.try_next().await? {
+///     println!("{} {}: {}", line.pod, line.container, line.line);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn pod_logs(
+    api: Api<Pod>,
+    wc: watcher::Config,
+    lp: LogParams,
+) -> impl Stream<Item = Result<LogLine, Error>> + Send {
+    let client = api.clone().into_client();
+    let pods = watcher(api, wc).default_backoff().map(Update::Pod).boxed();
+    stream! {
+        let mut updates = SelectAll::from_iter([pods]);
+        let mut followed = HashSet::<ContainerInstance>::new();
+        while let Some(update) = updates.next().await {
+            let (pod, initial) = match update {
+                Update::Line(line) => {
+                    yield line;
+                    continue;
+                }
+                Update::Pod(Ok(watcher::Event::InitApply(pod))) => (pod, true),
+                Update::Pod(Ok(watcher::Event::Apply(pod))) => (pod, false),
+                Update::Pod(Ok(watcher::Event::Delete(pod))) => {
+                    let uid = pod.uid().unwrap_or_default();
+                    followed.retain(|instance| instance.uid != uid);
+                    continue;
+                }
+                Update::Pod(Ok(watcher::Event::Init | watcher::Event::InitDone)) => continue,
+                Update::Pod(Err(err)) => {
+                    yield Err(Error::WatchFailed(err));
+                    continue;
+                }
+            };
+            let Some(namespace) = pod.namespace() else {
+                continue;
+            };
+            for status in started_containers(&pod) {
+                if lp.container.as_ref().is_some_and(|c| c != &status.name) {
+                    continue;
+                }
+                let instance = ContainerInstance {
+                    uid: pod.uid().unwrap_or_default(),
+                    container: status.name.clone(),
+                    restart_count: status.restart_count,
+                };
+                if !followed.insert(instance.clone()) {
+                    continue;
+                }
+                let lp = if initial {
+                    lp.clone()
+                } else {
+                    LogParams {
+                        container: lp.container.clone(),
+                        ..LogParams::default()
+                    }
+                };
+                let api = Api::namespaced(client.clone(), &namespace);
+                let tail = follow_container(api, namespace.clone(), pod.name_any(), instance, lp);
+                updates.push(tail.map(Update::Line).boxed());
+            }
+        }
+    }
+}
+
+enum Update {
+    Pod(Result<watcher::Event<Pod>, watcher::Error>),
+    Line(Result<LogLine, Error>),
+}
+
+/// A run of a container, which has logs of its own
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct ContainerInstance {
+    uid: String,
+    container: String,
+    restart_count: i32,
+}
+
+/// The statuses of the containers that have logs, because they are running or have run
+fn started_containers(pod: &Pod) -> impl Iterator<Item = &ContainerStatus> {
+    let status = pod.status.as_ref();
+    let statuses = status.into_iter().flat_map(|s| {
+        let init = s.init_container_statuses.iter().flatten();
+        let containers = s.container_statuses.iter().flatten();
+        let ephemeral = s.ephemeral_container_statuses.iter().flatten();
+        init.chain(containers).chain(ephemeral)
+    });
+    statuses.filter(|status| {
+        status
+            .state
+            .as_ref()
+            .is_some_and(|state| state.running.is_some() || state.terminated.is_some())
+    })
+}
+
+/// Whether the container instance is still running, so that its logs may continue
+fn is_running(pod: &Pod, instance: &ContainerInstance) -> bool {
+    pod.uid().as_deref() == Some(instance.uid.as_str())
+        && started_containers(pod).any(|status| {
+            status.name == instance.container
+                && status.restart_count == instance.restart_count
+                && status.state.as_ref().is_some_and(|state| state.running.is_some())
+        })
+}
+
+fn follow_container(
+    api: Api<Pod>,
+    namespace: String,
+    pod: String,
+    instance: ContainerInstance,
+    lp: LogParams,
+) -> impl Stream<Item = Result<LogLine, Error>> + Send {
+    stream! {
+        // the timestamp of the last line seen, and how many of the lines seen have that timestamp
+        let mut last_seen: Option<(DateTime<Utc>, usize)> = None;
+        loop {
+            let mut lp = LogParams {
+                container: Some(instance.container.clone()),
+                follow: true,
+                timestamps: true,
+                ..lp.clone()
+            };
+            let resumed = last_seen;
+            if let Some((since, _)) = resumed {
+                // resume, `sinceTime` only has a precision of seconds so the lines seen are skipped below
+                lp.since_seconds = None;
+                lp.since_time = Some(since);
+                lp.tail_lines = None;
+            }
+            // the lines of this stream with the timestamp of the last line seen before resuming
+            let mut repeated = 0;
+            let logs = match api.log_stream(&pod, &lp).await {
+                Ok(logs) => logs,
+                Err(source) => {
+                    yield Err(Error::LogStreamFailed {
+                        namespace,
+                        pod,
+                        container: instance.container,
+                        source,
+                    });
+                    return;
+                }
+            };
+            let mut lines = pin!(logs.lines());
+            while let Some(line) = lines.next().await {
+                let line = match line {
+                    Ok(line) => line,
+                    Err(source) => {
+                        yield Err(Error::ReadFailed {
+                            namespace: namespace.clone(),
+                            pod: pod.clone(),
+                            container: instance.container.clone(),
+                            source,
+                        });
+                        break;
+                    }
+                };
+                let (timestamp, line) = split_timestamp(&line);
+                if let Some(timestamp) = timestamp {
+                    match resumed {
+                        Some((since, _)) if timestamp < since => continue,
+                        Some((since, seen)) if timestamp == since => {
+                            // lines may share a timestamp, only the ones already yielded are skipped
+                            repeated += 1;
+                            if repeated <= seen {
+                                continue;
+                            }
+                        }
+                        _ => {}
+                    }
+                    last_seen = match last_seen {
+                        Some((last, seen)) if last == timestamp => Some((last, seen + 1)),
+                        _ => Some((timestamp, 1)),
+                    };
+                }
+                yield Ok(LogLine {
+                    namespace: namespace.clone(),
+                    pod: pod.clone(),
+                    container: instance.container.clone(),
+                    timestamp,
+                    line: line.to_string(),
+                });
+            }
+            // the logs of a container end when it exits, but also on log rotation and disconnects
+            match api.get_opt(&pod).await {
+                Ok(Some(current)) if is_running(&current, &instance) => {
+                    tracing::debug!("resuming the logs of {namespace}/{pod}/{}", instance.container);
+                }
+                _ => return,
+            }
+        }
+    }
+}
+
+/// Split the RFC3339 timestamp the kubelet prepends to lines with `timestamps` off the line
+fn split_timestamp(line: &str) -> (Option<DateTime<Utc>>, &str) {
+    line.split_once(' ')
+        .and_then(|(timestamp, rest)| {
+            let timestamp = DateTime::parse_from_rfc3339(timestamp).ok()?;
+            Some((Some(timestamp.with_timezone(&Utc)), rest))
+        })
+        .unwrap_or((None, line))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{pod_logs, LogLine};
+    use crate::watcher;
+    use futures::StreamExt;
+    use http::{Method, Response, StatusCode};
+    use k8s_openapi::api::core::v1::Pod;
+    use kube::{
+        api::{Api, LogParams},
+        Client,
+    };
+
+    #[tokio::test]
+    async fn logs_of_started_containers_are_followed() {
+        let (client, mock) = Client::mock();
+        let pod = serde_json::json!({
+            "apiVersion": "v1",
+            "kind": "Pod",
+            "metadata": { "name": "web", "namespace": "default", "uid": "1", "resourceVersion": "2" },
+            "status": { "containerStatuses": [
+                { "name": "app", "restartCount": 0, "state": { "running": {} },
+                  "image": "app", "imageID": "", "ready": true },
+                { "name": "sidecar", "restartCount": 0, "state": { "waiting": {} },
+                  "image": "sidecar", "imageID": "", "ready": false },
+            ] },
+        });
+        let list = serde_json::json!({
+            "apiVersion": "v1",
+            "kind": "PodList",
+            "metadata": { "resourceVersion": "2" },
+            "items": [pod],
+        });
+        mock.expect(Method::GET, "/api/v1/namespaces/default/pods")
+            .respond_json(StatusCode::OK, &list);
+        mock.expect(Method::GET, "/api/v1/namespaces/default/pods/web/log")
+            .respond_with(Response::new(
+                b"2024-05-01T10:00:00.000000001Z hello\n2024-05-01T10:00:00.5Z world\n".to_vec(),
+            ));
+
+        let api: Api<Pod> = Api::default_namespaced(client);
+        let lp = LogParams {
+            tail_lines: Some(10),
+            ..LogParams::default()
+        };
+        let lines: Vec<LogLine> = pod_logs(api, watcher::Config::default(), lp)
+            .filter_map(|line| async move { line.ok() })
+            .take(2)
+            .collect()
+            .await;
+        let text: Vec<_> = lines
+            .iter()
+            .map(|l| format!("{}/{}: {}", l.pod, l.container, l.line))
+            .collect();
+        assert_eq!(text, ["web/app: hello", "web/app: world"]);
+        assert_eq!(
+            lines[1].timestamp.unwrap().to_rfc3339(),
+            "2024-05-01T10:00:00.500+00:00"
+        );
+
+        let log_request = mock
+            .requests()
+            .into_iter()
+            .find(|r| r.uri.path().ends_with("/log"))
+            .unwrap();
+        let query = log_request.uri.query().unwrap_or_default();
+        assert!(
+            query.contains("container=app") && query.contains("follow=true"),
+            "{query}"
+        );
+        assert!(
+            query.contains("tailLines=10") && query.contains("timestamps=true"),
+            "{query}"
+        );
+    }
+
+    #[tokio::test]
+    async fn resumed_logs_skip_only_the_lines_seen() {
+        let (client, mock) = Client::mock();
+        let pod = serde_json::json!({
+            "apiVersion": "v1",
+            "kind": "Pod",
+            "metadata": { "name": "web", "namespace": "default", "uid": "1", "resourceVersion": "2" },
+            "status": { "containerStatuses": [
+                { "name": "app", "restartCount": 0, "state": { "running": {} },
+                  "image": "app", "imageID": "", "ready": true },
+            ] },
+        });
+        let list = serde_json::json!({
+            "apiVersion": "v1",
+            "kind": "PodList",
+            "metadata": { "resourceVersion": "2" },
+            "items": [pod],
+        });
+        mock.expect(Method::GET, "/api/v1/namespaces/default/pods")
+            .respond_json(StatusCode::OK, &list);
+        mock.expect(Method::GET, "/api/v1/namespaces/default/pods/web/log")
+            .respond_with(Response::new(
+                b"2024-05-01T10:00:00.1Z a\n2024-05-01T10:00:00.1Z b\n".to_vec(),
+            ));
+        mock.expect(Method::GET, "/api/v1/namespaces/default/pods/web")
+            .respond_json(StatusCode::OK, &pod);
+        // the resumed stream starts at the second of the last line seen
+        mock.expect(Method::GET, "/api/v1/namespaces/default/pods/web/log")
+            .respond_with(Response::new(
+                b"2024-05-01T10:00:00Z old\n2024-05-01T10:00:00.1Z a\n2024-05-01T10:00:00.1Z b\n\
+                  2024-05-01T10:00:00.1Z c\n2024-05-01T10:00:01Z d\n"
+                    .to_vec(),
+            ));
+
+        let api: Api<Pod> = Api::default_namespaced(client);
+        let lines: Vec<String> = pod_logs(api, watcher::Config::default(), LogParams::default())
+            .filter_map(|line| async move { line.ok().map(|l| l.line) })
+            .take(4)
+            .collect()
+            .await;
+        assert_eq!(lines, ["a", "b", "c", "d"]);
+
+        let resumed = mock
+            .requests()
+            .into_iter()
+            .filter(|r| r.uri.path().ends_with("/log"))
+            .nth(1)
+            .unwrap();
+        let query = resumed.uri.query().unwrap_or_default();
+        assert!(query.contains("sinceTime=2024-05-01T10%3A00%3A00Z"), "{query}");
+    }
+}
@@ -0,0 +1,317 @@
+//! A reflector specialized for `CustomResourceDefinition`s
+use std::{collections::HashMap, sync::Arc};
+
+use async_broadcast::{InactiveReceiver, Receiver, Sender};
+use async_stream::stream;
+use futures::{Stream, StreamExt};
+use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::{
+    CustomResourceDefinition, JSONSchemaProps,
+};
+use kube_client::{core::GroupVersionKind, Api, Client, ResourceExt};
+
+use super::{
+    reflector,
+    store::{self, Store, WriterDropped},
+};
+use crate::watcher;
+
+/// How many changes a slow subscriber can fall behind before it misses the oldest ones
+const CHANGE_BUFFER: usize = 128;
+
+/// A change to the set of `CustomResourceDefinition`s in the cluster
+#[derive(Clone, Debug)]
+pub enum CrdChange {
+    /// A definition was created or modified
+    Applied(Arc<CustomResourceDefinition>),
+    /// A definition was deleted
+    Deleted(Arc<CustomResourceDefinition>),
+}
+
+impl CrdChange {
+    /// The definition that changed
+    #[must_use]
+    pub fn crd(&self) -> &Arc<CustomResourceDefinition> {
+        match self {
+            Self::Applied(crd) | Self::Deleted(crd) => crd,
+        }
+    }
+}
+
+/// A read-only cache of the `CustomResourceDefinition`s in the cluster
+///
+/// Created by [`crd_cache`], which returns the stream that keeps it up to date.
+/// Cheap to clone, all clones share the same state.
+#[derive(Clone, Debug)]
+pub struct CrdCache {
+    store: Store<CustomResourceDefinition>,
+    changes: InactiveReceiver<CrdChange>,
+}
+
+impl CrdCache {
+    /// The definition serving `gvk`, if any
+    ///
+    /// Matches on group and kind only, use [`CrdCache::schema_for`] to check that the version is served.
+    #[must_use]
+    pub fn get(&self, gvk: &GroupVersionKind) -> Option<Arc<CustomResourceDefinition>> {
+        self.store
+            .find(|crd| crd.spec.group == gvk.group && crd.spec.names.kind == gvk.kind)
+    }
+
+    /// The OpenAPI v3 schema of `gvk`, if its definition and version are known
+    ///
+    /// Returns `None` when the version is not defined, or has no schema.
+    #[must_use]
+    pub fn schema_for(&self, gvk: &GroupVersionKind) -> Option<JSONSchemaProps> {
+        let crd = self.get(gvk)?;
+        crd.spec
+            .versions
+            .iter()
+            .find(|v| v.name == gvk.version)?
+            .schema
+            .as_ref()?
+            .open_api_v3_schema
+            .clone()
+    }
+
+    /// A snapshot of all known definitions
+    #[must_use]
+    pub fn state(&self) -> Vec<Arc<CustomResourceDefinition>> {
+        self.store.state()
+    }
+
+    /// Subscribe to changes of the definitions
+    ///
+    /// Only changes made after subscribing are received. A subscriber that falls too far
+    /// behind misses the oldest changes, so consumers that must not miss anything should
+    /// check the [`CrdCache::state`] after subscribing.
+    #[must_use]
+    pub fn subscribe(&self) -> Receiver<CrdChange> {
+        self.changes.activate_cloned()
+    }
+
+    /// Wait for the initial list of definitions to be cached
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream returned by [`crd_cache`] was dropped before it
+    /// finished the initial list.
+    pub async fn wait_until_ready(&self) -> Result<(), WriterDropped> {
+        self.store.wait_until_ready().await
+    }
+}
+
+/// Cache the `CustomResourceDefinition`s of the cluster for schema lookups
+///
+/// Returns the [`CrdCache`] and a stream of the [`CrdChange`]s it observes.
+/// The stream must be polled to keep the cache up to date, just like a [`reflector()`].
+///
+/// ```no_run
+/// use futures::StreamExt;
+/// use kube::{core::GroupVersionKind, runtime::reflector::crd_cache};
+/// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+/// # let client: kube::Client = todo!();
+/// let (cache, changes) = crd_cache(client);
+/// tokio::spawn(changes.for_each(|_| std::future::ready(())));
+/// cache.wait_until_ready().await?;
+///
+/// let gvk = GroupVersionKind::gvk("cert-manager.io", "v1", "Certificate");
+/// if let Some(schema) = cache.schema_for(&gvk) {
+///     println!("Certificate has fields {:?}", schema.properties.unwrap_or_default().keys());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[must_use]
+pub fn crd_cache(client: Client) -> (CrdCache, impl Stream<Item = watcher::Result<CrdChange>>) {
+    let api: Api<CustomResourceDefinition> = Api::all(client);
+    track(watcher(api, watcher::Config::default()))
+}
+
+fn track<W>(stream: W) -> (CrdCache, impl Stream<Item = watcher::Result<CrdChange>>)
+where
+    W: Stream<Item = watcher::Result<watcher::Event<CustomResourceDefinition>>>,
+{
+    let (reader, writer) = store::store();
+    let (mut tx, rx) = async_broadcast::broadcast(CHANGE_BUFFER);
+    tx.set_overflow(true);
+    tx.set_await_active(false);
+    let cache = CrdCache {
+        store: reader,
+        changes: rx.deactivate(),
+    };
+
+    let mut events = Box::pin(reflector(writer, stream));
+    let changes = stream! {
+        let mut tracker = ChangeTracker::default();
+        while let Some(event) = events.next().await {
+            match event {
+                Ok(event) => {
+                    for change in tracker.apply(event) {
+                        notify(&tx, &change);
+                        yield Ok(change);
+                    }
+                }
+                Err(err) => yield Err(err),
+            }
+        }
+    };
+    (cache, changes)
+}
+
+fn notify(tx: &Sender<CrdChange>, change: &CrdChange) {
+    // the channel overflows rather than filling up, and having no subscribers is fine
+    let _ = tx.try_broadcast(change.clone());
+}
+
+/// Turns watcher events into changes, so relists only report what actually changed
+#[derive(Default)]
+struct ChangeTracker {
+    known: HashMap<String, Arc<CustomResourceDefinition>>,
+    relisted: Option<HashMap<String, Arc<CustomResourceDefinition>>>,
+}
+
+impl ChangeTracker {
+    fn apply(&mut self, event: watcher::Event<CustomResourceDefinition>) -> Vec<CrdChange> {
+        match event {
+            watcher::Event::Apply(crd) => {
+                let crd = Arc::new(crd);
+                self.known.insert(crd.name_any(), crd.clone());
+                vec![CrdChange::Applied(crd)]
+            }
+            watcher::Event::Delete(crd) => {
+                let crd = self
+                    .known
+                    .remove(&crd.name_any())
+                    .unwrap_or_else(|| Arc::new(crd));
+                vec![CrdChange::Deleted(crd)]
+            }
+            watcher::Event::Init => {
+                self.relisted = Some(HashMap::new());
+                vec![]
+            }
+            watcher::Event::InitApply(crd) => {
+                let crd = Arc::new(crd);
+                let changed = is_changed(self.known.get(&crd.name_any()), &crd);
+                self.relisted
+                    .get_or_insert_with(HashMap::new)
+                    .insert(crd.name_any(), crd.clone());
+                if changed {
+                    vec![CrdChange::Applied(crd)]
+                } else {
+                    vec![]
+                }
+            }
+            watcher::Event::InitDone => {
+                let relisted = self.relisted.take().unwrap_or_default();
+                let previous = std::mem::replace(&mut self.known, relisted);
+                previous
+                    .into_iter()
+                    .filter(|(name, _)| !self.known.contains_key(name))
+                    .map(|(_, crd)| CrdChange::Deleted(crd))
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Whether `crd` is new or has a different `resourceVersion` than the `known` one
+fn is_changed(known: Option<&Arc<CustomResourceDefinition>>, crd: &CustomResourceDefinition) -> bool {
+    match known {
+        Some(known) => known.resource_version() != crd.resource_version(),
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::stream;
+    use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::{
+        CustomResourceDefinitionNames, CustomResourceDefinitionSpec, CustomResourceDefinitionVersion,
+        CustomResourceValidation,
+    };
+    use kube_client::api::ObjectMeta;
+
+    fn crd(name: &str, resource_version: &str) -> CustomResourceDefinition {
+        CustomResourceDefinition {
+            metadata: ObjectMeta {
+                name: Some(format!("{name}s.example.com")),
+                resource_version: Some(resource_version.into()),
+                ..ObjectMeta::default()
+            },
+            spec: CustomResourceDefinitionSpec {
+                group: "example.com".into(),
+                names: CustomResourceDefinitionNames {
+                    kind: name.into(),
+                    plural: format!("{name}s"),
+                    ..CustomResourceDefinitionNames::default()
+                },
+                scope: "Namespaced".into(),
+                versions: vec![CustomResourceDefinitionVersion {
+                    name: "v1".into(),
+                    served: true,
+                    storage: true,
+                    schema: Some(CustomResourceValidation {
+                        open_api_v3_schema: Some(JSONSchemaProps {
+                            type_: Some("object".into()),
+                            ..JSONSchemaProps::default()
+                        }),
+                    }),
+                    ..CustomResourceDefinitionVersion::default()
+                }],
+                ..CustomResourceDefinitionSpec::default()
+            },
+            status: None,
+        }
+    }
+
+    fn names(changes: &[CrdChange]) -> Vec<String> {
+        changes
+            .iter()
+            .map(|change| match change {
+                CrdChange::Applied(crd) => format!("applied {}", crd.name_any()),
+                CrdChange::Deleted(crd) => format!("deleted {}", crd.name_any()),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn looks_up_schemas_and_reports_changes() {
+        let events = stream::iter([
+            Ok(watcher::Event::Init),
+            Ok(watcher::Event::InitApply(crd("Foo", "1"))),
+            Ok(watcher::Event::InitApply(crd("Bar", "1"))),
+            Ok(watcher::Event::InitDone),
+            Ok(watcher::Event::Delete(crd("Bar", "2"))),
+            // a relist only reports what changed while the watch was down
+            Ok(watcher::Event::Init),
+            Ok(watcher::Event::InitApply(crd("Foo", "1"))),
+            Ok(watcher::Event::InitApply(crd("Baz", "3"))),
+            Ok(watcher::Event::InitDone),
+        ]);
+        let (cache, changes) = track(events);
+        let mut subscriber = cache.subscribe();
+        let changes = changes.map(Result::unwrap).collect::<Vec<_>>().await;
+        assert_eq!(names(&changes), [
+            "applied foos.example.com",
+            "applied bars.example.com",
+            "deleted bars.example.com",
+            "applied bazs.example.com",
+        ]);
+        assert_eq!(
+            subscriber.next().await.unwrap().crd().name_any(),
+            "foos.example.com"
+        );
+
+        let schema = cache.schema_for(&GroupVersionKind::gvk("example.com", "v1", "Foo"));
+        assert_eq!(schema.and_then(|s| s.type_).as_deref(), Some("object"));
+        assert!(cache
+            .schema_for(&GroupVersionKind::gvk("example.com", "v2", "Foo"))
+            .is_none());
+        assert!(cache
+            .schema_for(&GroupVersionKind::gvk("example.com", "v1", "Bar"))
+            .is_none());
+        assert_eq!(cache.state().len(), 2);
+    }
+}
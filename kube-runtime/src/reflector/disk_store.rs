@@ -0,0 +1,473 @@
+//! A reflector store that keeps objects on disk
+//!
+//! [`Store`](super::Store) keeps every object in memory, which is not an option for controllers
+//! that must watch resources whose total size exceeds the available RAM, such as all `Secret`s
+//! in a large cluster. [`DiskStore`] instead serializes objects into an embedded [`sled`] database,
+//! and only keeps an index of [`ObjectRef`]s in memory, so lookups by key and iteration over
+//! keys stay cheap while object bodies are read from disk on demand.
+//!
+//! `DiskStore` is a separate type with its own [`reflector`], rather than another backend of
+//! [`Store`](super::Store). It can therefore not back a [`Controller`](crate::Controller), which
+//! looks up objects in a `Store`, and is meant for code that reads the cached objects itself.
+//!
+//! Requires the `unstable-runtime-disk-store` feature.
+use super::{Lookup, ObjectRef};
+use crate::{
+    utils::delayed_init::{self, DelayedInit},
+    watcher,
+};
+use ahash::AHashSet;
+use async_stream::stream;
+use futures::{Stream, StreamExt};
+use parking_lot::RwLock;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    fmt::Debug,
+    hash::Hash,
+    marker::PhantomData,
+    ops::Deref,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+use thiserror::Error;
+
+/// Errors from a [`DiskStore`]
+#[derive(Debug, Error)]
+pub enum Error {
+    /// The embedded database failed
+    #[error("disk store database error: {0}")]
+    Database(#[source] sled::Error),
+
+    /// An object could not be (de)serialized
+    #[error("disk store serialization error: {0}")]
+    Serde(#[source] serde_json::Error),
+
+    /// The writer was dropped before the store became ready
+    #[error("writer was dropped before store became ready")]
+    WriterDropped,
+}
+
+/// The tree of a generation, which is dropped from the database once it is retired and unused
+///
+/// Iterators of [`DiskStore::iter`] keep the tree, so a relist does not drop it under them.
+struct Tree {
+    tree: sled::Tree,
+    db: sled::Db,
+    retired: AtomicBool,
+}
+
+impl Tree {
+    fn open(db: &sled::Db, generation: u64) -> Result<Arc<Self>, Error> {
+        let tree = db.open_tree(tree_name(generation)).map_err(Error::Database)?;
+        Ok(Arc::new(Self {
+            tree,
+            db: db.clone(),
+            retired: AtomicBool::new(false),
+        }))
+    }
+
+    fn retire(&self) {
+        self.retired.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Deref for Tree {
+    type Target = sled::Tree;
+
+    fn deref(&self) -> &sled::Tree {
+        &self.tree
+    }
+}
+
+impl Drop for Tree {
+    fn drop(&mut self) {
+        if *self.retired.get_mut() {
+            if let Err(err) = self.db.drop_tree(self.tree.name()) {
+                tracing::warn!(error = %err, "failed to drop replaced disk store generation");
+            }
+        }
+    }
+}
+
+/// The currently active generation of the store
+struct Generation<K: Lookup>
+where
+    K::DynamicType: Eq + Hash,
+{
+    tree: Arc<Tree>,
+    index: AHashSet<ObjectRef<K>>,
+}
+
+type State<K> = Arc<RwLock<Generation<K>>>;
+
+/// A writable [`DiskStore`] handle
+///
+/// Like [`Writer`](super::store::Writer), this is exclusive and must be moved into a [`reflector`].
+pub struct Writer<K: 'static + Lookup>
+where
+    K::DynamicType: Eq + Hash,
+{
+    db: sled::Db,
+    state: State<K>,
+    generation: u64,
+    buffer: Option<Generation<K>>,
+    dyntype: K::DynamicType,
+    ready_tx: Option<delayed_init::Initializer<()>>,
+    ready_rx: Arc<DelayedInit<()>>,
+}
+
+impl<K> Writer<K>
+where
+    K: 'static + Lookup + Clone + Serialize + DeserializeOwned,
+    K::DynamicType: Eq + Hash + Clone,
+{
+    /// Open a disk store in the directory at `path`
+    ///
+    /// Any previous contents are discarded; the store is repopulated by the watcher.
+    pub fn open(path: impl AsRef<Path>, dyntype: K::DynamicType) -> Result<Self, Error> {
+        let db = sled::open(path).map_err(Error::Database)?;
+        for name in db.tree_names() {
+            if name != db.name() {
+                db.drop_tree(name).map_err(Error::Database)?;
+            }
+        }
+        let tree = Tree::open(&db, 0)?;
+        let (ready_tx, ready_rx) = DelayedInit::new();
+        Ok(Writer {
+            db,
+            state: Arc::new(RwLock::new(Generation {
+                tree,
+                index: AHashSet::new(),
+            })),
+            generation: 0,
+            buffer: None,
+            dyntype,
+            ready_tx: Some(ready_tx),
+            ready_rx: Arc::new(ready_rx),
+        })
+    }
+
+    /// Return a read handle to the store
+    #[must_use]
+    pub fn as_reader(&self) -> DiskStore<K> {
+        DiskStore {
+            state: self.state.clone(),
+            ready_rx: self.ready_rx.clone(),
+            _kind: PhantomData,
+        }
+    }
+
+    /// Applies a single watcher event to the store
+    pub fn apply_watcher_event(&mut self, event: &watcher::Event<K>) -> Result<(), Error> {
+        match event {
+            watcher::Event::Apply(obj) => {
+                let key = obj.to_object_ref(self.dyntype.clone());
+                let value = serde_json::to_vec(obj).map_err(Error::Serde)?;
+                let mut state = self.state.write();
+                state.tree.insert(db_key(&key), value).map_err(Error::Database)?;
+                state.index.insert(key);
+            }
+            watcher::Event::Delete(obj) => {
+                let key = obj.to_object_ref(self.dyntype.clone());
+                let mut state = self.state.write();
+                state.tree.remove(db_key(&key)).map_err(Error::Database)?;
+                state.index.remove(&key);
+            }
+            watcher::Event::Init => {
+                if let Some(stale) = self.buffer.take() {
+                    stale.tree.retire();
+                }
+                self.generation += 1;
+                let tree = Tree::open(&self.db, self.generation)?;
+                tree.clear().map_err(Error::Database)?;
+                self.buffer = Some(Generation {
+                    tree,
+                    index: AHashSet::new(),
+                });
+            }
+            watcher::Event::InitApply(obj) => {
+                let key = obj.to_object_ref(self.dyntype.clone());
+                let value = serde_json::to_vec(obj).map_err(Error::Serde)?;
+                if let Some(buffer) = &mut self.buffer {
+                    buffer.tree.insert(db_key(&key), value).map_err(Error::Database)?;
+                    buffer.index.insert(key);
+                }
+            }
+            watcher::Event::InitDone => {
+                if let Some(buffer) = self.buffer.take() {
+                    let old = std::mem::replace(&mut *self.state.write(), buffer);
+                    // dropped once the iterators that still read it are done
+                    old.tree.retire();
+                }
+
+                // Mark as ready after the Restart, "releasing" any calls to DiskStore::wait_until_ready()
+                if let Some(ready_tx) = self.ready_tx.take() {
+                    ready_tx.init(())
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A readable, disk-backed cache of Kubernetes objects of kind `K`
+///
+/// Cloning will produce a new reference to the same backing store.
+/// Unlike [`Store`](super::Store), objects are deserialized on every read and returned by value,
+/// and the store can not be used by a [`Controller`](crate::Controller).
+pub struct DiskStore<K: 'static + Lookup>
+where
+    K::DynamicType: Eq + Hash,
+{
+    state: State<K>,
+    ready_rx: Arc<DelayedInit<()>>,
+    _kind: PhantomData<fn() -> K>,
+}
+
+impl<K: 'static + Lookup> Clone for DiskStore<K>
+where
+    K::DynamicType: Eq + Hash,
+{
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+            ready_rx: self.ready_rx.clone(),
+            _kind: PhantomData,
+        }
+    }
+}
+
+impl<K: 'static + Lookup> Debug for DiskStore<K>
+where
+    K::DynamicType: Eq + Hash,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DiskStore")
+            .field("len", &self.state.read().index.len())
+            .finish()
+    }
+}
+
+impl<K> DiskStore<K>
+where
+    K: 'static + Lookup + Clone + DeserializeOwned,
+    K::DynamicType: Eq + Hash + Clone,
+{
+    /// Wait for the store to be populated by Kubernetes.
+    ///
+    /// # Errors
+    /// Returns an error if the [`Writer`] was dropped before any value was written.
+    pub async fn wait_until_ready(&self) -> Result<(), Error> {
+        self.ready_rx.get().await.map_err(|_| Error::WriterDropped)
+    }
+
+    /// Read the entry referred to by `key` from disk, if it is in the cache.
+    ///
+    /// `key.namespace` is ignored for cluster-scoped resources.
+    /// The same staleness caveats as for [`Store::get`](super::Store::get) apply.
+    pub fn get(&self, key: &ObjectRef<K>) -> Result<Option<K>, Error> {
+        let state = self.state.read();
+        let value = match state.tree.get(db_key(key)).map_err(Error::Database)? {
+            Some(value) => Some(value),
+            // Try to erase the namespace and try again, in case the object is cluster-scoped
+            None if key.namespace.is_some() => {
+                let mut cluster_key = key.clone();
+                cluster_key.namespace = None;
+                state.tree.get(db_key(&cluster_key)).map_err(Error::Database)?
+            }
+            None => None,
+        };
+        drop(state);
+        value
+            .map(|v| serde_json::from_slice(&v).map_err(Error::Serde))
+            .transpose()
+    }
+
+    /// Return the references of all objects in the store
+    ///
+    /// This is served from the in-memory index and does not touch the disk.
+    #[must_use]
+    pub fn keys(&self) -> Vec<ObjectRef<K>> {
+        self.state.read().index.iter().cloned().collect()
+    }
+
+    /// Iterate over a snapshot of all stored objects, reading them from disk one at a time
+    ///
+    /// An iterator keeps reading the objects of the generation it started on when a relist
+    /// completes in the meantime.
+    pub fn iter(&self) -> impl Iterator<Item = Result<K, Error>> {
+        let tree = self.state.read().tree.clone();
+        let values = tree.iter().values();
+        values.map(move |v| {
+            let _generation = &tree;
+            let v = v.map_err(Error::Database)?;
+            serde_json::from_slice(&v).map_err(Error::Serde)
+        })
+    }
+
+    /// Return the number of elements in the store
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.state.read().index.len()
+    }
+
+    /// Return whether the store is empty
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.state.read().index.is_empty()
+    }
+}
+
+/// Create a (Reader, Writer) for a [`DiskStore`] of a typed resource `K` in the directory at `path`
+///
+/// The `Writer` should be passed to a disk-backed [`reflector`].
+pub fn disk_store<K>(path: impl AsRef<Path>) -> Result<(DiskStore<K>, Writer<K>), Error>
+where
+    K: Lookup + Clone + Serialize + DeserializeOwned + 'static,
+    K::DynamicType: Eq + Hash + Clone + Default,
+{
+    let w = Writer::<K>::open(path, K::DynamicType::default())?;
+    let r = w.as_reader();
+    Ok((r, w))
+}
+
+/// Cache objects from a [`watcher()`](crate::watcher()) stream into a local [`DiskStore`]
+///
+/// This is the disk-backed equivalent of [`reflector()`](super::reflector()) and passes the stream
+/// through unmodified. Failures to write to the store are logged, and the affected object will be
+/// corrected by the next event for it or the next relist.
+///
+/// ```no_run
+/// use futures::StreamExt;
+/// use k8s_openapi::api::core::v1::Secret;
+/// use kube::runtime::{reflector::disk_store, watcher, WatchStreamExt};
+/// # use kube::api::Api;
+/// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+/// # let client: kube::Client = todo!();
+/// let secrets: Api<Secret> = Api::all(client);
+/// let (reader, writer) = disk_store::disk_store("/var/cache/secrets")?;
+/// let rf = disk_store::reflector(writer, watcher(secrets, Default::default()));
+/// rf.applied_objects().for_each(|_| std::future::ready(())).await;
+/// # Ok(())
+/// # }
+/// ```
+pub fn reflector<K, W>(mut writer: Writer<K>, stream: W) -> impl Stream<Item = W::Item>
+where
+    K: Lookup + Clone + Serialize + DeserializeOwned,
+    K::DynamicType: Eq + Hash + Clone,
+    W: Stream<Item = watcher::Result<watcher::Event<K>>>,
+{
+    let mut stream = Box::pin(stream);
+    stream! {
+        while let Some(event) = stream.next().await {
+            match event {
+                Ok(ev) => {
+                    if let Err(err) = writer.apply_watcher_event(&ev) {
+                        tracing::error!(error = %err, "failed to write event to disk store");
+                    }
+                    yield Ok(ev);
+                },
+                Err(ev) => yield Err(ev)
+            }
+        }
+    }
+}
+
+fn tree_name(generation: u64) -> String {
+    format!("generation-{generation}")
+}
+
+fn db_key<K: Lookup>(key: &ObjectRef<K>) -> Vec<u8> {
+    format!("{}/{}", key.namespace.as_deref().unwrap_or_default(), key.name).into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::disk_store;
+    use crate::{reflector::ObjectRef, watcher};
+    use k8s_openapi::api::core::v1::ConfigMap;
+    use kube_client::api::ObjectMeta;
+
+    fn cm(name: &str, ns: Option<&str>) -> ConfigMap {
+        ConfigMap {
+            metadata: ObjectMeta {
+                name: Some(name.to_string()),
+                namespace: ns.map(String::from),
+                ..ObjectMeta::default()
+            },
+            ..ConfigMap::default()
+        }
+    }
+
+    #[test]
+    fn apply_and_delete_hit_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let (reader, mut writer) = disk_store::<ConfigMap>(dir.path()).unwrap();
+        let obj = cm("a", Some("ns"));
+        writer
+            .apply_watcher_event(&watcher::Event::Apply(obj.clone()))
+            .unwrap();
+        assert_eq!(reader.len(), 1);
+        assert_eq!(reader.get(&ObjectRef::from(&obj)).unwrap(), Some(obj.clone()));
+        writer
+            .apply_watcher_event(&watcher::Event::Delete(obj.clone()))
+            .unwrap();
+        assert!(reader.is_empty());
+        assert_eq!(reader.get(&ObjectRef::from(&obj)).unwrap(), None);
+    }
+
+    #[test]
+    fn relist_replaces_contents() {
+        let dir = tempfile::tempdir().unwrap();
+        let (reader, mut writer) = disk_store::<ConfigMap>(dir.path()).unwrap();
+        let old = cm("old", None);
+        let new = cm("new", None);
+        writer
+            .apply_watcher_event(&watcher::Event::Apply(old.clone()))
+            .unwrap();
+        writer.apply_watcher_event(&watcher::Event::Init).unwrap();
+        writer
+            .apply_watcher_event(&watcher::Event::InitApply(new.clone()))
+            .unwrap();
+        // old contents are served until the relist completes
+        assert_eq!(reader.keys(), vec![ObjectRef::from(&old)]);
+        writer.apply_watcher_event(&watcher::Event::InitDone).unwrap();
+        assert_eq!(reader.keys(), vec![ObjectRef::from(&new)]);
+        let all: Vec<_> = reader.iter().collect::<Result<_, _>>().unwrap();
+        assert_eq!(all, vec![new.clone()]);
+        // cluster-scoped lookups ignore the namespace
+        assert_eq!(
+            reader.get(&ObjectRef::from(&new).within("ns")).unwrap(),
+            Some(new)
+        );
+    }
+
+    #[test]
+    fn iterators_keep_replaced_generations() {
+        let dir = tempfile::tempdir().unwrap();
+        let (reader, mut writer) = disk_store::<ConfigMap>(dir.path()).unwrap();
+        let old = [cm("a", None), cm("b", None)];
+        for obj in &old {
+            writer
+                .apply_watcher_event(&watcher::Event::Apply(obj.clone()))
+                .unwrap();
+        }
+        let mut iter = reader.iter();
+        assert_eq!(iter.next().unwrap().unwrap(), old[0]);
+
+        writer.apply_watcher_event(&watcher::Event::Init).unwrap();
+        writer.apply_watcher_event(&watcher::Event::InitDone).unwrap();
+        assert!(reader.is_empty());
+        assert_eq!(iter.next().unwrap().unwrap(), old[1]);
+        assert!(iter.next().is_none());
+
+        let generations = || writer.db.tree_names().len();
+        // the default tree, and the replaced generation until the iterator is dropped
+        assert_eq!(generations(), 3);
+        drop(iter);
+        assert_eq!(generations(), 2);
+    }
+}
@@ -1,10 +1,13 @@
 //! Caches objects in memory
 
+mod crds;
+#[cfg(feature = "unstable-runtime-disk-store")] pub mod disk_store;
 mod dispatcher;
 mod object_ref;
 pub mod store;
 
 pub use self::{
+    crds::{crd_cache, CrdCache, CrdChange},
     dispatcher::ReflectHandle,
     object_ref::{Extra as ObjectRefExtra, Lookup, ObjectRef},
 };
@@ -1,5 +1,5 @@
 //! Waits for objects to reach desired states
-use std::{future, pin::pin};
+use std::{future, pin::pin, time::Duration};
 
 use futures::TryStreamExt;
 use kube_client::{Api, Resource};
@@ -13,6 +13,9 @@ use crate::watcher::{self, watch_object};
 pub enum Error {
     #[error("failed to probe for whether the condition is fulfilled yet: {0}")]
     ProbeFailed(#[source] watcher::Error),
+
+    #[error("condition was not fulfilled within {0:?}")]
+    TimedOut(Duration),
 }
 
 /// Watch an object, and wait for some condition `cond` to return `true`.
@@ -24,7 +27,7 @@ pub enum Error {
 /// # Caveats
 ///
 /// Keep in mind that the condition is typically fulfilled by an external service, which might not even be available. `await_condition`
-/// does *not* automatically add a timeout. If this is desired, wrap it in [`tokio::time::timeout`].
+/// does *not* automatically add a timeout. If this is desired, use [`await_condition_with_timeout`].
 ///
 /// # Errors
 ///
@@ -68,6 +71,41 @@ where
     Ok(obj)
 }
 
+/// Like [`await_condition`], but gives up after `timeout`
+///
+/// # Errors
+///
+/// Fails with [`Error::TimedOut`] if the condition is not fulfilled within `timeout`, and otherwise
+/// like [`await_condition`].
+///
+/// # Usage
+///
+/// ```
+/// use k8s_openapi::api::apps::v1::Deployment;
+/// use kube::{Api, runtime::wait::{await_condition_with_timeout, conditions}};
+/// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+/// # let client: kube::Client = todo!();
+///
+/// let deploys: Api<Deployment> = Api::default_namespaced(client);
+/// let timeout = std::time::Duration::from_secs(300);
+/// await_condition_with_timeout(deploys, "web", conditions::is_deployment_completed(), timeout).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn await_condition_with_timeout<K>(
+    api: Api<K>,
+    name: &str,
+    cond: impl Condition<K>,
+    timeout: Duration,
+) -> Result<Option<K>, Error>
+where
+    K: Clone + Debug + Send + DeserializeOwned + Resource + 'static,
+{
+    tokio::time::timeout(timeout, await_condition(api, name, cond))
+        .await
+        .map_err(|_| Error::TimedOut(timeout))?
+}
+
 /// A trait for condition functions to be used by [`await_condition`]
 ///
 /// Note that this is auto-implemented for functions of type `fn(Option<&K>) -> bool`.
@@ -164,7 +202,7 @@ pub mod conditions {
     pub use super::Condition;
     use k8s_openapi::{
         api::{
-            apps::v1::Deployment,
+            apps::v1::{DaemonSet, Deployment, StatefulSet},
             batch::v1::Job,
             core::v1::{Pod, Service},
             networking::v1::Ingress,
@@ -225,6 +263,25 @@ pub mod conditions {
         }
     }
 
+    /// An await condition for `Pod` that returns `true` once it is ready to serve requests
+    ///
+    /// Unlike [`is_pod_running`], this waits for the readiness probes of all containers to pass.
+    #[must_use]
+    pub fn is_pod_ready() -> impl Condition<Pod> {
+        |obj: Option<&Pod>| {
+            if let Some(pod) = &obj {
+                if let Some(status) = &pod.status {
+                    if let Some(conds) = &status.conditions {
+                        if let Some(pcond) = conds.iter().find(|c| c.type_ == "Ready") {
+                            return pcond.status == "True";
+                        }
+                    }
+                }
+            }
+            false
+        }
+    }
+
     /// An await condition for `Job` that returns `true` once it is completed
     #[must_use]
     pub fn is_job_completed() -> impl Condition<Job> {
@@ -242,6 +299,23 @@ pub mod conditions {
         }
     }
 
+    /// An await condition for `Job` that returns `true` once it has either completed or failed
+    #[must_use]
+    pub fn is_job_finished() -> impl Condition<Job> {
+        |obj: Option<&Job>| {
+            if let Some(job) = &obj {
+                if let Some(s) = &job.status {
+                    if let Some(conds) = &s.conditions {
+                        return conds
+                            .iter()
+                            .any(|c| (c.type_ == "Complete" || c.type_ == "Failed") && c.status == "True");
+                    }
+                }
+            }
+            false
+        }
+    }
+
     /// An await condition for `Deployment` that returns `true` once the latest deployment has completed
     ///
     /// This looks for the condition that Kubernetes sets for completed deployments:
@@ -264,6 +338,71 @@ pub mod conditions {
         }
     }
 
+    /// Workloads whose rollouts can be awaited with [`is_rolled_out`]
+    pub trait RolledOut {
+        /// Whether the controller has observed the latest spec, and every pod runs the latest template
+        fn is_rolled_out(&self) -> bool;
+    }
+
+    /// Whether the workload controller has observed the latest generation of `obj`
+    fn observed_latest<K: Resource>(obj: &K, observed_generation: Option<i64>) -> bool {
+        observed_generation.is_some_and(|observed| observed >= obj.meta().generation.unwrap_or(0))
+    }
+
+    impl RolledOut for Deployment {
+        fn is_rolled_out(&self) -> bool {
+            let Some(status) = &self.status else { return false };
+            let replicas = self.spec.as_ref().and_then(|s| s.replicas).unwrap_or(1);
+            observed_latest(self, status.observed_generation)
+                && status.updated_replicas.unwrap_or(0) >= replicas
+                // old pods that are still terminating count towards replicas
+                && status.replicas.unwrap_or(0) <= status.updated_replicas.unwrap_or(0)
+                && status.available_replicas.unwrap_or(0) >= status.updated_replicas.unwrap_or(0)
+        }
+    }
+
+    impl RolledOut for StatefulSet {
+        fn is_rolled_out(&self) -> bool {
+            let Some(status) = &self.status else { return false };
+            let spec = self.spec.as_ref();
+            let replicas = spec.and_then(|s| s.replicas).unwrap_or(1);
+            if !observed_latest(self, status.observed_generation)
+                || status.ready_replicas.unwrap_or(0) < replicas
+            {
+                return false;
+            }
+            let partition = spec
+                .and_then(|s| s.update_strategy.as_ref())
+                .and_then(|s| s.rolling_update.as_ref())
+                .and_then(|r| r.partition);
+            match partition {
+                // only the pods at or above the partition are updated
+                Some(partition) => status.updated_replicas.unwrap_or(0) >= replicas - partition,
+                None => status.update_revision.is_some() && status.current_revision == status.update_revision,
+            }
+        }
+    }
+
+    impl RolledOut for DaemonSet {
+        fn is_rolled_out(&self) -> bool {
+            let Some(status) = &self.status else { return false };
+            observed_latest(self, status.observed_generation)
+                && status.updated_number_scheduled.unwrap_or(0) >= status.desired_number_scheduled
+                && status.number_available.unwrap_or(0) >= status.desired_number_scheduled
+        }
+    }
+
+    /// An await condition for `Deployment`s, `StatefulSet`s and `DaemonSet`s that returns `true` once the latest rollout has completed
+    ///
+    /// This follows the checks of `kubectl rollout status`: the controller has to have observed the
+    /// latest generation, and all desired pods have to be updated and available. Unlike
+    /// [`is_deployment_completed`], this is `false` right after a change to the spec, until the
+    /// controller has caught up. `StatefulSet`s with the `OnDelete` update strategy never complete.
+    #[must_use]
+    pub fn is_rolled_out<K: RolledOut>() -> impl Condition<K> {
+        |obj: Option<&K>| obj.is_some_and(RolledOut::is_rolled_out)
+    }
+
     /// An await condition for `Service`s of type `LoadBalancer` that returns `true` once the backing load balancer has an external IP or hostname
     #[must_use]
     pub fn is_service_loadbalancer_provisioned() -> impl Condition<Service> {
@@ -514,6 +653,38 @@ pub mod conditions {
             assert!(is_pod_running().matches_object(Some(&p)))
         }
 
+        #[test]
+        /// fail if pod is running but not ready yet
+        fn pod_ready_unready() {
+            use super::{is_pod_ready, is_pod_running, Condition};
+
+            let pod = r#"
+                apiVersion: v1
+                kind: Pod
+                metadata:
+                  namespace: default
+                  name: testpod
+                spec:
+                  containers:
+                    - name: testcontainer
+                      image: alpine
+                status:
+                  conditions:
+                    - lastProbeTime: null
+                      lastTransitionTime: "2025-03-06T03:53:24Z"
+                      message: "containers with unready status: [testcontainer]"
+                      reason: ContainersNotReady
+                      status: "False"
+                      type: Ready
+                  phase: Running
+            "#;
+
+            let p = serde_yaml::from_str(pod).unwrap();
+            assert!(is_pod_running().matches_object(Some(&p)));
+            assert!(!is_pod_ready().matches_object(Some(&p)));
+            assert!(!is_pod_ready().matches_object(None))
+        }
+
         #[test]
         /// fail if pod is unschedulable
         fn pod_running_unschedulable() {
@@ -653,6 +824,58 @@ pub mod conditions {
             assert!(!is_job_completed().matches_object(None))
         }
 
+        #[test]
+        /// pass if job failed, fail while it is running
+        fn job_finished_failed() {
+            use super::{is_job_completed, is_job_finished, Condition};
+
+            let job = r#"
+                apiVersion: batch/v1
+                kind: Job
+                metadata:
+                  name: pi
+                  namespace: default
+                spec:
+                  template:
+                    spec:
+                      containers:
+                      - name: pi
+                        image: perl:5.34.0
+                status:
+                  conditions:
+                  - lastProbeTime: "2025-03-06T05:27:56Z"
+                    lastTransitionTime: "2025-03-06T05:27:56Z"
+                    message: Job has reached the specified backoff limit
+                    reason: BackoffLimitExceeded
+                    status: "True"
+                    type: Failed
+                  failed: 5
+                  startTime: "2025-03-06T05:27:27Z"
+            "#;
+
+            let j = serde_yaml::from_str(job).unwrap();
+            assert!(is_job_finished().matches_object(Some(&j)));
+            assert!(!is_job_completed().matches_object(Some(&j)));
+
+            let running = r#"
+                apiVersion: batch/v1
+                kind: Job
+                metadata:
+                  name: pi
+                spec:
+                  template:
+                    spec:
+                      containers:
+                      - name: pi
+                        image: perl:5.34.0
+                status:
+                  active: 1
+            "#;
+            let j = serde_yaml::from_str(running).unwrap();
+            assert!(!is_job_finished().matches_object(Some(&j)));
+            assert!(!is_job_finished().matches_object(None))
+        }
+
         #[test]
         /// pass when deployment has been fully rolled out
         fn deployment_completed_ok() {
@@ -717,6 +940,136 @@ pub mod conditions {
             assert!(is_deployment_completed().matches_object(Some(&d)))
         }
 
+        #[test]
+        /// pass only once the restarted generation has been observed and rolled out
+        fn deployment_rolled_out() {
+            use super::{is_rolled_out, Condition};
+            use k8s_openapi::api::apps::v1::Deployment;
+
+            let depl = |observed: i64, updated: i32, available: i32| -> Deployment {
+                serde_yaml::from_str(&format!(
+                    r#"
+                    apiVersion: apps/v1
+                    kind: Deployment
+                    metadata:
+                      name: testapp
+                      generation: 3
+                    spec:
+                      replicas: 3
+                      selector:
+                        matchLabels:
+                          app: test
+                      template:
+                        spec:
+                          containers:
+                          - name: postgres
+                            image: postgres
+                    status:
+                      observedGeneration: {observed}
+                      replicas: 3
+                      updatedReplicas: {updated}
+                      availableReplicas: {available}
+                "#
+                ))
+                .unwrap()
+            };
+
+            assert!(is_rolled_out::<Deployment>().matches_object(Some(&depl(3, 3, 3))));
+            // the controller has not seen the restart yet
+            assert!(!is_rolled_out::<Deployment>().matches_object(Some(&depl(2, 3, 3))));
+            assert!(!is_rolled_out::<Deployment>().matches_object(Some(&depl(3, 2, 3))));
+            assert!(!is_rolled_out::<Deployment>().matches_object(Some(&depl(3, 3, 2))));
+            assert!(!is_rolled_out::<Deployment>().matches_object(None));
+        }
+
+        #[test]
+        /// pass once the pods of a statefulset run the update revision, or the pods above its partition do
+        fn statefulset_rolled_out() {
+            use super::{is_rolled_out, Condition};
+            use k8s_openapi::api::apps::v1::StatefulSet;
+
+            let sts = |strategy: &str, updated: i32, current: &str| -> StatefulSet {
+                serde_yaml::from_str(&format!(
+                    r#"
+                    apiVersion: apps/v1
+                    kind: StatefulSet
+                    metadata:
+                      name: db
+                      generation: 2
+                    spec:
+                      replicas: 3
+                      serviceName: db
+                      selector:
+                        matchLabels:
+                          app: db
+                      updateStrategy: {strategy}
+                      template:
+                        spec:
+                          containers:
+                          - name: postgres
+                            image: postgres
+                    status:
+                      observedGeneration: 2
+                      replicas: 3
+                      readyReplicas: 3
+                      updatedReplicas: {updated}
+                      currentRevision: {current}
+                      updateRevision: db-2
+                "#
+                ))
+                .unwrap()
+            };
+
+            let rolling = "{ type: RollingUpdate }";
+            assert!(is_rolled_out::<StatefulSet>().matches_object(Some(&sts(rolling, 3, "db-2"))));
+            assert!(!is_rolled_out::<StatefulSet>().matches_object(Some(&sts(rolling, 2, "db-1"))));
+
+            let partitioned = "{ type: RollingUpdate, rollingUpdate: { partition: 2 } }";
+            assert!(is_rolled_out::<StatefulSet>().matches_object(Some(&sts(partitioned, 1, "db-1"))));
+            assert!(!is_rolled_out::<StatefulSet>().matches_object(Some(&sts(partitioned, 0, "db-1"))));
+        }
+
+        #[test]
+        /// pass once every scheduled daemonset pod is updated and available
+        fn daemonset_rolled_out() {
+            use super::{is_rolled_out, Condition};
+            use k8s_openapi::api::apps::v1::DaemonSet;
+
+            let ds = |updated: i32, available: i32| -> DaemonSet {
+                serde_yaml::from_str(&format!(
+                    r#"
+                    apiVersion: apps/v1
+                    kind: DaemonSet
+                    metadata:
+                      name: agent
+                      generation: 4
+                    spec:
+                      selector:
+                        matchLabels:
+                          app: agent
+                      template:
+                        spec:
+                          containers:
+                          - name: agent
+                            image: agent
+                    status:
+                      observedGeneration: 4
+                      currentNumberScheduled: 2
+                      desiredNumberScheduled: 2
+                      numberMisscheduled: 0
+                      numberReady: 2
+                      updatedNumberScheduled: {updated}
+                      numberAvailable: {available}
+                "#
+                ))
+                .unwrap()
+            };
+
+            assert!(is_rolled_out::<DaemonSet>().matches_object(Some(&ds(2, 2))));
+            assert!(!is_rolled_out::<DaemonSet>().matches_object(Some(&ds(1, 2))));
+            assert!(!is_rolled_out::<DaemonSet>().matches_object(Some(&ds(2, 1))));
+        }
+
         #[test]
         /// fail if deployment update is still rolling out
         fn deployment_completed_pending() {
@@ -1020,6 +1373,59 @@ pub mod conditions {
     }
 }
 
+/// Utilities for rolling out workloads
+pub mod rollout {
+    use super::{await_condition, conditions};
+    use kube_client::{core::util::Restart, Api, Resource};
+    use serde::de::DeserializeOwned;
+    use std::fmt::Debug;
+    use thiserror::Error;
+
+    #[derive(Debug, Error)]
+    pub enum Error {
+        #[error("failed to restart object: {0}")]
+        Restart(#[source] kube_client::Error),
+        #[error("failed to wait for rollout: {0}")]
+        Await(#[source] super::Error),
+        #[error("object was deleted during the rollout")]
+        Deleted,
+    }
+
+    /// Restart the pods of a workload like `kubectl rollout restart`, and wait for the rollout to complete
+    ///
+    /// Waits for [`conditions::is_rolled_out`], without a timeout. Wrap the future in
+    /// [`tokio::time::timeout`] to give up on rollouts that are stuck.
+    ///
+    /// ```no_run
+    /// use k8s_openapi::api::apps::v1::Deployment;
+    /// use kube::{runtime::wait::rollout::restart_and_await, Api};
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// let deploys: Api<Deployment> = Api::default_namespaced(client);
+    /// let rollout = restart_and_await(deploys, "web");
+    /// let _web = tokio::time::timeout(std::time::Duration::from_secs(300), rollout).await??;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`](enum@Error) if the object could not be restarted, if the wait
+    /// was interrupted, or if the object was deleted before the rollout completed.
+    pub async fn restart_and_await<K>(api: Api<K>, name: &str) -> Result<K, Error>
+    where
+        K: conditions::RolledOut + Restart + Clone + Debug + Send + DeserializeOwned + Resource + 'static,
+    {
+        let restarted = api.rollout_restart(name).await.map_err(Error::Restart)?;
+        let uid = restarted.meta().uid.clone().unwrap_or_default();
+        let cond = conditions::is_rolled_out::<K>().or(conditions::is_deleted(&uid));
+        match await_condition(api, name, cond).await.map_err(Error::Await)? {
+            Some(obj) if obj.meta().uid.as_deref() == Some(uid.as_str()) => Ok(obj),
+            _ => Err(Error::Deleted),
+        }
+    }
+}
+
 /// Utilities for deleting objects
 pub mod delete {
     use super::{await_condition, conditions};
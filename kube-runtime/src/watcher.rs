@@ -13,7 +13,7 @@ use kube_client::{
     error::ErrorResponse,
     Api, Error as ClientErr,
 };
-use serde::de::DeserializeOwned;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::{clone::Clone, collections::VecDeque, fmt::Debug, future, time::Duration};
 use thiserror::Error;
 use tracing::{debug, error, warn};
@@ -127,6 +127,57 @@ impl<K> Event<K> {
     }
 }
 
+/// Objects and the `resourceVersion` they are current at, to start a [`seeded_watcher`] from
+///
+/// Seeds can be persisted, or handed over from another instance, to skip the initial LIST.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Seed<K> {
+    /// The `resourceVersion` the objects were observed at
+    pub resource_version: String,
+    /// The objects that exist at `resource_version`
+    pub objects: Vec<K>,
+}
+
+impl<K: Resource> Seed<K> {
+    /// Create a seed from objects observed at `resource_version`
+    #[must_use]
+    pub fn new(objects: Vec<K>, resource_version: impl Into<String>) -> Self {
+        Self {
+            resource_version: resource_version.into(),
+            objects,
+        }
+    }
+
+    /// Create a seed from a complete list, returns `None` if the list has no `resourceVersion`
+    ///
+    /// The list must not be a page of a paginated list.
+    #[must_use]
+    pub fn from_list(list: ObjectList<K>) -> Option<Self> {
+        let resource_version = list.metadata.resource_version.filter(|rv| !rv.is_empty())?;
+        Some(Self::new(list.items, resource_version))
+    }
+
+    /// Create a seed from the latest state of a set of objects, e.g. the contents of a reflector store
+    ///
+    /// The newest `resourceVersion` of the objects is used. This relies on versions being
+    /// increasing integers, which holds for etcd backed apiservers, but is not guaranteed by the
+    /// Kubernetes API. Returns `None` if any object has a version that is not an integer, or if
+    /// there are no objects to take a version from.
+    ///
+    /// Deletions are not reflected in object versions, so objects deleted after the newest
+    /// remaining object was changed are only removed on the next relist.
+    #[must_use]
+    pub fn from_objects(objects: Vec<K>) -> Option<Self> {
+        let mut newest: Option<u64> = None;
+        for obj in &objects {
+            let version = obj.meta().resource_version.as_deref()?.parse().ok()?;
+            newest = newest.max(Some(version));
+        }
+        Some(Self::new(objects, newest?.to_string()))
+    }
+}
+
 #[derive(Educe, Default)]
 #[educe(Debug)]
 /// The internal finite state machine driving the [`watcher`]
@@ -569,8 +620,7 @@ where
                     (None, State::InitialWatch { stream })
                 }
                 Some(Ok(WatchEvent::Bookmark(bm))) => {
-                    let marks_initial_end = bm.metadata.annotations.contains_key("k8s.io/initial-events-end");
-                    if marks_initial_end {
+                    if bm.is_initial_events_end() {
                         (Some(Ok(Event::InitDone)), State::Watching {
                             resource_version: bm.metadata.resource_version,
                             stream,
@@ -755,9 +805,59 @@ where
 pub fn watcher<K: Resource + Clone + DeserializeOwned + Debug + Send + 'static>(
     api: Api<K>,
     watcher_config: Config,
+) -> impl Stream<Item = Result<Event<K>>> + Send {
+    watch_from(api, watcher_config, State::default())
+}
+
+/// Watches a Kubernetes Resource for changes continuously, starting from a known set of objects
+///
+/// This behaves like [`watcher`], but instead of the initial LIST, the objects of the [`Seed`] are
+/// emitted as the first `Init`, `InitApply` and `InitDone` sequence, after which the watch starts
+/// from the `resourceVersion` of the seed. This makes it cheap to (re)start watchers from a
+/// persisted state, or when taking over from another leader, without every instance doing a cold LIST.
+///
+/// ```no_run
+/// use kube::{api::Api, Client, runtime::{reflector, watcher::{self, seeded_watcher, Seed}, WatchStreamExt}};
+/// use k8s_openapi::api::core::v1::ConfigMap;
+/// use futures::StreamExt;
+/// # async fn wrapper(client: Client, seed: Seed<ConfigMap>) {
+/// let api: Api<ConfigMap> = Api::default_namespaced(client);
+/// let (reader, writer) = reflector::store();
+/// let stream = seeded_watcher(api, watcher::Config::default(), seed)
+///     .default_backoff()
+///     .reflect(writer)
+///     .applied_objects();
+/// # }
+/// ```
+///
+/// If the `resourceVersion` of the seed is too old to watch from, the apiserver returns a `410 Gone`,
+/// which is propagated as an error after which the watcher recovers with a LIST, like [`watcher`] does.
+/// A seed without a `resourceVersion` starts with a LIST right away.
+pub fn seeded_watcher<K: Resource + Clone + DeserializeOwned + Debug + Send + 'static>(
+    api: Api<K>,
+    watcher_config: Config,
+    seed: Seed<K>,
+) -> impl Stream<Item = Result<Event<K>>> + Send {
+    let (init, state) = if seed.resource_version.is_empty() {
+        (None, State::default())
+    } else {
+        // resume from the seed as if it was the last page of a LIST
+        (Some(Ok(Event::Init)), State::InitPage {
+            continue_token: None,
+            objects: seed.objects.into(),
+            last_bookmark: Some(seed.resource_version),
+        })
+    };
+    futures::stream::iter(init).chain(watch_from(api, watcher_config, state))
+}
+
+fn watch_from<K: Resource + Clone + DeserializeOwned + Debug + Send + 'static>(
+    api: Api<K>,
+    watcher_config: Config,
+    state: State<K>,
 ) -> impl Stream<Item = Result<Event<K>>> + Send {
     futures::stream::unfold(
-        (api, watcher_config, State::default()),
+        (api, watcher_config, state),
         |(api, watcher_config, state)| async {
             let (event, state) = step(&FullObject { api: &api }, &watcher_config, state).await;
             Some((event, (api, watcher_config, state)))
@@ -961,3 +1061,71 @@ impl Backoff for DefaultBackoff {
         self.0.reset();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::TryStreamExt;
+    use http::{Method, StatusCode};
+    use k8s_openapi::api::core::v1::ConfigMap;
+    use kube::{api::ObjectMeta, Client};
+
+    fn config_map(name: &str, resource_version: &str) -> ConfigMap {
+        ConfigMap {
+            metadata: ObjectMeta {
+                name: Some(name.into()),
+                resource_version: Some(resource_version.into()),
+                ..ObjectMeta::default()
+            },
+            ..ConfigMap::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn seeded_watcher_skips_the_initial_list() {
+        let (client, mock) = Client::mock();
+        mock.expect(Method::GET, "/api/v1/namespaces/default/configmaps")
+            .respond_watch([WatchEvent::Added(config_map("new", "12"))]);
+        let api: Api<ConfigMap> = Api::default_namespaced(client);
+
+        let seed = Seed::from_objects(vec![config_map("a", "7"), config_map("b", "10")]).unwrap();
+        assert_eq!(seed.resource_version, "10");
+        let events: Vec<_> = seeded_watcher(api, Config::default(), seed)
+            .take(5)
+            .try_collect()
+            .await
+            .unwrap();
+        let names: Vec<_> = events
+            .iter()
+            .map(|event| match event {
+                Event::Init => "init".to_string(),
+                Event::InitApply(cm) => format!("init {}", cm.name_any()),
+                Event::InitDone => "done".to_string(),
+                Event::Apply(cm) => format!("apply {}", cm.name_any()),
+                Event::Delete(cm) => format!("delete {}", cm.name_any()),
+            })
+            .collect();
+        assert_eq!(names, ["init", "init a", "init b", "done", "apply new"]);
+
+        let sent = mock.requests();
+        assert_eq!(sent.len(), 1, "no list is sent");
+        let query = sent[0].uri.query().unwrap_or_default();
+        assert!(
+            query.contains("watch=true") && query.contains("resourceVersion=10"),
+            "{query}"
+        );
+    }
+
+    #[test]
+    fn seeds_need_a_resource_version() {
+        assert!(Seed::<ConfigMap>::from_objects(vec![]).is_none());
+        assert!(Seed::from_objects(vec![config_map("a", "opaque")]).is_none());
+        let list = ObjectList {
+            types: Default::default(),
+            metadata: Default::default(),
+            items: vec![config_map("a", "1")],
+        };
+        assert!(Seed::from_list(list).is_none());
+    }
+}
@@ -0,0 +1,159 @@
+//! A high-level entrypoint for scripts and small tools
+//!
+//! [`Cluster`] wraps a [`Client`] and hands out [`Api`]s without having to pick the right
+//! constructor for the scope of every resource. Everything it returns is a regular [`Api`], so
+//! the full low-level interface is still available.
+//!
+//! ```no_run
+//! use kube::prelude::*;
+//! use k8s_openapi::api::core::v1::{Node, Pod};
+//!
+//! # async fn wrapper() -> Result<(), kube::Error> {
+//! let cluster = Cluster::connect().await?;
+//! for pod in cluster.api::<Pod>().list(&ListParams::default()).await? {
+//!     println!("pod {}", pod.name_any());
+//! }
+//! for node in cluster.api::<Node>().list(&ListParams::default()).await? {
+//!     println!("node {}", node.name_any());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+use crate::{
+    api::DynamicObject,
+    core::{ClusterResourceScope, NamespaceResourceScope},
+    Api, Client, Config, Resource, Result,
+};
+
+mod private {
+    pub trait Sealed {}
+    impl Sealed for super::NamespaceResourceScope {}
+    impl Sealed for super::ClusterResourceScope {}
+}
+
+/// Resource scopes that [`Cluster::api`] knows the default [`Api`] for
+pub trait DefaultScope: private::Sealed + Sized {
+    /// The default [`Api`] for resources of this scope
+    fn default_api<K>(client: Client) -> Api<K>
+    where
+        K: Resource<Scope = Self>,
+        K::DynamicType: Default;
+}
+
+impl DefaultScope for NamespaceResourceScope {
+    fn default_api<K>(client: Client) -> Api<K>
+    where
+        K: Resource<Scope = Self>,
+        K::DynamicType: Default,
+    {
+        Api::default_namespaced(client)
+    }
+}
+
+impl DefaultScope for ClusterResourceScope {
+    fn default_api<K>(client: Client) -> Api<K>
+    where
+        K: Resource<Scope = Self>,
+        K::DynamicType: Default,
+    {
+        Api::all(client)
+    }
+}
+
+/// A connection to a cluster, see the [module docs](self)
+#[derive(Clone)]
+pub struct Cluster {
+    client: Client,
+}
+
+impl Cluster {
+    /// Connect to the cluster of the environment
+    ///
+    /// This uses the current context of the kubeconfig, or the service account when running in a pod,
+    /// like [`Client::try_default`].
+    pub async fn connect() -> Result<Self> {
+        Ok(Self::from(Client::try_default().await?))
+    }
+
+    /// Connect to the cluster described by `config`
+    pub fn from_config(config: Config) -> Result<Self> {
+        Ok(Self::from(Client::try_from(config)?))
+    }
+
+    /// The underlying [`Client`]
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    /// The default namespace, used by [`Cluster::api`] for namespaced resources
+    pub fn default_namespace(&self) -> &str {
+        self.client.default_namespace()
+    }
+
+    /// An [`Api`] for `K`
+    ///
+    /// Namespaced resources use the default namespace, and cluster scoped resources the whole cluster.
+    /// Use [`Cluster::namespaced`] or [`Cluster::all`] to choose otherwise.
+    pub fn api<K>(&self) -> Api<K>
+    where
+        K: Resource,
+        K::DynamicType: Default,
+        K::Scope: DefaultScope,
+    {
+        <K::Scope as DefaultScope>::default_api(self.client.clone())
+    }
+
+    /// An [`Api`] for `K` in `namespace`
+    pub fn namespaced<K>(&self, namespace: &str) -> Api<K>
+    where
+        K: Resource<Scope = NamespaceResourceScope>,
+        K::DynamicType: Default,
+    {
+        Api::namespaced(self.client.clone(), namespace)
+    }
+
+    /// An [`Api`] for `K` across all namespaces, or for a cluster scoped resource
+    pub fn all<K>(&self) -> Api<K>
+    where
+        K: Resource,
+        K::DynamicType: Default,
+    {
+        Api::all(self.client.clone())
+    }
+
+    /// An [`Api`] for a resource that is only known at runtime, e.g. from a manifest
+    ///
+    /// See [`Api::dynamic_from_gvk`].
+    pub async fn dynamic(&self, api_version: &str, kind: &str) -> Result<Api<DynamicObject>> {
+        Api::dynamic_from_gvk(self.client.clone(), api_version, kind).await
+    }
+}
+
+impl From<Client> for Cluster {
+    fn from(client: Client) -> Self {
+        Self { client }
+    }
+}
+
+impl From<Cluster> for Client {
+    fn from(cluster: Cluster) -> Self {
+        cluster.client
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Cluster;
+    use crate::Client;
+    use k8s_openapi::api::core::v1::{Node, Pod};
+
+    #[tokio::test]
+    async fn api_picks_constructor_by_scope() {
+        let (client, _mock) = Client::mock();
+        let cluster = Cluster::from(client);
+        assert_eq!(cluster.api::<Pod>().resource_url(), "/api/v1/namespaces/default/pods");
+        assert_eq!(cluster.api::<Node>().resource_url(), "/api/v1/nodes");
+        assert_eq!(cluster.namespaced::<Pod>("apps").resource_url(), "/api/v1/namespaces/apps/pods");
+        assert_eq!(cluster.all::<Pod>().resource_url(), "/api/v1/pods");
+    }
+}
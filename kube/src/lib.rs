@@ -145,6 +145,10 @@ cfg_client! {
     pub use client::Client;
     #[doc(inline)]
     pub use discovery::Discovery;
+
+    pub mod cluster;
+    #[doc(inline)]
+    pub use cluster::Cluster;
 }
 
 cfg_config! {
@@ -172,6 +176,10 @@ pub use kube_derive::Resource;
 #[cfg_attr(docsrs, doc(cfg(feature = "derive")))]
 pub use kube_derive::CELSchema;
 
+#[cfg(feature = "derive")]
+#[cfg_attr(docsrs, doc(cfg(feature = "derive")))]
+pub use kube_derive::DeepMerge;
+
 #[cfg(feature = "runtime")]
 #[cfg_attr(docsrs, doc(cfg(feature = "runtime")))]
 #[doc(inline)]
@@ -180,6 +188,14 @@ pub use kube_runtime as runtime;
 pub use crate::core::{CustomResourceExt, Resource, ResourceExt};
 #[doc(inline)] pub use kube_core as core;
 
+#[cfg(feature = "test-utils")]
+#[cfg_attr(docsrs, doc(cfg(feature = "test-utils")))]
+pub mod test;
+
+#[cfg(feature = "operator")]
+#[cfg_attr(docsrs, doc(cfg(feature = "operator")))]
+pub mod operator;
+
 // Mock tests for the runtime
 #[cfg(test)]
 #[cfg(all(feature = "derive", feature = "runtime"))]
@@ -196,12 +212,21 @@ pub mod prelude {
     //! use kube::prelude::*;
     //! ```
     //!
+    //! Besides the extension traits, it contains what scripts and small tools need for the
+    //! common case: the [`Cluster`](crate::Cluster) facade, [`Api`](crate::Api) and its parameters.
+    //!
     //! The prelude may grow over time as additional items see ubiquitous use.
 
     #[cfg(feature = "client")]
     #[allow(unreachable_pub)]
     pub use crate::client::ConfigExt as _;
 
+    #[cfg(feature = "client")]
+    pub use crate::{
+        api::{Api, DeleteParams, ListParams, Patch, PatchParams, PostParams, WatchParams},
+        Client, Cluster,
+    };
+
     #[cfg(feature = "unstable-client")] pub use crate::client::scope::NamespacedRef;
 
     #[allow(unreachable_pub)] pub use crate::core::PartialObjectMetaExt as _;
@@ -209,6 +234,7 @@ pub mod prelude {
     pub use crate::{core::crd::CustomResourceExt as _, Resource as _, ResourceExt as _};
 
     #[cfg(feature = "runtime")] pub use crate::runtime::utils::WatchStreamExt as _;
+    #[cfg(feature = "runtime")] pub use crate::runtime::watcher;
 }
 
 // Tests that require a cluster and the complete feature set
@@ -0,0 +1,403 @@
+//! A consistent operational surface for operators
+//!
+//! [`Settings`] collects the knobs that almost every operator needs: which cluster to talk to,
+//! which namespaces to watch, whether to run leader election, where to serve metrics and how much
+//! to log. They are loaded from, in increasing order of precedence:
+//!
+//! 1. a YAML file, given by `--config` or the `<PREFIX>_CONFIG` environment variable,
+//! 2. environment variables, e.g. `<PREFIX>_NAMESPACES=default,kube-system`,
+//! 3. command line flags, e.g. `--namespace default --namespace kube-system`.
+//!
+//! | Setting                      | Flag                          | Environment variable                |
+//! |------------------------------|-------------------------------|-------------------------------------|
+//! | `kubeconfig`                 | `--kubeconfig`                | `<PREFIX>_KUBECONFIG`               |
+//! | `context`                    | `--context`                   | `<PREFIX>_CONTEXT`                  |
+//! | `namespaces`                 | `--namespace`, `-n` (repeat)  | `<PREFIX>_NAMESPACES` (comma separated) |
+//! | `leader-election`            | `--leader-election[=bool]`    | `<PREFIX>_LEADER_ELECTION`          |
+//! | `leader-election-namespace`  | `--leader-election-namespace` | `<PREFIX>_LEADER_ELECTION_NAMESPACE` |
+//! | `identity`                   | `--identity`                  | `<PREFIX>_IDENTITY`                 |
+//! | `metrics-addr`               | `--metrics-addr`              | `<PREFIX>_METRICS_ADDR`             |
+//! | `log-level`                  | `--log-level`                 | `<PREFIX>_LOG_LEVEL`                |
+//!
+//! Other arguments, like the flags and subcommands of the operator itself and all arguments after
+//! `--`, are kept in [`Settings::args`] in their original order, so that they can be parsed further.
+//!
+//! ```no_run
+//! use k8s_openapi::api::core::v1::ConfigMap;
+//! use kube::operator::Settings;
+//! # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+//! let settings = Settings::load("MY_OPERATOR")?;
+//! let client = settings.client().await?;
+//! let apis = settings.apis::<ConfigMap>(&client);
+//! if let Some(elector) = settings.leader_elector(&client, "my-operator").await? {
+//!     let leadership = elector.run(); // only reconcile while leading
+//! }
+//! # Ok(())
+//! # }
+//! ```
+use std::{
+    net::SocketAddr,
+    path::{Path, PathBuf},
+};
+
+use k8s_openapi::NamespaceResourceScope;
+use serde::Deserialize;
+
+use crate::{
+    config::{InferConfigError, KubeConfigOptions, Kubeconfig, KubeconfigError},
+    runtime::leader_election::{self, LeaderElector, Lock},
+    Api, Client, Config, Resource,
+};
+
+/// Errors from loading [`Settings`] or acting on them
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// Failed to read the settings file
+    #[error("failed to read settings file {0:?}: {1}")]
+    ReadFile(PathBuf, #[source] std::io::Error),
+
+    /// Failed to parse the settings file
+    #[error("failed to parse settings file {0:?}: {1}")]
+    ParseFile(PathBuf, #[source] serde_yaml::Error),
+
+    /// A flag without its value
+    #[error("missing value for {0}")]
+    MissingValue(String),
+
+    /// A setting with a value of the wrong type
+    #[error("invalid value {value:?} for {key}")]
+    InvalidValue {
+        /// The setting
+        key: String,
+        /// The rejected value
+        value: String,
+    },
+
+    /// Failed to load the selected kubeconfig
+    #[error("failed to load kubeconfig: {0}")]
+    Kubeconfig(#[source] KubeconfigError),
+
+    /// Failed to infer a config
+    #[error(transparent)]
+    InferConfig(#[from] InferConfigError),
+
+    /// Failed to create a client
+    #[error("failed to create client: {0}")]
+    Client(#[source] crate::Error),
+
+    /// Leader election is enabled, but no identity is set
+    #[error("leader election requires an identity, set identity or HOSTNAME")]
+    MissingIdentity,
+
+    /// Failed to set up leader election
+    #[error("failed to set up leader election: {0}")]
+    LeaderElection(#[source] leader_election::Error),
+}
+
+/// Common operator settings, see the [module documentation](self)
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Settings {
+    /// The kubeconfig to use instead of inferring the config
+    pub kubeconfig: Option<PathBuf>,
+    /// The kubeconfig context to use instead of the current one
+    pub context: Option<String>,
+    /// The namespaces to watch, all namespaces when empty
+    pub namespaces: Vec<String>,
+    /// Whether to only reconcile while holding a leader election lock
+    pub leader_election: bool,
+    /// The namespace of the leader election lock, the client's default namespace when unset
+    pub leader_election_namespace: Option<String>,
+    /// The identity of this replica for leader election, `HOSTNAME` when unset
+    pub identity: Option<String>,
+    /// The address to serve metrics on, if any
+    pub metrics_addr: Option<SocketAddr>,
+    /// The log level or filter directive, e.g. `info` or `my_operator=debug`
+    pub log_level: Option<String>,
+    /// The command line arguments that are not settings
+    #[serde(skip)]
+    pub args: Vec<String>,
+}
+
+impl Settings {
+    /// Load the settings from the process environment and command line arguments
+    ///
+    /// Environment variables are read with the `env_prefix`, e.g. `MY_OPERATOR_NAMESPACES` for the
+    /// prefix `MY_OPERATOR`.
+    pub fn load(env_prefix: &str) -> Result<Self, Error> {
+        Self::from_sources(env_prefix, std::env::vars(), std::env::args().skip(1))
+    }
+
+    /// Load the settings from the given environment variables and command line arguments
+    ///
+    /// `args` must not include the program name.
+    pub fn from_sources(
+        env_prefix: &str,
+        env: impl IntoIterator<Item = (String, String)>,
+        args: impl IntoIterator<Item = String>,
+    ) -> Result<Self, Error> {
+        let prefix = format!("{env_prefix}_");
+        let env = env
+            .into_iter()
+            .filter_map(|(k, v)| {
+                let key = k.strip_prefix(&prefix)?.to_lowercase().replace('_', "-");
+                Some((key, v))
+            })
+            .collect::<Vec<_>>();
+        let (flags, args) = parse_flags(args)?;
+
+        let file = flags
+            .iter()
+            .chain(&env)
+            .find(|(k, _)| k == "config")
+            .map(|(_, v)| PathBuf::from(v));
+        let mut settings = match file {
+            Some(path) => Self::from_file(&path)?,
+            None => Self::default(),
+        };
+        // other variables may share the prefix, e.g. service links of a service named like the operator,
+        // which are ignored
+        for (key, value) in env.iter().filter(|(k, _)| k != "config") {
+            settings.set(key, value)?;
+        }
+        let mut namespaces = vec![];
+        for (key, value) in flags.iter().filter(|(k, _)| k != "config") {
+            if key == "namespace" {
+                namespaces.push(value.clone());
+            } else {
+                settings.set(key, value)?;
+            }
+        }
+        if !namespaces.is_empty() {
+            settings.namespaces = namespaces;
+        }
+        settings.args = args;
+        Ok(settings)
+    }
+
+    /// Load the settings from a YAML file
+    pub fn from_file(path: &Path) -> Result<Self, Error> {
+        let data = std::fs::read_to_string(path).map_err(|e| Error::ReadFile(path.into(), e))?;
+        serde_yaml::from_str(&data).map_err(|e| Error::ParseFile(path.into(), e))
+    }
+
+    /// Set the setting `key`, unknown keys are ignored
+    fn set(&mut self, key: &str, value: &str) -> Result<(), Error> {
+        let invalid = || Error::InvalidValue {
+            key: key.into(),
+            value: value.into(),
+        };
+        match key {
+            "kubeconfig" => self.kubeconfig = Some(value.into()),
+            "context" => self.context = Some(value.into()),
+            "namespaces" => {
+                self.namespaces = value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|ns| !ns.is_empty())
+                    .map(String::from)
+                    .collect();
+            }
+            "leader-election" => self.leader_election = value.parse().map_err(|_| invalid())?,
+            "leader-election-namespace" => self.leader_election_namespace = Some(value.into()),
+            "identity" => self.identity = Some(value.into()),
+            "metrics-addr" => self.metrics_addr = Some(value.parse().map_err(|_| invalid())?),
+            "log-level" => self.log_level = Some(value.into()),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// The client [`Config`] selected by the `kubeconfig` and `context` settings
+    ///
+    /// The config is inferred as with [`Config::infer`] when neither is set.
+    pub async fn config(&self) -> Result<Config, Error> {
+        if self.kubeconfig.is_none() && self.context.is_none() {
+            return Ok(Config::infer().await?);
+        }
+        let kubeconfig = match &self.kubeconfig {
+            Some(path) => Kubeconfig::read_from(path),
+            None => Kubeconfig::read(),
+        }
+        .map_err(Error::Kubeconfig)?;
+        let options = KubeConfigOptions {
+            context: self.context.clone(),
+            ..KubeConfigOptions::default()
+        };
+        Config::from_custom_kubeconfig(kubeconfig, &options)
+            .await
+            .map_err(Error::Kubeconfig)
+    }
+
+    /// A [`Client`] for the selected [`Config`]
+    pub async fn client(&self) -> Result<Client, Error> {
+        Client::try_from(self.config().await?).map_err(Error::Client)
+    }
+
+    /// An [`Api`] for every watched namespace, or a single cluster wide [`Api`] when the
+    /// `namespaces` setting is empty
+    pub fn apis<K>(&self, client: &Client) -> Vec<Api<K>>
+    where
+        K: Resource<Scope = NamespaceResourceScope>,
+        K::DynamicType: Default,
+    {
+        if self.namespaces.is_empty() {
+            return vec![Api::all(client.clone())];
+        }
+        self.namespaces
+            .iter()
+            .map(|ns| Api::namespaced(client.clone(), ns))
+            .collect()
+    }
+
+    /// A [`LeaderElector`] for the lock `name` when leader election is enabled
+    ///
+    /// The lock is a `Lease`, or a `ConfigMap` on clusters without the `coordination.k8s.io` API group.
+    pub async fn leader_elector(&self, client: &Client, name: &str) -> Result<Option<LeaderElector>, Error> {
+        if !self.leader_election {
+            return Ok(None);
+        }
+        let identity = self
+            .identity
+            .clone()
+            .or_else(|| std::env::var("HOSTNAME").ok())
+            .ok_or(Error::MissingIdentity)?;
+        let namespace = self
+            .leader_election_namespace
+            .as_deref()
+            .unwrap_or(client.default_namespace());
+        let lock = Lock::detect(client.clone(), namespace, name)
+            .await
+            .map_err(Error::LeaderElection)?;
+        Ok(Some(LeaderElector::new(
+            lock,
+            identity,
+            leader_election::Config::default(),
+        )))
+    }
+}
+
+/// The flags of settings, besides `--no-leader-election`
+const FLAGS: &[&str] = &[
+    "config",
+    "kubeconfig",
+    "context",
+    "namespace",
+    "leader-election",
+    "leader-election-namespace",
+    "identity",
+    "metrics-addr",
+    "log-level",
+];
+
+/// Split `args` into the `(key, value)` pairs of settings and the other arguments
+///
+/// Settings are given as `--key value`, `--key=value` or bare boolean flags.
+fn parse_flags(
+    args: impl IntoIterator<Item = String>,
+) -> Result<(Vec<(String, String)>, Vec<String>), Error> {
+    let (mut flags, mut rest) = (vec![], vec![]);
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            rest.extend(args);
+            break;
+        }
+        let Some((key, value)) = setting(&arg) else {
+            rest.push(arg);
+            continue;
+        };
+        let (key, value) = match (key, value) {
+            (key, Some(value)) => (key, value.to_string()),
+            ("leader-election", None) => (key, "true".to_string()),
+            ("no-leader-election", None) => ("leader-election", "false".to_string()),
+            (key, None) => (key, args.next().ok_or_else(|| Error::MissingValue(arg.clone()))?),
+        };
+        flags.push((key.to_string(), value));
+    }
+    Ok((flags, rest))
+}
+
+/// The key and the value, if included, of an argument that is a setting
+fn setting(arg: &str) -> Option<(&str, Option<&str>)> {
+    if arg == "-n" {
+        return Some(("namespace", None));
+    }
+    let flag = arg.strip_prefix("--")?;
+    let (key, value) = match flag.split_once('=') {
+        Some((key, value)) => (key, Some(value)),
+        None => (flag, None),
+    };
+    let known = FLAGS.contains(&key) || (key == "no-leader-election" && value.is_none());
+    known.then_some((key, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings<'a>(s: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+        s.into_iter().map(String::from).collect()
+    }
+
+    #[test]
+    fn flags_override_env_override_file() {
+        let file = std::env::temp_dir().join(format!("kube-operator-settings-{}.yaml", std::process::id()));
+        let yaml = "namespaces: [from-file]\nidentity: file\ncontext: file\n\
+                    log-level: debug\nmetrics-addr: 0.0.0.0:8080\n";
+        std::fs::write(&file, yaml).unwrap();
+        let env = [
+            ("OP_CONFIG", file.to_str().unwrap()),
+            ("OP_NAMESPACES", "a, b"),
+            ("OP_CONTEXT", "env"),
+            ("OP_LEADER_ELECTION", "true"),
+            ("OP_METRICS_ADDR", "127.0.0.1:9090"),
+            ("OP_LOG_LEVEL", "info"),
+            ("OP_SERVICE_HOST", "ignored"),
+            ("OTHER_CONTEXT", "ignored"),
+        ]
+        .map(|(k, v)| (k.to_string(), v.to_string()));
+        let args = strings(["--context=flag", "-n", "c", "--namespace", "d"]);
+        let settings = Settings::from_sources("OP", env, args).unwrap();
+        std::fs::remove_file(&file).unwrap();
+
+        assert_eq!(settings, Settings {
+            context: Some("flag".into()),
+            namespaces: strings(["c", "d"]),
+            leader_election: true,
+            identity: Some("file".into()),
+            metrics_addr: Some("127.0.0.1:9090".parse().unwrap()),
+            log_level: Some("info".into()),
+            ..Settings::default()
+        });
+    }
+
+    #[test]
+    fn other_args_are_kept() {
+        let load = |args: &[&str]| Settings::from_sources("OP", [], strings(args.iter().copied()));
+        assert!(load(&["--leader-election"]).unwrap().leader_election);
+        assert!(
+            !load(&["--leader-election", "--no-leader-election"])
+                .unwrap()
+                .leader_election
+        );
+        assert!(matches!(load(&["--context"]), Err(Error::MissingValue(_))));
+        assert!(matches!(
+            load(&["--leader-election=nope"]),
+            Err(Error::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            load(&["--metrics-addr", "nope"]),
+            Err(Error::InvalidValue { .. })
+        ));
+        let settings = load(&["--metrics-addr", "[::]:8080", "--log-level=my_operator=debug"]).unwrap();
+        assert_eq!(settings.metrics_addr, Some("[::]:8080".parse().unwrap()));
+        assert_eq!(settings.log_level.as_deref(), Some("my_operator=debug"));
+
+        let args = ["serve", "--port", "80", "-n", "a", "--", "--context", "x"];
+        let settings = load(&args).unwrap();
+        assert_eq!(settings.namespaces, ["a"]);
+        assert_eq!(settings.context, None);
+        assert_eq!(settings.args, ["serve", "--port", "80", "--context", "x"]);
+    }
+}
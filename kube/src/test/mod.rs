@@ -0,0 +1,189 @@
+//! Scaffolding for integration tests that run against a real cluster
+//!
+//! Operators typically need their [`CustomResource`](crate::CustomResource) types installed
+//! before any integration test can run. [`install_crds`] applies the derived CRDs
+//! of several types at once, waits for them to become `Established`, and returns
+//! a [`CrdGuard`] that removes them again when the test is done.
+//!
+//! ```no_run
+//! # use kube::{Client, CustomResource};
+//! # use schemars::JsonSchema;
+//! # use serde::{Deserialize, Serialize};
+//! # #[derive(CustomResource, Deserialize, Serialize, Clone, Debug, JsonSchema)]
+//! # #[kube(group = "clux.dev", version = "v1", kind = "Foo", namespaced)]
+//! # pub struct FooSpec { info: String }
+//! # #[derive(CustomResource, Deserialize, Serialize, Clone, Debug, JsonSchema)]
+//! # #[kube(group = "clux.dev", version = "v1", kind = "Bar", namespaced)]
+//! # pub struct BarSpec { info: String }
+//! # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = Client::try_default().await?;
+//! let crds = kube::test::install_crds::<(Foo, Bar)>(client.clone()).await?;
+//! // ... exercise the controller against the cluster
+//! crds.cleanup().await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Without a cluster, [`simulation`] runs reconcilers against recorded cluster state instead.
+use std::time::Duration;
+
+use crate::{
+    api::{Api, DeleteParams, Patch, PatchParams},
+    core::{CustomResourceExt, ErrorResponse},
+    runtime::wait::{self, await_condition, conditions},
+    Client,
+};
+use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition;
+
+pub mod simulation;
+
+/// Default field manager used when applying CRDs
+const FIELD_MANAGER: &str = "kube-test";
+
+/// Errors from the test scaffolding
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// Failed to apply a CRD
+    #[error("failed to apply crd {0}: {1}")]
+    Apply(String, #[source] crate::Error),
+
+    /// Failed to watch a CRD for establishment
+    #[error("failed to await crd {0}: {1}")]
+    Await(String, #[source] wait::Error),
+
+    /// A CRD did not become established in time
+    #[error("timed out waiting for crd {0} to become established")]
+    Timeout(String),
+
+    /// Failed to delete a CRD
+    #[error("failed to delete crd {0}: {1}")]
+    Delete(String, #[source] crate::Error),
+}
+
+/// A set of custom resource types whose CRDs can be installed together
+///
+/// Implemented for tuples of up to 8 [`CustomResourceExt`] types.
+/// Use a single element tuple like `(Foo,)` to install one CRD.
+pub trait CrdSet {
+    /// The CRDs of every type in the set
+    fn crds() -> Vec<CustomResourceDefinition>;
+}
+
+macro_rules! impl_crd_set {
+    ($($t:ident),+) => {
+        impl<$($t: CustomResourceExt),+> CrdSet for ($($t,)+) {
+            fn crds() -> Vec<CustomResourceDefinition> {
+                vec![$($t::crd()),+]
+            }
+        }
+    };
+}
+
+impl_crd_set!(A);
+impl_crd_set!(A, B);
+impl_crd_set!(A, B, C);
+impl_crd_set!(A, B, C, D);
+impl_crd_set!(A, B, C, D, E);
+impl_crd_set!(A, B, C, D, E, F);
+impl_crd_set!(A, B, C, D, E, F, G);
+impl_crd_set!(A, B, C, D, E, F, G, H);
+
+/// Apply the CRDs of all types in `S` and wait for them to become established
+///
+/// Waits up to 30 seconds per CRD. See [`install_crds_with_timeout`] to configure this.
+pub async fn install_crds<S: CrdSet>(client: Client) -> Result<CrdGuard, Error> {
+    install_crds_with_timeout::<S>(client, Duration::from_secs(30)).await
+}
+
+/// Apply the CRDs of all types in `S` and wait up to `timeout` for each to become established
+pub async fn install_crds_with_timeout<S: CrdSet>(
+    client: Client,
+    timeout: Duration,
+) -> Result<CrdGuard, Error> {
+    let api: Api<CustomResourceDefinition> = Api::all(client);
+    let pp = PatchParams::apply(FIELD_MANAGER).force();
+    let mut guard = CrdGuard {
+        api: api.clone(),
+        names: vec![],
+        armed: true,
+    };
+    for crd in S::crds() {
+        let name = crd.metadata.name.clone().unwrap_or_default();
+        api.patch(&name, &pp, &Patch::Apply(&crd))
+            .await
+            .map_err(|e| Error::Apply(name.clone(), e))?;
+        // track before waiting so a failed wait still cleans up
+        guard.names.push(name.clone());
+        let established = await_condition(api.clone(), &name, conditions::is_crd_established());
+        match tokio::time::timeout(timeout, established).await {
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => return Err(Error::Await(name, e)),
+            Err(_) => return Err(Error::Timeout(name)),
+        }
+    }
+    Ok(guard)
+}
+
+/// Guard object for CRDs installed by [`install_crds`]
+///
+/// Prefer calling [`CrdGuard::cleanup`] at the end of a test to delete the CRDs deterministically.
+/// If the guard is dropped without cleanup, deletion is spawned in the background on the
+/// current tokio runtime, which may not complete if the runtime is shutting down.
+#[must_use = "the CRDs are deleted when the guard is dropped"]
+pub struct CrdGuard {
+    api: Api<CustomResourceDefinition>,
+    names: Vec<String>,
+    armed: bool,
+}
+
+impl CrdGuard {
+    /// Names of the installed CRDs
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Delete the installed CRDs
+    ///
+    /// CRDs that no longer exist are ignored.
+    pub async fn cleanup(mut self) -> Result<(), Error> {
+        self.armed = false;
+        for name in std::mem::take(&mut self.names) {
+            delete_crd(&self.api, &name)
+                .await
+                .map_err(|e| Error::Delete(name, e))?;
+        }
+        Ok(())
+    }
+
+    /// Leave the CRDs installed when the guard is dropped
+    ///
+    /// Useful for inspecting the cluster after a failing test.
+    pub fn keep(mut self) {
+        self.armed = false;
+    }
+}
+
+impl Drop for CrdGuard {
+    fn drop(&mut self) {
+        if !self.armed || self.names.is_empty() {
+            return;
+        }
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            let api = self.api.clone();
+            let names = std::mem::take(&mut self.names);
+            handle.spawn(async move {
+                for name in names {
+                    let _ = delete_crd(&api, &name).await;
+                }
+            });
+        }
+    }
+}
+
+async fn delete_crd(api: &Api<CustomResourceDefinition>, name: &str) -> crate::Result<()> {
+    match api.delete(name, &DeleteParams::default()).await {
+        Ok(_) => Ok(()),
+        Err(crate::Error::Api(ErrorResponse { code: 404, .. })) => Ok(()),
+        Err(e) => Err(e),
+    }
+}
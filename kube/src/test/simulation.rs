@@ -0,0 +1,297 @@
+//! Running reconcilers against recorded cluster state, without a cluster
+//!
+//! A [`Simulation`] loads a snapshot of objects into a [`FakeCluster`], e.g. fixtures, or the
+//! state of a [`Store`](crate::runtime::reflector::Store) captured from a real cluster. It then
+//! runs reconcilers against the fake in rounds: every round reconciles every object of the
+//! reconciled types once, and the simulation stops at a fixed point, once a round leaves the
+//! cluster unchanged. The [`Report`] lists every change the reconcilers made on the way, which
+//! answers what a controller would do to that state.
+//!
+//! ```
+//! use std::sync::Arc;
+//! use k8s_openapi::api::core::v1::ConfigMap;
+//! use kube::{
+//!     api::{Api, Patch, PatchParams, ResourceExt},
+//!     runtime::controller::Action,
+//!     test::simulation::Simulation,
+//!     Client,
+//! };
+//!
+//! async fn reconcile(cm: Arc<ConfigMap>, client: Arc<Client>) -> Result<Action, kube::Error> {
+//!     let api: Api<ConfigMap> = Api::namespaced((*client).clone(), &cm.namespace().unwrap());
+//!     let patch = serde_json::json!({ "metadata": { "labels": { "reconciled": "true" } } });
+//!     api.patch(&cm.name_any(), &PatchParams::default(), &Patch::Merge(&patch)).await?;
+//!     Ok(Action::await_change())
+//! }
+//!
+//! # async fn doc() -> Result<(), Box<dyn std::error::Error>> {
+//! let sim = Simulation::new().load_yaml(r#"
+//! apiVersion: v1
+//! kind: ConfigMap
+//! metadata: { name: settings, namespace: default }
+//! "#)?;
+//! let client = Arc::new(sim.client());
+//! let report = sim.reconciler(reconcile, client).run().await;
+//!
+//! assert!(report.converged);
+//! assert_eq!(report.mutations.len(), 1);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The simulation only sees what the reconcilers do through the client. Requeues of the returned
+//! actions are ignored, since every object is reconciled in every round anyway, and the
+//! [`FakeCluster`] documents what the fake apiserver does and does not do.
+use std::{fmt::Display, future::Future, pin::Pin, sync::Arc};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    api::{Api, ListParams, ResourceExt},
+    client::fake::{FakeCluster, Mutation},
+    runtime::controller::Action,
+    Client, Resource,
+};
+
+/// Reconciles every object of one type once, and returns the failures
+type Round = Box<dyn Fn() -> RoundFuture + Send + Sync>;
+type RoundFuture = Pin<Box<dyn Future<Output = Vec<ReconcileError>> + Send>>;
+
+/// Reconcilers and the cluster state they run against, see the [module docs](self)
+pub struct Simulation {
+    client: Client,
+    cluster: FakeCluster,
+    reconcilers: Vec<Round>,
+    max_rounds: usize,
+}
+
+/// The outcome of [`Simulation::run`]
+#[derive(Clone, Debug, Default)]
+pub struct Report {
+    /// How many rounds were run, including the final round without changes
+    pub rounds: usize,
+    /// Whether a fixed point was reached within the maximum number of rounds
+    pub converged: bool,
+    /// Every change the reconcilers made, in the order they were made
+    pub mutations: Vec<Mutation>,
+    /// Every failed reconciliation, in the order they failed
+    pub errors: Vec<ReconcileError>,
+}
+
+/// A reconciliation that failed during a [`Simulation`]
+#[derive(Clone, Debug)]
+pub struct ReconcileError {
+    /// The round the failure happened in, starting at 1
+    pub round: usize,
+    /// The kind of the reconciled object
+    pub kind: String,
+    /// The namespace of the object, `None` for cluster scoped objects
+    pub namespace: Option<String>,
+    /// The name of the object, `None` if the objects could not be listed
+    pub name: Option<String>,
+    /// The displayed error
+    pub message: String,
+}
+
+impl Default for Simulation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Simulation {
+    /// A simulation against an empty [`FakeCluster`], without reconcilers
+    ///
+    /// Stops after at most 10 rounds, see [`Simulation::max_rounds`].
+    pub fn new() -> Self {
+        let (client, cluster) = Client::fake();
+        Self {
+            client,
+            cluster,
+            reconcilers: vec![],
+            max_rounds: 10,
+        }
+    }
+
+    /// A client for the fake cluster, to build the contexts of the reconcilers with
+    pub fn client(&self) -> Client {
+        self.client.clone()
+    }
+
+    /// The fake cluster, for loading objects and inspecting the state after a run
+    pub fn cluster(&self) -> &FakeCluster {
+        &self.cluster
+    }
+
+    /// Load a snapshot of objects of type `K`
+    ///
+    /// For the state of a [`Store`](crate::runtime::reflector::Store), clone the objects out of
+    /// [`Store::state`](crate::runtime::reflector::Store::state) first.
+    #[must_use]
+    pub fn load<K>(self, objects: impl IntoIterator<Item = K>) -> Self
+    where
+        K: Resource + Serialize,
+        K::DynamicType: Default,
+    {
+        for obj in objects {
+            self.cluster.insert(&obj);
+        }
+        self
+    }
+
+    /// Load the objects of multi-document YAML fixtures, see [`FakeCluster::load_yaml`]
+    pub fn load_yaml(self, manifests: &str) -> crate::Result<Self> {
+        self.cluster.load_yaml(manifests)?;
+        Ok(self)
+    }
+
+    /// Stop after `rounds` rounds, even if no fixed point was reached
+    #[must_use]
+    pub fn max_rounds(mut self, rounds: usize) -> Self {
+        self.max_rounds = rounds;
+        self
+    }
+
+    /// Add a reconciler for objects of type `K`, with the same signature as for a
+    /// [`Controller`](crate::runtime::Controller)
+    ///
+    /// Every round, the objects of type `K` are listed across all namespaces, and reconciled one by one.
+    #[must_use]
+    pub fn reconciler<K, Ctx, E, ReconcilerFut>(
+        mut self,
+        reconcile: impl Fn(Arc<K>, Arc<Ctx>) -> ReconcilerFut + Send + Sync + 'static,
+        context: Arc<Ctx>,
+    ) -> Self
+    where
+        K: Resource + Clone + DeserializeOwned + std::fmt::Debug + Send + Sync + 'static,
+        K::DynamicType: Default,
+        Ctx: Send + Sync + 'static,
+        E: Display + Send,
+        ReconcilerFut: Future<Output = Result<Action, E>> + Send + 'static,
+    {
+        let api: Api<K> = Api::all(self.client.clone());
+        let reconcile = Arc::new(reconcile);
+        self.reconcilers.push(Box::new(move || -> RoundFuture {
+            let (api, reconcile, context) = (api.clone(), reconcile.clone(), context.clone());
+            Box::pin(async move {
+                let kind = K::kind(&K::DynamicType::default()).into_owned();
+                let objects = match api.list(&ListParams::default()).await {
+                    Ok(objects) => objects,
+                    Err(err) => {
+                        return vec![ReconcileError {
+                            round: 0,
+                            kind,
+                            namespace: None,
+                            name: None,
+                            message: err.to_string(),
+                        }]
+                    }
+                };
+                let mut errors = vec![];
+                for obj in objects {
+                    let (namespace, name) = (obj.namespace(), obj.name_any());
+                    if let Err(err) = reconcile(Arc::new(obj), context.clone()).await {
+                        errors.push(ReconcileError {
+                            round: 0,
+                            kind: kind.clone(),
+                            namespace,
+                            name: Some(name),
+                            message: err.to_string(),
+                        });
+                    }
+                }
+                errors
+            })
+        }));
+        self
+    }
+
+    /// Run the reconcilers until a round no longer changes the cluster
+    ///
+    /// Changes made to the cluster before the run are not part of the [`Report`].
+    pub async fn run(&self) -> Report {
+        let mut report = Report::default();
+        self.cluster.take_mutations();
+        while report.rounds < self.max_rounds {
+            report.rounds += 1;
+            let round = report.rounds;
+            for reconciler in &self.reconcilers {
+                let errors = reconciler()
+                    .await
+                    .into_iter()
+                    .map(|err| ReconcileError { round, ..err });
+                report.errors.extend(errors);
+            }
+            let mutations = self.cluster.take_mutations();
+            if mutations.is_empty() {
+                report.converged = true;
+                break;
+            }
+            report.mutations.extend(mutations);
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use k8s_openapi::api::core::v1::ConfigMap;
+
+    use super::Simulation;
+    use crate::{
+        api::{Api, ObjectMeta, PostParams, ResourceExt},
+        runtime::controller::Action,
+        Client,
+    };
+
+    fn config_map(name: &str, replicas: &str) -> ConfigMap {
+        ConfigMap {
+            metadata: ObjectMeta {
+                name: Some(name.into()),
+                namespace: Some("default".into()),
+                ..ObjectMeta::default()
+            },
+            data: Some([("replicas".to_string(), replicas.to_string())].into()),
+            ..ConfigMap::default()
+        }
+    }
+
+    /// Creates a `{name}-{i}` copy for every replica a config map asks for
+    async fn reconcile(cm: Arc<ConfigMap>, client: Arc<Client>) -> Result<Action, crate::Error> {
+        let api: Api<ConfigMap> = Api::default_namespaced((*client).clone());
+        let replicas = cm.data.as_ref().and_then(|data| data.get("replicas"));
+        for i in 0..replicas.and_then(|r| r.parse().ok()).unwrap_or(0) {
+            let name = format!("{}-{i}", cm.name_any());
+            if api.get_opt(&name).await?.is_none() {
+                // the copies ask for as many replicas as their index, so the chain ends
+                let copy = config_map(&name, &i.to_string());
+                api.create(&PostParams::default(), &copy).await?;
+            }
+        }
+        Ok(Action::await_change())
+    }
+
+    #[tokio::test]
+    async fn runs_reconcilers_to_a_fixed_point() {
+        let sim = Simulation::new().load([config_map("web", "2")]);
+        let client = Arc::new(sim.client());
+        let report = sim.reconciler(reconcile, client).run().await;
+
+        assert!(report.converged);
+        assert!(report.errors.is_empty());
+        // web-0 and web-1 in the first round, web-1-0 in the second, nothing new in the third
+        assert_eq!(report.rounds, 3);
+        let mut created: Vec<_> = report.mutations.iter().map(|m| m.name.as_str()).collect();
+        created.sort_unstable();
+        assert_eq!(created, ["web-0", "web-1", "web-1-0"]);
+        assert!(report.mutations.iter().all(|m| m.is_create()));
+
+        let sim = Simulation::new().load([config_map("web", "2")]).max_rounds(1);
+        let client = Arc::new(sim.client());
+        let report = sim.reconciler(reconcile, client).run().await;
+        assert!(!report.converged);
+        assert_eq!(report.mutations.len(), 2);
+    }
+}